@@ -0,0 +1,375 @@
+//! **This crate is not meant to be imported directly by users**.
+//! You should import [mina-hasher](https://crates.io/crates/mina-hasher) instead.
+//!
+//! mina-hasher-deriver provides the `#[derive(Hashable)]` macro, refer to the
+//! [mina-hasher](https://o1-labs.github.io/proof-systems/rustdoc/mina_hasher/index.html)
+//! documentation.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Lit, Meta,
+    NestedMeta, Result, Type, WherePredicate,
+};
+
+/// Maximum length of domain strings
+const MAX_DOMAIN_STRING_LEN: usize = 20;
+
+/// The [Hashable] derive macro.
+/// It generates an implementation of \[`mina_hasher::Hashable`\], which appends
+/// the members of a type to the random oracle input in the order of their
+/// declaration, as long as they implement \[`mina_hasher::AppendToROInput`\].
+///
+/// For example:
+///
+/// ```ignore
+/// #[derive(Clone, mina_hasher::Hashable)]
+/// #[hashable(domain_string = "MyType")]
+/// struct MyType {
+///     // ...
+/// }
+/// ```
+///
+/// The domain string can depend on a parameter, in which case the type of
+/// the parameter and a function computing the domain string from it must be
+/// given:
+///
+/// ```text
+/// #[derive(Clone, mina_hasher::Hashable)]
+/// #[hashable(domain_param = "NetworkId", domain_fn = "my_domain_string")]
+/// struct MyType {
+/// ```
+///
+/// where `fn my_domain_string(param: NetworkId) -> Option<String>`.
+/// Without `domain_string` nor `domain_fn`, the domain string is `None`.
+///
+/// You can skip a member, or append only the `N` least significant bits of an
+/// integer member:
+///
+/// ```text
+/// #[derive(Clone, mina_hasher::Hashable)]
+/// struct MyType {
+///     #[hashable(skip)]
+///     cache: NotHashable,
+///     #[hashable(bits = 3)]
+///     tag: u8,
+/// ```
+///
+/// The members of enum variants are appended after a tag holding the index of
+/// the variant, with the smallest number of bits needed, or the number of bits
+/// given by `#[hashable(tag_bits = N)]`. Note that the variants should all have
+/// the same size, since Mina hashes fixed-length inputs.
+#[proc_macro_derive(Hashable, attributes(hashable))]
+pub fn derive_hashable(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    derive(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The `#[hashable(...)]` attributes of a type
+#[derive(Default)]
+struct TypeAttributes {
+    domain_string: Option<String>,
+    domain_param: Option<Type>,
+    domain_fn: Option<syn::Path>,
+    tag_bits: Option<usize>,
+}
+
+/// The `#[hashable(...)]` attributes of a member
+#[derive(Default)]
+struct MemberAttributes {
+    skip: bool,
+    bits: Option<usize>,
+}
+
+/// Returns the items of the `#[hashable(...)]` attributes
+fn hashable_items(attrs: &[Attribute]) -> Result<Vec<NestedMeta>> {
+    let mut items = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("hashable")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => items.extend(list.nested),
+            meta => {
+                return Err(Error::new(
+                    meta.span(),
+                    "expected `#[hashable(...)]` attribute",
+                ))
+            }
+        }
+    }
+    Ok(items)
+}
+
+fn lit_str(lit: &Lit) -> Result<String> {
+    match lit {
+        Lit::Str(s) => Ok(s.value()),
+        _ => Err(Error::new(lit.span(), "expected a string literal")),
+    }
+}
+
+fn lit_usize(lit: &Lit) -> Result<usize> {
+    match lit {
+        Lit::Int(i) => i.base10_parse(),
+        _ => Err(Error::new(lit.span(), "expected an integer literal")),
+    }
+}
+
+fn type_attributes(attrs: &[Attribute]) -> Result<TypeAttributes> {
+    let mut attributes = TypeAttributes::default();
+    for item in hashable_items(attrs)? {
+        match &item {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("domain_string") => {
+                let domain_string = lit_str(&nv.lit)?;
+                if domain_string.len() > MAX_DOMAIN_STRING_LEN {
+                    return Err(Error::new(
+                        nv.lit.span(),
+                        format!("domain strings must have length <= {MAX_DOMAIN_STRING_LEN}"),
+                    ));
+                }
+                attributes.domain_string = Some(domain_string);
+            }
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("domain_param") => {
+                attributes.domain_param = Some(syn::parse_str(&lit_str(&nv.lit)?)?);
+            }
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("domain_fn") => {
+                attributes.domain_fn = Some(syn::parse_str(&lit_str(&nv.lit)?)?);
+            }
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("tag_bits") => {
+                attributes.tag_bits = Some(lit_usize(&nv.lit)?);
+            }
+            _ => {
+                return Err(Error::new(
+                    item.span(),
+                    "expected `domain_string`, `domain_param`, `domain_fn` or `tag_bits`",
+                ))
+            }
+        }
+    }
+
+    if attributes.domain_string.is_some() && attributes.domain_fn.is_some() {
+        return Err(Error::new(
+            proc_macro2::Span::call_site(),
+            "`domain_string` and `domain_fn` cannot be used together",
+        ));
+    }
+    Ok(attributes)
+}
+
+fn member_attributes(attrs: &[Attribute]) -> Result<MemberAttributes> {
+    let mut attributes = MemberAttributes::default();
+    for item in hashable_items(attrs)? {
+        match &item {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                attributes.skip = true;
+            }
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("bits") => {
+                let bits = lit_usize(&nv.lit)?;
+                if bits > 64 {
+                    return Err(Error::new(nv.lit.span(), "at most 64 bits are supported"));
+                }
+                attributes.bits = Some(bits);
+            }
+            _ => return Err(Error::new(item.span(), "expected `skip` or `bits`")),
+        }
+    }
+    Ok(attributes)
+}
+
+/// Generates the statements appending the members `fields`, bound to
+/// `bindings`, to `roi`, and adds the bounds required by their types to
+/// `predicates`
+fn append_members(
+    fields: &Fields,
+    bindings: &[TokenStream2],
+    type_params: &[Ident],
+    predicates: &mut Vec<WherePredicate>,
+) -> Result<Vec<TokenStream2>> {
+    let mut statements = vec![];
+    for (field, binding) in fields.iter().zip(bindings) {
+        let attributes = member_attributes(&field.attrs)?;
+        if attributes.skip {
+            continue;
+        }
+        match attributes.bits {
+            Some(bits) => statements.push(quote! {
+                let roi = roi.append_bits(*#binding as u64, #bits);
+            }),
+            None => {
+                let ty = &field.ty;
+                if mentions_any(ty.to_token_stream(), type_params) {
+                    predicates.push(syn::parse_quote!(#ty: ::mina_hasher::AppendToROInput));
+                }
+                statements.push(quote! {
+                    let roi = ::mina_hasher::AppendToROInput::append_to_roinput(#binding, roi);
+                });
+            }
+        }
+    }
+    Ok(statements)
+}
+
+/// Whether `tokens` mention one of the identifiers `idents`
+fn mentions_any(tokens: TokenStream2, idents: &[Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => idents.contains(&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
+/// The names bound to the members `fields` when destructuring them
+fn member_bindings(fields: &Fields) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let ident = format_ident!("field_{}", i);
+                quote!(#ident)
+            }
+        })
+        .collect()
+}
+
+/// The pattern destructuring the members `fields` into [`member_bindings`]
+fn member_pattern(fields: &Fields, bindings: &[TokenStream2]) -> TokenStream2 {
+    match fields {
+        Fields::Named(_) => quote!({ #( #bindings ),* }),
+        Fields::Unnamed(_) => quote!(( #( #bindings ),* )),
+        Fields::Unit => quote!(),
+    }
+}
+
+fn derive(input: DeriveInput) -> Result<TokenStream2> {
+    let attributes = type_attributes(&input.attrs)?;
+    // Members whose types depend on type parameters get trait bounds
+    let type_params: Vec<Ident> = input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let mut predicates: Vec<WherePredicate> = vec![];
+
+    let append = match &input.data {
+        Data::Struct(data) => {
+            if attributes.tag_bits.is_some() {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "`tag_bits` is only supported on enums",
+                ));
+            }
+            let bindings = member_bindings(&data.fields);
+            let pattern = member_pattern(&data.fields, &bindings);
+            let statements =
+                append_members(&data.fields, &bindings, &type_params, &mut predicates)?;
+            quote! {
+                #[allow(unused_variables)]
+                let Self #pattern = self;
+                #( #statements )*
+            }
+        }
+        Data::Enum(data) => {
+            let variants = data.variants.len();
+            if variants == 0 {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "enums without variants are not supported by `#[derive(Hashable)]`",
+                ));
+            }
+            let min_tag_bits = (usize::BITS - variants.saturating_sub(1).leading_zeros()) as usize;
+            let tag_bits = attributes.tag_bits.unwrap_or(min_tag_bits);
+            if tag_bits < min_tag_bits || tag_bits > 64 {
+                return Err(Error::new(
+                    input.ident.span(),
+                    format!("`tag_bits` must be between {min_tag_bits} and 64"),
+                ));
+            }
+
+            let mut arms = vec![];
+            for (tag, variant) in data.variants.iter().enumerate() {
+                if !hashable_items(&variant.attrs)?.is_empty() {
+                    return Err(Error::new(
+                        variant.span(),
+                        "`#[hashable(...)]` attributes are not supported on variants",
+                    ));
+                }
+                let ident = &variant.ident;
+                let bindings = member_bindings(&variant.fields);
+                let pattern = member_pattern(&variant.fields, &bindings);
+                let statements =
+                    append_members(&variant.fields, &bindings, &type_params, &mut predicates)?;
+                let tag = tag as u64;
+                arms.push(quote! {
+                    #[allow(unused_variables)]
+                    Self::#ident #pattern => {
+                        let roi = roi.append_bits(#tag, #tag_bits);
+                        #( #statements )*
+                        roi
+                    }
+                });
+            }
+            quote! {
+                let roi = match self {
+                    #( #arms )*
+                };
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                input.ident.span(),
+                "unions are not supported by `#[derive(Hashable)]`",
+            ))
+        }
+    };
+
+    let domain_param = match &attributes.domain_param {
+        Some(ty) => quote!(#ty),
+        None => quote!(()),
+    };
+    let domain_string = match (&attributes.domain_string, &attributes.domain_fn) {
+        (Some(domain_string), _) => quote! {
+            fn domain_string(_: Self::D) -> Option<String> {
+                #domain_string.to_string().into()
+            }
+        },
+        (None, Some(domain_fn)) => quote! {
+            fn domain_string(domain_param: Self::D) -> Option<String> {
+                #domain_fn(domain_param)
+            }
+        },
+        (None, None) => quote! {
+            fn domain_string(_: Self::D) -> Option<String> {
+                None
+            }
+        },
+    };
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    if !type_params.is_empty() {
+        // `Clone` is a supertrait of `Hashable`, and the derived `Clone` of
+        // generic types is itself bounded
+        let (_, ty_generics, _) = input.generics.split_for_impl();
+        predicates.push(syn::parse_quote!(#name #ty_generics: ::core::clone::Clone));
+    }
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::mina_hasher::Hashable for #name #ty_generics #where_clause {
+            type D = #domain_param;
+
+            fn to_roinput(&self) -> ::mina_hasher::ROInput {
+                let roi = ::mina_hasher::ROInput::new();
+                #append
+                roi
+            }
+
+            #domain_string
+        }
+    })
+}
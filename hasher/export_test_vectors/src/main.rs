@@ -0,0 +1,67 @@
+mod vectors;
+use inner::*;
+
+/// "Usage: cargo run -p export_hasher_test_vectors -- [hex|b10] <OUTPUT_FILE>",
+fn main() {
+    inner::main();
+}
+
+mod inner {
+    use super::vectors;
+    use std::{
+        env,
+        fs::File,
+        io::{self, Write},
+        str::FromStr,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum Mode {
+        Hex,
+        B10,
+    }
+
+    impl FromStr for Mode {
+        type Err = ();
+
+        fn from_str(input: &str) -> Result<Self, Self::Err> {
+            match input.to_lowercase().as_str() {
+                "b10" => Ok(Mode::B10),
+                "hex" => Ok(Mode::Hex),
+                _ => Err(()),
+            }
+        }
+    }
+
+    pub(crate) fn main() {
+        let args: Vec<String> = env::args().collect();
+        match args.len() {
+            3 => {
+                // parse command-line args
+                let mode: Mode = args
+                    .get(1)
+                    .expect("missing mode")
+                    .parse()
+                    .expect("invalid mode");
+                let output_file = args.get(2).expect("missing file");
+
+                // generate vectors
+                let vectors = vectors::generate(mode);
+
+                // save to output file
+                let writer: Box<dyn Write> = match output_file.as_str() {
+                    "-" => Box::new(io::stdout()),
+                    _ => Box::new(File::create(output_file).expect("could not create file")),
+                };
+                serde_json::to_writer_pretty(writer, &vectors).expect("could not write to file");
+            }
+            _ => {
+                println!(
+                    "usage: cargo run -p export_hasher_test_vectors -- [{:?}|{:?}] <OUTPUT_FILE>",
+                    Mode::Hex,
+                    Mode::B10,
+                );
+            }
+        }
+    }
+}
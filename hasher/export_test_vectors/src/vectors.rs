@@ -0,0 +1,225 @@
+use super::Mode;
+use ark_ff::{PrimeField, UniformRand as _};
+use mina_curves::pasta::{Fp, Fq};
+use mina_hasher::{create_kimchi, create_legacy, Hashable, Hasher, ROInput};
+use o1_utils::FieldHelpers;
+use rand::Rng;
+use serde::Serialize;
+
+//
+// structs
+//
+
+#[derive(Debug, Serialize)]
+pub struct TestVectors {
+    name: String,
+    test_vectors: Vec<TestVector>,
+}
+
+/// The random oracle input obtained by applying `input` in order, in each of
+/// its serialized forms, and its legacy and kimchi Poseidon hashes (without
+/// domain string)
+#[derive(Debug, Serialize)]
+pub struct TestVector {
+    name: String,
+    input: Vec<Append>,
+    serialized: String,
+    bytes: String,
+    fields: Vec<String>,
+    legacy: String,
+    kimchi: String,
+}
+
+/// A call to one of the `ROInput::append_*` methods, where `value` is a
+/// boolean as `true` or `false`, an integer in base 10, bytes in hex, or a
+/// field element in the requested [Mode]
+#[derive(Debug, Serialize)]
+pub struct Append {
+    append: &'static str,
+    value: String,
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Bool(bool),
+    U32(u32),
+    U64(u64),
+    Bytes(Vec<u8>),
+    Field(Fp),
+    Scalar(Fq),
+}
+
+#[derive(Clone)]
+struct Input(ROInput);
+
+impl Hashable for Input {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        self.0.clone()
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        None
+    }
+}
+
+//
+// logic
+//
+
+fn encode_field<F: PrimeField>(f: F, mode: Mode) -> String {
+    match mode {
+        Mode::Hex => f.to_hex(),
+        Mode::B10 => f.to_biguint().to_string(),
+    }
+}
+
+impl Op {
+    fn append_to(&self, roi: ROInput) -> ROInput {
+        match self {
+            Op::Bool(b) => roi.append_bool(*b),
+            Op::U32(x) => roi.append_u32(*x),
+            Op::U64(x) => roi.append_u64(*x),
+            Op::Bytes(bytes) => roi.append_bytes(bytes),
+            Op::Field(f) => roi.append_field(*f),
+            Op::Scalar(s) => roi.append_scalar(*s),
+        }
+    }
+
+    fn encode(&self, mode: Mode) -> Append {
+        let (append, value) = match self {
+            Op::Bool(b) => ("bool", b.to_string()),
+            Op::U32(x) => ("u32", x.to_string()),
+            Op::U64(x) => ("u64", x.to_string()),
+            Op::Bytes(bytes) => ("bytes", hex::encode(bytes)),
+            Op::Field(f) => ("field", encode_field(*f, mode)),
+            Op::Scalar(s) => ("scalar", encode_field(*s, mode)),
+        };
+        Append { append, value }
+    }
+}
+
+/// generates a random call to one of the `ROInput::append_*` methods
+fn rand_op(rng: &mut impl Rng) -> Op {
+    match rng.gen_range(0..6) {
+        0 => Op::Bool(rng.gen()),
+        1 => Op::U32(rng.gen()),
+        2 => Op::U64(rng.gen()),
+        3 => {
+            let length = rng.gen_range(0..40);
+            Op::Bytes((0..length).map(|_| rng.gen()).collect())
+        }
+        4 => Op::Field(Fp::rand(rng)),
+        _ => Op::Scalar(Fq::rand(rng)),
+    }
+}
+
+fn test_vector(name: String, ops: Vec<Op>, mode: Mode) -> TestVector {
+    let roi = ops.iter().fold(ROInput::new(), |roi, op| op.append_to(roi));
+    let input = Input(roi.clone());
+
+    TestVector {
+        name,
+        input: ops.iter().map(|op| op.encode(mode)).collect(),
+        serialized: hex::encode(roi.serialize()),
+        bytes: hex::encode(roi.to_bytes()),
+        fields: roi
+            .to_fields()
+            .into_iter()
+            .map(|f| encode_field(f, mode))
+            .collect(),
+        legacy: encode_field(create_legacy::<Input>(()).hash(&input), mode),
+        kimchi: encode_field(create_kimchi::<Input>(()).hash(&input), mode),
+    }
+}
+
+/// creates a set of test vectors
+pub fn generate(mode: Mode) -> TestVectors {
+    let rng = &mut o1_utils::tests::make_test_rng(Some([0u8; 32]));
+
+    // edge cases of each append method, then of the packing of bits
+    let mut cases = vec![
+        ("empty", vec![]),
+        ("bool_false", vec![Op::Bool(false)]),
+        ("bool_true", vec![Op::Bool(true)]),
+        (
+            "bools",
+            [true, false, true, true, false]
+                .into_iter()
+                .map(Op::Bool)
+                .collect(),
+        ),
+        ("u32_zero", vec![Op::U32(0)]),
+        ("u32_max", vec![Op::U32(u32::MAX)]),
+        ("u64_zero", vec![Op::U64(0)]),
+        ("u64_max", vec![Op::U64(u64::MAX)]),
+        ("bytes_empty", vec![Op::Bytes(vec![])]),
+        ("bytes", vec![Op::Bytes(vec![0x01, 0xac, 0xff])]),
+        ("field_zero", vec![Op::Field(Fp::from(0u64))]),
+        ("field_max", vec![Op::Field(-Fp::from(1u64))]),
+        ("scalar_zero", vec![Op::Scalar(Fq::from(0u64))]),
+        ("scalar_max", vec![Op::Scalar(-Fq::from(1u64))]),
+        (
+            // bits are packed into field elements of 254 bits
+            "bits_254",
+            [vec![Op::Bytes(vec![0xff; 31])], vec![Op::Bool(true); 6]].concat(),
+        ),
+        (
+            "bits_255",
+            [vec![Op::Bytes(vec![0xff; 31])], vec![Op::Bool(true); 7]].concat(),
+        ),
+        ("two_scalars", vec![Op::Scalar(-Fq::from(1u64)); 2]),
+        (
+            // fields are packed before bits, whatever the order of the calls
+            "bool_field",
+            vec![Op::Bool(true), Op::Field(-Fp::from(1u64))],
+        ),
+        (
+            "field_bool_scalar_u32",
+            vec![
+                Op::Field(Fp::from(1729u64)),
+                Op::Bool(true),
+                Op::Scalar(Fq::from(1729u64)),
+                Op::U32(1729),
+            ],
+        ),
+    ]
+    .into_iter()
+    .map(|(name, ops)| (name.to_string(), ops))
+    .collect::<Vec<_>>();
+
+    // random combinations
+    for i in 0..16 {
+        let length = rng.gen_range(1..10);
+        let ops = (0..length).map(|_| rand_op(rng)).collect();
+        cases.push((format!("random_{i}"), ops));
+    }
+
+    let test_vectors = cases
+        .into_iter()
+        .map(|(name, ops)| test_vector(name, ops, mode))
+        .collect();
+
+    TestVectors {
+        name: "roinput".into(),
+        test_vectors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roinput_test_vectors_regression() {
+        // The vectors published with mina-hasher are generated by this tool
+        let expected: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/test_vectors/roinput.json"))
+                .expect("couldn't deserialize test vector file");
+
+        let vectors =
+            serde_json::to_value(generate(Mode::Hex)).expect("couldn't serialize test vectors");
+        assert_eq!(vectors, expected);
+    }
+}
@@ -4,8 +4,9 @@
 pub mod poseidon;
 pub mod roinput;
 pub use mina_curves::pasta::Fp;
+pub use mina_hasher_deriver::Hashable;
 pub use poseidon::{PoseidonHasherKimchi, PoseidonHasherLegacy};
-pub use roinput::ROInput;
+pub use roinput::{AppendToROInput, ROInput, ROInputError};
 
 use ark_ff::PrimeField;
 use o1_utils::FieldHelpers;
@@ -8,9 +8,34 @@ use bitvec::{prelude::*, view::AsBits};
 use ark_ff::PrimeField;
 use mina_curves::pasta::{Fp, Fq};
 use o1_utils::FieldHelpers;
+use thiserror::Error;
 
 use super::Hashable;
 
+/// Size of the header of a serialized random oracle input: the number of
+/// field elements and the number of bits, both as little-endian `u32`
+const SER_HEADER_SIZE: usize = 8;
+
+/// Random oracle input deserialization errors
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ROInputError {
+    /// Input is too short to contain the header
+    #[error("input is too short to contain the header")]
+    Header,
+
+    /// Length of the payload does not match the header
+    #[error("payload length does not match the header")]
+    Length,
+
+    /// Padding bits of the last byte are not zero
+    #[error("non-zero padding bits")]
+    Padding,
+
+    /// Invalid base field element
+    #[error("invalid base field element")]
+    Field,
+}
+
 /// Random oracle input structure
 ///
 /// The random oracle input encapsulates the serialization format and methods using during hashing.
@@ -118,6 +143,14 @@ impl ROInput {
         self.append_bytes(&x.to_le_bytes())
     }
 
+    /// Append the `bits` least significant bits of `x`, least significant bit first
+    pub fn append_bits(mut self, x: u64, bits: usize) -> Self {
+        assert!(bits <= u64::BITS as usize);
+        self.bits
+            .extend_from_bitslice(&x.to_le_bytes().as_bits::<Lsb0>()[..bits]);
+        self
+    }
+
     /// Serialize random oracle input to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bits: BitVec<u8> = self.fields.iter().fold(BitVec::new(), |mut acc, fe| {
@@ -173,6 +206,132 @@ impl ROInput {
 
         fields
     }
+
+    /// Serialize random oracle input so that it can be deserialized with
+    /// [`ROInput::deserialize`]
+    ///
+    /// The output is the number of field elements and the number of bits, both
+    /// as little-endian `u32`, followed by [`ROInput::to_bytes`].
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SER_HEADER_SIZE);
+        bytes.extend((self.fields.len() as u32).to_le_bytes());
+        bytes.extend((self.bits.len() as u32).to_le_bytes());
+        bytes.extend(self.to_bytes());
+        bytes
+    }
+
+    /// Deserialize random oracle input from the output of [`ROInput::serialize`]
+    pub fn deserialize(input: &[u8]) -> Result<Self, ROInputError> {
+        if input.len() < SER_HEADER_SIZE {
+            return Err(ROInputError::Header);
+        }
+        let (header, payload) = input.split_at(SER_HEADER_SIZE);
+        let fields_len = u32::from_le_bytes(header[..4].try_into().expect("4 bytes")) as usize;
+        let bits_len = u32::from_le_bytes(header[4..].try_into().expect("4 bytes")) as usize;
+
+        let field_bits = Fp::MODULUS_BIT_SIZE as usize;
+        let fields_bits_len = fields_len
+            .checked_mul(field_bits)
+            .ok_or(ROInputError::Length)?;
+        let total_bits_len = fields_bits_len
+            .checked_add(bits_len)
+            .ok_or(ROInputError::Length)?;
+        if payload.len() != (total_bits_len + 7) / 8 {
+            return Err(ROInputError::Length);
+        }
+
+        let payload = payload.as_bits::<Lsb0>();
+        if payload[total_bits_len..].any() {
+            return Err(ROInputError::Padding);
+        }
+
+        // Copy into fresh bit vectors so that the chunks are byte-aligned
+        // (see the workaround in `to_fields`)
+        let fields = payload[..fields_bits_len]
+            .chunks(field_bits)
+            .map(|chunk| {
+                let mut bv = BitVec::<u8>::new();
+                bv.extend_from_bitslice(chunk);
+                bv.resize(field_bits + 1, false);
+                Fp::from_bytes(&bv.into_vec()).map_err(|_| ROInputError::Field)
+            })
+            .collect::<Result<Vec<Fp>, _>>()?;
+
+        let mut bits = BitVec::<u8>::new();
+        bits.extend_from_bitslice(&payload[fields_bits_len..total_bits_len]);
+
+        Ok(ROInput { fields, bits })
+    }
+}
+
+/// Interface for values that can be appended to a random oracle input
+///
+/// This is the encoding used by `#[derive(Hashable)]` for each member of a
+/// type (see [`Hashable`](macro@crate::Hashable)).  Every [`Hashable`] type is
+/// appended as its [`Hashable::to_roinput`].
+pub trait AppendToROInput {
+    /// Append `self` to the random oracle input `roi`
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput;
+}
+
+impl<H: Hashable> AppendToROInput for H {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        roi.append_hashable(self)
+    }
+}
+
+impl AppendToROInput for Fp {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        roi.append_field(*self)
+    }
+}
+
+impl AppendToROInput for Fq {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        roi.append_scalar(*self)
+    }
+}
+
+impl AppendToROInput for bool {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        roi.append_bool(*self)
+    }
+}
+
+impl AppendToROInput for u8 {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        roi.append_bytes(&[*self])
+    }
+}
+
+impl AppendToROInput for u32 {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        roi.append_u32(*self)
+    }
+}
+
+impl AppendToROInput for u64 {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        roi.append_u64(*self)
+    }
+}
+
+impl<T: AppendToROInput, const N: usize> AppendToROInput for [T; N] {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        self.iter().fold(roi, |roi, x| x.append_to_roinput(roi))
+    }
+}
+
+/// An option is appended as a flag, followed by its value, or by the default
+/// value if it is `None`, so that its size does not depend on the flag
+impl<T: AppendToROInput + Default> AppendToROInput for Option<T> {
+    fn append_to_roinput(&self, roi: ROInput) -> ROInput {
+        let roi = roi.append_bool(self.is_some());
+        match self {
+            Some(x) => x.append_to_roinput(roi),
+            None => T::default().append_to_roinput(roi),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -908,4 +1067,79 @@ mod tests {
         };
         assert_ne!(b1.to_roinput(), b2.to_roinput());
     }
+
+    #[test]
+    fn serialize_deserialize() {
+        let scalar =
+            Fq::from_hex("18b7ef420128e69623c0c0dcfa28d47a029d462720deb769d7b5dd6f17444216")
+                .expect("failed to create scalar");
+        let field =
+            Fp::from_hex("41203c6bbac14b357301e1f386d80f52123fd00f02197491b690bddfa742ca22")
+                .expect("failed to create field");
+
+        for roi in [
+            ROInput::new(),
+            ROInput::new().append_bool(true),
+            ROInput::new().append_field(field),
+            ROInput::new()
+                .append_field(field)
+                .append_field(-field)
+                .append_bool(true)
+                .append_u32(1729)
+                .append_scalar(scalar)
+                .append_bytes(&[0x01, 0xff])
+                .append_u64(u64::MAX),
+        ] {
+            let bytes = roi.serialize();
+            assert_eq!(&bytes[8..], roi.to_bytes());
+            assert_eq!(ROInput::deserialize(&bytes), Ok(roi));
+        }
+
+        let bytes = ROInput::new()
+            .append_field(field)
+            .append_bool(true)
+            .serialize();
+        assert_eq!(bytes[..8], [0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00]);
+        // The bit follows the 255 bits of the field element
+        assert_eq!(bytes.len(), 8 + 32);
+        assert_eq!(bytes[39], 0x80 | (field.to_bytes()[31] & 0x7f));
+    }
+
+    #[test]
+    fn deserialize_errors() {
+        let field =
+            Fp::from_hex("41203c6bbac14b357301e1f386d80f52123fd00f02197491b690bddfa742ca22")
+                .expect("failed to create field");
+        let bytes = ROInput::new()
+            .append_field(field)
+            .append_bool(false)
+            .append_bool(false)
+            .append_bool(true)
+            .serialize();
+
+        assert_eq!(ROInput::deserialize(&[]), Err(ROInputError::Header));
+        assert_eq!(ROInput::deserialize(&bytes[..7]), Err(ROInputError::Header));
+        assert_eq!(
+            ROInput::deserialize(&bytes[..bytes.len() - 1]),
+            Err(ROInputError::Length)
+        );
+        assert_eq!(
+            ROInput::deserialize(&[bytes.clone(), vec![0]].concat()),
+            Err(ROInputError::Length)
+        );
+
+        // Dropping the last bit leaves it as a non-zero padding bit
+        let mut truncated = bytes.clone();
+        truncated[4] = 2;
+        assert_eq!(ROInput::deserialize(&truncated), Err(ROInputError::Padding));
+
+        // Field element greater than the modulus
+        let mut invalid = vec![0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        invalid.extend([0xff; 31]);
+        invalid.push(0x7f);
+        assert_eq!(ROInput::deserialize(&invalid), Err(ROInputError::Field));
+
+        // Counts overflowing the payload size
+        assert_eq!(ROInput::deserialize(&[0xff; 8]), Err(ROInputError::Length));
+    }
 }
@@ -0,0 +1,255 @@
+use mina_curves::pasta::Fq;
+use mina_hasher::{create_kimchi, create_legacy, AppendToROInput, Fp, Hashable, Hasher, ROInput};
+
+//
+// Structures with derived and hand-written implementations
+//
+
+#[derive(Clone, Hashable)]
+#[hashable(domain_string = "Point")]
+struct Point {
+    x: Fp,
+    y: Fp,
+}
+
+#[derive(Clone)]
+struct ManualPoint {
+    x: Fp,
+    y: Fp,
+}
+
+impl Hashable for ManualPoint {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new().append_field(self.x).append_field(self.y)
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        "Point".to_string().into()
+    }
+}
+
+#[derive(Clone, Hashable)]
+#[hashable(domain_param = "u32", domain_fn = "account_domain")]
+struct Account {
+    key: Point,
+    balance: u64,
+    nonce: u32,
+    #[hashable(skip)]
+    #[allow(dead_code)]
+    name: String,
+    #[hashable(bits = 3)]
+    permissions: u8,
+    is_new: bool,
+    state: [Fp; 2],
+    memo: [u8; 4],
+    delegate: Option<Fp>,
+    secret: Fq,
+}
+
+fn account_domain(height: u32) -> Option<String> {
+    format!("Account{height:03}").into()
+}
+
+#[derive(Clone)]
+struct ManualAccount {
+    key: ManualPoint,
+    balance: u64,
+    nonce: u32,
+    permissions: u8,
+    is_new: bool,
+    state: [Fp; 2],
+    memo: [u8; 4],
+    delegate: Option<Fp>,
+    secret: Fq,
+}
+
+impl Hashable for ManualAccount {
+    type D = u32;
+
+    fn to_roinput(&self) -> ROInput {
+        let mut roi = ROInput::new()
+            .append_hashable(&self.key)
+            .append_u64(self.balance)
+            .append_u32(self.nonce);
+        for i in 0..3 {
+            roi = roi.append_bool((self.permissions >> i) & 1 == 1);
+        }
+        roi.append_bool(self.is_new)
+            .append_field(self.state[0])
+            .append_field(self.state[1])
+            .append_bytes(&self.memo)
+            .append_bool(self.delegate.is_some())
+            .append_field(self.delegate.unwrap_or_default())
+            .append_scalar(self.secret)
+    }
+
+    fn domain_string(height: u32) -> Option<String> {
+        account_domain(height)
+    }
+}
+
+// Mirrors the body of signed commands: the tag of payments is [F, F, F] and
+// the tag of delegations is [F, F, T], i.e. 0 and 4 with the least
+// significant bit first
+#[derive(Clone, Hashable)]
+#[hashable(tag_bits = 3)]
+#[allow(dead_code)]
+enum Body {
+    Payment { receiver: Fp, amount: u64 },
+    Unused,
+    Unused2,
+    Unused3,
+    Delegation(Fp, #[hashable(skip)] u64),
+}
+
+#[derive(Clone, Hashable)]
+struct Wrapper<T>(T, u32);
+
+#[derive(Clone, Hashable)]
+struct Empty;
+
+//
+// Tests
+//
+
+fn account() -> (Account, ManualAccount) {
+    let key = (Fp::from(1u64), Fp::from(2u64));
+    let account = Account {
+        key: Point { x: key.0, y: key.1 },
+        balance: 1_000_000_000,
+        nonce: 42,
+        name: "alice".to_string(),
+        permissions: 0b101,
+        is_new: true,
+        state: [Fp::from(3u64), Fp::from(4u64)],
+        memo: *b"memo",
+        delegate: None,
+        secret: Fq::from(5u64),
+    };
+    let manual = ManualAccount {
+        key: ManualPoint { x: key.0, y: key.1 },
+        balance: account.balance,
+        nonce: account.nonce,
+        permissions: account.permissions,
+        is_new: account.is_new,
+        state: account.state,
+        memo: account.memo,
+        delegate: account.delegate,
+        secret: account.secret,
+    };
+    (account, manual)
+}
+
+#[test]
+fn derive_struct() {
+    let point = Point {
+        x: Fp::from(1u64),
+        y: Fp::from(2u64),
+    };
+    let manual = ManualPoint {
+        x: point.x,
+        y: point.y,
+    };
+    assert_eq!(point.to_roinput(), manual.to_roinput());
+    assert_eq!(Point::domain_string(()), ManualPoint::domain_string(()));
+    assert_eq!(
+        create_legacy::<Point>(()).hash(&point),
+        create_legacy::<ManualPoint>(()).hash(&manual)
+    );
+}
+
+#[test]
+fn derive_nested() {
+    let (mut account, mut manual) = account();
+    assert_eq!(account.to_roinput(), manual.to_roinput());
+
+    // The skipped member is not hashed
+    let roi = account.to_roinput();
+    account.name = "bob".to_string();
+    assert_eq!(account.to_roinput(), roi);
+
+    // Options have the same size whether they are set or not
+    account.delegate = Some(Fp::from(6u64));
+    manual.delegate = account.delegate;
+    assert_eq!(account.to_roinput(), manual.to_roinput());
+    assert_ne!(account.to_roinput(), roi);
+    assert_eq!(
+        account.to_roinput().to_fields().len(),
+        roi.to_fields().len()
+    );
+
+    // Only the given number of bits are hashed
+    account.permissions |= 0b1000;
+    assert_eq!(account.to_roinput(), manual.to_roinput());
+
+    assert_eq!(Account::domain_string(7), Some("Account007".to_string()));
+    assert_eq!(
+        create_kimchi::<Account>(7).hash(&account),
+        create_kimchi::<ManualAccount>(7).hash(&manual)
+    );
+}
+
+#[test]
+fn derive_enum() {
+    let receiver = Fp::from(9u64);
+    let payment = Body::Payment {
+        receiver,
+        amount: 100,
+    };
+    assert_eq!(
+        payment.to_roinput(),
+        ROInput::new()
+            .append_bool(false)
+            .append_bool(false)
+            .append_bool(false)
+            .append_field(receiver)
+            .append_u64(100)
+    );
+
+    let delegation = Body::Delegation(receiver, 100);
+    assert_eq!(
+        delegation.to_roinput(),
+        ROInput::new()
+            .append_bool(false)
+            .append_bool(false)
+            .append_bool(true)
+            .append_field(receiver)
+    );
+    assert_eq!(Body::domain_string(()), None);
+    assert_eq!(Body::Unused.to_roinput(), ROInput::new().append_bits(1, 3));
+}
+
+#[test]
+fn derive_generic_and_unit() {
+    let point = Point {
+        x: Fp::from(1u64),
+        y: Fp::from(2u64),
+    };
+    assert_eq!(
+        Wrapper(point.clone(), 3).to_roinput(),
+        point.to_roinput().append_u32(3)
+    );
+    assert_eq!(
+        Wrapper(true, 3).to_roinput(),
+        ROInput::new().append_bool(true).append_u32(3)
+    );
+    assert_eq!(Empty.to_roinput(), ROInput::new());
+}
+
+#[test]
+fn append_to_roinput() {
+    assert_eq!(
+        [1u32, 2u32].append_to_roinput(ROInput::new()),
+        ROInput::new().append_u32(1).append_u32(2)
+    );
+    assert_eq!(
+        None::<u64>.append_to_roinput(ROInput::new()),
+        ROInput::new().append_bool(false).append_u64(0)
+    );
+    assert_eq!(
+        ROInput::new().append_bits(0x1234, 16),
+        ROInput::new().append_bytes(&[0x34, 0x12])
+    );
+}
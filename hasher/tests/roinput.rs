@@ -0,0 +1,131 @@
+use mina_curves::pasta::Fq;
+use mina_hasher::{create_kimchi, create_legacy, Fp, Hashable, Hasher, ROInput, ROInputError};
+use o1_utils::FieldHelpers;
+use serde::Deserialize;
+use std::{fs::File, path::PathBuf};
+
+//
+// Helpers for test vectors
+//
+
+#[derive(Debug, Deserialize)]
+struct TestVectors {
+    test_vectors: Vec<TestVector>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TestVector {
+    name: String,
+    input: Vec<Append>,
+    serialized: String,
+    bytes: String,
+    fields: Vec<String>,
+    legacy: String,
+    kimchi: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Append {
+    append: String,
+    value: String,
+}
+
+#[derive(Clone)]
+struct Input(ROInput);
+
+impl Hashable for Input {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        self.0.clone()
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        None
+    }
+}
+
+impl TestVector {
+    fn roinput(&self) -> ROInput {
+        self.input.iter().fold(ROInput::new(), |roi, append| {
+            let value = &append.value;
+            match append.append.as_str() {
+                "bool" => roi.append_bool(value.parse().expect("invalid bool")),
+                "u32" => roi.append_u32(value.parse().expect("invalid u32")),
+                "u64" => roi.append_u64(value.parse().expect("invalid u64")),
+                "bytes" => roi.append_bytes(&hex::decode(value).expect("invalid bytes")),
+                "field" => roi.append_field(Fp::from_hex(value).expect("invalid field element")),
+                "scalar" => roi.append_scalar(Fq::from_hex(value).expect("invalid scalar")),
+                other => panic!("unknown append method {other}"),
+            }
+        })
+    }
+}
+
+fn test_vectors() -> Vec<TestVector> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests/test_vectors/roinput.json");
+
+    let file = File::open(&path).expect("couldn't open test vector file");
+    let test_vectors: TestVectors =
+        serde_json::from_reader(file).expect("couldn't deserialize test vector file");
+    test_vectors.test_vectors
+}
+
+//
+// Tests
+//
+
+#[test]
+fn roinput_test_vectors() {
+    for test_vector in test_vectors() {
+        let roi = test_vector.roinput();
+        let name = &test_vector.name;
+
+        assert_eq!(hex::encode(roi.to_bytes()), test_vector.bytes, "{name}");
+        assert_eq!(
+            roi.to_fields()
+                .into_iter()
+                .map(|f| f.to_hex())
+                .collect::<Vec<_>>(),
+            test_vector.fields,
+            "{name}"
+        );
+        assert_eq!(
+            hex::encode(roi.serialize()),
+            test_vector.serialized,
+            "{name}"
+        );
+
+        let input = Input(roi.clone());
+        assert_eq!(
+            create_legacy::<Input>(()).hash(&input).to_hex(),
+            test_vector.legacy,
+            "{name}"
+        );
+        assert_eq!(
+            create_kimchi::<Input>(()).hash(&input).to_hex(),
+            test_vector.kimchi,
+            "{name}"
+        );
+
+        // The serialized form is enough to reconstruct the input
+        let serialized = hex::decode(&test_vector.serialized).expect("invalid hex");
+        assert_eq!(ROInput::deserialize(&serialized), Ok(roi), "{name}");
+    }
+}
+
+#[test]
+fn roinput_deserialize_errors() {
+    for test_vector in test_vectors() {
+        let serialized = hex::decode(&test_vector.serialized).expect("invalid hex");
+        assert_eq!(
+            ROInput::deserialize(&serialized[..serialized.len() - 1]),
+            if serialized.len() > 8 {
+                Err(ROInputError::Length)
+            } else {
+                Err(ROInputError::Header)
+            }
+        );
+    }
+}
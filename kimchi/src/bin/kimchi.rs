@@ -0,0 +1,292 @@
+//! Command line interface to prove and verify circuits given by a
+//! [`CircuitDescription`] and a [`WitnessDescription`].
+//!
+//! Circuits are defined over the scalar field of Vesta, and proofs use
+//! Poseidon for the Fiat-Shamir transformation and the inner product argument
+//! over Vesta as polynomial commitment scheme. The SRS is generated from the
+//! domain size of the circuit. Descriptions and public inputs whose file name
+//! ends with `.cbor` are read and written in CBOR, other files in JSON.
+//!
+//! The verifier only needs the verifier index written by the `compile`
+//! command, the proof, and the public input it checks the proof against.
+//!
+//! The CLI is built with the `cli` feature.
+
+use clap::{Parser, Subcommand};
+
+use kimchi::{
+    circuits::{
+        constraints::ConstraintSystem,
+        description::{CircuitDescription, FieldString, Format, WitnessDescription},
+        diagnostics::check_witness,
+        gate::GateType,
+    },
+    curve::KimchiCurve,
+    groupmap::GroupMap,
+    linearization::expr_linearization,
+    mina_curves::pasta::{Fp, Vesta, VestaParameters},
+    mina_poseidon::{
+        constants::PlonkSpongeConstantsKimchi,
+        sponge::{DefaultFqSponge, DefaultFrSponge},
+    },
+    poly_commitment::{commitment::CommitmentCurve, ipa::OpeningProof, ipa::SRS, SRS as _},
+    profile::ProverProfile,
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::verify,
+    verifier_index::VerifierIndex,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::{collections::BTreeMap, error::Error, fs, path::Path, sync::Arc};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "kimchi",
+    version = "0.1",
+    about = "kimchi - prove and verify circuits from their description"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Builds the prover index of a circuit, and writes its verifier index
+    Compile {
+        #[arg(long, short = 'c', value_name = "FILE", help = "circuit description")]
+        circuit: String,
+
+        #[arg(long, short = 'o', value_name = "FILE", help = "output verifier index")]
+        output: Option<String>,
+    },
+    /// Proves that a witness satisfies a circuit
+    Prove {
+        #[arg(long, short = 'c', value_name = "FILE", help = "circuit description")]
+        circuit: String,
+
+        #[arg(long, short = 'w', value_name = "FILE", help = "witness description")]
+        witness: String,
+
+        #[arg(long, short = 'o', value_name = "FILE", help = "output proof")]
+        output: String,
+
+        #[arg(long, value_name = "FILE", help = "output public input of the proof")]
+        public_output: Option<String>,
+
+        #[arg(long, value_name = "FILE", help = "output JSON profile of the prover")]
+        profile: Option<String>,
+    },
+    /// Checks a witness against a circuit, and reports the failing constraints
+    Check {
+        #[arg(long, short = 'c', value_name = "FILE", help = "circuit description")]
+        circuit: String,
+
+        #[arg(long, short = 'w', value_name = "FILE", help = "witness description")]
+        witness: String,
+    },
+    /// Verifies a proof created by the `prove` command
+    Verify {
+        #[arg(
+            long,
+            short = 'i',
+            value_name = "FILE",
+            help = "verifier index written by the compile command"
+        )]
+        index: String,
+
+        #[arg(long, short = 'p', value_name = "FILE", help = "proof")]
+        proof: String,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "public input, an array of field elements"
+        )]
+        public_input: String,
+    },
+    /// Prints a summary of a circuit
+    Inspect {
+        #[arg(long, short = 'c', value_name = "FILE", help = "circuit description")]
+        circuit: String,
+    },
+}
+
+type Proof = ProverProof<Vesta, OpeningProof<Vesta>>;
+
+/// A public input, encoded as an array of field elements in the format of the
+/// descriptions.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct PublicInput(#[serde_as(as = "Vec<FieldString>")] Vec<Fp>);
+
+fn read_circuit(path: &str) -> Result<CircuitDescription<Fp>> {
+    let bytes = fs::read(path)?;
+    Ok(CircuitDescription::decode(
+        Format::from_path(Path::new(path)),
+        &bytes,
+    )?)
+}
+
+fn read_witness(path: &str) -> Result<WitnessDescription<Fp>> {
+    let bytes = fs::read(path)?;
+    Ok(WitnessDescription::decode(
+        Format::from_path(Path::new(path)),
+        &bytes,
+    )?)
+}
+
+fn prover_index(
+    circuit: &CircuitDescription<Fp>,
+) -> Result<ProverIndex<Vesta, OpeningProof<Vesta>>> {
+    let cs = circuit.constraint_system()?;
+    let srs_size = circuit.max_poly_size.unwrap_or(cs.domain.d1.size as usize);
+    let srs = SRS::<Vesta>::create(srs_size);
+    srs.get_lagrange_basis(cs.domain.d1);
+    let &endo_q = Vesta::other_curve_endo();
+    Ok(ProverIndex::create(cs, endo_q, Arc::new(srs)))
+}
+
+/// Reads a verifier index written by the `compile` command, and restores the
+/// fields that are not serialized.
+fn read_verifier_index(path: &str) -> Result<VerifierIndex<Vesta, OpeningProof<Vesta>>> {
+    let mut index: VerifierIndex<Vesta, OpeningProof<Vesta>> =
+        rmp_serde::from_slice(&fs::read(path)?)?;
+    let srs = SRS::<Vesta>::create(index.max_poly_size);
+    srs.get_lagrange_basis(index.domain);
+    index.srs = Arc::new(srs);
+    index.endo = *Vesta::other_curve_endo();
+    let (linearization, powers_of_alpha) = expr_linearization(Some(&index.feature_flags()), true);
+    index.linearization = linearization;
+    index.powers_of_alpha = powers_of_alpha;
+    Ok(index)
+}
+
+fn compile(circuit: String, output: Option<String>) -> Result<()> {
+    let index = prover_index(&read_circuit(&circuit)?)?;
+    let verifier_index = index.verifier_index();
+    println!("domain size: {}", index.cs.domain.d1.size);
+    println!(
+        "verifier index digest: {}",
+        verifier_index.digest::<BaseSponge>()
+    );
+    if let Some(output) = output {
+        fs::write(output, rmp_serde::to_vec(&verifier_index)?)?;
+    }
+    Ok(())
+}
+
+fn prove(
+    circuit: String,
+    witness: String,
+    output: String,
+    public_output: Option<String>,
+    profile_output: Option<String>,
+) -> Result<()> {
+    let circuit = read_circuit(&circuit)?;
+    let witness = read_witness(&witness)?;
+    let index = prover_index(&circuit)?;
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let mut profile = ProverProfile::default();
+    let proof = ProverProof::create_recursive_with_profile::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness.witness()?,
+        &witness.runtime_tables(),
+        &index,
+        vec![],
+        None,
+        &mut rand::rngs::OsRng,
+        profile_output.as_ref().map(|_| &mut profile),
+    )?;
+    if let Some(profile_output) = profile_output {
+        fs::write(profile_output, profile.to_json()?)?;
+    }
+    fs::write(output, rmp_serde::to_vec(&proof)?)?;
+    if let Some(public_output) = public_output {
+        let public_input = PublicInput(witness.public_input(circuit.public)?);
+        let format = Format::from_path(Path::new(&public_output));
+        fs::write(public_output, format.encode(&public_input)?)?;
+    }
+    Ok(())
+}
+
+fn check(circuit: String, witness: String) -> Result<()> {
+    let circuit = read_circuit(&circuit)?;
+    let witness = read_witness(&witness)?;
+    let cs = circuit.constraint_system()?;
+    let public_input = witness.public_input(circuit.public)?;
+    let report = check_witness::<Vesta>(&cs, &witness.witness()?, &public_input);
+    println!("{report}");
+    if !report.is_ok() {
+        return Err(format!("{} failures", report.failures.len()).into());
+    }
+    Ok(())
+}
+
+fn verify_proof(index: String, proof: String, public_input: String) -> Result<()> {
+    let index = read_verifier_index(&index)?;
+    let proof: Proof = rmp_serde::from_slice(&fs::read(proof)?)?;
+    let format = Format::from_path(Path::new(&public_input));
+    let PublicInput(public_input) = format.decode(&fs::read(public_input)?)?;
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &index,
+        &proof,
+        &public_input,
+    )?;
+    println!("the proof is valid");
+    Ok(())
+}
+
+fn inspect(circuit: String) -> Result<()> {
+    let circuit = read_circuit(&circuit)?;
+    let cs: ConstraintSystem<Fp> = circuit.constraint_system()?;
+    println!("version: {}", circuit.version);
+    println!("gates: {}", circuit.gates.len());
+    let mut gate_types: BTreeMap<String, usize> = BTreeMap::new();
+    for gate in &circuit.gates {
+        if gate.typ != GateType::Zero {
+            *gate_types.entry(format!("{:?}", gate.typ)).or_default() += 1;
+        }
+    }
+    for (typ, count) in gate_types {
+        println!("  {typ}: {count}");
+    }
+    println!("public inputs: {}", circuit.public);
+    println!("previous challenges: {}", circuit.prev_challenges);
+    println!("domain size: {}", cs.domain.d1.size);
+    println!("zero-knowledge rows: {}", cs.zk_rows);
+    println!("lookup tables: {}", circuit.lookup_tables.len());
+    println!("runtime tables: {}", circuit.runtime_tables.len());
+    println!("feature flags: {:?}", cs.feature_flags);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Compile { circuit, output } => compile(circuit, output),
+        Command::Prove {
+            circuit,
+            witness,
+            output,
+            public_output,
+            profile,
+        } => prove(circuit, witness, output, public_output, profile),
+        Command::Check { circuit, witness } => check(circuit, witness),
+        Command::Verify {
+            index,
+            proof,
+            public_input,
+        } => verify_proof(index, proof, public_input),
+        Command::Inspect { circuit } => inspect(circuit),
+    }
+}
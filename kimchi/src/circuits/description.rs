@@ -0,0 +1,492 @@
+//! A language-neutral description of circuits and witnesses.
+//!
+//! A [`CircuitDescription`] contains everything needed to build a
+//! [`ConstraintSystem`]: the gates and their wiring, the number of public
+//! inputs, the lookup tables and the runtime table configurations. A
+//! [`WitnessDescription`] contains the witness columns and the runtime tables
+//! of an execution of the circuit.
+//!
+//! Both are versioned, and can be encoded in JSON, or in CBOR with the `cbor`
+//! feature. Field elements
+//! are encoded as strings, in decimal or in hexadecimal with a `0x` prefix, so
+//! that descriptions can be produced without an implementation of the field.
+//! Gate types are encoded with the names of [`GateType`], and wires as
+//! `{"row": .., "col": ..}` objects.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "public": 1,
+//!   "gates": [
+//!     {
+//!       "typ": "Generic",
+//!       "wires": [{"row": 0, "col": 0}, ...],
+//!       "coeffs": ["1", "0", "0", "0", "0"]
+//!     }
+//!   ]
+//! }
+//! ```
+
+use crate::{
+    circuits::{
+        constraints::{ConstraintSystem, FeatureFlags},
+        gate::{CircuitGate, GateType},
+        lookup::{
+            runtime_tables::{RuntimeTable, RuntimeTableCfg},
+            tables::LookupTable,
+        },
+        wires::{GateWires, COLUMNS, PERMUTS},
+    },
+    error::{DescriptionError, SetupError},
+};
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{serde_as, DeserializeAs, SerializeAs};
+use std::{array, path::Path};
+
+/// The version of the description formats produced by this module.
+pub const DESCRIPTION_VERSION: u32 = 1;
+
+/// Encodes field elements as strings, see the [module documentation](self).
+pub struct FieldString;
+
+impl<F: PrimeField> SerializeAs<F> for FieldString {
+    fn serialize_as<S>(x: &F, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let n: BigUint = x.into_bigint().into();
+        serializer.serialize_str(&n.to_string())
+    }
+}
+
+impl<'de, F: PrimeField> DeserializeAs<'de, F> for FieldString {
+    fn deserialize_as<D>(deserializer: D) -> Result<F, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        parse_field(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parses a field element, in decimal or in hexadecimal with a `0x` prefix.
+///
+/// # Errors
+///
+/// Will give error if `s` is not a number, or is not smaller than the modulus.
+pub fn parse_field<F: PrimeField>(s: &str) -> Result<F, String> {
+    let n = match s.strip_prefix("0x") {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(s.as_bytes(), 10),
+    }
+    .ok_or_else(|| format!("invalid field element {s:?}"))?;
+    let modulus: BigUint = F::MODULUS.into();
+    if n >= modulus {
+        return Err(format!("field element {s} is not smaller than the modulus"));
+    }
+    Ok(F::from_le_bytes_mod_order(&n.to_bytes_le()))
+}
+
+/// The encoding of a description.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl Format {
+    /// The format of a file, from its extension: `.cbor` files are in CBOR,
+    /// any other file in JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            #[cfg(feature = "cbor")]
+            Some(ext) if ext == "cbor" => Format::Cbor,
+            _ => Format::Json,
+        }
+    }
+
+    /// Encodes `value` in this format.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the value cannot be encoded.
+    pub fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>, DescriptionError> {
+        match self {
+            Format::Json => serde_json::to_vec_pretty(value)
+                .map_err(|e| DescriptionError::Encoding(e.to_string())),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut bytes = vec![];
+                ciborium::ser::into_writer(value, &mut bytes)
+                    .map_err(|e| DescriptionError::Encoding(e.to_string()))?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decodes a value in this format.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not a valid encoding.
+    pub fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, DescriptionError> {
+        match self {
+            Format::Json => {
+                serde_json::from_slice(bytes).map_err(|e| DescriptionError::Decoding(e.to_string()))
+            }
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::de::from_reader(bytes)
+                .map_err(|e| DescriptionError::Decoding(e.to_string())),
+        }
+    }
+}
+
+/// A gate, see [`CircuitGate`].
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: PrimeField")]
+pub struct GateDescription<F: PrimeField> {
+    pub typ: GateType,
+    pub wires: GateWires,
+    #[serde_as(as = "Vec<FieldString>")]
+    #[serde(default)]
+    pub coeffs: Vec<F>,
+}
+
+impl<F: PrimeField> From<&CircuitGate<F>> for GateDescription<F> {
+    fn from(gate: &CircuitGate<F>) -> Self {
+        GateDescription {
+            typ: gate.typ,
+            wires: gate.wires,
+            coeffs: gate.coeffs.clone(),
+        }
+    }
+}
+
+impl<F: PrimeField> From<&GateDescription<F>> for CircuitGate<F> {
+    fn from(gate: &GateDescription<F>) -> Self {
+        CircuitGate::new(gate.typ, gate.wires, gate.coeffs.clone())
+    }
+}
+
+/// A fixed lookup table, see [`LookupTable`].
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: PrimeField")]
+pub struct LookupTableDescription<F: PrimeField> {
+    pub id: i32,
+    #[serde_as(as = "Vec<Vec<FieldString>>")]
+    pub data: Vec<Vec<F>>,
+}
+
+/// The configuration of a runtime table, see [`RuntimeTableCfg`].
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: PrimeField")]
+pub struct RuntimeTableCfgDescription<F: PrimeField> {
+    pub id: i32,
+    #[serde_as(as = "Vec<FieldString>")]
+    pub first_column: Vec<F>,
+}
+
+/// The content of a runtime table, see [`RuntimeTable`].
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: PrimeField")]
+pub struct RuntimeTableDescription<F: PrimeField> {
+    pub id: i32,
+    #[serde_as(as = "Vec<FieldString>")]
+    pub data: Vec<F>,
+}
+
+/// The description of a circuit, i.e. of the input of
+/// [`ConstraintSystem::create`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: PrimeField")]
+pub struct CircuitDescription<F: PrimeField> {
+    /// The version of the format, see [`DESCRIPTION_VERSION`]
+    pub version: u32,
+    /// The number of public inputs, in the first rows of the first column
+    pub public: usize,
+    /// The number of previous challenges verified by the circuit
+    #[serde(default)]
+    pub prev_challenges: usize,
+    pub gates: Vec<GateDescription<F>>,
+    #[serde(default)]
+    pub lookup_tables: Vec<LookupTableDescription<F>>,
+    #[serde(default)]
+    pub runtime_tables: Vec<RuntimeTableCfgDescription<F>>,
+    /// Disables the checks of the gates, for tests only
+    #[serde(default)]
+    pub disable_gates_checks: bool,
+    /// The maximal size of the polynomials, the domain size by default
+    #[serde(default)]
+    pub max_poly_size: Option<usize>,
+    /// The features that the circuit is expected to use. They are computed
+    /// from the gates and the lookup tables, and checked against this field
+    /// if it is present.
+    #[serde(default)]
+    pub feature_flags: Option<FeatureFlags>,
+}
+
+impl<F: PrimeField> CircuitDescription<F> {
+    /// Describes a circuit made of `gates`, with `public` public inputs.
+    pub fn new(gates: &[CircuitGate<F>], public: usize) -> Self {
+        CircuitDescription {
+            version: DESCRIPTION_VERSION,
+            public,
+            prev_challenges: 0,
+            gates: gates.iter().map(Into::into).collect(),
+            lookup_tables: vec![],
+            runtime_tables: vec![],
+            disable_gates_checks: false,
+            max_poly_size: None,
+            feature_flags: None,
+        }
+    }
+
+    /// Adds fixed lookup tables to the circuit.
+    pub fn lookup(mut self, lookup_tables: &[LookupTable<F>]) -> Self {
+        self.lookup_tables = lookup_tables
+            .iter()
+            .map(|table| LookupTableDescription {
+                id: table.id,
+                data: table.data.clone(),
+            })
+            .collect();
+        self
+    }
+
+    /// Adds runtime tables to the circuit.
+    pub fn runtime(mut self, runtime_tables: &[RuntimeTableCfg<F>]) -> Self {
+        self.runtime_tables = runtime_tables
+            .iter()
+            .map(|cfg| RuntimeTableCfgDescription {
+                id: cfg.id,
+                first_column: cfg.first_column.clone(),
+            })
+            .collect();
+        self
+    }
+
+    /// The gates of the circuit.
+    pub fn circuit_gates(&self) -> Vec<CircuitGate<F>> {
+        self.gates.iter().map(Into::into).collect()
+    }
+
+    /// Builds the constraint system of the circuit.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the version is not supported, if the wiring is not
+    /// a permutation of the cells of the circuit, if the constraint system
+    /// cannot be built, or if it does not have the declared feature flags.
+    pub fn constraint_system(&self) -> Result<ConstraintSystem<F>, DescriptionError> {
+        check_version(self.version)?;
+        // checked by the builder with an assertion
+        if self.gates.len() < 2 {
+            return Err(DescriptionError::Setup(SetupError::ConstraintSystem(
+                "the circuit must have at least 2 gates".to_string(),
+            )));
+        }
+        self.check_wiring()?;
+        let lookup_tables = self
+            .lookup_tables
+            .iter()
+            .map(|table| LookupTable {
+                id: table.id,
+                data: table.data.clone(),
+            })
+            .collect();
+        let runtime_tables = (!self.runtime_tables.is_empty()).then(|| {
+            self.runtime_tables
+                .iter()
+                .map(|cfg| RuntimeTableCfg {
+                    id: cfg.id,
+                    first_column: cfg.first_column.clone(),
+                })
+                .collect()
+        });
+        let cs = ConstraintSystem::create(self.circuit_gates())
+            .public(self.public)
+            .prev_challenges(self.prev_challenges)
+            .lookup(lookup_tables)
+            .runtime(runtime_tables)
+            .disable_gates_checks(self.disable_gates_checks)
+            .max_poly_size(self.max_poly_size)
+            .build()
+            .map_err(DescriptionError::Setup)?;
+        match self.feature_flags {
+            Some(flags) if flags != cs.feature_flags => Err(DescriptionError::FeatureFlags(
+                format!("{flags:?}"),
+                format!("{:?}", cs.feature_flags),
+            )),
+            _ => Ok(cs),
+        }
+    }
+
+    /// Checks that the wires are a permutation of the permuted cells, i.e.
+    /// of the first [`PERMUTS`] columns of the rows of the circuit. The
+    /// builder does not check it, and computes the permutation polynomials of
+    /// any wiring, or panics if a wire is out of range.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a wire is not a permuted cell, or if a cell is not
+    /// wired to by exactly one cell.
+    pub fn check_wiring(&self) -> Result<(), DescriptionError> {
+        let mut wired = vec![[0usize; PERMUTS]; self.gates.len()];
+        for (row, gate) in self.gates.iter().enumerate() {
+            for (col, wire) in gate.wires.iter().enumerate() {
+                if wire.row >= self.gates.len() || wire.col >= PERMUTS {
+                    return Err(DescriptionError::WireOutOfRange(
+                        row, col, wire.row, wire.col,
+                    ));
+                }
+                wired[wire.row][wire.col] += 1;
+            }
+        }
+        for (row, counts) in wired.iter().enumerate() {
+            if let Some(col) = counts.iter().position(|&count| count != 1) {
+                return Err(DescriptionError::WiringNotPermutation(
+                    row,
+                    col,
+                    counts[col],
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the description.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the description cannot be encoded.
+    pub fn encode(&self, format: Format) -> Result<Vec<u8>, DescriptionError> {
+        format.encode(self)
+    }
+
+    /// Decodes a description, and checks its version.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not a valid description.
+    pub fn decode(format: Format, bytes: &[u8]) -> Result<Self, DescriptionError> {
+        let description: Self = format.decode(bytes)?;
+        check_version(description.version)?;
+        Ok(description)
+    }
+}
+
+/// The description of a witness of a circuit.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "F: PrimeField")]
+pub struct WitnessDescription<F: PrimeField> {
+    /// The version of the format, see [`DESCRIPTION_VERSION`]
+    pub version: u32,
+    /// The witness, column by column. The public inputs are the first
+    /// values of the first column.
+    #[serde_as(as = "Vec<Vec<FieldString>>")]
+    pub columns: Vec<Vec<F>>,
+    #[serde(default)]
+    pub runtime_tables: Vec<RuntimeTableDescription<F>>,
+}
+
+impl<F: PrimeField> WitnessDescription<F> {
+    /// Describes a witness and its runtime tables.
+    pub fn new(witness: &[Vec<F>; COLUMNS], runtime_tables: &[RuntimeTable<F>]) -> Self {
+        WitnessDescription {
+            version: DESCRIPTION_VERSION,
+            columns: witness.to_vec(),
+            runtime_tables: runtime_tables
+                .iter()
+                .map(|table| RuntimeTableDescription {
+                    id: table.id,
+                    data: table.data.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The witness columns.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if there are not [`COLUMNS`] columns of the same size.
+    pub fn witness(&self) -> Result<[Vec<F>; COLUMNS], DescriptionError> {
+        if self.columns.len() != COLUMNS {
+            return Err(DescriptionError::WitnessColumns(
+                self.columns.len(),
+                COLUMNS,
+            ));
+        }
+        if self
+            .columns
+            .iter()
+            .any(|col| col.len() != self.columns[0].len())
+        {
+            return Err(DescriptionError::WitnessInconsistent);
+        }
+        Ok(array::from_fn(|i| self.columns[i].clone()))
+    }
+
+    /// The runtime tables.
+    pub fn runtime_tables(&self) -> Vec<RuntimeTable<F>> {
+        self.runtime_tables
+            .iter()
+            .map(|table| RuntimeTable {
+                id: table.id,
+                data: table.data.clone(),
+            })
+            .collect()
+    }
+
+    /// The first `public` values of the first column.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the witness is not well formed.
+    pub fn public_input(&self, public: usize) -> Result<Vec<F>, DescriptionError> {
+        let witness = self.witness()?;
+        if witness[0].len() < public {
+            return Err(DescriptionError::WitnessInconsistent);
+        }
+        Ok(witness[0][..public].to_vec())
+    }
+
+    /// Encodes the description.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the description cannot be encoded.
+    pub fn encode(&self, format: Format) -> Result<Vec<u8>, DescriptionError> {
+        format.encode(self)
+    }
+
+    /// Decodes a description, and checks its version.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` is not a valid description.
+    pub fn decode(format: Format, bytes: &[u8]) -> Result<Self, DescriptionError> {
+        let description: Self = format.decode(bytes)?;
+        check_version(description.version)?;
+        Ok(description)
+    }
+}
+
+fn check_version(version: u32) -> Result<(), DescriptionError> {
+    if version == DESCRIPTION_VERSION {
+        Ok(())
+    } else {
+        Err(DescriptionError::UnsupportedVersion(
+            version,
+            DESCRIPTION_VERSION,
+        ))
+    }
+}
@@ -0,0 +1,357 @@
+//! Diagnostics for witnesses that do not satisfy a circuit.
+//!
+//! [`ProverIndex::verify`](crate::prover_index::ProverIndex) stops at the
+//! first failure and only reports its location. [`check_witness`] instead
+//! evaluates the constraints of every gate, row by row, and collects all the
+//! failures in a [`WitnessReport`]. Each failure records the gate type, the
+//! row, the index of the failing constraint in the gate's
+//! [`constraint_checks`](GateType::constraint_checks), and the value of the
+//! witness cells it involves. Copy constraints are checked per permutation
+//! cycle, so that a mismatch is reported once with all the cells of the cycle.
+//!
+//! The report implements [`Display`](fmt::Display), which makes it easy to use
+//! from tests:
+//!
+//! ```ignore
+//! let report = check_witness::<Vesta>(&cs, &witness, &public);
+//! assert!(report.is_ok(), "{report}");
+//! ```
+//!
+//! Lookups are not checked.
+
+use crate::{
+    circuits::{
+        argument::{ArgumentEnv, ArgumentWitness},
+        berkeley_columns::{BerkeleyChallenges, Column, E},
+        constraints::ConstraintSystem,
+        expr::{Cache, Constants, PolishToken, Variable},
+        gate::{CurrOrNext, GateType},
+        wires::{Wire, COLUMNS, PERMUTS},
+    },
+    curve::KimchiCurve,
+};
+use ark_ff::{One, PrimeField, Zero};
+use std::{collections::HashMap, fmt};
+
+/// A cell of the witness, with its value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell<F> {
+    /// The position of the cell
+    pub wire: Wire,
+    /// The value of the cell in the witness
+    pub value: F,
+}
+
+impl<F: fmt::Display> fmt::Display for Cell<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(row {}, col {}) = {}",
+            self.wire.row, self.wire.col, self.value
+        )
+    }
+}
+
+/// A reason for a witness not to satisfy a circuit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Failure<F> {
+    /// The witness columns do not have the same length, or are longer than
+    /// the circuit
+    WitnessLength {
+        col: usize,
+        len: usize,
+        expected: usize,
+    },
+    /// A constraint of a gate does not evaluate to zero
+    Constraint {
+        typ: GateType,
+        row: usize,
+        /// The index of the constraint in the constraints of the gate
+        index: usize,
+        /// The value of the constraint, including the public input for the
+        /// first constraint of public input rows
+        value: F,
+        /// The witness cells the constraint depends on
+        cells: Vec<Cell<F>>,
+    },
+    /// The cells of a permutation cycle do not all have the same value
+    CopyConstraint {
+        /// The cells of the cycle, in the order of the permutation
+        cycle: Vec<Cell<F>>,
+    },
+    /// A wire points to a column that is not permuted, or to a row that is
+    /// not in the circuit
+    WireColumn { typ: GateType, src: Wire, dst: Wire },
+    /// A public input row does not only toggle its left wire
+    PublicInput { typ: GateType, row: usize },
+}
+
+impl<F: fmt::Display> fmt::Display for Failure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::WitnessLength { col, len, expected } => write!(
+                f,
+                "witness column {col} has {len} rows, expected {expected}"
+            ),
+            Failure::Constraint {
+                typ,
+                row,
+                index,
+                value,
+                cells,
+            } => {
+                writeln!(
+                    f,
+                    "row {row} ({typ:?}): constraint {index} evaluates to {value}"
+                )?;
+                write!(f, "    cells:")?;
+                for cell in cells {
+                    write!(f, "\n      {cell}")?;
+                }
+                Ok(())
+            }
+            Failure::CopyConstraint { cycle } => {
+                write!(f, "copy constraint cycle with different values:")?;
+                for cell in cycle {
+                    write!(f, "\n      {cell}")?;
+                }
+                Ok(())
+            }
+            Failure::WireColumn { typ, src, dst } => write!(
+                f,
+                "row {} ({typ:?}): the wire of column {} points to (row {}, col {}), outside of the permutation",
+                src.row, src.col, dst.row, dst.col
+            ),
+            Failure::PublicInput { typ, row } => write!(
+                f,
+                "row {row} ({typ:?}): public input gates must only toggle their left wire"
+            ),
+        }
+    }
+}
+
+/// The result of [`check_witness`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessReport<F> {
+    /// The failures, by row for the gate constraints, followed by the copy
+    /// constraints
+    pub failures: Vec<Failure<F>>,
+}
+
+impl<F> WitnessReport<F> {
+    /// Returns true if the witness satisfies the circuit
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Returns the failures of the constraints of the gate at `row`
+    pub fn failures_at(&self, row: usize) -> impl Iterator<Item = &Failure<F>> {
+        self.failures.iter().filter(move |failure| match failure {
+            Failure::Constraint { row: r, .. } | Failure::PublicInput { row: r, .. } => *r == row,
+            Failure::WireColumn { src, .. } => src.row == row,
+            Failure::CopyConstraint { cycle } => cycle.iter().any(|cell| cell.wire.row == row),
+            Failure::WitnessLength { .. } => false,
+        })
+    }
+}
+
+impl<F: fmt::Display> fmt::Display for WitnessReport<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "the witness satisfies the circuit");
+        }
+        write!(
+            f,
+            "the witness does not satisfy the circuit ({} failures):",
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n  {failure}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks `witness` against every constraint of `cs`, and reports all the
+/// failures. The witness columns must all have the same length, at most the
+/// number of gates of `cs`; the missing rows are zero.
+pub fn check_witness<G: KimchiCurve>(
+    cs: &ConstraintSystem<G::ScalarField>,
+    witness: &[Vec<G::ScalarField>; COLUMNS],
+    public: &[G::ScalarField],
+) -> WitnessReport<G::ScalarField> {
+    let mut failures = vec![];
+
+    let rows = cs.gates.len();
+    let len = witness[0].len().min(rows);
+    for (col, column) in witness.iter().enumerate() {
+        if column.len() != len {
+            failures.push(Failure::WitnessLength {
+                col,
+                len: column.len(),
+                expected: len,
+            });
+        }
+    }
+    if !failures.is_empty() {
+        return WitnessReport { failures };
+    }
+
+    // pad the witness to the padded circuit, like the prover
+    let witness: [Vec<G::ScalarField>; COLUMNS] = std::array::from_fn(|col| {
+        let mut column = witness[col].clone();
+        column.resize(rows, G::ScalarField::zero());
+        column
+    });
+
+    // the cells involved in each constraint, computed once per gate type
+    let mut variables: HashMap<GateType, Vec<Vec<(CurrOrNext, usize)>>> = HashMap::new();
+
+    for (row, gate) in cs.gates.iter().enumerate() {
+        if row < cs.public && gate.coeffs.first() != Some(&G::ScalarField::one()) {
+            failures.push(Failure::PublicInput { typ: gate.typ, row });
+        }
+
+        let constants = Constants {
+            endo_coefficient: cs.endo,
+            mds: &G::sponge_params().mds,
+            zk_rows: cs.zk_rows,
+        };
+        // the challenges do not appear in the gate constraints
+        let challenges = BerkeleyChallenges {
+            alpha: G::ScalarField::one(),
+            beta: G::ScalarField::one(),
+            gamma: G::ScalarField::one(),
+            joint_combiner: G::ScalarField::one(),
+        };
+        // missing coefficients are zero
+        let mut coeffs = gate.coeffs.clone();
+        coeffs.resize(COLUMNS.max(coeffs.len()), G::ScalarField::zero());
+        let next_row = |col: usize| witness[col].get(row + 1).copied().unwrap_or_default();
+        let argument_witness = ArgumentWitness {
+            curr: std::array::from_fn(|col| witness[col][row]),
+            next: std::array::from_fn(next_row),
+        };
+        let env = ArgumentEnv::<G::ScalarField, G::ScalarField>::create(
+            argument_witness,
+            coeffs,
+            constants,
+            challenges,
+        );
+
+        let mut cache = Cache::default();
+        let mut results = gate.typ.constraint_checks(&env, &mut cache);
+        if gate.typ == GateType::Generic && row < cs.public {
+            results[0] -= public.get(row).copied().unwrap_or_default();
+        }
+
+        for (index, value) in results.into_iter().enumerate() {
+            if value.is_zero() {
+                continue;
+            }
+            let cells = variables
+                .entry(gate.typ)
+                .or_insert_with(|| constraint_variables::<G::ScalarField>(gate.typ))[index]
+                .iter()
+                .map(|&(curr_or_next, col)| {
+                    let row = match curr_or_next {
+                        CurrOrNext::Curr => row,
+                        CurrOrNext::Next => row + 1,
+                    };
+                    Cell {
+                        wire: Wire { row, col },
+                        value: witness[col].get(row).copied().unwrap_or_default(),
+                    }
+                })
+                .collect();
+            failures.push(Failure::Constraint {
+                typ: gate.typ,
+                row,
+                index,
+                value,
+                cells,
+            });
+        }
+    }
+
+    failures.extend(check_permutation(cs, &witness));
+
+    WitnessReport { failures }
+}
+
+/// Returns the witness cells each constraint of `typ` depends on, sorted.
+fn constraint_variables<F: PrimeField>(typ: GateType) -> Vec<Vec<(CurrOrNext, usize)>> {
+    let mut cache = Cache::default();
+    typ.constraint_checks::<F, E<F>>(&ArgumentEnv::default(), &mut cache)
+        .iter()
+        .map(|constraint| {
+            let mut cells: Vec<_> = constraint
+                .to_polish()
+                .into_iter()
+                .filter_map(|token| match token {
+                    PolishToken::Cell(Variable {
+                        col: Column::Witness(col),
+                        row,
+                    }) => Some((row, col)),
+                    _ => None,
+                })
+                .collect();
+            cells.sort();
+            cells.dedup();
+            cells
+        })
+        .collect()
+}
+
+/// Walks every cycle of the permutation, and reports the cycles whose cells
+/// do not all have the same value, and the wires leaving the permutation.
+fn check_permutation<F: PrimeField>(
+    cs: &ConstraintSystem<F>,
+    witness: &[Vec<F>; COLUMNS],
+) -> Vec<Failure<F>> {
+    let mut failures = vec![];
+    let rows = cs.gates.len();
+    let mut visited = vec![[false; PERMUTS]; rows];
+
+    for row in 0..rows {
+        for col in 0..PERMUTS {
+            if visited[row][col] {
+                continue;
+            }
+
+            let start = Wire { row, col };
+            let mut cycle = vec![];
+            let mut src = start;
+            loop {
+                visited[src.row][src.col] = true;
+                cycle.push(Cell {
+                    wire: src,
+                    value: witness[src.col][src.row],
+                });
+
+                let gate = &cs.gates[src.row];
+                let dst = gate.wires[src.col];
+                if dst.col >= PERMUTS || dst.row >= rows {
+                    failures.push(Failure::WireColumn {
+                        typ: gate.typ,
+                        src,
+                        dst,
+                    });
+                    break;
+                }
+                // a well-formed permutation always comes back to the start,
+                // stop on malformed ones instead of looping forever
+                if dst == start || visited[dst.row][dst.col] {
+                    break;
+                }
+                src = dst;
+            }
+
+            if cycle.iter().any(|cell| cell.value != cycle[0].value) {
+                failures.push(Failure::CopyConstraint { cycle });
+            }
+        }
+    }
+
+    failures
+}
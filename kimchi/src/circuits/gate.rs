@@ -3,11 +3,11 @@
 use crate::{
     circuits::{
         argument::{Argument, ArgumentEnv},
-        berkeley_columns::BerkeleyChallenges,
+        berkeley_columns::{BerkeleyChallengeTerm, BerkeleyChallenges},
         constraints::ConstraintSystem,
         polynomials::{
             complete_add, endomul_scalar, endosclmul, foreign_field_add, foreign_field_mul,
-            poseidon, range_check, rot, turshi, varbasemul, xor,
+            generic, poseidon, range_check, rot, turshi, varbasemul, xor,
         },
         wires::*,
     },
@@ -21,7 +21,10 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use thiserror::Error;
 
-use super::{argument::ArgumentWitness, expr};
+use super::{
+    argument::ArgumentWitness,
+    expr::{self, constraints::ExprOps},
+};
 
 /// A row accessible from a given row, corresponds to the fact that we open all polynomials
 /// at `zeta` **and** `omega * zeta`.
@@ -110,6 +113,45 @@ pub enum GateType {
     Rot64,
 }
 
+impl GateType {
+    /// Returns the constraints of this gate type, evaluated over `env`.
+    /// Gates that are not checked row by row (zero and lookup gates) have no
+    /// constraints.
+    pub fn constraint_checks<F: PrimeField, T: ExprOps<F, BerkeleyChallengeTerm>>(
+        self,
+        env: &ArgumentEnv<F, T>,
+        cache: &mut expr::Cache,
+    ) -> Vec<T> {
+        match self {
+            GateType::Zero | GateType::Lookup => vec![],
+            GateType::Generic => generic::Generic::constraint_checks(env, cache),
+            GateType::Poseidon => poseidon::Poseidon::constraint_checks(env, cache),
+            GateType::CompleteAdd => complete_add::CompleteAdd::constraint_checks(env, cache),
+            GateType::VarBaseMul => varbasemul::VarbaseMul::constraint_checks(env, cache),
+            GateType::EndoMul => endosclmul::EndosclMul::constraint_checks(env, cache),
+            GateType::EndoMulScalar => endomul_scalar::EndomulScalar::constraint_checks(env, cache),
+            GateType::CairoClaim => turshi::Claim::constraint_checks(env, cache),
+            GateType::CairoInstruction => turshi::Instruction::constraint_checks(env, cache),
+            GateType::CairoFlags => turshi::Flags::constraint_checks(env, cache),
+            GateType::CairoTransition => turshi::Transition::constraint_checks(env, cache),
+            GateType::RangeCheck0 => {
+                range_check::circuitgates::RangeCheck0::constraint_checks(env, cache)
+            }
+            GateType::RangeCheck1 => {
+                range_check::circuitgates::RangeCheck1::constraint_checks(env, cache)
+            }
+            GateType::ForeignFieldAdd => {
+                foreign_field_add::circuitgates::ForeignFieldAdd::constraint_checks(env, cache)
+            }
+            GateType::ForeignFieldMul => {
+                foreign_field_mul::circuitgates::ForeignFieldMul::constraint_checks(env, cache)
+            }
+            GateType::Xor16 => xor::Xor16::constraint_checks(env, cache),
+            GateType::Rot64 => rot::Rot64::constraint_checks(env, cache),
+        }
+    }
+}
+
 /// Gate error
 #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CircuitGateError {
@@ -270,47 +312,11 @@ impl<F: PrimeField> CircuitGate<F> {
 
         // Perform witness verification on each constraint for this gate
         let results = match self.typ {
-            GateType::Zero => {
-                vec![]
-            }
             GateType::Generic => {
                 // TODO: implement the verification for the generic gate
                 vec![]
             }
-            GateType::Poseidon => poseidon::Poseidon::constraint_checks(&env, &mut cache),
-            GateType::CompleteAdd => complete_add::CompleteAdd::constraint_checks(&env, &mut cache),
-            GateType::VarBaseMul => varbasemul::VarbaseMul::constraint_checks(&env, &mut cache),
-            GateType::EndoMul => endosclmul::EndosclMul::constraint_checks(&env, &mut cache),
-            GateType::EndoMulScalar => {
-                endomul_scalar::EndomulScalar::constraint_checks(&env, &mut cache)
-            }
-            GateType::Lookup => {
-                // TODO: implement the verification for the lookup gate
-                // See https://github.com/MinaProtocol/mina/issues/14011
-                vec![]
-            }
-            GateType::CairoClaim => turshi::Claim::constraint_checks(&env, &mut cache),
-            GateType::CairoInstruction => turshi::Instruction::constraint_checks(&env, &mut cache),
-            GateType::CairoFlags => turshi::Flags::constraint_checks(&env, &mut cache),
-            GateType::CairoTransition => turshi::Transition::constraint_checks(&env, &mut cache),
-            GateType::RangeCheck0 => {
-                range_check::circuitgates::RangeCheck0::constraint_checks(&env, &mut cache)
-            }
-            GateType::RangeCheck1 => {
-                range_check::circuitgates::RangeCheck1::constraint_checks(&env, &mut cache)
-            }
-            GateType::ForeignFieldAdd => {
-                foreign_field_add::circuitgates::ForeignFieldAdd::constraint_checks(
-                    &env, &mut cache,
-                )
-            }
-            GateType::ForeignFieldMul => {
-                foreign_field_mul::circuitgates::ForeignFieldMul::constraint_checks(
-                    &env, &mut cache,
-                )
-            }
-            GateType::Xor16 => xor::Xor16::constraint_checks(&env, &mut cache),
-            GateType::Rot64 => rot::Rot64::constraint_checks(&env, &mut cache),
+            typ => typ.constraint_checks(&env, &mut cache),
         };
 
         // Check for failed constraints
@@ -34,6 +34,21 @@ pub enum LookupError {
     TableIDZeroMustHaveZeroEntry,
     #[error("Cannot create a combined table since ids for sub-tables are colliding. The collision type is: {collision_type}")]
     LookupTableIdCollision { collision_type: String },
+    #[error("The lookup table with id {0} is empty")]
+    EmptyTable(i32),
+    #[error("Row {row} of the lookup table with id {id} has {width} entries, expected {expected}")]
+    InconsistentTableWidth {
+        id: i32,
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    #[error("The lookup table with id {id} has {width} columns, but the lookups of the circuit combine at most {max_width} values")]
+    TableTooWide {
+        id: i32,
+        width: usize,
+        max_width: usize,
+    },
 }
 
 /// Lookup selectors
@@ -82,6 +82,51 @@ where
     vec![rt_check]
 }
 
+pub mod testing {
+    use super::{RuntimeTable, RuntimeTableCfg};
+    use crate::circuits::{
+        gate::{CircuitGate, GateType},
+        wires::{Wire, COLUMNS},
+    };
+    use ark_ff::PrimeField;
+    use std::array;
+
+    /// Create a circuit of `num_lookups` lookup gates, whose lookups are in a
+    /// single runtime table. The configuration of the table is returned with
+    /// the gates.
+    pub fn create_circuit<F: PrimeField>(
+        num_lookups: usize,
+    ) -> (Vec<CircuitGate<F>>, RuntimeTableCfg<F>) {
+        let gates = (0..num_lookups)
+            .map(|i| CircuitGate::new(GateType::Lookup, Wire::for_row(i), vec![]))
+            .collect();
+        let cfg = RuntimeTableCfg {
+            id: 1,
+            first_column: [8u32, 9, 8, 7, 1].into_iter().map(Into::into).collect(),
+        };
+        (gates, cfg)
+    }
+
+    /// Create the witness of a circuit created by [create_circuit], and the
+    /// content of its runtime table.
+    pub fn create_witness<F: PrimeField>(
+        num_lookups: usize,
+        cfg: &RuntimeTableCfg<F>,
+    ) -> ([Vec<F>; COLUMNS], RuntimeTable<F>) {
+        let data: Vec<F> = [0u32, 2, 3, 4, 5].into_iter().map(Into::into).collect();
+        let mut witness: [Vec<F>; COLUMNS] = array::from_fn(|_| vec![F::zero(); num_lookups]);
+        for row in 0..num_lookups {
+            witness[0][row] = F::one();
+            for lookup in 0..3 {
+                let i = (row + lookup) % data.len();
+                witness[1 + 2 * lookup][row] = cfg.first_column[i];
+                witness[2 + 2 * lookup][row] = data[i];
+            }
+        }
+        (witness, RuntimeTable { id: cfg.id, data })
+    }
+}
+
 #[cfg(feature = "ocaml_types")]
 pub mod caml {
     use super::{RuntimeTable, RuntimeTableCfg, RuntimeTableSpec};
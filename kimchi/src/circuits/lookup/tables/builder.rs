@@ -0,0 +1,274 @@
+//! Builders for user-defined fixed lookup tables.
+//!
+//! A [`LookupTableBuilder`] collects the rows of a table, from a closure or
+//! from an iterator. [`LookupTables`] gives each table an ID that does not
+//! collide with the tables used by gates, with the other tables or with the
+//! runtime tables reserved with [`LookupTables::reserve`], and checks that the
+//! tables can be queried by the lookups of a circuit.
+//!
+//! ```
+//! use kimchi::circuits::lookup::tables::builder::{LookupTableBuilder, LookupTables};
+//! use mina_curves::pasta::Fp;
+//!
+//! let mut tables = LookupTables::<Fp>::new();
+//! // x -> x^2 for bytes
+//! let square = tables
+//!     .add(LookupTableBuilder::from_fn(2, 256, |x| {
+//!         vec![Fp::from(x as u64), Fp::from((x * x) as u64)]
+//!     }))
+//!     .unwrap();
+//! assert_eq!(tables.tables()[0].id, square);
+//! ```
+
+use crate::circuits::lookup::{
+    index::LookupError,
+    lookups::LookupInfo,
+    runtime_tables::RuntimeTableCfg,
+    tables::{LookupTable, RANGE_CHECK_TABLE_ID, XOR_TABLE_ID},
+};
+use ark_ff::FftField;
+
+/// The first ID given by [`LookupTables::add`]. The IDs below are used by the
+/// tables of the gates.
+pub const FIRST_CUSTOM_TABLE_ID: i32 = RANGE_CHECK_TABLE_ID + 1;
+
+/// The rows of a fixed lookup table of a given width
+#[derive(Debug, Clone)]
+pub struct LookupTableBuilder<F> {
+    width: usize,
+    rows: Vec<Vec<F>>,
+}
+
+impl<F: FftField> LookupTableBuilder<F> {
+    /// Creates a table with `width` columns and no rows.
+    pub fn new(width: usize) -> Self {
+        LookupTableBuilder {
+            width,
+            rows: vec![],
+        }
+    }
+
+    /// Creates a table with `width` columns, whose `i`-th row is `f(i)` for
+    /// `i < len`.
+    pub fn from_fn(width: usize, len: usize, f: impl FnMut(usize) -> Vec<F>) -> Self {
+        Self::from_rows(width, (0..len).map(f))
+    }
+
+    /// Creates a table with `width` columns from its rows.
+    pub fn from_rows<R>(width: usize, rows: impl IntoIterator<Item = R>) -> Self
+    where
+        R: IntoIterator<Item = F>,
+    {
+        LookupTableBuilder {
+            width,
+            rows: rows
+                .into_iter()
+                .map(|row| row.into_iter().collect())
+                .collect(),
+        }
+    }
+
+    /// Appends a row to the table.
+    #[must_use]
+    pub fn row(mut self, row: impl IntoIterator<Item = F>) -> Self {
+        self.rows.push(row.into_iter().collect());
+        self
+    }
+
+    /// Appends a row of zeros to the table, if it does not contain one.
+    #[must_use]
+    pub fn with_zero_row(mut self) -> Self {
+        if !self.rows.iter().any(|row| row.iter().all(F::is_zero)) {
+            self.rows.push(vec![F::zero(); self.width]);
+        }
+        self
+    }
+
+    /// Returns the number of columns of the table.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows of the table.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns `true` if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Builds the table with the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the table is empty, or if a row does not have
+    /// [`Self::width`] entries.
+    pub fn build(self, id: i32) -> Result<LookupTable<F>, LookupError> {
+        if self.width == 0 || self.rows.is_empty() {
+            return Err(LookupError::EmptyTable(id));
+        }
+        let mut data = vec![Vec::with_capacity(self.rows.len()); self.width];
+        for (row, entries) in self.rows.into_iter().enumerate() {
+            if entries.len() != self.width {
+                return Err(LookupError::InconsistentTableWidth {
+                    id,
+                    row,
+                    width: entries.len(),
+                    expected: self.width,
+                });
+            }
+            for (column, entry) in data.iter_mut().zip(entries) {
+                column.push(entry);
+            }
+        }
+        Ok(LookupTable { id, data })
+    }
+}
+
+/// A set of fixed lookup tables with distinct IDs
+#[derive(Debug, Clone, Default)]
+pub struct LookupTables<F> {
+    tables: Vec<LookupTable<F>>,
+    /// The IDs of the runtime tables of the circuit, not given to fixed tables
+    runtime_ids: Vec<i32>,
+}
+
+impl<F: FftField> LookupTables<F> {
+    /// Creates an empty set of tables.
+    pub fn new() -> Self {
+        LookupTables {
+            tables: vec![],
+            runtime_ids: vec![],
+        }
+    }
+
+    /// Reserves the ID of a runtime table of the circuit, so that it is not
+    /// given to a fixed table. The constraint system would otherwise merge the
+    /// runtime table into the fixed table with the same ID.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a fixed table already has this ID.
+    pub fn reserve(&mut self, id: i32) -> Result<(), LookupError> {
+        if self.tables.iter().any(|table| table.id == id) {
+            return Err(LookupError::LookupTableIdCollision {
+                collision_type: format!("table ID {id} is already used by a fixed table"),
+            });
+        }
+        if !self.runtime_ids.contains(&id) {
+            self.runtime_ids.push(id);
+        }
+        Ok(())
+    }
+
+    /// Adds a table with the smallest free ID, starting from
+    /// [`FIRST_CUSTOM_TABLE_ID`], and returns this ID. The IDs of the tables
+    /// already added and the reserved IDs are not free.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the table is malformed, see
+    /// [`LookupTableBuilder::build`].
+    pub fn add(&mut self, table: LookupTableBuilder<F>) -> Result<i32, LookupError> {
+        let id = (FIRST_CUSTOM_TABLE_ID..)
+            .find(|id| !self.contains(*id))
+            .expect("there is a free table ID");
+        self.tables.push(table.build(id)?);
+        Ok(id)
+    }
+
+    /// Adds a table with the given ID. The table with ID 0 is given a row of
+    /// zeros if it does not have one, as required by the lookup argument.
+    ///
+    /// Note that the IDs of the tables used by gates ([`XOR_TABLE_ID`] and
+    /// [`RANGE_CHECK_TABLE_ID`]) can only be used by circuits without these
+    /// gates.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the ID is already used or reserved, or if the table
+    /// is malformed.
+    pub fn add_with_id(
+        &mut self,
+        id: i32,
+        table: LookupTableBuilder<F>,
+    ) -> Result<(), LookupError> {
+        if self.contains(id) {
+            return Err(LookupError::LookupTableIdCollision {
+                collision_type: format!("table ID {id} is already used"),
+            });
+        }
+        let table = if id == XOR_TABLE_ID {
+            table.with_zero_row()
+        } else {
+            table
+        };
+        self.tables.push(table.build(id)?);
+        Ok(())
+    }
+
+    /// Returns `true` if a table has the given ID, or if it is reserved.
+    pub fn contains(&self, id: i32) -> bool {
+        self.runtime_ids.contains(&id) || self.tables.iter().any(|table| table.id == id)
+    }
+
+    /// Returns the tables, in the order they were added.
+    pub fn tables(&self) -> &[LookupTable<F>] {
+        &self.tables
+    }
+
+    /// Checks that the tables can be queried by the lookups described by
+    /// `info`, i.e. that they are not wider than the largest joint lookup.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a table has too many columns.
+    pub fn check(&self, info: &LookupInfo) -> Result<(), LookupError> {
+        let max_width = info.max_joint_size as usize;
+        match self.tables.iter().find(|table| table.width() > max_width) {
+            Some(table) => Err(LookupError::TableTooWide {
+                id: table.id,
+                width: table.width(),
+                max_width,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the tables against `info` (see [`Self::check`]) and against
+    /// the runtime tables of the circuit, and returns them, to be given to
+    /// [`Builder::lookup`](crate::circuits::constraints::Builder::lookup).
+    ///
+    /// # Errors
+    ///
+    /// Will give error if a table has too many columns, or if a fixed table
+    /// has the ID of a runtime table.
+    pub fn build(
+        self,
+        info: &LookupInfo,
+        runtime_tables: &[RuntimeTableCfg<F>],
+    ) -> Result<Vec<LookupTable<F>>, LookupError> {
+        self.check(info)?;
+        let collisions: Vec<i32> = runtime_tables
+            .iter()
+            .map(|runtime| runtime.id)
+            .filter(|id| self.tables.iter().any(|table| table.id == *id))
+            .collect();
+        if !collisions.is_empty() {
+            return Err(LookupError::LookupTableIdCollision {
+                collision_type: format!(
+                    "fixed tables with the IDs of runtime tables: {collisions:?}"
+                ),
+            });
+        }
+        Ok(self.tables)
+    }
+}
+
+impl<F> From<LookupTables<F>> for Vec<LookupTable<F>> {
+    fn from(tables: LookupTables<F>) -> Self {
+        tables.tables
+    }
+}
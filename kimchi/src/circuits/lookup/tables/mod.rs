@@ -2,6 +2,7 @@ use ark_ff::{FftField, One, Zero};
 use poly_commitment::PolyComm;
 use serde::{Deserialize, Serialize};
 
+pub mod builder;
 pub mod range_check;
 pub mod xor;
 
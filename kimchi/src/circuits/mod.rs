@@ -4,6 +4,8 @@ pub mod macros;
 pub mod argument;
 pub mod berkeley_columns;
 pub mod constraints;
+pub mod description;
+pub mod diagnostics;
 pub mod domain_constant_evaluation;
 pub mod domains;
 pub mod expr;
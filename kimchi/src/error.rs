@@ -112,3 +112,61 @@ pub enum VerifierIndexError {
     #[error("srs has already been set")]
     SRSHasBeenSet,
 }
+
+/// Errors that can arise when loading a circuit or a witness description
+#[derive(Error, Debug, Clone)]
+pub enum DescriptionError {
+    #[error("unsupported format version {0} (supported: {1})")]
+    UnsupportedVersion(u32, u32),
+
+    #[error("the description could not be decoded: {0}")]
+    Decoding(String),
+
+    #[error("the description could not be encoded: {0}")]
+    Encoding(String),
+
+    #[error("the witness has {0} columns, expected {1}")]
+    WitnessColumns(usize, usize),
+
+    #[error("the witness columns are not all the same size")]
+    WitnessInconsistent,
+
+    #[error(
+        "the cell ({0}, {1}) is wired to ({2}, {3}), which is not a permuted cell of the circuit"
+    )]
+    WireOutOfRange(usize, usize, usize, usize),
+
+    #[error("the wiring is not a permutation: {2} cells are wired to the cell ({0}, {1})")]
+    WiringNotPermutation(usize, usize, usize),
+
+    #[error("the declared feature flags do not match the circuit: declared {0}, found {1}")]
+    FeatureFlags(String, String),
+
+    #[error("the constraint system cannot be built: {0}")]
+    Setup(SetupError),
+}
+
+/// Errors that can arise when generating or running a Solidity verifier
+#[derive(Error, Debug, Clone)]
+pub enum SolidityError {
+    #[error("the domain is larger than the SRS (domain size: {0}, max poly size: {1}), chunked commitments are not supported")]
+    ChunkedCommitments(usize, usize),
+
+    #[error("recursive proofs are not supported (previous challenges: {0})")]
+    PreviousChallenges(usize),
+
+    #[error("the commitment to {0} has {1} chunks, only single-chunk commitments are supported")]
+    IncorrectCommitmentLength(&'static str, usize),
+
+    #[error("the verifier SRS has {0} elements, at least 3 are needed to open at two points")]
+    VerifierSRSTooSmall(usize),
+
+    #[error("the linearization contains an unsupported term: {0}")]
+    UnsupportedLinearization(String),
+
+    #[error("the proof does not match the verifier index: {0}")]
+    ProofShape(&'static str),
+
+    #[error("the public input is of an unexpected size (expected {0}, got {1})")]
+    IncorrectPublicInputLength(usize, usize),
+}
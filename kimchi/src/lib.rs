@@ -20,10 +20,13 @@ pub mod linearization;
 pub mod oracles;
 pub mod plonk_sponge;
 pub mod precomputed_srs;
+pub mod profile;
 pub mod proof;
 pub mod prover;
 pub mod prover_index;
 pub mod snarky;
+#[cfg(feature = "bn254")]
+pub mod solidity;
 pub mod verifier;
 pub mod verifier_index;
 
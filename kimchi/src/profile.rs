@@ -0,0 +1,274 @@
+//! This module implements a profile of the prover, to find out which gates and
+//! which steps of the proof creation dominate the cost of a given circuit.
+//!
+//! A [`ProverProfile`] describes the shape of the circuit (the number of rows
+//! of each gate type and the degree of the constraints of each gate, which
+//! determines the domain used to compute the quotient polynomial), and, when
+//! given to [`ProverProof::create_recursive_with_profile`](crate::proof::ProverProof::create_recursive_with_profile),
+//! the time spent in each phase of the prover and the multi-scalar
+//! multiplications it computes. The profile can be written as JSON with
+//! [`ProverProfile::to_json`].
+
+use crate::{
+    circuits::{
+        argument::ArgumentEnv,
+        berkeley_columns::{self, E},
+        expr,
+        gate::GateType,
+        lookup,
+    },
+    curve::KimchiCurve,
+    prover_index::ProverIndex,
+};
+use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
+use poly_commitment::OpenProof;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Instant};
+
+/// The constraints of a gate type, or of the lookup argument
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstraintProfile {
+    /// The name of the gate type, or `Lookup`
+    pub name: String,
+    /// The number of constraints
+    pub constraints: usize,
+    /// The largest degree of the constraints, as given by
+    /// [`Expr::degree`](crate::circuits::expr::Expr::degree), including the
+    /// selector of the gate type
+    pub degree: u64,
+    /// The size of the smallest domain over which the constraints can be
+    /// evaluated, as a multiple of the size of the circuit domain
+    pub quotient_domain: u64,
+}
+
+/// The time spent in a phase of the prover
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseProfile {
+    /// The name of the phase
+    pub name: String,
+    /// The time spent in the phase, in seconds
+    pub seconds: f64,
+}
+
+/// Multi-scalar multiplications computed by the prover to commit to a
+/// polynomial
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MsmProfile {
+    /// The committed polynomial
+    pub name: String,
+    /// The number of multi-scalar multiplications, i.e. of chunks
+    pub count: usize,
+    /// The number of points of each multi-scalar multiplication
+    pub size: usize,
+    /// Whether `count` and `size` are estimated from the commitment scheme,
+    /// rather than recorded when committing
+    pub estimated: bool,
+}
+
+/// A profile of the prover on a given circuit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProverProfile {
+    /// The size of the domain of the circuit
+    pub domain_size: usize,
+    /// The number of zero-knowledge rows
+    pub zk_rows: u64,
+    /// The number of chunks of the polynomials
+    pub num_chunks: usize,
+    /// The number of public inputs
+    pub public: usize,
+    /// The number of rows of each gate type, the rows up to the domain size
+    /// being `Zero` gates
+    pub gates: BTreeMap<String, usize>,
+    /// The constraints of each gate type used by the circuit
+    pub constraints: Vec<ConstraintProfile>,
+    /// The phases of the prover, in order
+    pub phases: Vec<PhaseProfile>,
+    /// The multi-scalar multiplications of the prover, in order
+    pub msms: Vec<MsmProfile>,
+    /// The total time spent creating the proof, in seconds
+    pub total_seconds: f64,
+}
+
+impl ProverProfile {
+    /// Creates the profile of a circuit, without timings.
+    pub fn new<G: KimchiCurve, OpeningProof: OpenProof<G>>(
+        index: &ProverIndex<G, OpeningProof>,
+    ) -> Self
+    where
+        G::BaseField: PrimeField,
+    {
+        let d1_size = index.cs.domain.d1.size();
+        let num_chunks = if d1_size < index.max_poly_size {
+            1
+        } else {
+            d1_size / index.max_poly_size
+        };
+
+        let mut rows = BTreeMap::<GateType, usize>::new();
+        for gate in &index.cs.gates {
+            *rows.entry(gate.typ).or_default() += 1;
+        }
+
+        let mut cache = expr::Cache::default();
+        let env = ArgumentEnv::<G::ScalarField, E<G::ScalarField>>::default();
+        let mut constraints: Vec<_> = rows
+            .keys()
+            .map(|typ| {
+                // the prover only applies the constraints of a gate on its
+                // rows, by multiplying them with the selector of the gate
+                let exprs = typ
+                    .constraint_checks(&env, &mut cache)
+                    .into_iter()
+                    .map(|e| berkeley_columns::index(*typ) * e)
+                    .collect::<Vec<_>>();
+                (format!("{typ:?}"), exprs)
+            })
+            .collect();
+        if let Some(lcs) = &index.cs.lookup_constraint_system {
+            let exprs = lookup::constraints::constraints(&lcs.configuration, false);
+            constraints.push(("Lookup".to_string(), exprs));
+        }
+        let constraints = constraints
+            .into_iter()
+            .filter(|(_, exprs)| !exprs.is_empty())
+            .map(|(name, exprs)| {
+                let degree = exprs
+                    .iter()
+                    .map(|e| e.degree(d1_size as u64, index.cs.zk_rows))
+                    .max()
+                    .unwrap_or_default();
+                ConstraintProfile {
+                    name,
+                    constraints: exprs.len(),
+                    degree,
+                    quotient_domain: quotient_domain(degree, d1_size as u64),
+                }
+            })
+            .collect();
+
+        ProverProfile {
+            domain_size: d1_size,
+            zk_rows: index.cs.zk_rows,
+            num_chunks,
+            public: index.cs.public,
+            gates: rows
+                .into_iter()
+                .map(|(typ, rows)| (format!("{typ:?}"), rows))
+                .collect(),
+            constraints,
+            phases: vec![],
+            msms: vec![],
+            total_seconds: 0.,
+        }
+    }
+
+    /// Returns the time spent in the phase `name`, if the prover went
+    /// through it.
+    pub fn phase(&self, name: &str) -> Option<f64> {
+        self.phases
+            .iter()
+            .find(|phase| phase.name == name)
+            .map(|phase| phase.seconds)
+    }
+
+    /// Serializes the profile as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if the serialization fails.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// The smallest power of two `k` such that a polynomial of degree `degree`
+/// can be interpolated over a domain of size `k * d1_size`.
+fn quotient_domain(degree: u64, d1_size: u64) -> u64 {
+    let mut k = 1;
+    while k * d1_size <= degree {
+        k *= 2;
+    }
+    k
+}
+
+/// Records the phases and the multi-scalar multiplications of the prover in
+/// a [`ProverProfile`], if one is given.
+pub(crate) struct Profiler<'a> {
+    profile: Option<&'a mut ProverProfile>,
+    start: Option<Instant>,
+    phase: Option<(&'static str, Instant)>,
+}
+
+impl<'a> Profiler<'a> {
+    /// Initializes `profile` from the index, and starts timing the prover.
+    pub fn new<G: KimchiCurve, OpeningProof: OpenProof<G>>(
+        index: &ProverIndex<G, OpeningProof>,
+        profile: Option<&'a mut ProverProfile>,
+    ) -> Self
+    where
+        G::BaseField: PrimeField,
+    {
+        let profile = profile.map(|profile| {
+            *profile = ProverProfile::new(index);
+            profile
+        });
+        let start = profile.as_ref().map(|_| Instant::now());
+        Profiler {
+            profile,
+            start,
+            phase: None,
+        }
+    }
+
+    fn end_phase(&mut self) {
+        if let (Some(profile), Some((name, start))) = (&mut self.profile, self.phase.take()) {
+            profile.phases.push(PhaseProfile {
+                name: name.to_string(),
+                seconds: start.elapsed().as_secs_f64(),
+            });
+        }
+    }
+
+    /// Ends the current phase, and starts the phase `name`.
+    pub fn phase(&mut self, name: &'static str) {
+        if self.profile.is_some() {
+            self.end_phase();
+            self.phase = Some((name, Instant::now()));
+        }
+    }
+
+    /// Records `count` multi-scalar multiplications of `size` points to
+    /// commit to the polynomial `name`.
+    pub fn msm(&mut self, name: &str, count: usize, size: usize) {
+        if let Some(profile) = &mut self.profile {
+            profile.msms.push(MsmProfile {
+                name: name.to_string(),
+                count,
+                size,
+                estimated: false,
+            });
+        }
+    }
+
+    /// Records an estimate of `count` multi-scalar multiplications of `size`
+    /// points computed by the step `name`, e.g. the opening proof.
+    pub fn msm_estimate(&mut self, name: &str, count: usize, size: usize) {
+        if let Some(profile) = &mut self.profile {
+            profile.msms.push(MsmProfile {
+                name: name.to_string(),
+                count,
+                size,
+                estimated: true,
+            });
+        }
+    }
+
+    /// Ends the current phase, and records the total time.
+    pub fn finish(mut self) {
+        self.end_phase();
+        if let (Some(profile), Some(start)) = (self.profile, self.start) {
+            profile.total_seconds = start.elapsed().as_secs_f64();
+        }
+    }
+}
@@ -10,7 +10,10 @@ use ark_ec::AffineRepr;
 use ark_ff::{FftField, One, Zero};
 use ark_poly::univariate::DensePolynomial;
 use o1_utils::ExtendedDensePolynomial;
-use poly_commitment::commitment::{b_poly, b_poly_coefficients, PolyComm};
+use poly_commitment::{
+    accumulator::Accumulator,
+    commitment::{b_poly, b_poly_coefficients, PolyComm},
+};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::array;
@@ -353,6 +356,16 @@ impl<Eval> ProofEvaluations<Eval> {
     }
 }
 
+impl<G: AffineRepr> From<Accumulator<G>> for RecursionChallenge<G> {
+    /// Defers the check of an accumulator to a recursive proof.
+    fn from(accumulator: Accumulator<G>) -> Self {
+        RecursionChallenge {
+            chals: accumulator.chals,
+            comm: PolyComm::new(vec![accumulator.sg]),
+        }
+    }
+}
+
 impl<G: AffineRepr> RecursionChallenge<G> {
     pub fn new(chals: Vec<G::ScalarField>, comm: PolyComm<G>) -> RecursionChallenge<G> {
         RecursionChallenge { chals, comm }
@@ -27,6 +27,7 @@ use crate::{
     error::ProverError,
     lagrange_basis_evaluations::LagrangeBasisEvaluations,
     plonk_sponge::FrSponge,
+    profile::{Profiler, ProverProfile},
     proof::{
         LookupCommitments, PointEvaluations, ProofEvaluations, ProverCommitments, ProverProof,
         RecursionChallenge,
@@ -157,6 +158,40 @@ where
         )
     }
 
+    /// This function constructs prover's zk-proof like [`Self::create`], and
+    /// profiles the prover, see [`ProverProfile`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `create_recursive` process fails.
+    pub fn create_with_profile<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+    >(
+        groupmap: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        rng: &mut RNG,
+    ) -> Result<(Self, ProverProfile)>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        let mut profile = ProverProfile::default();
+        let proof = Self::create_recursive_with_profile::<EFqSponge, EFrSponge, RNG>(
+            groupmap,
+            witness,
+            runtime_tables,
+            index,
+            Vec::new(),
+            None,
+            rng,
+            Some(&mut profile),
+        )?;
+        Ok((proof, profile))
+    }
+
     /// This function constructs prover's recursive zk-proof from the witness &
     /// the `ProverIndex` against SRS instance
     ///
@@ -172,6 +207,48 @@ where
         EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
         EFrSponge: FrSponge<G::ScalarField>,
         RNG: RngCore + CryptoRng,
+    >(
+        group_map: &G::Map,
+        witness: [Vec<G::ScalarField>; COLUMNS],
+        runtime_tables: &[RuntimeTable<G::ScalarField>],
+        index: &ProverIndex<G, OpeningProof>,
+        prev_challenges: Vec<RecursionChallenge<G>>,
+        blinders: Option<[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
+        rng: &mut RNG,
+    ) -> Result<Self>
+    where
+        VerifierIndex<G, OpeningProof>: Clone,
+    {
+        Self::create_recursive_with_profile::<EFqSponge, EFrSponge, RNG>(
+            group_map,
+            witness,
+            runtime_tables,
+            index,
+            prev_challenges,
+            blinders,
+            rng,
+            None,
+        )
+    }
+
+    /// This function constructs prover's recursive zk-proof like
+    /// [`Self::create_recursive`]. If `profile` is given, it is overwritten
+    /// with the profile of the circuit and the timings of the prover, see
+    /// [`ProverProfile`].
+    ///
+    /// # Errors
+    ///
+    /// Will give error if inputs(like `lookup_context.joint_lookup_table_d8`)
+    /// are None.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `lookup_context.joint_lookup_table_d8` is None.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_recursive_with_profile<
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        EFrSponge: FrSponge<G::ScalarField>,
+        RNG: RngCore + CryptoRng,
     >(
         group_map: &G::Map,
         mut witness: [Vec<G::ScalarField>; COLUMNS],
@@ -180,11 +257,14 @@ where
         prev_challenges: Vec<RecursionChallenge<G>>,
         blinders: Option<[Option<PolyComm<G::ScalarField>>; COLUMNS]>,
         rng: &mut RNG,
+        profile: Option<&mut ProverProfile>,
     ) -> Result<Self>
     where
         VerifierIndex<G, OpeningProof>: Clone,
     {
         internal_tracing::checkpoint!(internal_traces; create_recursive);
+        let mut profiler = Profiler::new(index, profile);
+        profiler.phase("check_witness");
         let d1_size = index.cs.domain.d1.size();
 
         let (_, endo_r) = G::endos();
@@ -194,6 +274,8 @@ where
         } else {
             d1_size / index.max_poly_size
         };
+        // the number of points of the MSMs committing to a polynomial of size `len`
+        let msm_size = |len: usize| std::cmp::min(len, index.max_poly_size);
 
         // Verify the circuit satisfiability by the computed witness (baring plookup constraints)
         // Catch mistakes before proof generation.
@@ -235,6 +317,7 @@ where
         //~ 1. Pad the witness columns with Zero gates to make them the same length as the domain.
         //~    Then, randomize the last `zk_rows` of each columns.
         internal_tracing::checkpoint!(internal_traces; pad_witness);
+        profiler.phase("pad_witness");
         for w in &mut witness {
             if w.len() != length_witness {
                 return Err(ProverError::WitnessCsInconsistent);
@@ -251,6 +334,7 @@ where
 
         //~ 1. Setup the Fq-Sponge.
         internal_tracing::checkpoint!(internal_traces; set_up_fq_sponge);
+        profiler.phase("public_input");
         let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
 
         //~ 1. Absorb the digest of the VerifierIndex.
@@ -274,6 +358,7 @@ where
 
         //~ 1. Commit (non-hiding) to the negated public input polynomial.
         let public_comm = index.srs.commit_non_hiding(&public_poly, num_chunks);
+        profiler.msm("public", num_chunks, msm_size(public_poly.coeffs.len()));
         let public_comm = {
             index
                 .srs
@@ -297,6 +382,8 @@ where
         //~    Note: since the witness is in evaluation form,
         //~    we can use the `commit_evaluation` optimization.
         internal_tracing::checkpoint!(internal_traces; commit_to_witness_columns);
+        profiler.phase("witness_commitments");
+        profiler.msm("witness", COLUMNS * num_chunks, msm_size(d1_size));
         // generate blinders if not given externally
         let blinders_final: Vec<PolyComm<G::ScalarField>> = match blinders {
             None => (0..COLUMNS)
@@ -351,6 +438,7 @@ where
         //~    As mentioned above, we commit using the evaluations form rather than the coefficients
         //~    form so we can take advantage of the sparsity of the evaluations (i.e., there are many
         //~    0 entries and entries that have less-than-full-size field elemnts.)
+        profiler.phase("witness_interpolation");
         let witness_poly: [DensePolynomial<G::ScalarField>; COLUMNS] = (0..COLUMNS)
             .into_par_iter()
             .map(|i| {
@@ -372,6 +460,7 @@ where
                 "uses_lookup": true,
                 "uses_runtime_tables": lcs.runtime_tables.is_some(),
             });
+            profiler.phase("lookup_sorted");
             //~~ * if using runtime table:
             if let Some(cfg_runtime_tables) = &lcs.runtime_tables {
                 //~~~ * check that all the provided runtime tables have length and IDs that match the runtime table configuration of the index
@@ -423,6 +512,7 @@ where
                     index
                         .srs
                         .commit(&runtime_table_contribution, num_chunks, rng);
+                profiler.msm("runtime_table", num_chunks, msm_size(d1_size));
 
                 // absorb the commitment
                 absorb_commitment(&mut fq_sponge, &runtime_table_comm.commitment);
@@ -562,6 +652,11 @@ where
                 .iter()
                 .map(|v| index.srs.commit_evaluations(index.cs.domain.d1, v, rng))
                 .collect();
+            profiler.msm(
+                "lookup_sorted",
+                sorted.len() * num_chunks,
+                msm_size(d1_size),
+            );
 
             //~~ * Absorb each commitments to the sorted polynomials.
             sorted_comms
@@ -594,6 +689,7 @@ where
         //~ 1. If using lookup:
         if let Some(lcs) = &index.cs.lookup_constraint_system {
             //~~ * Compute the lookup aggregation polynomial.
+            profiler.phase("lookup_aggregation");
             let joint_lookup_table_d8 = lookup_context.joint_lookup_table_d8.as_ref().unwrap();
 
             let aggreg = lookup::constraints::aggregation::<_, G::ScalarField>(
@@ -616,6 +712,7 @@ where
             let aggreg_comm = index
                 .srs
                 .commit_evaluations(index.cs.domain.d1, &aggreg, rng);
+            profiler.msm("lookup_aggregation", num_chunks, msm_size(d1_size));
 
             //~~ * Absorb the commitment to the aggregation polynomial with the Fq-Sponge.
             absorb_commitment(&mut fq_sponge, &aggreg_comm.commitment);
@@ -633,10 +730,16 @@ where
 
         //~ 1. Compute the permutation aggregation polynomial $z$.
         internal_tracing::checkpoint!(internal_traces; z_permutation_aggregation_polynomial);
+        profiler.phase("permutation_aggregation");
         let z_poly = index.perm_aggreg(&witness, &beta, &gamma, rng)?;
 
         //~ 1. Commit (hidding) to the permutation aggregation polynomial $z$.
         let z_comm = index.srs.commit(&z_poly, num_chunks, rng);
+        profiler.msm(
+            "permutation_aggregation",
+            num_chunks,
+            msm_size(z_poly.coeffs.len()),
+        );
 
         //~ 1. Absorb the permutation aggregation polynomial $z$ with the Fq-Sponge.
         absorb_commitment(&mut fq_sponge, &z_comm.commitment);
@@ -675,6 +778,7 @@ where
         };
 
         internal_tracing::checkpoint!(internal_traces; eval_witness_polynomials_over_domains);
+        profiler.phase("quotient");
         let lagrange = index.cs.evaluate(&witness_poly, &z_poly);
         internal_tracing::checkpoint!(internal_traces; compute_index_evals);
         let env = {
@@ -899,7 +1003,13 @@ where
         };
 
         //~ 1. commit (hiding) to the quotient polynomial $t$
+        profiler.phase("quotient_commitment");
         let t_comm = { index.srs.commit(&quotient_poly, 7 * num_chunks, rng) };
+        profiler.msm(
+            "quotient",
+            7 * num_chunks,
+            msm_size(quotient_poly.coeffs.len()),
+        );
 
         //~ 1. Absorb the commitment of the quotient polynomial with the Fq-Sponge.
         absorb_commitment(&mut fq_sponge, &t_comm.commitment);
@@ -913,6 +1023,8 @@ where
         let omega = index.cs.domain.d1.group_gen;
         let zeta_omega = zeta * omega;
 
+        profiler.phase("evaluations");
+
         //~ 1. If lookup is used, evaluate the following polynomials at $\zeta$ and $\zeta \omega$:
         if index.cs.lookup_constraint_system.is_some() {
             //~~ * the aggregation polynomial
@@ -1143,6 +1255,7 @@ where
         //~ 1. Compute the ft polynomial.
         //~    This is to implement [Maller's optimization](https://o1-labs.github.io/proof-systems/kimchi/maller_15.html).
         internal_tracing::checkpoint!(internal_traces; compute_ft_poly);
+        profiler.phase("linearization");
         let ft: DensePolynomial<G::ScalarField> = {
             let f_chunked = {
                 // TODO: compute the linearization polynomial in evaluation form so
@@ -1469,6 +1582,10 @@ where
 
         //~ 1. Create an aggregated evaluation proof for all of these polynomials at $\zeta$ and $\zeta\omega$ using $u$ and $v$.
         internal_tracing::checkpoint!(internal_traces; create_aggregated_ipa);
+        profiler.phase("opening");
+        for (count, size) in OpeningProof::open_msms(&index.srs, 2) {
+            profiler.msm_estimate("opening", count, size);
+        }
         let proof = OpenProof::open(
             &*index.srs,
             group_map,
@@ -1503,6 +1620,7 @@ where
         };
 
         internal_tracing::checkpoint!(internal_traces; create_recursive_done);
+        profiler.finish();
 
         Ok(proof)
     }
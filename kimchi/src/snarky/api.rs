@@ -5,7 +5,10 @@
 use std::marker::PhantomData;
 
 use crate::{
-    circuits::{constraints::ConstraintSystem, gate::CircuitGate, polynomial::COLUMNS},
+    circuits::{
+        constraints::ConstraintSystem, gate::CircuitGate, lookup::tables::LookupTable,
+        polynomial::COLUMNS,
+    },
     curve::KimchiCurve,
     groupmap::GroupMap,
     mina_poseidon::FqSponge,
@@ -301,6 +304,13 @@ pub trait SnarkyCircuit: Sized {
         private_input: Option<&Self::PrivateInput>,
     ) -> SnarkyResult<Self::PublicOutput>;
 
+    /// The fixed lookup tables queried by the circuit with
+    /// [RunState::lookup]. See [LookupTables](crate::circuits::lookup::tables::builder::LookupTables)
+    /// to build them.
+    fn lookup_tables(&self) -> Vec<LookupTable<ScalarField<Self::Curve>>> {
+        vec![]
+    }
+
     /// Compiles the circuit to a prover index ([ProverIndexWrapper]) and a verifier index ([VerifierIndexWrapper]).
     fn compile_to_indexes(
         self,
@@ -308,11 +318,13 @@ pub trait SnarkyCircuit: Sized {
     where
         <Self::Curve as AffineRepr>::BaseField: PrimeField,
     {
+        let lookup_tables = self.lookup_tables();
         let compiled_circuit = compile(self)?;
 
         // create constraint system
         let cs = ConstraintSystem::create(compiled_circuit.gates.clone())
             .public(compiled_circuit.public_input_size)
+            .lookup(lookup_tables)
             .build()
             .unwrap();
 
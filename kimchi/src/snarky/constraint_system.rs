@@ -251,6 +251,8 @@ pub enum KimchiConstraint<Var, Field> {
     EcEndoscalar(Vec<EndoscaleScalarRound<Var>>),
     //[[Var; 15]; 4]
     RangeCheck(Vec<Vec<Var>>),
+    //[[Var; 7]; n]: the table ID, followed by three (index, value) pairs
+    Lookup(Vec<Vec<Var>>),
 }
 
 /* TODO: This is a Unique_id in OCaml. */
@@ -990,7 +992,7 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
             (s, ConstantOrVar::Constant) => match self.cached_constants.get(&s) {
                 Some(x) => *x,
                 None => {
-                    let x = self.create_internal(None, vec![]);
+                    let x = self.create_internal(Some(s), vec![]);
                     self.add_generic_constraint(
                         labels,
                         loc,
@@ -1683,6 +1685,16 @@ impl<Field: PrimeField> SnarkyConstraintSystem<Field> {
                 self.add_row(labels, loc, r2, GateType::RangeCheck1, vec![]);
                 self.add_row(labels, loc, r3, GateType::Zero, vec![]);
             }
+            KimchiConstraint::Lookup(rows) => {
+                for row in rows {
+                    assert_eq!(row.len(), PERMUTS, "size of lookup row is != 7");
+                    let vars = row
+                        .into_iter()
+                        .map(|v| Some(self.reduce_to_var(labels, loc, v)))
+                        .collect();
+                    self.add_row(labels, loc, vars, GateType::Lookup, vec![]);
+                }
+            }
         }
     }
     pub(crate) fn sponge_params(&self) -> mina_poseidon::poseidon::ArithmeticSpongeParams<Field> {
@@ -1809,7 +1821,8 @@ where
             | KimchiConstraint::EcScale { .. }
             | KimchiConstraint::EcEndoscale { .. }
             | KimchiConstraint::EcEndoscalar { .. }
-            | KimchiConstraint::RangeCheck { .. } => (),
+            | KimchiConstraint::RangeCheck { .. }
+            | KimchiConstraint::Lookup { .. } => (),
         };
         Ok(())
     }
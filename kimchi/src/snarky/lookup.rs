@@ -0,0 +1,201 @@
+use super::{constraint_system::KimchiConstraint, runner::Constraint};
+use crate::{circuits::lookup::lookups::LookupPattern, FieldVar, RunState, SnarkyResult};
+use ark_ff::PrimeField;
+use o1_utils::field_helpers::i32_to_field;
+use std::borrow::Cow;
+
+/// Constrains each `(index, value)` pair of `entries` to be a row of the
+/// two-column fixed lookup table `table_id`, using one `Lookup` gate per
+/// three pairs.
+pub fn lookup<F: PrimeField>(
+    runner: &mut RunState<F>,
+    loc: Cow<'static, str>,
+    table_id: i32,
+    entries: &[(FieldVar<F>, FieldVar<F>)],
+) -> SnarkyResult<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    // the table ID is a constant, so that it is wired to a generic gate
+    let table_id = FieldVar::constant(i32_to_field(table_id));
+
+    let per_row = LookupPattern::Lookup.max_lookups_per_row();
+    let rows = entries
+        .chunks(per_row)
+        .map(|chunk| {
+            // repeat the last lookup to fill the row
+            let last = chunk.last().expect("chunks are not empty");
+            let mut row = vec![table_id.clone()];
+            for i in 0..per_row {
+                let (index, value) = chunk.get(i).unwrap_or(last);
+                row.push(index.clone());
+                row.push(value.clone());
+            }
+            row
+        })
+        .collect();
+
+    let constraint = Constraint::KimchiConstraint(KimchiConstraint::Lookup(rows));
+    runner.add_constraint(constraint, Some("Lookup".into()), loc)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        circuits::lookup::tables::{
+            builder::{LookupTableBuilder, LookupTables},
+            LookupTable,
+        },
+        loc,
+        snarky::api::SnarkyCircuit,
+        FieldVar, RunState, SnarkyResult,
+    };
+    use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+    use mina_poseidon::{
+        constants::PlonkSpongeConstantsKimchi,
+        sponge::{DefaultFqSponge, DefaultFrSponge},
+    };
+    use poly_commitment::ipa::OpeningProof;
+
+    type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+    type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+    /// Looks up the squares of the private inputs
+    struct TestCircuit {
+        tables: LookupTables<Fp>,
+        squares: i32,
+    }
+
+    impl TestCircuit {
+        fn new() -> Self {
+            let mut tables = LookupTables::new();
+            let squares = tables
+                .add(LookupTableBuilder::from_fn(2, 16, |x| {
+                    vec![Fp::from(x as u64), Fp::from((x * x) as u64)]
+                }))
+                .unwrap();
+            TestCircuit { tables, squares }
+        }
+    }
+
+    impl SnarkyCircuit for TestCircuit {
+        type Curve = Vesta;
+        type Proof = OpeningProof<Self::Curve>;
+
+        type PrivateInput = Vec<u64>;
+        type PublicInput = ();
+        type PublicOutput = ();
+
+        fn circuit(
+            &self,
+            sys: &mut RunState<Fp>,
+            _public: Self::PublicInput,
+            private: Option<&Self::PrivateInput>,
+        ) -> SnarkyResult<Self::PublicOutput> {
+            let mut entries = vec![];
+            for i in 0..4 {
+                let x: FieldVar<Fp> = sys.compute(loc!(), |_| Fp::from(private.unwrap()[i]))?;
+                let x2: FieldVar<Fp> = sys.compute(loc!(), |_| {
+                    let x = private.unwrap()[i];
+                    Fp::from(x * x)
+                })?;
+                entries.push((x, x2));
+            }
+            sys.lookup(loc!(), self.squares, &entries)?;
+            Ok(())
+        }
+
+        fn lookup_tables(&self) -> Vec<LookupTable<Fp>> {
+            self.tables.tables().to_vec()
+        }
+    }
+
+    #[test]
+    fn snarky_lookup() {
+        let (mut prover_index, verifier_index) = TestCircuit::new().compile_to_indexes().unwrap();
+
+        // two lookup gates, the second one repeats its last lookup
+        assert!(prover_index.asm().matches("Lookup").count() >= 2);
+
+        let (proof, _) = prover_index
+            .prove::<BaseSponge, ScalarSponge>((), vec![3, 0, 15, 7], true)
+            .unwrap();
+        verifier_index.verify::<BaseSponge, ScalarSponge>(proof, (), ());
+    }
+
+    #[test]
+    fn snarky_lookup_constants() {
+        struct ConstantCircuit(TestCircuit);
+
+        impl SnarkyCircuit for ConstantCircuit {
+            type Curve = Vesta;
+            type Proof = OpeningProof<Self::Curve>;
+
+            type PrivateInput = ();
+            type PublicInput = ();
+            type PublicOutput = ();
+
+            fn circuit(
+                &self,
+                sys: &mut RunState<Fp>,
+                _public: Self::PublicInput,
+                _private: Option<&Self::PrivateInput>,
+            ) -> SnarkyResult<Self::PublicOutput> {
+                // the constant is reduced to a variable, whose witness is the
+                // constant
+                let x: FieldVar<Fp> = sys.compute(loc!(), |_| Fp::from(3u8))?;
+                let y = FieldVar::constant(Fp::from(9u8));
+                sys.lookup(loc!(), self.0.squares, &[(x, y)])?;
+                Ok(())
+            }
+
+            fn lookup_tables(&self) -> Vec<LookupTable<Fp>> {
+                self.0.lookup_tables()
+            }
+        }
+
+        let (mut prover_index, verifier_index) = ConstantCircuit(TestCircuit::new())
+            .compile_to_indexes()
+            .unwrap();
+        let (proof, _) = prover_index
+            .prove::<BaseSponge, ScalarSponge>((), (), true)
+            .unwrap();
+        verifier_index.verify::<BaseSponge, ScalarSponge>(proof, (), ());
+    }
+
+    #[test]
+    #[should_panic(expected = "ValueNotInTable")]
+    fn snarky_lookup_fail() {
+        struct BadCircuit(TestCircuit);
+
+        impl SnarkyCircuit for BadCircuit {
+            type Curve = Vesta;
+            type Proof = OpeningProof<Self::Curve>;
+
+            type PrivateInput = ();
+            type PublicInput = ();
+            type PublicOutput = ();
+
+            fn circuit(
+                &self,
+                sys: &mut RunState<Fp>,
+                _public: Self::PublicInput,
+                _private: Option<&Self::PrivateInput>,
+            ) -> SnarkyResult<Self::PublicOutput> {
+                let x: FieldVar<Fp> = sys.compute(loc!(), |_| Fp::from(3u8))?;
+                let y: FieldVar<Fp> = sys.compute(loc!(), |_| Fp::from(10u8))?;
+                sys.lookup(loc!(), self.0.squares, &[(x, y)])?;
+                Ok(())
+            }
+
+            fn lookup_tables(&self) -> Vec<LookupTable<Fp>> {
+                self.0.lookup_tables()
+            }
+        }
+
+        let (mut prover_index, _) = BadCircuit(TestCircuit::new()).compile_to_indexes().unwrap();
+        let _ = prover_index.prove::<BaseSponge, ScalarSponge>((), (), false);
+    }
+}
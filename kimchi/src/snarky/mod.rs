@@ -15,6 +15,7 @@ pub mod constraint_system;
 pub mod cvar;
 pub mod errors;
 pub mod folding;
+pub(crate) mod lookup;
 pub mod poseidon;
 pub(crate) mod range_checks;
 pub mod runner;
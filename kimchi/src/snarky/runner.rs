@@ -8,6 +8,7 @@ use super::{
     errors::{
         RealSnarkyError, SnarkyCompilationError, SnarkyError, SnarkyResult, SnarkyRuntimeResult,
     },
+    lookup::lookup,
     poseidon::poseidon,
     range_checks::range_check,
 };
@@ -675,4 +676,14 @@ where
     ) -> SnarkyResult<()> {
         range_check(self, loc, v0, v1, v2)
     }
+
+    ///constrains each (index, value) pair to be a row of the fixed lookup table `table_id`
+    pub fn lookup(
+        &mut self,
+        loc: Cow<'static, str>,
+        table_id: i32,
+        entries: &[(FieldVar<F>, FieldVar<F>)],
+    ) -> SnarkyResult<()> {
+        lookup(self, loc, table_id, entries)
+    }
 }
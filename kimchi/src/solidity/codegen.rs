@@ -0,0 +1,1202 @@
+//! Generation of the Solidity verifier.
+//!
+//! The verifier is written in inline assembly, with every intermediate value
+//! stored at a fixed memory address and every part of the proof read at a
+//! fixed calldata offset. This keeps the contract small, and lets the
+//! linearization, which has more than a thousand terms for the default
+//! gates, be compiled to nested Yul expressions that never reference a
+//! local variable, and can therefore not run into the "stack too deep"
+//! limit of the EVM.
+
+use super::{
+    check_index, index_commitment, point_words,
+    sponge::{KeccakFqSponge, KeccakFrSponge},
+    verify_signature, BN254VerifierIndex, Fr, ProofLayout, BN254, QUOTIENT_CHUNKS,
+};
+use crate::{
+    circuits::{
+        argument::ArgumentType,
+        berkeley_columns::{BerkeleyChallengeTerm, Column},
+        expr::{ConstantTerm, PolishToken, RowOffset},
+        gate::{CurrOrNext, GateType},
+        lookup::lookups::LookupPattern,
+        polynomials::permutation,
+        wires::*,
+    },
+    curve::KimchiCurve,
+    error::SolidityError,
+    plonk_sponge::FrSponge,
+};
+use ark_ec::AffineRepr;
+use ark_ff::{Field, PrimeField, Zero};
+use mina_poseidon::sponge::ScalarChallenge;
+use num_bigint::BigUint;
+use poly_commitment::SRS as _;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The maximal nesting of the expressions generated for the linearization.
+/// Deeper expressions are stored in memory first.
+const MAX_EXPRESSION_DEPTH: usize = 16;
+
+/// Renders a field element as a Yul literal.
+fn literal<F: PrimeField>(x: &F) -> String {
+    let n: BigUint = x.into_bigint().into();
+    if n < BigUint::from(1024u32) {
+        n.to_string()
+    } else {
+        format!("{n:#x}")
+    }
+}
+
+/// Renders a memory address or a calldata offset.
+fn address(addr: usize) -> String {
+    format!("{addr:#x}")
+}
+
+/// Renders the calldata offset of a word of the proof.
+fn proof_offset(word: usize) -> String {
+    // 4 bytes of function selector
+    address(4 + 32 * word)
+}
+
+/// Loads a word of the proof.
+fn proof_word(word: usize) -> String {
+    format!("calldataload({})", proof_offset(word))
+}
+
+/// Loads a word of memory.
+fn load(addr: usize) -> String {
+    format!("mload({})", address(addr))
+}
+
+/// Renders the coordinates of a point of the verifier index.
+fn point_literals<G: AffineRepr>(p: &G) -> String
+where
+    G::BaseField: PrimeField,
+{
+    let [x, y] = point_words(p);
+    let word = |w: [u8; 32]| literal(&G::BaseField::from_be_bytes_mod_order(&w));
+    format!("{}, {}", word(x), word(y))
+}
+
+/// Memory used by the verifier, allocated from the first free address of
+/// Solidity.
+struct Memory {
+    next: usize,
+    names: Vec<(usize, String)>,
+}
+
+impl Memory {
+    fn alloc(&mut self, name: impl Into<String>, words: usize) -> usize {
+        let addr = self.next;
+        self.next += 32 * words;
+        self.names.push((addr, name.into()));
+        addr
+    }
+}
+
+/// The memory slots of the values computed by the verifier.
+struct Slots {
+    fail: usize,
+    sponge: usize,
+    scratch: usize,
+    pairing: usize,
+    public_comm: usize,
+    ft_comm: usize,
+    table_comm: usize,
+    batch_comm: usize,
+    batch_eval0: usize,
+    batch_eval1: usize,
+    v_power: usize,
+    v: usize,
+    beta: usize,
+    gamma: usize,
+    alpha: usize,
+    zeta: usize,
+    zeta_omega: usize,
+    joint_combiner: usize,
+    zeta_n_minus_1: usize,
+    zeta_to_srs_len: usize,
+    permutation_vanishing: usize,
+    vanishes_on_zk_rows: usize,
+    permutation_alphas: usize,
+    permutation_product: usize,
+    permutation_scalar: usize,
+    constant_term: usize,
+    ft_eval0: usize,
+    tmp: usize,
+    kzg_a: usize,
+    kzg_b: usize,
+}
+
+impl Slots {
+    fn new(mem: &mut Memory) -> Self {
+        Slots {
+            fail: mem.alloc("failure flag", 1),
+            sponge: mem.alloc("sponge state and input", 2),
+            scratch: mem.alloc("precompile scratch space", 6),
+            pairing: mem.alloc("pairing input", 24),
+            public_comm: mem.alloc("public input commitment", 2),
+            ft_comm: mem.alloc("ft commitment", 2),
+            table_comm: mem.alloc("lookup table commitment", 2),
+            batch_comm: mem.alloc("batched commitment", 2),
+            batch_eval0: mem.alloc("batched evaluation at zeta", 1),
+            batch_eval1: mem.alloc("batched evaluation at zeta * omega", 1),
+            v_power: mem.alloc("power of v", 1),
+            v: mem.alloc("v", 1),
+            beta: mem.alloc("beta", 1),
+            gamma: mem.alloc("gamma", 1),
+            alpha: mem.alloc("alpha", 1),
+            zeta: mem.alloc("zeta", 1),
+            zeta_omega: mem.alloc("zeta * omega", 1),
+            joint_combiner: mem.alloc("joint combiner", 1),
+            zeta_n_minus_1: mem.alloc("zeta^n - 1", 1),
+            zeta_to_srs_len: mem.alloc("zeta^max_poly_size", 1),
+            permutation_vanishing: mem.alloc("permutation vanishing polynomial at zeta", 1),
+            vanishes_on_zk_rows: mem.alloc("vanishing polynomial of the zk rows at zeta", 1),
+            permutation_alphas: mem.alloc("powers of alpha of the permutation", 3),
+            permutation_product: mem.alloc("permutation product", 1),
+            permutation_scalar: mem.alloc("permutation scalar", 1),
+            constant_term: mem.alloc("constant term of the linearization", 1),
+            ft_eval0: mem.alloc("ft_eval0", 1),
+            tmp: mem.alloc("temporary value", 1),
+            kzg_a: mem.alloc("opening interpolant constant coefficient", 1),
+            kzg_b: mem.alloc("opening interpolant linear coefficient", 1),
+        }
+    }
+
+    fn challenge(&self, term: BerkeleyChallengeTerm) -> usize {
+        match term {
+            BerkeleyChallengeTerm::Alpha => self.alpha,
+            BerkeleyChallengeTerm::Beta => self.beta,
+            BerkeleyChallengeTerm::Gamma => self.gamma,
+            BerkeleyChallengeTerm::JointCombiner => self.joint_combiner,
+        }
+    }
+}
+
+/// Helper functions shared by every verifier. `R`, `R_MINUS_2`, `ENDO_R`,
+/// `FAIL`, `SPONGE`, `SPONGE_INPUT`, `S0` to `S5`, `BATCH_COMM`,
+/// `BATCH_EVAL0`, `BATCH_EVAL1`, `V_POWER` and `V` are replaced by their
+/// values.
+const HELPERS: &str = "
+function fadd(a, b) -> r {
+    r := addmod(a, b, R)
+}
+
+function fsub(a, b) -> r {
+    r := addmod(a, sub(R, b), R)
+}
+
+function fmul(a, b) -> r {
+    r := mulmod(a, b, R)
+}
+
+function fpow(b, e) -> r {
+    r := 1
+    for {} e { e := shr(1, e) } {
+        if and(e, 1) {
+            r := mulmod(r, b, R)
+        }
+        b := mulmod(b, b, R)
+    }
+}
+
+// inversion with the modexp precompile, the inverse of 0 is 0
+function finv(x) -> r {
+    mstore(S0, 0x20)
+    mstore(S1, 0x20)
+    mstore(S2, 0x20)
+    mstore(S3, x)
+    mstore(S4, R_MINUS_2)
+    mstore(S5, R)
+    if iszero(staticcall(gas(), 0x05, S0, 0xc0, S0, 0x20)) {
+        mstore(FAIL, 1)
+    }
+    r := mload(S0)
+}
+
+function absorb(x) {
+    mstore(SPONGE_INPUT, x)
+    mstore(SPONGE, keccak256(SPONGE, 0x40))
+}
+
+function absorbCalldata(offset, words) {
+    for { let i := 0 } lt(i, words) { i := add(i, 1) } {
+        absorb(calldataload(add(offset, shl(5, i))))
+    }
+}
+
+function squeeze() -> r {
+    r := keccak256(SPONGE, 0x20)
+    mstore(SPONGE, r)
+}
+
+function challenge() -> r {
+    r := and(squeeze(), 0xffffffffffffffffffffffffffffffff)
+}
+
+// see `ScalarChallenge::to_field`
+function toField(c) -> r {
+    let a := 2
+    let b := 2
+    for { let i := 64 } i { i := sub(i, 1) } {
+        a := addmod(a, a, R)
+        b := addmod(b, b, R)
+        let s := 1
+        if iszero(and(shr(sub(shl(1, i), 2), c), 1)) {
+            s := sub(R, 1)
+        }
+        switch and(shr(sub(shl(1, i), 1), c), 1)
+        case 0 {
+            b := addmod(b, s, R)
+        }
+        default {
+            a := addmod(a, s, R)
+        }
+    }
+    r := addmod(mulmod(a, ENDO_R, R), b, R)
+}
+
+function checkScalars(offset, words) {
+    for { let i := 0 } lt(i, words) { i := add(i, 1) } {
+        if iszero(lt(calldataload(add(offset, shl(5, i))), R)) {
+            mstore(FAIL, 1)
+        }
+    }
+}
+
+// acc += s * (x, y)
+function ecMulAdd(acc, x, y, s) {
+    mstore(S0, x)
+    mstore(S1, y)
+    mstore(S2, s)
+    if iszero(staticcall(gas(), 0x07, S0, 0x60, S0, 0x40)) {
+        mstore(FAIL, 1)
+    }
+    mstore(S2, mload(acc))
+    mstore(S3, mload(add(acc, 0x20)))
+    if iszero(staticcall(gas(), 0x06, S0, 0x80, acc, 0x40)) {
+        mstore(FAIL, 1)
+    }
+}
+
+function ecMulAddCalldata(acc, offset, s) {
+    ecMulAdd(acc, calldataload(offset), calldataload(add(offset, 0x20)), s)
+}
+
+// adds a commitment and its evaluations to the batched opening
+function batch(x, y, e0, e1) {
+    let vPower := mload(V_POWER)
+    ecMulAdd(BATCH_COMM, x, y, vPower)
+    mstore(BATCH_EVAL0, addmod(mload(BATCH_EVAL0), mulmod(vPower, e0, R), R))
+    mstore(BATCH_EVAL1, addmod(mload(BATCH_EVAL1), mulmod(vPower, e1, R), R))
+    mstore(V_POWER, mulmod(vPower, mload(V), R))
+}
+
+function batchCalldata(comm, evals) {
+    batch(calldataload(comm), calldataload(add(comm, 0x20)), calldataload(evals), calldataload(add(evals, 0x20)))
+}
+";
+
+struct Generator<'a> {
+    index: &'a BN254VerifierIndex,
+    layout: ProofLayout,
+    mem: Memory,
+    slots: Slots,
+    code: String,
+}
+
+/// An expression of the linearization, and its nesting depth.
+#[derive(Clone)]
+struct Expression {
+    code: String,
+    depth: usize,
+}
+
+impl Expression {
+    fn leaf(code: String) -> Self {
+        Expression { code, depth: 1 }
+    }
+}
+
+impl<'a> Generator<'a> {
+    fn line(&mut self, line: impl AsRef<str>) {
+        self.code.push_str(line.as_ref());
+        self.code.push('\n');
+    }
+
+    fn comment(&mut self, comment: &str) {
+        self.line("");
+        self.line(format!("// {comment}"));
+    }
+
+    fn store(&mut self, addr: usize, value: impl AsRef<str>) {
+        self.line(format!("mstore({}, {})", address(addr), value.as_ref()));
+    }
+
+    /// The evaluation of `col` at $\zeta$ or $\zeta\omega$.
+    fn evaluation(&self, col: Column, row: CurrOrNext) -> Result<String, SolidityError> {
+        let word = self.layout.evaluation(col).ok_or_else(|| {
+            SolidityError::UnsupportedLinearization(format!("no evaluation of {col:?}"))
+        })?;
+        Ok(proof_word(match row {
+            CurrOrNext::Curr => word,
+            CurrOrNext::Next => word + 1,
+        }))
+    }
+
+    fn eval(&self, col: Column) -> String {
+        self.evaluation(col, CurrOrNext::Curr)
+            .expect("the evaluations of the permutation are always present")
+    }
+
+    fn eval_next(&self, col: Column) -> String {
+        self.evaluation(col, CurrOrNext::Next)
+            .expect("the evaluations of the permutation are always present")
+    }
+
+    /// The product of `(zeta - omega^k)` over `k` in `from..n`.
+    fn vanishes_on_last_rows(&self, rows: u64) -> String {
+        let domain = self.index.domain;
+        let mut acc = "1".to_string();
+        for k in domain.size - rows..domain.size {
+            let root = domain.group_gen.pow([k]);
+            acc = format!(
+                "fmul({acc}, fsub({}, {}))",
+                load(self.slots.zeta),
+                literal(&root)
+            );
+        }
+        acc
+    }
+
+    fn transcript(&mut self) {
+        let layout = &self.layout;
+        let (w_comm, z_comm, t_comm, sorted_comm, aggreg_comm, runtime_comm, ft_eval1, quotient) = (
+            layout.w_comm,
+            layout.z_comm,
+            layout.t_comm,
+            layout.sorted_comm,
+            layout.aggreg_comm,
+            layout.runtime_comm,
+            layout.ft_eval1,
+            layout.quotient,
+        );
+        let slots = &self.slots;
+        let (sponge, public_comm, joint_combiner, beta, gamma, alpha, zeta, v) = (
+            slots.sponge,
+            slots.public_comm,
+            slots.joint_combiner,
+            slots.beta,
+            slots.gamma,
+            slots.alpha,
+            slots.zeta,
+            slots.v,
+        );
+        let digest = self.index.digest::<KeccakFqSponge<ark_bn254::g1::Config>>();
+
+        self.comment("Fiat-Shamir: Fq-sponge");
+        self.line(format!("absorb({})", literal(&digest)));
+        self.line(format!("absorb({})", load(public_comm)));
+        self.line(format!("absorb({})", load(public_comm + 32)));
+        self.line(format!(
+            "absorbCalldata({}, {})",
+            proof_offset(w_comm),
+            2 * COLUMNS
+        ));
+        if let Some(li) = &self.index.lookup_index {
+            if let Some(runtime_comm) = runtime_comm {
+                self.line(format!("absorbCalldata({}, 2)", proof_offset(runtime_comm)));
+            }
+            if li.joint_lookup_used {
+                self.store(joint_combiner, "toField(challenge())");
+            } else {
+                let (_, endo_r) = BN254::endos();
+                let zero = ScalarChallenge(Fr::zero()).to_field(endo_r);
+                self.store(joint_combiner, literal(&zero));
+            }
+            self.line(format!(
+                "absorbCalldata({}, {})",
+                proof_offset(sorted_comm),
+                aggreg_comm - sorted_comm
+            ));
+        }
+        self.store(beta, "challenge()");
+        self.store(gamma, "challenge()");
+        if self.index.lookup_index.is_some() {
+            self.line(format!("absorbCalldata({}, 2)", proof_offset(aggreg_comm)));
+        }
+        self.line(format!("absorbCalldata({}, 2)", proof_offset(z_comm)));
+        self.store(alpha, "toField(challenge())");
+        self.line(format!(
+            "absorbCalldata({}, {})",
+            proof_offset(t_comm),
+            2 * QUOTIENT_CHUNKS
+        ));
+        self.store(zeta, "toField(challenge())");
+
+        self.comment("Fiat-Shamir: Fr-sponge");
+        self.line(format!("let digest := mod(squeeze(), {})", modulus::<Fr>()));
+        self.store(sponge, "0");
+        self.line("absorb(digest)");
+        let prev_challenges_digest = KeccakFrSponge::<Fr>::new(BN254::sponge_params()).digest();
+        self.line(format!("absorb({})", literal(&prev_challenges_digest)));
+        self.line(format!(
+            "absorbCalldata({}, {})",
+            proof_offset(ft_eval1),
+            quotient - ft_eval1
+        ));
+        self.store(v, "toField(challenge())");
+    }
+
+    fn powers(&mut self) {
+        let index = self.index;
+        let domain = index.domain;
+        let slots = &self.slots;
+        let (zeta, zeta_omega, zeta_n_minus_1, zeta_to_srs_len, permutation_vanishing) = (
+            slots.zeta,
+            slots.zeta_omega,
+            slots.zeta_n_minus_1,
+            slots.zeta_to_srs_len,
+            slots.permutation_vanishing,
+        );
+        let (vanishes_on_zk_rows, permutation_alphas, alpha) = (
+            slots.vanishes_on_zk_rows,
+            slots.permutation_alphas,
+            slots.alpha,
+        );
+
+        self.comment("powers of zeta and vanishing polynomials");
+        self.store(
+            zeta_omega,
+            format!("fmul({}, {})", load(zeta), literal(&domain.group_gen)),
+        );
+        self.store(
+            zeta_n_minus_1,
+            format!("fsub(fpow({}, {}), 1)", load(zeta), domain.size),
+        );
+        self.store(
+            zeta_to_srs_len,
+            format!("fpow({}, {})", load(zeta), index.max_poly_size),
+        );
+        let vanishing = self.vanishes_on_last_rows(index.zk_rows);
+        self.store(permutation_vanishing, vanishing);
+        let vanishing = self.vanishes_on_last_rows(index.zk_rows + 1);
+        self.store(vanishes_on_zk_rows, vanishing);
+
+        let exponents: Vec<u32> = index
+            .powers_of_alpha
+            .get_exponents(ArgumentType::Permutation, permutation::CONSTRAINTS)
+            .collect();
+        for (i, e) in exponents.into_iter().enumerate() {
+            self.store(
+                permutation_alphas + 32 * i,
+                format!("fpow({}, {e})", load(alpha)),
+            );
+        }
+    }
+
+    /// Compiles the constant term of the linearization, and stores it at
+    /// `constant_term`.
+    fn linearization(&mut self) -> Result<(), SolidityError> {
+        let index = self.index;
+        let tokens = &index.linearization.constant_term;
+        if !index.linearization.index_terms.is_empty() {
+            return Err(SolidityError::UnsupportedLinearization(
+                "index terms".to_string(),
+            ));
+        }
+
+        // the Lagrange basis polynomials, evaluated once
+        let mut lagrange = BTreeMap::new();
+        for token in tokens {
+            if let PolishToken::UnnormalizedLagrangeBasis(RowOffset { zk_rows, offset }) = token {
+                let i = if *zk_rows {
+                    offset - index.zk_rows as i32
+                } else {
+                    *offset
+                };
+                let mem = &mut self.mem;
+                lagrange
+                    .entry(i)
+                    .or_insert_with(|| mem.alloc(format!("Lagrange basis {i} at zeta"), 1));
+            }
+        }
+        if !lagrange.is_empty() {
+            self.comment("unnormalized Lagrange basis at zeta");
+        }
+        let domain = index.domain;
+        for (i, addr) in &lagrange {
+            let root = if *i < 0 {
+                domain.group_gen.pow([(-i) as u64]).inverse().unwrap()
+            } else {
+                domain.group_gen.pow([*i as u64])
+            };
+            self.store(
+                *addr,
+                format!(
+                    "fmul({}, finv(fsub({}, {})))",
+                    load(self.slots.zeta_n_minus_1),
+                    load(self.slots.zeta),
+                    literal(&root)
+                ),
+            );
+        }
+
+        self.comment("constant term of the linearization");
+        let mds = &BN254::sponge_params().mds;
+        let mut stack: Vec<Expression> = vec![];
+        let mut cache: Vec<usize> = vec![];
+        let pop = |stack: &mut Vec<Expression>| {
+            stack
+                .pop()
+                .ok_or_else(|| SolidityError::UnsupportedLinearization("empty stack".to_string()))
+        };
+        for token in tokens {
+            use ConstantTerm::*;
+            use PolishToken::*;
+            let expr = match token {
+                Constant(EndoCoefficient) => Expression::leaf(literal(&index.endo)),
+                Constant(Mds { row, col }) => Expression::leaf(literal(&mds[*row][*col])),
+                Constant(Literal(x)) => Expression::leaf(literal(x)),
+                Challenge(term) => Expression::leaf(load(self.slots.challenge(*term))),
+                Cell(v) => Expression::leaf(self.evaluation(v.col, v.row)?),
+                VanishesOnZeroKnowledgeAndPreviousRows => {
+                    Expression::leaf(load(self.slots.vanishes_on_zk_rows))
+                }
+                UnnormalizedLagrangeBasis(RowOffset { zk_rows, offset }) => {
+                    let i = if *zk_rows {
+                        offset - index.zk_rows as i32
+                    } else {
+                        *offset
+                    };
+                    Expression::leaf(load(lagrange[&i]))
+                }
+                Dup => {
+                    let top = pop(&mut stack)?;
+                    let top = self.materialize(top, None);
+                    stack.push(top.clone());
+                    top
+                }
+                Pow(n) => {
+                    let x = pop(&mut stack)?;
+                    Expression {
+                        code: format!("fpow({}, {n})", x.code),
+                        depth: x.depth + 1,
+                    }
+                }
+                Add | Mul | Sub => {
+                    let y = pop(&mut stack)?;
+                    let x = pop(&mut stack)?;
+                    let op = match token {
+                        Add => "fadd",
+                        Mul => "fmul",
+                        _ => "fsub",
+                    };
+                    Expression {
+                        code: format!("{op}({}, {})", x.code, y.code),
+                        depth: x.depth.max(y.depth) + 1,
+                    }
+                }
+                Store => {
+                    let top = pop(&mut stack)?;
+                    let addr = self.mem.alloc(format!("cached term {}", cache.len()), 1);
+                    cache.push(addr);
+                    self.materialize(top, Some(addr))
+                }
+                Load(i) => Expression::leaf(load(*cache.get(*i).ok_or_else(|| {
+                    SolidityError::UnsupportedLinearization(format!("load of {i}"))
+                })?)),
+                SkipIf(..) | SkipIfNot(..) => {
+                    return Err(SolidityError::UnsupportedLinearization(format!(
+                        "{token:?}"
+                    )))
+                }
+            };
+            let expr = if expr.depth > MAX_EXPRESSION_DEPTH {
+                self.materialize(expr, None)
+            } else {
+                expr
+            };
+            stack.push(expr);
+        }
+        match stack.as_slice() {
+            [result] => {
+                let code = result.code.clone();
+                self.store(self.slots.constant_term, code);
+                Ok(())
+            }
+            _ => Err(SolidityError::UnsupportedLinearization(
+                "the expression does not evaluate to a single value".to_string(),
+            )),
+        }
+    }
+
+    /// Stores `expr` in memory, at `addr` or at a new address, and returns an
+    /// expression that loads it.
+    fn materialize(&mut self, expr: Expression, addr: Option<usize>) -> Expression {
+        if expr.depth == 1 && addr.is_none() {
+            return expr;
+        }
+        let addr = addr.unwrap_or_else(|| self.mem.alloc("intermediate term", 1));
+        self.store(addr, &expr.code);
+        Expression::leaf(load(addr))
+    }
+
+    /// Computes `ft_eval0` and the scalar of the permutation argument, see
+    /// [`crate::verifier`].
+    fn permutation(&mut self) {
+        let index = self.index;
+        let s = &self.slots;
+        let (beta, gamma, zeta, zkp, product, scalar, ft_eval0, tmp, constant_term) = (
+            load(s.beta),
+            load(s.gamma),
+            load(s.zeta),
+            load(s.permutation_vanishing),
+            s.permutation_product,
+            s.permutation_scalar,
+            s.ft_eval0,
+            s.tmp,
+            load(s.constant_term),
+        );
+        let zeta_n_minus_1 = load(s.zeta_n_minus_1);
+        let alphas: Vec<_> = (0..3)
+            .map(|i| load(s.permutation_alphas + 32 * i))
+            .collect();
+        let public_eval = proof_word(self.layout.public_evals);
+        let z = self.eval(Column::Z);
+        let z_next = self.eval_next(Column::Z);
+
+        self.comment("permutation argument");
+        self.store(product, "1");
+        for i in 0..PERMUTS - 1 {
+            let term = format!(
+                "fadd(fadd(fmul({beta}, {}), {}), {gamma})",
+                self.eval(Column::Permutation(i)),
+                self.eval(Column::Witness(i))
+            );
+            self.store(product, format!("fmul({}, {term})", load(product)));
+        }
+        self.store(
+            scalar,
+            format!(
+                "fsub(0, fmul(fmul(fmul(fmul({z_next}, {beta}), {}), {zkp}), {}))",
+                alphas[0],
+                load(product)
+            ),
+        );
+
+        self.comment("ft_eval0");
+        self.store(
+            ft_eval0,
+            format!(
+                "fmul(fmul(fmul(fmul(fadd({}, {gamma}), {z_next}), {}), {zkp}), {})",
+                self.eval(Column::Witness(PERMUTS - 1)),
+                alphas[0],
+                load(product)
+            ),
+        );
+        self.store(ft_eval0, format!("fsub({}, {public_eval})", load(ft_eval0)));
+        self.store(tmp, format!("fmul(fmul({}, {zkp}), {z})", alphas[0]));
+        for (i, shift) in index.shift.iter().enumerate() {
+            let term = format!(
+                "fadd(fadd({gamma}, fmul(fmul({beta}, {zeta}), {})), {})",
+                literal(shift),
+                self.eval(Column::Witness(i))
+            );
+            self.store(tmp, format!("fmul({}, {term})", load(tmp)));
+        }
+        self.store(ft_eval0, format!("fsub({}, {})", load(ft_eval0), load(tmp)));
+        let w = literal(index.w());
+        self.store(
+            tmp,
+            format!(
+                "fmul(fadd(fmul(fmul({zeta_n_minus_1}, {}), fsub({zeta}, {w})), fmul(fmul({zeta_n_minus_1}, {}), fsub({zeta}, 1))), fsub(1, {z}))",
+                alphas[1], alphas[2]
+            ),
+        );
+        self.store(
+            ft_eval0,
+            format!(
+                "fadd({}, fmul({}, finv(fmul(fsub({zeta}, {w}), fsub({zeta}, 1)))))",
+                load(ft_eval0),
+                load(tmp)
+            ),
+        );
+        self.store(
+            ft_eval0,
+            format!("fsub({}, {constant_term})", load(ft_eval0)),
+        );
+    }
+
+    /// Computes the commitment to `ft`, the public input commitment being
+    /// computed at the start.
+    fn ft_commitment(&mut self) {
+        let s = &self.slots;
+        let (ft_comm, scalar, tmp) = (s.ft_comm, s.permutation_scalar, s.tmp);
+        let (zeta_n_minus_1, zeta_to_srs_len) = (load(s.zeta_n_minus_1), load(s.zeta_to_srs_len));
+        let sigma = point_literals(&self.index.sigma_comm[PERMUTS - 1].chunks[0]);
+
+        self.comment("ft commitment: f_comm - (zeta^n - 1) * t_comm");
+        self.line(format!(
+            "ecMulAdd({}, {sigma}, {})",
+            address(ft_comm),
+            load(scalar)
+        ));
+        self.store(tmp, format!("fsub(0, {zeta_n_minus_1})"));
+        for i in 0..QUOTIENT_CHUNKS {
+            if i > 0 {
+                self.store(tmp, format!("fmul({}, {zeta_to_srs_len})", load(tmp)));
+            }
+            self.line(format!(
+                "ecMulAddCalldata({}, {}, {})",
+                address(ft_comm),
+                proof_offset(self.layout.t_comm + 2 * i),
+                load(tmp)
+            ));
+        }
+    }
+
+    fn batch_column(&mut self, col: Column) {
+        let evals = proof_offset(
+            self.layout
+                .evaluation(col)
+                .expect("the batched columns have evaluations"),
+        );
+        let comm = match col {
+            Column::Z => Some(self.layout.z_comm),
+            Column::Witness(i) => Some(self.layout.w_comm + 2 * i),
+            Column::LookupSorted(i) => Some(self.layout.sorted_comm + 2 * i),
+            Column::LookupAggreg => Some(self.layout.aggreg_comm),
+            Column::LookupRuntimeTable => self.layout.runtime_comm,
+            _ => None,
+        };
+        match comm {
+            Some(comm) => self.line(format!("batchCalldata({}, {evals})", proof_offset(comm))),
+            None => {
+                let comm = index_commitment(self.index, col)
+                    .expect("the batched columns have commitments");
+                self.line(format!(
+                    "batch({}, calldataload({evals}), calldataload(add({evals}, 0x20)))",
+                    point_literals(&comm.chunks[0])
+                ))
+            }
+        }
+    }
+
+    /// Batches the commitments and evaluations, in the order of
+    /// [`crate::verifier`].
+    fn batch(&mut self) {
+        let index = self.index;
+        let s = &self.slots;
+        let (v_power, public_comm, ft_comm, ft_eval0) =
+            (s.v_power, s.public_comm, s.ft_comm, s.ft_eval0);
+        let (table_comm, joint_combiner, tmp) = (s.table_comm, s.joint_combiner, s.tmp);
+
+        self.comment("batched opening");
+        self.store(v_power, "1");
+        self.line(format!(
+            "batch({}, {}, {}, {})",
+            load(public_comm),
+            load(public_comm + 32),
+            proof_word(self.layout.public_evals),
+            proof_word(self.layout.public_evals + 1)
+        ));
+        self.line(format!(
+            "batch({}, {}, {}, {})",
+            load(ft_comm),
+            load(ft_comm + 32),
+            load(ft_eval0),
+            proof_word(self.layout.ft_eval1)
+        ));
+
+        let mut columns: Vec<_> = [
+            Column::Z,
+            Column::Index(GateType::Generic),
+            Column::Index(GateType::Poseidon),
+            Column::Index(GateType::CompleteAdd),
+            Column::Index(GateType::VarBaseMul),
+            Column::Index(GateType::EndoMul),
+            Column::Index(GateType::EndoMulScalar),
+        ]
+        .into_iter()
+        .chain((0..COLUMNS).map(Column::Witness))
+        .chain((0..COLUMNS).map(Column::Coefficient))
+        .chain((0..PERMUTS - 1).map(Column::Permutation))
+        .chain(
+            [
+                GateType::RangeCheck0,
+                GateType::RangeCheck1,
+                GateType::ForeignFieldAdd,
+                GateType::ForeignFieldMul,
+                GateType::Xor16,
+                GateType::Rot64,
+            ]
+            .into_iter()
+            .map(Column::Index)
+            .filter(|col| index_commitment(index, *col).is_some()),
+        )
+        .collect();
+        if let Some(li) = &index.lookup_index {
+            columns.extend((0..=li.lookup_info.max_per_row).map(Column::LookupSorted));
+            columns.push(Column::LookupAggreg);
+        }
+        for col in columns {
+            self.batch_column(col);
+        }
+
+        let Some(li) = &index.lookup_index else {
+            return;
+        };
+        self.comment("lookup table commitment");
+        let joint_combiner = load(joint_combiner);
+        self.store(tmp, "1");
+        for (i, comm) in li.lookup_table.iter().enumerate() {
+            if i > 0 {
+                self.store(tmp, format!("fmul({}, {joint_combiner})", load(tmp)));
+            }
+            self.line(format!(
+                "ecMulAdd({}, {}, {})",
+                address(table_comm),
+                point_literals(&comm.chunks[0]),
+                load(tmp)
+            ));
+        }
+        if let Some(table_ids) = &li.table_ids {
+            self.line(format!(
+                "ecMulAdd({}, {}, fpow({joint_combiner}, {}))",
+                address(table_comm),
+                point_literals(&table_ids.chunks[0]),
+                li.lookup_info.max_joint_size
+            ));
+        }
+        if let Some(runtime_comm) = self.layout.runtime_comm {
+            self.line(format!(
+                "ecMulAddCalldata({}, {}, {joint_combiner})",
+                address(table_comm),
+                proof_offset(runtime_comm)
+            ));
+        }
+        let evals = self
+            .layout
+            .evaluation(Column::LookupTable)
+            .expect("lookups have a table evaluation");
+        self.line(format!(
+            "batch({}, {}, {}, {})",
+            load(table_comm),
+            load(table_comm + 32),
+            proof_word(evals),
+            proof_word(evals + 1)
+        ));
+        if li.runtime_tables_selector.is_some() {
+            self.batch_column(Column::LookupRuntimeTable);
+            self.batch_column(Column::LookupRuntimeSelector);
+        }
+        for pattern in [
+            LookupPattern::Xor,
+            LookupPattern::Lookup,
+            LookupPattern::RangeCheck,
+            LookupPattern::ForeignFieldMul,
+        ] {
+            if li.lookup_selectors[pattern].is_some() {
+                self.batch_column(Column::LookupKindIndex(pattern));
+            }
+        }
+    }
+
+    /// Checks the batched KZG opening at `zeta` and `zeta * omega`, see
+    /// [`poly_commitment::kzg::KZGProof::verify`].
+    fn opening(&mut self) {
+        let srs = self.index.srs();
+        let s = &self.slots;
+        let (batch_comm, kzg_a, kzg_b, pairing, scratch, fail) =
+            (s.batch_comm, s.kzg_a, s.kzg_b, s.pairing, s.scratch, s.fail);
+        let (zeta, zeta_omega) = (load(s.zeta), load(s.zeta_omega));
+        let (e0, e1) = (load(s.batch_eval0), load(s.batch_eval1));
+        let quotient = proof_offset(self.layout.quotient);
+        let blinding = proof_word(self.layout.blinding);
+
+        self.comment("the interpolant a + bX of the evaluations, committed with the SRS");
+        self.store(
+            kzg_b,
+            format!("fmul(fsub({e1}, {e0}), finv(fsub({zeta_omega}, {zeta})))"),
+        );
+        self.store(kzg_a, format!("fsub({e0}, fmul({}, {zeta}))", load(kzg_b)));
+        self.line(format!(
+            "ecMulAdd({}, {}, fsub(0, {}))",
+            address(batch_comm),
+            point_literals(&srs.full_srs.g[0]),
+            load(kzg_a)
+        ));
+        self.line(format!(
+            "ecMulAdd({}, {}, fsub(0, {}))",
+            address(batch_comm),
+            point_literals(&srs.full_srs.g[1]),
+            load(kzg_b)
+        ));
+        self.line(format!(
+            "ecMulAdd({}, {}, fsub(0, {blinding}))",
+            address(batch_comm),
+            point_literals(&srs.full_srs.h)
+        ));
+
+        // e(N, [1]) * e(-Q, [(X - zeta)(X - zeta * omega)]) = 1, with the
+        // divisor expanded over the G2 points of the SRS
+        self.comment("pairing check");
+        let g2 = |p: &ark_bn254::G2Affine| -> [String; 4] {
+            match p.xy() {
+                None => ["0", "0", "0", "0"].map(String::from),
+                Some((x, y)) => [
+                    literal(&x.c1),
+                    literal(&x.c0),
+                    literal(&y.c1),
+                    literal(&y.c0),
+                ],
+            }
+        };
+        let g2_points = [
+            ark_bn254::G2Affine::generator(),
+            srs.verifier_srs.g[0],
+            srs.verifier_srs.g[1],
+            srs.verifier_srs.g[2],
+        ];
+        let scalars = [
+            None,
+            Some(format!("fsub(0, fmul({zeta}, {zeta_omega}))")),
+            Some(format!("fadd({zeta}, {zeta_omega})")),
+            Some(format!("sub({}, 1)", modulus::<Fr>())),
+        ];
+        for (i, (g2_point, scalar)) in g2_points.iter().zip(scalars).enumerate() {
+            let base = pairing + 192 * i;
+            match scalar {
+                None => {
+                    self.store(base, load(batch_comm));
+                    self.store(base + 32, load(batch_comm + 32));
+                }
+                Some(scalar) => self.line(format!(
+                    "ecMulAddCalldata({}, {quotient}, {scalar})",
+                    address(base)
+                )),
+            }
+            for (j, coordinate) in g2(g2_point).iter().enumerate() {
+                self.store(base + 64 + 32 * j, coordinate);
+            }
+        }
+        self.line(format!(
+            "let success := staticcall(gas(), 0x08, {}, {}, {}, 0x20)",
+            address(pairing),
+            address(192 * g2_points.len()),
+            address(scratch)
+        ));
+        self.store(
+            0,
+            format!(
+                "and(and(success, {}), iszero({}))",
+                load(scratch),
+                load(fail)
+            ),
+        );
+        self.line("return(0, 0x20)");
+    }
+}
+
+/// Replaces the identifiers of `code` that are in `names`.
+fn substitute(code: &str, names: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(code.len());
+    let mut ident = String::new();
+    let flush = |ident: &mut String, out: &mut String| {
+        match names.iter().find(|(name, _)| name == ident) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(ident),
+        }
+        ident.clear();
+    };
+    for c in code.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            ident.push(c);
+        } else {
+            flush(&mut ident, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut ident, &mut out);
+    out
+}
+
+/// The modulus of `F`, as a Yul literal.
+fn modulus<F: PrimeField>() -> String {
+    let n: BigUint = F::MODULUS.into();
+    format!("{n:#x}")
+}
+
+/// Generates the body of the `verify` function of the verifier for `index`,
+/// as Yul statements.
+///
+/// # Errors
+///
+/// Will give error if the verifier index is not supported, see
+/// [`super`].
+pub fn verifier_assembly(index: &BN254VerifierIndex) -> Result<String, SolidityError> {
+    check_index(index)?;
+    let mut mem = Memory {
+        next: 0x80,
+        names: vec![],
+    };
+    let slots = Slots::new(&mut mem);
+    let mut gen = Generator {
+        index,
+        layout: ProofLayout::new(index),
+        mem,
+        slots,
+        code: String::new(),
+    };
+
+    let layout = &gen.layout;
+    let s = &gen.slots;
+    let public_comm = s.public_comm;
+    let (size, ft_eval1, quotient, blinding) = (
+        layout.size,
+        layout.ft_eval1,
+        layout.quotient,
+        layout.blinding,
+    );
+
+    gen.comment("the public input, encoded after the proof");
+    gen.line(format!(
+        "let publicInput := add(4, calldataload({}))",
+        proof_offset(size)
+    ));
+    gen.line(format!(
+        "if iszero(eq(calldataload(publicInput), {})) {{",
+        index.public
+    ));
+    gen.line("    mstore(0, 0)");
+    gen.line("    return(0, 0x20)");
+    gen.line("}");
+    gen.line("publicInput := add(publicInput, 0x20)");
+
+    gen.comment("the scalars must be canonical");
+    gen.line(format!("checkScalars(publicInput, {})", index.public));
+    gen.line(format!(
+        "checkScalars({}, {})",
+        proof_offset(ft_eval1),
+        quotient - ft_eval1
+    ));
+    gen.line(format!("checkScalars({}, 1)", proof_offset(blinding)));
+
+    gen.comment("commitment to the negated public input, blinded by h");
+    let srs = index.srs();
+    let [h_x, h_y] = point_words(&srs.full_srs.h);
+    gen.store(
+        public_comm,
+        literal(&ark_bn254::Fq::from_be_bytes_mod_order(&h_x)),
+    );
+    gen.store(
+        public_comm + 32,
+        literal(&ark_bn254::Fq::from_be_bytes_mod_order(&h_y)),
+    );
+    let lagrange_basis = srs.full_srs.get_lagrange_basis(index.domain);
+    for (i, comm) in lagrange_basis.iter().take(index.public).enumerate() {
+        gen.line(format!(
+            "ecMulAdd({}, {}, fsub(0, calldataload(add(publicInput, {}))))",
+            address(public_comm),
+            point_literals(&comm.chunks[0]),
+            address(32 * i)
+        ));
+    }
+
+    gen.transcript();
+    gen.powers();
+    gen.linearization()?;
+    gen.permutation();
+    gen.ft_commitment();
+    gen.batch();
+    gen.opening();
+
+    let s = &gen.slots;
+    let scratch: Vec<_> = (0..6)
+        .map(|i| (format!("S{i}"), address(s.scratch + 32 * i)))
+        .collect();
+    let mut names = vec![
+        ("R", modulus::<Fr>()),
+        ("R_MINUS_2", literal(&-Fr::from(2u64))),
+        ("ENDO_R", literal(&BN254::endos().1)),
+        ("FAIL", address(s.fail)),
+        ("SPONGE", address(s.sponge)),
+        ("SPONGE_INPUT", address(s.sponge + 32)),
+        ("BATCH_COMM", address(s.batch_comm)),
+        ("BATCH_EVAL0", address(s.batch_eval0)),
+        ("BATCH_EVAL1", address(s.batch_eval1)),
+        ("V_POWER", address(s.v_power)),
+        ("V", address(s.v)),
+    ];
+    names.extend(
+        scratch
+            .iter()
+            .map(|(name, addr)| (name.as_str(), addr.clone())),
+    );
+    let helpers = substitute(HELPERS, &names);
+
+    let mut out = String::new();
+    writeln!(out, "// memory layout").unwrap();
+    for (addr, name) in &gen.mem.names {
+        writeln!(out, "// {}: {name}", address(*addr)).unwrap();
+    }
+    writeln!(out, "mstore(0x40, {})", address(gen.mem.next)).unwrap();
+    out.push_str(&helpers);
+    out.push_str(&gen.code);
+    Ok(out)
+}
+
+/// Generates a Solidity contract that verifies proofs for `index`.
+///
+/// The proofs must be created with [`KeccakFqSponge`] and [`KeccakFrSponge`],
+/// and passed to the contract with [`super::calldata`].
+///
+/// # Errors
+///
+/// Will give error if the verifier index is not supported, see
+/// [`super`].
+pub fn verifier_contract(index: &BN254VerifierIndex) -> Result<String, SolidityError> {
+    let assembly = verifier_assembly(index)?;
+    let layout = ProofLayout::new(index);
+    let mut out = String::new();
+    writeln!(out, "// SPDX-License-Identifier: Apache-2.0").unwrap();
+    writeln!(out, "// Generated by kimchi, do not edit.").unwrap();
+    writeln!(out, "pragma solidity ^0.8.0;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "/// @notice Verifies kimchi proofs over BN254 with KZG commitments."
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "/// Domain size: {}, public inputs: {}, lookups: {}.",
+        index.domain.size,
+        index.public,
+        index.lookup_index.is_some()
+    )
+    .unwrap();
+    writeln!(out, "contract KimchiVerifier {{").unwrap();
+    writeln!(out, "    /// @notice `{}`", verify_signature(index)).unwrap();
+    writeln!(
+        out,
+        "    function verify(uint256[{}] calldata, uint256[] calldata) external view returns (bool valid) {{",
+        layout.size
+    )
+    .unwrap();
+    writeln!(out, "        assembly {{").unwrap();
+    for line in assembly.lines() {
+        if line.is_empty() {
+            writeln!(out).unwrap();
+        } else {
+            writeln!(out, "            {line}").unwrap();
+        }
+    }
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    Ok(out)
+}
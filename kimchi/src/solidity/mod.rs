@@ -0,0 +1,415 @@
+//! Solidity verifiers for kimchi proofs over BN254 with KZG commitments.
+//!
+//! [`verifier_contract`] turns a [`VerifierIndex`] into a self-contained
+//! Solidity contract with a single function
+//!
+//! ```solidity
+//! function verify(uint256[N] calldata proof, uint256[] calldata publicInput)
+//!     external view returns (bool valid);
+//! ```
+//!
+//! which runs the same checks as [`crate::verifier::verify`]: it replays the
+//! Fiat-Shamir transcript, evaluates the linearization of the circuit at
+//! $\zeta$, computes the commitment to $ft$ from the permutation argument and
+//! the quotient, combines the lookup table if the circuit uses lookups, and
+//! checks the batched KZG opening with the `ecPairing` precompile.
+//!
+//! The contract hashes with `keccak256` instead of Poseidon, so proofs must be
+//! created with the sponges of the [`sponge`] module, and encoded with
+//! [`calldata`]. The generated code is tested against [`crate::verifier`]
+//! with a small Yul interpreter, which also estimates the gas used, without a
+//! Solidity toolchain. Compiling the whole contract needs `solc`, see the
+//! ignored `test_solidity_contract_compiles` test.
+//!
+//! Only circuits whose polynomials fit in a single chunk, i.e. whose domain is
+//! not larger than the SRS, and that do not verify previous recursion
+//! challenges are supported.
+
+pub mod sponge;
+#[cfg(test)]
+mod yul;
+
+mod codegen;
+
+use crate::{
+    circuits::{berkeley_columns::Column, gate::GateType, wires::*},
+    error::SolidityError,
+    proof::ProverProof,
+    verifier_index::VerifierIndex,
+};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_poly::EvaluationDomain;
+use poly_commitment::{commitment::PolyComm, kzg::KZGProof};
+use sha3::{Digest, Keccak256};
+use sponge::to_word;
+
+pub use codegen::{verifier_assembly, verifier_contract};
+
+/// The curve supported by the generated verifiers.
+pub type BN254 = ark_bn254::G1Affine;
+
+/// The scalar field of [`BN254`].
+pub type Fr = ark_bn254::Fr;
+
+/// The opening proof supported by the generated verifiers.
+pub type BN254KZGProof = KZGProof<ark_bn254::Bn254>;
+
+/// A verifier index for which a Solidity verifier can be generated.
+pub type BN254VerifierIndex = VerifierIndex<BN254, BN254KZGProof>;
+
+/// The number of chunks of the quotient commitment.
+const QUOTIENT_CHUNKS: usize = 7;
+
+/// The position, in 32-byte words, of each part of an encoded proof.
+///
+/// Points take two words (`x` and `y`, with `(0, 0)` for the point at
+/// infinity) and scalars one. The evaluations directly follow `ft_eval1` and
+/// the public input evaluations, in the order in which they are absorbed by
+/// the Fr-sponge, so that the verifier can absorb them in a single loop.
+pub(crate) struct ProofLayout {
+    pub w_comm: usize,
+    pub z_comm: usize,
+    pub t_comm: usize,
+    pub sorted_comm: usize,
+    pub aggreg_comm: usize,
+    pub runtime_comm: Option<usize>,
+    pub ft_eval1: usize,
+    pub public_evals: usize,
+    pub evals: usize,
+    pub quotient: usize,
+    pub blinding: usize,
+    pub size: usize,
+    /// The columns whose evaluations are in the proof, in absorption order.
+    pub columns: Vec<Column>,
+}
+
+impl ProofLayout {
+    pub fn new(index: &BN254VerifierIndex) -> Self {
+        let (num_sorted, lookup_comms, runtime) = match &index.lookup_index {
+            None => (0, 0, false),
+            Some(li) => {
+                let runtime = li.runtime_tables_selector.is_some();
+                let num_sorted = li.lookup_info.max_per_row + 1;
+                (num_sorted, num_sorted + 1 + usize::from(runtime), runtime)
+            }
+        };
+        let columns: Vec<_> = sponge::absorbed_columns()
+            .filter(|col| has_evaluation(index, *col))
+            .collect();
+
+        let w_comm = 0;
+        let z_comm = w_comm + 2 * COLUMNS;
+        let t_comm = z_comm + 2;
+        let sorted_comm = t_comm + 2 * QUOTIENT_CHUNKS;
+        let aggreg_comm = sorted_comm + 2 * num_sorted;
+        let runtime_comm = runtime.then_some(aggreg_comm + 2);
+        let ft_eval1 = t_comm + 2 * QUOTIENT_CHUNKS + 2 * lookup_comms;
+        let public_evals = ft_eval1 + 1;
+        let evals = public_evals + 2;
+        let quotient = evals + 2 * columns.len();
+        let blinding = quotient + 2;
+        ProofLayout {
+            w_comm,
+            z_comm,
+            t_comm,
+            sorted_comm,
+            aggreg_comm,
+            runtime_comm,
+            ft_eval1,
+            public_evals,
+            evals,
+            quotient,
+            blinding,
+            size: blinding + 1,
+            columns,
+        }
+    }
+
+    /// The position of the evaluation of `col` at $\zeta$, the evaluation at
+    /// $\zeta\omega$ being the next word.
+    pub fn evaluation(&self, col: Column) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| *c == col)
+            .map(|i| self.evals + 2 * i)
+    }
+}
+
+/// Whether proofs for `index` contain an evaluation of `col`.
+fn has_evaluation(index: &BN254VerifierIndex, col: Column) -> bool {
+    match col {
+        Column::Z | Column::Witness(_) | Column::Coefficient(_) | Column::Permutation(_) => true,
+        Column::LookupAggreg | Column::LookupTable => index.lookup_index.is_some(),
+        Column::LookupSorted(i) => index
+            .lookup_index
+            .as_ref()
+            .map_or(false, |li| i <= li.lookup_info.max_per_row),
+        Column::LookupRuntimeTable => index
+            .lookup_index
+            .as_ref()
+            .map_or(false, |li| li.runtime_tables_selector.is_some()),
+        _ => index_commitment(index, col).is_some(),
+    }
+}
+
+/// The commitment to `col` if it is part of the verifier index.
+pub(crate) fn index_commitment(
+    index: &BN254VerifierIndex,
+    col: Column,
+) -> Option<&PolyComm<BN254>> {
+    match col {
+        Column::Coefficient(i) => Some(&index.coefficients_comm[i]),
+        Column::Permutation(i) => Some(&index.sigma_comm[i]),
+        Column::LookupKindIndex(pattern) => {
+            index.lookup_index.as_ref()?.lookup_selectors[pattern].as_ref()
+        }
+        Column::LookupRuntimeSelector => index
+            .lookup_index
+            .as_ref()?
+            .runtime_tables_selector
+            .as_ref(),
+        Column::Index(gate) => match gate {
+            GateType::Generic => Some(&index.generic_comm),
+            GateType::Poseidon => Some(&index.psm_comm),
+            GateType::CompleteAdd => Some(&index.complete_add_comm),
+            GateType::VarBaseMul => Some(&index.mul_comm),
+            GateType::EndoMul => Some(&index.emul_comm),
+            GateType::EndoMulScalar => Some(&index.endomul_scalar_comm),
+            GateType::RangeCheck0 => index.range_check0_comm.as_ref(),
+            GateType::RangeCheck1 => index.range_check1_comm.as_ref(),
+            GateType::ForeignFieldAdd => index.foreign_field_add_comm.as_ref(),
+            GateType::ForeignFieldMul => index.foreign_field_mul_comm.as_ref(),
+            GateType::Xor16 => index.xor_comm.as_ref(),
+            GateType::Rot64 => index.rot_comm.as_ref(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Checks that the generated verifier can handle proofs for `index`.
+pub(crate) fn check_index(index: &BN254VerifierIndex) -> Result<(), SolidityError> {
+    if index.domain.size() > index.max_poly_size {
+        return Err(SolidityError::ChunkedCommitments(
+            index.domain.size(),
+            index.max_poly_size,
+        ));
+    }
+    if index.prev_challenges > 0 {
+        return Err(SolidityError::PreviousChallenges(index.prev_challenges));
+    }
+    if index.srs().verifier_srs.g.len() < 3 {
+        return Err(SolidityError::VerifierSRSTooSmall(
+            index.srs().verifier_srs.g.len(),
+        ));
+    }
+
+    let single_chunk = |name, comm: &PolyComm<BN254>| {
+        if comm.len() == 1 {
+            Ok(())
+        } else {
+            Err(SolidityError::IncorrectCommitmentLength(name, comm.len()))
+        }
+    };
+    let columns = [
+        Column::Index(GateType::Generic),
+        Column::Index(GateType::Poseidon),
+        Column::Index(GateType::CompleteAdd),
+        Column::Index(GateType::VarBaseMul),
+        Column::Index(GateType::EndoMul),
+        Column::Index(GateType::EndoMulScalar),
+    ]
+    .into_iter()
+    .chain(sponge::absorbed_columns())
+    .chain(std::iter::once(Column::Permutation(PERMUTS - 1)));
+    for col in columns {
+        if let Some(comm) = index_commitment(index, col) {
+            single_chunk("the verifier index", comm)?;
+        }
+    }
+    if let Some(li) = &index.lookup_index {
+        for comm in li.lookup_table.iter().chain(&li.table_ids) {
+            single_chunk("the lookup table", comm)?;
+        }
+    }
+    Ok(())
+}
+
+/// Encodes a point as its affine coordinates, or `(0, 0)` for the point at
+/// infinity, like the precompiles of the EVM.
+pub(crate) fn point_words<G: AffineRepr>(p: &G) -> [[u8; 32]; 2]
+where
+    G::BaseField: PrimeField,
+{
+    match p.xy() {
+        None => [[0u8; 32]; 2],
+        Some((x, y)) => [to_word(x), to_word(y)],
+    }
+}
+
+/// Encodes `proof` as the first argument of the generated `verify` function.
+///
+/// # Errors
+///
+/// Will give error if the proof does not have the shape expected by `index`.
+pub fn encode_proof(
+    index: &BN254VerifierIndex,
+    proof: &ProverProof<BN254, BN254KZGProof>,
+) -> Result<Vec<[u8; 32]>, SolidityError> {
+    check_index(index)?;
+    let layout = ProofLayout::new(index);
+
+    if !proof.prev_challenges.is_empty() {
+        return Err(SolidityError::ProofShape("unexpected previous challenges"));
+    }
+
+    let mut words = Vec::with_capacity(layout.size);
+    fn push_comm(
+        words: &mut Vec<[u8; 32]>,
+        comm: &PolyComm<BN254>,
+        chunks: usize,
+    ) -> Result<(), SolidityError> {
+        if comm.len() != chunks {
+            return Err(SolidityError::ProofShape("a commitment has the wrong size"));
+        }
+        comm.chunks
+            .iter()
+            .for_each(|chunk| words.extend(point_words(chunk)));
+        Ok(())
+    }
+
+    let commitments = &proof.commitments;
+    for comm in &commitments.w_comm {
+        push_comm(&mut words, comm, 1)?;
+    }
+    push_comm(&mut words, &commitments.z_comm, 1)?;
+    push_comm(&mut words, &commitments.t_comm, QUOTIENT_CHUNKS)?;
+    match (&index.lookup_index, &commitments.lookup) {
+        (None, None) => (),
+        (Some(li), Some(lookup)) => {
+            if lookup.sorted.len() != li.lookup_info.max_per_row + 1 {
+                return Err(SolidityError::ProofShape(
+                    "wrong number of sorted commitments",
+                ));
+            }
+            for comm in &lookup.sorted {
+                push_comm(&mut words, comm, 1)?;
+            }
+            push_comm(&mut words, &lookup.aggreg, 1)?;
+            match (&li.runtime_tables_selector, &lookup.runtime) {
+                (None, None) => (),
+                (Some(_), Some(runtime)) => push_comm(&mut words, runtime, 1)?,
+                _ => return Err(SolidityError::ProofShape("unexpected runtime table")),
+            }
+        }
+        _ => return Err(SolidityError::ProofShape("unexpected lookup commitments")),
+    }
+
+    let single_eval = |evals: &[Fr]| match evals {
+        [eval] => Ok(to_word(eval)),
+        _ => Err(SolidityError::ProofShape(
+            "an evaluation has the wrong size",
+        )),
+    };
+    words.push(to_word(&proof.ft_eval1));
+    let public = proof
+        .evals
+        .public
+        .as_ref()
+        .ok_or(SolidityError::ProofShape(
+            "missing public input evaluations",
+        ))?;
+    words.push(single_eval(&public.zeta)?);
+    words.push(single_eval(&public.zeta_omega)?);
+    for col in sponge::absorbed_columns() {
+        match (proof.evals.get_column(col), layout.evaluation(col)) {
+            (None, None) => (),
+            (Some(evals), Some(_)) => {
+                words.push(single_eval(&evals.zeta)?);
+                words.push(single_eval(&evals.zeta_omega)?);
+            }
+            _ => return Err(SolidityError::ProofShape("unexpected evaluations")),
+        }
+    }
+
+    words.extend(point_words(&proof.proof.quotient));
+    words.push(to_word(&proof.proof.blinding));
+
+    assert_eq!(words.len(), layout.size);
+    Ok(words)
+}
+
+/// The signature of the `verify` function of the contract for `index`.
+pub(crate) fn verify_signature(index: &BN254VerifierIndex) -> String {
+    format!(
+        "verify(uint256[{}],uint256[])",
+        ProofLayout::new(index).size
+    )
+}
+
+/// Encodes a call to the `verify` function of the contract generated for
+/// `index`, to check `proof` against `public_input`.
+///
+/// # Errors
+///
+/// Will give error if the proof does not have the shape expected by `index`,
+/// or if the public input has the wrong size.
+pub fn calldata(
+    index: &BN254VerifierIndex,
+    proof: &ProverProof<BN254, BN254KZGProof>,
+    public_input: &[Fr],
+) -> Result<Vec<u8>, SolidityError> {
+    if public_input.len() != index.public {
+        return Err(SolidityError::IncorrectPublicInputLength(
+            index.public,
+            public_input.len(),
+        ));
+    }
+    let proof = encode_proof(index, proof)?;
+
+    let mut data = Keccak256::digest(verify_signature(index))[..4].to_vec();
+    let word = |n: usize| to_word(&Fr::from(n as u64));
+    proof.iter().for_each(|w| data.extend(w));
+    // the public input is a dynamic array: its head is its offset
+    data.extend(word(32 * (proof.len() + 1)));
+    data.extend(word(public_input.len()));
+    public_input.iter().for_each(|x| data.extend(to_word(x)));
+    Ok(data)
+}
+
+/// Runs the verifier generated for `index` on `calldata` with the
+/// [`yul`] interpreter and at most `gas_limit` gas, and returns whether the
+/// proof was accepted, with the (estimated) gas used.
+#[cfg(test)]
+pub(crate) fn execute(
+    index: &BN254VerifierIndex,
+    calldata: &[u8],
+    gas_limit: u64,
+) -> Result<(bool, u64), String> {
+    let assembly = verifier_assembly(index).map_err(|e| e.to_string())?;
+    yul::execute(&assembly, calldata, gas_limit)
+        .map(|execution| (accepted(&execution.output), execution.gas_used))
+}
+
+/// Calls the `verify` function of `contract` with `calldata` with the
+/// [`yul`] interpreter, in a transaction with at most `gas_limit` gas, and
+/// returns whether the proof was accepted, with the (estimated) gas used by
+/// the transaction.
+#[cfg(test)]
+pub(crate) fn execute_contract(
+    contract: &str,
+    calldata: &[u8],
+    gas_limit: u64,
+) -> Result<(bool, u64), String> {
+    yul::execute_contract(contract, calldata, gas_limit)
+        .map(|execution| (accepted(&execution.output), execution.gas_used))
+}
+
+/// Whether `output` is the encoding of `true`.
+#[cfg(test)]
+fn accepted(output: &[u8]) -> bool {
+    let mut accepted = [0u8; 32];
+    accepted[31] = 1;
+    output == accepted
+}
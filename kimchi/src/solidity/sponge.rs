@@ -0,0 +1,199 @@
+//! Keccak-based sponges for the Fiat-Shamir transformation.
+//!
+//! Poseidon is expensive to evaluate on the EVM, whereas `keccak256` is a
+//! cheap opcode. The sponges in this module replace the Poseidon sponges of
+//! [`mina_poseidon::sponge`] and [`crate::plonk_sponge`] by a simple
+//! duplex construction over `keccak256` that the generated verifier
+//! reproduces word for word:
+//!
+//! - the state is a single 32-byte word, initially zero;
+//! - absorbing a field element `x` sets `state = keccak256(state || x)`,
+//!   where `x` is encoded as a 32-byte big-endian integer;
+//! - squeezing sets `state = keccak256(state)` and returns the new state.
+//!
+//! Challenges are the low 128 bits of a squeeze, and digests are a squeeze
+//! reduced modulo the order of the target field. Points are absorbed as
+//! their two affine coordinates, the point at infinity being absorbed as
+//! `(0, 0)`.
+
+use crate::{circuits::berkeley_columns::Column, plonk_sponge::FrSponge, proof::*};
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ff::{BigInteger, PrimeField};
+use mina_poseidon::{
+    poseidon::ArithmeticSpongeParams,
+    sponge::{FqSponge, ScalarChallenge},
+};
+use sha3::{Digest, Keccak256};
+use std::marker::PhantomData;
+
+/// The number of bytes of a challenge.
+const CHALLENGE_LENGTH_IN_BYTES: usize = 16;
+
+/// Encodes a field element as a 32-byte big-endian word.
+pub fn to_word<F: PrimeField>(x: &F) -> [u8; 32] {
+    let bytes = x.into_bigint().to_bytes_be();
+    assert!(bytes.len() <= 32, "field elements must fit in a word");
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+/// The state shared by [`KeccakFqSponge`] and [`KeccakFrSponge`].
+#[derive(Clone, Debug, Default)]
+struct KeccakState([u8; 32]);
+
+impl KeccakState {
+    fn absorb(&mut self, word: &[u8; 32]) {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.0);
+        hasher.update(word);
+        self.0 = hasher.finalize().into();
+    }
+
+    fn squeeze(&mut self) -> [u8; 32] {
+        self.0 = Keccak256::digest(self.0).into();
+        self.0
+    }
+
+    fn squeeze_challenge<F: PrimeField>(&mut self) -> F {
+        let word = self.squeeze();
+        F::from_be_bytes_mod_order(&word[32 - CHALLENGE_LENGTH_IN_BYTES..])
+    }
+}
+
+/// A keccak sponge operating on the base field of the curve `P`.
+#[derive(Clone, Debug)]
+pub struct KeccakFqSponge<P> {
+    state: KeccakState,
+    _curve: PhantomData<P>,
+}
+
+impl<P: SWCurveConfig> FqSponge<P::BaseField, Affine<P>, P::ScalarField> for KeccakFqSponge<P>
+where
+    P::BaseField: PrimeField,
+{
+    fn new(_params: &'static ArithmeticSpongeParams<P::BaseField>) -> Self {
+        KeccakFqSponge {
+            state: KeccakState::default(),
+            _curve: PhantomData,
+        }
+    }
+
+    fn absorb_fq(&mut self, x: &[P::BaseField]) {
+        x.iter().for_each(|x| self.state.absorb(&to_word(x)));
+    }
+
+    fn absorb_g(&mut self, g: &[Affine<P>]) {
+        for g in g {
+            if g.infinity {
+                self.state.absorb(&[0u8; 32]);
+                self.state.absorb(&[0u8; 32]);
+            } else {
+                self.state.absorb(&to_word(&g.x));
+                self.state.absorb(&to_word(&g.y));
+            }
+        }
+    }
+
+    fn absorb_fr(&mut self, x: &[P::ScalarField]) {
+        x.iter().for_each(|x| self.state.absorb(&to_word(x)));
+    }
+
+    fn challenge_fq(&mut self) -> P::BaseField {
+        self.state.squeeze_challenge()
+    }
+
+    fn challenge(&mut self) -> P::ScalarField {
+        self.state.squeeze_challenge()
+    }
+
+    fn digest_fq(mut self) -> P::BaseField {
+        P::BaseField::from_be_bytes_mod_order(&self.state.squeeze())
+    }
+
+    fn digest(mut self) -> P::ScalarField {
+        P::ScalarField::from_be_bytes_mod_order(&self.state.squeeze())
+    }
+}
+
+/// A keccak sponge operating on the scalar field `F`.
+#[derive(Clone, Debug)]
+pub struct KeccakFrSponge<F> {
+    state: KeccakState,
+    _field: PhantomData<F>,
+}
+
+/// The columns whose evaluations are absorbed by [`FrSponge::absorb_evaluations`],
+/// in order. Columns without an evaluation in the proof are skipped.
+pub(crate) fn absorbed_columns() -> impl Iterator<Item = Column> {
+    use crate::circuits::{gate::GateType, lookup::lookups::LookupPattern, wires::*};
+
+    [
+        Column::Z,
+        Column::Index(GateType::Generic),
+        Column::Index(GateType::Poseidon),
+        Column::Index(GateType::CompleteAdd),
+        Column::Index(GateType::VarBaseMul),
+        Column::Index(GateType::EndoMul),
+        Column::Index(GateType::EndoMulScalar),
+    ]
+    .into_iter()
+    .chain((0..COLUMNS).map(Column::Witness))
+    .chain((0..COLUMNS).map(Column::Coefficient))
+    .chain((0..PERMUTS - 1).map(Column::Permutation))
+    .chain([
+        Column::Index(GateType::RangeCheck0),
+        Column::Index(GateType::RangeCheck1),
+        Column::Index(GateType::ForeignFieldAdd),
+        Column::Index(GateType::ForeignFieldMul),
+        Column::Index(GateType::Xor16),
+        Column::Index(GateType::Rot64),
+        Column::LookupAggreg,
+        Column::LookupTable,
+    ])
+    .chain((0..5).map(Column::LookupSorted))
+    .chain([
+        Column::LookupRuntimeTable,
+        Column::LookupRuntimeSelector,
+        Column::LookupKindIndex(LookupPattern::Xor),
+        Column::LookupKindIndex(LookupPattern::Lookup),
+        Column::LookupKindIndex(LookupPattern::RangeCheck),
+        Column::LookupKindIndex(LookupPattern::ForeignFieldMul),
+    ])
+}
+
+impl<F: PrimeField> FrSponge<F> for KeccakFrSponge<F> {
+    fn new(_params: &'static ArithmeticSpongeParams<F>) -> Self {
+        KeccakFrSponge {
+            state: KeccakState::default(),
+            _field: PhantomData,
+        }
+    }
+
+    fn absorb(&mut self, x: &F) {
+        self.state.absorb(&to_word(x));
+    }
+
+    fn absorb_multiple(&mut self, x: &[F]) {
+        x.iter().for_each(|x| self.absorb(x));
+    }
+
+    fn challenge(&mut self) -> ScalarChallenge<F> {
+        ScalarChallenge(self.state.squeeze_challenge())
+    }
+
+    fn digest(mut self) -> F {
+        F::from_be_bytes_mod_order(&self.state.squeeze())
+    }
+
+    // Same order as `DefaultFrSponge`: all evaluations of a polynomial are
+    // absorbed at the same time.
+    fn absorb_evaluations(&mut self, e: &ProofEvaluations<PointEvaluations<Vec<F>>>) {
+        for col in absorbed_columns() {
+            if let Some(evals) = e.get_column(col) {
+                self.absorb_multiple(&evals.zeta);
+                self.absorb_multiple(&evals.zeta_omega);
+            }
+        }
+    }
+}
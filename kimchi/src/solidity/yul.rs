@@ -0,0 +1,987 @@
+//! A small interpreter for the subset of [Yul](https://docs.soliditylang.org/en/latest/yul.html)
+//! emitted by the verifier generator.
+//!
+//! It follows the EVM semantics of the builtins it supports (256-bit
+//! wrapping arithmetic, byte-addressed memory, calldata, `keccak256`) and of
+//! the precompiles used by the verifier: `modexp` (0x05), `ecAdd` (0x06),
+//! `ecMul` (0x07) and `ecPairing` (0x08) over BN254.
+//!
+//! Gas is metered with the costs of the Shanghai schedule for the builtins,
+//! the memory expansion and the precompiles. The interpreter does not see the
+//! bytecode `solc` would emit, so the stack manipulations and jumps are
+//! charged per variable access, assignment, call and branch, as unoptimized
+//! code would pay them: the gas used is an estimate, not the exact cost of a
+//! deployed contract.
+//!
+//! This lets the generated verifiers be checked against the Rust verifier
+//! without a Solidity compiler or an Ethereum node. It is only built for the
+//! tests.
+
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use num_bigint::BigUint;
+use num_traits::One;
+use once_cell::sync::Lazy;
+use sha3::{Digest, Keccak256};
+use std::{collections::HashMap, rc::Rc};
+
+/// The largest amount of memory a program can touch, in bytes.
+const MAX_MEMORY: usize = 1 << 24;
+
+/// The gas paid by every transaction.
+const TRANSACTION_GAS: u64 = 21_000;
+
+/// The gas of pushing a literal or duplicating a variable (`PUSH`, `DUP`).
+const STACK_GAS: u64 = 3;
+
+/// The gas of assigning a variable (`SWAP` and `POP`).
+const ASSIGN_GAS: u64 = 5;
+
+/// The gas of a conditional branch (`JUMPI` and `JUMPDEST`).
+const BRANCH_GAS: u64 = 11;
+
+/// The gas of calling a function and returning from it (pushing the return
+/// address, two `JUMP`s and two `JUMPDEST`s).
+const CALL_GAS: u64 = 21;
+
+static WORD_MODULUS: Lazy<BigUint> = Lazy::new(|| BigUint::one() << 256);
+
+static BASE_FIELD_MODULUS: Lazy<BigUint> = Lazy::new(|| Fq::MODULUS.into());
+
+//
+// Parsing
+//
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(BigUint),
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Comma,
+    Assign,
+    Arrow,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '{' | '}' | '(' | ')' | ',' => {
+                tokens.push(match c {
+                    '{' => Token::LBrace,
+                    '}' => Token::RBrace,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    _ => Token::Comma,
+                });
+                i += 1;
+            }
+            ':' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Assign);
+                i += 2;
+            }
+            '-' if chars.get(i + 1) == Some(&'>') => {
+                tokens.push(Token::Arrow);
+                i += 2;
+            }
+            _ if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let number = match literal.strip_prefix("0x") {
+                    Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+                    None => BigUint::parse_bytes(literal.as_bytes(), 10),
+                }
+                .ok_or_else(|| format!("invalid number literal {literal}"))?;
+                tokens.push(Token::Number(number));
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '_' | '$' | '.'))
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => return Err(format!("unexpected character {c:?}")),
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(BigUint),
+    Ident(String),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug)]
+struct Function {
+    params: Vec<String>,
+    returns: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+#[derive(Debug)]
+enum Stmt {
+    Block(Vec<Stmt>),
+    Function(String, Rc<Function>),
+    Let(Vec<String>, Option<Expr>),
+    Assign(Vec<String>, Expr),
+    Expr(Expr),
+    If(Expr, Vec<Stmt>),
+    Switch(Expr, Vec<(Option<BigUint>, Vec<Stmt>)>),
+    For(Vec<Stmt>, Expr, Vec<Stmt>, Vec<Stmt>),
+    Break,
+    Continue,
+    Leave,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or("unexpected end of input")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        let token = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(format!("expected {expected:?}, got {token:?}"))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.next()? {
+            Token::Ident(name) => Ok(name),
+            token => Err(format!("expected an identifier, got {token:?}")),
+        }
+    }
+
+    fn idents(&mut self, end: &Token) -> Result<Vec<String>, String> {
+        let mut names = vec![];
+        while self.peek() != Some(end) {
+            if !names.is_empty() {
+                self.expect(Token::Comma)?;
+            }
+            names.push(self.ident()?);
+        }
+        Ok(names)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        self.expect(Token::LBrace)?;
+        let mut stmts = vec![];
+        while self.peek() != Some(&Token::RBrace) {
+            stmts.push(self.stmt()?);
+        }
+        self.pos += 1;
+        Ok(stmts)
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        match self.next()? {
+            Token::Number(n) => Ok(Expr::Literal(n)),
+            Token::Ident(name) if name == "true" => Ok(Expr::Literal(BigUint::one())),
+            Token::Ident(name) if name == "false" => Ok(Expr::Literal(BigUint::zero())),
+            Token::Ident(name) => {
+                if self.peek() != Some(&Token::LParen) {
+                    return Ok(Expr::Ident(name));
+                }
+                self.pos += 1;
+                let mut args = vec![];
+                while self.peek() != Some(&Token::RParen) {
+                    if !args.is_empty() {
+                        self.expect(Token::Comma)?;
+                    }
+                    args.push(self.expr()?);
+                }
+                self.pos += 1;
+                Ok(Expr::Call(name, args))
+            }
+            token => Err(format!("expected an expression, got {token:?}")),
+        }
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, String> {
+        let keyword = match self.peek() {
+            Some(Token::LBrace) => return Ok(Stmt::Block(self.block()?)),
+            Some(Token::Ident(name)) => name.clone(),
+            token => return Err(format!("expected a statement, got {token:?}")),
+        };
+        match keyword.as_str() {
+            "function" => {
+                self.pos += 1;
+                let name = self.ident()?;
+                self.expect(Token::LParen)?;
+                let params = self.idents(&Token::RParen)?;
+                self.expect(Token::RParen)?;
+                let mut returns = vec![];
+                if self.peek() == Some(&Token::Arrow) {
+                    self.pos += 1;
+                    returns.push(self.ident()?);
+                    while self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                        returns.push(self.ident()?);
+                    }
+                }
+                let body = self.block()?;
+                Ok(Stmt::Function(
+                    name,
+                    Rc::new(Function {
+                        params,
+                        returns,
+                        body,
+                    }),
+                ))
+            }
+            "let" => {
+                self.pos += 1;
+                let mut names = vec![self.ident()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    names.push(self.ident()?);
+                }
+                let value = if self.peek() == Some(&Token::Assign) {
+                    self.pos += 1;
+                    Some(self.expr()?)
+                } else {
+                    None
+                };
+                Ok(Stmt::Let(names, value))
+            }
+            "if" => {
+                self.pos += 1;
+                let cond = self.expr()?;
+                Ok(Stmt::If(cond, self.block()?))
+            }
+            "switch" => {
+                self.pos += 1;
+                let value = self.expr()?;
+                let mut cases = vec![];
+                loop {
+                    match self.peek() {
+                        Some(Token::Ident(k)) if k == "case" => {
+                            self.pos += 1;
+                            let literal = match self.expr()? {
+                                Expr::Literal(n) => n,
+                                e => return Err(format!("expected a literal, got {e:?}")),
+                            };
+                            cases.push((Some(literal), self.block()?));
+                        }
+                        Some(Token::Ident(k)) if k == "default" => {
+                            self.pos += 1;
+                            cases.push((None, self.block()?));
+                        }
+                        _ => break,
+                    }
+                }
+                Ok(Stmt::Switch(value, cases))
+            }
+            "for" => {
+                self.pos += 1;
+                let init = self.block()?;
+                let cond = self.expr()?;
+                let post = self.block()?;
+                let body = self.block()?;
+                Ok(Stmt::For(init, cond, post, body))
+            }
+            "break" | "continue" | "leave" => {
+                self.pos += 1;
+                Ok(match keyword.as_str() {
+                    "break" => Stmt::Break,
+                    "continue" => Stmt::Continue,
+                    _ => Stmt::Leave,
+                })
+            }
+            _ => match self.tokens.get(self.pos + 1) {
+                Some(Token::Assign) | Some(Token::Comma) => {
+                    let mut names = vec![self.ident()?];
+                    while self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                        names.push(self.ident()?);
+                    }
+                    self.expect(Token::Assign)?;
+                    Ok(Stmt::Assign(names, self.expr()?))
+                }
+                _ => Ok(Stmt::Expr(self.expr()?)),
+            },
+        }
+    }
+}
+
+//
+// Execution
+//
+
+/// How a statement hands control back to its parent.
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Leave,
+}
+
+/// The result of a successful execution.
+pub struct Execution {
+    /// The data passed to `return`
+    pub output: Vec<u8>,
+    /// The (estimated) gas used
+    pub gas_used: u64,
+}
+
+/// Why the execution stopped before the end of the program.
+enum Halt {
+    Return(Vec<u8>),
+    Revert,
+    Error(String),
+}
+
+impl From<String> for Halt {
+    fn from(e: String) -> Self {
+        Halt::Error(e)
+    }
+}
+
+type Scope = HashMap<String, BigUint>;
+
+struct Machine<'a> {
+    calldata: &'a [u8],
+    memory: Vec<u8>,
+    functions: HashMap<String, Rc<Function>>,
+    gas_left: u64,
+}
+
+fn to_word(x: &BigUint) -> [u8; 32] {
+    let bytes = x.to_bytes_be();
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+fn to_usize(x: &BigUint) -> Result<usize, String> {
+    usize::try_from(x).map_err(|_| format!("{x} is too large to be an offset"))
+}
+
+fn bool_word(b: bool) -> BigUint {
+    if b {
+        BigUint::one()
+    } else {
+        BigUint::zero()
+    }
+}
+
+fn read_g1(input: &[u8]) -> Option<G1Affine> {
+    let x = BigUint::from_bytes_be(&input[..32]);
+    let y = BigUint::from_bytes_be(&input[32..64]);
+    if x >= *BASE_FIELD_MODULUS || y >= *BASE_FIELD_MODULUS {
+        return None;
+    }
+    if x.is_zero() && y.is_zero() {
+        return Some(G1Affine::zero());
+    }
+    let p = G1Affine::new_unchecked(x.into(), y.into());
+    p.is_on_curve().then_some(p)
+}
+
+fn read_g2(input: &[u8]) -> Option<G2Affine> {
+    let coordinates: Vec<BigUint> = input
+        .chunks(32)
+        .take(4)
+        .map(BigUint::from_bytes_be)
+        .collect();
+    if coordinates.iter().any(|c| *c >= *BASE_FIELD_MODULUS) {
+        return None;
+    }
+    if coordinates.iter().all(Zero::is_zero) {
+        return Some(G2Affine::zero());
+    }
+    let fq = |c: &BigUint| Fq::from(c.clone());
+    let x = Fq2::new(fq(&coordinates[1]), fq(&coordinates[0]));
+    let y = Fq2::new(fq(&coordinates[3]), fq(&coordinates[2]));
+    let p = G2Affine::new_unchecked(x, y);
+    (p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()).then_some(p)
+}
+
+fn write_g1(p: G1Affine) -> Vec<u8> {
+    match p.xy() {
+        None => vec![0u8; 64],
+        Some((x, y)) => [
+            to_word(&x.into_bigint().into()),
+            to_word(&y.into_bigint().into()),
+        ]
+        .concat(),
+    }
+}
+
+/// The gas of `n` words of memory, which grows quadratically.
+fn memory_gas(n: u64) -> u64 {
+    3 * n + n * n / 512
+}
+
+/// The gas of `keccak256` over `len` bytes.
+fn keccak_gas(len: usize) -> u64 {
+    30 + 6 * ((len as u64 + 31) / 32)
+}
+
+/// The gas of the `modexp` precompile, see EIP-2565.
+fn modexp_gas(bl: usize, el: usize, ml: usize, exponent_head: &BigUint) -> u64 {
+    let words = (bl.max(ml) as u64 + 7) / 8;
+    let complexity = words * words;
+    let head_bits = exponent_head.bits().saturating_sub(1);
+    let iterations = if el <= 32 {
+        head_bits
+    } else {
+        8 * (el as u64 - 32) + head_bits
+    };
+    (complexity * iterations.max(1) / 3).max(200)
+}
+
+/// The gas of a call to the precompile at `address` with `input`, if it is a
+/// precompile, see EIP-1108 for the curve operations.
+fn precompile_gas(address: &BigUint, input: &[u8]) -> Option<u64> {
+    let word = |i: usize| {
+        let mut data = input.get(32 * i..).unwrap_or_default().to_vec();
+        data.resize(32, 0);
+        BigUint::from_bytes_be(&data[..32])
+    };
+    match u64::try_from(address).ok()? {
+        0x05 => {
+            let (bl, el, ml) = (word(0), word(1), word(2));
+            let (bl, el, ml) = (
+                usize::try_from(&bl).ok()?,
+                usize::try_from(&el).ok()?,
+                usize::try_from(&ml).ok()?,
+            );
+            let start = 96usize.checked_add(bl)?;
+            let mut head = input.get(start..).unwrap_or_default().to_vec();
+            head.resize(32, 0);
+            let head = BigUint::from_bytes_be(&head[..el.min(32)]);
+            Some(modexp_gas(bl, el, ml, &head))
+        }
+        0x06 => Some(150),
+        0x07 => Some(6_000),
+        0x08 => Some(45_000 + 34_000 * (input.len() / 192) as u64),
+        _ => None,
+    }
+}
+
+/// Runs the precompile at `address`, or returns `None` if the call fails.
+fn precompile(address: &BigUint, input: &[u8]) -> Option<Vec<u8>> {
+    let padded = |len: usize| {
+        let mut data = input.to_vec();
+        data.resize(data.len().max(len), 0);
+        data
+    };
+    match u64::try_from(address).ok()? {
+        0x05 => {
+            let input = padded(96);
+            let bl = usize::try_from(BigUint::from_bytes_be(&input[..32])).ok()?;
+            let el = usize::try_from(BigUint::from_bytes_be(&input[32..64])).ok()?;
+            let ml = usize::try_from(BigUint::from_bytes_be(&input[64..96])).ok()?;
+            if bl.max(el).max(ml) > 1024 {
+                return None;
+            }
+            let input = padded(96 + bl + el + ml);
+            let base = BigUint::from_bytes_be(&input[96..96 + bl]);
+            let exponent = BigUint::from_bytes_be(&input[96 + bl..96 + bl + el]);
+            let modulus = BigUint::from_bytes_be(&input[96 + bl + el..96 + bl + el + ml]);
+            let result = if modulus.is_zero() {
+                BigUint::zero()
+            } else {
+                base.modpow(&exponent, &modulus)
+            };
+            let bytes = result.to_bytes_be();
+            let mut output = vec![0u8; ml];
+            if !result.is_zero() {
+                output[ml - bytes.len()..].copy_from_slice(&bytes);
+            }
+            Some(output)
+        }
+        0x06 => {
+            let input = padded(128);
+            let p = read_g1(&input[..64])?;
+            let q = read_g1(&input[64..128])?;
+            Some(write_g1((p + q).into_affine()))
+        }
+        0x07 => {
+            let input = padded(96);
+            let p = read_g1(&input[..64])?;
+            let scalar = BigUint::from_bytes_be(&input[64..96]);
+            Some(write_g1(p.mul_bigint(scalar.to_u64_digits()).into_affine()))
+        }
+        0x08 => {
+            if input.len() % 192 != 0 {
+                return None;
+            }
+            let mut g1s = vec![];
+            let mut g2s = vec![];
+            for pair in input.chunks(192) {
+                g1s.push(read_g1(&pair[..64])?);
+                g2s.push(read_g2(&pair[64..])?);
+            }
+            let result = Bn254::multi_pairing(g1s, g2s);
+            Some(to_word(&bool_word(result.is_zero())).to_vec())
+        }
+        _ => None,
+    }
+}
+
+impl<'a> Machine<'a> {
+    fn charge(&mut self, gas: u64) -> Result<(), String> {
+        self.gas_left = self.gas_left.checked_sub(gas).ok_or("out of gas")?;
+        Ok(())
+    }
+
+    fn touch(&mut self, offset: usize, len: usize) -> Result<(), String> {
+        let end = offset
+            .checked_add(len)
+            .filter(|end| *end <= MAX_MEMORY)
+            .ok_or("memory access out of bounds")?;
+        if end > self.memory.len() {
+            let words = (end + 31) / 32;
+            self.charge(memory_gas(words as u64) - memory_gas(self.memory.len() as u64 / 32))?;
+            self.memory.resize(words * 32, 0);
+        }
+        Ok(())
+    }
+
+    fn read_memory(&mut self, offset: &BigUint, len: &BigUint) -> Result<Vec<u8>, String> {
+        let len = to_usize(len)?;
+        if len == 0 {
+            return Ok(vec![]);
+        }
+        let offset = to_usize(offset)?;
+        self.touch(offset, len)?;
+        Ok(self.memory[offset..offset + len].to_vec())
+    }
+
+    fn write_memory(&mut self, offset: &BigUint, data: &[u8]) -> Result<(), String> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let offset = to_usize(offset)?;
+        self.touch(offset, data.len())?;
+        self.memory[offset..offset + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn builtin(&mut self, name: &str, args: &[BigUint]) -> Result<Option<BigUint>, Halt> {
+        let m = &*WORD_MODULUS;
+        let arity = match name {
+            "gas" | "calldatasize" => 0,
+            "iszero" | "not" | "mload" | "calldataload" | "pop" => 1,
+            "addmod" | "mulmod" => 3,
+            "staticcall" => 6,
+            _ => 2,
+        };
+        if args.len() != arity {
+            return Err(Halt::Error(format!(
+                "{name} expects {arity} arguments, got {}",
+                args.len()
+            )));
+        }
+        let gas = match name {
+            "gas" | "calldatasize" | "pop" => 2,
+            "mul" | "div" | "mod" => 5,
+            "addmod" | "mulmod" => 8,
+            "keccak256" => keccak_gas(to_usize(&args[1])?),
+            // the precompiles are always warm, see EIP-2929
+            "staticcall" => 100,
+            "return" | "revert" => 0,
+            _ => 3,
+        };
+        self.charge(gas)?;
+        let value = match name {
+            "add" => (&args[0] + &args[1]) % m,
+            "sub" => (&args[0] + m - &args[1]) % m,
+            "mul" => (&args[0] * &args[1]) % m,
+            "div" if args[1].is_zero() => BigUint::zero(),
+            "div" => &args[0] / &args[1],
+            "mod" if args[1].is_zero() => BigUint::zero(),
+            "mod" => &args[0] % &args[1],
+            "addmod" if args[2].is_zero() => BigUint::zero(),
+            "addmod" => (&args[0] + &args[1]) % &args[2],
+            "mulmod" if args[2].is_zero() => BigUint::zero(),
+            "mulmod" => (&args[0] * &args[1]) % &args[2],
+            "lt" => bool_word(args[0] < args[1]),
+            "gt" => bool_word(args[0] > args[1]),
+            "eq" => bool_word(args[0] == args[1]),
+            "iszero" => bool_word(args[0].is_zero()),
+            "and" => &args[0] & &args[1],
+            "or" => &args[0] | &args[1],
+            "xor" => &args[0] ^ &args[1],
+            "not" => m - 1u32 - &args[0],
+            "shl" if args[0] >= BigUint::from(256u32) => BigUint::zero(),
+            "shl" => (&args[1] << to_usize(&args[0])?) % m,
+            "shr" if args[0] >= BigUint::from(256u32) => BigUint::zero(),
+            "shr" => &args[1] >> to_usize(&args[0])?,
+            "gas" => BigUint::from(self.gas_left),
+            "calldatasize" => self.calldata.len().into(),
+            "calldataload" => {
+                let mut word = [0u8; 32];
+                if let Ok(offset) = usize::try_from(&args[0]) {
+                    for (i, byte) in word.iter_mut().enumerate() {
+                        *byte = offset
+                            .checked_add(i)
+                            .and_then(|j| self.calldata.get(j))
+                            .copied()
+                            .unwrap_or(0);
+                    }
+                }
+                BigUint::from_bytes_be(&word)
+            }
+            "mload" => BigUint::from_bytes_be(&self.read_memory(&args[0], &32u32.into())?),
+            "mstore" => {
+                self.write_memory(&args[0], &to_word(&args[1]))?;
+                return Ok(None);
+            }
+            "keccak256" => {
+                let data = self.read_memory(&args[0], &args[1])?;
+                BigUint::from_bytes_be(&Keccak256::digest(data))
+            }
+            "staticcall" => {
+                let input = self.read_memory(&args[2], &args[3])?;
+                if let Some(gas) = precompile_gas(&args[1], &input) {
+                    // at most all but one 64th of the gas left is forwarded,
+                    // and the call consumes it all if it is not enough
+                    let available = self.gas_left - self.gas_left / 64;
+                    let forwarded = u64::try_from(&args[0]).map_or(available, |g| g.min(available));
+                    if gas > forwarded {
+                        self.charge(forwarded)?;
+                        return Ok(Some(BigUint::zero()));
+                    }
+                    self.charge(gas)?;
+                }
+                match precompile(&args[1], &input) {
+                    None => BigUint::zero(),
+                    Some(output) => {
+                        let len = to_usize(&args[5])?.min(output.len());
+                        self.write_memory(&args[4], &output[..len])?;
+                        BigUint::one()
+                    }
+                }
+            }
+            "pop" => return Ok(None),
+            "return" => return Err(Halt::Return(self.read_memory(&args[0], &args[1])?)),
+            "revert" => return Err(Halt::Revert),
+            _ => return Err(Halt::Error(format!("unknown function {name}"))),
+        };
+        Ok(Some(value))
+    }
+
+    fn lookup(scopes: &[Scope], name: &str) -> Result<BigUint, String> {
+        scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .ok_or_else(|| format!("unknown variable {name}"))
+    }
+
+    fn assign(scopes: &mut [Scope], name: &str, value: BigUint) -> Result<(), String> {
+        let slot = scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+            .ok_or_else(|| format!("unknown variable {name}"))?;
+        *slot = value;
+        Ok(())
+    }
+
+    fn eval(&mut self, scopes: &mut Vec<Scope>, expr: &Expr) -> Result<Vec<BigUint>, Halt> {
+        match expr {
+            Expr::Literal(n) => {
+                self.charge(STACK_GAS)?;
+                Ok(vec![n.clone()])
+            }
+            Expr::Ident(name) => {
+                self.charge(STACK_GAS)?;
+                Ok(vec![Self::lookup(scopes, name)?])
+            }
+            Expr::Call(name, args) => {
+                // Like the EVM, evaluate the arguments from right to left
+                let mut values = vec![];
+                for arg in args.iter().rev() {
+                    values.push(self.eval_single(scopes, arg)?);
+                }
+                values.reverse();
+
+                let Some(function) = self.functions.get(name).cloned() else {
+                    return Ok(self.builtin(name, &values)?.into_iter().collect());
+                };
+                if values.len() != function.params.len() {
+                    return Err(Halt::Error(format!("wrong number of arguments for {name}")));
+                }
+                self.charge(CALL_GAS)?;
+                let mut frame: Scope = function.params.iter().cloned().zip(values).collect();
+                for r in &function.returns {
+                    frame.insert(r.clone(), BigUint::zero());
+                }
+                let mut frame = vec![frame];
+                self.exec_block(&mut frame, &function.body)?;
+                Ok(function
+                    .returns
+                    .iter()
+                    .map(|r| frame[0][r].clone())
+                    .collect())
+            }
+        }
+    }
+
+    fn eval_single(&mut self, scopes: &mut Vec<Scope>, expr: &Expr) -> Result<BigUint, Halt> {
+        let mut values = self.eval(scopes, expr)?;
+        if values.len() != 1 {
+            return Err(Halt::Error(format!(
+                "expected a single value from {expr:?}"
+            )));
+        }
+        Ok(values.remove(0))
+    }
+
+    fn exec_block(&mut self, scopes: &mut Vec<Scope>, stmts: &[Stmt]) -> Result<Flow, Halt> {
+        scopes.push(Scope::new());
+        let flow = self.exec_stmts(scopes, stmts);
+        scopes.pop();
+        flow
+    }
+
+    fn exec_stmts(&mut self, scopes: &mut Vec<Scope>, stmts: &[Stmt]) -> Result<Flow, Halt> {
+        for stmt in stmts {
+            if let Stmt::Function(name, function) = stmt {
+                self.functions.insert(name.clone(), function.clone());
+            }
+        }
+        for stmt in stmts {
+            match self.exec(scopes, stmt)? {
+                Flow::Next => (),
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec(&mut self, scopes: &mut Vec<Scope>, stmt: &Stmt) -> Result<Flow, Halt> {
+        match stmt {
+            Stmt::Block(stmts) => return self.exec_block(scopes, stmts),
+            Stmt::Function(..) => (),
+            Stmt::Let(names, value) => {
+                let values = match value {
+                    Some(value) => self.eval(scopes, value)?,
+                    None => vec![BigUint::zero(); names.len()],
+                };
+                if values.len() != names.len() {
+                    return Err(Halt::Error(format!("cannot bind {names:?}")));
+                }
+                let scope = scopes.last_mut().expect("there is always a scope");
+                for (name, value) in names.iter().zip(values) {
+                    scope.insert(name.clone(), value);
+                }
+            }
+            Stmt::Assign(names, value) => {
+                let values = self.eval(scopes, value)?;
+                if values.len() != names.len() {
+                    return Err(Halt::Error(format!("cannot assign {names:?}")));
+                }
+                for (name, value) in names.iter().zip(values) {
+                    self.charge(ASSIGN_GAS)?;
+                    Self::assign(scopes, name, value)?;
+                }
+            }
+            Stmt::Expr(expr) => {
+                if !self.eval(scopes, expr)?.is_empty() {
+                    return Err(Halt::Error(format!("unused value from {expr:?}")));
+                }
+            }
+            Stmt::If(cond, body) => {
+                let cond = self.eval_single(scopes, cond)?;
+                self.charge(BRANCH_GAS)?;
+                if !cond.is_zero() {
+                    return self.exec_block(scopes, body);
+                }
+            }
+            Stmt::Switch(value, cases) => {
+                let value = self.eval_single(scopes, value)?;
+                // each case is compared with `DUP`, `PUSH`, `EQ` and `JUMPI`
+                let tried = cases
+                    .iter()
+                    .position(|(literal, _)| literal.as_ref() == Some(&value))
+                    .map_or(cases.len(), |i| i + 1);
+                self.charge(tried as u64 * (2 * STACK_GAS + 3 + BRANCH_GAS))?;
+                let case = cases
+                    .iter()
+                    .find(|(literal, _)| literal.as_ref() == Some(&value))
+                    .or_else(|| cases.iter().find(|(literal, _)| literal.is_none()));
+                if let Some((_, body)) = case {
+                    return self.exec_block(scopes, body);
+                }
+            }
+            Stmt::For(init, cond, post, body) => {
+                scopes.push(Scope::new());
+                let flow = (|| {
+                    self.exec_stmts(scopes, init)?;
+                    loop {
+                        let cond = self.eval_single(scopes, cond)?;
+                        self.charge(BRANCH_GAS)?;
+                        if cond.is_zero() {
+                            break;
+                        }
+                        match self.exec_block(scopes, body)? {
+                            Flow::Break => break,
+                            Flow::Leave => return Ok(Flow::Leave),
+                            Flow::Next | Flow::Continue => (),
+                        }
+                        self.exec_block(scopes, post)?;
+                        // jump back to the condition
+                        self.charge(CALL_GAS / 2)?;
+                    }
+                    Ok(Flow::Next)
+                })();
+                scopes.pop();
+                return flow;
+            }
+            Stmt::Break => return Ok(Flow::Break),
+            Stmt::Continue => return Ok(Flow::Continue),
+            Stmt::Leave => return Ok(Flow::Leave),
+        }
+        Ok(Flow::Next)
+    }
+}
+
+/// Executes the Yul statements in `source` with the given `calldata` and at
+/// most `gas_limit` gas, and returns the data passed to `return`, or an empty
+/// vector if the program completes without returning, with the gas used.
+///
+/// # Errors
+///
+/// Will give error if the program cannot be parsed, reverts, runs out of
+/// gas, or uses a feature of Yul that is not supported.
+pub fn execute(source: &str, calldata: &[u8], gas_limit: u64) -> Result<Execution, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut program = vec![];
+    while parser.peek().is_some() {
+        program.push(parser.stmt()?);
+    }
+
+    let mut machine = Machine {
+        calldata,
+        memory: vec![],
+        functions: HashMap::new(),
+        gas_left: gas_limit,
+    };
+    let output = match machine.exec_block(&mut vec![], &program) {
+        Ok(_) => vec![],
+        Err(Halt::Return(data)) => data,
+        Err(Halt::Revert) => return Err("the program reverted".into()),
+        Err(Halt::Error(e)) => return Err(e),
+    };
+    Ok(Execution {
+        output,
+        gas_used: gas_limit - machine.gas_left,
+    })
+}
+
+/// The gas paid by a transaction with `calldata` before any code runs.
+fn intrinsic_gas(calldata: &[u8]) -> u64 {
+    TRANSACTION_GAS
+        + calldata
+            .iter()
+            .map(|byte| if *byte == 0 { 4 } else { 16 })
+            .sum::<u64>()
+}
+
+/// Calls the function of a contract generated by
+/// [`super::verifier_contract`] in a transaction with at most `gas_limit`
+/// gas, as the EVM would: the selector of `calldata` must be the one of the
+/// signature declared by the contract, and the assembly block of the function
+/// is executed with `calldata`. The gas used includes the intrinsic gas of
+/// the transaction, but not the dispatch of the Solidity wrapper.
+///
+/// # Errors
+///
+/// Will give error if the contract does not declare a function with an
+/// assembly block, if `calldata` selects another function, or if the
+/// function fails to execute, see [`execute`].
+pub fn execute_contract(
+    contract: &str,
+    calldata: &[u8],
+    gas_limit: u64,
+) -> Result<Execution, String> {
+    let invalid = || "the contract does not declare a function".to_string();
+    let name = contract.find("function ").ok_or_else(invalid)? + "function ".len();
+    let params = name + contract[name..].find('(').ok_or_else(invalid)?;
+    let end = params + contract[params..].find(')').ok_or_else(invalid)?;
+    let types: Vec<_> = contract[params + 1..end]
+        .split(',')
+        .map(|param| param.split_whitespace().next().unwrap_or_default())
+        .collect();
+    let signature = format!("{}({})", &contract[name..params], types.join(","));
+    if calldata.get(..4) != Some(&Keccak256::digest(&signature)[..4]) {
+        return Err(format!("the call does not select {signature}"));
+    }
+
+    let body = end
+        + contract[end..]
+            .find("assembly {")
+            .ok_or("the function has no assembly block")?
+        + "assembly {".len();
+    let mut depth = 1;
+    let body_end = contract[body..]
+        .char_indices()
+        .find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => (),
+            }
+            (depth == 0).then_some(body + i)
+        })
+        .ok_or("the assembly block is not terminated")?;
+    let intrinsic = intrinsic_gas(calldata);
+    let gas_limit = gas_limit.checked_sub(intrinsic).ok_or("out of gas")?;
+    let execution = execute(&contract[body..body_end], calldata, gas_limit)?;
+    Ok(Execution {
+        gas_used: intrinsic + execution.gas_used,
+        ..execution
+    })
+}
@@ -0,0 +1,136 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::COLUMNS,
+    },
+    curve::KimchiCurve,
+    error::VerifyError,
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::{accumulate, succinct_verify, verify, Context},
+};
+use ark_ff::Zero;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+    FqSponge,
+};
+use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+    SRS as _,
+};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+/// A generic circuit with `public` public inputs, expecting `prev_challenges`
+/// accumulators from previous proofs
+fn prover_index(
+    srs: &Arc<SRS<Vesta>>,
+    public: usize,
+    prev_challenges: usize,
+) -> ProverIndex<Vesta, OpeningProof<Vesta>> {
+    let cs = ConstraintSystem::create(create_circuit(0, public))
+        .public(public)
+        .prev_challenges(prev_challenges)
+        .build()
+        .unwrap();
+    srs.get_lagrange_basis(cs.domain.d1);
+    let &endo_q = Vesta::other_curve_endo();
+    ProverIndex::create(cs, endo_q, srs.clone())
+}
+
+fn witness(public: &[Fp]) -> [Vec<Fp>; COLUMNS] {
+    let len = create_circuit::<Fp>(0, public.len()).len();
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); len]);
+    fill_in_witness(0, &mut witness, public);
+    witness
+}
+
+#[test]
+fn test_accumulation() {
+    let srs = Arc::new(SRS::<Vesta>::create(32));
+    let index = prover_index(&srs, 3, 0);
+    let verifier_index = index.verifier_index();
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+
+    let publics: Vec<_> = (1..=4u64)
+        .map(|i| vec![Fp::from(i), Fp::from(2 * i), Fp::from(3 * i)])
+        .collect();
+    let proofs: Vec<_> = publics
+        .iter()
+        .map(|public| {
+            ProverProof::create::<BaseSponge, ScalarSponge, _>(
+                &group_map,
+                witness(public),
+                &[],
+                &index,
+                rng,
+            )
+            .unwrap()
+        })
+        .collect();
+    let contexts: Vec<_> = proofs
+        .iter()
+        .zip(&publics)
+        .map(|(proof, public_input)| Context {
+            verifier_index: &verifier_index,
+            proof,
+            public_input,
+        })
+        .collect();
+
+    // the accumulators of the proofs can be checked later, all at once
+    let accumulators =
+        succinct_verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &contexts).unwrap();
+    assert_eq!(accumulators.len(), proofs.len());
+    assert!(srs.check_accumulators(&accumulators, rng));
+
+    // or folded into a single accumulator
+    let (accumulator, folding) =
+        accumulate::<Vesta, BaseSponge, ScalarSponge>(&group_map, &contexts).unwrap();
+    let sponge = BaseSponge::new(Vesta::other_curve_sponge_params());
+    assert_eq!(
+        srs.verify_accumulation(&group_map, &accumulators, &folding, sponge),
+        Some(accumulator.clone())
+    );
+    assert!(srs.check_accumulators(&[accumulator.clone()], rng));
+
+    // a proof checked against a wrong public input is rejected
+    let wrong_public = vec![Fp::zero(); 3];
+    let wrong_contexts = [Context {
+        verifier_index: &verifier_index,
+        proof: &proofs[1],
+        public_input: &wrong_public,
+    }];
+    assert!(matches!(
+        succinct_verify::<Vesta, BaseSponge, ScalarSponge>(&group_map, &wrong_contexts),
+        Err(VerifyError::OpenProof)
+    ));
+
+    // the check of the accumulator can be deferred to a recursive proof
+    let recursive_index = prover_index(&srs, 0, 1);
+    let proof = ProverProof::create_recursive::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness(&[]),
+        &[],
+        &recursive_index,
+        vec![accumulator.into()],
+        None,
+        rng,
+    )
+    .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &recursive_index.verifier_index(),
+        &proof,
+        &[],
+    )
+    .unwrap();
+}
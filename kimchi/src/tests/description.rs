@@ -0,0 +1,262 @@
+use crate::{
+    circuits::{
+        description::{
+            parse_field, CircuitDescription, Format, WitnessDescription, DESCRIPTION_VERSION,
+        },
+        gate::GateType,
+        lookup::runtime_tables,
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{Wire, COLUMNS, PERMUTS},
+    },
+    curve::KimchiCurve,
+    error::DescriptionError,
+    linearization::expr_linearization,
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::verify,
+    verifier_index::VerifierIndex,
+};
+use ark_ff::{One, PrimeField, Zero};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+    SRS as _,
+};
+use std::{array, sync::Arc};
+
+type SpongeParams = PlonkSpongeConstantsKimchi;
+type BaseSponge = DefaultFqSponge<VestaParameters, SpongeParams>;
+type ScalarSponge = DefaultFrSponge<Fp, SpongeParams>;
+
+fn generic_circuit() -> (CircuitDescription<Fp>, WitnessDescription<Fp>) {
+    let public = vec![Fp::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    (
+        CircuitDescription::new(&gates, public.len()),
+        WitnessDescription::new(&witness, &[]),
+    )
+}
+
+fn runtime_table_circuit() -> (CircuitDescription<Fp>, WitnessDescription<Fp>) {
+    let num_lookups = 20;
+    let (gates, cfg) = runtime_tables::testing::create_circuit(num_lookups);
+    let (witness, runtime_table) = runtime_tables::testing::create_witness(num_lookups, &cfg);
+    (
+        CircuitDescription::new(&gates, 0).runtime(&[cfg]),
+        WitnessDescription::new(&witness, &[runtime_table]),
+    )
+}
+
+/// Proves and verifies a circuit loaded from its description.
+fn prove_and_verify(circuit: &CircuitDescription<Fp>, witness: &WitnessDescription<Fp>) {
+    let cs = circuit.constraint_system().unwrap();
+    let srs = SRS::<Vesta>::create(cs.domain.d1.size as usize);
+    srs.get_lagrange_basis(cs.domain.d1);
+    let &endo_q = Vesta::other_curve_endo();
+    let index = ProverIndex::<Vesta, OpeningProof<Vesta>>::create(cs, endo_q, Arc::new(srs));
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let proof = ProverProof::create::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness.witness().unwrap(),
+        &witness.runtime_tables(),
+        &index,
+        &mut rand::rngs::OsRng,
+    )
+    .unwrap();
+    let public_input = witness.public_input(circuit.public).unwrap();
+
+    // the verifier only has the serialized verifier index
+    let bytes = rmp_serde::to_vec(&index.verifier_index()).unwrap();
+    let mut verifier_index: VerifierIndex<Vesta, OpeningProof<Vesta>> =
+        rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(verifier_index.feature_flags(), index.cs.feature_flags);
+    verifier_index.srs = Arc::clone(&index.srs);
+    verifier_index.endo = endo_q;
+    let (linearization, powers_of_alpha) =
+        expr_linearization(Some(&verifier_index.feature_flags()), true);
+    verifier_index.linearization = linearization;
+    verifier_index.powers_of_alpha = powers_of_alpha;
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &public_input,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_description_round_trip() {
+    let formats = [
+        Format::Json,
+        #[cfg(feature = "cbor")]
+        Format::Cbor,
+    ];
+    for (circuit, witness) in [generic_circuit(), runtime_table_circuit()] {
+        for format in formats {
+            let bytes = circuit.encode(format).unwrap();
+            assert_eq!(CircuitDescription::decode(format, &bytes).unwrap(), circuit);
+            let bytes = witness.encode(format).unwrap();
+            assert_eq!(WitnessDescription::decode(format, &bytes).unwrap(), witness);
+        }
+    }
+}
+
+#[test]
+fn test_description_prove_and_verify() {
+    let (circuit, witness) = generic_circuit();
+    let json = circuit.encode(Format::Json).unwrap();
+    let circuit = CircuitDescription::decode(Format::Json, &json).unwrap();
+    prove_and_verify(&circuit, &witness);
+
+    let (circuit, witness) = runtime_table_circuit();
+    #[cfg(feature = "cbor")]
+    let witness = {
+        let cbor = witness.encode(Format::Cbor).unwrap();
+        WitnessDescription::decode(Format::Cbor, &cbor).unwrap()
+    };
+    prove_and_verify(&circuit, &witness);
+}
+
+#[test]
+fn test_description_json_format() {
+    let json = r#"{
+        "version": 1,
+        "public": 1,
+        "gates": [
+            {
+                "typ": "Generic",
+                "wires": [
+                    {"row": 0, "col": 0}, {"row": 0, "col": 1}, {"row": 0, "col": 2},
+                    {"row": 0, "col": 3}, {"row": 0, "col": 4}, {"row": 0, "col": 5},
+                    {"row": 0, "col": 6}
+                ],
+                "coeffs": ["1", "0x0", "0", "0", "0"]
+            },
+            {
+                "typ": "Lookup",
+                "wires": [
+                    {"row": 1, "col": 0}, {"row": 1, "col": 1}, {"row": 1, "col": 2},
+                    {"row": 1, "col": 3}, {"row": 1, "col": 4}, {"row": 1, "col": 5},
+                    {"row": 1, "col": 6}
+                ]
+            }
+        ],
+        "lookup_tables": [{"id": 0, "data": [["0", "0x2a"]]}]
+    }"#;
+    let circuit = CircuitDescription::<Fp>::decode(Format::Json, json.as_bytes()).unwrap();
+    assert_eq!(circuit.public, 1);
+    assert_eq!(circuit.gates[0].typ, GateType::Generic);
+    assert_eq!(circuit.gates[0].coeffs[0], Fp::one());
+    assert_eq!(circuit.lookup_tables[0].data[0][1], Fp::from(42u8));
+    assert!(circuit.gates[1].coeffs.is_empty());
+    assert!(circuit.runtime_tables.is_empty());
+
+    let cs = circuit.constraint_system().unwrap();
+    assert!(cs.feature_flags.lookup_features.patterns.lookup);
+}
+
+#[test]
+fn test_description_errors() {
+    let (mut circuit, witness) = generic_circuit();
+
+    // unknown versions are rejected
+    circuit.version = DESCRIPTION_VERSION + 1;
+    let bytes = circuit.encode(Format::Json).unwrap();
+    assert!(matches!(
+        CircuitDescription::<Fp>::decode(Format::Json, &bytes),
+        Err(DescriptionError::UnsupportedVersion(..))
+    ));
+    circuit.version = DESCRIPTION_VERSION;
+
+    // the builder panics on circuits with a single gate
+    let mut small_circuit = circuit.clone();
+    small_circuit.gates.truncate(1);
+    assert!(matches!(
+        small_circuit.constraint_system(),
+        Err(DescriptionError::Setup(..))
+    ));
+
+    // the declared feature flags must match the circuit
+    let mut flags = circuit.constraint_system().unwrap().feature_flags;
+    circuit.feature_flags = Some(flags);
+    assert!(circuit.constraint_system().is_ok());
+    flags.xor = true;
+    circuit.feature_flags = Some(flags);
+    assert!(matches!(
+        circuit.constraint_system(),
+        Err(DescriptionError::FeatureFlags(..))
+    ));
+
+    let mut bad_witness = witness.clone();
+    bad_witness.columns.pop();
+    assert!(matches!(
+        bad_witness.witness(),
+        Err(DescriptionError::WitnessColumns(14, COLUMNS))
+    ));
+    let mut bad_witness = witness;
+    bad_witness.columns[3].pop();
+    assert!(matches!(
+        bad_witness.witness(),
+        Err(DescriptionError::WitnessInconsistent)
+    ));
+}
+
+#[test]
+fn test_description_wiring() {
+    let (circuit, _) = generic_circuit();
+    let last_row = circuit.gates.len() - 1;
+
+    // a wire to a row out of the circuit
+    let mut bad_circuit = circuit.clone();
+    bad_circuit.gates[1].wires[2] = Wire::new(last_row + 1, 2);
+    assert!(matches!(
+        bad_circuit.constraint_system(),
+        Err(DescriptionError::WireOutOfRange(1, 2, row, 2)) if row == last_row + 1
+    ));
+
+    // a wire to a column that is not permuted
+    let mut bad_circuit = circuit.clone();
+    bad_circuit.gates[1].wires[2] = Wire::new(1, PERMUTS);
+    assert!(matches!(
+        bad_circuit.constraint_system(),
+        Err(DescriptionError::WireOutOfRange(1, 2, 1, PERMUTS))
+    ));
+
+    // two cells wired to the same cell
+    let mut bad_circuit = circuit.clone();
+    bad_circuit.gates[1].wires[2] = Wire::new(1, 3);
+    assert!(matches!(
+        bad_circuit.constraint_system(),
+        Err(DescriptionError::WiringNotPermutation(1, 2, 0))
+    ));
+
+    // swapping the wires of two cells is a valid wiring
+    let mut circuit = circuit;
+    circuit.gates[1].wires[2] = Wire::new(last_row, 0);
+    circuit.gates[last_row].wires[0] = Wire::new(1, 2);
+    assert!(circuit.check_wiring().is_ok());
+}
+
+#[test]
+fn test_description_field_elements() {
+    assert_eq!(parse_field::<Fp>("42").unwrap(), Fp::from(42u8));
+    assert_eq!(parse_field::<Fp>("0x2a").unwrap(), Fp::from(42u8));
+    assert_eq!(
+        parse_field::<Fp>(&(-Fp::one()).to_string()).unwrap(),
+        -Fp::one()
+    );
+    let modulus = num_bigint::BigUint::from(Fp::MODULUS);
+    assert!(parse_field::<Fp>(&modulus.to_string()).is_err());
+    assert!(parse_field::<Fp>("-1").is_err());
+    assert!(parse_field::<Fp>("0xzz").is_err());
+}
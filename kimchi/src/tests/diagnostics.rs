@@ -0,0 +1,157 @@
+use crate::circuits::{
+    constraints::ConstraintSystem,
+    diagnostics::{check_witness, Failure},
+    gate::{CircuitGate, Connect, GateType},
+    polynomials::{
+        generic::{
+            testing::{create_circuit, fill_in_witness},
+            GenericGateSpec,
+        },
+        rot::{self, RotMode},
+    },
+    wires::{Wire, COLUMNS},
+};
+use ark_ff::{One, Zero};
+use mina_curves::pasta::{Fp, Vesta};
+use std::array;
+
+fn generic_circuit(public: &[Fp]) -> (ConstraintSystem<Fp>, [Vec<Fp>; COLUMNS]) {
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, public);
+    let cs = ConstraintSystem::create(gates)
+        .public(public.len())
+        .build()
+        .unwrap();
+    (cs, witness)
+}
+
+#[test]
+fn test_diagnostics_valid_witness() {
+    let public = vec![Fp::from(3u8); 5];
+    let (cs, witness) = generic_circuit(&public);
+    let report = check_witness::<Vesta>(&cs, &witness, &public);
+    assert!(report.is_ok(), "{report}");
+    assert_eq!(report.to_string(), "the witness satisfies the circuit");
+
+    let mut gates = vec![CircuitGate::create_generic_gadget(
+        Wire::for_row(0),
+        GenericGateSpec::Pub,
+        None,
+    )];
+    CircuitGate::extend_rot(&mut gates, 13, RotMode::Left, 0);
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero()]);
+    rot::extend_rot(&mut witness, 0x0123_4567_89ab_cdef, 13, RotMode::Left);
+    let cs = ConstraintSystem::create(gates).build().unwrap();
+    let report = check_witness::<Vesta>(&cs, &witness, &[]);
+    assert!(report.is_ok(), "{report}");
+}
+
+#[test]
+fn test_diagnostics_generic_constraint() {
+    let public = vec![Fp::from(3u8); 5];
+    let (cs, mut witness) = generic_circuit(&public);
+
+    // the first generic gate after the public inputs adds its two inputs
+    let row = public.len();
+    witness[2][row] += Fp::one();
+    let report = check_witness::<Vesta>(&cs, &witness, &public);
+    let failures: Vec<_> = report.failures_at(row).collect();
+    assert!(!failures.is_empty(), "{report}");
+    match failures[0] {
+        Failure::Constraint {
+            typ, index, cells, ..
+        } => {
+            assert_eq!(*typ, GateType::Generic);
+            assert_eq!(*index, 0);
+            assert!(cells.iter().any(|cell| cell.wire == Wire { row, col: 2 }));
+        }
+        failure => panic!("unexpected failure: {failure}"),
+    }
+    assert!(report.to_string().contains(&format!("row {row} (Generic)")));
+
+    // a wrong public input makes the first constraint of its row fail
+    let (_, witness) = generic_circuit(&public);
+    let mut bad_public = public.clone();
+    bad_public[1] = Fp::from(4u8);
+    let report = check_witness::<Vesta>(&cs, &witness, &bad_public);
+    assert_eq!(report.failures.len(), 1, "{report}");
+    assert!(matches!(
+        &report.failures[0],
+        Failure::Constraint { typ: GateType::Generic, row: 1, index: 0, value, .. }
+            if *value == -Fp::one()
+    ));
+}
+
+#[test]
+fn test_diagnostics_copy_constraint() {
+    // two generic gates without coefficients, whose left wires are connected
+    let mut gates: Vec<_> = (0..2)
+        .map(|row| CircuitGate::new(GateType::Generic, Wire::for_row(row), vec![]))
+        .collect();
+    gates.connect_cell_pair((0, 0), (1, 0));
+    let cs = ConstraintSystem::create(gates).build().unwrap();
+
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); 2]);
+    witness[0][0] = Fp::from(1u8);
+    witness[0][1] = Fp::from(2u8);
+    let report = check_witness::<Vesta>(&cs, &witness, &[]);
+    assert_eq!(report.failures.len(), 1, "{report}");
+    match &report.failures[0] {
+        Failure::CopyConstraint { cycle } => {
+            let cells: Vec<_> = cycle.iter().map(|cell| (cell.wire, cell.value)).collect();
+            assert_eq!(
+                cells,
+                vec![
+                    (Wire { row: 0, col: 0 }, Fp::from(1u8)),
+                    (Wire { row: 1, col: 0 }, Fp::from(2u8))
+                ]
+            );
+        }
+        failure => panic!("unexpected failure: {failure}"),
+    }
+    assert_eq!(report.failures_at(1).count(), 1);
+
+    let mut short_witness = witness;
+    short_witness[3].pop();
+    let report = check_witness::<Vesta>(&cs, &short_witness, &[]);
+    assert_eq!(
+        report.failures,
+        vec![Failure::WitnessLength {
+            col: 3,
+            len: 1,
+            expected: 2
+        }]
+    );
+}
+
+#[test]
+fn test_diagnostics_custom_gate() {
+    let mut gates = vec![CircuitGate::create_generic_gadget(
+        Wire::for_row(0),
+        GenericGateSpec::Pub,
+        None,
+    )];
+    CircuitGate::extend_rot(&mut gates, 13, RotMode::Left, 0);
+    let cs = ConstraintSystem::create(gates).build().unwrap();
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero()]);
+    rot::extend_rot(&mut witness, 0x0123_4567_89ab_cdef, 13, RotMode::Left);
+
+    // change one of the crumbs of the rotated word
+    witness[14][1] += Fp::one();
+    let report = check_witness::<Vesta>(&cs, &witness, &[]);
+    assert!(!report.is_ok());
+    for failure in &report.failures {
+        match failure {
+            Failure::Constraint {
+                typ, row, cells, ..
+            } => {
+                assert_eq!((*typ, *row), (GateType::Rot64, 1), "{report}");
+                assert!(cells
+                    .iter()
+                    .any(|cell| cell.wire == Wire { row: 1, col: 14 }));
+            }
+            failure => panic!("unexpected failure: {failure}"),
+        }
+    }
+}
@@ -2,13 +2,18 @@ use super::framework::{print_witness, TestFramework};
 use crate::circuits::{
     gate::{CircuitGate, GateType},
     lookup::{
+        index::LookupError,
+        lookups::LookupInfo,
         runtime_tables::{RuntimeTable, RuntimeTableCfg},
-        tables::LookupTable,
+        tables::{
+            builder::{LookupTableBuilder, LookupTables, FIRST_CUSTOM_TABLE_ID},
+            LookupTable,
+        },
     },
     polynomial::COLUMNS,
     wires::Wire,
 };
-use ark_ff::{UniformRand, Zero};
+use ark_ff::{One, UniformRand, Zero};
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
@@ -729,3 +734,132 @@ fn test_dummy_zero_entry_is_counted_while_computing_domain_size() {
     // As the dummy entry has been added, we reached the next power of two
     assert!(domain_size == (1 << (power_of_2 + 1)));
 }
+
+#[test]
+fn test_lookup_tables_builder() {
+    let mut tables = LookupTables::<Fp>::new();
+
+    // byte -> number of bits set, and a 4-bit S-box
+    let popcount = tables
+        .add(LookupTableBuilder::from_fn(2, 256, |x| {
+            vec![Fp::from(x as u64), Fp::from(x.count_ones())]
+        }))
+        .unwrap();
+    let sbox: [u64; 16] = [12, 5, 6, 11, 9, 0, 10, 13, 3, 14, 15, 8, 4, 7, 1, 2];
+    let sbox_id = tables
+        .add(LookupTableBuilder::from_rows(
+            2,
+            sbox.iter()
+                .enumerate()
+                .map(|(x, y)| [Fp::from(x as u64), Fp::from(*y)]),
+        ))
+        .unwrap();
+    assert_eq!(popcount, FIRST_CUSTOM_TABLE_ID);
+    assert_eq!(sbox_id, FIRST_CUSTOM_TABLE_ID + 1);
+
+    // the table with ID 0 is given a zero row
+    tables
+        .add_with_id(
+            0,
+            LookupTableBuilder::new(2).row([Fp::from(1u8), Fp::from(2u8)]),
+        )
+        .unwrap();
+    assert!(tables.tables()[2].has_zero_entry());
+    assert_eq!(tables.tables()[2].len(), 2);
+
+    // automatic IDs skip the IDs already used
+    tables
+        .add_with_id(
+            FIRST_CUSTOM_TABLE_ID + 2,
+            LookupTableBuilder::new(1).row([Fp::zero()]),
+        )
+        .unwrap();
+    let id = tables
+        .add(LookupTableBuilder::new(1).row([Fp::zero()]))
+        .unwrap();
+    assert_eq!(id, FIRST_CUSTOM_TABLE_ID + 3);
+
+    // automatic IDs skip the IDs of the runtime tables
+    let runtime_id = FIRST_CUSTOM_TABLE_ID + 4;
+    tables.reserve(runtime_id).unwrap();
+    let id = tables
+        .add(LookupTableBuilder::new(1).row([Fp::zero()]))
+        .unwrap();
+    assert_eq!(id, runtime_id + 1);
+    assert!(matches!(
+        tables.add_with_id(runtime_id, LookupTableBuilder::new(1).row([Fp::zero()])),
+        Err(LookupError::LookupTableIdCollision { .. })
+    ));
+    assert!(matches!(
+        tables.reserve(sbox_id),
+        Err(LookupError::LookupTableIdCollision { .. })
+    ));
+
+    assert!(matches!(
+        tables.add_with_id(sbox_id, LookupTableBuilder::new(1).row([Fp::zero()])),
+        Err(LookupError::LookupTableIdCollision { .. })
+    ));
+    assert!(matches!(
+        tables.add(LookupTableBuilder::new(2)),
+        Err(LookupError::EmptyTable(_))
+    ));
+    assert!(matches!(
+        tables.add(LookupTableBuilder::new(2).row([Fp::zero()])),
+        Err(LookupError::InconsistentTableWidth {
+            row: 0,
+            width: 1,
+            expected: 2,
+            ..
+        })
+    ));
+
+    // the lookup gate queries tables of at most two columns
+    let gates: Vec<CircuitGate<Fp>> = (0..4)
+        .map(|i| CircuitGate::new(GateType::Lookup, Wire::for_row(i), vec![]))
+        .collect();
+    let info = LookupInfo::create_from_gates(&gates, false).unwrap();
+    assert!(tables.check(&info).is_ok());
+    let mut wide_tables = tables.clone();
+    let wide = wide_tables
+        .add(LookupTableBuilder::new(3).row([Fp::zero(), Fp::one(), Fp::one()]))
+        .unwrap();
+    assert!(matches!(
+        wide_tables.build(&info, &[]),
+        Err(LookupError::TableTooWide { id, width: 3, max_width: 2 }) if id == wide
+    ));
+
+    // a runtime table whose ID was not reserved collides with a fixed table
+    let runtime = RuntimeTableCfg {
+        id: popcount,
+        first_column: vec![Fp::zero()],
+    };
+    assert!(matches!(
+        tables.clone().build(&info, &[runtime]),
+        Err(LookupError::LookupTableIdCollision { .. })
+    ));
+
+    // prove lookups into the tables
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero(); gates.len()]);
+    for row in 0..gates.len() {
+        let x = 17 * row as u64 + 3;
+        witness[0][row] = Fp::from(popcount as u64);
+        for lookup in 0..3 {
+            let x = x + lookup as u64;
+            witness[1 + 2 * lookup][row] = Fp::from(x);
+            witness[2 + 2 * lookup][row] = Fp::from(x.count_ones());
+        }
+    }
+    witness[0][3] = Fp::from(sbox_id as u64);
+    for lookup in 0..3 {
+        witness[1 + 2 * lookup][3] = Fp::from(lookup as u64);
+        witness[2 + 2 * lookup][3] = Fp::from(sbox[lookup]);
+    }
+
+    TestFramework::<Vesta>::default()
+        .gates(gates)
+        .witness(witness)
+        .lookup_tables(tables.build(&info, &[]).unwrap())
+        .setup()
+        .prove_and_verify::<BaseSponge, ScalarSponge>()
+        .unwrap();
+}
@@ -1,6 +1,9 @@
 // IMPROVEME: move all tests in top-level directory tests
+mod accumulation;
 mod and;
 mod chunked;
+mod description;
+mod diagnostics;
 mod ec;
 mod endomul;
 mod endomul_scalar;
@@ -12,9 +15,12 @@ mod keccak;
 mod lookup;
 mod not;
 mod poseidon;
+mod profile;
 mod range_check;
 mod recursion;
 mod rot;
 mod serde;
+#[cfg(feature = "bn254")]
+mod solidity;
 mod varbasemul;
 mod xor;
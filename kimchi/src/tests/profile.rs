@@ -0,0 +1,135 @@
+use crate::{
+    circuits::{
+        constraints::ConstraintSystem,
+        gate::CircuitGate,
+        polynomials::{
+            generic::GenericGateSpec,
+            rot::{self, RotMode},
+        },
+        wires::{Wire, COLUMNS},
+    },
+    curve::KimchiCurve,
+    profile::ProverProfile,
+    proof::ProverProof,
+    prover_index::ProverIndex,
+    verifier::verify,
+};
+use ark_ff::Zero;
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi,
+    sponge::{DefaultFqSponge, DefaultFrSponge},
+};
+use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+    SRS as _,
+};
+use std::{array, sync::Arc};
+
+type BaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type ScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+#[test]
+fn test_prover_profile() {
+    let mut gates = vec![CircuitGate::create_generic_gadget(
+        Wire::for_row(0),
+        GenericGateSpec::Pub,
+        None,
+    )];
+    CircuitGate::extend_rot(&mut gates, 13, RotMode::Left, 0);
+    let mut witness: [Vec<Fp>; COLUMNS] = array::from_fn(|_| vec![Fp::zero()]);
+    rot::extend_rot(&mut witness, 0x0123_4567_89ab_cdef, 13, RotMode::Left);
+
+    let cs = ConstraintSystem::create(gates).build().unwrap();
+    let srs = SRS::<Vesta>::create(cs.domain.d1.size as usize);
+    srs.get_lagrange_basis(cs.domain.d1);
+    let &endo_q = Vesta::other_curve_endo();
+    let index = ProverIndex::<Vesta, OpeningProof<Vesta>>::create(cs, endo_q, Arc::new(srs));
+
+    // the shape of the circuit is known before proving
+    let profile = ProverProfile::new(&index);
+    assert_eq!(profile.domain_size, index.cs.domain.d1.size as usize);
+    assert_eq!(profile.num_chunks, 1);
+    assert_eq!(profile.gates["Generic"], 1);
+    assert_eq!(profile.gates["Rot64"], 1);
+    assert_eq!(profile.gates["RangeCheck0"], 2);
+    assert_eq!(
+        profile.gates.values().sum::<usize>(),
+        index.cs.domain.d1.size as usize
+    );
+    let names: Vec<_> = profile
+        .constraints
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Generic", "RangeCheck0", "Rot64", "Lookup"]);
+    for constraint in &profile.constraints {
+        assert!(constraint.constraints > 0);
+        assert!(constraint.degree >= profile.domain_size as u64);
+        assert!(constraint.quotient_domain <= 8);
+    }
+    // the multiplication term of the generic gate, times its selector
+    assert_eq!(
+        profile.constraints[0].degree,
+        4 * profile.domain_size as u64
+    );
+    assert!(profile.phases.is_empty());
+
+    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+    let (proof, profile) = ProverProof::create_with_profile::<BaseSponge, ScalarSponge, _>(
+        &group_map,
+        witness,
+        &[],
+        &index,
+        &mut rand::rngs::OsRng,
+    )
+    .unwrap();
+    verify::<Vesta, BaseSponge, ScalarSponge, OpeningProof<Vesta>>(
+        &group_map,
+        &index.verifier_index(),
+        &proof,
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(profile.constraints, ProverProfile::new(&index).constraints);
+    for phase in [
+        "witness_commitments",
+        "lookup_sorted",
+        "lookup_aggregation",
+        "permutation_aggregation",
+        "quotient",
+        "opening",
+    ] {
+        assert!(profile.phase(phase).is_some(), "missing phase {phase}");
+    }
+    let phases: f64 = profile.phases.iter().map(|phase| phase.seconds).sum();
+    assert!(phases <= profile.total_seconds);
+    let witness_msm = profile.msms.iter().find(|msm| msm.name == "witness");
+    assert_eq!(witness_msm.map(|msm| msm.count), Some(COLUMNS));
+    assert!(profile.msms.iter().any(|msm| msm.name == "quotient"));
+    // only the opening is estimated, the commitments are recorded
+    assert!(profile
+        .msms
+        .iter()
+        .all(|msm| msm.estimated == (msm.name == "opening")));
+    let opening: Vec<_> = profile
+        .msms
+        .iter()
+        .filter(|msm| msm.name == "opening")
+        .map(|msm| (msm.count, msm.size))
+        .collect();
+    let rounds = profile.domain_size.trailing_zeros() as usize;
+    assert_eq!(opening.len(), rounds);
+    assert_eq!(opening[0], (2, profile.domain_size / 2 + 2));
+
+    let json: serde_json::Value = serde_json::from_str(&profile.to_json().unwrap()).unwrap();
+    assert_eq!(json["gates"]["Rot64"], 1);
+    assert_eq!(
+        json["phases"].as_array().unwrap().len(),
+        profile.phases.len()
+    );
+    assert_eq!(json["msms"][0]["estimated"], false);
+}
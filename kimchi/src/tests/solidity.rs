@@ -0,0 +1,279 @@
+use crate::{
+    circuits::{
+        gate::{CircuitGate, GateType},
+        lookup::{
+            runtime_tables::{self, RuntimeTable, RuntimeTableCfg},
+            tables::LookupTable,
+        },
+        polynomials::generic::testing::{create_circuit, fill_in_witness},
+        wires::{Wire, COLUMNS},
+    },
+    proof::ProverProof,
+    prover_index::testing::new_index_for_test_with_lookups_and_custom_srs,
+    solidity::{
+        self,
+        sponge::{KeccakFqSponge, KeccakFrSponge},
+        BN254KZGProof, BN254VerifierIndex, Fr, BN254,
+    },
+    verifier::verify,
+};
+use ark_ff::{One, Zero};
+use groupmap::GroupMap;
+use poly_commitment::{commitment::CommitmentCurve, kzg::PairingSRS, SRS as _};
+use std::array;
+
+type BaseSponge = KeccakFqSponge<ark_bn254::g1::Config>;
+type ScalarSponge = KeccakFrSponge<Fr>;
+
+/// The gas limit of the calls, the gas limit of a block on Ethereum.
+const GAS_LIMIT: u64 = 30_000_000;
+
+/// An upper bound on the (estimated) gas used to verify the proofs of the
+/// tests, including the intrinsic gas of the transaction. They use about
+/// 750k to 850k gas.
+const VERIFY_GAS: u64 = 1_000_000;
+
+struct Circuit {
+    gates: Vec<CircuitGate<Fr>>,
+    witness: [Vec<Fr>; COLUMNS],
+    public: Vec<Fr>,
+    lookup_tables: Vec<LookupTable<Fr>>,
+    runtime_tables_setup: Option<Vec<RuntimeTableCfg<Fr>>>,
+    runtime_tables: Vec<RuntimeTable<Fr>>,
+}
+
+impl Circuit {
+    fn new(gates: Vec<CircuitGate<Fr>>, witness: [Vec<Fr>; COLUMNS]) -> Self {
+        Circuit {
+            gates,
+            witness,
+            public: vec![],
+            lookup_tables: vec![],
+            runtime_tables_setup: None,
+            runtime_tables: vec![],
+        }
+    }
+
+    fn prove(
+        self,
+    ) -> (
+        BN254VerifierIndex,
+        ProverProof<BN254, BN254KZGProof>,
+        Vec<Fr>,
+    ) {
+        let index = new_index_for_test_with_lookups_and_custom_srs(
+            self.gates,
+            self.public.len(),
+            0,
+            self.lookup_tables,
+            self.runtime_tables_setup,
+            false,
+            None,
+            |d1, size| {
+                let srs = PairingSRS::create(size);
+                srs.full_srs.get_lagrange_basis(d1);
+                srs
+            },
+        );
+        let group_map = <BN254 as CommitmentCurve>::Map::setup();
+        let proof = ProverProof::create_recursive::<BaseSponge, ScalarSponge, _>(
+            &group_map,
+            self.witness,
+            &self.runtime_tables,
+            &index,
+            vec![],
+            None,
+            &mut rand::rngs::OsRng,
+        )
+        .unwrap();
+        (index.verifier_index(), proof, self.public)
+    }
+}
+
+/// Checks that the Rust verifier and the generated verifier agree on `proof`.
+fn check(
+    index: &BN254VerifierIndex,
+    proof: &ProverProof<BN254, BN254KZGProof>,
+    public: &[Fr],
+    expected: bool,
+) {
+    let group_map = <BN254 as CommitmentCurve>::Map::setup();
+    let rust =
+        verify::<BN254, BaseSponge, ScalarSponge, BN254KZGProof>(&group_map, index, proof, public);
+    assert_eq!(rust.is_ok(), expected, "rust verifier: {rust:?}");
+
+    let calldata = solidity::calldata(index, proof, public).unwrap();
+    let (accepted, gas_used) = solidity::execute(index, &calldata, GAS_LIMIT).unwrap();
+    assert_eq!(accepted, expected);
+    assert!(gas_used <= VERIFY_GAS, "the verifier used {gas_used} gas");
+}
+
+/// Checks that the `verify` function of the generated contract agrees with
+/// the Rust verifier.
+fn check_contract(
+    contract: &str,
+    index: &BN254VerifierIndex,
+    proof: &ProverProof<BN254, BN254KZGProof>,
+    public: &[Fr],
+    expected: bool,
+) {
+    let calldata = solidity::calldata(index, proof, public).unwrap();
+    let (accepted, gas_used) = solidity::execute_contract(contract, &calldata, GAS_LIMIT).unwrap();
+    assert_eq!(accepted, expected);
+    assert!(
+        gas_used <= VERIFY_GAS,
+        "the transaction used {gas_used} gas"
+    );
+}
+
+/// Checks a valid proof, and a few altered ones.
+fn check_circuit(circuit: Circuit) {
+    let (index, proof, public) = circuit.prove();
+    check(&index, &proof, &public, true);
+
+    let mut bad_proof = proof.clone();
+    bad_proof.ft_eval1 += Fr::one();
+    check(&index, &bad_proof, &public, false);
+
+    let mut bad_proof = proof.clone();
+    bad_proof.evals.w[0].zeta[0] += Fr::one();
+    check(&index, &bad_proof, &public, false);
+
+    let mut bad_proof = proof.clone();
+    bad_proof.proof.blinding += Fr::one();
+    check(&index, &bad_proof, &public, false);
+
+    if !public.is_empty() {
+        let mut bad_public = public.clone();
+        bad_public[0] += Fr::one();
+        check(&index, &proof, &bad_public, false);
+    }
+}
+
+#[test]
+fn test_solidity_generic() {
+    let public = vec![Fr::from(3u8); 5];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fr>; COLUMNS] = array::from_fn(|_| vec![Fr::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+
+    let mut circuit = Circuit::new(gates, witness);
+    circuit.public = public;
+    check_circuit(circuit);
+}
+
+#[test]
+fn test_solidity_lookup() {
+    // a joint table with an index column, whose first row is zero
+    let values: Vec<Fr> = (0..10u64).map(|i| Fr::from(i * i)).collect();
+    let index_column: Vec<Fr> = (0..10u64).map(Fr::from).collect();
+    let table = LookupTable {
+        id: 0,
+        data: vec![index_column, values.clone()],
+    };
+
+    let num_lookups = 20;
+    let gates = (0..num_lookups)
+        .map(|i| CircuitGate::new(GateType::Lookup, Wire::for_row(i), vec![]))
+        .collect();
+    let mut witness: [Vec<Fr>; COLUMNS] = array::from_fn(|_| vec![Fr::zero(); num_lookups]);
+    for row in 0..num_lookups {
+        for lookup in 0..3 {
+            let i = (row + 3 * lookup) % values.len();
+            witness[1 + 2 * lookup][row] = Fr::from(i as u64);
+            witness[2 + 2 * lookup][row] = values[i];
+        }
+    }
+
+    let mut circuit = Circuit::new(gates, witness);
+    circuit.lookup_tables = vec![table];
+    check_circuit(circuit);
+}
+
+#[test]
+fn test_solidity_runtime_table() {
+    let num_lookups = 20;
+    let (gates, cfg) = runtime_tables::testing::create_circuit(num_lookups);
+    let (witness, runtime_table) = runtime_tables::testing::create_witness(num_lookups, &cfg);
+
+    let mut circuit = Circuit::new(gates, witness);
+    circuit.runtime_tables_setup = Some(vec![cfg]);
+    circuit.runtime_tables = vec![runtime_table];
+    check_circuit(circuit);
+}
+
+#[test]
+fn test_solidity_contract() {
+    let public = vec![Fr::from(3u8); 2];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fr>; COLUMNS] = array::from_fn(|_| vec![Fr::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let mut circuit = Circuit::new(gates, witness);
+    circuit.public = public;
+    let (index, proof, public) = circuit.prove();
+
+    let contract = solidity::verifier_contract(&index).unwrap();
+    assert!(contract.starts_with("// SPDX-License-Identifier: Apache-2.0"));
+    assert!(contract.contains("contract KimchiVerifier"));
+    let size = solidity::encode_proof(&index, &proof).unwrap().len();
+    assert!(contract.contains(&format!("function verify(uint256[{size}] calldata")));
+
+    // the contract is called like an external caller would
+    check_contract(&contract, &index, &proof, &public, true);
+
+    let mut bad_proof = proof.clone();
+    bad_proof.proof.blinding += Fr::one();
+    check_contract(&contract, &index, &bad_proof, &public, false);
+
+    let mut bad_public = public.clone();
+    bad_public[1] += Fr::one();
+    check_contract(&contract, &index, &proof, &bad_public, false);
+
+    // the selector must be the one of the declared function
+    let mut calldata = solidity::calldata(&index, &proof, &public).unwrap();
+    calldata[0] ^= 1;
+    assert!(solidity::execute_contract(&contract, &calldata, GAS_LIMIT).is_err());
+
+    // the proof is not accepted without enough gas: either the transaction
+    // runs out of gas, or a precompile call gets too little gas and fails
+    let calldata = solidity::calldata(&index, &proof, &public).unwrap();
+    let (_, gas_used) = solidity::execute_contract(&contract, &calldata, GAS_LIMIT).unwrap();
+    assert!(!matches!(
+        solidity::execute_contract(&contract, &calldata, gas_used - 1),
+        Ok((true, _))
+    ));
+    assert_eq!(
+        solidity::execute_contract(&contract, &calldata, 21_000),
+        Err("out of gas".to_string())
+    );
+}
+
+/// Compiles the generated contract with `solc`, which the other tests do not
+/// need: they only run its assembly block. Run it with `--ignored` where
+/// `solc` is installed.
+#[test]
+#[ignore = "requires solc on the PATH"]
+fn test_solidity_contract_compiles() {
+    let public = vec![Fr::from(3u8); 2];
+    let gates = create_circuit(0, public.len());
+    let mut witness: [Vec<Fr>; COLUMNS] = array::from_fn(|_| vec![Fr::zero(); gates.len()]);
+    fill_in_witness(0, &mut witness, &public);
+    let mut circuit = Circuit::new(gates, witness);
+    circuit.public = public;
+    let (index, _, _) = circuit.prove();
+    let contract = solidity::verifier_contract(&index).unwrap();
+
+    let path = std::env::temp_dir().join(format!("KimchiVerifier-{}.sol", std::process::id()));
+    std::fs::write(&path, contract).unwrap();
+    let output = std::process::Command::new("solc")
+        .arg("--bin")
+        .arg(&path)
+        .output()
+        .expect("solc is not installed");
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        output.status.success(),
+        "solc failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -25,9 +25,11 @@ use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Polynomial};
 use mina_poseidon::{sponge::ScalarChallenge, FqSponge};
 use o1_utils::ExtendedDensePolynomial;
 use poly_commitment::{
+    accumulator::Accumulator,
     commitment::{
         absorb_commitment, combined_inner_product, BatchEvaluationProof, Evaluation, PolyComm,
     },
+    ipa::OpeningProof as IpaOpeningProof,
     OpenProof, SRS as _,
 };
 use rand::thread_rng;
@@ -1246,3 +1248,91 @@ where
         Err(VerifyError::OpenProof)
     }
 }
+
+/// Verifies a batch of zk-proofs using the inner product argument like
+/// [`batch_verify`], except for the final check of their opening proofs,
+/// which requires a multi-scalar multiplication of the size of the SRS. This
+/// check is deferred to the returned accumulators, in the order of the proofs,
+/// which can be checked later with
+/// [`SRS::check_accumulators`](poly_commitment::ipa::SRS::check_accumulators)
+/// or folded with [`accumulate`].
+///
+/// # Errors
+///
+/// Will give error if `srs` of `proof` is invalid or the succinct verification
+/// fails.
+pub fn succinct_verify<G, EFqSponge, EFrSponge>(
+    group_map: &G::Map,
+    proofs: &[Context<G, IpaOpeningProof<G>>],
+) -> Result<Vec<Accumulator<G>>>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    if proofs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let srs = proofs[0].verifier_index.srs();
+    for &Context { verifier_index, .. } in proofs {
+        if verifier_index.srs().max_poly_size() != srs.max_poly_size() {
+            return Err(VerifyError::DifferentSRS);
+        }
+    }
+
+    let mut batch = vec![];
+    for &Context {
+        verifier_index,
+        proof,
+        public_input,
+    } in proofs
+    {
+        batch.push(to_batch::<G, EFqSponge, EFrSponge, IpaOpeningProof<G>>(
+            verifier_index,
+            proof,
+            public_input,
+        )?);
+    }
+
+    srs.succinct_verify(group_map, &mut batch, &mut thread_rng())
+        .ok_or(VerifyError::OpenProof)
+}
+
+/// Verifies a batch of zk-proofs with [`succinct_verify`], and folds their
+/// accumulators into a single one. Checking the returned accumulator with
+/// [`SRS::check_accumulators`](poly_commitment::ipa::SRS::check_accumulators)
+/// completes the verification of the proofs.
+///
+/// The folding is proven by the returned opening proof, which can be checked
+/// by [`SRS::verify_accumulation`](poly_commitment::ipa::SRS::verify_accumulation)
+/// given the accumulators of the proofs and a new `EFqSponge`.
+///
+/// # Errors
+///
+/// Will give error if `srs` of `proof` is invalid, or if the succinct
+/// verification or the folding fails.
+pub fn accumulate<G, EFqSponge, EFrSponge>(
+    group_map: &G::Map,
+    proofs: &[Context<G, IpaOpeningProof<G>>],
+) -> Result<(Accumulator<G>, IpaOpeningProof<G>)>
+where
+    G: KimchiCurve,
+    G::BaseField: PrimeField,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+{
+    let accumulators = succinct_verify::<G, EFqSponge, EFrSponge>(group_map, proofs)?;
+    if accumulators.is_empty() {
+        return Err(VerifyError::OpenProof);
+    }
+    let srs = proofs[0].verifier_index.srs();
+    srs.accumulate(
+        group_map,
+        &accumulators,
+        EFqSponge::new(G::other_curve_sponge_params()),
+        &mut thread_rng(),
+    )
+    .ok_or(VerifyError::OpenProof)
+}
@@ -5,6 +5,7 @@ use crate::{
     alphas::Alphas,
     circuits::{
         berkeley_columns::{BerkeleyChallengeTerm, Column},
+        constraints::FeatureFlags,
         expr::{Linearization, PolishToken},
         lookup::{index::LookupSelectors, lookups::LookupInfo},
         polynomials::permutation::{vanishes_on_last_n_rows, zk_w},
@@ -336,6 +337,26 @@ impl<G: KimchiCurve, OpeningProof: OpenProof<G>> VerifierIndex<G, OpeningProof>
         self.w.get_or_init(|| zk_w(self.domain, self.zk_rows))
     }
 
+    /// The features used by the circuit, from the commitments to the
+    /// selectors of the optional gates and from the lookup configuration.
+    /// They determine the linearization, which is not serialized.
+    pub fn feature_flags(&self) -> FeatureFlags {
+        FeatureFlags {
+            range_check0: self.range_check0_comm.is_some(),
+            range_check1: self.range_check1_comm.is_some(),
+            foreign_field_add: self.foreign_field_add_comm.is_some(),
+            foreign_field_mul: self.foreign_field_mul_comm.is_some(),
+            xor: self.xor_comm.is_some(),
+            rot: self.rot_comm.is_some(),
+            lookup_features: self
+                .lookup_index
+                .as_ref()
+                .map_or(FeatureFlags::default().lookup_features, |index| {
+                    index.lookup_info.features
+                }),
+        }
+    }
+
     /// Deserializes a [`VerifierIndex`] from a file, given a pointer to an SRS and an optional offset in the file.
     ///
     /// # Errors
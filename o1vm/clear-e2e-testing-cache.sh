@@ -12,6 +12,7 @@ rm -rf op-program-db-for-latest-l2-block \
   state.json \
   meta.json \
   out.json \
+  proofs.bin \
   cpu.pprof
 
 RUNTIME=$(($(date +%s) - START))
@@ -9,6 +9,7 @@ use core::{
 use libflate::zlib::{Decoder, Encoder};
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Keccak256};
 use std::io::{Read, Write};
 
 pub const PAGE_ADDRESS_SIZE: u32 = 12;
@@ -79,6 +80,44 @@ pub struct ParsePreimageKeyError(String);
 #[derive(Debug, PartialEq)]
 pub struct PreimageKey(pub [u8; 32]);
 
+/// Type of a preimage key, encoded in its first byte, as specified by the
+/// Optimism fault proof program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreimageKeyType {
+    /// Data local to the program, like the boot information
+    Local = 1,
+    /// The remaining 31 bytes are the keccak256 digest of the preimage
+    Keccak256 = 2,
+    GlobalGeneric = 3,
+    /// The remaining 31 bytes are the sha256 digest of the preimage
+    Sha256 = 4,
+    Blob = 5,
+    Precompile = 6,
+}
+
+impl TryFrom<u8> for PreimageKeyType {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        use PreimageKeyType::*;
+        match value {
+            1 => Ok(Local),
+            2 => Ok(Keccak256),
+            3 => Ok(GlobalGeneric),
+            4 => Ok(Sha256),
+            5 => Ok(Blob),
+            6 => Ok(Precompile),
+            _ => Err(value),
+        }
+    }
+}
+
+impl PreimageKey {
+    pub fn key_type(&self) -> Option<PreimageKeyType> {
+        PreimageKeyType::try_from(self.0[0]).ok()
+    }
+}
+
 use std::str::FromStr;
 
 impl FromStr for PreimageKey {
@@ -203,6 +242,46 @@ impl Display for State {
     }
 }
 
+impl State {
+    /// Keccak256 digest of the memory. The pages are hashed by increasing
+    /// index, and each page is padded with zeroes up to [PAGE_SIZE] so that the
+    /// root does not depend on how the pages have been loaded.
+    pub fn memory_root(&self) -> [u8; 32] {
+        let mut pages: Vec<&Page> = self.memory.iter().collect();
+        pages.sort_by_key(|page| page.index);
+        let mut hasher = Keccak256::new();
+        for page in pages {
+            hasher.update(page.index.to_be_bytes());
+            hasher.update(&page.data);
+            let padding = (PAGE_SIZE as usize).saturating_sub(page.data.len());
+            hasher.update(vec![0u8; padding]);
+        }
+        hasher.finalize().into()
+    }
+
+    /// Keccak256 commitment to the state of the VM, i.e. the memory root, the
+    /// instruction pointers, the registers and the status of the execution.
+    /// The hints and the preimage being read are not part of the commitment,
+    /// as they are provided by the preimage oracle.
+    pub fn commitment(&self) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.memory_root());
+        hasher.update(self.preimage_key);
+        hasher.update(self.preimage_offset.to_be_bytes());
+        hasher.update(self.pc.to_be_bytes());
+        hasher.update(self.next_pc.to_be_bytes());
+        hasher.update(self.lo.to_be_bytes());
+        hasher.update(self.hi.to_be_bytes());
+        hasher.update(self.heap.to_be_bytes());
+        hasher.update([self.exit, self.exited as u8]);
+        hasher.update(self.step.to_be_bytes());
+        for register in self.registers.iter() {
+            hasher.update(register.to_be_bytes());
+        }
+        hasher.finalize().into()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HostProgram {
     pub name: String,
@@ -434,16 +513,24 @@ mod tests {
 
     #[test]
     fn test_meta_deserialize_from_file() {
-        let path = "meta_test.json";
-        let mut output =
-            File::create(path).unwrap_or_else(|_| panic!("Could not create file {path}"));
+        let dir = std::env::temp_dir().join(format!("o1vm-meta-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("Could not create temporary directory");
+        let path = dir.join("meta_test.json");
+        let mut output = File::create(&path)
+            .unwrap_or_else(|_| panic!("Could not create file {}", path.display()));
         write!(output, "{}", META_SAMPLE)
-            .unwrap_or_else(|_| panic!("Could not write to file {path}"));
+            .unwrap_or_else(|_| panic!("Could not write to file {}", path.display()));
 
-        let input = File::open(path).unwrap_or_else(|_| panic!("Could not open file {path}"));
+        let input =
+            File::open(&path).unwrap_or_else(|_| panic!("Could not open file {}", path.display()));
         let buffered = BufReader::new(input);
-        let read: Meta = serde_json::from_reader(buffered)
-            .unwrap_or_else(|_| panic!("Failed to deserialize metadata from file {path}"));
+        let read: Meta = serde_json::from_reader(buffered).unwrap_or_else(|_| {
+            panic!(
+                "Failed to deserialize metadata from file {}",
+                path.display()
+            )
+        });
+        std::fs::remove_dir_all(&dir).expect("Could not remove temporary directory");
 
         let expected = Meta {
             symbols: vec![
@@ -103,10 +103,40 @@ impl From<MipsVmConfigurationArgs> for VmConfiguration {
 
 #[derive(Parser, Debug, Clone)]
 pub struct RunArgs {
-    #[arg(long = "preimage-db-dir", value_name = "PREIMAGE_DB_DIR")]
+    #[arg(
+        long = "preimage-db-dir",
+        value_name = "PREIMAGE_DB_DIR",
+        help = "directory of preimages, using the layout of the op-program data directory. Without host program, the preimages are served from this directory"
+    )]
     pub preimage_db_dir: Option<String>,
+    #[arg(
+        long = "record-preimages",
+        requires = "preimage_db_dir",
+        help = "record the preimages served by the host program in the preimage directory, to replay the run offline"
+    )]
+    pub record_preimages: bool,
+    #[arg(
+        long = "trace",
+        value_name = "FILE",
+        help = "write the trace of the execution, as JSON lines or as MessagePack if the extension is .msgpack or .bin"
+    )]
+    pub trace: Option<String>,
+    #[arg(
+        long = "witness-workers",
+        value_name = "N",
+        default_value_t = 0,
+        help = "number of threads generating the witness and the proof of the segments while the execution goes on. With 0, the segments are proven sequentially by the main thread"
+    )]
+    pub witness_workers: usize,
     #[arg(long = "srs-filepath", value_name = "SRS_CACHE")]
     pub srs_cache: Option<String>,
+    #[arg(
+        long = "proofs",
+        value_name = "FILE",
+        default_value = "proofs.bin",
+        help = "file the segment proofs are written to as soon as they are generated, as a stream of MessagePack values"
+    )]
+    pub proofs_file: String,
     // it's important that vm_cfg is last in order to properly parse the host field
     #[command(flatten)]
     pub vm_cfg: MipsVmConfigurationArgs,
@@ -126,6 +156,47 @@ pub struct GenStateJsonArgs {
     pub output: String,
 }
 
+#[derive(Parser, Debug, Clone)]
+pub struct DiffArgs {
+    #[arg(value_name = "TRACE", help = "first trace file")]
+    pub left: String,
+    #[arg(value_name = "TRACE", help = "second trace file")]
+    pub right: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct BreakArgs {
+    #[arg(
+        long = "break-at",
+        value_name = "ADDR",
+        required = true,
+        help = "breakpoint address (in hexadecimal), can be given multiple times"
+    )]
+    pub break_at: Vec<String>,
+    #[arg(
+        long = "hit",
+        value_name = "N",
+        default_value = "1",
+        help = "stop at the N-th time a breakpoint is reached"
+    )]
+    pub hit: u64,
+    #[arg(long = "preimage-db-dir", value_name = "PREIMAGE_DB_DIR")]
+    pub preimage_db_dir: Option<String>,
+    #[arg(long = "trace", value_name = "FILE")]
+    pub trace: Option<String>,
+    // it's important that vm_cfg is last in order to properly parse the host field
+    #[command(flatten)]
+    pub vm_cfg: MipsVmConfigurationArgs,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Debug {
+    /// Print the first step at which two execution traces differ
+    Diff(DiffArgs),
+    /// Execute the program until a breakpoint address, and print the state
+    Break(Box<BreakArgs>),
+}
+
 #[derive(Subcommand, Clone, Debug)]
 pub enum Cannon {
     Run(RunArgs),
@@ -133,4 +204,6 @@ pub enum Cannon {
     TestPreimageRead(RunArgs),
     #[command(name = "gen-state-json")]
     GenStateJson(GenStateJsonArgs),
+    #[command(subcommand)]
+    Debug(Debug),
 }
@@ -1,6 +1,7 @@
 use clap::Parser;
 
 pub mod cannon;
+pub mod riscv;
 
 #[derive(Parser, Debug, Clone)]
 #[command(
@@ -11,4 +12,6 @@ pub mod cannon;
 pub enum Commands {
     #[command(subcommand)]
     Cannon(cannon::Cannon),
+    #[command(subcommand)]
+    Riscv(riscv::Riscv),
 }
@@ -0,0 +1,125 @@
+use crate::cannon::*;
+use clap::{arg, Parser, Subcommand};
+
+#[derive(Parser, Debug, Clone)]
+pub struct RiscvVmConfigurationArgs {
+    #[arg(
+        short = 'i',
+        long,
+        value_name = "FILE",
+        help = "RISC-V 32 bits ELF file to execute"
+    )]
+    input: String,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        default_value = "out.json",
+        help = "output state file"
+    )]
+    output: String,
+
+    #[arg(
+        long = "proof-at",
+        short = 'p',
+        long,
+        value_name = "FREQ",
+        default_value = "never",
+        help = "steps at which the instructions executed so far are proven, in addition to each full chunk"
+    )]
+    proof_at: StepFrequency,
+
+    #[arg(
+        long = "snapshot-fmt",
+        value_name = "FORMAT",
+        default_value = "state-%d.json"
+    )]
+    snapshot_fmt: String,
+
+    #[arg(long = "stop-at", value_name = "FREQ", default_value = "never")]
+    stop_at: StepFrequency,
+
+    #[arg(long = "info-at", value_name = "FREQ", default_value = "never")]
+    info_at: StepFrequency,
+
+    #[arg(
+        long = "snapshot-state-at",
+        value_name = "FREQ",
+        default_value = "never"
+    )]
+    snapshot_state_at: StepFrequency,
+
+    #[arg(
+        long = "halt-address",
+        value_name = "ADDR",
+        help = "halt address (in hexadecimal). Jumping to this address will halt the program."
+    )]
+    halt_address: Option<String>,
+
+    #[arg(name = "host", value_name = "HOST", help = "host program specification <host program> [host program arguments]", num_args = 1.., last = true)]
+    host: Vec<String>,
+}
+
+impl From<RiscvVmConfigurationArgs> for VmConfiguration {
+    fn from(cfg: RiscvVmConfigurationArgs) -> Self {
+        VmConfiguration {
+            input_state_file: cfg.input,
+            output_state_file: cfg.output,
+            metadata_file: None,
+            proof_at: cfg.proof_at,
+            stop_at: cfg.stop_at,
+            snapshot_state_at: cfg.snapshot_state_at,
+            info_at: cfg.info_at,
+            // Proofs are not written on disk
+            proof_fmt: String::new(),
+            snapshot_fmt: cfg.snapshot_fmt,
+            pprof_cpu: false,
+            halt_address: cfg.halt_address.map(|s| {
+                u32::from_str_radix(s.trim_start_matches("0x"), 16)
+                    .expect("Failed to parse halt address as hex")
+            }),
+            host: if cfg.host.is_empty() {
+                None
+            } else {
+                Some(HostProgram {
+                    name: cfg.host[0].to_string(),
+                    arguments: cfg.host[1..].to_vec(),
+                })
+            },
+        }
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct RunArgs {
+    #[arg(
+        long = "preimage-db-dir",
+        value_name = "PREIMAGE_DB_DIR",
+        help = "directory of preimages, using the layout of the op-program data directory. Without host program, the preimages are served from this directory"
+    )]
+    pub preimage_db_dir: Option<String>,
+    #[arg(
+        long = "record-preimages",
+        requires = "preimage_db_dir",
+        help = "record the preimages served by the host program in the preimage directory, to replay the run offline"
+    )]
+    pub record_preimages: bool,
+    #[arg(long = "srs-filepath", value_name = "SRS_CACHE")]
+    pub srs_cache: Option<String>,
+    #[arg(
+        long = "proofs",
+        value_name = "FILE",
+        default_value = "proofs.bin",
+        help = "file the segment proofs are written to as soon as they are generated, as a stream of MessagePack values"
+    )]
+    pub proofs_file: String,
+    // it's important that vm_cfg is last in order to properly parse the host field
+    #[command(flatten)]
+    pub vm_cfg: RiscvVmConfigurationArgs,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Riscv {
+    /// Execute a RISC-V 32 bits ELF binary, and prove its execution
+    Run(RunArgs),
+}
@@ -78,22 +78,22 @@ pub fn make_state<T: EndianParse>(file: ElfBytes<T>) -> Result<State, String> {
         let mut data = vec![0; page_size_usize];
         // Special case where all code fits in one page
         if first_page_index == last_page_index {
-            let data_length = code_section_end_address - code_section_starting_address;
+            let data_length = code_section_size;
             let page_offset = code_section_starting_address - start_page_address;
             data[page_offset..page_offset + data_length]
                 .copy_from_slice(&text_section_data[0..data_length]);
             data_offset += data_length;
         } else {
-            let data_length = if page_index == last_page_index {
-                code_section_end_address - end_page_address
-            } else {
-                page_size_usize
-            };
             let page_offset = if page_index == first_page_index {
                 code_section_starting_address - start_page_address
             } else {
                 0
             };
+            let data_length = if page_index == last_page_index {
+                code_section_end_address - end_page_address + 1
+            } else {
+                page_size_usize - page_offset
+            };
             data[page_offset..page_offset + data_length]
                 .copy_from_slice(&text_section_data[data_offset..data_offset + data_length]);
 
@@ -108,6 +108,19 @@ pub fn make_state<T: EndianParse>(file: ElfBytes<T>) -> Result<State, String> {
 
     // FIXME: add data section into memory for static data saved in the binary
 
+    // The program break starts at the first page following all the sections
+    // that are loaded in memory, as it is done by Linux. The heap grows from
+    // there, using the syscalls brk and mmap.
+    let heap = {
+        let end_of_sections = sections_by_name
+            .values()
+            .filter(|shdr| shdr.sh_flags & (elf::abi::SHF_ALLOC as u64) != 0)
+            .map(|shdr| (shdr.sh_addr + shdr.sh_size) as usize)
+            .max()
+            .unwrap_or(code_section_end_address + 1);
+        (((end_of_sections + page_size_usize - 1) / page_size_usize) * page_size_usize) as u32
+    };
+
     // FIXME: we're lucky that RISCV32i and MIPS have the same number of
     let registers: [u32; 32] = [0; 32];
 
@@ -133,7 +146,7 @@ pub fn make_state<T: EndianParse>(file: ElfBytes<T>) -> Result<State, String> {
         lo: 0,
         // FIXME: only because Cannon related
         hi: 0,
-        heap: 0,
+        heap,
         exit: 0,
         exited: false,
         step: 0,
@@ -24,6 +24,7 @@ pub mod registers;
 pub mod tests;
 #[cfg(test)]
 pub mod tests_helpers;
+pub mod trace;
 pub mod witness;
 
 pub use interpreter::{ITypeInstruction, Instruction, JTypeInstruction, RTypeInstruction};
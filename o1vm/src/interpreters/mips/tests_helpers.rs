@@ -8,7 +8,7 @@ use crate::{
     preimage_oracle::PreImageOracleT,
 };
 use rand::{CryptoRng, Rng, RngCore};
-use std::{fs, path::PathBuf};
+use std::{fs, path::PathBuf, sync::Arc};
 
 // FIXME: we should parametrize the tests with different fields.
 use ark_bn254::Fr as Fp;
@@ -49,40 +49,46 @@ where
             // Initializing with random data
             (
                 0,
-                (0..PAGE_SIZE).map(|_| rng.gen_range(0u8..=255)).collect(),
+                Arc::new((0..PAGE_SIZE).map(|_| rng.gen_range(0u8..=255)).collect()),
             ),
             // Executable memory. Allocating 4 * 4kB
-            (PAGE_INDEX_EXECUTABLE_MEMORY, vec![0; PAGE_SIZE as usize]),
+            (
+                PAGE_INDEX_EXECUTABLE_MEMORY,
+                Arc::new(vec![0; PAGE_SIZE as usize]),
+            ),
             (
                 PAGE_INDEX_EXECUTABLE_MEMORY + 1,
-                vec![0; PAGE_SIZE as usize],
+                Arc::new(vec![0; PAGE_SIZE as usize]),
             ),
             (
                 PAGE_INDEX_EXECUTABLE_MEMORY + 2,
-                vec![0; PAGE_SIZE as usize],
+                Arc::new(vec![0; PAGE_SIZE as usize]),
             ),
             (
                 PAGE_INDEX_EXECUTABLE_MEMORY + 3,
-                vec![0; PAGE_SIZE as usize],
+                Arc::new(vec![0; PAGE_SIZE as usize]),
             ),
         ],
         last_memory_accesses: [0; 3],
         memory_write_index: vec![
             // Read/write memory
-            (0, vec![0; PAGE_SIZE as usize]),
+            (0, Arc::new(vec![0; PAGE_SIZE as usize])),
             // Executable memory. Allocating 4 * 4kB
-            (PAGE_INDEX_EXECUTABLE_MEMORY, vec![0; PAGE_SIZE as usize]),
+            (
+                PAGE_INDEX_EXECUTABLE_MEMORY,
+                Arc::new(vec![0; PAGE_SIZE as usize]),
+            ),
             (
                 PAGE_INDEX_EXECUTABLE_MEMORY + 1,
-                vec![0; PAGE_SIZE as usize],
+                Arc::new(vec![0; PAGE_SIZE as usize]),
             ),
             (
                 PAGE_INDEX_EXECUTABLE_MEMORY + 2,
-                vec![0; PAGE_SIZE as usize],
+                Arc::new(vec![0; PAGE_SIZE as usize]),
             ),
             (
                 PAGE_INDEX_EXECUTABLE_MEMORY + 3,
-                vec![0; PAGE_SIZE as usize],
+                Arc::new(vec![0; PAGE_SIZE as usize]),
             ),
         ],
         last_memory_write_index_accesses: [0; 3],
@@ -105,6 +111,8 @@ where
         preimage_key: None,
         keccak_env: None,
         hash_counter: 0,
+        tracer: None,
+        generate_witness: true,
     };
     // Initialize general purpose registers with random values
     for reg in env.registers.general_purpose.iter_mut() {
@@ -124,8 +132,9 @@ pub(crate) fn write_instruction(
     let instr_pointer: u32 = env.get_instruction_pointer().try_into().unwrap();
     let page = instr_pointer >> PAGE_ADDRESS_SIZE;
     let page_address = (instr_pointer & PAGE_ADDRESS_MASK) as usize;
-    env.memory[page as usize].1[page_address] = ((instr >> 24) & 0xFF) as u8;
-    env.memory[page as usize].1[page_address + 1] = ((instr >> 16) & 0xFF) as u8;
-    env.memory[page as usize].1[page_address + 2] = ((instr >> 8) & 0xFF) as u8;
-    env.memory[page as usize].1[page_address + 3] = (instr & 0xFF) as u8;
+    let memory = Arc::make_mut(&mut env.memory[page as usize].1);
+    memory[page_address] = ((instr >> 24) & 0xFF) as u8;
+    memory[page_address + 1] = ((instr >> 16) & 0xFF) as u8;
+    memory[page_address + 2] = ((instr >> 8) & 0xFF) as u8;
+    memory[page_address + 3] = (instr & 0xFF) as u8;
 }
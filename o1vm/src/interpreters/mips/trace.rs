@@ -0,0 +1,347 @@
+//! Execution traces of the MIPS interpreter, used to debug executions
+//! diverging from the reference implementation.
+//!
+//! When a [Tracer] is attached to the witness environment
+//! ([crate::interpreters::mips::witness::Env::tracer]), each step is recorded
+//! as a [TraceStep] containing the instruction pointer, the decoded
+//! instruction, the registers modified by the instruction, the memory accesses
+//! and the syscall number if any. The steps are streamed either as JSON lines
+//! or as MessagePack values, depending on the extension of the trace file (see
+//! [TraceFormat::from_path]).
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use super::registers::{Registers, NUM_REGISTERS};
+
+/// A register modified by an instruction. The register indices are the ones
+/// of [Registers], e.g. [super::registers::REGISTER_HI] for `hi`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterDiff {
+    pub register: usize,
+    pub before: u32,
+    pub after: u32,
+}
+
+/// Contiguous bytes read or written by an instruction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryAccess {
+    pub address: u32,
+    pub write: bool,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub step: u64,
+    pub pc: u32,
+    pub instruction: String,
+    /// The 32 bits word encoding the instruction
+    pub opcode: u32,
+    pub registers: Vec<RegisterDiff>,
+    pub memory: Vec<MemoryAccess>,
+    /// The syscall number, i.e. the value of `$v0`, for syscall instructions
+    pub syscall: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// A stream of MessagePack values, more compact
+    MessagePack,
+}
+
+impl TraceFormat {
+    /// Files with the extension `msgpack` or `bin` use MessagePack, all the
+    /// others use JSON lines.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("msgpack") | Some("bin") => TraceFormat::MessagePack,
+            _ => TraceFormat::Jsonl,
+        }
+    }
+}
+
+fn to_io_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Record the steps executed by the interpreter
+pub struct Tracer {
+    writer: Box<dyn Write>,
+    format: TraceFormat,
+    registers: Registers<u32>,
+    current: Option<TraceStep>,
+}
+
+impl Tracer {
+    pub fn new(writer: Box<dyn Write>, format: TraceFormat) -> Self {
+        Tracer {
+            writer,
+            format,
+            registers: Registers::default(),
+            current: None,
+        }
+    }
+
+    /// Create a tracer writing in the given file, using the format given by
+    /// its extension.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let format = TraceFormat::from_path(&path);
+        let file = File::create(path)?;
+        Ok(Self::new(Box::new(BufWriter::new(file)), format))
+    }
+
+    /// Start recording a step, before the instruction is executed
+    pub fn begin_step(
+        &mut self,
+        step: u64,
+        instruction: String,
+        opcode: u32,
+        syscall: Option<u32>,
+        registers: &Registers<u32>,
+    ) {
+        self.registers = registers.clone();
+        self.current = Some(TraceStep {
+            step,
+            pc: registers.current_instruction_pointer,
+            instruction,
+            opcode,
+            registers: vec![],
+            memory: vec![],
+            syscall,
+        });
+    }
+
+    /// Record a memory access of the current step. Accesses to contiguous
+    /// addresses of the same kind are merged.
+    pub fn record_memory_access(&mut self, address: u32, value: u8, write: bool) {
+        if let Some(step) = self.current.as_mut() {
+            match step.memory.last_mut() {
+                Some(last)
+                    if last.write == write
+                        && last.address.wrapping_add(last.bytes.len() as u32) == address =>
+                {
+                    last.bytes.push(value)
+                }
+                _ => step.memory.push(MemoryAccess {
+                    address,
+                    write,
+                    bytes: vec![value],
+                }),
+            }
+        }
+    }
+
+    /// Finish the current step, after the instruction has been executed, and
+    /// write it in the trace.
+    pub fn end_step(&mut self, registers: &Registers<u32>) -> io::Result<()> {
+        let Some(mut step) = self.current.take() else {
+            return Ok(());
+        };
+        step.registers = (0..NUM_REGISTERS)
+            .filter(|&i| self.registers[i] != registers[i])
+            .map(|i| RegisterDiff {
+                register: i,
+                before: self.registers[i],
+                after: registers[i],
+            })
+            .collect();
+        match self.format {
+            TraceFormat::Jsonl => {
+                serde_json::to_writer(&mut self.writer, &step).map_err(to_io_error)?;
+                self.writer.write_all(b"\n")
+            }
+            TraceFormat::MessagePack => {
+                rmp_serde::encode::write(&mut self.writer, &step).map_err(to_io_error)
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Iterate over the steps of a trace
+pub struct TraceReader<R: BufRead> {
+    reader: R,
+    format: TraceFormat,
+}
+
+impl TraceReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let format = TraceFormat::from_path(&path);
+        let file = File::open(path)?;
+        Ok(Self::new(BufReader::new(file), format))
+    }
+}
+
+impl<R: BufRead> TraceReader<R> {
+    pub fn new(reader: R, format: TraceFormat) -> Self {
+        TraceReader { reader, format }
+    }
+}
+
+impl<R: BufRead> Iterator for TraceReader<R> {
+    type Item = io::Result<TraceStep>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.format {
+            TraceFormat::Jsonl => {
+                let mut line = String::new();
+                match self.reader.read_line(&mut line) {
+                    Ok(0) => None,
+                    Ok(_) => Some(serde_json::from_str(&line).map_err(to_io_error)),
+                    Err(e) => Some(Err(e)),
+                }
+            }
+            TraceFormat::MessagePack => match self.reader.fill_buf() {
+                Ok([]) => None,
+                Ok(_) => {
+                    Some(rmp_serde::decode::from_read(self.reader.by_ref()).map_err(to_io_error))
+                }
+                Err(e) => Some(Err(e)),
+            },
+        }
+    }
+}
+
+/// The first step at which two traces differ. One of the steps is missing if
+/// the corresponding trace is shorter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the step in the traces
+    pub index: usize,
+    pub left: Option<TraceStep>,
+    pub right: Option<TraceStep>,
+}
+
+/// Return the first step at which the two traces differ, if any
+pub fn first_divergence<L, R>(left: L, right: R) -> io::Result<Option<Divergence>>
+where
+    L: IntoIterator<Item = io::Result<TraceStep>>,
+    R: IntoIterator<Item = io::Result<TraceStep>>,
+{
+    let mut left = left.into_iter();
+    let mut right = right.into_iter();
+    let mut index = 0;
+    loop {
+        let (left, right) = (left.next().transpose()?, right.next().transpose()?);
+        match (left, right) {
+            (None, None) => return Ok(None),
+            (Some(l), Some(r)) if l == r => index += 1,
+            (left, right) => return Ok(Some(Divergence { index, left, right })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    /// A writer whose content can be read after the tracer is done
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn record_steps(format: TraceFormat) -> Vec<u8> {
+        let buffer = SharedBuffer::default();
+        let mut tracer = Tracer::new(Box::new(buffer.clone()), format);
+        let mut registers = Registers::<u32>::default();
+        for step in 0..3 {
+            tracer.begin_step(step, "NoOp".to_string(), 0, None, &registers);
+            tracer.record_memory_access(0x1000, 1, false);
+            tracer.record_memory_access(0x1001, 2, false);
+            tracer.record_memory_access(0x1002, 3, true);
+            registers.general_purpose[4] += 1;
+            registers.current_instruction_pointer += 4;
+            tracer.end_step(&registers).unwrap();
+        }
+        tracer.flush().unwrap();
+        let bytes = buffer.0.borrow().clone();
+        bytes
+    }
+
+    #[test]
+    fn test_trace_roundtrip() {
+        for format in [TraceFormat::Jsonl, TraceFormat::MessagePack] {
+            let bytes = record_steps(format);
+            let steps = TraceReader::new(&bytes[..], format)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(steps.len(), 3);
+            assert_eq!(steps[2].step, 2);
+            assert_eq!(steps[2].pc, 8);
+            assert_eq!(
+                steps[2].registers,
+                vec![
+                    RegisterDiff {
+                        register: 4,
+                        before: 2,
+                        after: 3
+                    },
+                    RegisterDiff {
+                        register: super::super::registers::REGISTER_CURRENT_IP,
+                        before: 8,
+                        after: 12
+                    }
+                ]
+            );
+            assert_eq!(
+                steps[0].memory,
+                vec![
+                    MemoryAccess {
+                        address: 0x1000,
+                        write: false,
+                        bytes: vec![1, 2]
+                    },
+                    MemoryAccess {
+                        address: 0x1002,
+                        write: true,
+                        bytes: vec![3]
+                    }
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let bytes = record_steps(TraceFormat::Jsonl);
+        let steps = TraceReader::new(&bytes[..], TraceFormat::Jsonl)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        let trace = || steps.clone().into_iter().map(Ok);
+
+        assert_eq!(first_divergence(trace(), trace()).unwrap(), None);
+
+        let mut diverging = steps.clone();
+        diverging[1].registers[0].after = 42;
+        let divergence = first_divergence(trace(), diverging.clone().into_iter().map(Ok))
+            .unwrap()
+            .unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.left, Some(steps[1].clone()));
+        assert_eq!(divergence.right, Some(diverging[1].clone()));
+
+        let divergence = first_divergence(trace(), trace().take(2)).unwrap().unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.right, None);
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
                 RTypeInstruction,
             },
             registers::Registers,
+            trace::Tracer,
         },
     },
     lookups::{Lookup, LookupTableIDs},
@@ -34,6 +35,7 @@ use std::{
     array,
     fs::File,
     io::{BufWriter, Write},
+    sync::Arc,
 };
 
 // TODO: do we want to be more restrictive and refer to the number of accesses
@@ -101,11 +103,14 @@ impl Default for LookupMultiplicities {
 /// machine has access to its internal state and some external memory. In
 /// addition to that, it has access to the environment of the Keccak interpreter
 /// that is used to verify the preimage requested during the execution.
+///
+/// The memory pages are shared with the snapshots of the environment, see
+/// [EnvSnapshot], and copied on the first write following a snapshot.
 pub struct Env<Fp, PreImageOracle: PreImageOracleT> {
     pub instruction_counter: u64,
-    pub memory: Vec<(u32, Vec<u8>)>,
+    pub memory: Vec<(u32, Arc<Vec<u8>>)>,
     pub last_memory_accesses: [usize; 3],
-    pub memory_write_index: Vec<(u32, Vec<u64>)>,
+    pub memory_write_index: Vec<(u32, Arc<Vec<u64>>)>,
     pub last_memory_write_index_accesses: [usize; 3],
     pub registers: Registers<u32>,
     pub registers_write_index: Registers<u64>,
@@ -125,6 +130,38 @@ pub struct Env<Fp, PreImageOracle: PreImageOracleT> {
     pub keccak_env: Option<KeccakEnv<Fp>>,
     pub hash_counter: u64,
     pub lookup_multiplicities: LookupMultiplicities,
+    /// Records the steps executed, see [super::trace]
+    pub tracer: Option<Tracer>,
+    /// Whether the steps fill the scratch and lookup states. Without it, the
+    /// steps only update the state of the VM, which is enough to take
+    /// snapshots of the execution, see [crate::pickles::chunk].
+    pub generate_witness: bool,
+}
+
+/// The part of the witness environment carried from one step to the next,
+/// i.e. everything but the per-step scratch and lookup states, the preimage
+/// oracle and the statistics. Contrary to [State], it includes the write
+/// indices of the memory and registers, so that an environment restored with
+/// [Env::restore] generates the same witness as the one it was taken from.
+///
+/// A snapshot shares the memory pages of the environment: taking it does not
+/// copy the memory, and only the pages written after it are copied.
+#[derive(Clone)]
+pub struct EnvSnapshot<Fp> {
+    pub instruction_counter: u64,
+    pub memory: Vec<(u32, Arc<Vec<u8>>)>,
+    pub last_memory_accesses: [usize; 3],
+    pub memory_write_index: Vec<(u32, Arc<Vec<u64>>)>,
+    pub last_memory_write_index_accesses: [usize; 3],
+    pub registers: Registers<u32>,
+    pub registers_write_index: Registers<u64>,
+    pub halt: bool,
+    pub syscall_env: SyscallEnv,
+    pub preimage: Option<Vec<u8>>,
+    pub preimage_bytes_read: u64,
+    pub preimage_key: Option<[u8; 32]>,
+    pub keccak_env: Option<KeccakEnv<Fp>>,
+    pub hash_counter: u64,
 }
 
 fn fresh_scratch_state<Fp: Field, const N: usize>() -> [Fp; N] {
@@ -178,6 +215,9 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp,
     }
 
     fn add_lookup(&mut self, lookup: Lookup<Self::Variable>) {
+        if !self.generate_witness {
+            return;
+        }
         let mut add_value = |x: Fp| {
             self.lookup_state_idx += 1;
             self.lookup_state.push(x);
@@ -217,6 +257,8 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp,
                 }
                 LookupTableIDs::SparseLookup => self.lookup_multiplicities.sparse_lookup[idx] += 1,
                 LookupTableIDs::ResetLookup => self.lookup_multiplicities.reset_lookup[idx] += 1,
+                // Only used by the RISC-V interpreter
+                LookupTableIDs::CompressedInstructionLookup => (),
                 // RAM ones, no multiplicities
                 LookupTableIDs::MemoryLookup => (),
                 LookupTableIDs::RegisterLookup => (),
@@ -295,6 +337,9 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp,
         let page_address = (addr & PAGE_ADDRESS_MASK) as usize;
         let memory_page_idx = self.get_memory_page_index(page);
         let value = self.memory[memory_page_idx].1[page_address];
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record_memory_access(addr, value, false)
+        }
         self.write_column(output, value.into());
         value.into()
     }
@@ -304,8 +349,11 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp,
         let page = addr >> PAGE_ADDRESS_SIZE;
         let page_address = (addr & PAGE_ADDRESS_MASK) as usize;
         let memory_page_idx = self.get_memory_page_index(page);
-        self.memory[memory_page_idx].1[page_address] =
-            value.try_into().expect("push_memory values fit in a u8");
+        let value: u8 = value.try_into().expect("push_memory values fit in a u8");
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer.record_memory_access(addr, value, true)
+        }
+        Arc::make_mut(&mut self.memory[memory_page_idx].1)[page_address] = value;
     }
 
     unsafe fn fetch_memory_access(
@@ -327,7 +375,8 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp,
         let page = addr >> PAGE_ADDRESS_SIZE;
         let page_address = (addr & PAGE_ADDRESS_MASK) as usize;
         let memory_write_index_page_idx = self.get_memory_access_page_index(page);
-        self.memory_write_index[memory_write_index_page_idx].1[page_address] = value;
+        Arc::make_mut(&mut self.memory_write_index[memory_write_index_page_idx].1)[page_address] =
+            value;
     }
 
     fn constant(x: u32) -> Self::Variable {
@@ -907,18 +956,18 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
 
         let syscall_env = SyscallEnv::create(&state);
 
-        let mut initial_memory: Vec<(u32, Vec<u8>)> = state
+        let initial_memory: Vec<(u32, Arc<Vec<u8>>)> = state
             .memory
             .into_iter()
             // Check that the conversion from page data is correct
-            .map(|page| (page.index, page.data))
+            .map(|page| {
+                let mut data = page.data;
+                data.extend((0..(page_size - data.len())).map(|_| 0u8));
+                assert_eq!(data.len(), page_size);
+                (page.index, Arc::new(data))
+            })
             .collect();
 
-        for (_address, initial_memory) in initial_memory.iter_mut() {
-            initial_memory.extend((0..(page_size - initial_memory.len())).map(|_| 0u8));
-            assert_eq!(initial_memory.len(), page_size);
-        }
-
         let memory_offsets = initial_memory
             .iter()
             .map(|(offset, _)| *offset)
@@ -947,12 +996,13 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
         };
 
         Env {
-            instruction_counter: state.step,
+            // The state stores the normalized instruction counter
+            instruction_counter: state.step * MAX_ACC,
             memory: initial_memory.clone(),
             last_memory_accesses: [0usize; 3],
             memory_write_index: memory_offsets
                 .iter()
-                .map(|offset| (*offset, vec![0u64; page_size]))
+                .map(|offset| (*offset, Arc::new(vec![0u64; page_size])))
                 .collect(),
             last_memory_write_index_accesses: [0usize; 3],
             registers: initial_registers.clone(),
@@ -973,6 +1023,8 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
             keccak_env: None,
             hash_counter: 0,
             lookup_multiplicities: LookupMultiplicities::new(),
+            tracer: None,
+            generate_witness: true,
         }
     }
 
@@ -993,10 +1045,15 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
     }
 
     pub fn write_column(&mut self, column: Column, value: u64) {
-        self.write_field_column(column, value.into())
+        if self.generate_witness {
+            self.write_field_column(column, value.into())
+        }
     }
 
     pub fn write_field_column(&mut self, column: Column, value: Fp) {
+        if !self.generate_witness {
+            return;
+        }
         match column {
             Column::ScratchState(idx) => self.scratch_state[idx] = value,
             Column::ScratchStateInverse(idx) => self.scratch_state_inverse[idx] = value,
@@ -1025,7 +1082,7 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
 
         // Memory not found; dynamically allocate
         let memory = vec![0u8; PAGE_SIZE as usize];
-        self.memory.push((page, memory));
+        self.memory.push((page, Arc::new(memory)));
         let i = self.memory.len() - 1;
         self.update_last_memory_access(i);
         i
@@ -1052,7 +1109,8 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
 
         // Memory not found; dynamically allocate
         let memory_write_index = vec![0u64; PAGE_SIZE as usize];
-        self.memory_write_index.push((page, memory_write_index));
+        self.memory_write_index
+            .push((page, Arc::new(memory_write_index)));
         let i = self.memory_write_index.len() - 1;
         self.update_last_memory_write_index_access(i);
         i
@@ -1242,15 +1300,49 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
         self.reset_scratch_state();
         self.reset_scratch_state_inverse();
         self.reset_lookup_state();
-        let (opcode, _instruction) = self.decode_instruction();
+        let (opcode, instruction) = self.decode_instruction();
 
         self.pp_info(&config.info_at, metadata, start);
         self.snapshot_state_at(&config.snapshot_state_at);
 
+        let step = self.normalized_instruction_counter();
+        if let Some(tracer) = self.tracer.as_mut() {
+            let is_syscall = matches!(
+                opcode,
+                Instruction::RType(
+                    RTypeInstruction::SyscallMmap
+                        | RTypeInstruction::SyscallExitGroup
+                        | RTypeInstruction::SyscallReadHint
+                        | RTypeInstruction::SyscallReadPreimage
+                        | RTypeInstruction::SyscallReadOther
+                        | RTypeInstruction::SyscallWriteHint
+                        | RTypeInstruction::SyscallWritePreimage
+                        | RTypeInstruction::SyscallWriteOther
+                        | RTypeInstruction::SyscallFcntl
+                        | RTypeInstruction::SyscallOther
+                )
+            );
+            // The syscall number is given in $v0
+            let syscall = is_syscall.then_some(self.registers.general_purpose[2]);
+            tracer.begin_step(
+                step,
+                format!("{:?}", opcode),
+                instruction,
+                syscall,
+                &self.registers,
+            );
+        }
+
         interpreter::interpret_instruction(self, opcode);
 
         self.instruction_counter = self.next_instruction_counter();
 
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer
+                .end_step(&self.registers)
+                .expect("Error writing the execution trace");
+        }
+
         config.halt_address.iter().for_each(|halt_address: &u32| {
             if self.registers.current_instruction_pointer == *halt_address {
                 debug!("Program jumped to halt address: {:#X}", halt_address);
@@ -1279,7 +1371,7 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
         opcode
     }
 
-    fn should_trigger_at(&self, at: &StepFrequency) -> bool {
+    pub fn should_trigger_at(&self, at: &StepFrequency) -> bool {
         let m: u64 = self.normalized_instruction_counter();
         match at {
             StepFrequency::Never => false,
@@ -1318,6 +1410,97 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
         None
     }
 
+    /// Build a cannon state from the current state of the execution.
+    pub fn to_state(&self) -> State {
+        let mut preimage_key = [0u8; 32];
+        for i in 0..8 {
+            let bytes = u32::to_be_bytes(self.registers.preimage_key[i]);
+            for j in 0..4 {
+                preimage_key[4 * i + j] = bytes[j]
+            }
+        }
+        let memory = self
+            .memory
+            .iter()
+            .map(|(idx, data)| Page {
+                index: *idx,
+                data: data.to_vec(),
+            })
+            .collect();
+        State {
+            pc: self.registers.current_instruction_pointer,
+            next_pc: self.registers.next_instruction_pointer,
+            step: self.normalized_instruction_counter(),
+            registers: self.registers.general_purpose,
+            lo: self.registers.lo,
+            hi: self.registers.hi,
+            heap: self.registers.heap_pointer,
+            // FIXME: it should be the exit code. We do not keep it in the
+            // witness atm
+            exit: if self.halt { 1 } else { 0 },
+            last_hint: self.syscall_env.last_hint.clone(),
+            exited: self.halt,
+            preimage_offset: self.registers.preimage_offset,
+            preimage_key,
+            memory,
+            preimage: self.preimage.clone(),
+        }
+    }
+
+    /// Take a snapshot of the environment, see [EnvSnapshot]
+    pub fn snapshot(&self) -> EnvSnapshot<Fp> {
+        EnvSnapshot {
+            instruction_counter: self.instruction_counter,
+            memory: self.memory.clone(),
+            last_memory_accesses: self.last_memory_accesses,
+            memory_write_index: self.memory_write_index.clone(),
+            last_memory_write_index_accesses: self.last_memory_write_index_accesses,
+            registers: self.registers.clone(),
+            registers_write_index: self.registers_write_index.clone(),
+            halt: self.halt,
+            syscall_env: self.syscall_env.clone(),
+            preimage: self.preimage.clone(),
+            preimage_bytes_read: self.preimage_bytes_read,
+            preimage_key: self.preimage_key,
+            keccak_env: self.keccak_env.clone(),
+            hash_counter: self.hash_counter,
+        }
+    }
+
+    /// Restore an environment from a snapshot. The preimage oracle must
+    /// serve the preimages requested by the execution from the snapshot on.
+    /// The environment does not record a trace, and generates the witness of
+    /// the steps.
+    pub fn restore(snapshot: EnvSnapshot<Fp>, preimage_oracle: PreImageOracle) -> Self {
+        Env {
+            instruction_counter: snapshot.instruction_counter,
+            memory: snapshot.memory,
+            last_memory_accesses: snapshot.last_memory_accesses,
+            memory_write_index: snapshot.memory_write_index,
+            last_memory_write_index_accesses: snapshot.last_memory_write_index_accesses,
+            registers: snapshot.registers,
+            registers_write_index: snapshot.registers_write_index,
+            scratch_state_idx: 0,
+            scratch_state_idx_inverse: 0,
+            scratch_state: fresh_scratch_state(),
+            scratch_state_inverse: fresh_scratch_state(),
+            lookup_state_idx: 0,
+            lookup_state: vec![],
+            halt: snapshot.halt,
+            syscall_env: snapshot.syscall_env,
+            selector: N_MIPS_SEL_COLS,
+            preimage_oracle,
+            preimage: snapshot.preimage,
+            preimage_bytes_read: snapshot.preimage_bytes_read,
+            preimage_key: snapshot.preimage_key,
+            keccak_env: snapshot.keccak_env,
+            hash_counter: snapshot.hash_counter,
+            lookup_multiplicities: LookupMultiplicities::new(),
+            tracer: None,
+            generate_witness: true,
+        }
+    }
+
     fn snapshot_state_at(&mut self, at: &StepFrequency) {
         if self.should_trigger_at(at) {
             let filename = format!(
@@ -1326,37 +1509,7 @@ impl<Fp: PrimeField, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
             );
             let file = File::create(filename.clone()).expect("Impossible to open file");
             let mut writer = BufWriter::new(file);
-            let mut preimage_key = [0u8; 32];
-            for i in 0..8 {
-                let bytes = u32::to_be_bytes(self.registers.preimage_key[i]);
-                for j in 0..4 {
-                    preimage_key[4 * i + j] = bytes[j]
-                }
-            }
-            let memory = self
-                .memory
-                .clone()
-                .into_iter()
-                .map(|(idx, data)| Page { index: idx, data })
-                .collect();
-            let s: State = State {
-                pc: self.registers.current_instruction_pointer,
-                next_pc: self.registers.next_instruction_pointer,
-                step: self.normalized_instruction_counter(),
-                registers: self.registers.general_purpose,
-                lo: self.registers.lo,
-                hi: self.registers.hi,
-                heap: self.registers.heap_pointer,
-                // FIXME: it should be the exit code. We do not keep it in the
-                // witness atm
-                exit: if self.halt { 1 } else { 0 },
-                last_hint: self.syscall_env.last_hint.clone(),
-                exited: self.halt,
-                preimage_offset: self.registers.preimage_offset,
-                preimage_key,
-                memory,
-                preimage: self.preimage.clone(),
-            };
+            let s: State = self.to_state();
             let _ = serde_json::to_writer(&mut writer, &s);
             info!(
                 "Snapshot state in {}, step {}",
@@ -1,18 +1,27 @@
 use super::{
     interpreter::{
         IInstruction,
-        Instruction::{self, IType, MType, RType, SBType, SType, SyscallType, UJType, UType},
-        RInstruction, SBInstruction, SInstruction, SyscallInstruction, UInstruction, UJInstruction,
+        Instruction::{
+            self, AType, IType, MType, RType, SBType, SType, SyscallType, UJType, UType,
+        },
+        MInstruction, RInstruction, SBInstruction, SInstruction, SyscallInstruction, UInstruction,
+        UJInstruction,
     },
     INSTRUCTION_SET_SIZE, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE,
 };
-use kimchi::circuits::{
-    berkeley_columns::BerkeleyChallengeTerm,
-    expr::{ConstantExpr, Expr},
-};
+use crate::RelationColumnType;
+use kimchi_msm::columns::{Column as WitnessColumn, ColumnIndexer};
 use strum::EnumCount;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// The number of columns used for relation witness in the RISC-V circuit:
+/// the scratch state, the scratch state to be inverted and the instruction
+/// counter.
+pub const N_RISCV32IM_REL_COLS: usize = SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + 1;
+
+/// The number of witness columns used to store the instruction selectors.
+pub const N_RISCV32IM_SEL_COLS: usize = INSTRUCTION_SET_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Column {
     ScratchState(usize),
     ScratchStateInverse(usize),
@@ -113,9 +122,63 @@ impl From<Instruction> for usize {
                     + SyscallInstruction::COUNT
                     + mtype as usize
             }
+            AType(atype) => {
+                SCRATCH_SIZE
+                    + SCRATCH_SIZE_INVERSE
+                    + 1
+                    + RInstruction::COUNT
+                    + IInstruction::COUNT
+                    + SInstruction::COUNT
+                    + SBInstruction::COUNT
+                    + UInstruction::COUNT
+                    + UJInstruction::COUNT
+                    + SyscallInstruction::COUNT
+                    + MInstruction::COUNT
+                    + atype as usize
+            }
+        }
+    }
+}
+
+impl ColumnIndexer<RelationColumnType> for Column {
+    const N_COL: usize = N_RISCV32IM_REL_COLS + N_RISCV32IM_SEL_COLS;
+
+    fn to_column(self) -> WitnessColumn<RelationColumnType> {
+        match self {
+            Self::ScratchState(ss) => {
+                assert!(
+                    ss < SCRATCH_SIZE,
+                    "The maximum index is {}, got {}",
+                    SCRATCH_SIZE,
+                    ss
+                );
+                WitnessColumn::Relation(RelationColumnType::Scratch(ss))
+            }
+            Self::ScratchStateInverse(ss) => {
+                assert!(
+                    ss < SCRATCH_SIZE_INVERSE,
+                    "The maximum index is {}, got {}",
+                    SCRATCH_SIZE_INVERSE,
+                    ss
+                );
+                WitnessColumn::Relation(RelationColumnType::ScratchInverse(ss))
+            }
+            Self::InstructionCounter => {
+                WitnessColumn::Relation(RelationColumnType::InstructionCounter)
+            }
+            Self::Selector(s) => {
+                assert!(
+                    s < N_RISCV32IM_SEL_COLS,
+                    "The maximum index is {}, got {}",
+                    N_RISCV32IM_SEL_COLS,
+                    s
+                );
+                WitnessColumn::DynamicSelector(s)
+            }
         }
     }
 }
 
-// FIXME: use other challenges, not Berkeley.
-pub type E<F> = Expr<ConstantExpr<F, BerkeleyChallengeTerm>, Column>;
+/// The expressions are built over the same columns than the ones used by the
+/// pickles prover, see [crate::pickles::column_env].
+pub type E<F> = crate::E<F>;
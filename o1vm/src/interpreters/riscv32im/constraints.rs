@@ -1,17 +1,21 @@
 use super::{
-    column::{Column, E},
-    interpreter::{Instruction, InterpreterEnv},
-    INSTRUCTION_SET_SIZE,
+    column::{Column, E, N_RISCV32IM_REL_COLS},
+    interpreter::{interpret_instruction, Instruction, InterpreterEnv, RInstruction},
+    INSTRUCTION_SET_SIZE, RISCV_BYTE_COUNTER_OFF, RISCV_CHUNK_BYTES_LEN, RISCV_END_OF_PREIMAGE_OFF,
+    RISCV_HASH_COUNTER_OFF, RISCV_HAS_N_BYTES_OFF, RISCV_PREIMAGE_BYTES_OFF, RISCV_PREIMAGE_KEY,
 };
 use crate::{
-    interpreters::riscv32im::{constraints::ConstantTerm::Literal, SCRATCH_SIZE},
-    lookups::Lookup,
+    interpreters::riscv32im::constraints::ConstantTerm::Literal,
+    lookups::{Lookup, LookupTableIDs},
 };
 use ark_ff::{Field, One};
 use kimchi::circuits::{
     expr::{ConstantTerm, Expr, ExprInner, Operations, Variable},
     gate::CurrOrNext,
 };
+use kimchi_msm::columns::ColumnIndexer;
+use std::array;
+use strum::IntoEnumIterator;
 
 pub struct Env<F: Field> {
     pub scratch_state_idx: usize,
@@ -61,7 +65,7 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
 
     fn variable(&self, column: Self::Position) -> Self::Variable {
         Expr::Atom(ExprInner::Cell(Variable {
-            col: column,
+            col: column.to_column(),
             row: CurrOrNext::Curr,
         }))
     }
@@ -69,7 +73,7 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
     fn activate_selector(&mut self, selector: Instruction) {
         // Sanity check: we only want to activate once per instruction
         assert!(self.selector.is_none(), "A selector has been already activated. You might need to reset the environment if you want to start a new instruction.");
-        let n = usize::from(selector) - SCRATCH_SIZE - 1;
+        let n = usize::from(selector) - N_RISCV32IM_REL_COLS;
         self.selector = Some(self.variable(Column::Selector(n)))
     }
 
@@ -282,6 +286,10 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         self.variable(position)
     }
 
+    unsafe fn sparse(&mut self, _x: &Self::Variable, position: Self::Position) -> Self::Variable {
+        self.variable(position)
+    }
+
     unsafe fn add_witness(
         &mut self,
         _y: &Self::Variable,
@@ -414,6 +422,18 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         self.variable(position)
     }
 
+    unsafe fn expand_instruction(
+        &mut self,
+        _halfword: &Self::Variable,
+        position_expanded: Self::Position,
+        position_is_compressed: Self::Position,
+    ) -> (Self::Variable, Self::Variable) {
+        (
+            self.variable(position_expanded),
+            self.variable(position_is_compressed),
+        )
+    }
+
     fn copy(&mut self, x: &Self::Variable, position: Self::Position) -> Self::Variable {
         let res = self.variable(position);
         self.constraints.push(x.clone() - res.clone());
@@ -426,8 +446,92 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
 
     fn report_exit(&mut self, _exit_code: &Self::Variable) {}
 
+    fn request_preimage_write(
+        &mut self,
+        _addr: &Self::Variable,
+        _len: &Self::Variable,
+        pos: Self::Position,
+    ) -> Self::Variable {
+        // How many preimages have been read entirely so far
+        let hash_counter = self.variable(Column::ScratchState(RISCV_HASH_COUNTER_OFF));
+
+        // How many bytes of the preimage have been read before this step
+        let byte_counter = self.variable(Column::ScratchState(RISCV_BYTE_COUNTER_OFF));
+
+        // Whether the preimage is read entirely in this step (boolean)
+        let end_of_preimage = self.variable(Column::ScratchState(RISCV_END_OF_PREIMAGE_OFF));
+
+        // The preimage key composed of 248 bits
+        let preimage_key = self.variable(Column::ScratchState(RISCV_PREIMAGE_KEY));
+
+        // The (at most) 4 bytes of the preimage read in this step
+        let bytes: [_; RISCV_CHUNK_BYTES_LEN] =
+            array::from_fn(|i| self.variable(Column::ScratchState(RISCV_PREIMAGE_BYTES_OFF + i)));
+
+        // Whether at least n bytes of the preimage are read in this step
+        let has_n_bytes: [_; RISCV_CHUNK_BYTES_LEN] =
+            array::from_fn(|i| self.variable(Column::ScratchState(RISCV_HAS_N_BYTES_OFF + i)));
+
+        // The number of bytes read in this step, including the bytes of the
+        // length prefix
+        let actual_read_bytes = self.variable(pos);
+
+        for has_n_bytes in has_n_bytes.iter() {
+            self.assert_boolean(has_n_bytes);
+        }
+        self.assert_boolean(&end_of_preimage);
+        // The bytes of the preimage are read in order: the flags are of the
+        // form 1...10...0
+        for i in 1..RISCV_CHUNK_BYTES_LEN {
+            self.add_constraint(
+                has_n_bytes[i].clone() * (Self::Variable::one() - has_n_bytes[i - 1].clone()),
+            );
+        }
+        let num_preimage_bytes_read = has_n_bytes
+            .iter()
+            .fold(Self::constant(0), |acc, has_n_bytes| {
+                acc + has_n_bytes.clone()
+            });
+
+        // Check that 0 <= preimage bytes read <= actual read <= 4
+        self.lookup_2bits(&actual_read_bytes);
+        self.lookup_2bits(&(actual_read_bytes.clone() - num_preimage_bytes_read));
+
+        for byte in bytes.iter() {
+            self.lookup_8bits(byte);
+        }
+
+        // COMMUNICATION CHANNEL: Write the preimage bytes
+        for i in 0..RISCV_CHUNK_BYTES_LEN {
+            self.add_lookup(Lookup::write_if(
+                has_n_bytes[i].clone(),
+                LookupTableIDs::SyscallLookup,
+                vec![
+                    hash_counter.clone(),
+                    byte_counter.clone() + Self::constant(i as u32),
+                    bytes[i].clone(),
+                ],
+            ));
+        }
+
+        // COMMUNICATION CHANNEL: Read the hash output once the preimage has
+        // been read entirely
+        self.add_lookup(Lookup::read_if(
+            end_of_preimage,
+            LookupTableIDs::SyscallLookup,
+            vec![hash_counter, preimage_key],
+        ));
+
+        actual_read_bytes
+    }
+
+    fn request_hint_write(&mut self, _addr: &Self::Variable, _len: &Self::Variable) {
+        // No-op, witness only
+    }
+
     fn reset(&mut self) {
         self.scratch_state_idx = 0;
+        self.scratch_state_idx_inverse = 0;
         self.constraints.clear();
         self.lookups.clear();
         self.selector = None;
@@ -469,3 +573,33 @@ impl<Fp: Field> Env<Fp> {
         self.lookups.clone()
     }
 }
+
+/// Return all the constraints of the RISC-V interpreter, each one multiplied
+/// by the selector of its instruction, followed by the constraints on the
+/// selectors.
+/// The instructions `fence` and `fence.i` are not implemented, and their
+/// selectors are therefore never activated.
+pub fn get_all_constraints<Fp: Field>() -> Vec<E<Fp>> {
+    let mut riscv_con_env = Env::<Fp>::default();
+    let unimplemented_instructions = [
+        Instruction::RType(RInstruction::Fence),
+        Instruction::RType(RInstruction::FenceI),
+    ];
+    let mut constraints = Instruction::iter()
+        .flat_map(|instr_typ| instr_typ.into_iter())
+        .filter(|instr| !unimplemented_instructions.contains(instr))
+        .fold(vec![], |mut acc, instr| {
+            interpret_instruction(&mut riscv_con_env, instr);
+            let selector = riscv_con_env.get_selector();
+            let constraints_with_selector: Vec<E<Fp>> = riscv_con_env
+                .get_constraints()
+                .into_iter()
+                .map(|c| selector.clone() * c)
+                .collect();
+            acc.extend(constraints_with_selector);
+            riscv_con_env.reset();
+            acc
+        });
+    constraints.extend(riscv_con_env.get_selector_constraints());
+    constraints
+}
@@ -4,6 +4,13 @@
 //! The implementation mostly follows (and copy) code from the MIPS interpreter
 //! available [here](../mips/interpreter.rs).
 //!
+//! On top of the base integer instruction set and the M extension, the atomic
+//! instructions of the A extension and the compressed instructions of the C
+//! extension are supported, so that binaries built for `riscv32imac` targets
+//! can be executed. Compressed instructions are expanded to the 32-bit
+//! instruction they stand for when they are fetched, see
+//! [expand_compressed_instruction].
+//!
 //! ## Credits
 //!
 //! We would like to thank the authors of the following documentations:
@@ -29,12 +36,55 @@
 //! most significant bit being the sign - 1 for negative, 0 for positive).
 //! By default, unsigned operations are performed.
 
-use super::registers::{REGISTER_CURRENT_IP, REGISTER_HEAP_POINTER, REGISTER_NEXT_IP};
+use super::{
+    registers::{
+        REGISTER_CURRENT_IP, REGISTER_HEAP_POINTER, REGISTER_NEXT_IP, REGISTER_PREIMAGE_KEY_END,
+        REGISTER_PREIMAGE_OFFSET,
+    },
+    PAGE_ADDRESS_SIZE,
+};
 use crate::lookups::{Lookup, LookupTableIDs};
 use ark_ff::{One, Zero};
 use strum::{EnumCount, IntoEnumIterator};
 use strum_macros::{EnumCount, EnumIter};
 
+pub const FD_STDIN: u32 = 0;
+pub const FD_STDOUT: u32 = 1;
+pub const FD_STDERR: u32 = 2;
+pub const FD_HINT_READ: u32 = 3;
+pub const FD_HINT_WRITE: u32 = 4;
+pub const FD_PREIMAGE_READ: u32 = 5;
+pub const FD_PREIMAGE_WRITE: u32 = 6;
+
+// Syscall numbers, following the Linux generic ABI used by RISC-V. The
+// syscall number is passed in register a7, the arguments in a0-a5, and the
+// return value is written in a0.
+pub const SYSCALL_READ: u32 = 63;
+pub const SYSCALL_WRITE: u32 = 64;
+pub const SYSCALL_EXIT: u32 = 93;
+pub const SYSCALL_EXIT_GROUP: u32 = 94;
+pub const SYSCALL_BRK: u32 = 214;
+pub const SYSCALL_MMAP: u32 = 222;
+/// Custom syscall used by the test programs to stop the execution.
+pub const SYSCALL_SUCCESS: u32 = 42;
+
+/// Value returned in a0 when the syscall is given an invalid file descriptor,
+/// i.e. `-EBADF` as a 32 bits two's complement integer.
+pub const EBADF_RETURN_VALUE: u32 = (-9i32) as u32;
+
+/// Value returned in a0 by the syscalls which are not implemented, i.e.
+/// `-ENOSYS` as a 32 bits two's complement integer.
+pub const ENOSYS_RETURN_VALUE: u32 = (-38i32) as u32;
+
+/// Encoding of the instruction `ecall`
+pub const ECALL_ENCODING: u32 = 0b1110011;
+
+/// Register indices used by the syscalls ABI
+pub const REGISTER_A0: u32 = 10;
+pub const REGISTER_A1: u32 = 11;
+pub const REGISTER_A2: u32 = 12;
+pub const REGISTER_A7: u32 = 17;
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, EnumCount, EnumIter, Hash, Ord, PartialOrd)]
 pub enum Instruction {
     RType(RInstruction),
@@ -45,6 +95,7 @@ pub enum Instruction {
     UJType(UJInstruction),
     SyscallType(SyscallInstruction),
     MType(MInstruction),
+    AType(AInstruction),
 }
 
 // See
@@ -309,7 +360,31 @@ pub enum UJInstruction {
 )]
 pub enum SyscallInstruction {
     #[default]
+    /// Custom syscall (a7 = 42) used by the test programs to halt the
+    /// execution.
     SyscallSuccess,
+    /// `exit` and `exit_group`: halts the execution with the exit code
+    /// given in a0.
+    SyscallExit,
+    /// `read` on [FD_HINT_READ]
+    SyscallReadHint,
+    /// `read` on [FD_PREIMAGE_READ]: reads at most 4 bytes of the preimage
+    /// whose key has been previously written on [FD_PREIMAGE_WRITE].
+    SyscallReadPreimage,
+    /// `read` on any other file descriptor
+    SyscallReadOther,
+    /// `write` on [FD_HINT_WRITE]
+    SyscallWriteHint,
+    /// `write` on [FD_PREIMAGE_WRITE]: updates the preimage key
+    SyscallWritePreimage,
+    /// `write` on any other file descriptor
+    SyscallWriteOther,
+    /// `brk`: moves the program break
+    SyscallBrk,
+    /// `mmap`: only anonymous mappings are supported
+    SyscallMmap,
+    /// Any other syscall: it has no effect and returns `-ENOSYS`
+    SyscallOther,
 }
 
 /// M extension instructions
@@ -369,6 +444,310 @@ pub enum MInstruction {
     Remu, // remu
 }
 
+/// A extension instructions
+/// Following <https://msyksphinz-self.github.io/riscv-isadoc/html/rva.html>
+///
+/// The VM runs a single hart, therefore the reservation set by `lr.w` can not
+/// be invalidated by another hart and `sc.w` always succeeds.
+/// The ordering bits `aq` and `rl` are ignored, as the memory accesses are
+/// already sequentially consistent.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, EnumCount, EnumIter, Default, Hash, Ord, PartialOrd,
+)]
+pub enum AInstruction {
+    #[default]
+    /// Format: `lr.w rd, (rs1)`
+    ///
+    /// Description: loads a word from the address in rs1, places the value in
+    /// rd, and registers a reservation on the memory address.
+    /// Implementation: `x[rd] = LoadReserved32(M[x[rs1]])`
+    LoadReserved, // lr.w
+    /// Format: `sc.w rd, rs2, (rs1)`
+    ///
+    /// Description: writes a word in rs2 to the address in rs1, provided a
+    /// valid reservation still exists on that address. SC writes zero to rd on
+    /// success or a nonzero code on failure.
+    /// Implementation: `x[rd] = StoreConditional32(M[x[rs1]], x[rs2])`
+    StoreConditional, // sc.w
+    /// Format: `amoswap.w rd, rs2, (rs1)`
+    ///
+    /// Description: atomically load a 32-bit signed data value from the
+    /// address in rs1, place the value into register rd, swap the loaded value
+    /// and the original 32-bit signed value in rs2, then store the result back
+    /// to the address in rs1.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] SWAP x[rs2])`
+    AmoSwap, // amoswap.w
+    /// Format: `amoadd.w rd, rs2, (rs1)`
+    ///
+    /// Description: atomically load a 32-bit signed data value from the
+    /// address in rs1, place the value into register rd, apply add the loaded
+    /// value and the original 32-bit signed value in rs2, then store the
+    /// result back to the address in rs1.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] + x[rs2])`
+    AmoAdd, // amoadd.w
+    /// Format: `amoxor.w rd, rs2, (rs1)`
+    ///
+    /// Description: same as `amoadd.w`, using a bitwise XOR.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] ^ x[rs2])`
+    AmoXor, // amoxor.w
+    /// Format: `amoand.w rd, rs2, (rs1)`
+    ///
+    /// Description: same as `amoadd.w`, using a bitwise AND.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] & x[rs2])`
+    AmoAnd, // amoand.w
+    /// Format: `amoor.w rd, rs2, (rs1)`
+    ///
+    /// Description: same as `amoadd.w`, using a bitwise OR.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] | x[rs2])`
+    AmoOr, // amoor.w
+    /// Format: `amomin.w rd, rs2, (rs1)`
+    ///
+    /// Description: same as `amoadd.w`, storing the minimum of the two
+    /// values, treated as signed numbers.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] MIN x[rs2])`
+    AmoMin, // amomin.w
+    /// Format: `amomax.w rd, rs2, (rs1)`
+    ///
+    /// Description: same as `amoadd.w`, storing the maximum of the two
+    /// values, treated as signed numbers.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] MAX x[rs2])`
+    AmoMax, // amomax.w
+    /// Format: `amominu.w rd, rs2, (rs1)`
+    ///
+    /// Description: same as `amoadd.w`, storing the minimum of the two
+    /// values, treated as unsigned numbers.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] MINU x[rs2])`
+    AmoMinUnsigned, // amominu.w
+    /// Format: `amomaxu.w rd, rs2, (rs1)`
+    ///
+    /// Description: same as `amoadd.w`, storing the maximum of the two
+    /// values, treated as unsigned numbers.
+    /// Implementation: `x[rd] = AMO32(M[x[rs1]] MAXU x[rs2])`
+    AmoMaxUnsigned, // amomaxu.w
+}
+
+/// Expand a compressed instruction of the C extension, given by the 16 lowest
+/// bits of `instruction`, to the 32-bit instruction it stands for.
+/// Following <https://msyksphinz-self.github.io/riscv-isadoc/html/rvc.html>
+///
+/// Compressed instructions are recognised by their two lowest bits, `op`,
+/// which are different from `11`. The registers `rd'`, `rs1'` and `rs2'` are
+/// the registers `x8` to `x15` encoded on 3 bits.
+/// ```text
+/// | 15     13 | 12                                               2 | 1   0 |
+/// |  funct3   |    registers and immediate, depending on the format  |  op   |
+/// ```
+///
+/// Return `None` for 32-bit instructions, for the illegal instruction `0`, and
+/// for the instructions which are not supported: `c.ebreak`, the floating
+/// point loads and stores, and the encodings reserved for RV64.
+pub fn expand_compressed_instruction(instruction: u32) -> Option<u32> {
+    const OPCODE_OP_IMM: u32 = 0b0010011;
+    const OPCODE_OP: u32 = 0b0110011;
+    const OPCODE_LOAD: u32 = 0b0000011;
+    const OPCODE_STORE: u32 = 0b0100011;
+    const OPCODE_BRANCH: u32 = 0b1100011;
+    const OPCODE_LUI: u32 = 0b0110111;
+    const OPCODE_JAL: u32 = 0b1101111;
+    const OPCODE_JALR: u32 = 0b1100111;
+
+    // Encoders of the 32-bit formats. The immediates are given as the bits of
+    // their two's complement representation.
+    let itype = |imm: u32, rs1: u32, funct3: u32, rd: u32, opcode: u32| {
+        ((imm & 0xFFF) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+    };
+    let rtype = |funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32| {
+        (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | OPCODE_OP
+    };
+    let stype = |imm: u32, rs2: u32, rs1: u32| {
+        (((imm >> 5) & 0x7F) << 25)
+            | (rs2 << 20)
+            | (rs1 << 15)
+            | (0b010 << 12)
+            | ((imm & 0x1F) << 7)
+            | OPCODE_STORE
+    };
+    let sbtype = |imm: u32, rs1: u32, funct3: u32| {
+        (((imm >> 12) & 0x1) << 31)
+            | (((imm >> 5) & 0x3F) << 25)
+            | (rs1 << 15)
+            | (funct3 << 12)
+            | (((imm >> 1) & 0xF) << 8)
+            | (((imm >> 11) & 0x1) << 7)
+            | OPCODE_BRANCH
+    };
+    let ujtype = |imm: u32, rd: u32| {
+        (((imm >> 20) & 0x1) << 31)
+            | (((imm >> 1) & 0x3FF) << 21)
+            | (((imm >> 11) & 0x1) << 20)
+            | (((imm >> 12) & 0xFF) << 12)
+            | (rd << 7)
+            | OPCODE_JAL
+    };
+
+    // Build a value from the bits of the instruction. Each element of
+    // `mapping` is a pair `(instruction bit, value bit)`.
+    let from_bits = |mapping: &[(u32, u32)]| {
+        mapping.iter().fold(0, |acc, (instruction_bit, value_bit)| {
+            acc | (((instruction >> instruction_bit) & 1) << value_bit)
+        })
+    };
+    // Sign extend a value of `bitlength` bits
+    let sext = |value: u32, bitlength: u32| {
+        ((value << (32 - bitlength)) as i32 >> (32 - bitlength)) as u32
+    };
+
+    let op = instruction & 0b11; // bits 0-1
+    let funct3 = (instruction >> 13) & 0x7; // bits 13-15
+    let bit12 = (instruction >> 12) & 0x1;
+    // rd or rs1 at bits 7-11, and rs2 at bits 2-6
+    let rd_rs1 = (instruction >> 7) & 0x1F;
+    let rs2 = (instruction >> 2) & 0x1F;
+    // rd' or rs1' at bits 7-9, and rd' or rs2' at bits 2-4
+    let rd_rs1_prime = ((instruction >> 7) & 0x7) + 8;
+    let rd_rs2_prime = ((instruction >> 2) & 0x7) + 8;
+    // The 6 bits immediate of the CI format, used by c.addi, c.li, c.andi and
+    // the shifts
+    let imm6 = from_bits(&[(2, 0), (3, 1), (4, 2), (5, 3), (6, 4), (12, 5)]);
+    // The offset of the loads and stores c.lw and c.sw
+    let word_offset = from_bits(&[(6, 2), (10, 3), (11, 4), (12, 5), (5, 6)]);
+    // The offset of the jumps c.j and c.jal
+    let jump_offset = sext(
+        from_bits(&[
+            (3, 1),
+            (4, 2),
+            (5, 3),
+            (11, 4),
+            (2, 5),
+            (7, 6),
+            (6, 7),
+            (9, 8),
+            (10, 9),
+            (8, 10),
+            (12, 11),
+        ]),
+        12,
+    );
+    // The offset of the branches c.beqz and c.bnez
+    let branch_offset = sext(
+        from_bits(&[
+            (3, 1),
+            (4, 2),
+            (10, 3),
+            (11, 4),
+            (2, 5),
+            (5, 6),
+            (6, 7),
+            (12, 8),
+        ]),
+        9,
+    );
+
+    let expanded = match (op, funct3) {
+        (0b11, _) => return None,
+        (0b00, 0b000) => {
+            // c.addi4spn: addi rd', x2, nzuimm
+            let nzuimm = from_bits(&[
+                (6, 2),
+                (5, 3),
+                (11, 4),
+                (12, 5),
+                (7, 6),
+                (8, 7),
+                (9, 8),
+                (10, 9),
+            ]);
+            if nzuimm == 0 {
+                return None;
+            }
+            itype(nzuimm, 2, 0b000, rd_rs2_prime, OPCODE_OP_IMM)
+        }
+        // c.lw: lw rd', uimm(rs1')
+        (0b00, 0b010) => itype(word_offset, rd_rs1_prime, 0b010, rd_rs2_prime, OPCODE_LOAD),
+        // c.sw: sw rs2', uimm(rs1')
+        (0b00, 0b110) => stype(word_offset, rd_rs2_prime, rd_rs1_prime),
+        // c.addi: addi rd, rd, nzimm
+        (0b01, 0b000) => itype(sext(imm6, 6), rd_rs1, 0b000, rd_rs1, OPCODE_OP_IMM),
+        // c.jal: jal x1, offset
+        (0b01, 0b001) => ujtype(jump_offset, 1),
+        // c.li: addi rd, x0, imm
+        (0b01, 0b010) => itype(sext(imm6, 6), 0, 0b000, rd_rs1, OPCODE_OP_IMM),
+        (0b01, 0b011) if rd_rs1 == 2 => {
+            // c.addi16sp: addi x2, x2, nzimm
+            let nzimm = from_bits(&[(6, 4), (2, 5), (5, 6), (3, 7), (4, 8), (12, 9)]);
+            if nzimm == 0 {
+                return None;
+            }
+            itype(sext(nzimm, 10), 2, 0b000, 2, OPCODE_OP_IMM)
+        }
+        (0b01, 0b011) => {
+            // c.lui: lui rd, nzimm
+            if imm6 == 0 {
+                return None;
+            }
+            ((sext(imm6, 6) & 0xFFFFF) << 12) | (rd_rs1 << 7) | OPCODE_LUI
+        }
+        (0b01, 0b100) => match (instruction >> 10) & 0x3 // bits 10-11
+        {
+            // c.srli: srli rd', rd', shamt
+            0b00 if bit12 == 0 => itype(imm6, rd_rs1_prime, 0b101, rd_rs1_prime, OPCODE_OP_IMM),
+            // c.srai: srai rd', rd', shamt
+            0b01 if bit12 == 0 => itype(
+                (0b0100000 << 5) | imm6,
+                rd_rs1_prime,
+                0b101,
+                rd_rs1_prime,
+                OPCODE_OP_IMM,
+            ),
+            // c.andi: andi rd', rd', imm
+            0b10 => itype(sext(imm6, 6), rd_rs1_prime, 0b111, rd_rs1_prime, OPCODE_OP_IMM),
+            0b11 if bit12 == 0 => {
+                let (funct7, funct3) = match (instruction >> 5) & 0x3 // bits 5-6
+                {
+                    0b00 => (0b0100000, 0b000), // c.sub: sub rd', rd', rs2'
+                    0b01 => (0b0000000, 0b100), // c.xor: xor rd', rd', rs2'
+                    0b10 => (0b0000000, 0b110), // c.or: or rd', rd', rs2'
+                    _ => (0b0000000, 0b111), // c.and: and rd', rd', rs2'
+                };
+                rtype(funct7, rd_rs2_prime, rd_rs1_prime, funct3, rd_rs1_prime)
+            }
+            _ => return None,
+        },
+        // c.j: jal x0, offset
+        (0b01, 0b101) => ujtype(jump_offset, 0),
+        // c.beqz: beq rs1', x0, offset
+        (0b01, 0b110) => sbtype(branch_offset, rd_rs1_prime, 0b000),
+        // c.bnez: bne rs1', x0, offset
+        (0b01, 0b111) => sbtype(branch_offset, rd_rs1_prime, 0b001),
+        // c.slli: slli rd, rd, shamt
+        (0b10, 0b000) if bit12 == 0 => itype(imm6, rd_rs1, 0b001, rd_rs1, OPCODE_OP_IMM),
+        (0b10, 0b010) if rd_rs1 != 0 => {
+            // c.lwsp: lw rd, uimm(x2)
+            let offset = from_bits(&[(4, 2), (5, 3), (6, 4), (12, 5), (2, 6), (3, 7)]);
+            itype(offset, 2, 0b010, rd_rs1, OPCODE_LOAD)
+        }
+        (0b10, 0b100) => match (bit12, rd_rs1, rs2) {
+            // c.jr: jalr x0, 0(rs1)
+            (0, 1.., 0) => itype(0, rd_rs1, 0b000, 0, OPCODE_JALR),
+            // c.mv: add rd, x0, rs2
+            (0, _, 1..) => rtype(0, rs2, 0, 0b000, rd_rs1),
+            // c.jalr: jalr x1, 0(rs1)
+            (1, 1.., 0) => itype(0, rd_rs1, 0b000, 1, OPCODE_JALR),
+            // c.add: add rd, rd, rs2
+            (1, _, 1..) => rtype(0, rs2, rd_rs1, 0b000, rd_rs1),
+            // c.ebreak, and c.jr with x0
+            _ => return None,
+        },
+        (0b10, 0b110) => {
+            // c.swsp: sw rs2, uimm(x2)
+            let offset = from_bits(&[(9, 2), (10, 3), (11, 4), (12, 5), (7, 6), (8, 7)]);
+            stype(offset, rs2, 2)
+        }
+        _ => return None,
+    };
+    Some(expanded)
+}
+
 impl IntoIterator for Instruction {
     type Item = Instruction;
     type IntoIter = std::vec::IntoIter<Instruction>;
@@ -431,6 +810,13 @@ impl IntoIterator for Instruction {
                 }
                 iter_contents.into_iter()
             }
+            Instruction::AType(_) => {
+                let mut iter_contents = Vec::with_capacity(AInstruction::COUNT);
+                for atype in AInstruction::iter() {
+                    iter_contents.push(Instruction::AType(atype));
+                }
+                iter_contents.into_iter()
+            }
         }
     }
 }
@@ -444,8 +830,9 @@ impl std::fmt::Display for Instruction {
             Instruction::SBType(sbtype) => write!(f, "{}", sbtype),
             Instruction::UType(utype) => write!(f, "{}", utype),
             Instruction::UJType(ujtype) => write!(f, "{}", ujtype),
-            Instruction::SyscallType(_syscall) => write!(f, "ecall"),
+            Instruction::SyscallType(syscall) => write!(f, "{}", syscall),
             Instruction::MType(mtype) => write!(f, "{}", mtype),
+            Instruction::AType(atype) => write!(f, "{}", atype),
         }
     }
 }
@@ -531,6 +918,24 @@ impl std::fmt::Display for UJInstruction {
     }
 }
 
+impl std::fmt::Display for SyscallInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyscallInstruction::SyscallSuccess => write!(f, "ecall"),
+            SyscallInstruction::SyscallExit => write!(f, "ecall(exit)"),
+            SyscallInstruction::SyscallReadHint => write!(f, "ecall(read hint)"),
+            SyscallInstruction::SyscallReadPreimage => write!(f, "ecall(read preimage)"),
+            SyscallInstruction::SyscallReadOther => write!(f, "ecall(read)"),
+            SyscallInstruction::SyscallWriteHint => write!(f, "ecall(write hint)"),
+            SyscallInstruction::SyscallWritePreimage => write!(f, "ecall(write preimage)"),
+            SyscallInstruction::SyscallWriteOther => write!(f, "ecall(write)"),
+            SyscallInstruction::SyscallBrk => write!(f, "ecall(brk)"),
+            SyscallInstruction::SyscallMmap => write!(f, "ecall(mmap)"),
+            SyscallInstruction::SyscallOther => write!(f, "ecall(other)"),
+        }
+    }
+}
+
 impl std::fmt::Display for MInstruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -546,6 +951,24 @@ impl std::fmt::Display for MInstruction {
     }
 }
 
+impl std::fmt::Display for AInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AInstruction::LoadReserved => write!(f, "lr.w"),
+            AInstruction::StoreConditional => write!(f, "sc.w"),
+            AInstruction::AmoSwap => write!(f, "amoswap.w"),
+            AInstruction::AmoAdd => write!(f, "amoadd.w"),
+            AInstruction::AmoXor => write!(f, "amoxor.w"),
+            AInstruction::AmoAnd => write!(f, "amoand.w"),
+            AInstruction::AmoOr => write!(f, "amoor.w"),
+            AInstruction::AmoMin => write!(f, "amomin.w"),
+            AInstruction::AmoMax => write!(f, "amomax.w"),
+            AInstruction::AmoMinUnsigned => write!(f, "amominu.w"),
+            AInstruction::AmoMaxUnsigned => write!(f, "amomaxu.w"),
+        }
+    }
+}
+
 pub trait InterpreterEnv {
     /// A position can be seen as an indexed variable
     type Position;
@@ -994,6 +1417,53 @@ pub trait InterpreterEnv {
         ip
     }
 
+    /// Fetch the instruction at `instruction_pointer`, and return it with the
+    /// address of the instruction following it.
+    ///
+    /// Compressed instructions are expanded to the 32-bit instruction they
+    /// stand for, using the table [LookupTableIDs::CompressedInstructionLookup],
+    /// and they move the instruction pointer by 2 bytes instead of 4.
+    fn fetch_instruction(
+        &mut self,
+        instruction_pointer: &Self::Variable,
+    ) -> (Self::Variable, Self::Variable) {
+        // The next instruction pointer register is read to keep the register
+        // accesses consistent, but the address of the next instruction depends
+        // on the length of the instruction.
+        let _ = self.get_next_instruction_pointer();
+
+        let v0 = self.read_memory(instruction_pointer);
+        let v1 = self.read_memory(&(instruction_pointer.clone() + Self::constant(1)));
+        let v2 = self.read_memory(&(instruction_pointer.clone() + Self::constant(2)));
+        let v3 = self.read_memory(&(instruction_pointer.clone() + Self::constant(3)));
+        let halfword = (v1 * Self::constant(1 << 8)) + v0;
+        let word =
+            (v3 * Self::constant(1 << 24)) + (v2 * Self::constant(1 << 16)) + halfword.clone();
+
+        let (expanded, is_compressed) = {
+            let expanded_position = self.alloc_scratch();
+            let is_compressed_position = self.alloc_scratch();
+            unsafe { self.expand_instruction(&halfword, expanded_position, is_compressed_position) }
+        };
+        self.assert_boolean(&is_compressed);
+        self.add_lookup(Lookup::read_one(
+            LookupTableIDs::CompressedInstructionLookup,
+            vec![halfword, expanded.clone(), is_compressed.clone()],
+        ));
+
+        let instruction = {
+            let position = self.alloc_scratch();
+            self.copy(
+                &((is_compressed.clone() * expanded)
+                    + ((Self::constant(1) - is_compressed.clone()) * word)),
+                position,
+            )
+        };
+        let next_instruction_pointer =
+            instruction_pointer.clone() + Self::constant(4) - (Self::constant(2) * is_compressed);
+        (instruction, next_instruction_pointer)
+    }
+
     fn constant(x: u32) -> Self::Variable;
 
     /// Extract the bits from the variable `x` between `highest_bit` and `lowest_bit`, and store
@@ -1147,6 +1617,16 @@ pub trait InterpreterEnv {
         position: Self::Position,
     ) -> Self::Variable;
 
+    /// Returns the sparse representation of the 16-bit value `x`, where each
+    /// bit of `x` is stored in its own nibble, storing the result in
+    /// `position`.
+    ///
+    /// # Safety
+    ///
+    /// There are no constraints on the returned value; callers must look up
+    /// `x` and the returned value in [`LookupTableIDs::ResetLookup`].
+    unsafe fn sparse(&mut self, x: &Self::Variable, position: Self::Position) -> Self::Variable;
+
     /// Returns `x + y` and the overflow bit, storing the results in `position_out` and
     /// `position_overflow` respectively.
     ///
@@ -1348,6 +1828,22 @@ pub trait InterpreterEnv {
         position: Self::Position,
     ) -> Self::Variable;
 
+    /// Returns the 32-bit instruction that the compressed instruction
+    /// `halfword` stands for, or 0 if it is not a compressed instruction, and
+    /// whether it is a compressed instruction, storing the results in
+    /// `position_expanded` and `position_is_compressed`.
+    ///
+    /// # Safety
+    ///
+    /// There are no constraints on the returned values; callers must manually add constraints to
+    /// ensure that they are correctly constructed.
+    unsafe fn expand_instruction(
+        &mut self,
+        halfword: &Self::Variable,
+        position_expanded: Self::Position,
+        position_is_compressed: Self::Position,
+    ) -> (Self::Variable, Self::Variable);
+
     fn copy(&mut self, x: &Self::Variable, position: Self::Position) -> Self::Variable;
 
     /// Increases the heap pointer by `by_amount` if `if_is_true` is `1`, and returns the previous
@@ -1394,6 +1890,22 @@ pub trait InterpreterEnv {
 
     fn report_exit(&mut self, exit_code: &Self::Variable);
 
+    /// Request the preimage oracle for `len` bytes and store the bytes starting
+    /// from `addr`, and it returns the number of bytes actually read.
+    /// The number of bytes actually read will be set into `pos`.
+    /// The first 8 bytes will be the length of the preimage, encoded as an
+    /// unsigned 64bits, and the rest will be the preimage.
+    fn request_preimage_write(
+        &mut self,
+        addr: &Self::Variable,
+        len: &Self::Variable,
+        pos: Self::Position,
+    ) -> Self::Variable;
+
+    /// Send the `len` bytes starting at `addr` to the preimage oracle as a
+    /// hint.
+    fn request_hint_write(&mut self, addr: &Self::Variable, len: &Self::Variable);
+
     fn reset(&mut self);
 }
 
@@ -1408,6 +1920,7 @@ pub fn interpret_instruction<Env: InterpreterEnv>(env: &mut Env, instr: Instruct
         Instruction::UJType(ujtype) => interpret_ujtype(env, ujtype),
         Instruction::SyscallType(syscall) => interpret_syscall(env, syscall),
         Instruction::MType(mtype) => interpret_mtype(env, mtype),
+        Instruction::AType(atype) => interpret_atype(env, atype),
     }
 }
 
@@ -1421,18 +1934,7 @@ pub fn interpret_instruction<Env: InterpreterEnv>(env: &mut Env, instr: Instruct
 /// [here](https://www.cs.cornell.edu/courses/cs3410/2024fa/assignments/cpusim/riscv-instructions.pdf)
 pub fn interpret_rtype<Env: InterpreterEnv>(env: &mut Env, instr: RInstruction) {
     let instruction_pointer = env.get_instruction_pointer();
-    let next_instruction_pointer = env.get_next_instruction_pointer();
-
-    let instruction = {
-        let v0 = env.read_memory(&instruction_pointer);
-        let v1 = env.read_memory(&(instruction_pointer.clone() + Env::constant(1)));
-        let v2 = env.read_memory(&(instruction_pointer.clone() + Env::constant(2)));
-        let v3 = env.read_memory(&(instruction_pointer.clone() + Env::constant(3)));
-        (v3 * Env::constant(1 << 24))
-            + (v2 * Env::constant(1 << 16))
-            + (v1 * Env::constant(1 << 8))
-            + v0
-    };
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
 
     // FIXME: constrain the opcode to match the instruction given as a parameter
     let opcode = {
@@ -1647,18 +2149,7 @@ pub fn interpret_rtype<Env: InterpreterEnv>(env: &mut Env, instr: RInstruction)
 /// [here](https://www.cs.cornell.edu/courses/cs3410/2024fa/assignments/cpusim/riscv-instructions.pdf)
 pub fn interpret_itype<Env: InterpreterEnv>(env: &mut Env, instr: IInstruction) {
     let instruction_pointer = env.get_instruction_pointer();
-    let next_instruction_pointer = env.get_next_instruction_pointer();
-
-    let instruction = {
-        let v0 = env.read_memory(&instruction_pointer);
-        let v1 = env.read_memory(&(instruction_pointer.clone() + Env::constant(1)));
-        let v2 = env.read_memory(&(instruction_pointer.clone() + Env::constant(2)));
-        let v3 = env.read_memory(&(instruction_pointer.clone() + Env::constant(3)));
-        (v3 * Env::constant(1 << 24))
-            + (v2 * Env::constant(1 << 16))
-            + (v1 * Env::constant(1 << 8))
-            + v0
-    };
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
 
     let opcode = {
         let pos = env.alloc_scratch();
@@ -1965,19 +2456,8 @@ pub fn interpret_itype<Env: InterpreterEnv>(env: &mut Env, instr: IInstruction)
 pub fn interpret_stype<Env: InterpreterEnv>(env: &mut Env, instr: SInstruction) {
     /* fetch instruction pointer from the program state */
     let instruction_pointer = env.get_instruction_pointer();
-    /* compute the next instruction ptr and add one, as well record raml lookup */
-    let next_instruction_pointer = env.get_next_instruction_pointer();
-    /* read instruction from ip address */
-    let instruction = {
-        let v0 = env.read_memory(&instruction_pointer);
-        let v1 = env.read_memory(&(instruction_pointer.clone() + Env::constant(1)));
-        let v2 = env.read_memory(&(instruction_pointer.clone() + Env::constant(2)));
-        let v3 = env.read_memory(&(instruction_pointer.clone() + Env::constant(3)));
-        (v3 * Env::constant(1 << 24))
-            + (v2 * Env::constant(1 << 16))
-            + (v1 * Env::constant(1 << 8))
-            + v0
-    };
+    /* read the instruction at the ip address, and compute the address of the next one */
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
 
     /* fetch opcode from instruction bit 0 - 6 for a total len of 7 */
     let opcode = {
@@ -2137,17 +2617,7 @@ pub fn interpret_stype<Env: InterpreterEnv>(env: &mut Env, instr: SInstruction)
 /// [here](https://www.cs.cornell.edu/courses/cs3410/2024fa/assignments/cpusim/riscv-instructions.pdf)
 pub fn interpret_sbtype<Env: InterpreterEnv>(env: &mut Env, instr: SBInstruction) {
     let instruction_pointer = env.get_instruction_pointer();
-    let next_instruction_pointer = env.get_next_instruction_pointer();
-    let instruction = {
-        let v0 = env.read_memory(&instruction_pointer);
-        let v1 = env.read_memory(&(instruction_pointer.clone() + Env::constant(1)));
-        let v2 = env.read_memory(&(instruction_pointer.clone() + Env::constant(2)));
-        let v3 = env.read_memory(&(instruction_pointer.clone() + Env::constant(3)));
-        (v3 * Env::constant(1 << 24))
-            + (v2 * Env::constant(1 << 16))
-            + (v1 * Env::constant(1 << 8))
-            + v0
-    };
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
     let opcode = {
         let pos = env.alloc_scratch();
         unsafe { env.bitmask(&instruction, 7, 0, pos) }
@@ -2205,10 +2675,10 @@ pub fn interpret_sbtype<Env: InterpreterEnv>(env: &mut Env, instr: SBInstruction
                 - (opcode * Env::constant(1 << 0))    // opcode at bits 0-7
                 - (imm11.clone() * Env::constant(1 << 7))     // imm11 at bits 8
                 - (imm1_4.clone() * Env::constant(1 << 8))    // imm1_4 at bits 9-11
-                - (funct3 * Env::constant(1 << 11))   // funct3 at bits 11-14
-                - (rs1.clone() * Env::constant(1 << 14))      // rs1 at bits 15-20
-                - (rs2.clone() * Env::constant(1 << 19))      // rs2 at bits 20-24
-                - (imm5_10.clone() * Env::constant(1 << 24))  // imm5_10 at bits 25-30
+                - (funct3 * Env::constant(1 << 12))   // funct3 at bits 12-14
+                - (rs1.clone() * Env::constant(1 << 15))      // rs1 at bits 15-19
+                - (rs2.clone() * Env::constant(1 << 20))      // rs2 at bits 20-24
+                - (imm5_10.clone() * Env::constant(1 << 25))  // imm5_10 at bits 25-30
                 - (imm12.clone() * Env::constant(1 << 31)), // imm12 at bits 31
         );
 
@@ -2220,165 +2690,65 @@ pub fn interpret_sbtype<Env: InterpreterEnv>(env: &mut Env, instr: SBInstruction
     // extra bit is because the 0th bit in the immediate is always 0 i.e you cannot jump to an odd address
     let imm0_12 = env.sign_extend(&imm0_12, 13);
 
-    match instr {
+    let local_rs1 = env.read_register(&rs1);
+    let local_rs2 = env.read_register(&rs2);
+
+    // Whether the branch is taken
+    let taken = match instr {
         SBInstruction::BranchEq => {
             // beq: if (x[rs1] == x[rs2]) pc += sext(offset)
-            let local_rs1 = env.read_register(&rs1);
-            let local_rs2 = env.read_register(&rs2);
-
-            let equals = env.equal(&local_rs1, &local_rs2);
-            let offset = (Env::constant(1) - equals.clone()) * Env::constant(4) + equals * imm0_12;
-            let offset = env.sign_extend(&offset, 12);
-            let addr = {
-                let res_scratch = env.alloc_scratch();
-                let overflow_scratch = env.alloc_scratch();
-                let (res, _overflow) = unsafe {
-                    env.add_witness(
-                        &next_instruction_pointer,
-                        &offset,
-                        res_scratch,
-                        overflow_scratch,
-                    )
-                };
-                // FIXME: Requires a range check
-                res
-            };
-            env.set_instruction_pointer(next_instruction_pointer);
-            env.set_next_instruction_pointer(addr);
+            env.equal(&local_rs1, &local_rs2)
         }
         SBInstruction::BranchNeq => {
             // bne: if (x[rs1] != x[rs2]) pc += sext(offset)
-            let local_rs1 = env.read_register(&rs1);
-            let local_rs2 = env.read_register(&rs2);
-
-            let equals = env.equal(&local_rs1, &local_rs2);
-            let offset = equals.clone() * Env::constant(4) + (Env::constant(1) - equals) * imm0_12;
-            let addr = {
-                let res_scratch = env.alloc_scratch();
-                let overflow_scratch = env.alloc_scratch();
-                let (res, _overflow) = unsafe {
-                    env.add_witness(
-                        &next_instruction_pointer,
-                        &offset,
-                        res_scratch,
-                        overflow_scratch,
-                    )
-                };
-                // FIXME: Requires a range check
-                res
-            };
-            env.set_instruction_pointer(next_instruction_pointer);
-            env.set_next_instruction_pointer(addr);
+            Env::constant(1) - env.equal(&local_rs1, &local_rs2)
         }
         SBInstruction::BranchLessThan => {
             // blt: if (x[rs1] < x[rs2]) pc += sext(offset)
-            let local_rs1 = env.read_register(&rs1);
-            let local_rs2 = env.read_register(&rs2);
-
-            let less_than = {
-                let rd_scratch = env.alloc_scratch();
-                unsafe { env.test_less_than_signed(&local_rs1, &local_rs2, rd_scratch) }
-            };
-            let offset = (less_than.clone()) * imm0_12
-                + (Env::constant(1) - less_than.clone()) * Env::constant(4);
-
-            let addr = {
-                let res_scratch = env.alloc_scratch();
-                let overflow_scratch = env.alloc_scratch();
-                let (res, _overflow) = unsafe {
-                    env.add_witness(
-                        &next_instruction_pointer,
-                        &offset,
-                        res_scratch,
-                        overflow_scratch,
-                    )
-                };
-                // FIXME: Requires a range check
-                res
-            };
-            env.set_instruction_pointer(next_instruction_pointer);
-            env.set_next_instruction_pointer(addr);
+            let rd_scratch = env.alloc_scratch();
+            unsafe { env.test_less_than_signed(&local_rs1, &local_rs2, rd_scratch) }
         }
         SBInstruction::BranchGreaterThanEqual => {
             // bge: if (x[rs1] >= x[rs2]) pc += sext(offset)
-            let local_rs1 = env.read_register(&rs1);
-            let local_rs2 = env.read_register(&rs2);
-
-            let less_than = {
-                let rd_scratch = env.alloc_scratch();
-                unsafe { env.test_less_than_signed(&local_rs1, &local_rs2, rd_scratch) }
-            };
-
-            let offset =
-                less_than.clone() * Env::constant(4) + (Env::constant(1) - less_than) * imm0_12;
             // greater than equal is the negation of less than
-            let addr = {
-                let res_scratch = env.alloc_scratch();
-                let overflow_scratch = env.alloc_scratch();
-                let (res, _overflow) = unsafe {
-                    env.add_witness(
-                        &next_instruction_pointer,
-                        &offset,
-                        res_scratch,
-                        overflow_scratch,
-                    )
-                };
-                // FIXME: Requires a range check
-                res
-            };
-            env.set_instruction_pointer(next_instruction_pointer);
-            env.set_next_instruction_pointer(addr);
+            let rd_scratch = env.alloc_scratch();
+            let less_than =
+                unsafe { env.test_less_than_signed(&local_rs1, &local_rs2, rd_scratch) };
+            Env::constant(1) - less_than
         }
         SBInstruction::BranchLessThanUnsigned => {
             // bltu: if (x[rs1] <u x[rs2]) pc += sext(offset)
-            let local_rs1 = env.read_register(&rs1);
-            let local_rs2 = env.read_register(&rs2);
-
-            let less_than = {
-                let rd_scratch = env.alloc_scratch();
-                unsafe { env.test_less_than(&local_rs1, &local_rs2, rd_scratch) }
-            };
-
-            let offset = (Env::constant(1) - less_than.clone()) * Env::constant(4)
-                + less_than.clone() * imm0_12;
-
-            let addr = {
-                let res_scratch = env.alloc_scratch();
-                let overflow_scratch = env.alloc_scratch();
-                let (res, _overflow) = unsafe {
-                    env.add_witness(&instruction_pointer, &offset, res_scratch, overflow_scratch)
-                };
-                // FIXME: Requires a range check
-                res
-            };
-
-            env.set_instruction_pointer(next_instruction_pointer);
-            env.set_next_instruction_pointer(addr);
+            let rd_scratch = env.alloc_scratch();
+            unsafe { env.test_less_than(&local_rs1, &local_rs2, rd_scratch) }
         }
         SBInstruction::BranchGreaterThanEqualUnsigned => {
             // bgeu: if (x[rs1] >=u x[rs2]) pc += sext(offset)
-            let local_rs1 = env.read_register(&rs1);
-            let local_rs2 = env.read_register(&rs2);
-
+            // greater than equal is the negation of less than
             let rd_scratch = env.alloc_scratch();
             let less_than = unsafe { env.test_less_than(&local_rs1, &local_rs2, rd_scratch) };
-            let offset =
-                less_than.clone() * Env::constant(4) + (Env::constant(1) - less_than) * imm0_12;
-
-            // greater than equal is the negation of less than
-            let addr = {
-                let res_scratch = env.alloc_scratch();
-                let overflow_scratch = env.alloc_scratch();
-                let (res, _overflow) = unsafe {
-                    env.add_witness(&instruction_pointer, &offset, res_scratch, overflow_scratch)
-                };
-                res
-            };
-
-            env.set_instruction_pointer(next_instruction_pointer);
-            env.set_next_instruction_pointer(addr);
+            Env::constant(1) - less_than
         }
     };
+
+    // The offset is relative to the address of the branch, and the execution
+    // continues with the following instruction when the branch is not taken.
+    let target = {
+        let res_scratch = env.alloc_scratch();
+        let overflow_scratch = env.alloc_scratch();
+        let (res, _overflow) = unsafe {
+            env.add_witness(
+                &instruction_pointer,
+                &imm0_12,
+                res_scratch,
+                overflow_scratch,
+            )
+        };
+        // FIXME: Requires a range check
+        res
+    };
+    let addr = taken.clone() * target + (Env::constant(1) - taken) * next_instruction_pointer;
+    env.set_instruction_pointer(addr.clone());
+    env.set_next_instruction_pointer(addr + Env::constant(4u32));
 }
 
 /// Interpret an U-type instruction.
@@ -2391,18 +2761,7 @@ pub fn interpret_sbtype<Env: InterpreterEnv>(env: &mut Env, instr: SBInstruction
 /// [here](https://www.cs.cornell.edu/courses/cs3410/2024fa/assignments/cpusim/riscv-instructions.pdf)
 pub fn interpret_utype<Env: InterpreterEnv>(env: &mut Env, instr: UInstruction) {
     let instruction_pointer = env.get_instruction_pointer();
-    let next_instruction_pointer = env.get_next_instruction_pointer();
-
-    let instruction = {
-        let v0 = env.read_memory(&instruction_pointer);
-        let v1 = env.read_memory(&(instruction_pointer.clone() + Env::constant(1)));
-        let v2 = env.read_memory(&(instruction_pointer.clone() + Env::constant(2)));
-        let v3 = env.read_memory(&(instruction_pointer.clone() + Env::constant(3)));
-        (v3 * Env::constant(1 << 24))
-            + (v2 * Env::constant(1 << 16))
-            + (v1 * Env::constant(1 << 8))
-            + v0
-    };
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
 
     let opcode = {
         let pos = env.alloc_scratch();
@@ -2492,18 +2851,7 @@ pub fn interpret_utype<Env: InterpreterEnv>(env: &mut Env, instr: UInstruction)
 /// ```
 pub fn interpret_ujtype<Env: InterpreterEnv>(env: &mut Env, instr: UJInstruction) {
     let instruction_pointer = env.get_instruction_pointer();
-    let next_instruction_pointer = env.get_next_instruction_pointer();
-
-    let instruction = {
-        let v0 = env.read_memory(&instruction_pointer);
-        let v1 = env.read_memory(&(instruction_pointer.clone() + Env::constant(1)));
-        let v2 = env.read_memory(&(instruction_pointer.clone() + Env::constant(2)));
-        let v3 = env.read_memory(&(instruction_pointer.clone() + Env::constant(3)));
-        (v3 * Env::constant(1 << 24))
-            + (v2 * Env::constant(1 << 16))
-            + (v1 * Env::constant(1 << 8))
-            + v0
-    };
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
 
     let opcode = {
         let pos = env.alloc_scratch();
@@ -2568,9 +2916,372 @@ pub fn interpret_ujtype<Env: InterpreterEnv>(env: &mut Env, instr: UJInstruction
     }
 }
 
-pub fn interpret_syscall<Env: InterpreterEnv>(env: &mut Env, _instr: SyscallInstruction) {
-    // FIXME: check if it is syscall success. There is only one syscall atm
-    env.set_halted(Env::constant(1));
+/// Interpret a syscall, triggered by the instruction `ecall`.
+///
+/// The syscalls follow the Linux ABI for RISC-V: the syscall number is given in
+/// register a7, the arguments in registers a0 to a5, and the result is written
+/// in a0. Errors are reported by returning a negative value, e.g. `-EBADF`.
+/// The decoding of the syscall number and of the file descriptor is performed
+/// when fetching the instruction, see
+/// [crate::interpreters::riscv32im::witness::Env::decode_instruction].
+pub fn interpret_syscall<Env: InterpreterEnv>(env: &mut Env, instr: SyscallInstruction) {
+    let instruction_pointer = env.get_instruction_pointer();
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
+    // All the syscalls share the same encoding, the variant being selected by
+    // the content of the registers.
+    env.add_constraint(instruction - Env::constant(ECALL_ENCODING));
+    // The syscall number must match the variant being executed.
+    let syscall = env.read_register(&Env::constant(REGISTER_A7));
+
+    match instr {
+        SyscallInstruction::SyscallSuccess => {
+            env.add_constraint(syscall - Env::constant(SYSCALL_SUCCESS));
+            env.set_halted(Env::constant(1));
+        }
+        SyscallInstruction::SyscallExit => {
+            let is_exit = env.equal(&syscall, &Env::constant(SYSCALL_EXIT));
+            let is_exit_group = env.equal(&syscall, &Env::constant(SYSCALL_EXIT_GROUP));
+            env.add_constraint(is_exit + is_exit_group - Env::constant(1));
+            let exit_code = env.read_register(&Env::constant(REGISTER_A0));
+            env.report_exit(&exit_code);
+            env.set_halted(Env::constant(1));
+        }
+        SyscallInstruction::SyscallReadHint => {
+            // We don't really write here, since the value is unused, per the
+            // cannon implementation. Just claim that we read the requested
+            // length.
+            env.add_constraint(syscall - Env::constant(SYSCALL_READ));
+            let fd_id = env.read_register(&Env::constant(REGISTER_A0));
+            env.add_constraint(fd_id - Env::constant(FD_HINT_READ));
+            let length = env.read_register(&Env::constant(REGISTER_A2));
+            env.write_register(&Env::constant(REGISTER_A0), length);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallReadPreimage => {
+            env.add_constraint(syscall - Env::constant(SYSCALL_READ));
+            let fd_id = env.read_register(&Env::constant(REGISTER_A0));
+            env.add_constraint(fd_id - Env::constant(FD_PREIMAGE_READ));
+            let addr = env.read_register(&Env::constant(REGISTER_A1));
+            let length = env.read_register(&Env::constant(REGISTER_A2));
+            let preimage_offset =
+                env.read_register(&Env::constant(REGISTER_PREIMAGE_OFFSET as u32));
+            let read_length = {
+                let pos = env.alloc_scratch();
+                env.request_preimage_write(&addr, &length, pos)
+            };
+            env.write_register(
+                &Env::constant(REGISTER_PREIMAGE_OFFSET as u32),
+                preimage_offset + read_length.clone(),
+            );
+            env.write_register(&Env::constant(REGISTER_A0), read_length);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallReadOther => {
+            env.add_constraint(syscall - Env::constant(SYSCALL_READ));
+            let fd_id = env.read_register(&Env::constant(REGISTER_A0));
+            // The hint and preimage file descriptors are handled by dedicated
+            // instructions, see decode_instruction.
+            let is_hint = env.equal(&fd_id, &Env::constant(FD_HINT_READ));
+            let is_preimage = env.equal(&fd_id, &Env::constant(FD_PREIMAGE_READ));
+            env.add_constraint(is_hint + is_preimage);
+            let is_stdin = env.equal(&fd_id, &Env::constant(FD_STDIN));
+            // Reading stdin always returns EOF, i.e. 0 bytes read. Any other
+            // file descriptor is rejected with EBADF.
+            let res = (Env::constant(1) - is_stdin) * Env::constant(EBADF_RETURN_VALUE);
+            env.write_register(&Env::constant(REGISTER_A0), res);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallWriteHint => {
+            env.add_constraint(syscall - Env::constant(SYSCALL_WRITE));
+            let fd_id = env.read_register(&Env::constant(REGISTER_A0));
+            env.add_constraint(fd_id - Env::constant(FD_HINT_WRITE));
+            let addr = env.read_register(&Env::constant(REGISTER_A1));
+            let length = env.read_register(&Env::constant(REGISTER_A2));
+            env.request_hint_write(&addr, &length);
+            env.write_register(&Env::constant(REGISTER_A0), length);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallWritePreimage => {
+            env.add_constraint(syscall - Env::constant(SYSCALL_WRITE));
+            let fd_id = env.read_register(&Env::constant(REGISTER_A0));
+            env.add_constraint(fd_id - Env::constant(FD_PREIMAGE_WRITE));
+            let addr = env.read_register(&Env::constant(REGISTER_A1));
+            let write_length = env.read_register(&Env::constant(REGISTER_A2));
+
+            // As in the MIPS interpreter, we assume that the remaining
+            // `write_length` represents how much of the key remains to be
+            // written, i.e. the program sends the full key in one syscall and
+            // retries with the rest until all the bytes have been written.
+            let bytes_to_preserve_in_register = {
+                let pos = env.alloc_scratch();
+                unsafe { env.bitmask(&write_length, 2, 0, pos) }
+            };
+            env.lookup_2bits(&bytes_to_preserve_in_register);
+            let register_idx = {
+                let registers_left_to_write_after_this = {
+                    let pos = env.alloc_scratch();
+                    // The virtual register is 32 bits wide, so we can just
+                    // read 6 bytes. If the register has an incorrect value, it
+                    // will be unprovable and we'll fault.
+                    unsafe { env.bitmask(&write_length, 6, 2, pos) }
+                };
+                env.range_check8(&registers_left_to_write_after_this, 4);
+                Env::constant(REGISTER_PREIMAGE_KEY_END as u32) - registers_left_to_write_after_this
+            };
+
+            let [r0, r1, r2, r3] = {
+                let register_value = {
+                    let initial_register_value = env.read_register(&register_idx);
+                    // We should clear the register if our offset into the read
+                    // will replace all of its bytes.
+                    let should_clear_register = env.is_zero(&bytes_to_preserve_in_register);
+                    let pos = env.alloc_scratch();
+                    env.copy(
+                        &((Env::constant(1) - should_clear_register) * initial_register_value),
+                        pos,
+                    )
+                };
+                [
+                    {
+                        let pos = env.alloc_scratch();
+                        unsafe { env.bitmask(&register_value, 32, 24, pos) }
+                    },
+                    {
+                        let pos = env.alloc_scratch();
+                        unsafe { env.bitmask(&register_value, 24, 16, pos) }
+                    },
+                    {
+                        let pos = env.alloc_scratch();
+                        unsafe { env.bitmask(&register_value, 16, 8, pos) }
+                    },
+                    {
+                        let pos = env.alloc_scratch();
+                        unsafe { env.bitmask(&register_value, 8, 0, pos) }
+                    },
+                ]
+            };
+            env.lookup_8bits(&r0);
+            env.lookup_8bits(&r1);
+            env.lookup_8bits(&r2);
+            env.lookup_8bits(&r3);
+
+            // We choose our read address so that the bytes we read come
+            // aligned with the target bytes in the register, to avoid an
+            // expensive bitshift.
+            let read_address = addr.clone() - bytes_to_preserve_in_register.clone();
+
+            let m0 = env.read_memory(&read_address);
+            let m1 = env.read_memory(&(read_address.clone() + Env::constant(1)));
+            let m2 = env.read_memory(&(read_address.clone() + Env::constant(2)));
+            let m3 = env.read_memory(&(read_address.clone() + Env::constant(3)));
+
+            // We only read the bytes up to the end of the current word, to
+            // follow the same semantic than the MIPS interpreter.
+            let [overwrite_0, overwrite_1, overwrite_2, overwrite_3] = {
+                let next_word_addr = {
+                    // The address is decomposed as `word * 4 + byte_subaddr`,
+                    // where `word` fits in 30 bits, checked with a 16 bits
+                    // and a 14 bits limbs.
+                    let [byte_subaddr, word_low, word_high] =
+                        [(2, 0), (18, 2), (32, 18)].map(|(highest_bit, lowest_bit)| {
+                            let pos = env.alloc_scratch();
+                            unsafe { env.bitmask(&addr, highest_bit, lowest_bit, pos) }
+                        });
+                    env.range_check8(&byte_subaddr, 2);
+                    env.lookup_16bits(&word_low);
+                    env.range_check16(&word_high, 14);
+                    env.add_constraint(
+                        addr.clone()
+                            - byte_subaddr.clone()
+                            - word_low * Env::constant(1 << 2)
+                            - word_high * Env::constant(1 << 18),
+                    );
+                    addr.clone() + Env::constant(4) - byte_subaddr
+                };
+                let overwrite_0 = env.equal(&bytes_to_preserve_in_register, &Env::constant(0));
+                let overwrite_1 = overwrite_0.clone()
+                    - env.equal(&(read_address.clone() + Env::constant(1)), &next_word_addr)
+                    + env.equal(&bytes_to_preserve_in_register, &Env::constant(1));
+                let overwrite_2 = overwrite_1.clone()
+                    - env.equal(&(read_address.clone() + Env::constant(2)), &next_word_addr)
+                    + env.equal(&bytes_to_preserve_in_register, &Env::constant(2));
+                let overwrite_3 = overwrite_2.clone()
+                    - env.equal(&(read_address.clone() + Env::constant(3)), &next_word_addr)
+                    + env.equal(&bytes_to_preserve_in_register, &Env::constant(3));
+                [overwrite_0, overwrite_1, overwrite_2, overwrite_3]
+            };
+
+            let value = {
+                let value = ((overwrite_0.clone() * m0
+                    + (Env::constant(1) - overwrite_0.clone()) * r0)
+                    * Env::constant(1 << 24))
+                    + ((overwrite_1.clone() * m1 + (Env::constant(1) - overwrite_1.clone()) * r1)
+                        * Env::constant(1 << 16))
+                    + ((overwrite_2.clone() * m2 + (Env::constant(1) - overwrite_2.clone()) * r2)
+                        * Env::constant(1 << 8))
+                    + (overwrite_3.clone() * m3 + (Env::constant(1) - overwrite_3.clone()) * r3);
+                let pos = env.alloc_scratch();
+                env.copy(&value, pos)
+            };
+
+            // Update the preimage key.
+            env.write_register(&register_idx, value);
+            // Reset the preimage offset.
+            env.write_register(
+                &Env::constant(REGISTER_PREIMAGE_OFFSET as u32),
+                Env::constant(0u32),
+            );
+            // Return the number of bytes written.
+            env.write_register(
+                &Env::constant(REGISTER_A0),
+                overwrite_0 + overwrite_1 + overwrite_2 + overwrite_3,
+            );
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallWriteOther => {
+            env.add_constraint(syscall - Env::constant(SYSCALL_WRITE));
+            let fd_id = env.read_register(&Env::constant(REGISTER_A0));
+            let is_hint = env.equal(&fd_id, &Env::constant(FD_HINT_WRITE));
+            let is_preimage = env.equal(&fd_id, &Env::constant(FD_PREIMAGE_WRITE));
+            env.add_constraint(is_hint + is_preimage);
+            let write_length = env.read_register(&Env::constant(REGISTER_A2));
+            let is_stdout = env.equal(&fd_id, &Env::constant(FD_STDOUT));
+            let is_stderr = env.equal(&fd_id, &Env::constant(FD_STDERR));
+            // Writing on stdout or stderr is accepted, and the output is
+            // discarded. Any other file descriptor is rejected with EBADF.
+            let known_fd = is_stdout + is_stderr;
+            let res = known_fd.clone() * write_length
+                + (Env::constant(1) - known_fd) * Env::constant(EBADF_RETURN_VALUE);
+            env.write_register(&Env::constant(REGISTER_A0), res);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallBrk => {
+            // brk(addr) moves the program break to `addr` and returns the new
+            // program break. As anonymous mappings are allocated from the same
+            // pointer, the break can only grow: requests below the current
+            // break, including the common `brk(0)` used to query it, leave it
+            // unchanged and return the current value, which callers interpret
+            // as a failure.
+            env.add_constraint(syscall - Env::constant(SYSCALL_BRK));
+            let requested_break = env.read_register(&Env::constant(REGISTER_A0));
+            let current_break = env.read_register(&Env::constant(REGISTER_HEAP_POINTER as u32));
+            let is_below = {
+                let pos = env.alloc_scratch();
+                unsafe { env.test_less_than(&requested_break, &current_break, pos) }
+            };
+            env.assert_boolean(&is_below);
+            // As both values are 32 bits, `requested_break - current_break +
+            // 2^32 * is_below` fits in 32 bits if and only if `is_below` is
+            // the result of the comparison. It is range checked with two 16
+            // bits limbs.
+            {
+                let difference = requested_break.clone()
+                    + is_below.clone() * Env::constant(1 << 16) * Env::constant(1 << 16)
+                    - current_break.clone();
+                let low = {
+                    let pos = env.alloc_scratch();
+                    unsafe { env.bitmask(&difference, 16, 0, pos) }
+                };
+                let high = {
+                    let pos = env.alloc_scratch();
+                    unsafe { env.bitmask(&difference, 32, 16, pos) }
+                };
+                env.lookup_16bits(&low);
+                env.lookup_16bits(&high);
+                env.add_constraint(difference - low - high * Env::constant(1 << 16));
+            }
+            let should_move = Env::constant(1) - is_below.clone();
+            // The increment is multiplied by the flag to avoid computing a
+            // negative value when the break does not move.
+            let increment =
+                should_move.clone() * requested_break.clone() - should_move.clone() * current_break;
+            let old_break = env.increase_heap_pointer(&increment, &should_move);
+            let new_break = {
+                let pos = env.alloc_scratch();
+                env.copy(&(is_below * old_break + should_move * requested_break), pos)
+            };
+            env.write_register(&Env::constant(REGISTER_A0), new_break);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallMmap => {
+            // mmap(addr, length, prot, flags, fd, offset). Only the address
+            // and the length are used: when no address is given, the memory
+            // is allocated from the heap pointer, rounded up to a full page.
+            // Otherwise, the address is returned as is.
+            env.add_constraint(syscall - Env::constant(SYSCALL_MMAP));
+            let requested_alloc_size = env.read_register(&Env::constant(REGISTER_A1));
+            // The size is decomposed as `size_in_pages * PAGE_SIZE +
+            // remainder`, where the remainder fits in `PAGE_ADDRESS_SIZE`
+            // bits and the number of pages in the remaining 20 bits, checked
+            // with a 16 bits and a 4 bits limbs.
+            let [remainder, pages_low, pages_high] = [
+                (PAGE_ADDRESS_SIZE, 0),
+                (PAGE_ADDRESS_SIZE + 16, PAGE_ADDRESS_SIZE),
+                (32, PAGE_ADDRESS_SIZE + 16),
+            ]
+            .map(|(highest_bit, lowest_bit)| {
+                let pos = env.alloc_scratch();
+                unsafe { env.bitmask(&requested_alloc_size, highest_bit, lowest_bit, pos) }
+            });
+            env.range_check16(&remainder, PAGE_ADDRESS_SIZE);
+            env.lookup_16bits(&pages_low);
+            env.range_check8(&pages_high, 32 - PAGE_ADDRESS_SIZE - 16);
+            let size_in_pages = pages_low + pages_high * Env::constant(1 << 16);
+            env.add_constraint(
+                requested_alloc_size
+                    - remainder.clone()
+                    - size_in_pages.clone() * Env::constant(1 << PAGE_ADDRESS_SIZE),
+            );
+            let requires_extra_page = Env::constant(1) - env.is_zero(&remainder);
+            let actual_alloc_size =
+                (size_in_pages + requires_extra_page) * Env::constant(1 << PAGE_ADDRESS_SIZE);
+            let address = env.read_register(&Env::constant(REGISTER_A0));
+            let address_is_zero = env.is_zero(&address);
+            let old_heap_ptr = env.increase_heap_pointer(&actual_alloc_size, &address_is_zero);
+            let return_position = {
+                let pos = env.alloc_scratch();
+                env.copy(
+                    &(address_is_zero.clone() * old_heap_ptr
+                        + (Env::constant(1) - address_is_zero) * address),
+                    pos,
+                )
+            };
+            env.write_register(&Env::constant(REGISTER_A0), return_position);
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+        SyscallInstruction::SyscallOther => {
+            // The syscalls implemented by the other variants are excluded,
+            // see decode_instruction.
+            let is_implemented = [
+                SYSCALL_SUCCESS,
+                SYSCALL_READ,
+                SYSCALL_WRITE,
+                SYSCALL_EXIT,
+                SYSCALL_EXIT_GROUP,
+                SYSCALL_BRK,
+                SYSCALL_MMAP,
+            ]
+            .into_iter()
+            .fold(Env::constant(0), |acc, number| {
+                acc + env.equal(&syscall, &Env::constant(number))
+            });
+            env.add_constraint(is_implemented);
+            env.write_register(
+                &Env::constant(REGISTER_A0),
+                Env::constant(ENOSYS_RETURN_VALUE),
+            );
+            env.set_instruction_pointer(next_instruction_pointer.clone());
+            env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+        }
+    }
 }
 
 /// Interpret an M-type instruction.
@@ -2583,18 +3294,7 @@ pub fn interpret_syscall<Env: InterpreterEnv>(env: &mut Env, _instr: SyscallInst
 /// [here](https://www.cs.cornell.edu/courses/cs3410/2024fa/assignments/cpusim/riscv-instructions.pdf)
 pub fn interpret_mtype<Env: InterpreterEnv>(env: &mut Env, instr: MInstruction) {
     let instruction_pointer = env.get_instruction_pointer();
-    let next_instruction_pointer = env.get_next_instruction_pointer();
-
-    let instruction = {
-        let v0 = env.read_memory(&instruction_pointer);
-        let v1 = env.read_memory(&(instruction_pointer.clone() + Env::constant(1)));
-        let v2 = env.read_memory(&(instruction_pointer.clone() + Env::constant(2)));
-        let v3 = env.read_memory(&(instruction_pointer.clone() + Env::constant(3)));
-        (v3 * Env::constant(1 << 24))
-            + (v2 * Env::constant(1 << 16))
-            + (v1 * Env::constant(1 << 8))
-            + v0
-    };
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
 
     let opcode = {
         let pos = env.alloc_scratch();
@@ -2767,3 +3467,365 @@ pub fn interpret_mtype<Env: InterpreterEnv>(env: &mut Env, instr: MInstruction)
         }
     }
 }
+
+/// Returns `x + y` modulo 2^32, for 32-bit values `x` and `y`.
+///
+/// The result is written with its overflow bit, and the caller must check
+/// that the result fits in 32 bits.
+fn add_32bits<Env: InterpreterEnv>(
+    env: &mut Env,
+    x: &Env::Variable,
+    y: &Env::Variable,
+) -> Env::Variable {
+    let res_scratch = env.alloc_scratch();
+    let overflow_scratch = env.alloc_scratch();
+    let (res, overflow) = unsafe { env.add_witness(x, y, res_scratch, overflow_scratch) };
+    env.assert_boolean(&overflow);
+    env.add_constraint(
+        x.clone() + y.clone()
+            - res.clone()
+            - overflow * Env::constant(1 << 16) * Env::constant(1 << 16),
+    );
+    res
+}
+
+/// Returns the 16-bit halves `(low, high)` of the 32-bit value `x`, and their
+/// sparse representations, checked with [`LookupTableIDs::ResetLookup`].
+#[allow(clippy::type_complexity)]
+fn sparse_halves<Env: InterpreterEnv>(
+    env: &mut Env,
+    x: &Env::Variable,
+) -> [(Env::Variable, Env::Variable); 2] {
+    let halves = [(16, 0), (32, 16)].map(|(highest_bit, lowest_bit)| {
+        let dense = {
+            let pos = env.alloc_scratch();
+            unsafe { env.bitmask(x, highest_bit, lowest_bit, pos) }
+        };
+        let sparse = {
+            let pos = env.alloc_scratch();
+            unsafe { env.sparse(&dense, pos) }
+        };
+        env.add_lookup(Lookup::read_one(
+            LookupTableIDs::ResetLookup,
+            vec![dense.clone(), sparse.clone()],
+        ));
+        (dense, sparse)
+    });
+    env.add_constraint(
+        x.clone() - halves[0].0.clone() - halves[1].0.clone() * Env::constant(1 << 16),
+    );
+    halves
+}
+
+/// Returns `(x xor y, x and y)` for 32-bit values `x` and `y`.
+///
+/// In the sparse representation, the sum of two 16-bit values holds the sum
+/// of their bits in each nibble, which is the sum of their xor and of twice
+/// their and.
+fn xor_and_32bits<Env: InterpreterEnv>(
+    env: &mut Env,
+    x: &Env::Variable,
+    y: &Env::Variable,
+) -> (Env::Variable, Env::Variable) {
+    let x_halves = sparse_halves(env, x);
+    let y_halves = sparse_halves(env, y);
+    let [(xor_lo, and_lo), (xor_hi, and_hi)] = [0, 1].map(|i| {
+        let (x_dense, x_sparse) = &x_halves[i];
+        let (y_dense, y_sparse) = &y_halves[i];
+        let [(xor, xor_sparse), (and, and_sparse)] = [true, false].map(|is_xor| {
+            let dense = {
+                let pos = env.alloc_scratch();
+                if is_xor {
+                    unsafe { env.xor_witness(x_dense, y_dense, pos) }
+                } else {
+                    unsafe { env.and_witness(x_dense, y_dense, pos) }
+                }
+            };
+            let sparse = {
+                let pos = env.alloc_scratch();
+                unsafe { env.sparse(&dense, pos) }
+            };
+            env.add_lookup(Lookup::read_one(
+                LookupTableIDs::ResetLookup,
+                vec![dense.clone(), sparse.clone()],
+            ));
+            (dense, sparse)
+        });
+        env.add_constraint(
+            x_sparse.clone() + y_sparse.clone() - xor_sparse - and_sparse * Env::constant(2),
+        );
+        (xor, and)
+    });
+    (
+        xor_lo + xor_hi * Env::constant(1 << 16),
+        and_lo + and_hi * Env::constant(1 << 16),
+    )
+}
+
+/// Returns 1 if `x < y` as unsigned 32-bit values, or 0 otherwise.
+///
+/// The result `lt` is checked by range checking `x + lt * 2^32 - y` on 32
+/// bits.
+fn less_than_32bits<Env: InterpreterEnv>(
+    env: &mut Env,
+    x: &Env::Variable,
+    y: &Env::Variable,
+) -> Env::Variable {
+    let lt = {
+        let pos = env.alloc_scratch();
+        unsafe { env.test_less_than(x, y, pos) }
+    };
+    env.assert_boolean(&lt);
+    let diff = x.clone() + lt.clone() * Env::constant(1 << 16) * Env::constant(1 << 16) - y.clone();
+    let [lo, hi] = [(16, 0), (32, 16)].map(|(highest_bit, lowest_bit)| {
+        let pos = env.alloc_scratch();
+        let half = unsafe { env.bitmask(&diff, highest_bit, lowest_bit, pos) };
+        env.lookup_16bits(&half);
+        half
+    });
+    env.add_constraint(diff - lo - hi * Env::constant(1 << 16));
+    lt
+}
+
+/// Returns `x` with its sign bit flipped, so that comparing flipped values as
+/// unsigned integers compares the original values as signed integers.
+fn flip_sign_32bits<Env: InterpreterEnv>(env: &mut Env, x: &Env::Variable) -> Env::Variable {
+    let [lo, hi, sign] = [(16, 0), (31, 16), (32, 31)].map(|(highest_bit, lowest_bit)| {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(x, highest_bit, lowest_bit, pos) }
+    });
+    env.lookup_16bits(&lo);
+    env.range_check16(&hi, 15);
+    env.assert_boolean(&sign);
+    env.add_constraint(
+        x.clone()
+            - lo.clone()
+            - hi.clone() * Env::constant(1 << 16)
+            - sign.clone() * Env::constant(1 << 31),
+    );
+    lo + hi * Env::constant(1 << 16) + (Env::constant(1) - sign) * Env::constant(1 << 31)
+}
+
+/// Interpret an A-type instruction.
+/// The encoding of an A-type instruction is as follows:
+/// ```text
+/// | 31     27 | 26 | 25 | 24     20 | 19     15 | 14        12 | 11    7 | 6      0 |
+/// |  funct5   | aq | rl |    rs2    |    rs1    |    funct3    |    rd   |  opcode  |
+/// ```
+/// Following the documentation found
+/// [here](https://msyksphinz-self.github.io/riscv-isadoc/html/rva.html)
+///
+/// The words are loaded and stored using the same byte order than the
+/// instructions `lw` and `sw`.
+pub fn interpret_atype<Env: InterpreterEnv>(env: &mut Env, instr: AInstruction) {
+    let instruction_pointer = env.get_instruction_pointer();
+    let (instruction, next_instruction_pointer) = env.fetch_instruction(&instruction_pointer);
+
+    let opcode = {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(&instruction, 7, 0, pos) }
+    };
+    env.range_check8(&opcode, 7);
+
+    let rd = {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(&instruction, 12, 7, pos) }
+    };
+    env.range_check8(&rd, 5);
+
+    let funct3 = {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(&instruction, 15, 12, pos) }
+    };
+    env.range_check8(&funct3, 3);
+
+    let rs1 = {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(&instruction, 20, 15, pos) }
+    };
+    env.range_check8(&rs1, 5);
+
+    let rs2 = {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(&instruction, 25, 20, pos) }
+    };
+    env.range_check8(&rs2, 5);
+
+    // The bits aq and rl
+    let ordering = {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(&instruction, 27, 25, pos) }
+    };
+    env.range_check8(&ordering, 2);
+
+    let funct5 = {
+        let pos = env.alloc_scratch();
+        unsafe { env.bitmask(&instruction, 32, 27, pos) }
+    };
+    env.range_check8(&funct5, 5);
+
+    // Check decomposition of A type instruction
+    env.add_constraint(
+        instruction
+            - (opcode.clone() * Env::constant(1 << 0))    // opcode at bits 0-6
+            - (rd.clone() * Env::constant(1 << 7))        // rd at bits 7-11
+            - (funct3.clone() * Env::constant(1 << 12))   // funct3 at bits 12-14
+            - (rs1.clone() * Env::constant(1 << 15))      // rs1 at bits 15-19
+            - (rs2.clone() * Env::constant(1 << 20))      // rs2 at bits 20-24
+            - (ordering.clone() * Env::constant(1 << 25)) // aq and rl at bits 25-26
+            - (funct5.clone() * Env::constant(1 << 27)), // funct5 at bits 27-31
+    );
+
+    // The operation is given by funct5, and funct3 is 010 for the 32-bit
+    // variants, which are the only ones in RV32
+    env.add_constraint(opcode - Env::constant(0b0101111));
+    env.add_constraint(funct3 - Env::constant(0b010));
+    let expected_funct5 = match instr {
+        AInstruction::LoadReserved => 0b00010,
+        AInstruction::StoreConditional => 0b00011,
+        AInstruction::AmoSwap => 0b00001,
+        AInstruction::AmoAdd => 0b00000,
+        AInstruction::AmoXor => 0b00100,
+        AInstruction::AmoAnd => 0b01100,
+        AInstruction::AmoOr => 0b01000,
+        AInstruction::AmoMin => 0b10000,
+        AInstruction::AmoMax => 0b10100,
+        AInstruction::AmoMinUnsigned => 0b11000,
+        AInstruction::AmoMaxUnsigned => 0b11100,
+    };
+    env.add_constraint(funct5 - Env::constant(expected_funct5));
+
+    // The address is given by rs1, without any offset, and it must be aligned
+    // on 4 bytes: its two lowest bits are zero.
+    let address = env.read_register(&rs1);
+    {
+        let address_2_18 = {
+            let pos = env.alloc_scratch();
+            unsafe { env.bitmask(&address, 18, 2, pos) }
+        };
+        env.lookup_16bits(&address_2_18);
+        let address_18_32 = {
+            let pos = env.alloc_scratch();
+            unsafe { env.bitmask(&address, 32, 18, pos) }
+        };
+        env.range_check16(&address_18_32, 14);
+        env.add_constraint(
+            address.clone()
+                - (address_2_18 * Env::constant(1 << 2))
+                - (address_18_32 * Env::constant(1 << 18)),
+        );
+    }
+    let local_rs2 = env.read_register(&rs2);
+    let old_value = {
+        let v0 = env.read_memory(&address);
+        let v1 = env.read_memory(&(address.clone() + Env::constant(1)));
+        let v2 = env.read_memory(&(address.clone() + Env::constant(2)));
+        let v3 = env.read_memory(&(address.clone() + Env::constant(3)));
+        (v0 * Env::constant(1 << 24))
+            + (v1 * Env::constant(1 << 16))
+            + (v2 * Env::constant(1 << 8))
+            + v3
+    };
+
+    let new_value = match instr {
+        AInstruction::LoadReserved => {
+            // lr.w: x[rd] = M[x[rs1]]
+            // The memory is left unchanged.
+            None
+        }
+        AInstruction::StoreConditional | AInstruction::AmoSwap => {
+            // sc.w: M[x[rs1]] = x[rs2], x[rd] = 0
+            // amoswap.w: M[x[rs1]] = x[rs2], x[rd] = old value
+            Some(local_rs2)
+        }
+        AInstruction::AmoAdd => {
+            // amoadd.w: M[x[rs1]] = old value + x[rs2]
+            Some(add_32bits(env, &old_value, &local_rs2))
+        }
+        AInstruction::AmoXor | AInstruction::AmoAnd | AInstruction::AmoOr => {
+            // amoxor.w, amoand.w, amoor.w: M[x[rs1]] = old value op x[rs2]
+            let (xor, and) = xor_and_32bits(env, &old_value, &local_rs2);
+            let res = match instr {
+                AInstruction::AmoXor => xor,
+                AInstruction::AmoAnd => and,
+                _ => xor + and,
+            };
+            let pos = env.alloc_scratch();
+            Some(env.copy(&res, pos))
+        }
+        AInstruction::AmoMin
+        | AInstruction::AmoMax
+        | AInstruction::AmoMinUnsigned
+        | AInstruction::AmoMaxUnsigned => {
+            // amomin[u].w, amomax[u].w: M[x[rs1]] = min/max(old value, x[rs2])
+            let old_is_less = match instr {
+                AInstruction::AmoMin | AInstruction::AmoMax => {
+                    let old_flipped = flip_sign_32bits(env, &old_value);
+                    let rs2_flipped = flip_sign_32bits(env, &local_rs2);
+                    less_than_32bits(env, &old_flipped, &rs2_flipped)
+                }
+                _ => less_than_32bits(env, &old_value, &local_rs2),
+            };
+            let (smallest, largest) = (
+                old_is_less.clone() * old_value.clone()
+                    + (Env::constant(1) - old_is_less.clone()) * local_rs2.clone(),
+                old_is_less.clone() * local_rs2.clone()
+                    + (Env::constant(1) - old_is_less) * old_value.clone(),
+            );
+            let res = match instr {
+                AInstruction::AmoMin | AInstruction::AmoMinUnsigned => smallest,
+                _ => largest,
+            };
+            let pos = env.alloc_scratch();
+            Some(env.copy(&res, pos))
+        }
+    };
+
+    if let Some(new_value) = new_value {
+        let [v0, v1, v2, v3] = [
+            {
+                let value_scratch = env.alloc_scratch();
+                unsafe { env.bitmask(&new_value, 32, 24, value_scratch) }
+            },
+            {
+                let value_scratch = env.alloc_scratch();
+                unsafe { env.bitmask(&new_value, 24, 16, value_scratch) }
+            },
+            {
+                let value_scratch = env.alloc_scratch();
+                unsafe { env.bitmask(&new_value, 16, 8, value_scratch) }
+            },
+            {
+                let value_scratch = env.alloc_scratch();
+                unsafe { env.bitmask(&new_value, 8, 0, value_scratch) }
+            },
+        ];
+
+        env.lookup_8bits(&v0);
+        env.lookup_8bits(&v1);
+        env.lookup_8bits(&v2);
+        env.lookup_8bits(&v3);
+        // The bytes of the new value also check that it fits in 32 bits
+        env.add_constraint(
+            new_value
+                - v0.clone() * Env::constant(1 << 24)
+                - v1.clone() * Env::constant(1 << 16)
+                - v2.clone() * Env::constant(1 << 8)
+                - v3.clone(),
+        );
+
+        env.write_memory(&address, v0);
+        env.write_memory(&(address.clone() + Env::constant(1u32)), v1);
+        env.write_memory(&(address.clone() + Env::constant(2u32)), v2);
+        env.write_memory(&(address.clone() + Env::constant(3u32)), v3);
+    }
+
+    match instr {
+        // The reservation can not be invalidated, the store always succeeds.
+        AInstruction::StoreConditional => env.write_register(&rd, Env::constant(0)),
+        _ => env.write_register(&rd, old_value),
+    }
+
+    env.set_instruction_pointer(next_instruction_pointer.clone());
+    env.set_next_instruction_pointer(next_instruction_pointer + Env::constant(4u32));
+}
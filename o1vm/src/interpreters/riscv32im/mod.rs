@@ -1,9 +1,33 @@
+/// The number of scratch columns allocated by the instructions. The columns
+/// following them are reserved for the communication channel between the
+/// preimage reads and the Keccak circuit.
+pub(crate) const SCRATCH_SIZE_WITHOUT_KECCAK: usize = 67;
+/// The number of preimages read entirely so far
+pub(crate) const RISCV_HASH_COUNTER_OFF: usize = SCRATCH_SIZE_WITHOUT_KECCAK;
+/// The number of bytes of the preimage read before this step, without its
+/// length prefix
+pub(crate) const RISCV_BYTE_COUNTER_OFF: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 1;
+/// A flag indicating whether the preimage is read entirely in this step
+pub(crate) const RISCV_END_OF_PREIMAGE_OFF: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 2;
+/// The at most 4 bytes of the preimage read in this step
+pub(crate) const RISCV_PREIMAGE_BYTES_OFF: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 3;
+/// Flags indicating whether at least N bytes of the preimage have been read
+/// in this step
+pub(crate) const RISCV_HAS_N_BYTES_OFF: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 3 + 4;
+/// The maximum number of bytes read in a step
+pub(crate) const RISCV_CHUNK_BYTES_LEN: usize = 4;
+/// The preimage key without its most significant byte, as a field element of
+/// 248 bits
+pub(crate) const RISCV_PREIMAGE_KEY: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 3 + 4 + 4;
+
 /// The minimal number of columns required for the VM
-pub const SCRATCH_SIZE: usize = 39;
-pub const SCRATCH_SIZE_INVERSE: usize = 1;
+// instructions + hash_counter + byte_counter + eof + bytes + has_n_bytes
+// + preimage key
+pub const SCRATCH_SIZE: usize = SCRATCH_SIZE_WITHOUT_KECCAK + 3 + 4 + 4 + 1;
+pub const SCRATCH_SIZE_INVERSE: usize = 11;
 
 /// Number of instructions in the ISA
-pub const INSTRUCTION_SET_SIZE: usize = 48;
+pub const INSTRUCTION_SET_SIZE: usize = 69;
 
 pub const PAGE_ADDRESS_SIZE: u32 = 12;
 pub const PAGE_SIZE: u32 = 1 << PAGE_ADDRESS_SIZE;
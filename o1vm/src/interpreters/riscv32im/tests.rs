@@ -1,13 +1,21 @@
 use super::{
-    registers::Registers, witness::Env, INSTRUCTION_SET_SIZE, PAGE_SIZE, SCRATCH_SIZE,
-    SCRATCH_SIZE_INVERSE,
+    registers::Registers,
+    witness::{Env, SyscallEnv},
+    INSTRUCTION_SET_SIZE, PAGE_SIZE, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE,
+    SCRATCH_SIZE_WITHOUT_KECCAK,
 };
-use crate::interpreters::riscv32im::{
-    constraints,
-    interpreter::{
-        interpret_instruction, IInstruction, Instruction, InterpreterEnv, MInstruction,
-        RInstruction, SBInstruction, SInstruction, SyscallInstruction, UInstruction, UJInstruction,
+use crate::{
+    cannon::{Hint, Preimage},
+    elf_loader::{parse_elf, Architecture},
+    interpreters::riscv32im::{
+        constraints,
+        interpreter::{
+            self, interpret_instruction, AInstruction, IInstruction, Instruction, InterpreterEnv,
+            MInstruction, RInstruction, SBInstruction, SInstruction, SyscallInstruction,
+            UInstruction, UJInstruction,
+        },
     },
+    preimage_oracle::{NullPreImageOracle, PreImageOracleT},
 };
 use ark_ff::Zero;
 use mina_curves::pasta::Fp;
@@ -34,6 +42,7 @@ fn test_regression_selectors_for_instructions() {
             + UJInstruction::COUNT
             + SyscallInstruction::COUNT
             + MInstruction::COUNT
+            + AInstruction::COUNT
     );
     // All instructions are degree 1 or 2.
     constraints
@@ -41,7 +50,7 @@ fn test_regression_selectors_for_instructions() {
         .for_each(|c| assert!(c.degree(1, 0) == 2 || c.degree(1, 0) == 1));
 }
 
-pub fn dummy_env() -> Env<Fp> {
+pub fn dummy_env() -> Env<Fp, NullPreImageOracle> {
     Env {
         instruction_counter: 0,
         memory: vec![(0, vec![0; PAGE_SIZE.try_into().unwrap()])],
@@ -55,7 +64,12 @@ pub fn dummy_env() -> Env<Fp> {
         scratch_state_inverse_idx: 0,
         scratch_state_inverse: [Fp::zero(); SCRATCH_SIZE_INVERSE],
         halt: false,
+        syscall_env: SyscallEnv::default(),
         selector: INSTRUCTION_SET_SIZE,
+        preimage_oracle: NullPreImageOracle,
+        preimage: None,
+        preimage_key: None,
+        hash_counter: 0,
     }
 }
 
@@ -475,7 +489,7 @@ pub fn generate_random_remu_instruction<RNG: RngCore + CryptoRng>(rng: &mut RNG)
 
 #[test]
 pub fn test_instruction_decoding_add() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_add_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -488,7 +502,7 @@ pub fn test_instruction_decoding_add() {
 
 #[test]
 pub fn test_instruction_decoding_sub() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_sub_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -501,7 +515,7 @@ pub fn test_instruction_decoding_sub() {
 
 #[test]
 pub fn test_instruction_decoding_sll() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_sll_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -514,7 +528,7 @@ pub fn test_instruction_decoding_sll() {
 
 #[test]
 pub fn test_instruction_decoding_slt() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_slt_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -527,7 +541,7 @@ pub fn test_instruction_decoding_slt() {
 
 #[test]
 pub fn test_instruction_decoding_sltu() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_sltu_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -543,7 +557,7 @@ pub fn test_instruction_decoding_sltu() {
 
 #[test]
 pub fn test_instruction_decoding_xor() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_xor_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -556,7 +570,7 @@ pub fn test_instruction_decoding_xor() {
 
 #[test]
 pub fn test_instruction_decoding_srl() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_srl_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -569,7 +583,7 @@ pub fn test_instruction_decoding_srl() {
 
 #[test]
 pub fn test_instruction_decoding_sr1() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_sra_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -585,7 +599,7 @@ pub fn test_instruction_decoding_sr1() {
 
 #[test]
 pub fn test_instruction_decoding_or() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_or_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -598,7 +612,7 @@ pub fn test_instruction_decoding_or() {
 
 #[test]
 pub fn test_instruction_decoding_and() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_and_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -611,7 +625,7 @@ pub fn test_instruction_decoding_and() {
 
 #[test]
 pub fn test_witness_bitmask_bounds() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     // Checking that the bit position given as upper bound is not included in
     // the output, i.e. the output is v[LOWER_BOUND:UPPER_BOUND-1]
     {
@@ -639,7 +653,7 @@ pub fn test_witness_bitmask_bounds() {
 
 #[test]
 pub fn test_instruction_decoding_mul() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_mul_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -652,7 +666,7 @@ pub fn test_instruction_decoding_mul() {
 
 #[test]
 pub fn test_instruction_decoding_mulh() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_mulh_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -665,7 +679,7 @@ pub fn test_instruction_decoding_mulh() {
 
 #[test]
 pub fn test_instruction_decoding_mulhsu() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_mulhsu_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -678,7 +692,7 @@ pub fn test_instruction_decoding_mulhsu() {
 
 #[test]
 pub fn test_instruction_decoding_mulhu() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_mulhu_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -691,7 +705,7 @@ pub fn test_instruction_decoding_mulhu() {
 
 #[test]
 pub fn test_instruction_decoding_div() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_div_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -704,7 +718,7 @@ pub fn test_instruction_decoding_div() {
 
 #[test]
 pub fn test_instruction_decoding_divu() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_divu_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -717,7 +731,7 @@ pub fn test_instruction_decoding_divu() {
 
 #[test]
 pub fn test_instruction_decoding_rem() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_rem_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -730,7 +744,7 @@ pub fn test_instruction_decoding_rem() {
 
 #[test]
 pub fn test_instruction_decoding_remu() {
-    let mut env: Env<Fp> = dummy_env();
+    let mut env = dummy_env();
     let mut rng = o1_utils::tests::make_test_rng(None);
     let instruction = generate_random_remu_instruction(&mut rng);
     env.memory[0].1[0] = instruction[0];
@@ -757,34 +771,34 @@ fn test_regression_number_of_constraints_per_instruction() {
         match instruction {
             Instruction::RType(rtype) => match rtype {
                 RInstruction::Add => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::Sub => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::ShiftLeftLogical => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::SetLessThan => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::SetLessThanUnsigned => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::Xor => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::ShiftRightLogical => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::ShiftRightArithmetic => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::Or => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::And => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 RInstruction::Fence => {
                     unimplemented!("Fence should not be implemented");
@@ -795,127 +809,515 @@ fn test_regression_number_of_constraints_per_instruction() {
             },
             Instruction::IType(itype) => match itype {
                 IInstruction::LoadByte => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::LoadHalf => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::LoadWord => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::LoadByteUnsigned => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::LoadHalfUnsigned => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::AddImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::SetLessThanImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::SetLessThanImmediateUnsigned => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::XorImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::OrImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::AndImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::ShiftLeftLogicalImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::ShiftRightLogicalImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::ShiftRightArithmeticImmediate => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 IInstruction::JumpAndLinkRegister => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
             },
             Instruction::SType(stype) => match stype {
                 SInstruction::StoreByte => {
-                    assert_eq!(env.constraints.len(), 1);
+                    assert_eq!(env.constraints.len(), 3);
                 }
                 SInstruction::StoreHalf => {
-                    assert_eq!(env.constraints.len(), 1);
+                    assert_eq!(env.constraints.len(), 3);
                 }
                 SInstruction::StoreWord => {
-                    assert_eq!(env.constraints.len(), 1);
+                    assert_eq!(env.constraints.len(), 3);
                 }
             },
             Instruction::SBType(sbtype) => match sbtype {
                 SBInstruction::BranchEq => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 SBInstruction::BranchNeq => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
                 SBInstruction::BranchLessThan => {
-                    assert_eq!(env.constraints.len(), 3);
+                    assert_eq!(env.constraints.len(), 5);
                 }
                 SBInstruction::BranchLessThanUnsigned => {
-                    assert_eq!(env.constraints.len(), 3);
+                    assert_eq!(env.constraints.len(), 5);
                 }
                 SBInstruction::BranchGreaterThanEqual => {
-                    assert_eq!(env.constraints.len(), 3);
+                    assert_eq!(env.constraints.len(), 5);
                 }
                 SBInstruction::BranchGreaterThanEqualUnsigned => {
-                    assert_eq!(env.constraints.len(), 3);
+                    assert_eq!(env.constraints.len(), 5);
                 }
             },
             Instruction::UType(utype) => match utype {
                 UInstruction::LoadUpperImmediate => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 UInstruction::AddUpperImmediate => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
             },
             Instruction::UJType(ujtype) => match ujtype {
                 UJInstruction::JumpAndLink => {
-                    assert_eq!(env.constraints.len(), 5);
+                    assert_eq!(env.constraints.len(), 7);
                 }
             },
             Instruction::SyscallType(syscall) => match syscall {
                 SyscallInstruction::SyscallSuccess => {
-                    assert_eq!(env.constraints.len(), 0);
+                    assert_eq!(env.constraints.len(), 4);
+                }
+                SyscallInstruction::SyscallExit => {
+                    assert_eq!(env.constraints.len(), 8);
+                }
+                SyscallInstruction::SyscallReadHint => {
+                    assert_eq!(env.constraints.len(), 8);
+                }
+                SyscallInstruction::SyscallReadPreimage => {
+                    assert_eq!(env.constraints.len(), 19);
+                }
+                SyscallInstruction::SyscallReadOther => {
+                    assert_eq!(env.constraints.len(), 14);
+                }
+                SyscallInstruction::SyscallWriteHint => {
+                    assert_eq!(env.constraints.len(), 8);
+                }
+                SyscallInstruction::SyscallWritePreimage => {
+                    assert_eq!(env.constraints.len(), 33);
+                }
+                SyscallInstruction::SyscallWriteOther => {
+                    assert_eq!(env.constraints.len(), 16);
+                }
+                SyscallInstruction::SyscallBrk => {
+                    assert_eq!(env.constraints.len(), 10);
+                }
+                SyscallInstruction::SyscallMmap => {
+                    assert_eq!(env.constraints.len(), 13);
+                }
+                SyscallInstruction::SyscallOther => {
+                    assert_eq!(env.constraints.len(), 21);
                 }
             },
             Instruction::MType(mtype) => match mtype {
                 MInstruction::Mul => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 MInstruction::Mulh => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 MInstruction::Mulhsu => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 MInstruction::Mulhu => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 MInstruction::Div => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 MInstruction::Divu => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 MInstruction::Rem => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
                 }
                 MInstruction::Remu => {
-                    assert_eq!(env.constraints.len(), 4);
+                    assert_eq!(env.constraints.len(), 6);
+                }
+            },
+            Instruction::AType(atype) => match atype {
+                AInstruction::LoadReserved => {
+                    assert_eq!(env.constraints.len(), 10);
+                }
+                AInstruction::StoreConditional => {
+                    assert_eq!(env.constraints.len(), 11);
+                }
+                AInstruction::AmoSwap => {
+                    assert_eq!(env.constraints.len(), 11);
+                }
+                AInstruction::AmoAdd => {
+                    assert_eq!(env.constraints.len(), 13);
+                }
+                AInstruction::AmoXor => {
+                    assert_eq!(env.constraints.len(), 16);
+                }
+                AInstruction::AmoAnd => {
+                    assert_eq!(env.constraints.len(), 16);
+                }
+                AInstruction::AmoOr => {
+                    assert_eq!(env.constraints.len(), 16);
+                }
+                AInstruction::AmoMin => {
+                    assert_eq!(env.constraints.len(), 18);
+                }
+                AInstruction::AmoMax => {
+                    assert_eq!(env.constraints.len(), 18);
+                }
+                AInstruction::AmoMinUnsigned => {
+                    assert_eq!(env.constraints.len(), 14);
+                }
+                AInstruction::AmoMaxUnsigned => {
+                    assert_eq!(env.constraints.len(), 14);
                 }
             },
         }
         env.reset()
     }
 }
+
+#[test]
+fn test_memory_page_allocated_without_write_index() {
+    let mut env = dummy_env();
+    // Reading the memory directly, as it is done when decoding instructions,
+    // allocates a page in the memory only.
+    let page_index = env.get_memory_page_index(5);
+    assert_eq!(env.memory[page_index].0, 5);
+    assert_eq!(env.memory_write_index.len(), 1);
+    // The last accesses refer to the indices of the memory pages
+    assert_eq!(env.get_memory_page_index(0), 0);
+    assert_eq!(env.get_memory_page_index(5), page_index);
+}
+
+// Sanity check that the scratch state is large enough for every instruction
+#[test]
+fn test_regression_scratch_state_size_per_instruction() {
+    let mut env = constraints::Env::<Fp>::default();
+    let unimplemented_instructions = [
+        Instruction::RType(RInstruction::Fence),
+        Instruction::RType(RInstruction::FenceI),
+    ];
+    let instructions = Instruction::iter()
+        .flat_map(|x| x.into_iter())
+        .filter(|x| !unimplemented_instructions.contains(x));
+    for instruction in instructions {
+        interpret_instruction(&mut env, instruction);
+        // The last scratch columns are reserved for the preimage reads
+        assert!(
+            env.scratch_state_idx <= SCRATCH_SIZE_WITHOUT_KECCAK,
+            "{instruction} uses too many scratch columns"
+        );
+        assert!(
+            env.scratch_state_idx_inverse <= SCRATCH_SIZE_INVERSE,
+            "{instruction} uses too many scratch inverse columns"
+        );
+        env.reset()
+    }
+}
+
+/// A preimage oracle returning a fixed preimage, and recording the keys and
+/// hints sent by the program.
+struct TestPreImageOracle {
+    preimage: Vec<u8>,
+    requested_keys: Vec<[u8; 32]>,
+    hints: Vec<Vec<u8>>,
+}
+
+impl PreImageOracleT for TestPreImageOracle {
+    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        self.requested_keys.push(key);
+        Preimage::create(self.preimage.clone())
+    }
+
+    fn hint(&mut self, hint: Hint) {
+        self.hints.push(hint.get())
+    }
+}
+
+fn run_program<PreImageOracle: PreImageOracleT>(
+    name: &str,
+    preimage_oracle: PreImageOracle,
+) -> (Env<Fp, PreImageOracle>, Vec<Instruction>) {
+    let curr_dir = std::env::current_dir().unwrap();
+    let path = curr_dir.join(std::path::PathBuf::from(format!(
+        "resources/programs/riscv32im/bin/{name}"
+    )));
+    let state = parse_elf(Architecture::RiscV32, &path).unwrap();
+    let mut witness = Env::create(PAGE_SIZE.try_into().unwrap(), state, preimage_oracle);
+    let mut syscalls = vec![];
+    while !witness.halt {
+        let instruction = witness.step();
+        if let Instruction::SyscallType(_) = instruction {
+            syscalls.push(instruction)
+        }
+    }
+    (witness, syscalls)
+}
+
+#[test]
+fn test_syscall_read_write() {
+    let (witness, syscalls) = run_program("syscall_write", NullPreImageOracle);
+    assert_eq!(
+        syscalls,
+        vec![
+            Instruction::SyscallType(SyscallInstruction::SyscallMmap),
+            Instruction::SyscallType(SyscallInstruction::SyscallWriteOther),
+            Instruction::SyscallType(SyscallInstruction::SyscallWriteOther),
+            Instruction::SyscallType(SyscallInstruction::SyscallReadOther),
+            Instruction::SyscallType(SyscallInstruction::SyscallReadOther),
+            Instruction::SyscallType(SyscallInstruction::SyscallExit),
+        ]
+    );
+    // s0 to s3
+    assert_eq!(witness.registers.general_purpose[8], 2);
+    assert_eq!(
+        witness.registers.general_purpose[9],
+        interpreter::EBADF_RETURN_VALUE
+    );
+    assert_eq!(witness.registers.general_purpose[18], 0);
+    assert_eq!(
+        witness.registers.general_purpose[19],
+        interpreter::EBADF_RETURN_VALUE
+    );
+    assert_eq!(witness.to_state().exit, 0);
+}
+
+#[test]
+fn test_syscall_brk_mmap() {
+    let (witness, syscalls) = run_program("syscall_brk_mmap", NullPreImageOracle);
+    assert_eq!(
+        syscalls,
+        vec![
+            Instruction::SyscallType(SyscallInstruction::SyscallBrk),
+            Instruction::SyscallType(SyscallInstruction::SyscallBrk),
+            Instruction::SyscallType(SyscallInstruction::SyscallMmap),
+            Instruction::SyscallType(SyscallInstruction::SyscallMmap),
+            Instruction::SyscallType(SyscallInstruction::SyscallExit),
+        ]
+    );
+    let initial_break = witness.registers.general_purpose[8];
+    // The program break starts on the page following the code
+    assert_eq!(initial_break % PAGE_SIZE, 0);
+    assert!(initial_break > witness.registers.current_instruction_pointer);
+    assert_eq!(witness.registers.general_purpose[9], initial_break + 8192);
+    assert_eq!(witness.registers.general_purpose[18], initial_break + 8192);
+    assert_eq!(
+        witness.registers.general_purpose[19],
+        initial_break + 8192 + PAGE_SIZE
+    );
+    assert_eq!(witness.registers.general_purpose[20], 0x12345678);
+    assert_eq!(
+        witness.registers.heap_pointer,
+        initial_break + 8192 + 2 * PAGE_SIZE
+    );
+    // exit_group(3)
+    assert_eq!(witness.registers.general_purpose[10], 3);
+    let state = witness.to_state();
+    assert!(state.exited);
+    assert_eq!(state.exit, 3);
+}
+
+#[test]
+fn test_syscall_preimage_and_hint() {
+    let preimage_oracle = TestPreImageOracle {
+        preimage: b"hello world".to_vec(),
+        requested_keys: vec![],
+        hints: vec![],
+    };
+    let (witness, syscalls) = run_program("syscall_preimage", preimage_oracle);
+    assert!(syscalls.contains(&Instruction::SyscallType(
+        SyscallInstruction::SyscallWritePreimage
+    )));
+    assert!(syscalls.contains(&Instruction::SyscallType(
+        SyscallInstruction::SyscallReadPreimage
+    )));
+
+    let mut expected_key = [0u8; 32];
+    expected_key[0] = 2;
+    expected_key[31] = 42;
+    assert_eq!(witness.preimage_oracle.requested_keys, vec![expected_key]);
+    assert_eq!(witness.preimage_key, Some(expected_key));
+
+    // The length of the preimage is read first, as a big-endian u64
+    let read_len = witness.registers.general_purpose[9];
+    assert_eq!(read_len, 8 + 11);
+    assert_eq!(witness.registers.preimage_offset, read_len);
+
+    // The preimage has been read entirely, and sent to the Keccak circuit
+    assert_eq!(witness.hash_counter, 1);
+
+    assert_eq!(witness.preimage_oracle.hints, vec![b"hi".to_vec()]);
+    assert_eq!(witness.registers.general_purpose[18], 6);
+    assert_eq!(witness.registers.general_purpose[19], 10);
+}
+
+#[test]
+fn test_syscall_other() {
+    let mut env = dummy_env();
+    // ecall, with the syscall getpid
+    env.memory[0].1[0..4].copy_from_slice(&0x00000073u32.to_le_bytes());
+    env.registers.general_purpose[interpreter::REGISTER_A7 as usize] = 172;
+    let instruction = env.step();
+    assert_eq!(
+        instruction,
+        Instruction::SyscallType(SyscallInstruction::SyscallOther)
+    );
+    assert_eq!(
+        env.registers.general_purpose[interpreter::REGISTER_A0 as usize],
+        interpreter::ENOSYS_RETURN_VALUE
+    );
+    assert_eq!(env.registers.current_instruction_pointer, 4);
+    assert!(!env.halt);
+}
+
+#[test]
+pub fn test_instruction_decoding_atomic() {
+    let mut env = dummy_env();
+    // lr.w a0, (a1)
+    env.memory[0].1[0..4].copy_from_slice(&0x1005a52fu32.to_le_bytes());
+    let (opcode, _instruction) = env.decode_instruction();
+    assert_eq!(opcode, Instruction::AType(AInstruction::LoadReserved));
+    // amomaxu.w.aqrl a0, a2, (a1)
+    env.memory[0].1[0..4].copy_from_slice(&0xe6c5a52fu32.to_le_bytes());
+    let (opcode, _instruction) = env.decode_instruction();
+    assert_eq!(opcode, Instruction::AType(AInstruction::AmoMaxUnsigned));
+}
+
+#[test]
+pub fn test_instruction_decoding_compressed() {
+    let mut env = dummy_env();
+    // Only the two first bytes are part of the instruction, the following
+    // ones must be ignored.
+    // c.li a0, 5 is expanded to addi a0, x0, 5
+    env.memory[0].1[0..4].copy_from_slice(&[0x15, 0x45, 0xff, 0xff]);
+    let (opcode, instruction) = env.decode_instruction();
+    assert_eq!(opcode, Instruction::IType(IInstruction::AddImmediate));
+    assert_eq!(instruction, 0x00500513);
+
+    // c.mv and c.jr, c.add and c.jalr only differ by the register rs2
+    let cases = [
+        // c.mv a2, a0 -> add a2, x0, a0
+        (0x862a, 0x00a00633, Instruction::RType(RInstruction::Add)),
+        // c.jr ra -> jalr x0, 0(ra)
+        (
+            0x8082,
+            0x00008067,
+            Instruction::IType(IInstruction::JumpAndLinkRegister),
+        ),
+        // c.add a2, a1 -> add a2, a2, a1
+        (0x962e, 0x00b60633, Instruction::RType(RInstruction::Add)),
+        // c.jalr t4 -> jalr ra, 0(t4)
+        (
+            0x9e82,
+            0x000e80e7,
+            Instruction::IType(IInstruction::JumpAndLinkRegister),
+        ),
+        // c.addi16sp and c.lui only differ by the register rd
+        // c.addi16sp sp, 16 -> addi sp, sp, 16
+        (
+            0x6141,
+            0x01010113,
+            Instruction::IType(IInstruction::AddImmediate),
+        ),
+        // c.lui a1, 1 -> lui a1, 1
+        (
+            0x6585,
+            0x000015b7,
+            Instruction::UType(UInstruction::LoadUpperImmediate),
+        ),
+    ];
+    for (compressed, expanded, expected) in cases {
+        env.memory[0].1[0..2].copy_from_slice(&(compressed as u16).to_le_bytes());
+        let (opcode, instruction) = env.decode_instruction();
+        assert_eq!(instruction, expanded, "{compressed:#06x}");
+        assert_eq!(opcode, expected, "{compressed:#06x}");
+    }
+}
+
+#[test]
+pub fn test_expand_compressed_instruction() {
+    for instruction in 0..(1 << 16) {
+        let expanded = interpreter::expand_compressed_instruction(instruction);
+        if instruction & 0b11 == 0b11 {
+            assert_eq!(expanded, None);
+        } else if let Some(expanded) = expanded {
+            // Compressed instructions are expanded to 32-bit instructions
+            assert_eq!(expanded & 0b11, 0b11, "{instruction:#06x}");
+        }
+    }
+    // The illegal instruction, c.ebreak, and c.fld are not expanded
+    for instruction in [0x0000, 0x9002, 0x2000] {
+        assert_eq!(
+            interpreter::expand_compressed_instruction(instruction),
+            None,
+            "{instruction:#06x}"
+        );
+    }
+}
+
+#[test]
+fn test_atomic_instructions() {
+    let (witness, _syscalls) = run_program("atomic", NullPreImageOracle);
+    let registers = &witness.registers.general_purpose;
+    // s1 to s11
+    assert_eq!(registers[9], 5);
+    assert_eq!(registers[18], 0);
+    assert_eq!(registers[19], 7);
+    assert_eq!(registers[20], 10);
+    assert_eq!(registers[21], 12);
+    assert_eq!(registers[22], 6);
+    assert_eq!(registers[23], 2);
+    assert_eq!(registers[24], 10);
+    assert_eq!(registers[25], -1i32 as u32);
+    assert_eq!(registers[26], 4);
+    assert_eq!(registers[27], 4);
+    // a3, the final value of the word in memory
+    assert_eq!(registers[13], -1i32 as u32);
+}
+
+#[test]
+fn test_compressed_instructions() {
+    let (witness, _syscalls) = run_program("compressed", NullPreImageOracle);
+    let registers = &witness.registers.general_purpose;
+    // s2 to s11
+    assert_eq!(registers[18], 0x1008);
+    assert_eq!(registers[19], 16);
+    assert_eq!(registers[20], -4i32 as u32);
+    assert_eq!(registers[21], 4);
+    assert_eq!(registers[22], 2);
+    assert_eq!(registers[23], 6);
+    assert_eq!(registers[24], 14);
+    assert_eq!(registers[25], 8);
+    assert_eq!(registers[26], 8);
+    assert_eq!(registers[27], 6);
+    // t0 to t3
+    assert_eq!(registers[5], 0x1008);
+    assert_eq!(registers[6], 10);
+    assert_eq!(registers[7], 0x2018);
+    assert_eq!(registers[28], 7);
+    // sp
+    assert_eq!(registers[2], 0x2010);
+}
@@ -3,18 +3,25 @@
 use super::{
     column::Column,
     interpreter::{
-        self, IInstruction, Instruction, InterpreterEnv, MInstruction, RInstruction, SBInstruction,
-        SInstruction, SyscallInstruction, UInstruction, UJInstruction,
+        self, AInstruction, IInstruction, Instruction, InterpreterEnv, MInstruction, RInstruction,
+        SBInstruction, SInstruction, SyscallInstruction, UInstruction, UJInstruction,
     },
     registers::Registers,
-    INSTRUCTION_SET_SIZE, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE,
+    INSTRUCTION_SET_SIZE, RISCV_BYTE_COUNTER_OFF, RISCV_END_OF_PREIMAGE_OFF,
+    RISCV_HASH_COUNTER_OFF, RISCV_HAS_N_BYTES_OFF, RISCV_PREIMAGE_BYTES_OFF, RISCV_PREIMAGE_KEY,
+    SCRATCH_SIZE, SCRATCH_SIZE_INVERSE,
 };
 use crate::{
-    cannon::{State, PAGE_ADDRESS_MASK, PAGE_ADDRESS_SIZE, PAGE_SIZE},
+    cannon::{
+        Hint, Page, Start, State, StepFrequency, VmConfiguration, PAGE_ADDRESS_MASK,
+        PAGE_ADDRESS_SIZE, PAGE_SIZE,
+    },
     lookups::Lookup,
+    preimage_oracle::PreImageOracleT,
 };
 use ark_ff::Field;
-use std::array;
+use log::{debug, info};
+use std::{array, fs::File, io::BufWriter};
 
 /// Maximum number of register accesses per instruction (based on demo)
 // FIXME: can be different
@@ -31,12 +38,29 @@ pub const NUM_INSTRUCTION_LOOKUP_TERMS: usize = 5;
 pub const NUM_LOOKUP_TERMS: usize =
     NUM_GLOBAL_LOOKUP_TERMS + NUM_DECODING_LOOKUP_TERMS + NUM_INSTRUCTION_LOOKUP_TERMS;
 
+#[derive(Clone, Default)]
+pub struct SyscallEnv {
+    pub last_hint: Option<Vec<u8>>,
+    /// The exit code passed by the program to the exit syscall, truncated to
+    /// its lowest byte as by the Linux kernel
+    pub exit_code: u8,
+}
+
+impl SyscallEnv {
+    pub fn create(state: &State) -> Self {
+        SyscallEnv {
+            last_hint: state.last_hint.clone(),
+            exit_code: state.exit,
+        }
+    }
+}
+
 /// This structure represents the environment the virtual machine state will use
 /// to transition. This environment will be used by the interpreter. The virtual
 /// machine has access to its internal state and some external memory. In
 /// addition to that, it has access to the environment of the Keccak interpreter
 /// that is used to verify the preimage requested during the execution.
-pub struct Env<Fp> {
+pub struct Env<Fp, PreImageOracle: PreImageOracleT> {
     pub instruction_counter: u64,
     pub memory: Vec<(u32, Vec<u8>)>,
     pub last_memory_accesses: [usize; 3],
@@ -49,14 +73,22 @@ pub struct Env<Fp> {
     pub scratch_state_inverse_idx: usize,
     pub scratch_state_inverse: [Fp; SCRATCH_SIZE_INVERSE],
     pub halt: bool,
+    pub syscall_env: SyscallEnv,
     pub selector: usize,
+    pub preimage_oracle: PreImageOracle,
+    pub preimage: Option<Vec<u8>>,
+    pub preimage_key: Option<[u8; 32]>,
+    /// The number of preimages read entirely so far, which identifies the
+    /// preimage being read in the communication channel with the Keccak
+    /// circuit
+    pub hash_counter: u64,
 }
 
 fn fresh_scratch_state<Fp: Field, const N: usize>() -> [Fp; N] {
     array::from_fn(|_| Fp::zero())
 }
 
-impl<Fp: Field> InterpreterEnv for Env<Fp> {
+impl<Fp: Field, PreImageOracle: PreImageOracleT> InterpreterEnv for Env<Fp, PreImageOracle> {
     type Position = Column;
 
     fn alloc_scratch(&mut self) -> Self::Position {
@@ -404,6 +436,13 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         res
     }
 
+    unsafe fn sparse(&mut self, x: &Self::Variable, position: Self::Position) -> Self::Variable {
+        let x: u16 = (*x).try_into().unwrap();
+        let res = u64::from_str_radix(&format!("{:b}", x), 16).unwrap();
+        self.write_column(position, res);
+        res
+    }
+
     unsafe fn add_witness(
         &mut self,
         x: &Self::Variable,
@@ -607,6 +646,20 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         res
     }
 
+    unsafe fn expand_instruction(
+        &mut self,
+        halfword: &Self::Variable,
+        position_expanded: Self::Position,
+        position_is_compressed: Self::Position,
+    ) -> (Self::Variable, Self::Variable) {
+        let halfword: u32 = (*halfword).try_into().unwrap();
+        let expanded = interpreter::expand_compressed_instruction(halfword).unwrap_or(0) as u64;
+        let is_compressed = (halfword & 0b11 != 0b11) as u64;
+        self.write_column(position_expanded, expanded);
+        self.write_column(position_is_compressed, is_compressed);
+        (expanded, is_compressed)
+    }
+
     fn copy(&mut self, x: &Self::Variable, position: Self::Position) -> Self::Variable {
         self.write_column(position, *x);
         *x
@@ -623,6 +676,7 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
     }
 
     fn report_exit(&mut self, exit_code: &Self::Variable) {
+        self.syscall_env.exit_code = *exit_code as u8;
         println!(
             "Exited with code {} at step {}",
             *exit_code,
@@ -630,6 +684,138 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
         );
     }
 
+    fn request_preimage_write(
+        &mut self,
+        addr: &Self::Variable,
+        len: &Self::Variable,
+        pos: Self::Position,
+    ) -> Self::Variable {
+        // The beginning of the syscall
+        if self.registers.preimage_offset == 0 {
+            let mut preimage_key = [0u8; 32];
+            for i in 0..8 {
+                let bytes = u32::to_be_bytes(self.registers.preimage_key[i]);
+                for j in 0..4 {
+                    preimage_key[4 * i + j] = bytes[j]
+                }
+            }
+            let preimage = self.preimage_oracle.get_preimage(preimage_key).get();
+            self.preimage = Some(preimage.clone());
+            self.preimage_key = Some(preimage_key);
+        }
+
+        const LENGTH_SIZE: usize = 8;
+
+        let preimage_len = self
+            .preimage
+            .as_ref()
+            .expect("to have a preimage if we're requesting it at a non-zero offset")
+            .len();
+        let preimage_offset = self.registers.preimage_offset as u64;
+
+        let max_read_len =
+            std::cmp::min(preimage_offset + len, (preimage_len + LENGTH_SIZE) as u64)
+                - preimage_offset;
+
+        // We read at most 4 bytes, ensuring that we respect word alignment.
+        // If the address is not aligned, the first call will read less than 4
+        // bytes, and the next ones will be aligned.
+        let actual_read_len = std::cmp::min(max_read_len, 4 - (addr & 3));
+
+        // The bytes of the preimage read in this step are sent to the Keccak
+        // circuit, see [super::constraints::Env::request_preimage_write].
+        self.write_column(
+            Column::ScratchState(RISCV_HASH_COUNTER_OFF),
+            self.hash_counter,
+        );
+        self.write_column(
+            Column::ScratchState(RISCV_BYTE_COUNTER_OFF),
+            preimage_offset.saturating_sub(LENGTH_SIZE as u64),
+        );
+        let mut preimage_read_len = 0;
+        for i in 0..actual_read_len {
+            let idx = (preimage_offset + i) as usize;
+            // The first 8 bytes of the read preimage are the preimage length,
+            // followed by the body of the preimage
+            let byte = if idx < LENGTH_SIZE {
+                u64::to_be_bytes(preimage_len as u64)[idx]
+            } else {
+                let byte = self.preimage.as_ref().unwrap()[idx - LENGTH_SIZE];
+                self.write_column(
+                    Column::ScratchState(RISCV_PREIMAGE_BYTES_OFF + preimage_read_len),
+                    byte as u64,
+                );
+                self.write_column(
+                    Column::ScratchState(RISCV_HAS_N_BYTES_OFF + preimage_read_len),
+                    1,
+                );
+                preimage_read_len += 1;
+                byte
+            };
+            unsafe {
+                self.push_memory(&(*addr + i), byte as u64);
+                self.push_memory_access(&(*addr + i), self.next_instruction_counter());
+            }
+        }
+
+        if actual_read_len > 0
+            && preimage_offset + actual_read_len == (preimage_len + LENGTH_SIZE) as u64
+        {
+            debug!("Preimage has been read entirely");
+            self.write_column(Column::ScratchState(RISCV_END_OF_PREIMAGE_OFF), 1);
+            // The key is stored without its most significant byte, so that it
+            // fits in a field element
+            let preimage_key = (1..32).fold(Fp::zero(), |acc, i| {
+                let byte = self.registers.preimage_key[i / 4].to_be_bytes()[i % 4];
+                acc * Fp::from(1u64 << 8) + Fp::from(byte)
+            });
+            self.write_field_column(Column::ScratchState(RISCV_PREIMAGE_KEY), preimage_key);
+            self.hash_counter += 1;
+        }
+
+        self.write_column(pos, actual_read_len);
+        actual_read_len
+    }
+
+    fn request_hint_write(&mut self, addr: &Self::Variable, len: &Self::Variable) {
+        let mut last_hint = match std::mem::take(&mut self.syscall_env.last_hint) {
+            Some(mut last_hint) => {
+                last_hint.reserve(*len as usize);
+                last_hint
+            }
+            None => Vec::with_capacity(*len as usize),
+        };
+
+        for i in 0..*len {
+            // Push memory access
+            unsafe { self.push_memory_access(&(*addr + i), self.next_instruction_counter()) };
+            // Fetch the value without allocating witness columns
+            let value = {
+                let addr: u32 = (*addr + i).try_into().unwrap();
+                self.get_memory_direct(addr)
+            };
+            last_hint.push(value);
+        }
+
+        let len = last_hint.len();
+        let mut idx = 0;
+
+        // A hint is prefixed by its length, encoded as a big-endian u32
+        while idx + 4 <= len {
+            let hint_len = u32::from_be_bytes(last_hint[idx..idx + 4].try_into().unwrap()) as usize;
+            idx += 4;
+            if idx + hint_len <= len {
+                let hint = last_hint[idx..idx + hint_len].to_vec();
+                idx += hint_len;
+                self.preimage_oracle.hint(Hint::create(hint));
+            }
+        }
+
+        let remaining = last_hint[idx..len].to_vec();
+
+        self.syscall_env.last_hint = Some(remaining);
+    }
+
     fn reset(&mut self) {
         self.scratch_state_idx = 0;
         self.scratch_state = fresh_scratch_state();
@@ -637,13 +823,15 @@ impl<Fp: Field> InterpreterEnv for Env<Fp> {
     }
 }
 
-impl<Fp: Field> Env<Fp> {
-    pub fn create(page_size: usize, state: State) -> Self {
+impl<Fp: Field, PreImageOracle: PreImageOracleT> Env<Fp, PreImageOracle> {
+    pub fn create(page_size: usize, state: State, preimage_oracle: PreImageOracle) -> Self {
         let initial_instruction_pointer = state.pc;
         let next_instruction_pointer = state.next_pc;
 
         let selector = INSTRUCTION_SET_SIZE;
 
+        let syscall_env = SyscallEnv::create(&state);
+
         let mut initial_memory: Vec<(u32, Vec<u8>)> = state
             .memory
             .into_iter()
@@ -662,11 +850,22 @@ impl<Fp: Field> Env<Fp> {
             .collect::<Vec<_>>();
 
         let initial_registers = {
+            let preimage_key = {
+                let mut preimage_key = [0u32; 8];
+                for (i, preimage_key_word) in preimage_key.iter_mut().enumerate() {
+                    *preimage_key_word = u32::from_be_bytes(
+                        state.preimage_key[i * 4..(i + 1) * 4].try_into().unwrap(),
+                    )
+                }
+                preimage_key
+            };
             Registers {
                 general_purpose: state.registers,
                 current_instruction_pointer: initial_instruction_pointer,
                 next_instruction_pointer,
                 heap_pointer: state.heap,
+                preimage_key,
+                preimage_offset: state.preimage_offset,
             }
         };
 
@@ -675,7 +874,8 @@ impl<Fp: Field> Env<Fp> {
         // set the stack pointer to the top of the stack
 
         Env {
-            instruction_counter: state.step,
+            // The state stores the normalized instruction counter
+            instruction_counter: state.step * MAX_ACC,
             memory: initial_memory.clone(),
             last_memory_accesses: [0usize; 3],
             memory_write_index: memory_offsets
@@ -690,7 +890,12 @@ impl<Fp: Field> Env<Fp> {
             scratch_state_inverse_idx: 0,
             scratch_state_inverse: fresh_scratch_state(),
             halt: state.exited,
+            syscall_env,
             selector,
+            preimage_oracle,
+            preimage: state.preimage,
+            preimage_key: None,
+            hash_counter: 0,
         }
     }
 
@@ -708,6 +913,21 @@ impl<Fp: Field> Env<Fp> {
                     << 8)
                 | (self.get_memory_direct(self.registers.current_instruction_pointer + 3) as u32);
         let instruction = instruction.to_be(); // convert to big endian for more straightforward decoding
+
+        // Compressed instructions are 16 bits long, and their two lowest bits
+        // are different from 11. They are decoded as the 32-bit instruction
+        // they are expanded to.
+        let instruction = if instruction & 0b11 != 0b11 {
+            let compressed = instruction & 0xFFFF;
+            interpreter::expand_compressed_instruction(compressed).unwrap_or_else(|| {
+                panic!(
+                    "Unknown or unsupported compressed instruction {:#06x}",
+                    compressed
+                )
+            })
+        } else {
+            instruction
+        };
         let opcode = {
             match instruction & 0b1111111 // bits 0-6
             {
@@ -827,6 +1047,28 @@ impl<Fp: Field> Env<Fp> {
                         _ => panic!("Unknown RType 0110011 instruction with full inst {} (funct5 = {}, funct2 = {}, funct3 = {})", instruction, funct5, funct2, funct3),
                     }
                 }
+                0b0101111 => {
+                    let funct5 = instruction >> 27 & 0x1F; // bits 27-31 for funct5
+                    let funct3 = instruction >> 12 & 0x7; // bits 12-14 for func3
+                    // Only the instructions on 32 bits words are supported
+                    if funct3 != 0b010 {
+                        panic!("Unknown AType instruction with full inst {} (funct5 = {}, funct3 = {})", instruction, funct5, funct3)
+                    }
+                    match funct5 {
+                        0b00010 => Instruction::AType(AInstruction::LoadReserved),
+                        0b00011 => Instruction::AType(AInstruction::StoreConditional),
+                        0b00001 => Instruction::AType(AInstruction::AmoSwap),
+                        0b00000 => Instruction::AType(AInstruction::AmoAdd),
+                        0b00100 => Instruction::AType(AInstruction::AmoXor),
+                        0b01100 => Instruction::AType(AInstruction::AmoAnd),
+                        0b01000 => Instruction::AType(AInstruction::AmoOr),
+                        0b10000 => Instruction::AType(AInstruction::AmoMin),
+                        0b10100 => Instruction::AType(AInstruction::AmoMax),
+                        0b11000 => Instruction::AType(AInstruction::AmoMinUnsigned),
+                        0b11100 => Instruction::AType(AInstruction::AmoMaxUnsigned),
+                        _ => panic!("Unknown AType instruction with full inst {} (funct5 = {}, funct3 = {})", instruction, funct5, funct3),
+                    }
+                }
                 0b0001111 =>
                 match (instruction >> 12) & 0x7 // bits 12-14 for func3
                 {
@@ -834,10 +1076,26 @@ impl<Fp: Field> Env<Fp> {
                     0b001 => Instruction::RType(RInstruction::FenceI),
                     _ => panic!("Unknown RType 0001111 (Fence) instruction with full inst {}", instruction),
                 },
-                // FIXME: we should implement more syscalls here, and check the register state.
-                // Even better, only one constructor call ecall, and in the
-                // interpreter, we do the action depending on it
-                0b1110011 => Instruction::SyscallType(SyscallInstruction::SyscallSuccess),
+                // The syscall is given by the register a7, and the file
+                // descriptor for read and write by the register a0.
+                0b1110011 => match self.registers.general_purpose[interpreter::REGISTER_A7 as usize] {
+                    interpreter::SYSCALL_SUCCESS => Instruction::SyscallType(SyscallInstruction::SyscallSuccess),
+                    interpreter::SYSCALL_EXIT | interpreter::SYSCALL_EXIT_GROUP => Instruction::SyscallType(SyscallInstruction::SyscallExit),
+                    interpreter::SYSCALL_READ => match self.registers.general_purpose[interpreter::REGISTER_A0 as usize] {
+                        interpreter::FD_HINT_READ => Instruction::SyscallType(SyscallInstruction::SyscallReadHint),
+                        interpreter::FD_PREIMAGE_READ => Instruction::SyscallType(SyscallInstruction::SyscallReadPreimage),
+                        _ => Instruction::SyscallType(SyscallInstruction::SyscallReadOther),
+                    },
+                    interpreter::SYSCALL_WRITE => match self.registers.general_purpose[interpreter::REGISTER_A0 as usize] {
+                        interpreter::FD_HINT_WRITE => Instruction::SyscallType(SyscallInstruction::SyscallWriteHint),
+                        interpreter::FD_PREIMAGE_WRITE => Instruction::SyscallType(SyscallInstruction::SyscallWritePreimage),
+                        _ => Instruction::SyscallType(SyscallInstruction::SyscallWriteOther),
+                    },
+                    interpreter::SYSCALL_BRK => Instruction::SyscallType(SyscallInstruction::SyscallBrk),
+                    interpreter::SYSCALL_MMAP => Instruction::SyscallType(SyscallInstruction::SyscallMmap),
+                    // The other syscalls are not executed, and report ENOSYS
+                    _ => Instruction::SyscallType(SyscallInstruction::SyscallOther),
+                },
                 _ => panic!("Unknown instruction with full inst {:b}, and opcode {:b}", instruction, instruction & 0b1111111),
             }
         };
@@ -865,6 +1123,123 @@ impl<Fp: Field> Env<Fp> {
         opcode
     }
 
+    /// Execute a single step in the RISCV32i program, following the
+    /// configuration of the runner: information is printed and the state is
+    /// saved at the requested steps, and the execution is stopped at the
+    /// requested step or when the program jumps to the halt address.
+    pub fn step_with_config(&mut self, config: &VmConfiguration, start: &Start) -> Instruction {
+        self.pp_info(&config.info_at, start);
+        self.snapshot_state_at(&config.snapshot_state_at, &config.snapshot_fmt);
+
+        let instruction = self.step();
+
+        config.halt_address.iter().for_each(|halt_address: &u32| {
+            if self.registers.current_instruction_pointer == *halt_address {
+                debug!("Program jumped to halt address: {:#X}", halt_address);
+                self.halt = true;
+            }
+        });
+
+        // Force stops at given iteration
+        if self.should_trigger_at(&config.stop_at) {
+            self.halt = true;
+            println!(
+                "Halted as requested at step={} instruction={:?}",
+                self.normalized_instruction_counter(),
+                instruction
+            );
+        }
+        instruction
+    }
+
+    pub fn should_trigger_at(&self, at: &StepFrequency) -> bool {
+        let m: u64 = self.normalized_instruction_counter();
+        match at {
+            StepFrequency::Never => false,
+            StepFrequency::Always => true,
+            StepFrequency::Exactly(n) => *n == m,
+            StepFrequency::Every(n) => m % *n == 0,
+            StepFrequency::Range(lo, hi_opt) => {
+                m >= *lo && (hi_opt.is_none() || m < hi_opt.unwrap())
+            }
+        }
+    }
+
+    /// Build a cannon state from the current state of the execution.
+    pub fn to_state(&self) -> State {
+        let mut preimage_key = [0u8; 32];
+        for i in 0..8 {
+            let bytes = u32::to_be_bytes(self.registers.preimage_key[i]);
+            for j in 0..4 {
+                preimage_key[4 * i + j] = bytes[j]
+            }
+        }
+        let memory = self
+            .memory
+            .clone()
+            .into_iter()
+            .map(|(idx, data)| Page { index: idx, data })
+            .collect();
+        State {
+            pc: self.registers.current_instruction_pointer,
+            next_pc: self.registers.next_instruction_pointer,
+            step: self.normalized_instruction_counter(),
+            registers: self.registers.general_purpose,
+            // Only used by MIPS
+            lo: 0,
+            // Only used by MIPS
+            hi: 0,
+            heap: self.registers.heap_pointer,
+            exit: self.syscall_env.exit_code,
+            last_hint: self.syscall_env.last_hint.clone(),
+            exited: self.halt,
+            preimage_offset: self.registers.preimage_offset,
+            preimage_key,
+            memory,
+            preimage: self.preimage.clone(),
+        }
+    }
+
+    fn snapshot_state_at(&mut self, at: &StepFrequency, snapshot_fmt: &str) {
+        if self.should_trigger_at(at) {
+            let filename =
+                snapshot_fmt.replace("%d", &self.normalized_instruction_counter().to_string());
+            let file = File::create(filename.clone()).expect("Impossible to open file");
+            let mut writer = BufWriter::new(file);
+            let _ = serde_json::to_writer(&mut writer, &self.to_state());
+            info!(
+                "Snapshot state in {}, step {}",
+                filename,
+                self.normalized_instruction_counter()
+            );
+        }
+    }
+
+    fn pp_info(&mut self, at: &StepFrequency, start: &Start) {
+        if self.should_trigger_at(at) {
+            let elapsed = start.time.elapsed();
+            // Compute the step number removing the MAX_ACC factor
+            let step = self.normalized_instruction_counter();
+            let pc = self.registers.current_instruction_pointer;
+
+            // The instructions are encoded in little-endian
+            let insn =
+                u32::from_le_bytes(array::from_fn(|i| self.get_memory_direct(pc + i as u32)));
+
+            // Approximate instruction per seconds
+            let how_many_steps = step as usize - start.step;
+
+            let ips = how_many_steps as f64 / elapsed.as_secs() as f64;
+
+            let pages = self.memory.len();
+
+            info!(
+                "processing step={} pc={:010x} insn={:010x} ips={:.2} pages={}",
+                step, pc, insn, ips, pages
+            );
+        }
+    }
+
     pub fn reset_scratch_state(&mut self) {
         self.scratch_state_idx = 0;
         self.scratch_state = fresh_scratch_state();
@@ -896,7 +1271,7 @@ impl<Fp: Field> Env<Fp> {
 
     pub fn get_memory_page_index(&mut self, page: u32) -> usize {
         for &i in self.last_memory_accesses.iter() {
-            if self.memory_write_index[i].0 == page {
+            if self.memory[i].0 == page {
                 return i;
             }
         }
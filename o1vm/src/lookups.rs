@@ -1,7 +1,10 @@
 //! Instantiation of the lookups for the VM project.
 
 use self::LookupTableIDs::*;
-use crate::{interpreters::keccak::pad_blocks, ramlookup::RAMLookup};
+use crate::{
+    interpreters::{keccak::pad_blocks, riscv32im::interpreter::expand_compressed_instruction},
+    ramlookup::RAMLookup,
+};
 use ark_ff::{Field, PrimeField};
 use kimchi::{
     circuits::polynomials::keccak::{
@@ -49,14 +52,18 @@ pub enum LookupTableIDs {
     /// Dual-column table of all values in the range [0, 2^16) and their sparse
     /// representation
     ResetLookup = 6,
+    /// Three-column table of all 16-bit values, their expansion to a 32-bit
+    /// instruction if they are a compressed RISC-V instruction (or 0), and
+    /// whether they are a compressed instruction
+    CompressedInstructionLookup = 7,
 
     // RAM Tables
-    MemoryLookup = 7,
-    RegisterLookup = 8,
+    MemoryLookup = 8,
+    RegisterLookup = 9,
     /// Syscalls communication channel
-    SyscallLookup = 9,
+    SyscallLookup = 10,
     /// Input/Output of Keccak steps
-    KeccakStepLookup = 10,
+    KeccakStepLookup = 11,
 }
 
 impl LookupTableID for LookupTableIDs {
@@ -73,10 +80,11 @@ impl LookupTableID for LookupTableIDs {
             4 => RangeCheck16Lookup,
             5 => SparseLookup,
             6 => ResetLookup,
-            7 => MemoryLookup,
-            8 => RegisterLookup,
-            9 => SyscallLookup,
-            10 => KeccakStepLookup,
+            7 => CompressedInstructionLookup,
+            8 => MemoryLookup,
+            9 => RegisterLookup,
+            10 => SyscallLookup,
+            11 => KeccakStepLookup,
             _ => panic!("Invalid table ID"),
         }
     }
@@ -87,7 +95,9 @@ impl LookupTableID for LookupTableIDs {
             RoundConstantsLookup => ROUNDS,
             AtMost4Lookup => 5,
             ByteLookup => 1 << 8,
-            RangeCheck16Lookup | SparseLookup | ResetLookup => 1 << 16,
+            RangeCheck16Lookup | SparseLookup | ResetLookup | CompressedInstructionLookup => {
+                1 << 16
+            }
             MemoryLookup | RegisterLookup | SyscallLookup | KeccakStepLookup => {
                 panic!("RAM Tables do not have a fixed length")
             }
@@ -96,8 +106,14 @@ impl LookupTableID for LookupTableIDs {
 
     fn is_fixed(&self) -> bool {
         match self {
-            PadLookup | RoundConstantsLookup | AtMost4Lookup | ByteLookup | RangeCheck16Lookup
-            | SparseLookup | ResetLookup => true,
+            PadLookup
+            | RoundConstantsLookup
+            | AtMost4Lookup
+            | ByteLookup
+            | RangeCheck16Lookup
+            | SparseLookup
+            | ResetLookup
+            | CompressedInstructionLookup => true,
             MemoryLookup | RegisterLookup | SyscallLookup | KeccakStepLookup => false,
         }
     }
@@ -119,7 +135,8 @@ impl LookupTableID for LookupTableIDs {
             | Self::AtMost4Lookup
             | Self::ByteLookup
             | Self::RangeCheck16Lookup
-            | Self::ResetLookup => Some(idx),
+            | Self::ResetLookup
+            | Self::CompressedInstructionLookup => Some(idx),
             Self::PadLookup => Some(idx - 1),
             Self::SparseLookup => {
                 // Big yikes. This is copied from below.
@@ -147,6 +164,7 @@ impl LookupTableID for LookupTableIDs {
             Self::RangeCheck16Lookup,
             Self::SparseLookup,
             Self::ResetLookup,
+            Self::CompressedInstructionLookup,
             Self::MemoryLookup,
             Self::RegisterLookup,
             Self::SyscallLookup,
@@ -174,6 +192,8 @@ pub(crate) trait FixedLookupTables<F> {
     fn table_sparse() -> LookupTable<F>;
     /// Returns the reset table
     fn table_reset() -> LookupTable<F>;
+    /// Returns the table of the expansions of compressed RISC-V instructions
+    fn table_compressed_instruction() -> LookupTable<F>;
 }
 
 impl<F: Field> FixedLookupTables<F> for LookupTable<F> {
@@ -188,8 +208,12 @@ impl<F: Field> FixedLookupTables<F> for LookupTable<F> {
             .fold(0u64, |acc, &x| acc * 256 + x as u64) as usize;
 
         match id {
-            RoundConstantsLookup | AtMost4Lookup | ByteLookup | RangeCheck16Lookup
-            | ResetLookup => {
+            RoundConstantsLookup
+            | AtMost4Lookup
+            | ByteLookup
+            | RangeCheck16Lookup
+            | ResetLookup
+            | CompressedInstructionLookup => {
                 if idx < id.length() && table.entries[idx] == value {
                     Some(idx)
                 } else {
@@ -311,4 +335,19 @@ impl<F: Field> FixedLookupTables<F> for LookupTable<F> {
                 .collect(),
         }
     }
+
+    fn table_compressed_instruction() -> Self {
+        Self {
+            table_id: CompressedInstructionLookup,
+            entries: (0..CompressedInstructionLookup.length() as u32)
+                .map(|i| {
+                    vec![
+                        F::from(i),
+                        F::from(expand_compressed_instruction(i).unwrap_or(0)),
+                        F::from(i & 0b11 != 0b11),
+                    ]
+                })
+                .collect(),
+        }
+    }
 }
@@ -0,0 +1,184 @@
+//! Witness generation for the MIPS interpreter, by chunks of execution.
+//!
+//! The rows of the witness are built from the state of the witness
+//! environment after each step, see [push_mips_row], and [push_riscv_row] for
+//! the RISC-V interpreter, whose execution is not chunked yet. Instead of building them
+//! while executing, the runner can take a [Chunk] at each segment boundary,
+//! i.e. a snapshot of the environment and the preimages served during the
+//! segment, and build the witness of the segment later, in another thread,
+//! with [Chunk::proof_inputs]. As the interpreter is deterministic, the proof
+//! inputs are the same as the ones built while executing.
+//!
+//! The execution taking the chunks does not need the witness, and should be
+//! run with [Env::generate_witness] unset, so that the witness of each step
+//! is built once, when its chunk is replayed. The snapshots share the memory
+//! pages of the environment, which are only copied when written to.
+
+use ark_ff::{PrimeField, UniformRand, Zero};
+use kimchi::curve::KimchiCurve;
+use rand::RngCore;
+
+use super::proof::{ProofInputs, PublicInputs};
+use crate::{
+    cannon::{Start, StepFrequency, VmConfiguration},
+    interpreters::{
+        mips::{
+            column::N_MIPS_REL_COLS,
+            witness::{Env, EnvSnapshot, MAX_ACC},
+            Instruction,
+        },
+        riscv32im::{column::N_RISCV32IM_REL_COLS, witness as riscv_witness},
+    },
+    preimage_oracle::{InMemoryPreImageOracle, PreImageOracleT},
+};
+
+/// Append the row of the step just executed by the environment
+pub fn push_mips_row<G: KimchiCurve, O: PreImageOracleT, R: RngCore>(
+    env: &Env<G::ScalarField, O>,
+    proof_inputs: &mut ProofInputs<G>,
+    rng: &mut R,
+) where
+    G::ScalarField: PrimeField,
+{
+    let evaluations = &mut proof_inputs.evaluations;
+    for (scratch, scratch_chunk) in env.scratch_state.iter().zip(evaluations.scratch.iter_mut()) {
+        scratch_chunk.push(*scratch);
+    }
+    for (scratch, scratch_chunk) in env
+        .scratch_state_inverse
+        .iter()
+        .zip(evaluations.scratch_inverse.iter_mut())
+    {
+        scratch_chunk.push(*scratch);
+    }
+    // The lookups are not enforced by the prover yet, hence the lookup state
+    // is not committed to, see [super::proof::ColumnLayout::MIPS].
+    evaluations
+        .instruction_counter
+        .push(G::ScalarField::from(env.instruction_counter));
+    // FIXME: Might be another value
+    evaluations.error.push(G::ScalarField::rand(rng));
+
+    evaluations.selector.push(G::ScalarField::from(
+        (env.selector - N_MIPS_REL_COLS) as u64,
+    ));
+}
+
+/// Append the row of the step just executed by the RISC-V environment. The
+/// remaining rows of the witness are filled with
+/// [ProofInputs::pad_with_last_row].
+pub fn push_riscv_row<G: KimchiCurve, O: PreImageOracleT, R: RngCore>(
+    env: &riscv_witness::Env<G::ScalarField, O>,
+    proof_inputs: &mut ProofInputs<G>,
+    rng: &mut R,
+) where
+    G::ScalarField: PrimeField,
+{
+    let evaluations = &mut proof_inputs.evaluations;
+    for (scratch, scratch_chunk) in env.scratch_state.iter().zip(evaluations.scratch.iter_mut()) {
+        scratch_chunk.push(*scratch);
+    }
+    for (scratch, scratch_chunk) in env
+        .scratch_state_inverse
+        .iter()
+        .zip(evaluations.scratch_inverse.iter_mut())
+    {
+        scratch_chunk.push(*scratch);
+    }
+    evaluations
+        .instruction_counter
+        .push(G::ScalarField::from(env.instruction_counter));
+    // FIXME: Might be another value
+    evaluations.error.push(G::ScalarField::rand(rng));
+
+    evaluations.selector.push(G::ScalarField::from(
+        (env.selector - N_RISCV32IM_REL_COLS) as u64,
+    ));
+}
+
+/// Fill the remaining rows of the witness with `NoOp` instructions
+pub fn pad_mips<G: KimchiCurve, O: PreImageOracleT, R: RngCore>(
+    env: &Env<G::ScalarField, O>,
+    proof_inputs: &mut ProofInputs<G>,
+    rng: &mut R,
+) where
+    G::ScalarField: PrimeField,
+{
+    let zero = G::ScalarField::zero();
+    // FIXME: Find a better way to get instruction selectors that doesn't
+    // reveal internals.
+    let noop_selector: G::ScalarField = {
+        let noop: usize = Instruction::NoOp.into();
+        G::ScalarField::from((noop - N_MIPS_REL_COLS) as u64)
+    };
+    let evaluations = &mut proof_inputs.evaluations;
+    evaluations
+        .scratch
+        .iter_mut()
+        .for_each(|x| x.resize(x.capacity(), zero));
+    evaluations
+        .scratch_inverse
+        .iter_mut()
+        .for_each(|x| x.resize(x.capacity(), zero));
+    evaluations.instruction_counter.resize(
+        evaluations.instruction_counter.capacity(),
+        G::ScalarField::from(env.instruction_counter),
+    );
+    evaluations
+        .error
+        .resize_with(evaluations.error.capacity(), || G::ScalarField::rand(rng));
+    evaluations
+        .selector
+        .resize(evaluations.selector.capacity(), noop_selector);
+}
+
+/// The configuration used to replay a chunk: the same as the one of the
+/// execution, without the side effects (logs, snapshots, stops) already
+/// performed by the execution.
+pub fn replay_configuration(configuration: &VmConfiguration) -> VmConfiguration {
+    VmConfiguration {
+        info_at: StepFrequency::Never,
+        snapshot_state_at: StepFrequency::Never,
+        stop_at: StepFrequency::Never,
+        proof_at: StepFrequency::Never,
+        ..configuration.clone()
+    }
+}
+
+/// A chunk of execution, from which the proof inputs of a segment can be
+/// generated independently of the rest of the execution.
+pub struct Chunk<F> {
+    /// The environment at the beginning of the chunk
+    pub snapshot: EnvSnapshot<F>,
+    /// The preimages requested during the chunk
+    pub preimages: InMemoryPreImageOracle,
+    /// The number of steps executed in the chunk
+    pub steps: usize,
+}
+
+impl<F: PrimeField> Chunk<F> {
+    /// Replay the chunk and build its proof inputs, bound to the commitments
+    /// to the states at the beginning and at the end of the chunk. The
+    /// witness is padded up to `domain_size` rows if the chunk is shorter.
+    /// The configuration should be obtained with [replay_configuration].
+    pub fn proof_inputs<G: KimchiCurve<ScalarField = F>, R: RngCore>(
+        self,
+        configuration: &VmConfiguration,
+        domain_size: usize,
+        rng: &mut R,
+    ) -> ProofInputs<G> {
+        let mut env = Env::restore(self.snapshot, self.preimages);
+        let start = Start::create(env.normalized_instruction_counter() as usize);
+        let initial_state = env.to_state();
+        let mut proof_inputs = ProofInputs::new(domain_size);
+        for _ in 0..self.steps {
+            env.step(configuration, &None, &start);
+            push_mips_row(&env, &mut proof_inputs, rng);
+        }
+        if self.steps < domain_size {
+            pad_mips(&env, &mut proof_inputs, rng);
+        }
+        proof_inputs.public_inputs = PublicInputs::new(&initial_state, &env.to_state(), MAX_ACC);
+        proof_inputs
+    }
+}
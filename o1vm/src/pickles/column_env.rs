@@ -2,10 +2,7 @@ use ark_ff::FftField;
 use ark_poly::{Evaluations, Radix2EvaluationDomain};
 use kimchi_msm::columns::Column;
 
-use crate::{
-    interpreters::mips::column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE},
-    pickles::proof::WitnessColumns,
-};
+use crate::pickles::proof::WitnessColumns;
 use kimchi::circuits::{
     berkeley_columns::{BerkeleyChallengeTerm, BerkeleyChallenges},
     domains::{Domain, EvaluationDomains},
@@ -30,7 +27,7 @@ pub enum RelationColumnType {
 pub struct ColumnEnvironment<'a, F: FftField> {
     /// The witness column polynomials. Includes relation columns and dynamic
     /// selector columns.
-    pub witness: &'a WitnessColumns<Evals<F>, [Evals<F>; N_MIPS_SEL_COLS]>,
+    pub witness: &'a WitnessColumns<Evals<F>, Vec<Evals<F>>>,
     /// The value `prod_{j != 1} (1 - ω^j)`, used for efficiently
     /// computing the evaluations of the unnormalized Lagrange basis
     /// polynomials.
@@ -44,53 +41,45 @@ pub struct ColumnEnvironment<'a, F: FftField> {
     pub domain: EvaluationDomains<F>,
 }
 
-pub fn get_all_columns(num_lookup_columns: usize) -> Vec<Column<RelationColumnType>> {
-    let mut cols = Vec::<Column<RelationColumnType>>::with_capacity(
-        SCRATCH_SIZE + SCRATCH_SIZE_INVERSE + num_lookup_columns + 2 + N_MIPS_SEL_COLS,
-    );
-    for i in 0..SCRATCH_SIZE {
-        cols.push(Column::Relation(RelationColumnType::Scratch(i)));
-    }
-    for i in 0..SCRATCH_SIZE_INVERSE {
-        cols.push(Column::Relation(RelationColumnType::ScratchInverse(i)));
-    }
-    for i in 0..num_lookup_columns {
-        cols.push(Column::Relation(RelationColumnType::LookupState(i)));
-    }
-    cols.push(Column::Relation(RelationColumnType::InstructionCounter));
-    cols.push(Column::Relation(RelationColumnType::Error));
-    for i in 0..N_MIPS_SEL_COLS {
-        cols.push(Column::DynamicSelector(i));
+impl<G> WitnessColumns<G, Vec<G>> {
+    /// Return all the columns, in the order they are committed to and opened.
+    pub fn get_all_columns(&self) -> Vec<Column<RelationColumnType>> {
+        let mut cols = Vec::<Column<RelationColumnType>>::with_capacity(
+            self.scratch.len()
+                + self.scratch_inverse.len()
+                + self.lookup_state.len()
+                + 2
+                + self.selector.len(),
+        );
+        for i in 0..self.scratch.len() {
+            cols.push(Column::Relation(RelationColumnType::Scratch(i)));
+        }
+        for i in 0..self.scratch_inverse.len() {
+            cols.push(Column::Relation(RelationColumnType::ScratchInverse(i)));
+        }
+        for i in 0..self.lookup_state.len() {
+            cols.push(Column::Relation(RelationColumnType::LookupState(i)));
+        }
+        cols.push(Column::Relation(RelationColumnType::InstructionCounter));
+        cols.push(Column::Relation(RelationColumnType::Error));
+        for i in 0..self.selector.len() {
+            cols.push(Column::DynamicSelector(i));
+        }
+        cols
     }
-    cols
-}
 
-impl<G> WitnessColumns<G, [G; N_MIPS_SEL_COLS]> {
+    /// Return the given column, or `None` if it is not part of the witness.
     pub fn get_column(&self, col: &Column<RelationColumnType>) -> Option<&G> {
         match *col {
             Column::Relation(i) => match i {
-                RelationColumnType::Scratch(i) => Some(&self.scratch[i]),
-                RelationColumnType::ScratchInverse(i) => Some(&self.scratch_inverse[i]),
-                RelationColumnType::LookupState(i) => Some(&self.lookup_state[i]),
+                RelationColumnType::Scratch(i) => self.scratch.get(i),
+                RelationColumnType::ScratchInverse(i) => self.scratch_inverse.get(i),
+                RelationColumnType::LookupState(i) => self.lookup_state.get(i),
                 RelationColumnType::InstructionCounter => Some(&self.instruction_counter),
                 RelationColumnType::Error => Some(&self.error),
             },
-            Column::DynamicSelector(i) => {
-                assert!(
-                    i < N_MIPS_SEL_COLS,
-                    "We do not have that many dynamic selector columns. We have {} columns and index {} was given",
-                    N_MIPS_SEL_COLS,
-                    i
-                );
-                let res = &self.selector[i];
-                Some(res)
-            }
-            _ => {
-                panic!(
-                    "We should not have any other type of columns. The column {:?} was given",
-                    col
-                );
-            }
+            Column::DynamicSelector(i) => self.selector.get(i),
+            _ => None,
         }
     }
 }
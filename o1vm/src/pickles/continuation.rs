@@ -0,0 +1,340 @@
+//! Continuations allow to prove a long execution incrementally, as a sequence
+//! of segments. Each segment is proven independently by [super::prover::prove]
+//! and its proof is labelled with the commitments to the VM state before and
+//! after the segment, see [super::proof::PublicInputs]. Only the instruction
+//! pointer and the instruction counter are bound to the witness: the
+//! commitments are not checked against the registers and the memory.
+//!
+//! A sequence of segment proofs attests the whole execution as long as each
+//! segment starts from the final state of the previous one, and its
+//! instruction counter goes on from the last instruction of the previous
+//! segment, see [super::proof::PublicInputs::new]. As the state
+//! commitments are computed from the same [crate::cannon::State] that is
+//! written by `--snapshot-state-at`, an execution resumed from a snapshot
+//! produces a continuation that can be appended to the one that stopped at
+//! the snapshot, see [Continuation::append].
+//!
+//! As a long execution has many segments, the segment proofs can be streamed
+//! to a file as soon as they are generated with a [SegmentWriter], and read
+//! back one at a time with a [SegmentReader] to be verified, see [verify].
+
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use kimchi::{circuits::domains::EvaluationDomains, curve::KimchiCurve, plonk_sponge::FrSponge};
+use mina_poseidon::FqSponge;
+use poly_commitment::{ipa::OpeningProof, OpenProof};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Borrow,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    marker::PhantomData,
+    path::Path,
+};
+use thiserror::Error;
+
+use super::{
+    proof::{ColumnLayout, Proof, PublicInputs},
+    verifier,
+};
+use crate::E;
+
+/// Errors that can arise when building or verifying a continuation
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ContinuationError {
+    #[error("the continuation does not contain any segment")]
+    Empty,
+    #[error("the first segment does not start from the expected initial state")]
+    UnexpectedInitialState,
+    #[error("the last segment does not end in the expected final state")]
+    UnexpectedFinalState,
+    #[error("the segment {0} does not start from the final state of the previous segment")]
+    BrokenLink(usize),
+    #[error("the segment {0} does not start at the instruction following the previous segment")]
+    NonConsecutive(usize),
+    #[error("the proof of the segment {0} does not verify")]
+    InvalidSegment(usize),
+    #[error("the segment {0} could not be written: {1}")]
+    Write(usize, String),
+}
+
+/// The proof of a segment, with the statement it is claimed to prove
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Segment<G: KimchiCurve> {
+    pub public_inputs: PublicInputs,
+    pub proof: Proof<G>,
+}
+
+/// A sequence of segment proofs, where each segment starts from the final
+/// state of the previous one, at the instruction following its last one.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Continuation<G: KimchiCurve> {
+    /// The number of memory accesses allowed per instruction by the
+    /// interpreter, which scales the instruction counter
+    max_accesses: u64,
+    segments: Vec<Segment<G>>,
+}
+
+impl<G: KimchiCurve> Continuation<G> {
+    pub fn new(max_accesses: u64) -> Self {
+        Continuation {
+            max_accesses,
+            segments: vec![],
+        }
+    }
+
+    pub fn segments(&self) -> &[Segment<G>] {
+        &self.segments
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The commitment to the state the first segment starts from
+    pub fn initial_state(&self) -> Option<[u8; 32]> {
+        self.segments
+            .first()
+            .map(|segment| segment.public_inputs.initial_state)
+    }
+
+    /// The statement of the last segment, i.e. the state reached at the end
+    /// of the continuation and the preimage key held by the VM.
+    pub fn final_statement(&self) -> Option<PublicInputs> {
+        self.segments.last().map(|segment| segment.public_inputs)
+    }
+
+    /// Append the proof of a segment claimed to prove `public_inputs` to the
+    /// continuation. The segment must start from the final state of the last
+    /// segment, if any, see [check_link]. The proof itself is not checked,
+    /// see [verify].
+    pub fn push(
+        &mut self,
+        public_inputs: PublicInputs,
+        proof: Proof<G>,
+    ) -> Result<(), ContinuationError> {
+        check_link(
+            self.segments.last().map(|last| &last.public_inputs),
+            &public_inputs,
+            self.max_accesses,
+            self.segments.len(),
+        )?;
+        self.segments.push(Segment {
+            public_inputs,
+            proof,
+        });
+        Ok(())
+    }
+
+    /// Append all the segments of `other`, for instance the continuation of
+    /// an execution resumed from a snapshot taken at the end of `self`.
+    pub fn append(&mut self, other: Continuation<G>) -> Result<(), ContinuationError> {
+        if let Some(first) = other.segments.first() {
+            check_link(
+                self.final_statement().as_ref(),
+                &first.public_inputs,
+                self.max_accesses,
+                self.segments.len(),
+            )?;
+        }
+        self.segments.extend(other.segments);
+        Ok(())
+    }
+}
+
+/// Check that the segment with index `index`, claimed to prove
+/// `public_inputs`, starts from the final state of the segment before it, if
+/// any. The instruction counter of its first instruction must also follow the
+/// one of the last instruction of the previous segment, which is incremented
+/// by `max_accesses` per instruction, so that no instruction is skipped or
+/// proven twice.
+fn check_link(
+    previous: Option<&PublicInputs>,
+    public_inputs: &PublicInputs,
+    max_accesses: u64,
+    index: usize,
+) -> Result<(), ContinuationError> {
+    match previous {
+        Some(previous) if previous.final_state != public_inputs.initial_state => {
+            Err(ContinuationError::BrokenLink(index))
+        }
+        Some(previous)
+            if previous.last_instruction_counter.checked_add(max_accesses)
+                != Some(public_inputs.first_instruction_counter) =>
+        {
+            Err(ContinuationError::NonConsecutive(index))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Write the segments of a continuation as a stream of MessagePack values, as
+/// soon as they are proven. Only the statement of the last segment is kept in
+/// memory, to check the continuity of the next one.
+pub struct SegmentWriter<G: KimchiCurve, W: Write> {
+    writer: W,
+    max_accesses: u64,
+    last: Option<PublicInputs>,
+    len: usize,
+    _curve: PhantomData<G>,
+}
+
+impl<G: KimchiCurve> SegmentWriter<G, BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P, max_accesses: u64) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file), max_accesses))
+    }
+}
+
+impl<G: KimchiCurve, W: Write> SegmentWriter<G, W> {
+    pub fn new(writer: W, max_accesses: u64) -> Self {
+        SegmentWriter {
+            writer,
+            max_accesses,
+            last: None,
+            len: 0,
+            _curve: PhantomData,
+        }
+    }
+
+    /// The number of segments written so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Write the proof of a segment claimed to prove `public_inputs`. As for
+    /// [Continuation::push], the segment must start from the final state of
+    /// the last segment, at the instruction following its last one, and the
+    /// proof itself is not checked.
+    pub fn push(
+        &mut self,
+        public_inputs: PublicInputs,
+        proof: Proof<G>,
+    ) -> Result<(), ContinuationError> {
+        check_link(
+            self.last.as_ref(),
+            &public_inputs,
+            self.max_accesses,
+            self.len,
+        )?;
+        let segment = Segment {
+            public_inputs,
+            proof,
+        };
+        rmp_serde::encode::write(&mut self.writer, &segment)
+            .map_err(|e| ContinuationError::Write(self.len, e.to_string()))?;
+        self.last = Some(public_inputs);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Flush the underlying writer and return it
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Iterate over the segments written by a [SegmentWriter], reading them one at
+/// a time.
+pub struct SegmentReader<G: KimchiCurve, R: BufRead> {
+    reader: R,
+    _curve: PhantomData<G>,
+}
+
+impl<G: KimchiCurve> SegmentReader<G, BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(Self::new(BufReader::new(file)))
+    }
+}
+
+impl<G: KimchiCurve, R: BufRead> SegmentReader<G, R> {
+    pub fn new(reader: R) -> Self {
+        SegmentReader {
+            reader,
+            _curve: PhantomData,
+        }
+    }
+}
+
+impl<G: KimchiCurve, R: BufRead> Iterator for SegmentReader<G, R> {
+    type Item = io::Result<Segment<G>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.fill_buf() {
+            Ok([]) => None,
+            Ok(_) => Some(
+                rmp_serde::decode::from_read(self.reader.by_ref())
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            ),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Verify a sequence of segments going from the state whose commitment is
+/// `initial_state` to the state whose commitment is `final_state`. Each
+/// segment proof is verified against its statement, and the continuity of the
+/// state and of the instruction counter between consecutive segments is
+/// checked, see [check_link]. The segments are consumed one at
+/// a time, so that they can be read from a [SegmentReader] instead of being
+/// all held in memory.
+pub fn verify<
+    G: KimchiCurve,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    EFrSponge: FrSponge<G::ScalarField>,
+    S: Borrow<Segment<G>>,
+>(
+    domain: EvaluationDomains<G::ScalarField>,
+    srs: &<OpeningProof<G> as OpenProof<G>>::SRS,
+    constraints: &[E<G::ScalarField>],
+    layout: &ColumnLayout,
+    segments: impl IntoIterator<Item = S>,
+    initial_state: &[u8; 32],
+    final_state: &[u8; 32],
+) -> Result<(), ContinuationError>
+where
+    <G as AffineRepr>::BaseField: PrimeField,
+{
+    let mut last: Option<PublicInputs> = None;
+    for (i, segment) in segments.into_iter().enumerate() {
+        let Segment {
+            public_inputs,
+            proof,
+        } = segment.borrow();
+        if last.is_none() && public_inputs.initial_state != *initial_state {
+            return Err(ContinuationError::UnexpectedInitialState);
+        }
+        // The link is checked before the proof as it is much cheaper
+        check_link(last.as_ref(), public_inputs, layout.max_accesses, i)?;
+        if !verifier::verify::<G, EFqSponge, EFrSponge>(
+            domain,
+            srs,
+            constraints,
+            layout,
+            public_inputs,
+            proof,
+        ) {
+            return Err(ContinuationError::InvalidSegment(i));
+        }
+        last = Some(*public_inputs);
+    }
+    match last {
+        None => Err(ContinuationError::Empty),
+        Some(last) if last.final_state != *final_state => {
+            Err(ContinuationError::UnexpectedFinalState)
+        }
+        Some(_) => Ok(()),
+    }
+}
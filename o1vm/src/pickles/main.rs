@@ -1,4 +1,3 @@
-use ark_ff::{UniformRand, Zero};
 use clap::Parser;
 use kimchi::{circuits::domains::EvaluationDomains, precomputed_srs::TestSRS};
 use log::debug;
@@ -8,27 +7,416 @@ use mina_poseidon::{
     sponge::{DefaultFqSponge, DefaultFrSponge},
 };
 use o1vm::{
-    cannon::{self, Start, State},
+    cannon::{self, HostProgram, Meta, Start, State, StepFrequency},
     cli, elf_loader,
-    interpreters::mips::{
-        column::N_MIPS_REL_COLS,
-        constraints as mips_constraints,
-        witness::{self as mips_witness},
-        Instruction,
+    interpreters::{
+        mips::{
+            constraints as mips_constraints,
+            trace::{first_divergence, Divergence, TraceReader, TraceStep, Tracer},
+            witness::{self as mips_witness},
+            Instruction,
+        },
+        riscv32im::{
+            self, constraints as riscv_constraints,
+            witness::{self as riscv_witness},
+        },
+    },
+    pickles::{
+        chunk::{self, pad_mips, push_mips_row, push_riscv_row, Chunk},
+        continuation::{self, SegmentReader, SegmentWriter},
+        proof::{ColumnLayout, Proof, ProofInputs, PublicInputs},
+        prover,
+    },
+    preimage_oracle::{
+        BufferingPreImageOracle, DirectoryPreImageOracle, NullPreImageOracle, PreImageOracle,
+        PreImageOracleT, RecordingPreImageOracle,
     },
-    pickles::{proof::ProofInputs, prover, verifier},
-    preimage_oracle::{NullPreImageOracle, PreImageOracle, PreImageOracleT},
     test_preimage_read, E,
 };
 use poly_commitment::{ipa::SRS, SRS as _};
 use rand::rngs::ThreadRng;
-use std::{fs::File, io::BufReader, path::Path, process::ExitCode, time::Instant};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    process::ExitCode,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Instant,
+};
+
+/// The segment proofs, written to a file as soon as they are generated
+type Segments = SegmentWriter<Vesta, BufWriter<File>>;
 
 pub fn cannon_main(args: cli::cannon::RunArgs) {
+    let configuration: cannon::VmConfiguration = args.vm_cfg.into();
+
+    let (state, meta) = load_mips_state(&configuration);
+    let meta = &meta;
+
+    // Initialize some data used for statistical computations
+    let start = Start::create(state.step as usize);
+
+    let (srs, domain_fp) = load_srs(&args.srs_cache);
+
+    // Initialize the environments
+    let preimage_oracle = create_preimage_oracle(
+        configuration.host.clone(),
+        &args.preimage_db_dir,
+        args.record_preimages,
+    );
+    let mut mips_wit_env = mips_witness::Env::<Fp, _>::create(
+        cannon::PAGE_SIZE as usize,
+        state,
+        BufferingPreImageOracle::create(preimage_oracle),
+    );
+    mips_wit_env.tracer = args
+        .trace
+        .map(|path| Tracer::create(path).expect("Error creating the trace file"));
+
+    let constraints = mips_constraints::get_all_constraints::<Fp>();
+
+    let initial_state = mips_wit_env.to_state().commitment();
+    let mut segments = Segments::create(&args.proofs_file, mips_witness::MAX_ACC)
+        .expect("Error creating the proofs file");
+    if args.witness_workers == 0 {
+        prove_sequentially(
+            &mut mips_wit_env,
+            &configuration,
+            meta,
+            &start,
+            domain_fp,
+            &srs,
+            &constraints,
+            &mut segments,
+        )
+    } else {
+        prove_pipelined(
+            &mut mips_wit_env,
+            args.witness_workers,
+            &configuration,
+            meta,
+            &start,
+            domain_fp,
+            &srs,
+            &constraints,
+            &mut segments,
+        )
+    };
+    segments.finish().expect("Error writing the proofs file");
+
+    if let Some(tracer) = mips_wit_env.tracer.as_mut() {
+        tracer.flush().expect("Error writing the trace file");
+    }
+
+    verify_continuation(
+        domain_fp,
+        &srs,
+        &constraints,
+        &ColumnLayout::MIPS,
+        &args.proofs_file,
+        &initial_state,
+        &mips_wit_env.to_state().commitment(),
+    );
+}
+
+/// An interpreter whose execution can be proven segment by segment by
+/// [prove_sequentially]
+trait SegmentedVm {
+    /// The maximum number of accesses of a step, see [PublicInputs::new]
+    const MAX_ACC: u64;
+    /// The columns of the witness of the interpreter
+    const LAYOUT: ColumnLayout;
+
+    fn halted(&self) -> bool;
+
+    fn should_trigger_at(&self, at: &StepFrequency) -> bool;
+
+    fn to_state(&self) -> State;
+
+    /// Execute a single step and append its row to the witness
+    fn step_row(
+        &mut self,
+        configuration: &cannon::VmConfiguration,
+        meta: &Option<Meta>,
+        start: &Start,
+        proof_inputs: &mut ProofInputs<Vesta>,
+        rng: &mut ThreadRng,
+    );
+
+    /// Fill the remaining rows of the witness, up to `domain_size` rows
+    fn pad(&self, proof_inputs: &mut ProofInputs<Vesta>, domain_size: usize, rng: &mut ThreadRng);
+
+    /// Release what is only needed to replay the segment that just ended
+    fn end_segment(&mut self) {}
+}
+
+impl<O: PreImageOracleT> SegmentedVm for mips_witness::Env<Fp, BufferingPreImageOracle<O>> {
+    const MAX_ACC: u64 = mips_witness::MAX_ACC;
+    const LAYOUT: ColumnLayout = ColumnLayout::MIPS;
+
+    fn halted(&self) -> bool {
+        self.halt
+    }
+
+    fn should_trigger_at(&self, at: &StepFrequency) -> bool {
+        self.should_trigger_at(at)
+    }
+
+    fn to_state(&self) -> State {
+        self.to_state()
+    }
+
+    fn step_row(
+        &mut self,
+        configuration: &cannon::VmConfiguration,
+        meta: &Option<Meta>,
+        start: &Start,
+        proof_inputs: &mut ProofInputs<Vesta>,
+        rng: &mut ThreadRng,
+    ) {
+        let _instr: Instruction = self.step(configuration, meta, start);
+        push_mips_row(self, proof_inputs, rng);
+    }
+
+    fn pad(&self, proof_inputs: &mut ProofInputs<Vesta>, _domain_size: usize, rng: &mut ThreadRng) {
+        pad_mips(self, proof_inputs, rng);
+    }
+
+    fn end_segment(&mut self) {
+        // The preimages are only needed to replay the segment
+        self.preimage_oracle.take();
+    }
+}
+
+impl<O: PreImageOracleT> SegmentedVm for riscv_witness::Env<Fp, O> {
+    const MAX_ACC: u64 = riscv_witness::MAX_ACC;
+    const LAYOUT: ColumnLayout = ColumnLayout::RISCV32IM;
+
+    fn halted(&self) -> bool {
+        self.halt
+    }
+
+    fn should_trigger_at(&self, at: &StepFrequency) -> bool {
+        self.should_trigger_at(at)
+    }
+
+    fn to_state(&self) -> State {
+        self.to_state()
+    }
+
+    fn step_row(
+        &mut self,
+        configuration: &cannon::VmConfiguration,
+        _meta: &Option<Meta>,
+        start: &Start,
+        proof_inputs: &mut ProofInputs<Vesta>,
+        rng: &mut ThreadRng,
+    ) {
+        let _instr = self.step_with_config(configuration, start);
+        push_riscv_row(self, proof_inputs, rng);
+    }
+
+    fn pad(&self, proof_inputs: &mut ProofInputs<Vesta>, domain_size: usize, rng: &mut ThreadRng) {
+        proof_inputs.pad_with_last_row(domain_size, rng);
+    }
+}
+
+/// Execute the program, building the witness of each segment while executing
+/// it, and prove the segments one after the other.
+#[allow(clippy::too_many_arguments)]
+fn prove_sequentially<V: SegmentedVm>(
+    wit_env: &mut V,
+    configuration: &cannon::VmConfiguration,
+    meta: &Option<Meta>,
+    start: &Start,
+    domain_fp: EvaluationDomains<Fp>,
+    srs: &SRS<Vesta>,
+    constraints: &[E<Fp>],
+    segments: &mut Segments,
+) {
     let mut rng = rand::thread_rng();
+    let domain_size = domain_fp.d1.size as usize;
+    let new_proof_inputs = || ProofInputs::<Vesta>::new_with_layout(domain_size, V::LAYOUT);
 
-    let configuration: cannon::VmConfiguration = args.vm_cfg.into();
+    let mut curr_proof_inputs = new_proof_inputs();
+    let mut segment_start = wit_env.to_state();
+    while !wit_env.halted() {
+        wit_env.step_row(configuration, meta, start, &mut curr_proof_inputs, &mut rng);
+
+        let segment_end = if curr_proof_inputs.evaluations.instruction_counter.len() == domain_size
+        {
+            true
+        } else if wit_env.should_trigger_at(&configuration.proof_at) {
+            debug!(
+                "Proving the {} instructions executed since the last proof",
+                curr_proof_inputs.evaluations.instruction_counter.len()
+            );
+            wit_env.pad(&mut curr_proof_inputs, domain_size, &mut rng);
+            true
+        } else {
+            false
+        };
+        if segment_end {
+            wit_env.end_segment();
+            set_public_inputs(
+                &mut curr_proof_inputs,
+                &mut segment_start,
+                wit_env.to_state(),
+                V::MAX_ACC,
+            );
+            prove_segment(
+                domain_fp,
+                srs,
+                constraints,
+                curr_proof_inputs,
+                segments,
+                &mut rng,
+            );
+
+            curr_proof_inputs = new_proof_inputs();
+        }
+    }
+
+    // The boundary rows of an empty segment would not match its statement.
+    // As the execution is already proven up to the final state, an empty last
+    // segment is not proven.
+    if !curr_proof_inputs.evaluations.instruction_counter.is_empty() {
+        debug!("Padding witness for proof generation");
+        wit_env.pad(&mut curr_proof_inputs, domain_size, &mut rng);
+        set_public_inputs(
+            &mut curr_proof_inputs,
+            &mut segment_start,
+            wit_env.to_state(),
+            V::MAX_ACC,
+        );
+        prove_segment(
+            domain_fp,
+            srs,
+            constraints,
+            curr_proof_inputs,
+            segments,
+            &mut rng,
+        );
+    }
+}
+
+/// Execute the program on the current thread and build the witness and the
+/// proof of the segments in `workers` other threads. The current thread only
+/// executes the steps, without filling the witness: at each segment boundary,
+/// the environment is snapshotted and the chunk of execution is queued, for a
+/// worker to build its witness while the execution goes on. The
+/// segments are the same as the ones of [prove_sequentially], and they are
+/// written in order by another thread as soon as they are proven.
+#[allow(clippy::too_many_arguments)]
+fn prove_pipelined<O: PreImageOracleT>(
+    mips_wit_env: &mut mips_witness::Env<Fp, BufferingPreImageOracle<O>>,
+    workers: usize,
+    configuration: &cannon::VmConfiguration,
+    meta: &Option<Meta>,
+    start: &Start,
+    domain_fp: EvaluationDomains<Fp>,
+    srs: &SRS<Vesta>,
+    constraints: &[E<Fp>],
+    segments: &mut Segments,
+) {
+    let domain_size = domain_fp.d1.size as usize;
+    let replay_configuration = chunk::replay_configuration(configuration);
+
+    // The queue is bounded to bound the number of snapshots held in memory
+    // when the workers are slower than the execution.
+    let (chunk_sender, chunk_receiver) = mpsc::sync_channel::<(usize, Chunk<Fp>)>(workers);
+    let chunk_receiver = Arc::new(Mutex::new(chunk_receiver));
+    let (proof_sender, proof_receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let chunk_receiver = Arc::clone(&chunk_receiver);
+            let proof_sender = proof_sender.clone();
+            let replay_configuration = &replay_configuration;
+            scope.spawn(move || {
+                let mut rng = rand::thread_rng();
+                loop {
+                    // The lock is released before the chunk is processed
+                    let next_chunk = chunk_receiver.lock().unwrap().recv();
+                    let Ok((segment, chunk)) = next_chunk else {
+                        break;
+                    };
+                    let proof_inputs =
+                        chunk.proof_inputs(replay_configuration, domain_size, &mut rng);
+                    let public_inputs = proof_inputs.public_inputs;
+                    let proof =
+                        prove_inputs(domain_fp, srs, constraints, proof_inputs, segment, &mut rng);
+                    if proof_sender.send((segment, public_inputs, proof)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // The queue is closed once all the workers are gone, so that the
+        // execution stops if they panic.
+        drop(chunk_receiver);
+        drop(proof_sender);
+
+        // The proofs are received out of order. Only the ones received before
+        // the next segment to be written are kept in memory.
+        scope.spawn(move || {
+            let mut pending = BTreeMap::new();
+            for (segment, public_inputs, proof) in proof_receiver {
+                pending.insert(segment, (public_inputs, proof));
+                while let Some((public_inputs, proof)) = pending.remove(&segments.len()) {
+                    segments
+                        .push(public_inputs, proof)
+                        .expect("Error writing the segment proof");
+                }
+            }
+        });
 
+        let mut segment = 0;
+        let mut send_chunk = |snapshot, preimages, steps| {
+            chunk_sender
+                .send((
+                    segment,
+                    Chunk {
+                        snapshot,
+                        preimages,
+                        steps,
+                    },
+                ))
+                .expect("The witness workers stopped unexpectedly");
+            segment += 1;
+        };
+
+        // The witness of the chunks is built by the workers only
+        mips_wit_env.generate_witness = false;
+        let mut snapshot = mips_wit_env.snapshot();
+        let mut steps = 0;
+        while !mips_wit_env.halt {
+            let _instr: Instruction = mips_wit_env.step(configuration, meta, start);
+            steps += 1;
+            if steps == domain_size || mips_wit_env.should_trigger_at(&configuration.proof_at) {
+                debug!("Queuing the {steps} instructions executed since the last segment");
+                send_chunk(
+                    std::mem::replace(&mut snapshot, mips_wit_env.snapshot()),
+                    mips_wit_env.preimage_oracle.take(),
+                    steps,
+                );
+                steps = 0;
+            }
+        }
+        // As in the sequential path, the last segment is not proven if it is
+        // empty.
+        if steps > 0 {
+            send_chunk(snapshot, mips_wit_env.preimage_oracle.take(), steps);
+        }
+        drop(chunk_sender);
+        mips_wit_env.generate_witness = true;
+    });
+}
+
+/// Read the MIPS state to execute and its metadata, if any
+fn load_mips_state(configuration: &cannon::VmConfiguration) -> (State, Option<Meta>) {
     let file =
         File::open(&configuration.input_state_file).expect("Error opening input state file ");
 
@@ -36,17 +424,229 @@ pub fn cannon_main(args: cli::cannon::RunArgs) {
     // Read the JSON contents of the file as an instance of `State`.
     let state: State = serde_json::from_reader(reader).expect("Error reading input state file");
 
-    let meta = &configuration.metadata_file.as_ref().map(|f| {
+    let meta = configuration.metadata_file.as_ref().map(|f| {
         let meta_file =
             File::open(f).unwrap_or_else(|_| panic!("Could not open metadata file {}", f));
         serde_json::from_reader(BufReader::new(meta_file))
             .unwrap_or_else(|_| panic!("Error deserializing metadata file {}", f))
     });
+    (state, meta)
+}
+
+fn cannon_debug_diff(args: cli::cannon::DiffArgs) -> ExitCode {
+    let left = TraceReader::open(&args.left).expect("Error opening the first trace file");
+    let right = TraceReader::open(&args.right).expect("Error opening the second trace file");
+    let print_step = |name: &str, step: Option<TraceStep>| match step {
+        Some(step) => println!(
+            "{name}:\n{}",
+            serde_json::to_string_pretty(&step).expect("Error printing the step")
+        ),
+        None => println!("{name}: end of the trace"),
+    };
+    match first_divergence(left, right).expect("Error reading the traces") {
+        None => {
+            println!("The traces are identical");
+            ExitCode::SUCCESS
+        }
+        Some(Divergence { index, left, right }) => {
+            println!("The traces diverge at the step {index} of the traces");
+            print_step(&args.left, left);
+            print_step(&args.right, right);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn cannon_debug_break(args: Box<cli::cannon::BreakArgs>) -> ExitCode {
+    let args = *args;
+    let breakpoints: Vec<u32> = args
+        .break_at
+        .iter()
+        .map(|s| {
+            u32::from_str_radix(s.trim_start_matches("0x"), 16)
+                .expect("Failed to parse breakpoint address as hex")
+        })
+        .collect();
+    let configuration: cannon::VmConfiguration = args.vm_cfg.into();
+    let (state, meta) = load_mips_state(&configuration);
+    let start = Start::create(state.step as usize);
+
+    let preimage_oracle =
+        create_preimage_oracle(configuration.host.clone(), &args.preimage_db_dir, false);
+    let mut mips_wit_env = mips_witness::Env::<Fp, Box<dyn PreImageOracleT>>::create(
+        cannon::PAGE_SIZE as usize,
+        state,
+        preimage_oracle,
+    );
+    mips_wit_env.tracer = args
+        .trace
+        .map(|path| Tracer::create(path).expect("Error creating the trace file"));
+
+    let mut hits = 0;
+    let reached = loop {
+        if mips_wit_env.halt {
+            break false;
+        }
+        if breakpoints.contains(&mips_wit_env.registers.current_instruction_pointer) {
+            hits += 1;
+            if hits == args.hit {
+                break true;
+            }
+        }
+        mips_wit_env.step(&configuration, &meta, &start);
+    };
+    if let Some(tracer) = mips_wit_env.tracer.as_mut() {
+        tracer.flush().expect("Error writing the trace file");
+    }
+
+    if reached {
+        print_mips_state(&mut mips_wit_env, &meta);
+        ExitCode::SUCCESS
+    } else {
+        println!(
+            "The program halted at step {} before reaching a breakpoint",
+            mips_wit_env.normalized_instruction_counter()
+        );
+        ExitCode::FAILURE
+    }
+}
+
+/// Print the state of the MIPS machine, before the execution of the current
+/// instruction.
+fn print_mips_state(
+    mips_wit_env: &mut mips_witness::Env<Fp, Box<dyn PreImageOracleT>>,
+    meta: &Option<Meta>,
+) {
+    let registers = mips_wit_env.registers.clone();
+    let pc = registers.current_instruction_pointer;
+    let (instruction, opcode) = mips_wit_env.decode_instruction();
+    let symbol = meta
+        .as_ref()
+        .and_then(|m| m.find_address_symbol(pc))
+        .unwrap_or("n/a".to_string());
+    println!("step: {}", mips_wit_env.normalized_instruction_counter());
+    println!("pc: {:#010x} ({})", pc, symbol);
+    println!("next pc: {:#010x}", registers.next_instruction_pointer);
+    println!("instruction: {:#010x} {:?}", opcode, instruction);
+    for (i, chunk) in registers.general_purpose.chunks(4).enumerate() {
+        let line = chunk
+            .iter()
+            .enumerate()
+            .map(|(j, value)| format!("r{:<2} = {:#010x}", 4 * i + j, value))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line);
+    }
+    println!(
+        "hi = {:#010x}  lo = {:#010x}  heap = {:#010x}",
+        registers.hi, registers.lo, registers.heap_pointer
+    );
+    println!(
+        "preimage key = {}  preimage offset = {}",
+        registers
+            .preimage_key
+            .iter()
+            .map(|word| format!("{:08x}", word))
+            .collect::<String>(),
+        registers.preimage_offset
+    );
+}
+
+pub fn riscv_main(args: cli::riscv::RunArgs) {
+    let configuration: cannon::VmConfiguration = args.vm_cfg.into();
+
+    let state = elf_loader::parse_elf(
+        elf_loader::Architecture::RiscV32,
+        Path::new(&configuration.input_state_file),
+    )
+    .expect("Error loading the ELF file");
 
     // Initialize some data used for statistical computations
     let start = Start::create(state.step as usize);
 
-    let (srs, domain_fp) = match &args.srs_cache {
+    let (srs, domain_fp) = load_srs(&args.srs_cache);
+
+    // Initialize the environments
+    let preimage_oracle = create_preimage_oracle(
+        configuration.host.clone(),
+        &args.preimage_db_dir,
+        args.record_preimages,
+    );
+    let mut riscv_wit_env = riscv_witness::Env::<Fp, Box<dyn PreImageOracleT>>::create(
+        riscv32im::PAGE_SIZE as usize,
+        state,
+        preimage_oracle,
+    );
+
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let initial_state = riscv_wit_env.to_state().commitment();
+    let mut segments = Segments::create(&args.proofs_file, riscv_witness::MAX_ACC)
+        .expect("Error creating the proofs file");
+    prove_sequentially(
+        &mut riscv_wit_env,
+        &configuration,
+        &None,
+        &start,
+        domain_fp,
+        &srs,
+        &constraints,
+        &mut segments,
+    );
+    segments.finish().expect("Error writing the proofs file");
+
+    verify_continuation(
+        domain_fp,
+        &srs,
+        &constraints,
+        &ColumnLayout::RISCV32IM,
+        &args.proofs_file,
+        &initial_state,
+        &riscv_wit_env.to_state().commitment(),
+    );
+
+    let file =
+        File::create(&configuration.output_state_file).expect("Error creating output state file");
+    serde_json::to_writer_pretty(file, &riscv_wit_env.to_state())
+        .expect("Error writing output state file");
+}
+
+/// Create the preimage oracle of the VM. The preimages are served by the host
+/// program if any, and recorded in `preimage_db_dir` if `record_preimages` is
+/// set. Without host program, they are served from `preimage_db_dir`.
+fn create_preimage_oracle(
+    host: Option<HostProgram>,
+    preimage_db_dir: &Option<String>,
+    record_preimages: bool,
+) -> Box<dyn PreImageOracleT> {
+    match (host, preimage_db_dir) {
+        (Some(host), _) => {
+            let mut po = PreImageOracle::create(host);
+            let _child = po.start();
+            match preimage_db_dir {
+                Some(dir) if record_preimages => {
+                    debug!("Recording the preimages in {}", dir);
+                    Box::new(RecordingPreImageOracle::create(po, dir))
+                }
+                _ => Box::new(po),
+            }
+        }
+        (None, Some(dir)) => {
+            debug!("Serving the preimages from {}", dir);
+            Box::new(DirectoryPreImageOracle::create(dir))
+        }
+        (None, None) => {
+            debug!("No preimage oracle provided 🤞");
+            // warning: the null preimage oracle has no data and will crash the program if used
+            Box::new(NullPreImageOracle)
+        }
+    }
+}
+
+/// Load the SRS from the given cache file, or create a new one for a domain
+/// of size 2^16.
+fn load_srs(srs_cache: &Option<String>) -> (SRS<Vesta>, EvaluationDomains<Fp>) {
+    match srs_cache {
         Some(cache) => {
             debug!("Loading SRS from cache {}", cache);
             let file_path = Path::new(cache);
@@ -80,105 +680,54 @@ pub fn cannon_main(args: cli::cannon::RunArgs) {
             debug!("SRS created successfully");
             (srs, domain_fp)
         }
-    };
-
-    // Initialize the environments
-    let mut mips_wit_env = match configuration.host.clone() {
-        Some(host) => {
-            let mut po = PreImageOracle::create(host);
-            let _child = po.start();
-            mips_witness::Env::<Fp, Box<dyn PreImageOracleT>>::create(
-                cannon::PAGE_SIZE as usize,
-                state,
-                Box::new(po),
-            )
-        }
-        None => {
-            debug!("No preimage oracle provided 🤞");
-            // warning: the null preimage oracle has no data and will crash the program if used
-            mips_witness::Env::<Fp, Box<dyn PreImageOracleT>>::create(
-                cannon::PAGE_SIZE as usize,
-                state,
-                Box::new(NullPreImageOracle),
-            )
-        }
-    };
-
-    let constraints = mips_constraints::get_all_constraints::<Fp>();
-    let domain_size = domain_fp.d1.size as usize;
-
-    let mut curr_proof_inputs: ProofInputs<Vesta> = ProofInputs::new(domain_size);
-    while !mips_wit_env.halt {
-        let _instr: Instruction = mips_wit_env.step(&configuration, meta, &start);
-        for (scratch, scratch_chunk) in mips_wit_env
-            .scratch_state
-            .iter()
-            .zip(curr_proof_inputs.evaluations.scratch.iter_mut())
-        {
-            scratch_chunk.push(*scratch);
-        }
-        for (scratch, scratch_chunk) in mips_wit_env
-            .scratch_state_inverse
-            .iter()
-            .zip(curr_proof_inputs.evaluations.scratch_inverse.iter_mut())
-        {
-            scratch_chunk.push(*scratch);
-        }
-        // Lookup state
-        {
-            let proof_inputs_length = curr_proof_inputs.evaluations.lookup_state.len();
-            let environment_length = mips_wit_env.lookup_state.len();
-            let lookup_state_size = std::cmp::max(proof_inputs_length, environment_length);
-            for idx in 0..lookup_state_size {
-                if idx >= environment_length {
-                    // We pad with 0s for dummy lookups missing from the environment.
-                    curr_proof_inputs.evaluations.lookup_state[idx].push(Fp::zero());
-                } else if idx >= proof_inputs_length {
-                    // We create a new column filled with 0s in the proof inputs.
-                    let mut new_vec =
-                        vec![Fp::zero(); curr_proof_inputs.evaluations.instruction_counter.len()];
-                    new_vec.push(Fp::from(mips_wit_env.lookup_state[idx]));
-                    curr_proof_inputs.evaluations.lookup_state.push(new_vec);
-                } else {
-                    // Push the value to the column.
-                    curr_proof_inputs.evaluations.lookup_state[idx]
-                        .push(Fp::from(mips_wit_env.lookup_state[idx]));
-                }
-            }
-        }
-        curr_proof_inputs
-            .evaluations
-            .instruction_counter
-            .push(Fp::from(mips_wit_env.instruction_counter));
-        // FIXME: Might be another value
-        curr_proof_inputs.evaluations.error.push(Fp::rand(&mut rng));
-
-        curr_proof_inputs
-            .evaluations
-            .selector
-            .push(Fp::from((mips_wit_env.selector - N_MIPS_REL_COLS) as u64));
-
-        if curr_proof_inputs.evaluations.instruction_counter.len() == domain_size {
-            prove_and_verify(domain_fp, &srs, &constraints, curr_proof_inputs, &mut rng);
-
-            curr_proof_inputs = ProofInputs::new(domain_size);
-        }
     }
+}
 
-    if curr_proof_inputs.evaluations.instruction_counter.len() < domain_size {
-        debug!("Padding witness for proof generation");
-        pad(&mips_wit_env, &mut curr_proof_inputs, &mut rng);
-        prove_and_verify(domain_fp, &srs, &constraints, curr_proof_inputs, &mut rng);
-    }
+/// Bind the proof inputs to the VM state at the beginning and at the end of
+/// the segment being proven, see [PublicInputs::new]. The end of the segment
+/// becomes the beginning of the next one.
+fn set_public_inputs(
+    curr_proof_inputs: &mut ProofInputs<Vesta>,
+    segment_start: &mut State,
+    final_state: State,
+    max_accesses: u64,
+) {
+    curr_proof_inputs.public_inputs = PublicInputs::new(segment_start, &final_state, max_accesses);
+    *segment_start = final_state;
 }
 
-fn prove_and_verify(
+/// Prove a segment of the execution and write it after the previous ones.
+fn prove_segment(
     domain_fp: EvaluationDomains<Fp>,
     srs: &SRS<Vesta>,
     constraints: &[E<Fp>],
     curr_proof_inputs: ProofInputs<Vesta>,
+    segments: &mut Segments,
     rng: &mut ThreadRng,
 ) {
+    let public_inputs = curr_proof_inputs.public_inputs;
+    let proof = prove_inputs(
+        domain_fp,
+        srs,
+        constraints,
+        curr_proof_inputs,
+        segments.len(),
+        rng,
+    );
+    segments
+        .push(public_inputs, proof)
+        .expect("Error writing the segment proof");
+}
+
+/// Prove the segment with index `segment` of the execution
+fn prove_inputs(
+    domain_fp: EvaluationDomains<Fp>,
+    srs: &SRS<Vesta>,
+    constraints: &[E<Fp>],
+    curr_proof_inputs: ProofInputs<Vesta>,
+    segment: usize,
+    rng: &mut ThreadRng,
+) -> Proof<Vesta> {
     let start_iteration = Instant::now();
     let proof = prover::prove::<
         Vesta,
@@ -188,58 +737,52 @@ fn prove_and_verify(
     >(domain_fp, srs, curr_proof_inputs, constraints, rng)
     .unwrap();
     debug!(
-        "Proof generated in {elapsed} μs",
+        "Proof of segment {segment} generated in {elapsed} μs",
         elapsed = start_iteration.elapsed().as_micros()
     );
+    proof
+}
+
+/// Verify the proofs of all the segments written in `proofs_file` and the
+/// state continuity between them, from the state with commitment
+/// `initial_state` to the one with commitment `final_state`. The proofs are
+/// checked against the columns of `layout`, and read one at a time.
+fn verify_continuation(
+    domain_fp: EvaluationDomains<Fp>,
+    srs: &SRS<Vesta>,
+    constraints: &[E<Fp>],
+    layout: &ColumnLayout,
+    proofs_file: &str,
+    initial_state: &[u8; 32],
+    final_state: &[u8; 32],
+) {
     let start_iteration = Instant::now();
-    let verif = verifier::verify::<
+    let mut num_segments = 0;
+    let segments = SegmentReader::<Vesta, _>::open(proofs_file)
+        .expect("Error opening the proofs file")
+        .map(|segment| {
+            num_segments += 1;
+            segment.expect("Error reading the segment proof")
+        });
+    let verif = continuation::verify::<
         Vesta,
         DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>,
         DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>,
-    >(domain_fp, srs, constraints, &proof);
+        _,
+    >(
+        domain_fp,
+        srs,
+        constraints,
+        layout,
+        segments,
+        initial_state,
+        final_state,
+    );
     debug!(
-        "Verification done in {elapsed} μs",
+        "Verification of {num_segments} segments done in {elapsed} μs",
         elapsed = start_iteration.elapsed().as_micros()
     );
-    assert!(verif);
-}
-
-fn pad(
-    witness_env: &mips_witness::Env<Fp, Box<dyn PreImageOracleT>>,
-    curr_proof_inputs: &mut ProofInputs<Vesta>,
-    rng: &mut ThreadRng,
-) {
-    let zero = Fp::zero();
-    // FIXME: Find a better way to get instruction selectors that doesn't
-    // reveal internals.
-    let noop_selector: Fp = {
-        let noop: usize = Instruction::NoOp.into();
-        Fp::from((noop - N_MIPS_REL_COLS) as u64)
-    };
-    curr_proof_inputs
-        .evaluations
-        .scratch
-        .iter_mut()
-        .for_each(|x| x.resize(x.capacity(), zero));
-    curr_proof_inputs
-        .evaluations
-        .scratch_inverse
-        .iter_mut()
-        .for_each(|x| x.resize(x.capacity(), zero));
-    curr_proof_inputs.evaluations.instruction_counter.resize(
-        curr_proof_inputs.evaluations.instruction_counter.capacity(),
-        Fp::from(witness_env.instruction_counter),
-    );
-    curr_proof_inputs
-        .evaluations
-        .error
-        .resize_with(curr_proof_inputs.evaluations.error.capacity(), || {
-            Fp::rand(rng)
-        });
-    curr_proof_inputs.evaluations.selector.resize(
-        curr_proof_inputs.evaluations.selector.capacity(),
-        noop_selector,
-    );
+    verif.expect("Error verifying the continuation");
 }
 
 fn gen_state_json(arg: cli::cannon::GenStateJsonArgs) -> Result<(), String> {
@@ -264,6 +807,15 @@ pub fn main() -> ExitCode {
             cli::cannon::Cannon::GenStateJson(args) => {
                 gen_state_json(args).expect("Error generating state.json");
             }
+            cli::cannon::Cannon::Debug(args) => match args {
+                cli::cannon::Debug::Diff(args) => return cannon_debug_diff(args),
+                cli::cannon::Debug::Break(args) => return cannon_debug_break(args),
+            },
+        },
+        cli::Commands::Riscv(args) => match args {
+            cli::riscv::Riscv::Run(args) => {
+                riscv_main(args);
+            }
         },
     }
     ExitCode::SUCCESS
@@ -3,8 +3,9 @@
 //! each instruction using the Pasta curves and the IPA PCS.
 //!
 //! A proof is generated for each set of N continuous instructions, where N is
-//! the size of the supported SRS. The proofs will then be aggregated using
-//! a modified version of pickles.
+//! the size of the supported SRS. The proofs are chained into a
+//! [continuation::Continuation], and will then be aggregated using a modified
+//! version of pickles.
 //!
 //! You can run this flavor by using:
 //!
@@ -12,11 +13,16 @@
 //! O1VM_FLAVOR=pickles bash run-code.sh
 //! ```
 
+pub mod chunk;
 pub mod column_env;
+pub mod continuation;
 pub mod proof;
 pub mod prover;
 pub mod verifier;
 
+use crate::E;
+use ark_ff::Field;
+
 /// Maximum degree of the constraints.
 /// It does include the additional degree induced by the multiplication of the
 /// selectors.
@@ -33,5 +39,24 @@ pub const DEGREE_QUOTIENT_POLYNOMIAL: u64 = 7;
 /// added for the selectors.
 pub const TOTAL_NUMBER_OF_CONSTRAINTS: usize = 466;
 
+/// Combine the constraints into `∑ α^i constraint_i`, like
+/// [kimchi::circuits::expr::Expr::combine_constraints], but as a balanced sum.
+/// The expression is evaluated recursively, and summing the thousands of
+/// constraints of the RISC-V interpreter one after the other overflows the
+/// stack.
+pub fn combine_constraints<F: Field>(constraints: &[E<F>]) -> E<F> {
+    fn combine<F: Field>(first_alpha: u32, constraints: &[E<F>]) -> E<F> {
+        if constraints.len() <= 1 {
+            return E::combine_constraints(
+                first_alpha..first_alpha + constraints.len() as u32,
+                constraints.to_vec(),
+            );
+        }
+        let (left, right) = constraints.split_at(constraints.len() / 2);
+        combine(first_alpha, left) + combine(first_alpha + left.len() as u32, right)
+    }
+    combine(0, constraints)
+}
+
 #[cfg(test)]
 mod tests;
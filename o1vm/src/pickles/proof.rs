@@ -1,41 +1,340 @@
+use ark_ff::{PrimeField, UniformRand};
 use kimchi::{curve::KimchiCurve, proof::PointEvaluations};
 use poly_commitment::{ipa::OpeningProof, PolyComm};
+use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{
+    de::DeserializeAsWrap, ser::SerializeAsWrap, serde_as, DeserializeAs, SerializeAs,
+};
 
-use crate::interpreters::mips::column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE};
+use kimchi::circuits::{
+    expr::{ExprInner, RowOffset},
+    gate::CurrOrNext,
+};
+use kimchi_msm::columns::Column;
 
+use super::column_env::RelationColumnType;
+use crate::{
+    cannon::State,
+    interpreters::{
+        mips::{
+            self,
+            column::{N_MIPS_SEL_COLS, SCRATCH_SIZE, SCRATCH_SIZE_INVERSE},
+        },
+        riscv32im::{self, column::N_RISCV32IM_SEL_COLS},
+    },
+    E,
+};
+
+/// The witness columns of a proof. The number of scratch columns, of scratch
+/// columns to be inverted and of selectors depends on the interpreter being
+/// proven, see [ColumnLayout].
+///
+/// The columns can be serialized with serde as any value of type `G` and
+/// `S`, or with [serde_with] with the representation of their values, see
+/// [Proof].
+#[derive(Serialize, Deserialize)]
 pub struct WitnessColumns<G, S> {
-    pub scratch: [G; SCRATCH_SIZE],
-    pub scratch_inverse: [G; SCRATCH_SIZE_INVERSE],
+    pub scratch: Vec<G>,
+    pub scratch_inverse: Vec<G>,
     pub lookup_state: Vec<G>,
     pub instruction_counter: G,
     pub error: G,
     pub selector: S,
 }
 
+impl<G, S> WitnessColumns<G, S> {
+    /// Apply `f` to each column and `f_selector` to the selectors.
+    pub fn map<G2, S2>(
+        self,
+        f: impl Fn(G) -> G2,
+        f_selector: impl FnOnce(S) -> S2,
+    ) -> WitnessColumns<G2, S2> {
+        let WitnessColumns {
+            scratch,
+            scratch_inverse,
+            lookup_state,
+            instruction_counter,
+            error,
+            selector,
+        } = self;
+        WitnessColumns {
+            scratch: scratch.into_iter().map(&f).collect(),
+            scratch_inverse: scratch_inverse.into_iter().map(&f).collect(),
+            lookup_state: lookup_state.into_iter().map(&f).collect(),
+            instruction_counter: f(instruction_counter),
+            error: f(error),
+            selector: f_selector(selector),
+        }
+    }
+
+    /// Apply `f` to a reference to each column and `f_selector` to a
+    /// reference to the selectors.
+    pub fn map_ref<'a, G2, S2>(
+        &'a self,
+        f: impl Fn(&'a G) -> G2,
+        f_selector: impl FnOnce(&'a S) -> S2,
+    ) -> WitnessColumns<G2, S2> {
+        WitnessColumns {
+            scratch: self.scratch.iter().map(&f).collect(),
+            scratch_inverse: self.scratch_inverse.iter().map(&f).collect(),
+            lookup_state: self.lookup_state.iter().map(&f).collect(),
+            instruction_counter: f(&self.instruction_counter),
+            error: f(&self.error),
+            selector: f_selector(&self.selector),
+        }
+    }
+}
+
+impl<G, S, GAs, SAs> SerializeAs<WitnessColumns<G, S>> for WitnessColumns<GAs, SAs>
+where
+    GAs: SerializeAs<G>,
+    SAs: SerializeAs<S>,
+{
+    fn serialize_as<Ser: Serializer>(
+        source: &WitnessColumns<G, S>,
+        serializer: Ser,
+    ) -> Result<Ser::Ok, Ser::Error> {
+        source
+            .map_ref(
+                SerializeAsWrap::<G, GAs>::new,
+                SerializeAsWrap::<S, SAs>::new,
+            )
+            .serialize(serializer)
+    }
+}
+
+impl<'de, G, S, GAs, SAs> DeserializeAs<'de, WitnessColumns<G, S>> for WitnessColumns<GAs, SAs>
+where
+    GAs: DeserializeAs<'de, G>,
+    SAs: DeserializeAs<'de, S>,
+{
+    fn deserialize_as<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<WitnessColumns<G, S>, D::Error> {
+        let columns =
+            WitnessColumns::<DeserializeAsWrap<G, GAs>, DeserializeAsWrap<S, SAs>>::deserialize(
+                deserializer,
+            )?;
+        Ok(columns.map(DeserializeAsWrap::into_inner, DeserializeAsWrap::into_inner))
+    }
+}
+
+impl<G> WitnessColumns<G, Vec<G>> {
+    /// Check that there is exactly one value per column of `layout`.
+    pub fn has_layout(&self, layout: &ColumnLayout) -> bool {
+        self.scratch.len() == layout.scratch_size
+            && self.scratch_inverse.len() == layout.scratch_size_inverse
+            && self.lookup_state.len() == layout.num_lookup_columns
+            && self.selector.len() == layout.num_selectors
+    }
+}
+
+/// The number of columns of each kind used by an interpreter.
+///
+/// The layout is fixed by the interpreter being proven: the verifier checks
+/// the proofs against the layout it expects, never against the number of
+/// columns found in the proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnLayout {
+    pub scratch_size: usize,
+    pub scratch_size_inverse: usize,
+    pub num_lookup_columns: usize,
+    pub num_selectors: usize,
+    /// The number of memory accesses allowed per instruction, by which the
+    /// instruction counter is incremented at each instruction
+    pub max_accesses: u64,
+}
+
+impl ColumnLayout {
+    /// The layout of the MIPS interpreter
+    pub const MIPS: Self = ColumnLayout {
+        scratch_size: SCRATCH_SIZE,
+        scratch_size_inverse: SCRATCH_SIZE_INVERSE,
+        num_lookup_columns: 0,
+        num_selectors: N_MIPS_SEL_COLS,
+        max_accesses: mips::witness::MAX_ACC,
+    };
+
+    /// The layout of the RISC-V interpreter
+    pub const RISCV32IM: Self = ColumnLayout {
+        scratch_size: riscv32im::SCRATCH_SIZE,
+        scratch_size_inverse: riscv32im::SCRATCH_SIZE_INVERSE,
+        num_lookup_columns: 0,
+        num_selectors: N_RISCV32IM_SEL_COLS,
+        max_accesses: riscv32im::witness::MAX_ACC,
+    };
+}
+
+/// The statement of a proof: the commitments to the state of the VM before
+/// and after the execution of the proven chunk, see
+/// [crate::cannon::State::commitment], the preimage key held by the VM at the
+/// end of the chunk, and the values of the witness on the boundary rows.
+///
+/// The public inputs are absorbed in the Fq-sponge before any commitment,
+/// therefore a proof does not verify against another statement. The boundary
+/// values are also enforced on the first and last rows of the witness, see
+/// [PublicInputs::boundary_constraints]. The statement is never read from the
+/// proof: it is supplied by the verifier, see [super::verifier::verify].
+///
+/// Only the boundary values are bound to the witness. The registers and the
+/// memory are only accessed through lookups, which are not proven yet, so
+/// nothing ties the state commitments to the values read and written by the
+/// instructions: they only label the proof, and are used to link the
+/// segments of a [super::continuation::Continuation].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputs {
+    pub initial_state: [u8; 32],
+    pub final_state: [u8; 32],
+    pub preimage_key: [u8; 32],
+    /// The instruction pointer of the first instruction of the chunk, i.e.
+    /// the value of the first scratch column on the first row
+    pub first_instruction_pointer: u32,
+    /// The instruction counter on the first row, i.e. after the execution of
+    /// the first instruction of the chunk
+    pub first_instruction_counter: u64,
+    /// The instruction counter on the last row, i.e. after the execution of
+    /// the last instruction of the chunk
+    pub last_instruction_counter: u64,
+}
+
+impl PublicInputs {
+    /// The statement of a chunk going from `initial_state` to `final_state`.
+    /// `max_accesses` is the number of memory accesses allowed per
+    /// instruction by the interpreter, which scales the instruction counter.
+    pub fn new(initial_state: &State, final_state: &State, max_accesses: u64) -> Self {
+        PublicInputs {
+            initial_state: initial_state.commitment(),
+            final_state: final_state.commitment(),
+            preimage_key: final_state.preimage_key,
+            first_instruction_pointer: initial_state.pc,
+            first_instruction_counter: (initial_state.step + 1) * max_accesses,
+            last_instruction_counter: final_state.step * max_accesses,
+        }
+    }
+
+    /// Encode the public inputs as scalar field elements. Each 32 bytes value
+    /// is split in two big-endian 128 bits limbs, which makes the encoding
+    /// injective for any field larger than 128 bits.
+    pub fn to_field_elements<F: PrimeField>(&self) -> Vec<F> {
+        [self.initial_state, self.final_state, self.preimage_key]
+            .iter()
+            .flat_map(|bytes| {
+                bytes
+                    .chunks(16)
+                    .map(F::from_be_bytes_mod_order)
+                    .collect::<Vec<_>>()
+            })
+            .chain([
+                F::from(self.first_instruction_pointer),
+                F::from(self.first_instruction_counter),
+                F::from(self.last_instruction_counter),
+            ])
+            .collect()
+    }
+
+    /// The constraints binding the boundary values to the witness. They only
+    /// hold on the first or the last row, as they are multiplied by the
+    /// corresponding unnormalized Lagrange basis polynomial.
+    // FIXME: bind the registers and the memory to the state commitments once
+    // the register and memory lookups are proven.
+    pub fn boundary_constraints<F: PrimeField>(&self) -> Vec<E<F>> {
+        let lagrange = |offset| {
+            E::Atom(ExprInner::UnnormalizedLagrangeBasis(RowOffset {
+                zk_rows: false,
+                offset,
+            }))
+        };
+        let cell = |col| E::cell(Column::Relation(col), CurrOrNext::Curr);
+        let first_row = lagrange(0);
+        let last_row = lagrange(-1);
+        vec![
+            first_row.clone()
+                * (cell(RelationColumnType::Scratch(0))
+                    - E::from(self.first_instruction_pointer as u64)),
+            first_row
+                * (cell(RelationColumnType::InstructionCounter)
+                    - E::from(self.first_instruction_counter)),
+            last_row
+                * (cell(RelationColumnType::InstructionCounter)
+                    - E::from(self.last_instruction_counter)),
+        ]
+    }
+}
+
 pub struct ProofInputs<G: KimchiCurve> {
+    pub public_inputs: PublicInputs,
     pub evaluations: WitnessColumns<Vec<G::ScalarField>, Vec<G::ScalarField>>,
+    /// The number of instruction selectors. The selector column of
+    /// [ProofInputs::evaluations] contains, for each row, the index of the
+    /// selector activated by the instruction, which is in `0..num_selectors`.
+    pub num_selectors: usize,
 }
 
 impl<G: KimchiCurve> ProofInputs<G> {
+    /// Create empty inputs for the MIPS interpreter.
     pub fn new(domain_size: usize) -> Self {
+        Self::new_with_layout(domain_size, ColumnLayout::MIPS)
+    }
+
+    /// Create empty inputs for an interpreter using the columns of `layout`.
+    pub fn new_with_layout(domain_size: usize, layout: ColumnLayout) -> Self {
         ProofInputs {
+            public_inputs: PublicInputs::default(),
             evaluations: WitnessColumns {
-                scratch: std::array::from_fn(|_| Vec::with_capacity(domain_size)),
-                scratch_inverse: std::array::from_fn(|_| Vec::with_capacity(domain_size)),
-                lookup_state: vec![],
+                scratch: (0..layout.scratch_size)
+                    .map(|_| Vec::with_capacity(domain_size))
+                    .collect(),
+                scratch_inverse: (0..layout.scratch_size_inverse)
+                    .map(|_| Vec::with_capacity(domain_size))
+                    .collect(),
+                lookup_state: (0..layout.num_lookup_columns)
+                    .map(|_| Vec::with_capacity(domain_size))
+                    .collect(),
                 instruction_counter: Vec::with_capacity(domain_size),
                 error: Vec::with_capacity(domain_size),
                 selector: Vec::with_capacity(domain_size),
             },
+            num_selectors: layout.num_selectors,
         }
     }
+
+    /// Pad the witness to `domain_size` rows by repeating the last row, and
+    /// fill the error column with random values. As the constraints only
+    /// involve the current row, the padding rows satisfy the constraints as
+    /// long as the last row does.
+    pub fn pad_with_last_row(&mut self, domain_size: usize, rng: &mut impl RngCore) {
+        let repeat_last = |column: &mut Vec<G::ScalarField>| {
+            let last = *column.last().expect("Cannot pad an empty witness");
+            column.resize(domain_size, last)
+        };
+        let evaluations = &mut self.evaluations;
+        evaluations.scratch.iter_mut().for_each(repeat_last);
+        evaluations.scratch_inverse.iter_mut().for_each(repeat_last);
+        evaluations.lookup_state.iter_mut().for_each(repeat_last);
+        repeat_last(&mut evaluations.instruction_counter);
+        evaluations
+            .error
+            .resize_with(domain_size, || G::ScalarField::rand(rng));
+        repeat_last(&mut evaluations.selector);
+    }
 }
 
 // FIXME: should we blind the commitment?
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+// The bounds on G required by ark-serialize are implied by KimchiCurve
+#[serde(bound = "")]
 pub struct Proof<G: KimchiCurve> {
-    pub commitments: WitnessColumns<PolyComm<G>, [PolyComm<G>; N_MIPS_SEL_COLS]>,
-    pub zeta_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]>,
-    pub zeta_omega_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]>,
+    pub commitments: WitnessColumns<PolyComm<G>, Vec<PolyComm<G>>>,
+    #[serde_as(
+        as = "WitnessColumns<o1_utils::serialization::SerdeAs, Vec<o1_utils::serialization::SerdeAs>>"
+    )]
+    pub zeta_evaluations: WitnessColumns<G::ScalarField, Vec<G::ScalarField>>,
+    #[serde_as(
+        as = "WitnessColumns<o1_utils::serialization::SerdeAs, Vec<o1_utils::serialization::SerdeAs>>"
+    )]
+    pub zeta_omega_evaluations: WitnessColumns<G::ScalarField, Vec<G::ScalarField>>,
     pub quotient_commitment: PolyComm<G>,
     pub quotient_evaluations: PointEvaluations<Vec<G::ScalarField>>,
     /// IPA opening proof
@@ -1,5 +1,3 @@
-use std::array;
-
 use ark_ff::{One, PrimeField, Zero};
 use ark_poly::{univariate::DensePolynomial, Evaluations, Polynomial, Radix2EvaluationDomain as D};
 use kimchi::{
@@ -30,7 +28,7 @@ use super::{
     proof::{Proof, ProofInputs, WitnessColumns},
     DEGREE_QUOTIENT_POLYNOMIAL,
 };
-use crate::{interpreters::mips::column::N_MIPS_SEL_COLS, E};
+use crate::E;
 use thiserror::Error;
 
 /// Errors that can arise when creating a proof
@@ -45,6 +43,8 @@ pub enum ProverError {
 /// `domain`.
 ///
 /// The proof is made of the following steps:
+/// 0. We absorb the public inputs, i.e. the initial and final VM states, in the
+///    sponge.
 /// 1. For each column, we create a commitment and absorb it in the sponge.
 /// 2. We compute the quotient polynomial.
 /// 3. We evaluate each polynomial (columns + quotient) to two challenges ζ and ζω.
@@ -71,17 +71,27 @@ where
     let num_chunks = 1;
     let omega = domain.d1.group_gen;
 
+    let ProofInputs {
+        public_inputs,
+        evaluations,
+        num_selectors,
+    } = inputs;
+
+    ////////////////////////////////////////////////////////////////////////////
+    // Round 0: Absorbing the public inputs
+    ////////////////////////////////////////////////////////////////////////////
+
     let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
+    fq_sponge.absorb_fr(&public_inputs.to_field_elements::<G::ScalarField>());
 
     ////////////////////////////////////////////////////////////////////////////
     // Round 1: Creating and absorbing column commitments
     ////////////////////////////////////////////////////////////////////////////
 
     debug!("Prover: interpolating all columns, including the selectors");
-    let ProofInputs { evaluations } = inputs;
     let polys: WitnessColumns<
         DensePolynomial<G::ScalarField>,
-        [DensePolynomial<G::ScalarField>; N_MIPS_SEL_COLS],
+        Vec<DensePolynomial<G::ScalarField>>,
     > = {
         let WitnessColumns {
             scratch,
@@ -95,17 +105,19 @@ where
         let domain_size = domain.d1.size as usize;
 
         // Build the selectors
-        let selector: [Vec<G::ScalarField>; N_MIPS_SEL_COLS] = array::from_fn(|i| {
-            let mut s_i = Vec::with_capacity(domain_size);
-            for s in &selector {
-                s_i.push(if G::ScalarField::from(i as u64) == *s {
-                    G::ScalarField::one()
-                } else {
-                    G::ScalarField::zero()
-                })
-            }
-            s_i
-        });
+        let selector: Vec<Vec<G::ScalarField>> = (0..num_selectors)
+            .map(|i| {
+                let mut s_i = Vec::with_capacity(domain_size);
+                for s in &selector {
+                    s_i.push(if G::ScalarField::from(i as u64) == *s {
+                        G::ScalarField::one()
+                    } else {
+                        G::ScalarField::zero()
+                    })
+                }
+                s_i
+            })
+            .collect();
 
         let eval_col = |evals: Vec<G::ScalarField>| {
             Evaluations::<G::ScalarField, D<G::ScalarField>>::from_vec_and_domain(evals, domain.d1)
@@ -126,17 +138,17 @@ where
             .collect::<Vec<_>>();
         let selector = selector.into_par_iter().map(eval_col).collect::<Vec<_>>();
         WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
+            scratch,
+            scratch_inverse,
             lookup_state,
             instruction_counter: eval_col(instruction_counter),
             error: eval_col(error.clone()),
-            selector: selector.try_into().unwrap(),
+            selector,
         }
     };
 
     debug!("Prover: committing to all columns, including the selectors");
-    let commitments: WitnessColumns<PolyComm<G>, [PolyComm<G>; N_MIPS_SEL_COLS]> = {
+    let commitments: WitnessColumns<PolyComm<G>, Vec<PolyComm<G>>> = {
         let WitnessColumns {
             scratch,
             scratch_inverse,
@@ -161,12 +173,12 @@ where
         let lookup_state = lookup_state.par_iter().map(comm).collect::<Vec<_>>();
         let selector = selector.par_iter().map(comm).collect::<Vec<_>>();
         WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
+            scratch,
+            scratch_inverse,
             lookup_state,
             instruction_counter: comm(instruction_counter),
             error: comm(error),
-            selector: selector.try_into().unwrap(),
+            selector,
         }
     };
 
@@ -198,12 +210,12 @@ where
             .collect::<Vec<_>>();
         let selector = selector.into_par_iter().map(eval_d8).collect::<Vec<_>>();
         WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
+            scratch,
+            scratch_inverse,
             lookup_state,
             instruction_counter: eval_d8(instruction_counter),
             error: eval_d8(error),
-            selector: selector.try_into().unwrap(),
+            selector,
         }
     };
 
@@ -267,8 +279,13 @@ where
     // ```
     let quotient_poly: DensePolynomial<G::ScalarField> = {
         // Compute ∑ α^i constraint_i as an expression
-        let combined_expr =
-            E::combine_constraints(0..(constraints.len() as u32), (constraints).to_vec());
+        // The boundary constraints bind the public inputs to the witness
+        let constraints: Vec<_> = constraints
+            .iter()
+            .cloned()
+            .chain(public_inputs.boundary_constraints())
+            .collect();
+        let combined_expr = super::combine_constraints(&constraints);
 
         // We want to compute the quotient polynomial, i.e.
         // t(X) = (∑ α^i constraint_i(X)) / Z_H(X).
@@ -343,20 +360,19 @@ where
         let lookup_state = lookup_state.par_iter().map(eval).collect::<Vec<_>>();
         let selector = selector.par_iter().map(eval).collect::<Vec<_>>();
         WitnessColumns {
-            scratch: scratch.try_into().unwrap(),
-            scratch_inverse: scratch_inverse.try_into().unwrap(),
+            scratch,
+            scratch_inverse,
             lookup_state,
             instruction_counter: eval(instruction_counter),
             error: eval(error),
-            selector: selector.try_into().unwrap(),
+            selector,
         }
     };
     // All evaluations at ζ
-    let zeta_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]> =
-        evals(&zeta);
+    let zeta_evaluations: WitnessColumns<G::ScalarField, Vec<G::ScalarField>> = evals(&zeta);
 
     // All evaluations at ζω
-    let zeta_omega_evaluations: WitnessColumns<G::ScalarField, [G::ScalarField; N_MIPS_SEL_COLS]> =
+    let zeta_omega_evaluations: WitnessColumns<G::ScalarField, Vec<G::ScalarField>> =
         evals(&zeta_omega);
 
     let chunked_quotient = quotient_poly
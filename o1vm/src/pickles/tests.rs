@@ -1,28 +1,43 @@
-use std::time::Instant;
+use std::{sync::Arc, time::Instant};
 
 use super::{
     super::interpreters::mips::column::SCRATCH_SIZE,
-    proof::{ProofInputs, WitnessColumns},
+    proof::{ColumnLayout, Proof, ProofInputs, PublicInputs, WitnessColumns},
     prover::prove,
 };
 use crate::{
-    interpreters::mips::{
-        column::SCRATCH_SIZE_INVERSE,
-        constraints as mips_constraints,
-        interpreter::{self, InterpreterEnv},
-        Instruction,
+    cannon::{Page, Start, State, VmConfiguration, PAGE_SIZE},
+    elf_loader::{self, Architecture},
+    interpreters::{
+        mips::{
+            column::{N_MIPS_SEL_COLS, SCRATCH_SIZE_INVERSE},
+            constraints as mips_constraints,
+            interpreter::{self, InterpreterEnv},
+            witness as mips_witness, Instruction,
+        },
+        riscv32im::{
+            self,
+            column::{N_RISCV32IM_REL_COLS, N_RISCV32IM_SEL_COLS},
+            constraints as riscv_constraints, witness as riscv_witness,
+        },
     },
     pickles::{
-        column_env::RelationColumnType, verifier::verify, MAXIMUM_DEGREE_CONSTRAINTS,
-        TOTAL_NUMBER_OF_CONSTRAINTS,
+        chunk::{pad_mips, push_mips_row, replay_configuration, Chunk},
+        column_env::RelationColumnType,
+        continuation::{
+            self, Continuation, ContinuationError, Segment, SegmentReader, SegmentWriter,
+        },
+        verifier::verify,
+        MAXIMUM_DEGREE_CONSTRAINTS, TOTAL_NUMBER_OF_CONSTRAINTS,
     },
+    preimage_oracle::{BufferingPreImageOracle, NullPreImageOracle},
     E,
 };
 use ark_ff::{Field, One, UniformRand, Zero};
 use kimchi::circuits::{domains::EvaluationDomains, expr::Expr, gate::CurrOrNext};
 use kimchi_msm::columns::Column;
 use log::debug;
-use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters};
+use mina_curves::pasta::{Fp, Fq, Pallas, PallasParameters, Vesta, VestaParameters};
 use mina_poseidon::{
     constants::PlonkSpongeConstantsKimchi,
     sponge::{DefaultFqSponge, DefaultFrSponge},
@@ -59,6 +74,13 @@ fn test_regression_constraints_with_selectors() {
     assert_eq!(max_degree, MAXIMUM_DEGREE_CONSTRAINTS);
 }
 
+#[test]
+fn test_riscv32im_constraints_degree() {
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+    let max_degree = constraints.iter().map(|c| c.degree(1, 0)).max().unwrap();
+    assert!(max_degree <= MAXIMUM_DEGREE_CONSTRAINTS);
+}
+
 fn zero_to_n_minus_one(n: usize) -> Vec<Fq> {
     (0..n).map(|i| Fq::from((i) as u64)).collect()
 }
@@ -67,10 +89,20 @@ fn zero_to_n_minus_one(n: usize) -> Vec<Fq> {
 fn test_small_circuit() {
     let domain = EvaluationDomains::<Fq>::create(8).unwrap();
     let srs = SRS::create(8);
+    // The first scratch column and the instruction counter start at 0 and 1
+    let public_inputs = PublicInputs {
+        first_instruction_pointer: 0,
+        first_instruction_counter: 1,
+        last_instruction_counter: 8,
+        ..PublicInputs::default()
+    };
     let proof_input = ProofInputs::<Pallas> {
+        public_inputs,
         evaluations: WitnessColumns {
-            scratch: std::array::from_fn(|_| zero_to_n_minus_one(8)),
-            scratch_inverse: std::array::from_fn(|_| (0..8).map(|_| Fq::zero()).collect()),
+            scratch: (0..SCRATCH_SIZE).map(|_| zero_to_n_minus_one(8)).collect(),
+            scratch_inverse: (0..SCRATCH_SIZE_INVERSE)
+                .map(|_| (0..8).map(|_| Fq::zero()).collect())
+                .collect(),
             lookup_state: vec![],
             instruction_counter: zero_to_n_minus_one(8)
                 .into_iter()
@@ -81,6 +113,7 @@ fn test_small_circuit() {
                 .collect(),
             selector: zero_to_n_minus_one(8),
         },
+        num_selectors: N_MIPS_SEL_COLS,
     };
     let mut expr = Expr::zero();
     for i in 0..SCRATCH_SIZE {
@@ -118,7 +151,14 @@ fn test_small_circuit() {
     .unwrap();
 
     let instant_before_verification = Instant::now();
-    let verif = verify::<Pallas, BaseSponge, ScalarSponge>(domain, &srs, &[expr.clone()], &proof);
+    let verif = verify::<Pallas, BaseSponge, ScalarSponge>(
+        domain,
+        &srs,
+        &[expr.clone()],
+        &ColumnLayout::MIPS,
+        &public_inputs,
+        &proof,
+    );
     let instant_after_verification = Instant::now();
     debug!(
         "Verification took: {} ms",
@@ -127,6 +167,776 @@ fn test_small_circuit() {
     assert!(verif, "Verification fails");
 }
 
+type VestaBaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type VestaScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi>;
+
+fn riscv32im_env(program: &str) -> riscv_witness::Env<Fp, NullPreImageOracle> {
+    let path = std::env::current_dir()
+        .unwrap()
+        .join("resources/programs/riscv32im/bin")
+        .join(program);
+    let state = elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
+    riscv_witness::Env::<Fp, NullPreImageOracle>::create(
+        riscv32im::PAGE_SIZE as usize,
+        state,
+        NullPreImageOracle,
+    )
+}
+
+/// Execute at most `max_steps` instructions with the given RISC-V environment
+/// and prove them as a single segment, bound to the states of the VM before
+/// and after the segment. The statement of the segment is returned with its
+/// proof.
+fn prove_riscv32im_segment(
+    env: &mut riscv_witness::Env<Fp, NullPreImageOracle>,
+    max_steps: usize,
+    domain: EvaluationDomains<Fp>,
+    srs: &poly_commitment::ipa::SRS<Vesta>,
+    constraints: &[E<Fp>],
+) -> (PublicInputs, Proof<Vesta>) {
+    let domain_size = domain.d1.size as usize;
+    let mut rng = make_test_rng(None);
+    let initial_state = env.to_state();
+
+    let mut proof_inputs =
+        ProofInputs::<Vesta>::new_with_layout(domain_size, ColumnLayout::RISCV32IM);
+    let evaluations = &mut proof_inputs.evaluations;
+    while !env.halt && evaluations.selector.len() < max_steps {
+        env.step();
+        for (scratch, column) in env.scratch_state.iter().zip(evaluations.scratch.iter_mut()) {
+            column.push(*scratch);
+        }
+        for (scratch, column) in env
+            .scratch_state_inverse
+            .iter()
+            .zip(evaluations.scratch_inverse.iter_mut())
+        {
+            column.push(*scratch);
+        }
+        evaluations
+            .instruction_counter
+            .push(Fp::from(env.instruction_counter));
+        evaluations.error.push(Fp::rand(&mut rng));
+        evaluations
+            .selector
+            .push(Fp::from((env.selector - N_RISCV32IM_REL_COLS) as u64));
+    }
+    assert!(evaluations.selector.len() <= domain_size);
+    proof_inputs.pad_with_last_row(domain_size, &mut rng);
+
+    let public_inputs = PublicInputs::new(&initial_state, &env.to_state(), riscv_witness::MAX_ACC);
+    proof_inputs.public_inputs = public_inputs;
+
+    let proof = prove::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+        domain,
+        srs,
+        proof_inputs,
+        constraints,
+        &mut rng,
+    )
+    .unwrap();
+    (public_inputs, proof)
+}
+
+#[test]
+fn test_riscv32im_program_execution_proof() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let (public_inputs, proof) =
+        prove_riscv32im_segment(&mut env, domain_size, domain, &srs, &constraints);
+    assert!(env.halt);
+    assert_ne!(public_inputs.initial_state, public_inputs.final_state);
+    let verif = verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+        domain,
+        &srs,
+        &constraints,
+        &ColumnLayout::RISCV32IM,
+        &public_inputs,
+        &proof,
+    );
+    assert!(verif, "Verification fails");
+}
+
+#[test]
+fn test_riscv32im_proof_with_unexpected_layout_is_rejected() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let (public_inputs, mut proof) =
+        prove_riscv32im_segment(&mut env, domain_size, domain, &srs, &constraints);
+
+    // The layout is fixed by the verifier, not by the proof
+    let verif = verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+        domain,
+        &srs,
+        &constraints,
+        &ColumnLayout::MIPS,
+        &public_inputs,
+        &proof,
+    );
+    assert!(!verif, "Verification succeeds with another layout");
+
+    // Drop the last selector, which is used by the constraints
+    proof.commitments.selector.pop();
+    proof.zeta_evaluations.selector.pop();
+    proof.zeta_omega_evaluations.selector.pop();
+    let verif = verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+        domain,
+        &srs,
+        &constraints,
+        &ColumnLayout::RISCV32IM,
+        &public_inputs,
+        &proof,
+    );
+    assert!(!verif, "Verification succeeds with a missing selector");
+
+    let truncated_layout = ColumnLayout {
+        num_selectors: N_RISCV32IM_SEL_COLS - 1,
+        ..ColumnLayout::RISCV32IM
+    };
+    let verif = verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+        domain,
+        &srs,
+        &constraints,
+        &truncated_layout,
+        &public_inputs,
+        &proof,
+    );
+    assert!(
+        !verif,
+        "Verification succeeds with an out of range selector"
+    );
+}
+
+#[test]
+fn test_riscv32im_proof_does_not_verify_against_tampered_state() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let (public_inputs, proof) = prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+
+    let tamperings: [fn(&mut PublicInputs); 6] = [
+        |public_inputs| public_inputs.initial_state[0] ^= 1,
+        |public_inputs| public_inputs.final_state[31] ^= 1,
+        |public_inputs| public_inputs.preimage_key[0] = 2,
+        |public_inputs| public_inputs.first_instruction_pointer += 4,
+        |public_inputs| public_inputs.first_instruction_counter += riscv_witness::MAX_ACC,
+        |public_inputs| public_inputs.last_instruction_counter += riscv_witness::MAX_ACC,
+    ];
+    for tamper in tamperings {
+        let mut claimed = public_inputs;
+        tamper(&mut claimed);
+        let verif = verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &ColumnLayout::RISCV32IM,
+            &claimed,
+            &proof,
+        );
+        assert!(!verif, "Verification succeeds against a tampered state");
+    }
+
+    // Sanity check: the honest statement still verifies
+    let verif = verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+        domain,
+        &srs,
+        &constraints,
+        &ColumnLayout::RISCV32IM,
+        &public_inputs,
+        &proof,
+    );
+    assert!(verif, "Verification fails");
+}
+
+#[test]
+fn test_riscv32im_proof_does_not_verify_against_another_segment_state() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let (first_public_inputs, first_proof) =
+        prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+    let (second_public_inputs, second_proof) =
+        prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+
+    // Honest proofs do not verify against the statement of another segment
+    for (public_inputs, proof) in [
+        (&second_public_inputs, &first_proof),
+        (&first_public_inputs, &second_proof),
+    ] {
+        let verif = verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &ColumnLayout::RISCV32IM,
+            public_inputs,
+            proof,
+        );
+        assert!(
+            !verif,
+            "Verification succeeds against a wrong claimed state"
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "The constraints are not satisfied")]
+fn test_riscv32im_witness_cannot_be_proven_for_a_wrong_initial_state() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+    let mut rng = make_test_rng(None);
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let mut initial_state = env.to_state();
+    let mut proof_inputs =
+        ProofInputs::<Vesta>::new_with_layout(domain_size, ColumnLayout::RISCV32IM);
+    let evaluations = &mut proof_inputs.evaluations;
+    env.step();
+    for (scratch, column) in env.scratch_state.iter().zip(evaluations.scratch.iter_mut()) {
+        column.push(*scratch);
+    }
+    for (scratch, column) in env
+        .scratch_state_inverse
+        .iter()
+        .zip(evaluations.scratch_inverse.iter_mut())
+    {
+        column.push(*scratch);
+    }
+    evaluations
+        .instruction_counter
+        .push(Fp::from(env.instruction_counter));
+    evaluations.error.push(Fp::rand(&mut rng));
+    evaluations
+        .selector
+        .push(Fp::from((env.selector - N_RISCV32IM_REL_COLS) as u64));
+    proof_inputs.pad_with_last_row(domain_size, &mut rng);
+
+    // The witness starts at another instruction than the claimed one, the
+    // boundary constraints cannot be satisfied.
+    initial_state.pc += 4;
+    proof_inputs.public_inputs =
+        PublicInputs::new(&initial_state, &env.to_state(), riscv_witness::MAX_ACC);
+    let _ = prove::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+        domain,
+        &srs,
+        proof_inputs,
+        &constraints,
+        &mut rng,
+    );
+}
+
+#[test]
+fn test_riscv32im_continuation() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let initial_state = env.to_state().commitment();
+    let mut continuation = Continuation::new(riscv_witness::MAX_ACC);
+    while !env.halt {
+        let (public_inputs, proof) =
+            prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+        continuation.push(public_inputs, proof).unwrap();
+    }
+    assert!(continuation.len() > 1);
+    let final_state = env.to_state().commitment();
+    assert_eq!(
+        continuation.final_statement().unwrap().final_state,
+        final_state
+    );
+
+    let verify_continuation = |segments: &[Segment<Vesta>], initial_state, final_state| {
+        continuation::verify::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+            domain,
+            &srs,
+            &constraints,
+            &ColumnLayout::RISCV32IM,
+            segments,
+            initial_state,
+            final_state,
+        )
+    };
+    let segments = continuation.segments();
+    assert_eq!(
+        verify_continuation(segments, &initial_state, &final_state),
+        Ok(())
+    );
+
+    // The continuation does not prove an execution starting from another
+    // state, or ending in another state
+    assert_eq!(
+        verify_continuation(segments, &final_state, &final_state),
+        Err(ContinuationError::UnexpectedInitialState)
+    );
+    assert_eq!(
+        verify_continuation(segments, &initial_state, &initial_state),
+        Err(ContinuationError::UnexpectedFinalState)
+    );
+    // In particular, a prefix of the continuation does not prove the whole
+    // execution
+    assert_eq!(
+        verify_continuation(&segments[..1], &initial_state, &final_state),
+        Err(ContinuationError::UnexpectedFinalState)
+    );
+
+    assert_eq!(
+        verify_continuation(&[], &initial_state, &final_state),
+        Err(ContinuationError::Empty)
+    );
+}
+
+#[test]
+fn test_riscv32im_continuation_rejects_broken_link() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let (first_public_inputs, first_proof) =
+        prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+    // Skip some instructions between the two segments
+    let _ = prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+    let (third_public_inputs, third_proof) =
+        prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+
+    let mut continuation = Continuation::new(riscv_witness::MAX_ACC);
+    continuation.push(first_public_inputs, first_proof).unwrap();
+    assert_eq!(
+        continuation.push(third_public_inputs, third_proof),
+        Err(ContinuationError::BrokenLink(1))
+    );
+    assert_eq!(continuation.len(), 1);
+}
+
+#[test]
+fn test_riscv32im_continuation_rejects_swapped_or_skipped_segments() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let initial_state = env.to_state().commitment();
+    let segments = (0..3)
+        .map(|_| {
+            let (public_inputs, proof) =
+                prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+            Segment {
+                public_inputs,
+                proof,
+            }
+        })
+        .collect::<Vec<_>>();
+    let final_state = env.to_state().commitment();
+    let verify_continuation = |segments: &[&Segment<Vesta>]| {
+        continuation::verify::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+            domain,
+            &srs,
+            &constraints,
+            &ColumnLayout::RISCV32IM,
+            segments.iter().copied(),
+            &initial_state,
+            &final_state,
+        )
+    };
+    assert_eq!(
+        verify_continuation(&[&segments[0], &segments[1], &segments[2]]),
+        Ok(())
+    );
+    assert_eq!(
+        verify_continuation(&[&segments[0], &segments[2], &segments[1]]),
+        Err(ContinuationError::BrokenLink(1))
+    );
+
+    // The state commitments are not bound to the witness, so a segment can be
+    // claimed to start from any state: the instruction counter still reveals
+    // that the second segment is skipped, or that the first one is proven
+    // twice.
+    let mut segments = segments.into_iter();
+    let first = segments.next().unwrap();
+    let third = segments.nth(1).unwrap();
+    let relabel = |public_inputs: PublicInputs, proof| Segment {
+        public_inputs: PublicInputs {
+            initial_state: first.public_inputs.final_state,
+            ..public_inputs
+        },
+        proof,
+    };
+    let skipping = relabel(third.public_inputs, third.proof);
+    let (public_inputs, proof) = prove_riscv32im_segment(
+        &mut riscv32im_env("add_sub_swap"),
+        4,
+        domain,
+        &srs,
+        &constraints,
+    );
+    let repeating = relabel(public_inputs, proof);
+    for segment in [&skipping, &repeating] {
+        assert_eq!(
+            verify_continuation(&[&first, segment]),
+            Err(ContinuationError::NonConsecutive(1))
+        );
+    }
+    let mut continuation = Continuation::new(riscv_witness::MAX_ACC);
+    continuation.push(first.public_inputs, first.proof).unwrap();
+    for segment in [skipping, repeating] {
+        assert_eq!(
+            continuation.push(segment.public_inputs, segment.proof),
+            Err(ContinuationError::NonConsecutive(1))
+        );
+    }
+    assert_eq!(continuation.len(), 1);
+}
+
+#[test]
+fn test_riscv32im_continuation_resumed_from_snapshot() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let initial_state = env.to_state().commitment();
+    let mut continuation = Continuation::new(riscv_witness::MAX_ACC);
+    let (public_inputs, proof) = prove_riscv32im_segment(&mut env, 6, domain, &srs, &constraints);
+    continuation.push(public_inputs, proof).unwrap();
+
+    // Resume the execution from a snapshot of the state, as written by
+    // `--snapshot-state-at`
+    let snapshot = serde_json::to_string(&env.to_state()).unwrap();
+    let state: State = serde_json::from_str(&snapshot).unwrap();
+    let mut resumed_env = riscv_witness::Env::<Fp, NullPreImageOracle>::create(
+        riscv32im::PAGE_SIZE as usize,
+        state,
+        NullPreImageOracle,
+    );
+    let mut resumed_continuation = Continuation::new(riscv_witness::MAX_ACC);
+    while !resumed_env.halt {
+        let (public_inputs, proof) =
+            prove_riscv32im_segment(&mut resumed_env, 6, domain, &srs, &constraints);
+        resumed_continuation.push(public_inputs, proof).unwrap();
+    }
+
+    continuation.append(resumed_continuation).unwrap();
+    let verif = continuation::verify::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+        domain,
+        &srs,
+        &constraints,
+        &ColumnLayout::RISCV32IM,
+        continuation.segments(),
+        &initial_state,
+        &resumed_env.to_state().commitment(),
+    );
+    assert_eq!(verif, Ok(()));
+}
+
+#[test]
+fn test_riscv32im_continuation_serialization() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let initial_state = env.to_state().commitment();
+    let mut continuation = Continuation::new(riscv_witness::MAX_ACC);
+    while !env.halt {
+        let (public_inputs, proof) =
+            prove_riscv32im_segment(&mut env, 6, domain, &srs, &constraints);
+        continuation.push(public_inputs, proof).unwrap();
+    }
+    let final_state = env.to_state().commitment();
+
+    let bytes = rmp_serde::to_vec(&continuation).unwrap();
+    let deserialized: Continuation<Vesta> = rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(deserialized.len(), continuation.len());
+    assert_eq!(
+        deserialized.final_statement(),
+        continuation.final_statement()
+    );
+    let verif = continuation::verify::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+        domain,
+        &srs,
+        &constraints,
+        &ColumnLayout::RISCV32IM,
+        deserialized.segments(),
+        &initial_state,
+        &final_state,
+    );
+    assert_eq!(verif, Ok(()));
+}
+
+#[test]
+fn test_riscv32im_segments_streamed() {
+    let domain_size = 1 << 8;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = riscv_constraints::get_all_constraints::<Fp>();
+
+    let mut env = riscv32im_env("add_sub_swap");
+    let initial_state = env.to_state().commitment();
+    let mut writer = SegmentWriter::new(vec![], riscv_witness::MAX_ACC);
+    while !env.halt {
+        let (public_inputs, proof) =
+            prove_riscv32im_segment(&mut env, 4, domain, &srs, &constraints);
+        writer.push(public_inputs, proof).unwrap();
+    }
+    let num_segments = writer.len();
+    assert!(num_segments > 1);
+
+    // A segment which does not start from the last final state is not
+    // written
+    let (public_inputs, proof) = prove_riscv32im_segment(
+        &mut riscv32im_env("add_sub_swap"),
+        4,
+        domain,
+        &srs,
+        &constraints,
+    );
+    assert_eq!(
+        writer.push(public_inputs, proof),
+        Err(ContinuationError::BrokenLink(num_segments))
+    );
+    let bytes = writer.finish().unwrap();
+
+    let segments = SegmentReader::<Vesta, _>::new(bytes.as_slice())
+        .map(|segment| segment.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(segments.len(), num_segments);
+
+    let verif = continuation::verify::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+        domain,
+        &srs,
+        &constraints,
+        &ColumnLayout::RISCV32IM,
+        SegmentReader::<Vesta, _>::new(bytes.as_slice()).map(|segment| segment.unwrap()),
+        &initial_state,
+        &env.to_state().commitment(),
+    );
+    assert_eq!(verif, Ok(()));
+
+    // A truncated stream is reported as an error
+    let mut truncated = SegmentReader::<Vesta, _>::new(&bytes[..bytes.len() - 1]);
+    assert!(truncated.nth(num_segments - 1).unwrap().is_err());
+}
+
+/// A MIPS environment running an infinite loop incrementing a counter stored
+/// in memory.
+fn mips_counter_env() -> mips_witness::Env<Fp, BufferingPreImageOracle<NullPreImageOracle>> {
+    let program: [u32; 5] = [
+        0x25080001, // addiu $t0, $t0, 1
+        0xAC080800, // sw $t0, 0x800($zero)
+        0x8C090800, // lw $t1, 0x800($zero)
+        0x08000000, // j 0
+        0x00000000, // nop
+    ];
+    let mut data: Vec<u8> = program.iter().flat_map(|word| word.to_be_bytes()).collect();
+    data.resize(PAGE_SIZE as usize, 0);
+    let state = State {
+        memory: vec![Page { index: 0, data }],
+        preimage_key: [0; 32],
+        preimage_offset: 0,
+        pc: 0,
+        next_pc: 4,
+        lo: 0,
+        hi: 0,
+        heap: 0x1000_0000,
+        exit: 0,
+        exited: false,
+        step: 0,
+        registers: [0; 32],
+        last_hint: None,
+        preimage: None,
+    };
+    mips_witness::Env::create(
+        PAGE_SIZE as usize,
+        state,
+        BufferingPreImageOracle::create(NullPreImageOracle),
+    )
+}
+
+#[test]
+fn test_mips_chunk_replay_matches_sequential_witness() {
+    let domain_size = 64;
+    let steps = 40;
+    let seed = [42; 32];
+    let configuration = VmConfiguration::default();
+    let start = Start::create(0);
+
+    let mut env = mips_counter_env();
+    // Some memory and registers are written before the chunk
+    for _ in 0..17 {
+        env.step(&configuration, &None, &start);
+    }
+    let chunk = Chunk {
+        snapshot: env.snapshot(),
+        preimages: env.preimage_oracle.take(),
+        steps,
+    };
+
+    let initial_state = env.to_state();
+    let mut rng = make_test_rng(Some(seed));
+    let mut expected = ProofInputs::<Vesta>::new(domain_size);
+    for _ in 0..steps {
+        env.step(&configuration, &None, &start);
+        push_mips_row(&env, &mut expected, &mut rng);
+    }
+    pad_mips(&env, &mut expected, &mut rng);
+    let final_state = env.to_state();
+
+    let replayed: ProofInputs<Vesta> = chunk.proof_inputs(
+        &replay_configuration(&configuration),
+        domain_size,
+        &mut make_test_rng(Some(seed)),
+    );
+
+    assert_eq!(
+        replayed.public_inputs,
+        PublicInputs::new(&initial_state, &final_state, mips_witness::MAX_ACC)
+    );
+    let (replayed, expected) = (replayed.evaluations, expected.evaluations);
+    assert_eq!(replayed.scratch, expected.scratch);
+    assert_eq!(replayed.scratch_inverse, expected.scratch_inverse);
+    assert_eq!(replayed.lookup_state, expected.lookup_state);
+    assert_eq!(replayed.instruction_counter, expected.instruction_counter);
+    assert_eq!(replayed.error, expected.error);
+    assert_eq!(replayed.selector, expected.selector);
+}
+
+#[test]
+fn test_mips_chunks_taken_without_witness() {
+    let domain_size = 64;
+    let steps = 40;
+    let seed = [7; 32];
+    let configuration = VmConfiguration::default();
+    let start = Start::create(0);
+
+    let mut env = mips_counter_env();
+    let mut execution = mips_counter_env();
+    execution.generate_witness = false;
+    for _ in 0..17 {
+        env.step(&configuration, &None, &start);
+        execution.step(&configuration, &None, &start);
+    }
+    assert!(execution.scratch_state.iter().all(|x| x.is_zero()));
+    assert!(execution.lookup_state.is_empty());
+    assert_eq!(
+        execution.to_state().commitment(),
+        env.to_state().commitment()
+    );
+
+    // The snapshot shares the memory of the execution, which is copied when
+    // the execution writes to it
+    let snapshot = execution.snapshot();
+    assert!(Arc::ptr_eq(&snapshot.memory[0].1, &execution.memory[0].1));
+    let memory = execution.memory.clone();
+    for _ in 0..steps {
+        execution.step(&configuration, &None, &start);
+    }
+    assert!(!Arc::ptr_eq(&snapshot.memory[0].1, &execution.memory[0].1));
+    assert_eq!(snapshot.memory, memory);
+
+    let chunk = Chunk {
+        snapshot,
+        preimages: execution.preimage_oracle.take(),
+        steps,
+    };
+    let replayed: ProofInputs<Vesta> = chunk.proof_inputs(
+        &replay_configuration(&configuration),
+        domain_size,
+        &mut make_test_rng(Some(seed)),
+    );
+    let chunk = Chunk {
+        snapshot: env.snapshot(),
+        preimages: env.preimage_oracle.take(),
+        steps,
+    };
+    let expected: ProofInputs<Vesta> = chunk.proof_inputs(
+        &replay_configuration(&configuration),
+        domain_size,
+        &mut make_test_rng(Some(seed)),
+    );
+    assert_eq!(replayed.public_inputs, expected.public_inputs);
+    assert_eq!(replayed.evaluations.scratch, expected.evaluations.scratch);
+    assert_eq!(
+        replayed.evaluations.instruction_counter,
+        expected.evaluations.instruction_counter
+    );
+    assert_eq!(replayed.evaluations.selector, expected.evaluations.selector);
+}
+
+#[test]
+fn test_mips_chunk_proof_verifies_against_mips_layout() {
+    let domain_size = 64;
+    let domain = EvaluationDomains::<Fp>::create(domain_size).unwrap();
+    let srs = SRS::create(domain_size);
+    let constraints = mips_constraints::get_all_constraints::<Fp>();
+    let configuration = VmConfiguration::default();
+    let start = Start::create(0);
+    let mut rng = make_test_rng(None);
+
+    let mut env = mips_counter_env();
+    for _ in 0..5 {
+        env.step(&configuration, &None, &start);
+    }
+    // The chunk is shorter than the domain, the last rows are padding
+    let chunk = Chunk {
+        snapshot: env.snapshot(),
+        preimages: env.preimage_oracle.take(),
+        steps: 23,
+    };
+    let proof_inputs: ProofInputs<Vesta> =
+        chunk.proof_inputs(&replay_configuration(&configuration), domain_size, &mut rng);
+    assert!(proof_inputs.evaluations.lookup_state.is_empty());
+    let public_inputs = proof_inputs.public_inputs;
+    let proof = prove::<Vesta, VestaBaseSponge, VestaScalarSponge, _>(
+        domain,
+        &srs,
+        proof_inputs,
+        &constraints,
+        &mut rng,
+    )
+    .unwrap();
+
+    let verify_against = |public_inputs: &PublicInputs| {
+        verify::<Vesta, VestaBaseSponge, VestaScalarSponge>(
+            domain,
+            &srs,
+            &constraints,
+            &ColumnLayout::MIPS,
+            public_inputs,
+            &proof,
+        )
+    };
+    assert!(verify_against(&public_inputs), "Verification fails");
+    let wrong_start = PublicInputs {
+        first_instruction_pointer: public_inputs.first_instruction_pointer + 4,
+        ..public_inputs
+    };
+    assert!(
+        !verify_against(&wrong_start),
+        "Verification succeeds against a wrong claimed state"
+    );
+}
+
 #[test]
 fn test_arkworks_batch_inversion_with_only_zeroes() {
     let input = vec![Fq::zero(); 8];
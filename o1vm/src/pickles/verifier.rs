@@ -6,7 +6,7 @@ use kimchi::{
     circuits::{
         berkeley_columns::BerkeleyChallenges,
         domains::EvaluationDomains,
-        expr::{ColumnEvaluations, Constants, Expr, ExprError, PolishToken},
+        expr::{ColumnEvaluations, Constants, ExprError, PolishToken},
         gate::CurrOrNext,
     },
     curve::KimchiCurve,
@@ -24,14 +24,14 @@ use poly_commitment::{
 };
 
 use super::{
-    column_env::{get_all_columns, RelationColumnType},
-    proof::{Proof, WitnessColumns},
+    column_env::RelationColumnType,
+    proof::{ColumnLayout, Proof, PublicInputs, WitnessColumns},
 };
-use crate::{interpreters::mips::column::N_MIPS_SEL_COLS, E};
+use crate::E;
 use kimchi_msm::columns::Column;
 
-type CommitmentColumns<G> = WitnessColumns<PolyComm<G>, [PolyComm<G>; N_MIPS_SEL_COLS]>;
-type EvaluationColumns<F> = WitnessColumns<F, [F; N_MIPS_SEL_COLS]>;
+type CommitmentColumns<G> = WitnessColumns<PolyComm<G>, Vec<PolyComm<G>>>;
+type EvaluationColumns<F> = WitnessColumns<F, Vec<F>>;
 
 struct ColumnEval<'a, G: AffineRepr> {
     commitment: &'a CommitmentColumns<G>,
@@ -62,6 +62,9 @@ impl<G: AffineRepr> ColumnEvaluations<G::ScalarField> for ColumnEval<'_, G> {
     }
 }
 
+/// Verify a proof of the constraints over the columns of `layout`, for the
+/// statement `public_inputs` claimed by the caller. A proof that does not have
+/// the expected shape is rejected.
 pub fn verify<
     G: KimchiCurve,
     EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
@@ -70,6 +73,8 @@ pub fn verify<
     domain: EvaluationDomains<G::ScalarField>,
     srs: &<OpeningProof<G> as OpenProof<G>>::SRS,
     constraints: &[E<G::ScalarField>],
+    layout: &ColumnLayout,
+    public_inputs: &PublicInputs,
     proof: &Proof<G>,
 ) -> bool
 where
@@ -84,15 +89,32 @@ where
         opening_proof,
     } = proof;
 
+    // The proof must commit to and open each column of the layout, and
+    // nothing else. The layout is never read from the proof.
+    if !commitments.has_layout(layout)
+        || !zeta_evaluations.has_layout(layout)
+        || !zeta_omega_evaluations.has_layout(layout)
+    {
+        return false;
+    }
+    let num_quotient_chunks = quotient_commitment.len();
+    if quotient_evaluations.zeta.len() != num_quotient_chunks
+        || quotient_evaluations.zeta_omega.len() != num_quotient_chunks
+    {
+        return false;
+    }
+
     ////////////////////////////////////////////////////////////////////////////
-    // TODO :  public inputs
+    // Absorbing the public inputs
     ////////////////////////////////////////////////////////////////////////////
 
+    let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
+    fq_sponge.absorb_fr(&public_inputs.to_field_elements::<G::ScalarField>());
+
     ////////////////////////////////////////////////////////////////////////////
     // Absorbing all the commitments to the columns
     ////////////////////////////////////////////////////////////////////////////
 
-    let mut fq_sponge = EFqSponge::new(G::other_curve_sponge_params());
     for comm in commitments.scratch.iter() {
         absorb_commitment(&mut fq_sponge, comm)
     }
@@ -197,25 +219,33 @@ where
         zk_rows: 0,
     };
 
-    let combined_expr =
-        Expr::combine_constraints(0..(constraints.len() as u32), constraints.to_vec());
+    let constraints: Vec<_> = constraints
+        .iter()
+        .cloned()
+        .chain(public_inputs.boundary_constraints())
+        .collect();
+    let combined_expr = super::combine_constraints(&constraints);
 
-    let numerator_zeta = PolishToken::evaluate(
+    // The constraints may refer to a column which is not in the layout
+    let Ok(numerator_zeta) = PolishToken::evaluate(
         combined_expr.to_polish().as_slice(),
         domain.d1,
         zeta,
         &column_eval,
         &constants,
         &challenges,
-    )
-    .unwrap_or_else(|_| panic!("Could not evaluate quotient polynomial at zeta"));
+    ) else {
+        return false;
+    };
 
     let v_chal = fr_sponge.challenge();
     let v = v_chal.to_field(endo_r);
     let u_chal = fr_sponge.challenge();
     let u = u_chal.to_field(endo_r);
 
-    let mut evaluations: Vec<_> = get_all_columns(column_eval.commitment.lookup_state.len())
+    let mut evaluations: Vec<_> = column_eval
+        .commitment
+        .get_all_columns()
         .into_iter()
         .map(|column| {
             let commitment = column_eval
@@ -1,13 +1,17 @@
 use crate::cannon::{
-    Hint, HostProgram, Preimage, HINT_CLIENT_READ_FD, HINT_CLIENT_WRITE_FD,
-    PREIMAGE_CLIENT_READ_FD, PREIMAGE_CLIENT_WRITE_FD,
+    Hint, HostProgram, Preimage, PreimageKey, PreimageKeyType, HINT_CLIENT_READ_FD,
+    HINT_CLIENT_WRITE_FD, PREIMAGE_CLIENT_READ_FD, PREIMAGE_CLIENT_WRITE_FD,
 };
 use command_fds::{CommandFdExt, FdMapping};
 use log::debug;
 use os_pipe::{PipeReader, PipeWriter};
+use sha3::{Digest, Keccak256};
 use std::{
-    io::{Read, Write},
+    collections::HashMap,
+    fs,
+    io::{self, Read, Write},
     os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    path::{Path, PathBuf},
     process::{Child, Command},
 };
 
@@ -234,6 +238,174 @@ impl PreImageOracleT for PreImageOracle {
     }
 }
 
+/// A preimage oracle serving the preimages from a local directory, without
+/// any host program. The directory uses the layout of the key-value store of
+/// op-program (`--datadir`): the preimage of the key `k` is hex-encoded in the
+/// file `<hex(k)>.txt`. All key types, including the local keys, are looked up
+/// in the directory.
+pub struct DirectoryPreImageOracle {
+    pub dir: PathBuf,
+}
+
+impl DirectoryPreImageOracle {
+    pub fn create<P: AsRef<Path>>(dir: P) -> DirectoryPreImageOracle {
+        DirectoryPreImageOracle {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Path of the file containing the preimage of `key`
+    pub fn path(&self, key: &[u8; 32]) -> PathBuf {
+        self.dir.join(format!("{}.txt", hex::encode(key)))
+    }
+
+    pub fn read(&self, key: &[u8; 32]) -> io::Result<Vec<u8>> {
+        let contents = fs::read_to_string(self.path(key))?;
+        hex::decode(contents.trim()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Store the preimage of `key` in the directory, creating the directory
+    /// if needed. The file is written atomically so that a concurrent reader
+    /// never sees a partial preimage.
+    pub fn write(&self, key: &[u8; 32], preimage: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let tmp_path = path.with_extension("txt.tmp");
+        fs::write(&tmp_path, hex::encode(preimage))?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+/// Check that a preimage matches a key committing to its content. The keys
+/// whose content is not a keccak256 digest are not checked.
+fn check_preimage(key: &[u8; 32], preimage: &[u8]) -> bool {
+    match PreimageKey(*key).key_type() {
+        Some(PreimageKeyType::Keccak256) => {
+            let digest: [u8; 32] = Keccak256::digest(preimage).into();
+            digest[1..] == key[1..]
+        }
+        _ => true,
+    }
+}
+
+impl PreImageOracleT for DirectoryPreImageOracle {
+    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        let preimage = self.read(&key).unwrap_or_else(|e| {
+            panic!(
+                "Could not read the preimage of key {} in {}: {e}",
+                hex::encode(key),
+                self.dir.display()
+            )
+        });
+        assert!(
+            check_preimage(&key, &preimage),
+            "The preimage stored in {} does not match its key",
+            self.path(&key).display()
+        );
+        Preimage::create(preimage)
+    }
+
+    // Hints are only used by a host program to fetch the data that will be
+    // requested next, all the data is already available in the directory.
+    fn hint(&mut self, hint: Hint) {
+        debug!("Ignoring hint {}", hex::encode(hint.get()));
+    }
+}
+
+/// A preimage oracle forwarding the requests to another oracle, usually a host
+/// program, and recording every preimage served in a directory. The run can
+/// then be replayed offline with a [DirectoryPreImageOracle] on the same
+/// directory.
+pub struct RecordingPreImageOracle<O: PreImageOracleT> {
+    pub oracle: O,
+    pub store: DirectoryPreImageOracle,
+}
+
+impl<O: PreImageOracleT> RecordingPreImageOracle<O> {
+    pub fn create<P: AsRef<Path>>(oracle: O, dir: P) -> RecordingPreImageOracle<O> {
+        RecordingPreImageOracle {
+            oracle,
+            store: DirectoryPreImageOracle::create(dir),
+        }
+    }
+}
+
+impl<O: PreImageOracleT> PreImageOracleT for RecordingPreImageOracle<O> {
+    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        let preimage = self.oracle.get_preimage(key).get();
+        self.store.write(&key, &preimage).unwrap_or_else(|e| {
+            panic!(
+                "Could not record the preimage of key {} in {}: {e}",
+                hex::encode(key),
+                self.store.dir.display()
+            )
+        });
+        Preimage::create(preimage)
+    }
+
+    fn hint(&mut self, hint: Hint) {
+        self.oracle.hint(hint)
+    }
+}
+
+/// A preimage oracle serving preimages kept in memory, for instance the ones
+/// collected by a [BufferingPreImageOracle] to replay a part of an execution.
+#[derive(Clone, Default)]
+pub struct InMemoryPreImageOracle {
+    pub preimages: HashMap<[u8; 32], Vec<u8>>,
+}
+
+impl PreImageOracleT for InMemoryPreImageOracle {
+    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        let preimage = self.preimages.get(&key).unwrap_or_else(|| {
+            panic!(
+                "The preimage of key {} is not available in memory",
+                hex::encode(key)
+            )
+        });
+        Preimage::create(preimage.clone())
+    }
+
+    fn hint(&mut self, hint: Hint) {
+        debug!("Ignoring hint {}", hex::encode(hint.get()));
+    }
+}
+
+/// A preimage oracle forwarding the requests to another oracle and keeping
+/// the preimages served since the last call to
+/// [BufferingPreImageOracle::take], so that the corresponding part of the
+/// execution can be replayed with an [InMemoryPreImageOracle].
+pub struct BufferingPreImageOracle<O: PreImageOracleT> {
+    pub oracle: O,
+    pub buffer: InMemoryPreImageOracle,
+}
+
+impl<O: PreImageOracleT> BufferingPreImageOracle<O> {
+    pub fn create(oracle: O) -> BufferingPreImageOracle<O> {
+        BufferingPreImageOracle {
+            oracle,
+            buffer: InMemoryPreImageOracle::default(),
+        }
+    }
+
+    /// Return the preimages served since the last call and empty the buffer
+    pub fn take(&mut self) -> InMemoryPreImageOracle {
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+impl<O: PreImageOracleT> PreImageOracleT for BufferingPreImageOracle<O> {
+    fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+        let preimage = self.oracle.get_preimage(key).get();
+        self.buffer.preimages.insert(key, preimage.clone());
+        Preimage::create(preimage)
+    }
+
+    fn hint(&mut self, hint: Hint) {
+        self.oracle.hint(hint)
+    }
+}
+
 impl PreImageOracleT for Box<dyn PreImageOracleT> {
     fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
         self.as_mut().get_preimage(key)
@@ -247,6 +419,105 @@ impl PreImageOracleT for Box<dyn PreImageOracleT> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::Rng;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "o1vm-preimages-{:016x}",
+            rand::thread_rng().gen::<u64>()
+        ))
+    }
+
+    fn keccak_key(preimage: &[u8]) -> [u8; 32] {
+        let mut key: [u8; 32] = Keccak256::digest(preimage).into();
+        key[0] = PreimageKeyType::Keccak256 as u8;
+        key
+    }
+
+    /// An in-memory oracle standing for a host program
+    struct MapPreImageOracle {
+        preimages: HashMap<[u8; 32], Vec<u8>>,
+        hints: Vec<Vec<u8>>,
+    }
+
+    impl PreImageOracleT for MapPreImageOracle {
+        fn get_preimage(&mut self, key: [u8; 32]) -> Preimage {
+            Preimage::create(self.preimages[&key].clone())
+        }
+
+        fn hint(&mut self, hint: Hint) {
+            self.hints.push(hint.get())
+        }
+    }
+
+    #[test]
+    fn test_directory_oracle() {
+        let dir = temp_dir();
+        let mut oracle = DirectoryPreImageOracle::create(&dir);
+
+        let preimage = b"o1vm preimage".to_vec();
+        let key = keccak_key(&preimage);
+        oracle.write(&key, &preimage).unwrap();
+        let mut local_key = [0u8; 32];
+        local_key[0] = PreimageKeyType::Local as u8;
+        local_key[31] = 1;
+        oracle.write(&local_key, &[42; 8]).unwrap();
+
+        // Same layout as the key-value store of op-program
+        assert_eq!(
+            fs::read_to_string(dir.join(format!("{}.txt", hex::encode(key)))).unwrap(),
+            hex::encode(&preimage)
+        );
+        assert_eq!(oracle.get_preimage(key).get(), preimage);
+        assert_eq!(oracle.get_preimage(local_key).get(), vec![42; 8]);
+        oracle.hint(Hint::create(b"l1-block-header 0x00".to_vec()));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match its key")]
+    fn test_directory_oracle_rejects_wrong_keccak_preimage() {
+        let dir = temp_dir();
+        let mut oracle = DirectoryPreImageOracle::create(&dir);
+        let key = keccak_key(b"o1vm preimage");
+        oracle.write(&key, b"another preimage").unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            oracle.get_preimage(key);
+        }));
+        fs::remove_dir_all(dir).unwrap();
+        std::panic::resume_unwind(result.unwrap_err())
+    }
+
+    #[test]
+    fn test_recording_oracle_replay() {
+        let dir = temp_dir();
+        let preimages: HashMap<[u8; 32], Vec<u8>> = (0..4u8)
+            .map(|i| {
+                let preimage = vec![i; 100 * i as usize];
+                (keccak_key(&preimage), preimage)
+            })
+            .collect();
+        let host = MapPreImageOracle {
+            preimages: preimages.clone(),
+            hints: vec![],
+        };
+        let mut recording = RecordingPreImageOracle::create(host, &dir);
+
+        recording.hint(Hint::create(b"hint".to_vec()));
+        for (key, preimage) in preimages.iter() {
+            assert_eq!(&recording.get_preimage(*key).get(), preimage);
+        }
+        assert_eq!(recording.oracle.hints, vec![b"hint".to_vec()]);
+
+        // Replay the run offline
+        let mut replay = DirectoryPreImageOracle::create(&dir);
+        for (key, preimage) in preimages.iter() {
+            assert_eq!(&replay.get_preimage(*key).get(), preimage);
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
 
     // Test that bidirectional channels work as expected
     // That is, after creating a pair (c0, c1)
@@ -1,4 +1,5 @@
-use o1vm::elf_loader::Architecture;
+use elf::{endian::LittleEndian, ElfBytes};
+use o1vm::{cannon::PAGE_SIZE, elf_loader::Architecture};
 
 #[test]
 // This test is used to check that the elf loader is working correctly.
@@ -19,3 +20,34 @@ fn test_correctly_parsing_elf() {
     // Which is the 17th
     assert_eq!(state.memory[0].index, 17);
 }
+
+#[test]
+// The text section must be loaded in memory up to its last byte, whether it
+// fits in one page or not.
+fn test_text_section_is_fully_loaded() {
+    let curr_dir = std::env::current_dir().unwrap();
+    let programs = curr_dir.join(std::path::PathBuf::from("resources/programs/riscv32im/bin"));
+    let page_size = PAGE_SIZE as usize;
+    for entry in std::fs::read_dir(programs).unwrap() {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        let file = ElfBytes::<LittleEndian>::minimal_parse(&data).unwrap();
+        let text_section = file.section_header_by_name(".text").unwrap().unwrap();
+        let (text_section_data, _) = file.section_data(&text_section).unwrap();
+
+        let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
+        for (offset, byte) in text_section_data.iter().enumerate() {
+            let address = text_section.sh_addr as usize + offset;
+            let page = state
+                .memory
+                .iter()
+                .find(|page| page.index as usize == address / page_size)
+                .unwrap_or_else(|| panic!("{path:?}: no page for address {address:#x}"));
+            assert_eq!(
+                page.data[address % page_size],
+                *byte,
+                "{path:?}: wrong byte at address {address:#x}"
+            );
+        }
+    }
+}
@@ -7,6 +7,7 @@ use o1vm::{
         witness::Env,
         PAGE_SIZE,
     },
+    preimage_oracle::NullPreImageOracle,
 };
 
 #[test]
@@ -16,7 +17,11 @@ fn test_registers_indexed_by_alias() {
         "resources/programs/riscv32im/bin/sll",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     assert_eq!(witness.registers[Ip], 65652);
     assert_eq!(witness.registers[NextIp], 65656);
@@ -47,7 +52,11 @@ fn test_no_action() {
         "resources/programs/riscv32im/bin/no-action",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
     // This is the output we get by running objdump -d no-action
     assert_eq!(witness.registers.current_instruction_pointer, 69844);
     assert_eq!(witness.registers.next_instruction_pointer, 69848);
@@ -74,7 +83,11 @@ fn test_fibonacci_7() {
         "resources/programs/riscv32im/bin/fibonacci-7",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
     // This is the output we get by running objdump -d fibonacci-7
     assert_eq!(witness.registers.current_instruction_pointer, 69932);
     assert_eq!(witness.registers.next_instruction_pointer, 69936);
@@ -96,7 +109,11 @@ fn test_sll() {
         "resources/programs/riscv32im/bin/sll",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -113,7 +130,11 @@ fn test_addi() {
         "resources/programs/riscv32im/bin/addi",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -129,7 +150,11 @@ fn test_add_1() {
         "resources/programs/riscv32im/bin/add_1",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -145,7 +170,11 @@ fn test_add_2() {
         "resources/programs/riscv32im/bin/add_2",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -167,7 +196,11 @@ fn test_add_overflow() {
         "resources/programs/riscv32im/bin/add_overflow",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -187,7 +220,11 @@ fn test_addi_negative() {
         "resources/programs/riscv32im/bin/addi_negative",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -207,7 +244,11 @@ fn test_add_sub_swap() {
         "resources/programs/riscv32im/bin/add_sub_swap",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -225,7 +266,11 @@ fn test_addi_overflow() {
         "resources/programs/riscv32im/bin/addi_overflow",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -243,7 +288,11 @@ fn test_addi_boundary_immediate() {
         "resources/programs/riscv32im/bin/addi_boundary_immediate",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -260,7 +309,11 @@ fn test_sub() {
         "resources/programs/riscv32im/bin/sub",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -277,7 +330,11 @@ fn test_sub_2() {
         "resources/programs/riscv32im/bin/sub_2",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -294,7 +351,11 @@ fn test_sub_3() {
         "resources/programs/riscv32im/bin/sub_3",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -311,7 +372,11 @@ fn test_xor() {
         "resources/programs/riscv32im/bin/xor",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -329,7 +394,11 @@ fn test_and() {
         "resources/programs/riscv32im/bin/and",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -347,7 +416,11 @@ fn test_slt() {
         "resources/programs/riscv32im/bin/slt",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -366,7 +439,11 @@ fn test_div_by_zero() {
         "resources/programs/riscv32im/bin/div_by_zero",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -381,7 +458,11 @@ fn test_divu_by_zero() {
         "resources/programs/riscv32im/bin/divu_by_zero",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -396,7 +477,11 @@ fn test_rem_by_zero() {
         "resources/programs/riscv32im/bin/rem_by_zero",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -411,7 +496,11 @@ fn test_remu_by_zero() {
         "resources/programs/riscv32im/bin/remu_by_zero",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -425,7 +514,11 @@ fn test_mul_overflow() {
         "resources/programs/riscv32im/bin/mul_overflow",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
@@ -441,7 +534,11 @@ fn test_jal() {
         "resources/programs/riscv32im/bin/jal",
     ));
     let state = o1vm::elf_loader::parse_elf(Architecture::RiscV32, &path).unwrap();
-    let mut witness = Env::<Fp>::create(PAGE_SIZE.try_into().unwrap(), state);
+    let mut witness = Env::<Fp, NullPreImageOracle>::create(
+        PAGE_SIZE.try_into().unwrap(),
+        state,
+        NullPreImageOracle,
+    );
 
     while !witness.halt {
         witness.step();
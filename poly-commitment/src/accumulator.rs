@@ -0,0 +1,308 @@
+//! This module implements the accumulation of inner product argument opening
+//! proofs, as described in [Halo](https://eprint.iacr.org/2019/1021).
+//!
+//! Checking an opening proof requires a multi-scalar multiplication of the
+//! size of the SRS, to check that the final folded base `sg` of the proof is
+//! the commitment to the polynomial whose coefficients are
+//! [`b_poly_coefficients`] of the challenges of the proof. Everything else can
+//! be checked with a multi-scalar multiplication of logarithmic size.
+//!
+//! [`SRS::succinct_verify`] does the latter, and returns an [`Accumulator`]
+//! deferring the expensive check. Many accumulators can then be
+//! - checked at once with a single multi-scalar multiplication of the size of
+//!   the SRS, see [`SRS::check_accumulators`], or
+//! - folded into a single accumulator with [`SRS::accumulate`], which proves
+//!   the folding with an opening proof. The folding is checked succinctly by
+//!   [`SRS::verify_accumulation`], which returns the new accumulator.
+
+use crate::{
+    commitment::{
+        b_poly, b_poly_coefficients, squeeze_challenge, BatchEvaluationProof, CommitmentCurve,
+        EndoCurve, Evaluation,
+    },
+    ipa::{endos, OpeningProof, SRS},
+    utils::DensePolynomialOrEvaluations,
+    PolyComm,
+};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Radix2EvaluationDomain as D};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use mina_poseidon::FqSponge;
+use o1_utils::math;
+use rand::{CryptoRng, RngCore};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+/// A deferred check that `sg` is the commitment to the polynomial
+/// `b_poly(chals, X)`, with no blinding factor.
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound = "G: CanonicalDeserialize + CanonicalSerialize")]
+pub struct Accumulator<G: AffineRepr> {
+    /// The challenges of an opening proof
+    #[serde_as(as = "Vec<o1_utils::serialization::SerdeAs>")]
+    pub chals: Vec<G::ScalarField>,
+    /// The final folded base of the opening proof
+    #[serde_as(as = "o1_utils::serialization::SerdeAs")]
+    pub sg: G,
+}
+
+impl<G: AffineRepr> Accumulator<G> {
+    /// Evaluates the polynomial committed to by `sg` at `x`.
+    pub fn evaluate(&self, x: G::ScalarField) -> G::ScalarField {
+        b_poly(&self.chals, x)
+    }
+
+    /// Returns the coefficients of the polynomial committed to by `sg`.
+    pub fn coefficients(&self) -> Vec<G::ScalarField> {
+        b_poly_coefficients(&self.chals)
+    }
+}
+
+/// Computes `sum_i scalars_i points_i`.
+fn msm<G: CommitmentCurve>(points: &[G], scalars: &[G::ScalarField]) -> G::Group {
+    let scalars: Vec<_> = scalars.par_iter().map(|s| s.into_bigint()).collect();
+    G::Group::msm_bigint(points, &scalars)
+}
+
+impl<G: CommitmentCurve> SRS<G>
+where
+    G::BaseField: PrimeField,
+{
+    /// The number of rounds of the opening proofs for this SRS.
+    fn rounds(&self) -> usize {
+        math::ceil_log2(self.g.len())
+    }
+
+    /// Verifies a batch of opening proofs, except for the final folded base
+    /// of each proof, and returns the accumulators deferring these checks,
+    /// in the order of the batch.
+    /// Returns `None` if the verification fails.
+    pub fn succinct_verify<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        batch: &mut [BatchEvaluationProof<G, EFqSponge, OpeningProof<G>>],
+        rng: &mut RNG,
+    ) -> Option<Vec<Accumulator<G>>>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+    {
+        let rounds = self.rounds();
+        if batch.iter().any(|proof| proof.opening.lr.len() != rounds) {
+            return None;
+        }
+
+        let rand_base = G::ScalarField::rand(rng);
+        let mut rand_base_i = G::ScalarField::one();
+        let mut scalars = vec![];
+        let mut points = vec![];
+        let mut accumulators = Vec::with_capacity(batch.len());
+        for proof in batch.iter_mut() {
+            let chals = self.push_succinct_check_terms(
+                group_map,
+                proof,
+                rand_base_i,
+                &mut scalars,
+                &mut points,
+            );
+            accumulators.push(Accumulator {
+                chals,
+                sg: proof.opening.sg,
+            });
+            rand_base_i *= &rand_base;
+        }
+
+        msm(&points, &scalars).is_zero().then_some(accumulators)
+    }
+
+    /// Checks the deferred part of the verification of opening proofs, using
+    /// a single multi-scalar multiplication of the size of the SRS.
+    pub fn check_accumulators<RNG>(&self, accumulators: &[Accumulator<G>], rng: &mut RNG) -> bool
+    where
+        RNG: RngCore + CryptoRng,
+    {
+        let padded_length = 1 << self.rounds();
+        if accumulators
+            .iter()
+            .any(|acc| 1 << acc.chals.len() != padded_length)
+        {
+            return false;
+        }
+
+        // sum_i rand_base^i sg_i == < sum_i rand_base^i s_i, self.g >
+        let rand_base = G::ScalarField::rand(rng);
+        let mut rand_base_i = G::ScalarField::one();
+        let mut points = self.g.clone();
+        points.resize(padded_length, G::zero());
+        let mut scalars = vec![G::ScalarField::zero(); padded_length];
+        for acc in accumulators {
+            let s = acc.coefficients();
+            scalars
+                .par_iter_mut()
+                .zip(s.par_iter())
+                .for_each(|(scalar, s)| *scalar += rand_base_i * s);
+            points.push(acc.sg);
+            scalars.push(-rand_base_i);
+            rand_base_i *= &rand_base;
+        }
+
+        msm(&points, &scalars).is_zero()
+    }
+
+    /// Absorbs the accumulators, and squeezes the challenges used to combine
+    /// their polynomials and to evaluate the combination.
+    fn accumulation_challenges<EFqSponge>(
+        accumulators: &[Accumulator<G>],
+        sponge: &mut EFqSponge,
+    ) -> (G::ScalarField, G::ScalarField)
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let (_, endo_r) = endos::<G>();
+        for acc in accumulators {
+            sponge.absorb_g(&[acc.sg]);
+            sponge.absorb_fr(&acc.chals);
+        }
+        let polyscale = squeeze_challenge(&endo_r, sponge);
+        let evaluation_point = squeeze_challenge(&endo_r, sponge);
+        (polyscale, evaluation_point)
+    }
+
+    /// Folds accumulators into a single one.
+    ///
+    /// The polynomials of the accumulators are combined with a challenge
+    /// `r`, and the combination is opened at a challenge `z`. The returned
+    /// opening proof can be checked by [`Self::verify_accumulation`], given
+    /// the same accumulators and sponge, which returns the new accumulator.
+    /// Returns `None` if the folding cannot be verified, which means that the
+    /// polynomial of an accumulator is not the one committed to by its `sg`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `accumulators` is empty, or if an accumulator does not
+    /// come from an opening proof for this SRS.
+    pub fn accumulate<EFqSponge, RNG>(
+        &self,
+        group_map: &G::Map,
+        accumulators: &[Accumulator<G>],
+        sponge: EFqSponge,
+        rng: &mut RNG,
+    ) -> Option<(Accumulator<G>, OpeningProof<G>)>
+    where
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        RNG: RngCore + CryptoRng,
+        G: EndoCurve,
+    {
+        assert!(!accumulators.is_empty(), "no accumulator to fold");
+        let padded_length = 1 << self.rounds();
+        assert!(
+            accumulators
+                .iter()
+                .all(|acc| 1 << acc.chals.len() == padded_length),
+            "the accumulators do not match the SRS"
+        );
+
+        let mut transcript = sponge.clone();
+        let (polyscale, evaluation_point) =
+            Self::accumulation_challenges(accumulators, &mut transcript);
+
+        let mut coeffs = vec![G::ScalarField::zero(); padded_length];
+        let mut polyscale_i = G::ScalarField::one();
+        for acc in accumulators {
+            let s = acc.coefficients();
+            coeffs
+                .par_iter_mut()
+                .zip(s.par_iter())
+                .for_each(|(c, s)| *c += polyscale_i * s);
+            polyscale_i *= &polyscale;
+        }
+        let poly = DensePolynomial::from_coefficients_vec(coeffs);
+
+        // the commitments of the accumulators are not blinded
+        let polys = vec![(
+            DensePolynomialOrEvaluations::<_, D<_>>::DensePolynomial(&poly),
+            PolyComm::new(vec![G::ScalarField::zero()]),
+        )];
+        let opening = self.open(
+            group_map,
+            &polys,
+            &[evaluation_point],
+            G::ScalarField::one(),
+            G::ScalarField::one(),
+            transcript,
+            rng,
+        );
+
+        self.verify_accumulation(group_map, accumulators, &opening, sponge)
+            .map(|accumulator| (accumulator, opening))
+    }
+
+    /// Checks succinctly that `opening` proves the folding of
+    /// `accumulators` (see [`Self::accumulate`]), and returns the folded
+    /// accumulator. Checking the folded accumulator with
+    /// [`Self::check_accumulators`] checks all the accumulators.
+    /// Returns `None` if the verification fails.
+    pub fn verify_accumulation<EFqSponge>(
+        &self,
+        group_map: &G::Map,
+        accumulators: &[Accumulator<G>],
+        opening: &OpeningProof<G>,
+        mut sponge: EFqSponge,
+    ) -> Option<Accumulator<G>>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let rounds = self.rounds();
+        if accumulators.is_empty()
+            || opening.lr.len() != rounds
+            || accumulators.iter().any(|acc| acc.chals.len() != rounds)
+        {
+            return None;
+        }
+
+        let (polyscale, evaluation_point) =
+            Self::accumulation_challenges(accumulators, &mut sponge);
+
+        // the commitment to the combined polynomial, and its evaluation
+        let mut scales = Vec::with_capacity(accumulators.len());
+        let mut polyscale_i = G::ScalarField::one();
+        let mut evaluation = G::ScalarField::zero();
+        for acc in accumulators {
+            scales.push(polyscale_i);
+            evaluation += polyscale_i * acc.evaluate(evaluation_point);
+            polyscale_i *= &polyscale;
+        }
+        let sgs: Vec<_> = accumulators.iter().map(|acc| acc.sg).collect();
+        let commitment = msm(&sgs, &scales).into_affine();
+
+        let mut proof = BatchEvaluationProof {
+            sponge,
+            evaluations: vec![Evaluation {
+                commitment: PolyComm::new(vec![commitment]),
+                evaluations: vec![vec![evaluation]],
+            }],
+            evaluation_points: vec![evaluation_point],
+            polyscale: G::ScalarField::one(),
+            evalscale: G::ScalarField::one(),
+            opening,
+            combined_inner_product: evaluation,
+        };
+        let mut scalars = vec![];
+        let mut points = vec![];
+        let chals = self.push_succinct_check_terms(
+            group_map,
+            &mut proof,
+            G::ScalarField::one(),
+            &mut scalars,
+            &mut points,
+        );
+
+        msm(&points, &scalars).is_zero().then_some(Accumulator {
+            chals,
+            sg: opening.sg,
+        })
+    }
+}
@@ -153,8 +153,6 @@ impl<G: CommitmentCurve> SRS<G> {
 
         let padded_length = 1 << max_rounds;
 
-        let (_, endo_r) = endos::<G>();
-
         // TODO: This will need adjusting
         let padding = padded_length - nonzero_length;
         let mut points = vec![self.h];
@@ -171,61 +169,25 @@ impl<G: CommitmentCurve> SRS<G> {
         let mut rand_base_i = G::ScalarField::one();
         let mut sg_rand_base_i = G::ScalarField::one();
 
-        for BatchEvaluationProof {
-            sponge,
-            evaluation_points,
-            polyscale,
-            evalscale,
-            evaluations,
-            opening,
-            combined_inner_product,
-        } in batch.iter_mut()
-        {
-            sponge.absorb_fr(&[shift_scalar::<G>(*combined_inner_product)]);
-
-            let u_base: G = {
-                let t = sponge.challenge_fq();
-                let (x, y) = group_map.to_group(t);
-                G::of_coordinates(x, y)
-            };
-
-            let Challenges { chal, chal_inv } = opening.challenges::<EFqSponge>(&endo_r, sponge);
-
-            sponge.absorb_g(&[opening.delta]);
-            let c = ScalarChallenge(sponge.challenge()).to_field(&endo_r);
-
-            // < s, sum_i evalscale^i pows(evaluation_point[i]) >
-            // ==
-            // sum_i evalscale^i < s, pows(evaluation_point[i]) >
-            let b0 = {
-                let mut scale = G::ScalarField::one();
-                let mut res = G::ScalarField::zero();
-                for &e in evaluation_points.iter() {
-                    let term = b_poly(&chal, e);
-                    res += &(scale * term);
-                    scale *= *evalscale;
-                }
-                res
-            };
-
-            let s = b_poly_coefficients(&chal);
-
-            let neg_rand_base_i = -rand_base_i;
-
-            // TERM
-            // - rand_base_i z1 G
-            //
-            // we also add -sg_rand_base_i * G to check correctness of sg.
-            points.push(opening.sg);
-            scalars.push(neg_rand_base_i * opening.z1 - sg_rand_base_i);
+        for proof in batch.iter_mut() {
+            let chal = self.push_succinct_check_terms(
+                group_map,
+                proof,
+                rand_base_i,
+                &mut scalars,
+                &mut points,
+            );
 
-            // Here we add
+            // We add -sg_rand_base_i * sg and
             // sg_rand_base_i * ( < s, self.g > )
             // =
             // < sg_rand_base_i s, self.g >
             //
             // to check correctness of the sg component.
+            points.push(proof.opening.sg);
+            scalars.push(-sg_rand_base_i);
             {
+                let s = b_poly_coefficients(&chal);
                 let terms: Vec<_> = s.par_iter().map(|s| sg_rand_base_i * s).collect();
 
                 for (i, term) in terms.iter().enumerate() {
@@ -233,47 +195,6 @@ impl<G: CommitmentCurve> SRS<G> {
                 }
             }
 
-            // TERM
-            // - rand_base_i * z2 * H
-            scalars[0] -= &(rand_base_i * opening.z2);
-
-            // TERM
-            // -rand_base_i * (z1 * b0 * U)
-            scalars.push(neg_rand_base_i * (opening.z1 * b0));
-            points.push(u_base);
-
-            // TERM
-            // rand_base_i c_i Q_i
-            // = rand_base_i c_i
-            //   (sum_j (chal_invs[j] L_j + chals[j] R_j) + P_prime)
-            // where P_prime = combined commitment + combined_inner_product * U
-            let rand_base_i_c_i = c * rand_base_i;
-            for ((l, r), (u_inv, u)) in opening.lr.iter().zip(chal_inv.iter().zip(chal.iter())) {
-                points.push(*l);
-                scalars.push(rand_base_i_c_i * u_inv);
-
-                points.push(*r);
-                scalars.push(rand_base_i_c_i * u);
-            }
-
-            // TERM
-            // sum_j evalscale^j (sum_i polyscale^i f_i) (elm_j)
-            // == sum_j sum_i evalscale^j polyscale^i f_i(elm_j)
-            // == sum_i polyscale^i sum_j evalscale^j f_i(elm_j)
-            combine_commitments(
-                evaluations,
-                &mut scalars,
-                &mut points,
-                *polyscale,
-                rand_base_i_c_i,
-            );
-
-            scalars.push(rand_base_i_c_i * *combined_inner_product);
-            points.push(u_base);
-
-            scalars.push(rand_base_i);
-            points.push(opening.delta);
-
             rand_base_i *= &rand_base;
             sg_rand_base_i *= &sg_rand_base;
         }
@@ -300,6 +221,112 @@ impl<G: CommitmentCurve> SRS<G> {
         msm_res == G::Group::zero()
     }
 
+    /// Adds to the multi-scalar multiplication `(scalars, points)` the terms
+    /// of the verification equation of an opening proof which do not involve
+    /// the bases `self.g`, scaled by `rand_base`, and returns the challenges
+    /// of the opening proof.
+    ///
+    /// The terms sum to zero if the proof is valid and if `sg` is the
+    /// commitment to the polynomial whose coefficients are
+    /// `b_poly_coefficients(chal)`. Checking the latter requires a
+    /// multi-scalar multiplication of the size of the SRS, which can be
+    /// deferred (see [`crate::accumulator`]).
+    pub(crate) fn push_succinct_check_terms<EFqSponge>(
+        &self,
+        group_map: &G::Map,
+        proof: &mut BatchEvaluationProof<G, EFqSponge, OpeningProof<G>>,
+        rand_base_i: G::ScalarField,
+        scalars: &mut Vec<G::ScalarField>,
+        points: &mut Vec<G>,
+    ) -> Vec<G::ScalarField>
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+        G::BaseField: PrimeField,
+    {
+        let (_, endo_r) = endos::<G>();
+        let BatchEvaluationProof {
+            sponge,
+            evaluation_points,
+            polyscale,
+            evalscale,
+            evaluations,
+            opening,
+            combined_inner_product,
+        } = proof;
+
+        sponge.absorb_fr(&[shift_scalar::<G>(*combined_inner_product)]);
+
+        let u_base: G = {
+            let t = sponge.challenge_fq();
+            let (x, y) = group_map.to_group(t);
+            G::of_coordinates(x, y)
+        };
+
+        let Challenges { chal, chal_inv } = opening.challenges::<EFqSponge>(&endo_r, sponge);
+
+        sponge.absorb_g(&[opening.delta]);
+        let c = ScalarChallenge(sponge.challenge()).to_field(&endo_r);
+
+        // < s, sum_i evalscale^i pows(evaluation_point[i]) >
+        // ==
+        // sum_i evalscale^i < s, pows(evaluation_point[i]) >
+        let b0 = {
+            let mut scale = G::ScalarField::one();
+            let mut res = G::ScalarField::zero();
+            for &e in evaluation_points.iter() {
+                let term = b_poly(&chal, e);
+                res += &(scale * term);
+                scale *= *evalscale;
+            }
+            res
+        };
+
+        let neg_rand_base_i = -rand_base_i;
+
+        // TERM
+        // - rand_base_i z1 G
+        points.push(opening.sg);
+        scalars.push(neg_rand_base_i * opening.z1);
+
+        // TERM
+        // - rand_base_i * z2 * H
+        points.push(self.h);
+        scalars.push(neg_rand_base_i * opening.z2);
+
+        // TERM
+        // -rand_base_i * (z1 * b0 * U)
+        scalars.push(neg_rand_base_i * (opening.z1 * b0));
+        points.push(u_base);
+
+        // TERM
+        // rand_base_i c_i Q_i
+        // = rand_base_i c_i
+        //   (sum_j (chal_invs[j] L_j + chals[j] R_j) + P_prime)
+        // where P_prime = combined commitment + combined_inner_product * U
+        let rand_base_i_c_i = c * rand_base_i;
+        for ((l, r), (u_inv, u)) in opening.lr.iter().zip(chal_inv.iter().zip(chal.iter())) {
+            points.push(*l);
+            scalars.push(rand_base_i_c_i * u_inv);
+
+            points.push(*r);
+            scalars.push(rand_base_i_c_i * u);
+        }
+
+        // TERM
+        // sum_j evalscale^j (sum_i polyscale^i f_i) (elm_j)
+        // == sum_j sum_i evalscale^j polyscale^i f_i(elm_j)
+        // == sum_i polyscale^i sum_j evalscale^j f_i(elm_j)
+        combine_commitments(evaluations, scalars, points, *polyscale, rand_base_i_c_i);
+
+        scalars.push(rand_base_i_c_i * *combined_inner_product);
+        points.push(u_base);
+
+        scalars.push(rand_base_i);
+        points.push(opening.delta);
+
+        chal
+    }
+
     /// This function creates a trusted-setup SRS instance for circuits with
     /// number of rows up to `depth`.
     ///
@@ -959,6 +986,15 @@ impl<BaseField: PrimeField, G: AffineRepr<BaseField = BaseField> + CommitmentCur
     {
         srs.verify(group_map, batch, rng)
     }
+
+    /// Each round of the folding loop commits to `L` and `R`, over half of
+    /// the bases of the previous round and the bases `H` and `U`.
+    fn open_msms(srs: &Self::SRS, _num_points: usize) -> Vec<(usize, usize)> {
+        let rounds = math::ceil_log2(srs.g.len());
+        (1..=rounds)
+            .map(|round| (2, (1 << (rounds - round)) + 2))
+            .collect()
+    }
 }
 
 /// Commitment round challenges (endo mapped) and their inverses.
@@ -137,10 +137,21 @@ impl<
 {
     /// Create a trusted setup for the KZG protocol.
     /// The setup is created using a toxic waste `toxic_waste` and a depth
-    /// `depth`.
+    /// `depth`. The verifier SRS can check openings at up to 2 points.
     pub fn create_trusted_setup(toxic_waste: F, depth: usize) -> Self {
+        Self::create_trusted_setup_with_verifier_depth(toxic_waste, depth, 3)
+    }
+
+    /// Create a trusted setup for the KZG protocol, with a verifier SRS of
+    /// depth `verifier_depth`. The verifier can check openings at up to
+    /// `verifier_depth - 1` points.
+    pub fn create_trusted_setup_with_verifier_depth(
+        toxic_waste: F,
+        depth: usize,
+        verifier_depth: usize,
+    ) -> Self {
         let full_srs = unsafe { SRS::create_trusted_setup(toxic_waste, depth) };
-        let verifier_srs = unsafe { SRS::create_trusted_setup(toxic_waste, 3) };
+        let verifier_srs = unsafe { SRS::create_trusted_setup(toxic_waste, verifier_depth) };
         Self {
             full_srs,
             verifier_srs,
@@ -148,6 +159,15 @@ impl<
     }
 }
 
+impl<Pair: Pairing> PairingSRS<Pair> {
+    /// The maximum number of distinct evaluation points that can be checked
+    /// in a single opening: the divisor polynomial must fit in the verifier
+    /// SRS.
+    pub fn max_evaluation_points(&self) -> usize {
+        self.verifier_srs.g.len().saturating_sub(1)
+    }
+}
+
 impl<Pair: Pairing> Default for PairingSRS<Pair> {
     fn default() -> Self {
         Self {
@@ -226,6 +246,12 @@ impl<
         }
         true
     }
+
+    /// The only multi-scalar multiplication is the commitment to the
+    /// quotient by the vanishing polynomial of the evaluation points.
+    fn open_msms(srs: &Self::SRS, num_points: usize) -> Vec<(usize, usize)> {
+        vec![(1, srs.full_srs.g.len().saturating_sub(num_points))]
+    }
 }
 
 impl<
@@ -333,37 +359,66 @@ impl<
 }
 
 /// The polynomial that evaluates to each of `evals` for the respective `elm`s.
-/// For now, only works for 2 evaluations points.
 /// `elm` is the vector of evaluation points and `evals` is the vector of
-/// evaluations at those points.
+/// evaluations at those points. The points must be pairwise distinct.
 fn eval_polynomial<F: PrimeField>(elm: &[F], evals: &[F]) -> DensePolynomial<F> {
     assert_eq!(elm.len(), evals.len());
-    let (zeta, zeta_omega) = if elm.len() == 2 {
-        (elm[0], elm[1])
-    } else {
-        todo!()
-    };
-    let (eval_zeta, eval_zeta_omega) = if evals.len() == 2 {
-        (evals[0], evals[1])
-    } else {
-        todo!()
-    };
+    if elm.len() == 2 {
+        let (zeta, zeta_omega) = (elm[0], elm[1]);
+        let (eval_zeta, eval_zeta_omega) = (evals[0], evals[1]);
+
+        // The polynomial that evaluates to `p(ζ)` at `ζ` and `p(ζω)` at
+        // `ζω`.
+        // We write `p(x) = a + bx`, which gives
+        // ```text
+        // p(ζ) = a + b * ζ
+        // p(ζω) = a + b * ζω
+        // ```
+        // and so
+        // ```text
+        // b = (p(ζω) - p(ζ)) / (ζω - ζ)
+        // a = p(ζ) - b * ζ
+        // ```
+        let b = (eval_zeta_omega - eval_zeta) / (zeta_omega - zeta);
+        let a = eval_zeta - b * zeta;
+        return DensePolynomial::from_coefficients_slice(&[a, b]);
+    }
 
-    // The polynomial that evaluates to `p(ζ)` at `ζ` and `p(ζω)` at
-    // `ζω`.
-    // We write `p(x) = a + bx`, which gives
-    // ```text
-    // p(ζ) = a + b * ζ
-    // p(ζω) = a + b * ζω
-    // ```
-    // and so
+    if elm.is_empty() {
+        return DensePolynomial::zero();
+    }
+
+    // In the general case, we use the barycentric form of the Lagrange
+    // interpolation
     // ```text
-    // b = (p(ζω) - p(ζ)) / (ζω - ζ)
-    // a = p(ζ) - b * ζ
+    // p(x) = Σ_i p(x_i) / q_i(x_i) * q_i(x)
     // ```
-    let b = (eval_zeta_omega - eval_zeta) / (zeta_omega - zeta);
-    let a = eval_zeta - b * zeta;
-    DensePolynomial::from_coefficients_slice(&[a, b])
+    // where `Z(x) = Π_j (x - x_j)` and `q_i(x) = Z(x) / (x - x_i)`.
+    // Each `q_i` is obtained from `Z` by synthetic division, so the whole
+    // interpolation costs `O(n²)` field operations.
+    let vanishing = divisor_polynomial(elm);
+    let quotient = |x_i: F| {
+        let z = &vanishing.coeffs;
+        let n = z.len() - 1;
+        let mut q = vec![F::zero(); n];
+        q[n - 1] = z[n];
+        for k in (1..n).rev() {
+            q[k - 1] = z[k] + x_i * q[k];
+        }
+        DensePolynomial::from_coefficients_vec(q)
+    };
+
+    let mut weights: Vec<F> = elm.iter().map(|x_i| quotient(*x_i).evaluate(x_i)).collect();
+    ark_ff::batch_inversion(&mut weights);
+
+    let mut coeffs = vec![F::zero(); elm.len()];
+    for ((x_i, eval_i), weight) in elm.iter().zip(evals).zip(weights) {
+        let scale = *eval_i * weight;
+        for (c, q) in coeffs.iter_mut().zip(quotient(*x_i).coeffs) {
+            *c += q * scale;
+        }
+    }
+    DensePolynomial::from_coefficients_vec(coeffs)
 }
 
 /// The polynomial that evaluates to `0` at the evaluation points.
@@ -388,8 +443,7 @@ impl<
     /// - `plnms`: the list of polynomials to open.
     /// The type is simply an alias to handle the polynomials in evaluations or
     /// coefficients forms.
-    /// - `elm`: vector of evaluation points. Note that it only works for two
-    /// elements for now.
+    /// - `elm`: vector of evaluation points.
     /// - `polyscale`: a challenge to batch the polynomials.
     pub fn create<D: EvaluationDomain<F>>(
         srs: &PairingSRS<Pair>,
@@ -401,7 +455,6 @@ impl<
         let evals: Vec<_> = elm.iter().map(|pt| p.evaluate(pt)).collect();
 
         let quotient_poly = {
-            // This is where the condition on two points is enforced.
             let eval_polynomial = eval_polynomial(elm, &evals);
             let divisor_polynomial = divisor_polynomial(elm);
             let numerator_polynomial = &p - &eval_polynomial;
@@ -426,9 +479,9 @@ impl<
         })
     }
 
-    /// Verify a proof. Note that it only works for two elements for now, i.e.
-    /// elm must be of size 2.
-    /// Also, chunking is not supported.
+    /// Verify a proof. Note that chunking is not supported, and that the
+    /// number of evaluation points must be smaller than the size of the
+    /// verifier SRS.
     pub fn verify(
         &self,
         srs: &PairingSRS<Pair>,        // SRS
@@ -436,6 +489,10 @@ impl<
         polyscale: F,                  // scaling factor for polynoms
         elm: &[F],                     // vector of evaluation points
     ) -> bool {
+        // The divisor polynomial must fit in a single chunk
+        if elm.len() > srs.max_evaluation_points() {
+            return false;
+        }
         let poly_commitment: G::Group = {
             let mut scalars: Vec<F> = Vec::new();
             let mut points = Vec::new();
@@ -1,3 +1,4 @@
+pub mod accumulator;
 mod combine;
 pub mod commitment;
 pub mod error;
@@ -221,4 +222,12 @@ pub trait OpenProof<G: CommitmentCurve>: Sized + Clone {
     where
         EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
         RNG: RngCore + CryptoRng;
+
+    /// An estimate of the multi-scalar multiplications computed by
+    /// [`Self::open`] to open polynomials at `num_points` points, as pairs of
+    /// the number of multi-scalar multiplications and of their number of
+    /// points. It is only used for profiling, and is empty by default.
+    fn open_msms(_srs: &Self::SRS, _num_points: usize) -> Vec<(usize, usize)> {
+        vec![]
+    }
 }
@@ -0,0 +1,178 @@
+use ark_ff::UniformRand;
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, Polynomial};
+use groupmap::GroupMap;
+use mina_curves::pasta::{Fp, Vesta as VestaG};
+use mina_poseidon::{
+    constants::PlonkSpongeConstantsKimchi as SC, sponge::DefaultFqSponge, FqSponge,
+};
+use poly_commitment::{
+    accumulator::Accumulator,
+    commitment::{combined_inner_product, BatchEvaluationProof, CommitmentCurve, Evaluation},
+    ipa::{OpeningProof, SRS},
+    utils::DensePolynomialOrEvaluations,
+    PolyComm, SRS as _,
+};
+use rand::Rng;
+
+type Sponge = DefaultFqSponge<mina_curves::pasta::VestaParameters, SC>;
+
+fn new_sponge() -> Sponge {
+    Sponge::new(mina_poseidon::pasta::fq_kimchi::static_params())
+}
+
+/// An opening proof of a random polynomial at two random points, with the
+/// data needed to verify it.
+struct Opening {
+    commitment: PolyComm<VestaG>,
+    evaluations: Vec<Vec<Fp>>,
+    points: Vec<Fp>,
+    polyscale: Fp,
+    evalscale: Fp,
+    proof: OpeningProof<VestaG>,
+}
+
+impl Opening {
+    fn new(srs: &SRS<VestaG>, rng: &mut (impl Rng + rand::CryptoRng)) -> Self {
+        let group_map = <VestaG as CommitmentCurve>::Map::setup();
+        let len = rng.gen_range(1..=srs.g.len());
+        let poly = DensePolynomial::<Fp>::rand(len - 1, rng);
+        let commitment = srs.commit(&poly, 1, rng);
+        let points = vec![Fp::rand(rng), Fp::rand(rng)];
+        let (polyscale, evalscale) = (Fp::rand(rng), Fp::rand(rng));
+        let polys = vec![(
+            DensePolynomialOrEvaluations::<_, ark_poly::Radix2EvaluationDomain<_>>::DensePolynomial(
+                &poly,
+            ),
+            commitment.blinders,
+        )];
+        let proof = srs.open(
+            &group_map,
+            &polys,
+            &points,
+            polyscale,
+            evalscale,
+            new_sponge(),
+            rng,
+        );
+        Opening {
+            commitment: commitment.commitment,
+            evaluations: points.iter().map(|x| vec![poly.evaluate(x)]).collect(),
+            points,
+            polyscale,
+            evalscale,
+            proof,
+        }
+    }
+
+    fn batch(&self) -> BatchEvaluationProof<VestaG, Sponge, OpeningProof<VestaG>> {
+        BatchEvaluationProof {
+            sponge: new_sponge(),
+            evaluations: vec![Evaluation {
+                commitment: self.commitment.clone(),
+                evaluations: self.evaluations.clone(),
+            }],
+            evaluation_points: self.points.clone(),
+            polyscale: self.polyscale,
+            evalscale: self.evalscale,
+            opening: &self.proof,
+            combined_inner_product: combined_inner_product(
+                &self.polyscale,
+                &self.evalscale,
+                &[self.evaluations.clone()],
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_ipa_accumulation() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let group_map = <VestaG as CommitmentCurve>::Map::setup();
+    let srs = SRS::<VestaG>::create(32);
+
+    let openings: Vec<_> = (0..5).map(|_| Opening::new(&srs, rng)).collect();
+    let mut batch: Vec<_> = openings.iter().map(Opening::batch).collect();
+    let accumulators = srs.succinct_verify(&group_map, &mut batch, rng).unwrap();
+    assert_eq!(accumulators.len(), openings.len());
+    assert!(srs.check_accumulators(&accumulators, rng));
+
+    // the full verification agrees
+    let mut batch: Vec<_> = openings.iter().map(Opening::batch).collect();
+    assert!(srs.verify(&group_map, &mut batch, rng));
+
+    // fold the accumulators into a single one
+    let (accumulator, proof) = srs
+        .accumulate(&group_map, &accumulators, new_sponge(), rng)
+        .unwrap();
+    assert_eq!(
+        srs.verify_accumulation(&group_map, &accumulators, &proof, new_sponge()),
+        Some(accumulator.clone())
+    );
+    assert!(srs.check_accumulators(&[accumulator.clone()], rng));
+
+    // accumulators can be folded again
+    let (folded, proof) = srs
+        .accumulate(
+            &group_map,
+            &[accumulator.clone(), accumulators[0].clone()],
+            new_sponge(),
+            rng,
+        )
+        .unwrap();
+    assert!(srs
+        .verify_accumulation(
+            &group_map,
+            &[accumulator, accumulators[0].clone()],
+            &proof,
+            new_sponge()
+        )
+        .is_some());
+    assert!(srs.check_accumulators(&[folded], rng));
+}
+
+#[test]
+fn test_ipa_accumulation_fails() {
+    let rng = &mut o1_utils::tests::make_test_rng(None);
+    let group_map = <VestaG as CommitmentCurve>::Map::setup();
+    let srs = SRS::<VestaG>::create(32);
+
+    let openings: Vec<_> = (0..3).map(|_| Opening::new(&srs, rng)).collect();
+    let mut batch: Vec<_> = openings.iter().map(Opening::batch).collect();
+    let accumulators = srs.succinct_verify(&group_map, &mut batch, rng).unwrap();
+
+    // a wrong evaluation is caught by the succinct check
+    let mut batch: Vec<_> = openings.iter().map(Opening::batch).collect();
+    batch[1].combined_inner_product += Fp::from(1u8);
+    assert!(srs.succinct_verify(&group_map, &mut batch, rng).is_none());
+
+    // a wrong folded base is caught by the deferred check
+    let mut bad = accumulators.clone();
+    bad[2].sg = accumulators[0].sg;
+    assert!(!srs.check_accumulators(&bad, rng));
+
+    // and when folding
+    assert!(srs
+        .accumulate(&group_map, &bad, new_sponge(), rng)
+        .is_none());
+
+    // the folding proof is bound to the accumulators
+    let (_, proof) = srs
+        .accumulate(&group_map, &accumulators, new_sponge(), rng)
+        .unwrap();
+    assert!(srs
+        .verify_accumulation(&group_map, &accumulators[..2], &proof, new_sponge())
+        .is_none());
+    assert!(srs
+        .verify_accumulation(&group_map, &bad, &proof, new_sponge())
+        .is_none());
+    assert!(srs
+        .verify_accumulation(&group_map, &[], &proof, new_sponge())
+        .is_none());
+
+    // accumulators of another SRS are rejected
+    let small = Accumulator {
+        chals: accumulators[0].chals[1..].to_vec(),
+        sg: accumulators[0].sg,
+    };
+    assert!(!srs.check_accumulators(&[small], rng));
+}
@@ -207,6 +207,68 @@ fn test_kzg_proof() {
     assert!(res);
 }
 
+#[test]
+fn test_kzg_proof_any_number_of_points() {
+    let n = 64;
+    let mut rng = o1_utils::tests::make_test_rng(None);
+    let x = ScalarField::rand(&mut rng);
+
+    let srs = PairingSRS::<Bn<Config>>::create_trusted_setup_with_verifier_depth(x, n, 9);
+    assert_eq!(srs.max_evaluation_points(), 8);
+
+    let polynomials: Vec<_> = (0..3)
+        .map(|_| {
+            let coeffs = (0..63).map(|_| ScalarField::rand(&mut rng)).collect();
+            DensePolynomial::from_coefficients_vec(coeffs)
+        })
+        .collect();
+    let comms: Vec<_> = polynomials
+        .iter()
+        .map(|p| srs.full_srs.commit(p, 1, &mut rng))
+        .collect();
+    let polynomials_and_blinders: Vec<(DensePolynomialOrEvaluations<_, D<_>>, _)> = polynomials
+        .iter()
+        .zip(comms.iter())
+        .map(|(p, comm)| {
+            let p = DensePolynomialOrEvaluations::DensePolynomial(p);
+            (p, comm.blinders.clone())
+        })
+        .collect();
+
+    for n_points in [1, 3, 8, 9] {
+        let evaluation_points: Vec<_> =
+            (0..n_points).map(|_| ScalarField::rand(&mut rng)).collect();
+        let evaluations: Vec<_> = polynomials
+            .iter()
+            .zip(comms.iter())
+            .map(|(p, commitment)| Evaluation {
+                commitment: commitment.commitment.clone(),
+                evaluations: evaluation_points
+                    .iter()
+                    .map(|x| vec![p.evaluate(x)])
+                    .collect(),
+            })
+            .collect();
+        let polyscale = ScalarField::rand(&mut rng);
+
+        let kzg_proof = KZGProof::<Bn<Config>>::create(
+            &srs,
+            polynomials_and_blinders.as_slice(),
+            &evaluation_points,
+            polyscale,
+        )
+        .unwrap();
+        let res = kzg_proof.verify(&srs, &evaluations, polyscale, &evaluation_points);
+        // The verifier SRS of depth 9 can only check up to 8 points
+        assert_eq!(res, n_points <= srs.max_evaluation_points());
+
+        // Wrong evaluations are rejected
+        let mut bad_evaluations = evaluations;
+        bad_evaluations[0].evaluations[0][0] += ScalarField::from(1u64);
+        assert!(!kzg_proof.verify(&srs, &bad_evaluations, polyscale, &evaluation_points));
+    }
+}
+
 /// Our points in G2 are not actually in the correct subgroup and serialize well.
 #[test]
 fn check_srs_g2_valid_and_serializes() {
@@ -2,7 +2,7 @@
 
 # Check if input file is provided
 if [ $# -lt 1 ]; then
-   echo "Usage: $0 <input_file> [srs-filepath]"
+   echo "Usage: [BACKEND=ipa|kzg] $0 <input_file> [srs-filepath]"
    exit 1
 fi
 
@@ -10,7 +10,11 @@ INPUT_FILE="$1"
 SRS_ARG=""
 if [ $# -eq 2 ]; then
    SRS_ARG="--srs-filepath $2"
+elif [ "${BACKEND:-ipa}" = "kzg" ]; then
+   # Without an SRS file, the KZG backend needs an explicit testing SRS
+   SRS_ARG="--insecure-dev-srs"
 fi
+SRS_ARG="$SRS_ARG --backend ${BACKEND:-ipa}"
 COMMITMENT_FILE="${INPUT_FILE%.*}_commitment.bin"
 ENCODED_FILE="${INPUT_FILE%.*}.bin"
 DECODED_FILE="${INPUT_FILE%.*}_decoded.${INPUT_FILE##*.}"
@@ -52,6 +56,110 @@ if ! cargo run --release --bin saffron verify-storage-proof --commitment "$COMMI
 fi
 echo "✓ Proof verification successful"
 
+# Sample random positions of the encoded data and verify their openings
+echo "Generating sampling seed..."
+SEED=$(head -c 32 /dev/urandom | xxd -p -c 32)
+echo "Seed: $SEED"
+
+echo "Generating sampling proof..."
+SAMPLING_PROOF=$(cargo run --release --bin saffron sample -i "$ENCODED_FILE" --seed "$SEED" --samples 16 $SRS_ARG | tee /dev/stderr | tail -n 1)
+if [ $? -ne 0 ]; then
+    echo "Sampling proof generation failed"
+    exit 1
+fi
+
+echo "Verifying sampling proof..."
+if ! cargo run --release --bin saffron verify-sample --commitment-file "$COMMITMENT_FILE" --seed "$SEED" --samples 16 --proof "$SAMPLING_PROOF" $SRS_ARG; then
+    echo "Sampling proof verification failed"
+    exit 1
+fi
+echo "✓ Sampling proof verification successful"
+
+# Store the encoded data and read a range of bytes with its proof
+STORE_DIR="${INPUT_FILE%.*}_store"
+RANGE_FILE="${INPUT_FILE%.*}_range.bin"
+INPUT_SIZE=$(wc -c < "$INPUT_FILE")
+RANGE_START=$((INPUT_SIZE / 3))
+RANGE_LEN=$((INPUT_SIZE / 3))
+
+echo "Storing $ENCODED_FILE in $STORE_DIR"
+if ! cargo run --release --bin saffron store -i "$ENCODED_FILE" --store-dir "$STORE_DIR" --name blob $SRS_ARG; then
+    echo "Storing failed"
+    exit 1
+fi
+
+echo "Reading $RANGE_LEN bytes from $RANGE_START..."
+RANGE_PROOF=$(cargo run --release --bin saffron read-range --store-dir "$STORE_DIR" --name blob --start "$RANGE_START" --len "$RANGE_LEN" -o "$RANGE_FILE" $SRS_ARG | tee /dev/stderr | tail -n 1)
+if [ $? -ne 0 ]; then
+    echo "Reading range failed"
+    exit 1
+fi
+
+if ! cmp -s "$RANGE_FILE" <(tail -c +$((RANGE_START + 1)) "$INPUT_FILE" | head -c "$RANGE_LEN"); then
+    echo "✗ Error: the range read differs from the input file"
+    exit 1
+fi
+
+echo "Verifying range proof..."
+if ! cargo run --release --bin saffron verify-range --commitment-file "$COMMITMENT_FILE" --start "$RANGE_START" --len "$RANGE_LEN" -d "$RANGE_FILE" --proof "$RANGE_PROOF" $SRS_ARG; then
+    echo "Range proof verification failed"
+    exit 1
+fi
+echo "✓ Range proof verification successful"
+rm -rf "$STORE_DIR" "$RANGE_FILE"
+
+# Update a few bytes of the data, and apply the diff to the encoded data
+UPDATED_FILE="${INPUT_FILE%.*}_updated.${INPUT_FILE##*.}"
+DIFF_FILE="${INPUT_FILE%.*}_diff.bin"
+UPDATED_DECODED_FILE="${INPUT_FILE%.*}_updated_decoded.${INPUT_FILE##*.}"
+cp "$INPUT_FILE" "$UPDATED_FILE"
+printf 'saffron' | dd of="$UPDATED_FILE" bs=1 seek="$RANGE_START" conv=notrunc status=none
+
+echo "Computing diff..."
+if ! cargo run --release --bin saffron diff --old "$INPUT_FILE" --new "$UPDATED_FILE" -o "$DIFF_FILE" $SRS_ARG; then
+    echo "Diff computation failed"
+    exit 1
+fi
+
+echo "Applying diff to $ENCODED_FILE..."
+NEW_COMMITMENT=$(cargo run --release --bin saffron apply-diff -i "$ENCODED_FILE" --diff "$DIFF_FILE" $SRS_ARG | tee /dev/stderr | tail -n 1)
+if [ $? -ne 0 ]; then
+    echo "Applying diff failed"
+    exit 1
+fi
+
+echo "Verifying new commitment..."
+if ! cargo run --release --bin saffron verify-diff --commitment-file "$COMMITMENT_FILE" --diff "$DIFF_FILE" --new-commitment "$NEW_COMMITMENT" $SRS_ARG; then
+    echo "Diff verification failed"
+    exit 1
+fi
+echo "✓ Diff verification successful"
+
+if ! cargo run --release --bin saffron decode -i "$ENCODED_FILE" -o "$UPDATED_DECODED_FILE" $SRS_ARG; then
+    echo "Decoding failed"
+    exit 1
+fi
+if ! cmp -s "$UPDATED_FILE" "$UPDATED_DECODED_FILE"; then
+    echo "✗ Error: the updated data differs from the decoded data"
+    exit 1
+fi
+rm -f "$UPDATED_FILE" "$DIFF_FILE" "$UPDATED_DECODED_FILE"
+
+# Revert the encoded data to the original version, by erasure coding it and
+# reconstructing it. Losing evaluations is tested in tests/erasure.rs.
+EXTENDED_FILE="${INPUT_FILE%.*}_extended.bin"
+echo "Erasure coding $INPUT_FILE to $EXTENDED_FILE"
+if ! cargo run --release --bin saffron encode -i "$INPUT_FILE" -o "$EXTENDED_FILE" --assert-commitment "$COMMITMENT" --rate half $SRS_ARG; then
+   echo "Encoding failed"
+   exit 1
+fi
+echo "Reconstructing $ENCODED_FILE from $EXTENDED_FILE"
+if ! cargo run --release --bin saffron reconstruct -i "$EXTENDED_FILE" -o "$ENCODED_FILE" --backend "${BACKEND:-ipa}"; then
+   echo "Reconstruction failed"
+   exit 1
+fi
+rm -f "$EXTENDED_FILE"
+
 
 # Run decode
 echo "Decoding $ENCODED_FILE to $DECODED_FILE"
@@ -11,7 +11,7 @@ use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use kimchi::curve::KimchiCurve;
 use mina_poseidon::FqSponge;
 use o1_utils::FieldHelpers;
-use poly_commitment::{commitment::CommitmentCurve, ipa::SRS, PolyComm, SRS as _};
+use poly_commitment::{commitment::CommitmentCurve, PolyComm, SRS};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
@@ -32,7 +32,7 @@ pub struct FieldBlob<G: CommitmentCurve> {
 
 #[instrument(skip_all, level = "debug")]
 fn commit_to_blob_data<G: CommitmentCurve>(
-    srs: &SRS<G>,
+    srs: &impl SRS<G>,
     data: &[DensePolynomial<G::ScalarField>],
 ) -> Vec<PolyComm<G>> {
     let num_chunks = 1;
@@ -47,7 +47,7 @@ impl<G: KimchiCurve> FieldBlob<G> {
         D: EvaluationDomain<G::ScalarField>,
         EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
     >(
-        srs: &SRS<G>,
+        srs: &impl SRS<G>,
         domain: D,
         bytes: &[u8],
     ) -> FieldBlob<G> {
@@ -109,7 +109,7 @@ impl<G: KimchiCurve> FieldBlob<G> {
 
     pub fn update<EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>>(
         &mut self,
-        srs: &SRS<G>,
+        srs: &impl SRS<G>,
         domain: &Radix2EvaluationDomain<G::ScalarField>,
         diff: Diff<G::ScalarField>,
     ) {
@@ -124,10 +124,14 @@ impl<G: KimchiCurve> FieldBlob<G> {
         };
         let chunks: Vec<DensePolynomial<G::ScalarField>> = diff_evaluations
             .into_par_iter()
-            .zip(self.chunks.par_iter())
-            .map(|(evals, p)| {
+            .enumerate()
+            .map(|(i, evals)| {
                 let d_p: DensePolynomial<G::ScalarField> = evals.interpolate();
-                p + &d_p
+                // the chunks beyond the current ones are new
+                match self.chunks.get(i) {
+                    Some(p) => p + &d_p,
+                    None => d_p,
+                }
             })
             .collect();
         self.commitment = commitment;
@@ -138,16 +142,18 @@ impl<G: KimchiCurve> FieldBlob<G> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{commitment::commit_to_field_elems, env};
+    use crate::{
+        commitment::{commit_to_diff, commit_to_field_elems},
+        env,
+    };
 
     use super::*;
     use crate::{diff::tests::*, utils::test_utils::*};
-    use ark_ec::AffineRepr;
-    use ark_ff::Zero;
     use ark_poly::Radix2EvaluationDomain;
     use mina_curves::pasta::{Fp, Vesta, VestaParameters};
     use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSponge};
     use once_cell::sync::Lazy;
+    use poly_commitment::{ipa::SRS, SRS as _};
     use proptest::prelude::*;
 
     type VestaFqSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
@@ -190,23 +196,6 @@ mod tests {
       }
     }
 
-    fn encode_to_chunk_size(xs: &[u8], chunk_size: usize) -> FieldBlob<Vesta> {
-        let mut blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, xs);
-        assert!(blob.chunks.len() <= chunk_size);
-        {
-            let pad = DensePolynomial::zero();
-            blob.chunks.resize(chunk_size, pad);
-        }
-        {
-            let pad = PolyComm::new(vec![Vesta::zero()]);
-            let mut commitments = blob.commitment.chunks.clone();
-            commitments.resize(chunk_size, pad);
-            let mut sponge = VestaFqSponge::new(Vesta::other_curve_sponge_params());
-            blob.commitment = Commitment::from_chunks(commitments, &mut sponge);
-        }
-        blob
-    }
-
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(10))]
         #[test]
@@ -216,7 +205,7 @@ mod tests {
         ) {
             // start with some random user data
             let mut xs_blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &xs);
-            let diff = Diff::<Fp>::create(&*DOMAIN, &xs, &ys).unwrap();
+            let diff = Diff::<Fp>::create(&*DOMAIN, &xs, &ys);
 
             // check that the user and SP agree on the data
             let user_commitment = {
@@ -240,8 +229,17 @@ mod tests {
             };
             prop_assert_eq!(updated_user_commitment, xs_blob.commitment.clone());
 
-            // the updated blob should be the same as if we just start with the new data (with appropriate padding)
-            let ys_blob = encode_to_chunk_size(&ys, xs_blob.chunks.len());
+            // a verifier only needs the old commitment and the diff to check the new one
+            let mut sponge = VestaFqSponge::new(Vesta::other_curve_sponge_params());
+            prop_assert!(user_commitment.verify_update(
+                commit_to_diff(&*SRS, *DOMAIN, &diff),
+                &xs_blob.commitment.folded,
+                &mut sponge
+            ));
+
+            // the updated blob should be the same as if we just start with the new data,
+            // even if the new data requires more or less chunks
+            let ys_blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &ys);
             prop_assert_eq!(xs_blob, ys_blob)
         }
 
@@ -1,4 +1,5 @@
-use clap::{arg, Parser};
+use crate::erasure::Rate;
+use clap::{arg, Parser, ValueEnum};
 use std::{fmt::Display, str::FromStr};
 
 #[derive(Debug, Clone)]
@@ -19,6 +20,19 @@ impl Display for HexString {
     }
 }
 
+/// The polynomial commitment scheme used to commit to the data and to prove
+/// its evaluations. The encoded data, the commitments and the proofs of a
+/// backend can only be used with the same backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// Inner product argument over Vesta, without trusted setup. The proofs
+    /// have a logarithmic size and are verified in linear time.
+    Ipa,
+    /// KZG over BN254, with a trusted setup. The proofs have a constant size
+    /// and are verified with pairings, e.g. on Ethereum.
+    Kzg,
+}
+
 #[derive(Parser)]
 pub struct EncodeFileArgs {
     #[arg(long, short = 'i', value_name = "FILE", help = "input file")]
@@ -35,12 +49,60 @@ pub struct EncodeFileArgs {
     #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
     pub srs_cache: Option<String>,
 
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
     #[arg(
         long = "assert-commitment",
         value_name = "COMMITMENT",
         help = "hash of commitments (hex encoded)"
     )]
     pub assert_commitment: Option<HexString>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "erasure code the encoded data at this rate, see reconstruct"
+    )]
+    pub rate: Option<Rate>,
+}
+
+#[derive(Parser)]
+pub struct ReconstructArgs {
+    #[arg(
+        long,
+        short = 'i',
+        value_name = "FILE",
+        help = "input file (erasure coded with encode --rate, lost evaluations being missing)"
+    )]
+    pub input: String,
+
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "FILE",
+        help = "output file (encoded as field elements)"
+    )]
+    pub output: String,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
 }
 
 #[derive(Parser)]
@@ -58,6 +120,20 @@ pub struct DecodeFileArgs {
 
     #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
     pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
 }
 
 #[derive(Parser)]
@@ -70,6 +146,20 @@ pub struct ComputeCommitmentArgs {
 
     #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
     pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
 }
 
 #[derive(Parser)]
@@ -85,6 +175,20 @@ pub struct StorageProofArgs {
     #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
     pub srs_cache: Option<String>,
 
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
     #[arg(
         long = "challenge",
         value_name = "CHALLENGE",
@@ -98,6 +202,20 @@ pub struct VerifyStorageProofArgs {
     #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
     pub srs_cache: Option<String>,
 
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
     #[arg(
         long,
         short = 'c',
@@ -117,6 +235,329 @@ pub struct VerifyStorageProofArgs {
     pub proof: HexString,
 }
 
+#[derive(Parser)]
+pub struct SampleArgs {
+    #[arg(
+        long,
+        short = 'i',
+        value_name = "FILE",
+        help = "input file (encoded as field elements)"
+    )]
+    pub input: String,
+
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
+    #[arg(
+        long = "seed",
+        value_name = "SEED",
+        help = "seed of the sampled positions (32 bytes, hex encoded)"
+    )]
+    pub seed: HexString,
+
+    #[arg(
+        long = "samples",
+        value_name = "N",
+        default_value_t = 16,
+        help = "number of positions to sample"
+    )]
+    pub samples: usize,
+}
+
+#[derive(Parser)]
+pub struct VerifySampleArgs {
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
+    #[arg(
+        long = "commitment-file",
+        value_name = "FILE",
+        help = "commitment file, as written by compute-commitment"
+    )]
+    pub commitment_file: String,
+
+    #[arg(
+        long = "seed",
+        value_name = "SEED",
+        help = "seed of the sampled positions (32 bytes, hex encoded)"
+    )]
+    pub seed: HexString,
+
+    #[arg(
+        long = "samples",
+        value_name = "N",
+        default_value_t = 16,
+        help = "number of positions to sample"
+    )]
+    pub samples: usize,
+
+    #[arg(long, short = 'p', value_name = "PROOF", help = "proof (hex encoded)")]
+    pub proof: HexString,
+}
+
+#[derive(Parser)]
+pub struct StoreArgs {
+    #[arg(
+        long,
+        short = 'i',
+        value_name = "FILE",
+        help = "input file (encoded as field elements)"
+    )]
+    pub input: String,
+
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
+    #[arg(
+        long = "store-dir",
+        value_name = "DIR",
+        help = "directory of the store"
+    )]
+    pub store_dir: String,
+
+    #[arg(long, value_name = "NAME", help = "name of the blob in the store")]
+    pub name: String,
+}
+
+#[derive(Parser)]
+pub struct ReadRangeArgs {
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
+    #[arg(
+        long = "store-dir",
+        value_name = "DIR",
+        help = "directory of the store"
+    )]
+    pub store_dir: String,
+
+    #[arg(long, value_name = "NAME", help = "name of the blob in the store")]
+    pub name: String,
+
+    #[arg(long, value_name = "START", help = "index of the first byte to read")]
+    pub start: usize,
+
+    #[arg(
+        long,
+        value_name = "LEN",
+        help = "number of bytes to read, at most about 31 KB (1024 field elements)"
+    )]
+    pub len: usize,
+
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "FILE",
+        help = "output file (bytes read)"
+    )]
+    pub output: String,
+}
+
+#[derive(Parser)]
+pub struct VerifyRangeArgs {
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
+    #[arg(
+        long = "commitment-file",
+        value_name = "FILE",
+        help = "commitment file, as written by compute-commitment"
+    )]
+    pub commitment_file: String,
+
+    #[arg(long, value_name = "START", help = "index of the first byte read")]
+    pub start: usize,
+
+    #[arg(long, value_name = "LEN", help = "number of bytes read")]
+    pub len: usize,
+
+    #[arg(
+        long,
+        short = 'd',
+        value_name = "FILE",
+        help = "file of the bytes read"
+    )]
+    pub data: String,
+
+    #[arg(long, short = 'p', value_name = "PROOF", help = "proof (hex encoded)")]
+    pub proof: HexString,
+}
+
+#[derive(Parser)]
+pub struct DiffArgs {
+    #[arg(long, value_name = "FILE", help = "old version of the data")]
+    pub old: String,
+
+    #[arg(long, value_name = "FILE", help = "new version of the data")]
+    pub new: String,
+
+    #[arg(long, short = 'o', value_name = "FILE", help = "output file (diff)")]
+    pub output: String,
+
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+}
+
+#[derive(Parser)]
+pub struct ApplyDiffArgs {
+    #[arg(
+        long,
+        short = 'i',
+        value_name = "FILE",
+        help = "input file (encoded as field elements), updated in place"
+    )]
+    pub input: String,
+
+    #[arg(long, value_name = "FILE", help = "diff file, as written by diff")]
+    pub diff: String,
+
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+}
+
+#[derive(Parser)]
+pub struct VerifyDiffArgs {
+    #[arg(long = "srs-filepath", value_name = "SRS_FILEPATH")]
+    pub srs_cache: Option<String>,
+
+    #[arg(
+        long,
+        help = "with the kzg backend and without --srs-filepath, use an SRS whose toxic waste is public (testing only)"
+    )]
+    pub insecure_dev_srs: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Backend::Ipa,
+        help = "polynomial commitment scheme"
+    )]
+    pub backend: Backend,
+
+    #[arg(
+        long = "commitment-file",
+        value_name = "FILE",
+        help = "commitment file of the old data, as written by compute-commitment"
+    )]
+    pub commitment_file: String,
+
+    #[arg(long, value_name = "FILE", help = "diff file, as written by diff")]
+    pub diff: String,
+
+    #[arg(
+        long = "new-commitment",
+        value_name = "COMMITMENT",
+        help = "commitment of the new data (hex encoded)"
+    )]
+    pub new_commitment: HexString,
+
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "FILE",
+        help = "output file for the commitment of the new data"
+    )]
+    pub output: Option<String>,
+}
+
 #[derive(Parser)]
 #[command(
     name = "saffron",
@@ -128,10 +569,28 @@ pub enum Commands {
     Encode(EncodeFileArgs),
     #[command(name = "decode")]
     Decode(DecodeFileArgs),
+    #[command(name = "reconstruct")]
+    Reconstruct(ReconstructArgs),
     #[command(name = "compute-commitment")]
     ComputeCommitment(ComputeCommitmentArgs),
     #[command(name = "storage-proof")]
     StorageProof(StorageProofArgs),
     #[command(name = "verify-storage-proof")]
     VerifyStorageProof(VerifyStorageProofArgs),
+    #[command(name = "sample")]
+    Sample(SampleArgs),
+    #[command(name = "verify-sample")]
+    VerifySample(VerifySampleArgs),
+    #[command(name = "store")]
+    Store(StoreArgs),
+    #[command(name = "read-range")]
+    ReadRange(ReadRangeArgs),
+    #[command(name = "verify-range")]
+    VerifyRange(VerifyRangeArgs),
+    #[command(name = "diff")]
+    Diff(DiffArgs),
+    #[command(name = "apply-diff")]
+    ApplyDiff(ApplyDiffArgs),
+    #[command(name = "verify-diff")]
+    VerifyDiff(VerifyDiffArgs),
 }
@@ -1,3 +1,4 @@
+use crate::diff::Diff;
 use ark_ec::AffineRepr;
 use ark_ff::One;
 use ark_poly::{Evaluations, Radix2EvaluationDomain as D};
@@ -6,8 +7,7 @@ use kimchi::curve::KimchiCurve;
 use mina_poseidon::FqSponge;
 use poly_commitment::{
     commitment::{absorb_commitment, CommitmentCurve},
-    ipa::SRS,
-    PolyComm, SRS as _,
+    PolyComm, SRS,
 };
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -38,18 +38,42 @@ impl<G: KimchiCurve> Commitment<G> {
         }
     }
 
+    /// Update the commitment with the commitments to the chunks of a diff.
+    /// The chunks of the diff beyond the current ones are new chunks, and the
+    /// current chunks beyond the diff are dropped.
     pub fn update<EFqSponge>(&self, diff: Vec<PolyComm<G>>, sponge: &mut EFqSponge) -> Self
     where
         EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
     {
-        let new_chunks = self.chunks.iter().zip(diff).map(|(g, d)| g.add(&d));
+        let new_chunks = diff
+            .into_iter()
+            .enumerate()
+            .map(|(i, d)| match self.chunks.get(i) {
+                Some(g) => g.add(&d),
+                None => d,
+            });
         Self::from_chunks(new_chunks.collect(), sponge)
     }
+
+    /// Check that `new` is the folded commitment obtained by updating `self`
+    /// with the committed diff, see [commit_to_diff]. The folded commitment
+    /// binds the chunks, so it is all a verifier needs to be given.
+    pub fn verify_update<EFqSponge>(
+        &self,
+        diff: Vec<PolyComm<G>>,
+        new: &PolyComm<G>,
+        sponge: &mut EFqSponge,
+    ) -> bool
+    where
+        EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        self.update(diff, sponge).folded == *new
+    }
 }
 
 #[instrument(skip_all, level = "debug")]
 pub fn commit_to_field_elems<G: KimchiCurve, EFqSponge>(
-    srs: &SRS<G>,
+    srs: &impl SRS<G>,
     domain: D<G::ScalarField>,
     field_elems: Vec<Vec<G::ScalarField>>,
 ) -> Commitment<G>
@@ -67,6 +91,18 @@ where
     Commitment::from_chunks(commitments, &mut sponge)
 }
 
+#[instrument(skip_all, level = "debug")]
+pub fn commit_to_diff<G: KimchiCurve>(
+    srs: &impl SRS<G>,
+    domain: D<G::ScalarField>,
+    diff: &Diff<G::ScalarField>,
+) -> Vec<PolyComm<G>> {
+    diff.as_evaluations(&domain)
+        .par_iter()
+        .map(|evals| srs.commit_evaluations_non_hiding(domain, evals))
+        .collect()
+}
+
 #[instrument(skip_all, level = "debug")]
 fn fold_commitments<G: AffineRepr, EFqSponge: FqSponge<G::BaseField, G, G::ScalarField>>(
     sponge: &mut EFqSponge,
@@ -1,60 +1,97 @@
 use crate::utils::encode_for_domain;
 use ark_ff::PrimeField;
 use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 use thiserror::Error;
 use tracing::instrument;
 
+/// The first bytes of an encoded diff.
+pub const DIFF_MAGIC: [u8; 4] = *b"SFDF";
+
+/// The version of the encoding of diffs, written after [DIFF_MAGIC]. It must be
+/// bumped whenever the fields of [Diff] change.
+pub const DIFF_VERSION: u8 = 1;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum DiffError {
+    #[error("Not a diff: the encoding does not start with the diff magic")]
+    InvalidMagic,
+    #[error("Unsupported diff version {0}, expected {DIFF_VERSION}")]
+    UnsupportedVersion(u8),
+    #[error("Invalid diff encoding: {0}")]
+    Decode(String),
+}
+
 // sparse representation, keeping only the non-zero differences
-#[derive(Clone, Debug, PartialEq)]
+// NOTE: the diff files of the binary are written with [Diff::to_bytes]: the
+// magic and version, followed by the MessagePack encoding of this structure,
+// each field element being serialized with ark-serialize.
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(bound = "F: CanonicalDeserialize + CanonicalSerialize")]
 pub struct Diff<F: PrimeField> {
+    #[serde_as(as = "Vec<Vec<(_, o1_utils::serialization::SerdeAs)>>")]
     pub chunks: Vec<Vec<(usize, F)>>,
     pub new_byte_len: usize,
 }
 
-#[derive(Debug, Error, Clone, PartialEq)]
-pub enum DiffError {
-    #[error("Capacity Mismatch: maximum number of chunks is {max_number_chunks}, attempted to create {attempted}")]
-    CapacityMismatch {
-        max_number_chunks: usize,
-        attempted: usize,
-    },
-}
-
 impl<F: PrimeField> Diff<F> {
+    /// The diff has one chunk per polynomial of the new data: the chunks
+    /// beyond the old data are diffed against zero, and the chunks of the old
+    /// data beyond the new data are dropped.
     #[instrument(skip_all, level = "debug")]
-    pub fn create<D: EvaluationDomain<F>>(
-        domain: &D,
-        old: &[u8],
-        new: &[u8],
-    ) -> Result<Diff<F>, DiffError> {
+    pub fn create<D: EvaluationDomain<F>>(domain: &D, old: &[u8], new: &[u8]) -> Diff<F> {
         let old_elems: Vec<Vec<F>> = encode_for_domain(domain, old);
-        let mut new_elems: Vec<Vec<F>> = encode_for_domain(domain, new);
-        if old_elems.len() < new_elems.len() {
-            return Err(DiffError::CapacityMismatch {
-                max_number_chunks: old_elems.len(),
-                attempted: new_elems.len(),
-            });
-        }
-        if old_elems.len() > new_elems.len() {
-            let padding = vec![F::zero(); domain.size()];
-            new_elems.resize(old_elems.len(), padding);
-        }
-        Ok(Diff {
+        let new_elems: Vec<Vec<F>> = encode_for_domain(domain, new);
+        Diff {
             new_byte_len: new.len(),
             chunks: new_elems
                 .par_iter()
-                .zip(old_elems)
-                .map(|(n, o)| {
-                    n.iter()
+                .enumerate()
+                .map(|(i, n)| match old_elems.get(i) {
+                    Some(o) => n
+                        .iter()
                         .zip(o)
                         .enumerate()
                         .map(|(index, (a, b))| (index, *a - b))
                         .filter(|(_, x)| !x.is_zero())
-                        .collect()
+                        .collect(),
+                    None => n
+                        .iter()
+                        .enumerate()
+                        .map(|(index, a)| (index, *a))
+                        .filter(|(_, x)| !x.is_zero())
+                        .collect(),
                 })
                 .collect(),
-        })
+        }
+    }
+
+    /// Encode the diff as [DIFF_MAGIC], [DIFF_VERSION] and the MessagePack
+    /// encoding of the diff.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = DIFF_MAGIC.to_vec();
+        bytes.push(DIFF_VERSION);
+        rmp_serde::encode::write(&mut bytes, self).expect("writing to a vector cannot fail");
+        bytes
+    }
+
+    /// Decode a diff encoded with [Diff::to_bytes], rejecting encodings of any
+    /// other version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Diff<F>, DiffError> {
+        let bytes = bytes
+            .strip_prefix(&DIFF_MAGIC[..])
+            .ok_or(DiffError::InvalidMagic)?;
+        match bytes.split_first() {
+            Some((&DIFF_VERSION, payload)) => {
+                rmp_serde::from_slice(payload).map_err(|e| DiffError::Decode(e.to_string()))
+            }
+            Some((&version, _)) => Err(DiffError::UnsupportedVersion(version)),
+            None => Err(DiffError::Decode("missing version".to_string())),
+        }
     }
 
     #[instrument(skip_all, level = "debug")]
@@ -78,7 +115,10 @@ impl<F: PrimeField> Diff<F> {
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::utils::{chunk_size_in_bytes, min_encoding_chunks, test_utils::UserData};
+    use crate::utils::{
+        chunk_size_in_bytes, min_encoding_chunks,
+        test_utils::{DataSize, UserData},
+    };
     use ark_ff::Zero;
     use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
     use mina_curves::pasta::Fp;
@@ -105,7 +145,8 @@ pub mod tests {
 
     pub fn random_diff(UserData(xs): UserData) -> BoxedStrategy<(UserData, UserData)> {
         let n_chunks = min_encoding_chunks(&*DOMAIN, &xs);
-        let max_byte_len = n_chunks * chunk_size_in_bytes(&*DOMAIN);
+        // the new data can need one more chunk than the old one
+        let max_byte_len = (n_chunks + 1) * chunk_size_in_bytes(&*DOMAIN);
         (0.0..=1.0, 0..=max_byte_len)
             .prop_flat_map(move |(threshold, n)| {
                 let mut ys = randomize_data(threshold, &xs);
@@ -117,6 +158,9 @@ pub mod tests {
     }
 
     fn add(mut evals: Vec<Vec<Fp>>, diff: &Diff<Fp>) -> Vec<Vec<Fp>> {
+        // the chunks beyond the old data start from zero, the ones beyond the
+        // new data are dropped
+        evals.resize(diff.chunks.len(), vec![Fp::zero(); DOMAIN.size()]);
         evals
             .par_iter_mut()
             .zip(diff.chunks.par_iter())
@@ -128,6 +172,40 @@ pub mod tests {
         evals.to_vec()
     }
 
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(20))]
+        #[test]
+        fn test_diff_serialization_round_trip((UserData(xs), UserData(ys)) in
+            (UserData::arbitrary().prop_flat_map(random_diff))
+        ) {
+            let diff = Diff::<Fp>::create(&*DOMAIN, &xs, &ys);
+            let bytes = diff.to_bytes();
+            let res = Diff::<Fp>::from_bytes(&bytes).unwrap();
+            prop_assert_eq!(diff, res);
+        }
+    }
+
+    #[test]
+    fn test_diff_rejects_unknown_encodings() {
+        let diff = Diff::<Fp>::create(&*DOMAIN, b"old data", b"new data");
+        let bytes = diff.to_bytes();
+
+        let mut next_version = bytes.clone();
+        next_version[DIFF_MAGIC.len()] = DIFF_VERSION + 1;
+        assert_eq!(
+            Diff::<Fp>::from_bytes(&next_version),
+            Err(DiffError::UnsupportedVersion(DIFF_VERSION + 1))
+        );
+
+        let bare = rmp_serde::to_vec(&diff).unwrap();
+        assert_eq!(Diff::<Fp>::from_bytes(&bare), Err(DiffError::InvalidMagic));
+
+        assert!(matches!(
+            Diff::<Fp>::from_bytes(&DIFF_MAGIC),
+            Err(DiffError::Decode(_))
+        ));
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(20))]
         #[test]
@@ -136,24 +214,19 @@ pub mod tests {
             (UserData::arbitrary().prop_flat_map(random_diff))
         ) {
             let diff = Diff::<Fp>::create(&*DOMAIN, &xs, &ys);
-            prop_assert!(diff.is_ok());
             let xs_elems = encode_for_domain(&*DOMAIN, &xs);
-            let ys_elems = {
-                let pad = vec![Fp::zero(); DOMAIN.size()];
-                let mut elems = encode_for_domain(&*DOMAIN, &ys);
-                elems.resize(xs_elems.len(), pad);
-                elems
-            };
-            let result = add(xs_elems.clone(), &diff.unwrap());
+            let ys_elems = encode_for_domain(&*DOMAIN, &ys);
+            prop_assert_eq!(diff.chunks.len(), ys_elems.len());
+            let result = add(xs_elems, &diff);
             prop_assert_eq!(result, ys_elems);
         }
     }
 
-    // Check that we CAN'T construct a diff that requires more polynomial chunks than the original data
+    // Check that we can construct a diff that requires more polynomial chunks than the original data
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(10))]
         #[test]
-        fn test_cannot_construct_bad_diff(
+        fn test_allow_growing_diff(
             (threshold, (UserData(data), UserData(mut extra))) in (
                 0.0..1.0,
                 UserData::arbitrary().prop_flat_map(|UserData(d1)| {
@@ -177,7 +250,35 @@ pub mod tests {
             let mut ys = randomize_data(threshold, &data);
             ys.append(&mut extra);
             let diff = Diff::<Fp>::create(&*DOMAIN, &data, &ys);
-            prop_assert!(diff.is_err());
+            let data_elems = encode_for_domain(&*DOMAIN, &data);
+            let ys_elems = encode_for_domain(&*DOMAIN, &ys);
+            prop_assert!(diff.chunks.len() > data_elems.len());
+            let result = add(data_elems, &diff);
+            prop_assert_eq!(result, ys_elems);
+        }
+    }
+
+    // Check that the trailing chunks are dropped when the new data requires less polynomial chunks
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10))]
+        #[test]
+        fn test_allow_shrinking_diff(
+            (threshold, UserData(data), fraction) in (
+                0.0..1.0,
+                UserData::arbitrary_with(DataSize::Medium)
+                    .prop_filter("at least two chunks", |UserData(d)| {
+                        min_encoding_chunks(&*DOMAIN, d) > 1
+                    }),
+                0.0..1.0,
+            )
+        ) {
+            let n_chunks = 1 + (fraction * (min_encoding_chunks(&*DOMAIN, &data) - 1) as f64) as usize;
+            let mut ys = randomize_data(threshold, &data);
+            ys.truncate(n_chunks * chunk_size_in_bytes(&*DOMAIN));
+            let diff = Diff::<Fp>::create(&*DOMAIN, &data, &ys);
+            prop_assert_eq!(diff.chunks.len(), n_chunks);
+            let result = add(encode_for_domain(&*DOMAIN, &data), &diff);
+            prop_assert_eq!(result, encode_for_domain(&*DOMAIN, &ys));
         }
     }
 }
@@ -1,7 +1,7 @@
 use std::{fs::File, path::Path};
 
 use kimchi::precomputed_srs::TestSRS;
-use poly_commitment::{commitment::CommitmentCurve, ipa::SRS};
+use poly_commitment::{commitment::CommitmentCurve, ipa::SRS, kzg::PairingSRS};
 use time::macros::format_description;
 use tracing::debug;
 use tracing_subscriber::{
@@ -33,6 +33,33 @@ pub fn get_srs_from_cache<G: CommitmentCurve>(cache: String) -> SRS<G> {
     srs
 }
 
+pub fn get_pairing_srs_from_cache(cache: String) -> PairingSRS<ark_bn254::Bn254> {
+    debug!("Loading SRS from cache {}", cache);
+    let file = File::open(Path::new(&cache)).expect("Error opening SRS cache file");
+    let srs = rmp_serde::from_read(&file).unwrap();
+    debug!("SRS loaded successfully from cache");
+    srs
+}
+
+/// The depth of the verifier part of the KZG SRS created by
+/// [create_insecure_pairing_srs]. A KZG opening can be verified at up to
+/// `PAIRING_SRS_VERIFIER_DEPTH - 1` points, which bounds the number of
+/// sampled positions and the length of the ranges read with a proof. The
+/// commands check this bound before opening or verifying.
+pub const PAIRING_SRS_VERIFIER_DEPTH: usize = 1 << 10;
+
+/// A KZG SRS whose toxic waste is a public constant, so that the commands
+/// agree on the SRS without a cache. Anybody can forge proofs for it, only use
+/// it for testing.
+pub fn create_insecure_pairing_srs(depth: usize) -> PairingSRS<ark_bn254::Bn254> {
+    let toxic_waste = ark_bn254::Fr::from(0x5aff_0000_u64);
+    PairingSRS::create_trusted_setup_with_verifier_depth(
+        toxic_waste,
+        depth,
+        PAIRING_SRS_VERIFIER_DEPTH,
+    )
+}
+
 pub fn init_console_subscriber() {
     let timer = UtcTime::new(format_description!(
         "[year]-[month]-[day]T[hour repr:24]:[minute]:[second].[subsecond digits:3]Z"
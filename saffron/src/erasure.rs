@@ -0,0 +1,348 @@
+//! Erasure coding of a [FieldBlob].
+//!
+//! Each polynomial of a blob has degree less than the domain size `n`, so it
+//! is determined by any `n` of its evaluations. Extending the blob evaluates
+//! every polynomial over a domain `k` times larger (Reed–Solomon code of rate
+//! `1/k`), and the blob can then be reconstructed from any `n` evaluations of
+//! each polynomial, i.e. a storage node can lose up to `(k - 1) * n`
+//! evaluations of each chunk. The polynomials, hence the commitments, are the
+//! same as the ones of the original blob.
+
+use crate::{blob::FieldBlob, commitment::Commitment};
+use ark_ff::{batch_inversion, PrimeField};
+use ark_poly::{
+    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, Radix2EvaluationDomain,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::ValueEnum;
+use kimchi::curve::KimchiCurve;
+use poly_commitment::commitment::CommitmentCurve;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use thiserror::Error;
+use tracing::{debug, instrument};
+
+/// Rate of the Reed–Solomon code, i.e. the ratio between the size of the
+/// domain of the blob and the size of the extended domain.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
+pub enum Rate {
+    /// Twice as many evaluations, any half of them recovers the data
+    Half,
+    /// Four times as many evaluations, any quarter of them recovers the data
+    Quarter,
+}
+
+impl Rate {
+    /// The number of evaluations stored for each evaluation of the blob
+    pub fn expansion_factor(&self) -> usize {
+        match self {
+            Rate::Half => 2,
+            Rate::Quarter => 4,
+        }
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ErasureError {
+    #[error(
+        "Not enough evaluations to reconstruct chunk {chunk}: got {available}, need {required}"
+    )]
+    NotEnoughEvaluations {
+        chunk: usize,
+        available: usize,
+        required: usize,
+    },
+    #[error("The evaluations of chunk {chunk} are not consistent with a polynomial of degree less than {domain_size}")]
+    InconsistentEvaluations { chunk: usize, domain_size: usize },
+    #[error("Chunk {chunk} has {got} evaluations, expected {expected}")]
+    WrongNumberOfEvaluations {
+        chunk: usize,
+        got: usize,
+        expected: usize,
+    },
+}
+
+// An ExtendedFieldBlob<G> stores the evaluations of the polynomials of a FieldBlob<G>
+// over a domain larger than the one used to encode the data. Lost evaluations are None.
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(bound = "G::ScalarField : CanonicalDeserialize + CanonicalSerialize")]
+pub struct ExtendedFieldBlob<G: CommitmentCurve> {
+    pub n_bytes: usize,
+    pub domain_size: usize,
+    pub rate: Rate,
+    pub commitment: Commitment<G>,
+    #[serde_as(as = "Vec<Vec<Option<o1_utils::serialization::SerdeAs>>>")]
+    pub chunks: Vec<Vec<Option<G::ScalarField>>>,
+}
+
+impl<G: KimchiCurve> FieldBlob<G> {
+    /// Evaluate the polynomials of the blob over the domain extended by the
+    /// expansion factor of `rate`.
+    #[instrument(skip_all, level = "debug")]
+    pub fn extend(&self, rate: Rate) -> ExtendedFieldBlob<G> {
+        let extended_domain = extended_domain(self.domain_size, rate);
+        let chunks = self
+            .chunks
+            .par_iter()
+            .map(|p| {
+                p.evaluate_over_domain_by_ref(extended_domain)
+                    .evals
+                    .into_iter()
+                    .map(Some)
+                    .collect()
+            })
+            .collect();
+        ExtendedFieldBlob {
+            n_bytes: self.n_bytes,
+            domain_size: self.domain_size,
+            rate,
+            commitment: self.commitment.clone(),
+            chunks,
+        }
+    }
+}
+
+impl<G: KimchiCurve> ExtendedFieldBlob<G> {
+    /// Recover the polynomials of the blob from the available evaluations.
+    /// Each chunk needs at least `domain_size` evaluations, the other ones
+    /// are checked to be consistent with the recovered polynomial.
+    #[instrument(skip_all, level = "debug")]
+    pub fn reconstruct(&self) -> Result<FieldBlob<G>, ErasureError> {
+        let extended_domain = extended_domain(self.domain_size, self.rate);
+        let chunks = self
+            .chunks
+            .par_iter()
+            .enumerate()
+            .map(|(chunk, evals)| {
+                if evals.len() != extended_domain.size() {
+                    return Err(ErasureError::WrongNumberOfEvaluations {
+                        chunk,
+                        got: evals.len(),
+                        expected: extended_domain.size(),
+                    });
+                }
+                let p = interpolate_with_erasures(extended_domain, self.domain_size, evals)
+                    .map_err(|available| ErasureError::NotEnoughEvaluations {
+                        chunk,
+                        available,
+                        required: self.domain_size,
+                    })?;
+                let consistent = p
+                    .evaluate_over_domain_by_ref(extended_domain)
+                    .evals
+                    .iter()
+                    .zip(evals)
+                    .all(|(x, y)| y.map_or(true, |y| *x == y));
+                if consistent {
+                    Ok(p)
+                } else {
+                    Err(ErasureError::InconsistentEvaluations {
+                        chunk,
+                        domain_size: self.domain_size,
+                    })
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(FieldBlob {
+            n_bytes: self.n_bytes,
+            domain_size: self.domain_size,
+            commitment: self.commitment.clone(),
+            chunks,
+        })
+    }
+
+    /// Reconstruct the blob and decode the data it encodes
+    #[instrument(skip_all, level = "debug")]
+    pub fn decode(&self) -> Result<Vec<u8>, ErasureError> {
+        let blob = self.reconstruct()?;
+        let domain = Radix2EvaluationDomain::new(self.domain_size).unwrap();
+        Ok(FieldBlob::decode(domain, blob))
+    }
+}
+
+fn extended_domain<F: PrimeField>(domain_size: usize, rate: Rate) -> Radix2EvaluationDomain<F> {
+    Radix2EvaluationDomain::new(domain_size * rate.expansion_factor())
+        .expect("The extended domain is too large for the field")
+}
+
+/// The polynomial vanishing on the given points, computed with a product tree
+fn vanishing_polynomial<F: PrimeField>(points: &[F]) -> DensePolynomial<F> {
+    match points {
+        [] => DensePolynomial::from_coefficients_vec(vec![F::one()]),
+        [x] => DensePolynomial::from_coefficients_vec(vec![-*x, F::one()]),
+        _ => {
+            let (left, right) = points.split_at(points.len() / 2);
+            let (left, right) = rayon::join(
+                || vanishing_polynomial(left),
+                || vanishing_polynomial(right),
+            );
+            &left * &right
+        }
+    }
+}
+
+/// Interpolate the polynomial of degree less than `degree_bound` from its
+/// evaluations over `domain`, some of them being missing. Return the number of
+/// evaluations available if it is not enough.
+///
+/// With Z the polynomial vanishing on the missing points, the evaluations of
+/// P * Z are known over the whole domain and its degree is less than the size
+/// of the domain, so it can be interpolated. P is then obtained by dividing by
+/// Z over a coset of the domain, where Z does not vanish.
+fn interpolate_with_erasures<F: PrimeField>(
+    domain: Radix2EvaluationDomain<F>,
+    degree_bound: usize,
+    evals: &[Option<F>],
+) -> Result<DensePolynomial<F>, usize> {
+    let missing: Vec<F> = evals
+        .iter()
+        .zip(domain.elements())
+        .filter_map(|(eval, x)| eval.is_none().then_some(x))
+        .collect();
+    let available = evals.len() - missing.len();
+    if available < degree_bound {
+        return Err(available);
+    }
+    debug!(
+        "Interpolating from {} evaluations, {} missing",
+        available,
+        missing.len()
+    );
+
+    let z = vanishing_polynomial(&missing);
+    let z_evals = domain.fft(&z);
+    let pz_evals: Vec<F> = evals
+        .iter()
+        .zip(z_evals)
+        .map(|(eval, z)| eval.map_or(F::zero(), |eval| eval * z))
+        .collect();
+    let pz = domain.ifft(&pz_evals);
+
+    let coset = domain
+        .get_coset(F::GENERATOR)
+        .expect("The multiplicative generator defines a coset of the domain");
+    let mut z_coset_evals = coset.fft(&z);
+    batch_inversion(&mut z_coset_evals);
+    let p_coset_evals: Vec<F> = coset
+        .fft(&pz)
+        .into_iter()
+        .zip(z_coset_evals)
+        .map(|(pz, z_inv)| pz * z_inv)
+        .collect();
+    let mut p = coset.ifft(&p_coset_evals);
+    p.truncate(degree_bound);
+    Ok(DensePolynomial::from_coefficients_vec(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{env, utils::test_utils::UserData};
+    use ark_ff::{One, UniformRand, Zero};
+    use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+    use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSponge};
+    use once_cell::sync::Lazy;
+    use poly_commitment::{ipa::SRS, SRS as _};
+    use proptest::prelude::*;
+    use rand::{seq::index::sample, Rng};
+
+    type VestaFqSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+
+    static SRS: Lazy<SRS<Vesta>> = Lazy::new(|| {
+        if let Ok(srs) = std::env::var("SRS_FILEPATH") {
+            env::get_srs_from_cache(srs)
+        } else {
+            SRS::create(1 << 16)
+        }
+    });
+
+    static DOMAIN: Lazy<Radix2EvaluationDomain<Fp>> =
+        Lazy::new(|| Radix2EvaluationDomain::new(SRS.size()).unwrap());
+
+    // Erase the evaluations of each chunk, keeping only `kept` of them
+    fn erase(blob: &mut ExtendedFieldBlob<Vesta>, kept: usize) {
+        let mut rng = rand::thread_rng();
+        for evals in blob.chunks.iter_mut() {
+            let erased = evals.len() - kept;
+            for i in sample(&mut rng, evals.len(), erased) {
+                evals[i] = None;
+            }
+        }
+    }
+
+    #[test]
+    fn test_vanishing_polynomial() {
+        let mut rng = rand::thread_rng();
+        let points: Vec<Fp> = (0..37).map(|_| Fp::rand(&mut rng)).collect();
+        let z = vanishing_polynomial(&points);
+        assert_eq!(z.coeffs.len(), points.len() + 1);
+        for x in points {
+            assert!(ark_poly::Polynomial::evaluate(&z, &x).is_zero());
+        }
+    }
+
+    // check that the data can be decoded from any subset of evaluations of the right size
+    proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+    #[test]
+    fn test_decode_from_any_sufficient_subset(UserData(xs) in UserData::arbitrary(),
+                                              rate in prop_oneof![Just(Rate::Half), Just(Rate::Quarter)])
+      { let blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &xs);
+        let mut extended = blob.extend(rate);
+        prop_assert_eq!(extended.chunks[0].len(), DOMAIN.size() * rate.expansion_factor());
+        erase(&mut extended, DOMAIN.size());
+        let reconstructed = extended.reconstruct().unwrap();
+        // the polynomials, and therefore the commitments, are unchanged
+        prop_assert_eq!(&reconstructed, &blob);
+        let ys = extended.decode().unwrap();
+        prop_assert_eq!(xs, ys);
+      }
+    }
+
+    proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2))]
+    #[test]
+    fn test_cannot_decode_from_too_few_evaluations(UserData(xs) in UserData::arbitrary())
+      { let blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &xs);
+        let mut extended = blob.extend(Rate::Half);
+        erase(&mut extended, DOMAIN.size() - 1);
+        let res = extended.decode();
+        prop_assert!(
+            matches!(
+                res,
+                Err(ErasureError::NotEnoughEvaluations { available, required, .. })
+                    if available == DOMAIN.size() - 1 && required == DOMAIN.size()
+            ),
+            "unexpected result {:?}",
+            res.map(|_| ())
+        );
+      }
+    }
+
+    proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2))]
+    #[test]
+    fn test_detect_corrupted_evaluation(UserData(xs) in UserData::arbitrary())
+      { let blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &xs);
+        let mut extended = blob.extend(Rate::Half);
+        erase(&mut extended, DOMAIN.size() + 1);
+        let mut rng = rand::thread_rng();
+        let chunk = rng.gen_range(0..extended.chunks.len());
+        let corrupted = extended.chunks[chunk]
+            .iter_mut()
+            .flatten()
+            .nth(rng.gen_range(0..=DOMAIN.size()))
+            .unwrap();
+        *corrupted += Fp::one();
+        prop_assert_eq!(
+            extended.reconstruct(),
+            Err(ErasureError::InconsistentEvaluations {
+                chunk,
+                domain_size: DOMAIN.size()
+            })
+        );
+      }
+    }
+}
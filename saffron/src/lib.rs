@@ -3,5 +3,7 @@ pub mod cli;
 pub mod commitment;
 pub mod diff;
 pub mod env;
+pub mod erasure;
 pub mod proof;
+pub mod storage;
 pub mod utils;
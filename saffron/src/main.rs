@@ -1,19 +1,30 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use clap::Parser;
-use kimchi::groupmap::GroupMap;
+use kimchi::{curve::KimchiCurve, groupmap::GroupMap};
 use mina_curves::pasta::{Fp, Vesta, VestaParameters};
-use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSponge};
-use poly_commitment::{commitment::CommitmentCurve, ipa::SRS, SRS as _};
+use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSponge, FqSponge};
+use poly_commitment::{
+    commitment::CommitmentCurve,
+    ipa::{OpeningProof, SRS},
+    kzg::{KZGProof, PairingSRS},
+    OpenProof, SRS as _,
+};
 use rand::rngs::OsRng;
 use saffron::{
     blob::FieldBlob,
     cli::{self, HexString},
-    commitment::commit_to_field_elems,
+    commitment::{commit_to_diff, commit_to_field_elems, Commitment},
+    diff::Diff,
     env,
-    proof::{self, StorageProof},
-    utils,
+    erasure::ExtendedFieldBlob,
+    proof::{self, Sample, SamplingProof, StorageProof},
+    storage::BlobStore,
+    utils::{self, QueryBytes},
 };
+use serde::{de::DeserializeOwned, Serialize};
 use std::{
     fs::File,
     io::{Read, Write},
@@ -23,6 +34,88 @@ use tracing::{debug, debug_span};
 pub const DEFAULT_SRS_SIZE: usize = 1 << 16;
 
 type VestaFqSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+type BN254FqSponge = DefaultFqSponge<ark_bn254::g1::Config, PlonkSpongeConstantsKimchi>;
+
+type ScalarField<B> = <<B as Backend>::Curve as AffineRepr>::ScalarField;
+type BackendSRS<B> = <<B as Backend>::OpeningProof as OpenProof<<B as Backend>::Curve>>::SRS;
+
+/// The curve, the polynomial commitment scheme and the sponge used by the
+/// commands, see [cli::Backend]
+pub trait Backend {
+    type BaseField: PrimeField;
+    type Curve: KimchiCurve<BaseField = Self::BaseField>;
+    type OpeningProof: OpenProof<Self::Curve> + Serialize + DeserializeOwned;
+    type FqSponge: Clone + FqSponge<Self::BaseField, Self::Curve, ScalarField<Self>>;
+
+    /// The SRS, read from the cache if given, and the domain of its size.
+    /// Without a cache, an SRS with a trusted setup is only created if
+    /// `insecure_dev_srs` is set, as anybody can forge proofs for it.
+    fn srs(
+        cache: Option<String>,
+        insecure_dev_srs: bool,
+    ) -> Result<(BackendSRS<Self>, Radix2EvaluationDomain<ScalarField<Self>>)>;
+
+    /// The maximum number of distinct points a single opening can be verified
+    /// at with the given SRS, if any.
+    fn max_opening_points(srs: &BackendSRS<Self>) -> Option<usize>;
+}
+
+/// Fail if the sample cannot be opened with the SRS of the backend, instead of
+/// producing a proof that never verifies.
+fn check_sample_size<B: Backend>(srs: &BackendSRS<B>, sample: &Sample) -> Result<()> {
+    match B::max_opening_points(srs) {
+        Some(max) if sample.opening_points() > max => Err(anyhow!(
+            "the backend can open a chunk at up to {} points at once, got {}",
+            max,
+            sample.opening_points()
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub struct Ipa;
+
+impl Backend for Ipa {
+    type BaseField = <Vesta as AffineRepr>::BaseField;
+    type Curve = Vesta;
+    type OpeningProof = OpeningProof<Vesta>;
+    type FqSponge = VestaFqSponge;
+
+    fn srs(
+        cache: Option<String>,
+        _insecure_dev_srs: bool,
+    ) -> Result<(SRS<Vesta>, Radix2EvaluationDomain<Fp>)> {
+        // The IPA does not need a trusted setup
+        Ok(get_srs(cache))
+    }
+
+    fn max_opening_points(_srs: &SRS<Vesta>) -> Option<usize> {
+        None
+    }
+}
+
+pub struct Kzg;
+
+impl Backend for Kzg {
+    type BaseField = ark_bn254::Fq;
+    type Curve = ark_bn254::G1Affine;
+    type OpeningProof = KZGProof<ark_bn254::Bn254>;
+    type FqSponge = BN254FqSponge;
+
+    fn srs(
+        cache: Option<String>,
+        insecure_dev_srs: bool,
+    ) -> Result<(
+        PairingSRS<ark_bn254::Bn254>,
+        Radix2EvaluationDomain<ark_bn254::Fr>,
+    )> {
+        get_kzg_srs(cache, insecure_dev_srs)
+    }
+
+    fn max_opening_points(srs: &PairingSRS<ark_bn254::Bn254>) -> Option<usize> {
+        Some(srs.max_evaluation_points())
+    }
+}
 
 fn get_srs(cache: Option<String>) -> (SRS<Vesta>, Radix2EvaluationDomain<Fp>) {
     let res = match cache {
@@ -51,24 +144,55 @@ fn get_srs(cache: Option<String>) -> (SRS<Vesta>, Radix2EvaluationDomain<Fp>) {
     res
 }
 
-fn decode_file(args: cli::DecodeFileArgs) -> Result<()> {
-    let (_, domain) = get_srs(args.srs_cache);
+fn get_kzg_srs(
+    cache: Option<String>,
+    insecure_dev_srs: bool,
+) -> Result<(
+    PairingSRS<ark_bn254::Bn254>,
+    Radix2EvaluationDomain<ark_bn254::Fr>,
+)> {
+    let srs = match cache {
+        Some(cache) => env::get_pairing_srs_from_cache(cache),
+        None if insecure_dev_srs => {
+            debug!(
+                "No SRS cache provided. Creating an insecure KZG SRS with domain size {}",
+                DEFAULT_SRS_SIZE
+            );
+            env::create_insecure_pairing_srs(DEFAULT_SRS_SIZE)
+        }
+        None => {
+            return Err(anyhow!(
+                "the kzg backend needs a trusted SRS, use --srs-filepath, or --insecure-dev-srs for testing"
+            ))
+        }
+    };
+    let domain = Radix2EvaluationDomain::new(srs.size()).unwrap();
+
+    debug_span!("get_lagrange_basis", basis_size = srs.size()).in_scope(|| {
+        srs.get_lagrange_basis(domain);
+    });
+
+    Ok((srs, domain))
+}
+
+fn decode_file<B: Backend>(args: cli::DecodeFileArgs) -> Result<()> {
+    let (_, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
     debug!(
         domain_size = domain.size(),
         input_file = args.input,
         "Decoding file"
     );
     let file = File::open(args.input)?;
-    let blob: FieldBlob<Vesta> = rmp_serde::decode::from_read(file)?;
-    let data = FieldBlob::<Vesta>::decode(domain, blob);
+    let blob: FieldBlob<B::Curve> = rmp_serde::decode::from_read(file)?;
+    let data = FieldBlob::<B::Curve>::decode(domain, blob);
     debug!(output_file = args.output, "Writing decoded blob to file");
     let mut writer = File::create(args.output)?;
     writer.write_all(&data)?;
     Ok(())
 }
 
-fn encode_file(args: cli::EncodeFileArgs) -> Result<()> {
-    let (srs, domain) = get_srs(args.srs_cache);
+fn encode_file<B: Backend>(args: cli::EncodeFileArgs) -> Result<()> {
+    let (srs, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
     debug!(
         domain_size = domain.size(),
         input_file = args.input,
@@ -77,7 +201,7 @@ fn encode_file(args: cli::EncodeFileArgs) -> Result<()> {
     let mut file = File::open(args.input)?;
     let mut buf = Vec::new();
     file.read_to_end(&mut buf)?;
-    let blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&srs, domain, &buf);
+    let blob = FieldBlob::<B::Curve>::encode::<_, B::FqSponge>(&srs, domain, &buf);
     if let Some(asserted) = args.assert_commitment {
         let asserted_commitment =
             rmp_serde::from_slice(&asserted.0).expect("failed to decode asserted commitment");
@@ -95,12 +219,34 @@ fn encode_file(args: cli::EncodeFileArgs) -> Result<()> {
     };
     debug!(output_file = args.output, "Writing encoded blob to file",);
     let mut writer = File::create(args.output)?;
+    match args.rate {
+        None => rmp_serde::encode::write(&mut writer, &blob)?,
+        Some(rate) => rmp_serde::encode::write(&mut writer, &blob.extend(rate))?,
+    }
+    Ok(())
+}
+
+fn reconstruct<B: Backend>(args: cli::ReconstructArgs) -> Result<()> {
+    let file = File::open(args.input)?;
+    let extended: ExtendedFieldBlob<B::Curve> = rmp_serde::decode::from_read(file)?;
+    let lost: usize = extended
+        .chunks
+        .iter()
+        .map(|evals| evals.iter().filter(|eval| eval.is_none()).count())
+        .sum();
+    debug!(
+        chunks = extended.chunks.len(),
+        lost, "Reconstructing the encoded blob"
+    );
+    let blob = extended.reconstruct()?;
+    debug!(output_file = args.output, "Writing encoded blob to file");
+    let mut writer = File::create(args.output)?;
     rmp_serde::encode::write(&mut writer, &blob)?;
     Ok(())
 }
 
-pub fn compute_commitment(args: cli::ComputeCommitmentArgs) -> Result<HexString> {
-    let (srs, domain_fp) = get_srs(args.srs_cache);
+pub fn compute_commitment<B: Backend>(args: cli::ComputeCommitmentArgs) -> Result<HexString> {
+    let (srs, domain_fp) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
     let buf = {
         let mut file = File::open(args.input)?;
         let mut buf = Vec::new();
@@ -109,7 +255,7 @@ pub fn compute_commitment(args: cli::ComputeCommitmentArgs) -> Result<HexString>
     };
     let commitment = {
         let field_elems = utils::encode_for_domain(&domain_fp, &buf);
-        commit_to_field_elems::<_, VestaFqSponge>(&srs, domain_fp, field_elems)
+        commit_to_field_elems::<_, B::FqSponge>(&srs, domain_fp, field_elems)
     };
     {
         let mut writer = File::create(args.output)?;
@@ -119,15 +265,15 @@ pub fn compute_commitment(args: cli::ComputeCommitmentArgs) -> Result<HexString>
     Ok(HexString(c))
 }
 
-pub fn storage_proof(args: cli::StorageProofArgs) -> Result<HexString> {
+pub fn storage_proof<B: Backend>(args: cli::StorageProofArgs) -> Result<HexString> {
     let file = File::open(args.input)?;
-    let blob: FieldBlob<Vesta> = rmp_serde::decode::from_read(file)?;
+    let blob: FieldBlob<B::Curve> = rmp_serde::decode::from_read(file)?;
     let proof = {
-        let (srs, _) = get_srs(args.srs_cache);
-        let group_map = <Vesta as CommitmentCurve>::Map::setup();
+        let (srs, _) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+        let group_map = <B::Curve as CommitmentCurve>::Map::setup();
         let mut rng = OsRng;
         let evaluation_point = utils::encode(&args.challenge.0);
-        proof::storage_proof::<Vesta, VestaFqSponge>(
+        proof::storage_proof::<B::Curve, B::OpeningProof, B::FqSponge>(
             &srs,
             &group_map,
             blob,
@@ -139,14 +285,14 @@ pub fn storage_proof(args: cli::StorageProofArgs) -> Result<HexString> {
     Ok(HexString(res))
 }
 
-pub fn verify_storage_proof(args: cli::VerifyStorageProofArgs) -> Result<()> {
-    let (srs, _) = get_srs(args.srs_cache);
-    let group_map = <Vesta as CommitmentCurve>::Map::setup();
+pub fn verify_storage_proof<B: Backend>(args: cli::VerifyStorageProofArgs) -> Result<()> {
+    let (srs, _) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let group_map = <B::Curve as CommitmentCurve>::Map::setup();
     let commitment = rmp_serde::from_slice(&args.commitment.0)?;
     let evaluation_point = utils::encode(&args.challenge.0);
-    let proof: StorageProof<Vesta> = rmp_serde::from_slice(&args.proof.0)?;
+    let proof: StorageProof<B::Curve, B::OpeningProof> = rmp_serde::from_slice(&args.proof.0)?;
     let mut rng = OsRng;
-    let res = proof::verify_storage_proof::<Vesta, VestaFqSponge>(
+    let res = proof::verify_storage_proof::<B::Curve, B::OpeningProof, B::FqSponge>(
         &srs,
         &group_map,
         commitment,
@@ -154,26 +300,234 @@ pub fn verify_storage_proof(args: cli::VerifyStorageProofArgs) -> Result<()> {
         &proof,
         &mut rng,
     );
-    assert!(res);
+    if !res {
+        return Err(anyhow!("the storage proof is invalid"));
+    }
+    Ok(())
+}
+
+fn sampling_seed(seed: &HexString) -> Result<[u8; 32]> {
+    seed.0
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("the seed must be 32 bytes long, got {}", seed.0.len()))
+}
+
+pub fn sample<B: Backend>(args: cli::SampleArgs) -> Result<HexString> {
+    let seed = sampling_seed(&args.seed)?;
+    let file = File::open(args.input)?;
+    let blob: FieldBlob<B::Curve> = rmp_serde::decode::from_read(file)?;
+    if blob.chunks.is_empty() {
+        return Err(anyhow!("cannot sample an empty blob"));
+    }
+    let (srs, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let sample = Sample::random(seed, blob.chunks.len(), domain.size(), args.samples);
+    check_sample_size::<B>(&srs, &sample)?;
+    debug!(
+        chunks = sample.openings().len(),
+        positions = sample.positions.len(),
+        "Opening the sampled positions"
+    );
+    let group_map = <B::Curve as CommitmentCurve>::Map::setup();
+    let mut rng = OsRng;
+    let proof = proof::sampling_proof::<B::Curve, B::OpeningProof, B::FqSponge>(
+        &srs, &group_map, domain, &blob, &sample, &mut rng,
+    );
+    let res = rmp_serde::to_vec(&proof)?;
+    Ok(HexString(res))
+}
+
+pub fn verify_sample<B: Backend>(args: cli::VerifySampleArgs) -> Result<()> {
+    let seed = sampling_seed(&args.seed)?;
+    let commitment: Commitment<B::Curve> = {
+        let file = File::open(args.commitment_file)?;
+        rmp_serde::decode::from_read(file)?
+    };
+    if commitment.chunks.is_empty() {
+        return Err(anyhow!("cannot sample an empty blob"));
+    }
+    let (srs, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let sample = Sample::random(seed, commitment.chunks.len(), domain.size(), args.samples);
+    check_sample_size::<B>(&srs, &sample)?;
+    let proof: SamplingProof<B::Curve, B::OpeningProof> = rmp_serde::from_slice(&args.proof.0)?;
+    let group_map = <B::Curve as CommitmentCurve>::Map::setup();
+    let mut rng = OsRng;
+    let res = proof::verify_sampling_proof::<B::Curve, B::OpeningProof, B::FqSponge>(
+        &srs,
+        &group_map,
+        domain,
+        &commitment,
+        &sample,
+        &proof,
+        &mut rng,
+    );
+    if !res {
+        return Err(anyhow!("the sampling proof is invalid"));
+    }
+    Ok(())
+}
+
+pub fn store<B: Backend>(args: cli::StoreArgs) -> Result<()> {
+    let file = File::open(args.input)?;
+    let blob: FieldBlob<B::Curve> = rmp_serde::decode::from_read(file)?;
+    let (_, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let store = BlobStore::create(args.store_dir)?;
+    store.put(&args.name, domain, &blob)?;
+    Ok(())
+}
+
+pub fn read_range<B: Backend>(args: cli::ReadRangeArgs) -> Result<HexString> {
+    let store = BlobStore::create(args.store_dir)?;
+    let blob = store.open::<B::Curve>(&args.name)?;
+    let (srs, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let query = QueryBytes {
+        start: args.start,
+        len: args.len,
+    };
+    let (_, sample) =
+        proof::range_sample::<ScalarField<B>>(&query, domain.size(), blob.metadata.n_chunks())?;
+    check_sample_size::<B>(&srs, &sample)?;
+    let group_map = <B::Curve as CommitmentCurve>::Map::setup();
+    let mut rng = OsRng;
+    let (bytes, proof) = blob.read_range_with_proof::<B::OpeningProof, B::FqSponge>(
+        &srs, &group_map, domain, &query, &mut rng,
+    )?;
+    let mut writer = File::create(args.output)?;
+    writer.write_all(&bytes)?;
+    let res = rmp_serde::to_vec(&proof)?;
+    Ok(HexString(res))
+}
+
+pub fn verify_range<B: Backend>(args: cli::VerifyRangeArgs) -> Result<()> {
+    let commitment: Commitment<B::Curve> = {
+        let file = File::open(args.commitment_file)?;
+        rmp_serde::decode::from_read(file)?
+    };
+    let bytes = std::fs::read(args.data)?;
+    let (srs, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let query = QueryBytes {
+        start: args.start,
+        len: args.len,
+    };
+    let (_, sample) =
+        proof::range_sample::<ScalarField<B>>(&query, domain.size(), commitment.chunks.len())?;
+    check_sample_size::<B>(&srs, &sample)?;
+    let proof: SamplingProof<B::Curve, B::OpeningProof> = rmp_serde::from_slice(&args.proof.0)?;
+    let group_map = <B::Curve as CommitmentCurve>::Map::setup();
+    let mut rng = OsRng;
+    let res = proof::verify_range_proof::<B::Curve, B::OpeningProof, B::FqSponge>(
+        &srs,
+        &group_map,
+        domain,
+        &commitment,
+        &query,
+        &bytes,
+        &proof,
+        &mut rng,
+    );
+    if !res {
+        return Err(anyhow!("the range proof is invalid"));
+    }
+    Ok(())
+}
+
+pub fn diff<B: Backend>(args: cli::DiffArgs) -> Result<()> {
+    let (_, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let old = std::fs::read(args.old)?;
+    let new = std::fs::read(args.new)?;
+    let diff = Diff::<ScalarField<B>>::create(&domain, &old, &new);
+    debug!(output_file = args.output, "Writing diff to file");
+    std::fs::write(args.output, diff.to_bytes())?;
+    Ok(())
+}
+
+pub fn apply_diff<B: Backend>(args: cli::ApplyDiffArgs) -> Result<HexString> {
+    let (srs, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let mut blob: FieldBlob<B::Curve> = {
+        let file = File::open(&args.input)?;
+        rmp_serde::decode::from_read(file)?
+    };
+    let diff = Diff::<ScalarField<B>>::from_bytes(&std::fs::read(args.diff)?)?;
+    blob.update::<B::FqSponge>(&srs, &domain, diff);
+    debug!(output_file = args.input, "Writing updated blob to file");
+    let mut writer = File::create(args.input)?;
+    rmp_serde::encode::write(&mut writer, &blob)?;
+    let c = rmp_serde::encode::to_vec(&blob.commitment.folded)?;
+    Ok(HexString(c))
+}
+
+pub fn verify_diff<B: Backend>(args: cli::VerifyDiffArgs) -> Result<()> {
+    let (srs, domain) = B::srs(args.srs_cache, args.insecure_dev_srs)?;
+    let commitment: Commitment<B::Curve> = {
+        let file = File::open(args.commitment_file)?;
+        rmp_serde::decode::from_read(file)?
+    };
+    let diff = Diff::<ScalarField<B>>::from_bytes(&std::fs::read(args.diff)?)?;
+    let new_folded = rmp_serde::from_slice(&args.new_commitment.0)?;
+    let diff_commitments = commit_to_diff(&srs, domain, &diff);
+    let mut sponge = B::FqSponge::new(B::Curve::other_curve_sponge_params());
+    if !commitment.verify_update(diff_commitments.clone(), &new_folded, &mut sponge) {
+        return Err(anyhow!(
+            "commitment mismatch: the diff does not update the commitment to {}",
+            args.new_commitment
+        ));
+    }
+    if let Some(output) = args.output {
+        let new_commitment = {
+            let mut sponge = B::FqSponge::new(B::Curve::other_curve_sponge_params());
+            commitment.update(diff_commitments, &mut sponge)
+        };
+        let mut writer = File::create(output)?;
+        rmp_serde::encode::write(&mut writer, &new_commitment)?;
+    }
     Ok(())
 }
 
 pub fn main() -> Result<()> {
     env::init_console_subscriber();
     let args = cli::Commands::parse();
+    macro_rules! with_backend {
+        ($f:ident($args:expr)) => {
+            match $args.backend {
+                cli::Backend::Ipa => $f::<Ipa>($args),
+                cli::Backend::Kzg => $f::<Kzg>($args),
+            }
+        };
+    }
     match args {
-        cli::Commands::Encode(args) => encode_file(args),
-        cli::Commands::Decode(args) => decode_file(args),
+        cli::Commands::Encode(args) => with_backend!(encode_file(args)),
+        cli::Commands::Decode(args) => with_backend!(decode_file(args)),
+        cli::Commands::Reconstruct(args) => with_backend!(reconstruct(args)),
         cli::Commands::ComputeCommitment(args) => {
-            let commitment = compute_commitment(args)?;
+            let commitment = with_backend!(compute_commitment(args))?;
             println!("{}", commitment);
             Ok(())
         }
         cli::Commands::StorageProof(args) => {
-            let proof = storage_proof(args)?;
+            let proof = with_backend!(storage_proof(args))?;
+            println!("{}", proof);
+            Ok(())
+        }
+        cli::Commands::VerifyStorageProof(args) => with_backend!(verify_storage_proof(args)),
+        cli::Commands::Sample(args) => {
+            let proof = with_backend!(sample(args))?;
+            println!("{}", proof);
+            Ok(())
+        }
+        cli::Commands::VerifySample(args) => with_backend!(verify_sample(args)),
+        cli::Commands::Store(args) => with_backend!(store(args)),
+        cli::Commands::ReadRange(args) => {
+            let proof = with_backend!(read_range(args))?;
             println!("{}", proof);
             Ok(())
         }
-        cli::Commands::VerifyStorageProof(args) => verify_storage_proof(args),
+        cli::Commands::VerifyRange(args) => with_backend!(verify_range(args)),
+        cli::Commands::Diff(args) => with_backend!(diff(args)),
+        cli::Commands::ApplyDiff(args) => {
+            let commitment = with_backend!(apply_diff(args))?;
+            println!("{}", commitment);
+            Ok(())
+        }
+        cli::Commands::VerifyDiff(args) => with_backend!(verify_diff(args)),
     }
 }
@@ -1,18 +1,27 @@
-use crate::blob::FieldBlob;
+use crate::{
+    blob::FieldBlob,
+    commitment::Commitment,
+    utils::{QueryBytes, QueryError, QueryField},
+};
 use ark_ec::AffineRepr;
 use ark_ff::{One, PrimeField, Zero};
-use ark_poly::{univariate::DensePolynomial, Polynomial, Radix2EvaluationDomain as D};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain as D,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use kimchi::curve::KimchiCurve;
 use mina_poseidon::FqSponge;
 use o1_utils::ExtendedDensePolynomial;
 use poly_commitment::{
-    commitment::{BatchEvaluationProof, CommitmentCurve, Evaluation},
-    ipa::{OpeningProof, SRS},
+    commitment::{
+        absorb_commitment, combined_inner_product, BatchEvaluationProof, CommitmentCurve,
+        Evaluation,
+    },
     utils::DensePolynomialOrEvaluations,
-    PolyComm,
+    OpenProof, PolyComm,
 };
-use rand::rngs::OsRng;
+use rand::{rngs::OsRng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use tracing::instrument;
@@ -20,20 +29,28 @@ use tracing::instrument;
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(bound = "G::ScalarField : CanonicalDeserialize + CanonicalSerialize")]
-pub struct StorageProof<G: CommitmentCurve> {
+pub struct StorageProof<G: CommitmentCurve, OpeningProof> {
     #[serde_as(as = "o1_utils::serialization::SerdeAs")]
     pub evaluation: G::ScalarField,
-    pub opening_proof: OpeningProof<G>,
+    #[serde(bound(
+        serialize = "OpeningProof: Serialize",
+        deserialize = "OpeningProof: Deserialize<'de>"
+    ))]
+    pub opening_proof: OpeningProof,
 }
 
 #[instrument(skip_all, level = "debug")]
-pub fn storage_proof<G: KimchiCurve, EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
-    srs: &SRS<G>,
+pub fn storage_proof<
+    G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+>(
+    srs: &OpeningProof::SRS,
     group_map: &G::Map,
     blob: FieldBlob<G>,
     evaluation_point: G::ScalarField,
     rng: &mut OsRng,
-) -> StorageProof<G>
+) -> StorageProof<G, OpeningProof>
 where
     G::BaseField: PrimeField,
 {
@@ -57,7 +74,8 @@ where
         sponge.absorb_fr(&[evaluation]);
         sponge
     };
-    let opening_proof = srs.open(
+    let opening_proof = OpeningProof::open(
+            srs,
             group_map,
             &[(
                 DensePolynomialOrEvaluations::<<G as AffineRepr>::ScalarField, D<G::ScalarField>> ::DensePolynomial(
@@ -82,13 +100,14 @@ where
 #[instrument(skip_all, level = "debug")]
 pub fn verify_storage_proof<
     G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
     EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
 >(
-    srs: &SRS<G>,
+    srs: &OpeningProof::SRS,
     group_map: &G::Map,
     commitment: PolyComm<G>,
     evaluation_point: G::ScalarField,
-    proof: &StorageProof<G>,
+    proof: &StorageProof<G, OpeningProof>,
     rng: &mut OsRng,
 ) -> bool
 where
@@ -97,7 +116,8 @@ where
     let mut opening_proof_sponge = EFqSponge::new(G::other_curve_sponge_params());
     opening_proof_sponge.absorb_fr(&[proof.evaluation]);
 
-    srs.verify(
+    OpeningProof::verify(
+        srs,
         group_map,
         &mut [BatchEvaluationProof {
             sponge: opening_proof_sponge.clone(),
@@ -115,24 +135,378 @@ where
     )
 }
 
+/// The positions sampled by a light client to check the availability of a
+/// blob. A position is the evaluation of a chunk at a root of unity of the
+/// domain, i.e. a field element of the encoded data. Each sampled chunk is
+/// opened at its own sampled roots of unity only, so that the proof grows
+/// with the number of positions, and not with the number of sampled chunks
+/// times the number of sampled roots of unity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    /// The sampled positions, given as the index of the chunk and the index of
+    /// the root of unity, sorted and without duplicates
+    pub positions: Vec<(usize, usize)>,
+}
+
+impl Sample {
+    /// Draw `n_positions` random positions in a blob of `n_chunks` chunks over
+    /// a domain of size `domain_size`. The positions only depend on the seed,
+    /// so that the prover and the verifier agree on them. The blob must have
+    /// at least one chunk.
+    pub fn random(seed: [u8; 32], n_chunks: usize, domain_size: usize, n_positions: usize) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        Self::from_positions(
+            (0..n_positions).map(|_| (rng.gen_range(0..n_chunks), rng.gen_range(0..domain_size))),
+        )
+    }
+
+    /// The sample covering the given positions, given as the index of the
+    /// chunk and the index of the root of unity
+    pub fn from_positions<I: IntoIterator<Item = (usize, usize)>>(positions: I) -> Self {
+        let mut positions: Vec<_> = positions.into_iter().collect();
+        positions.sort_unstable();
+        positions.dedup();
+        Sample { positions }
+    }
+
+    /// The sampled chunks, each with the sorted indices of its sampled roots
+    /// of unity. Each of them is opened separately.
+    pub fn openings(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut openings: Vec<(usize, Vec<usize>)> = vec![];
+        for (chunk, index) in self.positions.iter() {
+            match openings.last_mut() {
+                Some((last, indices)) if last == chunk => indices.push(*index),
+                _ => openings.push((*chunk, vec![*index])),
+            }
+        }
+        openings
+    }
+
+    /// The largest number of distinct points a sampled chunk is opened at
+    pub fn opening_points(&self) -> usize {
+        self.openings()
+            .iter()
+            .map(|(_, indices)| indices.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "G::ScalarField : CanonicalDeserialize + CanonicalSerialize")]
+pub struct SamplingProof<G: CommitmentCurve, OpeningProof> {
+    /// For each sampled chunk, its evaluations at its sampled roots of unity,
+    /// in the order of [Sample::openings]
+    #[serde_as(as = "Vec<Vec<o1_utils::serialization::SerdeAs>>")]
+    pub evaluations: Vec<Vec<G::ScalarField>>,
+    /// For each sampled chunk, the opening at its sampled roots of unity
+    #[serde(bound(
+        serialize = "OpeningProof: Serialize",
+        deserialize = "OpeningProof: Deserialize<'de>"
+    ))]
+    pub opening_proofs: Vec<OpeningProof>,
+}
+
+impl<G: CommitmentCurve, OpeningProof> SamplingProof<G, OpeningProof> {
+    /// The evaluations at the sampled positions, in the order of
+    /// [Sample::positions]
+    pub fn sampled_evaluations(&self) -> impl Iterator<Item = &G::ScalarField> {
+        self.evaluations.iter().flatten()
+    }
+}
+
+/// The sponge used to open a sampled chunk, and the scalars combining the
+/// evaluation points. They are derived from the index and the commitment of
+/// the chunk, the evaluation points and the evaluations, so that the
+/// challenges depend on everything the opening claims.
+fn sampling_sponge<G: KimchiCurve, EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>>(
+    chunk: usize,
+    commitment: &PolyComm<G>,
+    evaluation_points: &[G::ScalarField],
+    evaluations: &[G::ScalarField],
+) -> (EFqSponge, G::ScalarField, G::ScalarField) {
+    let mut sponge = EFqSponge::new(G::other_curve_sponge_params());
+    sponge.absorb_fr(&[G::ScalarField::from(chunk as u64)]);
+    absorb_commitment(&mut sponge, commitment);
+    sponge.absorb_fr(evaluation_points);
+    sponge.absorb_fr(evaluations);
+    let polyscale = sponge.challenge();
+    let evalscale = sponge.challenge();
+    (sponge, polyscale, evalscale)
+}
+
+/// Prove the evaluations of the blob at the sampled positions
+#[instrument(skip_all, level = "debug")]
+pub fn sampling_proof<
+    G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+>(
+    srs: &OpeningProof::SRS,
+    group_map: &G::Map,
+    domain: D<G::ScalarField>,
+    blob: &FieldBlob<G>,
+    sample: &Sample,
+    rng: &mut OsRng,
+) -> SamplingProof<G, OpeningProof>
+where
+    G::BaseField: PrimeField,
+{
+    let openings = sample.openings();
+    let evaluations: Vec<Vec<G::ScalarField>> = openings
+        .iter()
+        .map(|(chunk, indices)| {
+            indices
+                .iter()
+                .map(|j| blob.chunks[*chunk].evaluate(&domain.element(*j)))
+                .collect()
+        })
+        .collect();
+    let polys: Vec<_> = openings
+        .iter()
+        .map(|(chunk, _)| DensePolynomialOrEvaluations::DensePolynomial(&blob.chunks[*chunk]))
+        .collect();
+    open_sample::<G, OpeningProof, EFqSponge>(
+        srs,
+        group_map,
+        domain,
+        polys,
+        &blob.commitment,
+        sample,
+        evaluations,
+        rng,
+    )
+}
+
+/// Open the sampled chunks of a blob with the given commitment at their
+/// sampled roots of unity, given the chunks and their evaluations there, in
+/// the order of [Sample::openings].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn open_sample<
+    G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+>(
+    srs: &OpeningProof::SRS,
+    group_map: &G::Map,
+    domain: D<G::ScalarField>,
+    polys: Vec<DensePolynomialOrEvaluations<G::ScalarField, D<G::ScalarField>>>,
+    commitment: &Commitment<G>,
+    sample: &Sample,
+    evaluations: Vec<Vec<G::ScalarField>>,
+    rng: &mut OsRng,
+) -> SamplingProof<G, OpeningProof>
+where
+    G::BaseField: PrimeField,
+{
+    let opening_proofs = sample
+        .openings()
+        .into_iter()
+        .zip(polys)
+        .zip(evaluations.iter())
+        .map(|(((chunk, indices), poly), evals)| {
+            let evaluation_points: Vec<G::ScalarField> =
+                indices.iter().map(|i| domain.element(*i)).collect();
+            let (sponge, polyscale, evalscale) = sampling_sponge::<G, EFqSponge>(
+                chunk,
+                &commitment.chunks[chunk],
+                &evaluation_points,
+                evals,
+            );
+            OpeningProof::open(
+                srs,
+                group_map,
+                &[(
+                    poly,
+                    PolyComm {
+                        chunks: vec![G::ScalarField::zero()],
+                    },
+                )],
+                &evaluation_points,
+                polyscale,
+                evalscale,
+                sponge,
+                rng,
+            )
+        })
+        .collect();
+    SamplingProof {
+        evaluations,
+        opening_proofs,
+    }
+}
+
+/// Verify the evaluations of the blob with the given commitment at the
+/// sampled positions. The commitments to the chunks are checked against the
+/// folded commitment, and the openings of all the sampled chunks are verified
+/// in a single batch.
+#[instrument(skip_all, level = "debug")]
+pub fn verify_sampling_proof<
+    G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+>(
+    srs: &OpeningProof::SRS,
+    group_map: &G::Map,
+    domain: D<G::ScalarField>,
+    commitment: &Commitment<G>,
+    sample: &Sample,
+    proof: &SamplingProof<G, OpeningProof>,
+    rng: &mut OsRng,
+) -> bool
+where
+    G::BaseField: PrimeField,
+{
+    let openings = sample.openings();
+    let well_formed = proof.evaluations.len() == openings.len()
+        && proof.opening_proofs.len() == openings.len()
+        && openings
+            .iter()
+            .zip(proof.evaluations.iter())
+            .all(|((chunk, indices), evals)| {
+                *chunk < commitment.chunks.len() && evals.len() == indices.len()
+            });
+    if !well_formed {
+        return false;
+    }
+    let folded = {
+        let mut sponge = EFqSponge::new(G::other_curve_sponge_params());
+        Commitment::from_chunks(commitment.chunks.clone(), &mut sponge)
+    };
+    if folded != *commitment {
+        return false;
+    }
+    let mut batch: Vec<_> = openings
+        .into_iter()
+        .zip(proof.evaluations.iter())
+        .zip(proof.opening_proofs.iter())
+        .map(|(((chunk, indices), evals), opening)| {
+            let evaluation_points: Vec<G::ScalarField> =
+                indices.iter().map(|i| domain.element(*i)).collect();
+            let (sponge, polyscale, evalscale) = sampling_sponge::<G, EFqSponge>(
+                chunk,
+                &commitment.chunks[chunk],
+                &evaluation_points,
+                evals,
+            );
+            let evaluations = vec![Evaluation {
+                commitment: commitment.chunks[chunk].clone(),
+                evaluations: evals.iter().map(|e| vec![*e]).collect(),
+            }];
+            let combined_inner_product = combined_inner_product(
+                &polyscale,
+                &evalscale,
+                &[evaluations[0].evaluations.clone()],
+            );
+            BatchEvaluationProof {
+                sponge,
+                evaluation_points,
+                polyscale,
+                evalscale,
+                evaluations,
+                opening,
+                combined_inner_product,
+            }
+        })
+        .collect();
+    OpeningProof::verify(srs, group_map, &mut batch, rng)
+}
+
+/// The maximum number of field elements encoding a range of bytes read with a
+/// proof, i.e. about 31 KB. Opening a chunk at `k` points costs `O(k * n)` for a
+/// domain of size `n` with the IPA, so longer ranges must be read in several
+/// queries.
+pub const MAX_RANGE_POSITIONS: usize = 1 << 10;
+
+/// The sample covering the field elements encoding a range of bytes of a blob
+/// of `n_chunks` chunks over a domain of size `domain_size`. The range must be
+/// encoded by at most [MAX_RANGE_POSITIONS] field elements.
+pub fn range_sample<F: PrimeField>(
+    query: &QueryBytes,
+    domain_size: usize,
+    n_chunks: usize,
+) -> Result<(QueryField<F>, Sample), QueryError> {
+    let query_field = query.into_query_field(domain_size, n_chunks)?;
+    let positions = query_field.positions();
+    if positions.len() > MAX_RANGE_POSITIONS {
+        return Err(QueryError::RangeTooLong {
+            positions: positions.len(),
+            max: MAX_RANGE_POSITIONS,
+        });
+    }
+    let sample = Sample::from_positions(positions);
+    Ok((query_field, sample))
+}
+
+/// Verify that `bytes` is the range `query` of the blob with the given
+/// commitment, given the openings of the field elements encoding the range.
+#[instrument(skip_all, level = "debug")]
+#[allow(clippy::too_many_arguments)]
+pub fn verify_range_proof<
+    G: KimchiCurve,
+    OpeningProof: OpenProof<G>,
+    EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+>(
+    srs: &OpeningProof::SRS,
+    group_map: &G::Map,
+    domain: D<G::ScalarField>,
+    commitment: &Commitment<G>,
+    query: &QueryBytes,
+    bytes: &[u8],
+    proof: &SamplingProof<G, OpeningProof>,
+    rng: &mut OsRng,
+) -> bool
+where
+    G::BaseField: PrimeField,
+{
+    let Ok((query_field, sample)) =
+        range_sample::<G::ScalarField>(query, domain.size(), commitment.chunks.len())
+    else {
+        return false;
+    };
+    if !verify_sampling_proof::<G, OpeningProof, EFqSponge>(
+        srs, group_map, domain, commitment, &sample, proof, rng,
+    ) {
+        return false;
+    }
+    // The proof is well formed, so all the positions are in the sample
+    let evaluations: Vec<_> = proof.sampled_evaluations().collect();
+    let opened = query_field.apply_with(|chunk, index| {
+        let i = sample.positions.binary_search(&(chunk, index)).unwrap();
+        *evaluations[i]
+    });
+    opened == bytes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         commitment::commit_to_field_elems,
         env,
-        utils::{encode_for_domain, test_utils::UserData},
+        utils::{
+            decode_into, encode_for_domain,
+            test_utils::{DataSize, UserData},
+        },
     };
     use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
     use ark_std::UniformRand;
     use kimchi::groupmap::GroupMap;
     use mina_curves::pasta::{Fp, Vesta, VestaParameters};
     use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSponge};
+    use o1_utils::FieldHelpers;
     use once_cell::sync::Lazy;
-    use poly_commitment::{commitment::CommitmentCurve, ipa::SRS, SRS as _};
-    use proptest::prelude::*;
+    use poly_commitment::{
+        commitment::CommitmentCurve,
+        ipa::{OpeningProof, SRS},
+        kzg::{KZGProof, PairingSRS},
+        SRS as _,
+    };
+    use proptest::{prelude::*, test_runner::TestCaseResult};
 
     type VestaFqSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+    type BN254FqSponge = DefaultFqSponge<ark_bn254::g1::Config, PlonkSpongeConstantsKimchi>;
 
     static SRS: Lazy<SRS<Vesta>> = Lazy::new(|| {
         if let Ok(srs) = std::env::var("SRS_FILEPATH") {
@@ -148,30 +522,198 @@ mod tests {
     static GROUP_MAP: Lazy<<Vesta as CommitmentCurve>::Map> =
         Lazy::new(<Vesta as CommitmentCurve>::Map::setup);
 
+    static KZG_SRS: Lazy<PairingSRS<ark_bn254::Bn254>> =
+        Lazy::new(|| env::create_insecure_pairing_srs(1 << 16));
+
+    static KZG_DOMAIN: Lazy<Radix2EvaluationDomain<ark_bn254::Fr>> =
+        Lazy::new(|| Radix2EvaluationDomain::new(KZG_SRS.size()).unwrap());
+
+    static KZG_GROUP_MAP: Lazy<<ark_bn254::G1Affine as CommitmentCurve>::Map> =
+        Lazy::new(<ark_bn254::G1Affine as CommitmentCurve>::Map::setup);
+
+    fn check_storage_prove_verify<G, OpeningProof, EFqSponge>(
+        srs: &OpeningProof::SRS,
+        group_map: &G::Map,
+        domain: Radix2EvaluationDomain<G::ScalarField>,
+        data: &[u8],
+    ) -> TestCaseResult
+    where
+        G: KimchiCurve,
+        G::BaseField: PrimeField,
+        OpeningProof: OpenProof<G>,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    {
+        let mut rng = OsRng;
+        let commitment = {
+            let field_elems = encode_for_domain(&domain, data);
+            commit_to_field_elems::<_, EFqSponge>(srs, domain, field_elems)
+        };
+        let blob = FieldBlob::<G>::encode::<_, EFqSponge>(srs, domain, data);
+        let evaluation_point = G::ScalarField::rand(&mut rng);
+        let proof = storage_proof::<G, OpeningProof, EFqSponge>(
+            srs,
+            group_map,
+            blob,
+            evaluation_point,
+            &mut rng,
+        );
+        let res = verify_storage_proof::<G, OpeningProof, EFqSponge>(
+            srs,
+            group_map,
+            commitment.folded,
+            evaluation_point,
+            &proof,
+            &mut rng,
+        );
+        prop_assert!(res);
+        Ok(())
+    }
+
     proptest! {
     #![proptest_config(ProptestConfig::with_cases(5))]
     #[test]
     fn test_storage_prove_verify(UserData(data) in UserData::arbitrary()) {
+        check_storage_prove_verify::<Vesta, OpeningProof<Vesta>, VestaFqSponge>(
+            &*SRS, &*GROUP_MAP, *DOMAIN, &data
+        )?;
+      }
+    }
+
+    proptest! {
+    #![proptest_config(ProptestConfig::with_cases(3))]
+    #[test]
+    fn test_storage_prove_verify_kzg(UserData(data) in UserData::arbitrary_with(DataSize::Small)) {
+        check_storage_prove_verify::<ark_bn254::G1Affine, KZGProof<ark_bn254::Bn254>, BN254FqSponge>(
+            &*KZG_SRS, &*KZG_GROUP_MAP, *KZG_DOMAIN, &data
+        )?;
+      }
+    }
+
+    /// Prove and verify the sampled positions, returning the bytes encoded by
+    /// the sampled field elements
+    fn check_sampling_prove_verify<G, OpeningProof, EFqSponge>(
+        srs: &OpeningProof::SRS,
+        group_map: &G::Map,
+        domain: Radix2EvaluationDomain<G::ScalarField>,
+        data: &[u8],
+        seed: [u8; 32],
+        n_positions: usize,
+    ) -> Result<Vec<u8>, TestCaseError>
+    where
+        G: KimchiCurve,
+        G::BaseField: PrimeField,
+        OpeningProof: OpenProof<G>,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+    {
         let mut rng = OsRng;
         let commitment = {
-            let field_elems = encode_for_domain(&*DOMAIN, &data);
-            commit_to_field_elems::<_, VestaFqSponge>(&*SRS, *DOMAIN, field_elems)
+            let field_elems = encode_for_domain(&domain, data);
+            commit_to_field_elems::<_, EFqSponge>(srs, domain, field_elems)
         };
-        let blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &data);
-        let evaluation_point = Fp::rand(&mut rng);
-        let proof = storage_proof::<
-            Vesta, VestaFqSponge
-
-        >(&*SRS, &*GROUP_MAP, blob, evaluation_point, &mut rng);
-        let res = verify_storage_proof::<Vesta, VestaFqSponge>(
-            &*SRS,
-            &*GROUP_MAP,
-            commitment.folded,
-            evaluation_point,
+        let blob = FieldBlob::<G>::encode::<_, EFqSponge>(srs, domain, data);
+        let sample = Sample::random(seed, blob.chunks.len(), domain.size(), n_positions);
+        let mut proof = sampling_proof::<G, OpeningProof, EFqSponge>(
+            srs, group_map, domain, &blob, &sample, &mut rng,
+        );
+        // the evaluations are the encoded data
+        let field_elems = encode_for_domain(&domain, data);
+        prop_assert_eq!(sample.positions.len(), proof.sampled_evaluations().count());
+        for ((chunk, index), eval) in sample.positions.iter().zip(proof.sampled_evaluations()) {
+            prop_assert_eq!(field_elems[*chunk][*index], *eval);
+        }
+        let res = verify_sampling_proof::<G, OpeningProof, EFqSponge>(
+            srs,
+            group_map,
+            domain,
+            &commitment,
+            &sample,
             &proof,
             &mut rng,
         );
         prop_assert!(res);
+
+        let n = (G::ScalarField::MODULUS_BIT_SIZE / 8) as usize;
+        let m = G::ScalarField::size_in_bytes();
+        let mut buffer = vec![0u8; m];
+        let sampled_bytes = proof
+            .sampled_evaluations()
+            .flat_map(|x| {
+                decode_into(&mut buffer, *x);
+                buffer[(m - n)..m].to_vec()
+            })
+            .collect();
+
+        // a proof for other data does not verify
+        proof.evaluations[0][0] += G::ScalarField::one();
+        let res = verify_sampling_proof::<G, OpeningProof, EFqSponge>(
+            srs,
+            group_map,
+            domain,
+            &commitment,
+            &sample,
+            &proof,
+            &mut rng,
+        );
+        prop_assert!(!res);
+        proof.evaluations[0][0] -= G::ScalarField::one();
+
+        // the same evaluations at other positions do not verify
+        let (chunk, index) = sample.positions[0];
+        let moved = Sample::from_positions(
+            std::iter::once((chunk, (index + 1) % domain.size()))
+                .chain(sample.positions[1..].iter().copied()),
+        );
+        let shape = |sample: &Sample| -> Vec<(usize, usize)> {
+            sample
+                .openings()
+                .iter()
+                .map(|(chunk, indices)| (*chunk, indices.len()))
+                .collect()
+        };
+        if shape(&moved) == shape(&sample) {
+            let res = verify_sampling_proof::<G, OpeningProof, EFqSponge>(
+                srs,
+                group_map,
+                domain,
+                &commitment,
+                &moved,
+                &proof,
+                &mut rng,
+            );
+            prop_assert!(!res);
+        }
+        Ok(sampled_bytes)
+    }
+
+    proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+    #[test]
+    fn test_sampling_prove_verify(UserData(data) in UserData::arbitrary(),
+                                  n_positions in 1usize..20)
+      { check_sampling_prove_verify::<Vesta, OpeningProof<Vesta>, VestaFqSponge>(
+            &*SRS, &*GROUP_MAP, *DOMAIN, &data, OsRng.gen(), n_positions
+        )?;
+      }
+    }
+
+    // The encoding only depends on the size of the field elements, which is
+    // the same for both backends, so the same positions hold the same bytes.
+    proptest! {
+    #![proptest_config(ProptestConfig::with_cases(3))]
+    #[test]
+    fn test_sampling_prove_verify_across_backends(
+        UserData(data) in UserData::arbitrary_with(DataSize::Small),
+        n_positions in 1usize..20)
+      { let seed = OsRng.gen();
+        let ipa_bytes = check_sampling_prove_verify::<Vesta, OpeningProof<Vesta>, VestaFqSponge>(
+            &*SRS, &*GROUP_MAP, *DOMAIN, &data, seed, n_positions
+        )?;
+        let kzg_bytes = check_sampling_prove_verify::<
+            ark_bn254::G1Affine,
+            KZGProof<ark_bn254::Bn254>,
+            BN254FqSponge,
+        >(&*KZG_SRS, &*KZG_GROUP_MAP, *KZG_DOMAIN, &data, seed, n_positions)?;
+        prop_assert_eq!(ipa_bytes, kzg_bytes);
       }
     }
 }
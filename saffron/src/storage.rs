@@ -0,0 +1,376 @@
+//! Persistent storage of blobs.
+//!
+//! A [BlobStore] keeps each blob in a directory as two files:
+//! - `<name>.meta`, the [BlobMetadata] of the blob, encoded with MessagePack;
+//! - `<name>.evals`, the evaluations of the polynomials of the blob over the
+//!   domain, i.e. the encoded data, polynomial after polynomial. Each field
+//!   element is serialized with `ark-serialize` on a fixed number of bytes.
+//!
+//! The evaluations are memory-mapped when a blob is opened, so that a range of
+//! bytes can be read, and proven against the commitment of the blob, without
+//! loading the whole blob in memory.
+
+use crate::{
+    blob::FieldBlob,
+    commitment::Commitment,
+    proof::{open_sample, range_sample, SamplingProof},
+    utils::{QueryBytes, QueryError},
+};
+use ark_ff::PrimeField;
+use ark_poly::{EvaluationDomain, Evaluations, Radix2EvaluationDomain as D};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use kimchi::curve::KimchiCurve;
+use memmap2::Mmap;
+use mina_poseidon::FqSponge;
+use poly_commitment::{
+    commitment::CommitmentCurve, utils::DensePolynomialOrEvaluations, OpenProof,
+};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+use tracing::{debug, instrument};
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid metadata: {0}")]
+    Metadata(String),
+    #[error("The evaluations file has {got} bytes, expected {expected}")]
+    EvaluationsSizeMismatch { got: usize, expected: usize },
+    #[error("Domain size mismatch, got {got}, expected {expected}")]
+    DomainSizeMismatch { got: usize, expected: usize },
+    #[error(transparent)]
+    Query(#[from] QueryError),
+}
+
+/// Everything about a stored blob but its evaluations
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(bound = "G::ScalarField : CanonicalDeserialize + CanonicalSerialize")]
+pub struct BlobMetadata<G: CommitmentCurve> {
+    pub n_bytes: usize,
+    pub domain_size: usize,
+    pub commitment: Commitment<G>,
+}
+
+impl<G: CommitmentCurve> BlobMetadata<G> {
+    pub fn n_chunks(&self) -> usize {
+        self.commitment.chunks.len()
+    }
+}
+
+/// A directory of blobs
+pub struct BlobStore {
+    pub dir: PathBuf,
+}
+
+/// Number of bytes used to store a field element
+fn field_size<F: PrimeField>() -> usize {
+    F::zero().compressed_size()
+}
+
+impl BlobStore {
+    pub fn create<P: AsRef<Path>>(dir: P) -> Result<Self, StorageError> {
+        fs::create_dir_all(&dir)?;
+        Ok(BlobStore {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn metadata_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.meta"))
+    }
+
+    fn evaluations_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.evals"))
+    }
+
+    /// Store the blob under the given name, replacing any blob with the same
+    /// name.
+    #[instrument(skip_all, level = "debug")]
+    pub fn put<G: KimchiCurve>(
+        &self,
+        name: &str,
+        domain: D<G::ScalarField>,
+        blob: &FieldBlob<G>,
+    ) -> Result<(), StorageError> {
+        if domain.size() != blob.domain_size {
+            return Err(StorageError::DomainSizeMismatch {
+                got: blob.domain_size,
+                expected: domain.size(),
+            });
+        }
+        {
+            let mut writer = BufWriter::new(File::create(self.evaluations_path(name))?);
+            for p in blob.chunks.iter() {
+                for x in p.evaluate_over_domain_by_ref(domain).evals {
+                    x.serialize_compressed(&mut writer)
+                        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                }
+            }
+            writer.flush()?;
+        }
+        let metadata = BlobMetadata {
+            n_bytes: blob.n_bytes,
+            domain_size: blob.domain_size,
+            commitment: blob.commitment.clone(),
+        };
+        let mut writer = BufWriter::new(File::create(self.metadata_path(name))?);
+        rmp_serde::encode::write(&mut writer, &metadata)
+            .map_err(|e| StorageError::Metadata(e.to_string()))?;
+        writer.flush()?;
+        debug!(name, n_chunks = blob.chunks.len(), "Stored blob");
+        Ok(())
+    }
+
+    /// Open the blob stored under the given name
+    #[instrument(skip_all, level = "debug")]
+    pub fn open<G: KimchiCurve>(&self, name: &str) -> Result<StoredBlob<G>, StorageError> {
+        let metadata: BlobMetadata<G> = {
+            let file = File::open(self.metadata_path(name))?;
+            rmp_serde::decode::from_read(file).map_err(|e| StorageError::Metadata(e.to_string()))?
+        };
+        let file = File::open(self.evaluations_path(name))?;
+        // SAFETY: the file is only read, and the store assumes that the
+        // files are not modified while a blob is open.
+        let evaluations = unsafe { Mmap::map(&file)? };
+        let expected = metadata.n_chunks() * metadata.domain_size * field_size::<G::ScalarField>();
+        if evaluations.len() != expected {
+            return Err(StorageError::EvaluationsSizeMismatch {
+                got: evaluations.len(),
+                expected,
+            });
+        }
+        Ok(StoredBlob {
+            metadata,
+            evaluations,
+            tag: PhantomData,
+        })
+    }
+}
+
+/// A blob of a [BlobStore], whose evaluations are read on demand
+pub struct StoredBlob<G: CommitmentCurve> {
+    pub metadata: BlobMetadata<G>,
+    evaluations: Mmap,
+    tag: PhantomData<G>,
+}
+
+impl<G: KimchiCurve> StoredBlob<G> {
+    /// The evaluation of the polynomial `chunk` at the root of unity `index`
+    pub fn evaluation(&self, chunk: usize, index: usize) -> G::ScalarField {
+        let size = field_size::<G::ScalarField>();
+        let offset = (chunk * self.metadata.domain_size + index) * size;
+        G::ScalarField::deserialize_compressed_unchecked(&self.evaluations[offset..offset + size])
+            .expect("The evaluations file contains an invalid field element")
+    }
+
+    /// The evaluations of the polynomial `chunk` over the domain
+    pub fn chunk_evaluations(
+        &self,
+        chunk: usize,
+        domain: D<G::ScalarField>,
+    ) -> Evaluations<G::ScalarField, D<G::ScalarField>> {
+        let evals = (0..self.metadata.domain_size)
+            .map(|index| self.evaluation(chunk, index))
+            .collect();
+        Evaluations::from_vec_and_domain(evals, domain)
+    }
+
+    /// Read `query.len` bytes of the data, starting at `query.start`
+    pub fn read_range(&self, query: &QueryBytes) -> Result<Vec<u8>, StorageError> {
+        let query_field = query.into_query_field::<G::ScalarField>(
+            self.metadata.domain_size,
+            self.metadata.n_chunks(),
+        )?;
+        Ok(query_field.apply_with(|chunk, index| self.evaluation(chunk, index)))
+    }
+
+    /// Read a range of bytes, and prove it against the commitment of the blob
+    /// by opening the field elements encoding the range, see
+    /// [crate::proof::verify_range_proof]. Only the chunks touched by the
+    /// range are loaded, and each of them is only opened at the positions of
+    /// the range. The range is encoded by at most
+    /// [crate::proof::MAX_RANGE_POSITIONS] field elements.
+    #[instrument(skip_all, level = "debug")]
+    pub fn read_range_with_proof<OpeningProof, EFqSponge>(
+        &self,
+        srs: &OpeningProof::SRS,
+        group_map: &G::Map,
+        domain: D<G::ScalarField>,
+        query: &QueryBytes,
+        rng: &mut OsRng,
+    ) -> Result<(Vec<u8>, SamplingProof<G, OpeningProof>), StorageError>
+    where
+        OpeningProof: OpenProof<G>,
+        EFqSponge: Clone + FqSponge<G::BaseField, G, G::ScalarField>,
+        G::BaseField: PrimeField,
+    {
+        if domain.size() != self.metadata.domain_size {
+            return Err(StorageError::DomainSizeMismatch {
+                got: self.metadata.domain_size,
+                expected: domain.size(),
+            });
+        }
+        let (query_field, sample) = range_sample::<G::ScalarField>(
+            query,
+            self.metadata.domain_size,
+            self.metadata.n_chunks(),
+        )?;
+        let openings = sample.openings();
+        let evaluations: Vec<Vec<G::ScalarField>> = openings
+            .iter()
+            .map(|(chunk, indices)| {
+                indices
+                    .iter()
+                    .map(|index| self.evaluation(*chunk, *index))
+                    .collect()
+            })
+            .collect();
+        let chunks: Vec<_> = openings
+            .iter()
+            .map(|(chunk, _)| self.chunk_evaluations(*chunk, domain))
+            .collect();
+        let polys = chunks
+            .iter()
+            .map(|evals| DensePolynomialOrEvaluations::Evaluations(evals, domain))
+            .collect();
+        debug!(
+            chunks = openings.len(),
+            positions = sample.positions.len(),
+            "Opening the field elements of the range"
+        );
+        let proof = open_sample::<G, OpeningProof, EFqSponge>(
+            srs,
+            group_map,
+            domain,
+            polys,
+            &self.metadata.commitment,
+            &sample,
+            evaluations,
+            rng,
+        );
+        let bytes = query_field.apply_with(|chunk, index| self.evaluation(chunk, index));
+        Ok((bytes, proof))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        env,
+        proof::{verify_range_proof, MAX_RANGE_POSITIONS},
+        utils::test_utils::UserData,
+    };
+    use kimchi::groupmap::GroupMap;
+    use mina_curves::pasta::{Fp, Vesta, VestaParameters};
+    use mina_poseidon::{constants::PlonkSpongeConstantsKimchi, sponge::DefaultFqSponge};
+    use once_cell::sync::Lazy;
+    use poly_commitment::{
+        ipa::{OpeningProof, SRS},
+        SRS as _,
+    };
+    use proptest::prelude::*;
+    use rand::Rng;
+
+    type VestaFqSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi>;
+
+    static SRS: Lazy<SRS<Vesta>> = Lazy::new(|| {
+        if let Ok(srs) = std::env::var("SRS_FILEPATH") {
+            env::get_srs_from_cache(srs)
+        } else {
+            SRS::create(1 << 16)
+        }
+    });
+
+    static DOMAIN: Lazy<D<Fp>> = Lazy::new(|| D::new(SRS.size()).unwrap());
+
+    static GROUP_MAP: Lazy<<Vesta as CommitmentCurve>::Map> =
+        Lazy::new(<Vesta as CommitmentCurve>::Map::setup);
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "saffron-store-{:016x}",
+            rand::thread_rng().gen::<u64>()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    proptest! {
+    #![proptest_config(ProptestConfig::with_cases(5))]
+    #[test]
+    fn test_read_range_with_proof(UserData(xs) in UserData::arbitrary(),
+                                  (start, len) in (0.0..1.0, 0.0..1.0))
+      { let mut rng = OsRng;
+        let blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &xs);
+        let store = BlobStore::create(temp_dir()).unwrap();
+        store.put("blob", *DOMAIN, &blob).unwrap();
+        let stored = store.open::<Vesta>("blob").unwrap();
+        prop_assert_eq!(&stored.metadata.commitment, &blob.commitment);
+
+        let start = (start * (xs.len() - 1) as f64) as usize;
+        let len = 1 + (len * (xs.len() - start - 1) as f64) as usize;
+        let query = QueryBytes { start, len };
+        prop_assert_eq!(stored.read_range(&query).unwrap(), &xs[start..start + len]);
+
+        // the range is encoded by at most two more field elements than its
+        // length in field elements, longer ranges are read without a proof
+        let n = (Fp::MODULUS_BIT_SIZE / 8) as usize;
+        let len = std::cmp::min(len, (MAX_RANGE_POSITIONS - 2) * n);
+        let query = QueryBytes { start, len };
+
+        let (bytes, proof) = stored
+            .read_range_with_proof::<OpeningProof<Vesta>, VestaFqSponge>(&*SRS, &*GROUP_MAP, *DOMAIN, &query, &mut rng)
+            .unwrap();
+        prop_assert_eq!(&bytes, &xs[start..start + len]);
+        prop_assert!(verify_range_proof::<Vesta, OpeningProof<Vesta>, VestaFqSponge>(
+            &*SRS, &*GROUP_MAP, *DOMAIN, &blob.commitment, &query, &bytes, &proof, &mut rng
+        ));
+
+        // the proof does not verify for other bytes
+        let mut tampered = bytes.clone();
+        tampered[0] ^= 1;
+        prop_assert!(!verify_range_proof::<Vesta, OpeningProof<Vesta>, VestaFqSponge>(
+            &*SRS, &*GROUP_MAP, *DOMAIN, &blob.commitment, &query, &tampered, &proof, &mut rng
+        ));
+        fs::remove_dir_all(&store.dir).unwrap();
+      }
+    }
+
+    #[test]
+    fn test_read_range_with_proof_too_long() {
+        let mut rng = OsRng;
+        let n = (Fp::MODULUS_BIT_SIZE / 8) as usize;
+        let xs = vec![1u8; (MAX_RANGE_POSITIONS + 2) * n];
+        let blob = FieldBlob::<Vesta>::encode::<_, VestaFqSponge>(&*SRS, *DOMAIN, &xs);
+        let store = BlobStore::create(temp_dir()).unwrap();
+        store.put("blob", *DOMAIN, &blob).unwrap();
+        let stored = store.open::<Vesta>("blob").unwrap();
+        let query = QueryBytes {
+            start: 0,
+            len: (MAX_RANGE_POSITIONS + 1) * n,
+        };
+        // the bytes can be read, but not proven
+        assert_eq!(stored.read_range(&query).unwrap(), &xs[..query.len]);
+        let res = stored.read_range_with_proof::<OpeningProof<Vesta>, VestaFqSponge>(
+            &*SRS,
+            &*GROUP_MAP,
+            *DOMAIN,
+            &query,
+            &mut rng,
+        );
+        assert!(matches!(
+            res,
+            Err(StorageError::Query(QueryError::RangeTooLong { .. }))
+        ));
+        fs::remove_dir_all(&store.dir).unwrap();
+    }
+}
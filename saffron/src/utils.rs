@@ -84,6 +84,12 @@ pub struct QueryField<F> {
 impl<F: PrimeField> QueryField<F> {
     #[instrument(skip_all, level = "debug")]
     pub fn apply(self, data: &[Vec<F>]) -> Vec<u8> {
+        self.apply_with(|poly_index, eval_index| data[poly_index][eval_index])
+    }
+
+    /// Same as [QueryField::apply], reading the field elements with `value`,
+    /// given the index of the polynomial and the index of the evaluation.
+    pub fn apply_with<V: Fn(usize, usize) -> F>(self, value: V) -> Vec<u8> {
         let n = (F::MODULUS_BIT_SIZE / 8) as usize;
         let m = F::size_in_bytes();
         let mut buffer = vec![0u8; m];
@@ -92,13 +98,22 @@ impl<F: PrimeField> QueryField<F> {
             .into_iter()
             .take_while(|x| x <= &self.end)
             .for_each(|x| {
-                let value = data[x.poly_index][x.eval_index];
-                decode_into(&mut buffer, value);
+                decode_into(&mut buffer, value(x.poly_index, x.eval_index));
                 answer.extend_from_slice(&buffer[(m - n)..m]);
             });
 
         answer[(self.leftover_start)..(answer.len() - self.leftover_end)].to_vec()
     }
+
+    /// The positions, i.e. the index of the polynomial and the index of the
+    /// evaluation, of the field elements read by the query
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        self.start
+            .into_iter()
+            .take_while(|x| x <= &self.end)
+            .map(|x| (x.poly_index, x.eval_index))
+            .collect()
+    }
 }
 
 impl Iterator for FieldElt {
@@ -128,6 +143,8 @@ pub enum QueryError {
         n_polys: usize,
         domain_size: usize,
     },
+    #[error("Range too long: it is encoded by {positions} field elements, at most {max} can be read with a proof")]
+    RangeTooLong { positions: usize, max: usize },
 }
 
 impl QueryBytes {
@@ -0,0 +1,95 @@
+//! End-to-end test of the erasure coding commands: encode a file at rate 1/2,
+//! lose evaluations of every chunk, and reconstruct the encoded data.
+//!
+//! Set `SRS_FILEPATH` to a Vesta SRS to avoid creating one.
+
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use mina_curves::pasta::Vesta;
+use saffron::{blob::FieldBlob, erasure::ExtendedFieldBlob};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn saffron(args: &[&str]) -> bool {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_saffron"));
+    command.args(args);
+    if let Ok(srs) = std::env::var("SRS_FILEPATH") {
+        if args[0] == "encode" {
+            command.args(["--srs-filepath", &srs]);
+        }
+    }
+    command.status().unwrap().success()
+}
+
+fn path(dir: &Path, name: &str) -> String {
+    dir.join(name).to_str().unwrap().to_string()
+}
+
+fn read_extended(path: &str) -> ExtendedFieldBlob<Vesta> {
+    rmp_serde::decode::from_read(File::open(path).unwrap()).unwrap()
+}
+
+fn write_extended(path: &str, blob: &ExtendedFieldBlob<Vesta>) {
+    rmp_serde::encode::write(&mut File::create(path).unwrap(), blob).unwrap()
+}
+
+#[test]
+fn test_reconstruct_lost_evaluations() {
+    let dir: PathBuf = std::env::temp_dir().join(format!("saffron-erasure-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let input = path(&dir, "data.txt");
+    let data: Vec<u8> = (0..2_100_000u32).map(|i| (i * 7 + i / 251) as u8).collect();
+    fs::write(&input, &data).unwrap();
+
+    let extended_file = path(&dir, "data.ext");
+    assert!(saffron(&[
+        "encode",
+        "-i",
+        &input,
+        "-o",
+        &extended_file,
+        "--rate",
+        "half"
+    ]));
+    let mut extended = read_extended(&extended_file);
+    assert!(extended.chunks.len() > 1);
+
+    // a storage node loses the first half of the evaluations of the first
+    // chunk, the second half of the other ones
+    for (i, evals) in extended.chunks.iter_mut().enumerate() {
+        let half = evals.len() / 2;
+        let lost = if i == 0 { 0..half } else { half..evals.len() };
+        evals[lost].iter_mut().for_each(|eval| *eval = None);
+    }
+    let damaged_file = path(&dir, "data.damaged");
+    write_extended(&damaged_file, &extended);
+
+    let reconstructed_file = path(&dir, "data.bin");
+    assert!(saffron(&[
+        "reconstruct",
+        "-i",
+        &damaged_file,
+        "-o",
+        &reconstructed_file
+    ]));
+    let blob: FieldBlob<Vesta> =
+        rmp_serde::decode::from_read(File::open(&reconstructed_file).unwrap()).unwrap();
+    assert_eq!(blob.commitment, extended.commitment);
+    let domain = Radix2EvaluationDomain::new(blob.domain_size).unwrap();
+    assert_eq!(FieldBlob::decode(domain, blob), data);
+
+    // with one more evaluation lost, the data cannot be reconstructed
+    extended.chunks[0][extended.domain_size] = None;
+    write_extended(&damaged_file, &extended);
+    assert!(!saffron(&[
+        "reconstruct",
+        "-i",
+        &damaged_file,
+        "-o",
+        &reconstructed_file
+    ]));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
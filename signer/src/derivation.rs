@@ -0,0 +1,93 @@
+//! Hierarchical deterministic key derivation
+//!
+//! Mina wallets derive keys from a BIP39 mnemonic with BIP32 derivation over
+//! secp256k1, along the BIP44 path `m/44'/12586'/account'/0/0` (see
+//! [`account_path`]). The derived secp256k1 secret key is mapped to a Pallas
+//! scalar by clearing its two most significant bits, which makes it smaller
+//! than the order of the Pallas scalar field.
+
+use bip32::{DerivationPath, Language, Mnemonic, XPrv};
+use thiserror::Error;
+
+use crate::{keypair::KeypairError, Keypair, SecKey};
+
+/// Mina coin type, as registered in SLIP-0044
+pub const MINA_COIN_TYPE: u32 = 12586;
+
+/// Derivation error
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DerivationError {
+    /// Invalid mnemonic
+    #[error("invalid mnemonic")]
+    Mnemonic,
+    /// Invalid derivation path
+    #[error("invalid derivation path")]
+    Path,
+    /// Invalid seed, or derived key
+    #[error("key derivation failed")]
+    Derivation,
+    /// Invalid derived keypair
+    #[error(transparent)]
+    Keypair(#[from] KeypairError),
+}
+/// Derivation result
+pub type Result<T> = std::result::Result<T, DerivationError>;
+
+/// Derivation path of the Mina account with index `account`
+pub fn account_path(account: u32) -> String {
+    format!("m/44'/{MINA_COIN_TYPE}'/{account}'/0/0")
+}
+
+/// Compute the 64-byte seed of the English BIP39 mnemonic `phrase`, protected
+/// by `password` (which may be empty)
+///
+/// # Errors
+///
+/// Will give error if `phrase` is not a valid mnemonic.
+pub fn seed_from_mnemonic(phrase: &str, password: &str) -> Result<[u8; 64]> {
+    let mnemonic =
+        Mnemonic::new(phrase, Language::English).map_err(|_| DerivationError::Mnemonic)?;
+
+    Ok(*mnemonic.to_seed(password).as_bytes())
+}
+
+/// Derive the secret key at derivation path `path` (e.g. `m/44'/12586'/0'/0/0`)
+/// from `seed`
+///
+/// # Errors
+///
+/// Will give error if `path` is not a valid derivation path, or if the
+/// derivation fails.
+pub fn derive_secret_key(seed: &[u8], path: &str) -> Result<SecKey> {
+    let path: DerivationPath = path.parse().map_err(|_| DerivationError::Path)?;
+    let xprv = XPrv::derive_from_path(seed, &path).map_err(|_| DerivationError::Derivation)?;
+
+    let mut bytes = xprv.private_key().to_bytes();
+    // Clear the two most significant bits of the big-endian secp256k1 scalar,
+    // so that it is a canonical Pallas scalar
+    bytes[0] &= 0b0011_1111;
+
+    SecKey::from_bytes(&bytes).map_err(|_| DerivationError::Derivation)
+}
+
+/// Derive the keypair at derivation path `path` from `seed`
+///
+/// # Errors
+///
+/// Will give error if `path` is not a valid derivation path, or if the
+/// derivation fails.
+pub fn derive_keypair(seed: &[u8], path: &str) -> Result<Keypair> {
+    Ok(Keypair::from_secret_key(derive_secret_key(seed, path)?)?)
+}
+
+/// Derive the keypair of the Mina account with index `account` from the English
+/// BIP39 mnemonic `phrase`, protected by `password` (which may be empty)
+///
+/// # Errors
+///
+/// Will give error if `phrase` is not a valid mnemonic, or if the derivation
+/// fails.
+pub fn keypair_from_mnemonic(phrase: &str, password: &str, account: u32) -> Result<Keypair> {
+    let seed = seed_from_mnemonic(phrase, password)?;
+    derive_keypair(&seed, &account_path(account))
+}
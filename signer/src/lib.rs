@@ -1,11 +1,15 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+pub mod derivation;
 pub mod keypair;
+pub mod musig;
 pub mod pubkey;
 pub mod schnorr;
 pub mod seckey;
 pub mod signature;
+pub mod transaction;
+pub mod zkapp;
 
 use mina_hasher::{DomainParameter, Hashable};
 
@@ -58,6 +62,15 @@ pub trait Signer<H: Hashable> {
     /// Verify that the signature `sig` on `input` (see [`Hashable`]) is signed with the secret key corresponding to `pub_key`.
     /// Return `true` if the signature is valid and `false` otherwise.
     fn verify(&mut self, sig: &Signature, pub_key: &PubKey, input: &H) -> bool;
+
+    /// Verify a batch of signatures, where each entry of `batch` is a signature, the public key it
+    /// should be signed with and the signed input (see [`Signer::verify`]).
+    /// Return `true` if all the signatures are valid and `false` otherwise.
+    fn verify_batch(&mut self, batch: &[(&Signature, &PubKey, &H)]) -> bool {
+        batch
+            .iter()
+            .all(|(sig, pub_key, input)| self.verify(sig, pub_key, input))
+    }
 }
 
 /// Create a legacy signer context with domain parameters initialized with `domain_param`
@@ -0,0 +1,544 @@
+//! Mina Schnorr multi-signatures
+//!
+//! An implementation of the two-round [MuSig2](https://eprint.iacr.org/2020/1261)
+//! protocol for the Mina signature algorithm (see [`schnorr`](crate::schnorr)).
+//! `n` signers jointly produce an ordinary [`Signature`] that verifies, with any
+//! [`Signer`](crate::Signer) using the same hasher and domain parameters, against
+//! their aggregated public key.  All the signers are needed to sign (n-of-n).
+//!
+//! The protocol runs as follows.
+//! 1. The public keys of the signers are aggregated into a [`KeyAggContext`].
+//! 2. Each signer generates a fresh nonce with [`nonce_gen`], and sends its
+//!    [`PubNonce`] to the others (or to a coordinator).
+//! 3. The public nonces are aggregated with [`AggNonce::aggregate`], and each
+//!    signer sends its [`PartialSignature`], produced by [`MuSig::partial_sign`].
+//! 4. The partial signatures are aggregated into a [`Signature`] with
+//!    [`MuSig::aggregate`].  A partial signature can be checked with
+//!    [`MuSig::partial_verify`] to identify a misbehaving signer.
+//!
+//! **Important:** A [`SecNonce`] is consumed when signing, and must never be
+//! used for more than one signature.
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{
+    BigInteger, // for is_odd()
+    Field,      // for from_random_bytes()
+    PrimeField, // for into_bigint()
+    Zero,
+};
+use blake2::{
+    digest::{Update, VariableOutput},
+    Blake2bVar,
+};
+use mina_hasher::{DomainParameter, Hashable};
+use o1_utils::FieldHelpers;
+use rand::{CryptoRng, RngCore};
+use std::ops::Neg;
+use thiserror::Error;
+
+use crate::{schnorr, BaseField, CurvePoint, Keypair, PubKey, ScalarField, Schnorr, Signature};
+
+/// Multi-signature errors
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MuSigError {
+    /// No public key to aggregate
+    #[error("no public key to aggregate")]
+    NoSigners,
+    /// The aggregated public key is the point at infinity
+    #[error("invalid aggregated public key")]
+    AggregatePubKey,
+    /// An aggregated nonce is the point at infinity
+    #[error("invalid aggregated nonce")]
+    AggregateNonce,
+    /// The signer is not part of the key aggregation context
+    #[error("unknown signer")]
+    UnknownSigner,
+    /// A public key is given more than once
+    #[error("duplicate signer")]
+    DuplicateSigner,
+    /// The secret nonce was generated for another keypair
+    #[error("secret nonce does not match keypair")]
+    NonceKeypair,
+    /// Invalid nonce bytes
+    #[error("invalid nonce bytes")]
+    NonceBytes,
+    /// Invalid partial signature bytes
+    #[error("invalid partial signature bytes")]
+    PartialSignatureBytes,
+    /// Invalid hex
+    #[error("invalid hex")]
+    Hex,
+}
+/// Multi-signature result
+pub type Result<T> = std::result::Result<T, MuSigError>;
+
+/// Hash `data` with domain separation `tag` into a scalar field element
+fn hash_to_scalar(tag: &str, data: &[&[u8]]) -> ScalarField {
+    let mut blake_hasher = Blake2bVar::new(32).unwrap();
+    blake_hasher.update(tag.as_bytes());
+    for bytes in data {
+        blake_hasher.update(bytes);
+    }
+
+    let mut bytes = [0; 32];
+    blake_hasher
+        .finalize_variable(&mut bytes)
+        .expect("incorrect output size");
+    // Drop the top two bits to convert into a scalar field element
+    // (see the nonce derivation of the Schnorr signer)
+    bytes[bytes.len() - 1] &= 0b0011_1111;
+
+    ScalarField::from_random_bytes(&bytes[..]).expect("failed to create scalar from bytes")
+}
+
+/// Serialize a pair of curve points, in the format of [`PubKey::to_bytes`]
+fn points_to_bytes(r1: &CurvePoint, r2: &CurvePoint) -> Vec<u8> {
+    [
+        r1.x.to_bytes(),
+        r1.y.to_bytes(),
+        r2.x.to_bytes(),
+        r2.y.to_bytes(),
+    ]
+    .concat()
+}
+
+/// Deserialize a pair of curve points serialized with [`points_to_bytes`]
+fn points_from_bytes(bytes: &[u8]) -> Result<(CurvePoint, CurvePoint)> {
+    let len = BaseField::size_in_bytes();
+    if bytes.len() != 4 * len {
+        return Err(MuSigError::NonceBytes);
+    }
+    let coordinates = bytes
+        .chunks(len)
+        .map(|bytes| BaseField::from_bytes(bytes).map_err(|_| MuSigError::NonceBytes))
+        .collect::<Result<Vec<_>>>()?;
+    let point = |x, y| {
+        let point = CurvePoint::new_unchecked(x, y);
+        if point.is_on_curve() {
+            Ok(point)
+        } else {
+            Err(MuSigError::NonceBytes)
+        }
+    };
+
+    Ok((
+        point(coordinates[0], coordinates[1])?,
+        point(coordinates[2], coordinates[3])?,
+    ))
+}
+
+/// Aggregation of the public keys of the signers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyAggContext {
+    pub_keys: Vec<PubKey>,
+    coefficients: Vec<ScalarField>,
+    aggregate: PubKey,
+}
+
+impl KeyAggContext {
+    /// Aggregate the public keys `pub_keys` of the signers
+    ///
+    /// The aggregated public key is `sum_i a_i P_i`, where the coefficients
+    /// `a_i` are derived from the list of public keys, so the order of
+    /// `pub_keys` matters.
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `pub_keys` is empty, if it contains a public key
+    /// more than once, or if the aggregated public key is the point at
+    /// infinity.
+    pub fn new(pub_keys: &[PubKey]) -> Result<Self> {
+        if pub_keys.is_empty() {
+            return Err(MuSigError::NoSigners);
+        }
+        // A signer signs once, with the coefficient of its public key, so it
+        // cannot account for several occurrences of its key
+        if pub_keys
+            .iter()
+            .enumerate()
+            .any(|(i, pub_key)| pub_keys[..i].contains(pub_key))
+        {
+            return Err(MuSigError::DuplicateSigner);
+        }
+
+        let key_list: Vec<u8> = pub_keys.iter().flat_map(PubKey::to_bytes).collect();
+        let key_list = hash_to_scalar("MinaMuSigKeyList", &[&key_list]).to_bytes();
+        let coefficients: Vec<ScalarField> = pub_keys
+            .iter()
+            .map(|pub_key| hash_to_scalar("MinaMuSigKeyCoef", &[&key_list, &pub_key.to_bytes()]))
+            .collect();
+
+        let aggregate = pub_keys
+            .iter()
+            .zip(&coefficients)
+            .fold(CurvePoint::zero().into_group(), |acc, (pub_key, a)| {
+                acc + pub_key.point().mul_bigint(a.into_bigint())
+            });
+        if aggregate.is_zero() {
+            return Err(MuSigError::AggregatePubKey);
+        }
+
+        Ok(KeyAggContext {
+            pub_keys: pub_keys.to_vec(),
+            coefficients,
+            // Safe because the sum of curve points is on the curve
+            aggregate: PubKey::from_point_unsafe(aggregate.into_affine()),
+        })
+    }
+
+    /// Borrow the aggregated public key, which verifies the aggregated signatures
+    pub fn aggregate_pub_key(&self) -> &PubKey {
+        &self.aggregate
+    }
+
+    /// Borrow the public keys of the signers
+    pub fn pub_keys(&self) -> &[PubKey] {
+        &self.pub_keys
+    }
+
+    /// The coefficient of `pub_key` in the aggregated public key
+    fn coefficient(&self, pub_key: &PubKey) -> Result<ScalarField> {
+        self.pub_keys
+            .iter()
+            .position(|p| p == pub_key)
+            .map(|i| self.coefficients[i])
+            .ok_or(MuSigError::UnknownSigner)
+    }
+}
+
+/// Secret nonce of a signer, for a single signature
+///
+/// The secret nonce is consumed by [`MuSig::partial_sign`], and can neither be
+/// cloned nor serialized.
+pub struct SecNonce {
+    k1: ScalarField,
+    k2: ScalarField,
+    pub_key: PubKey,
+}
+
+/// Public nonce of a signer, sent to the other signers in the first round
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PubNonce {
+    /// First nonce point
+    pub r1: CurvePoint,
+    /// Second nonce point
+    pub r2: CurvePoint,
+}
+
+impl PubNonce {
+    /// Deserialize public nonce from bytes
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` are not the serialization of two curve points.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (r1, r2) = points_from_bytes(bytes)?;
+        Ok(PubNonce { r1, r2 })
+    }
+
+    /// Deserialize public nonce from hex
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `nonce_hex` is not the serialization of two curve points.
+    pub fn from_hex(nonce_hex: &str) -> Result<Self> {
+        let bytes: Vec<u8> = hex::decode(nonce_hex).map_err(|_| MuSigError::Hex)?;
+        PubNonce::from_bytes(&bytes)
+    }
+
+    /// Serialize public nonce into bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        points_to_bytes(&self.r1, &self.r2)
+    }
+
+    /// Serialize public nonce into hex
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+}
+
+/// Aggregation of the public nonces of all the signers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggNonce {
+    /// Sum of the first nonce points
+    pub r1: CurvePoint,
+    /// Sum of the second nonce points
+    pub r2: CurvePoint,
+}
+
+impl AggNonce {
+    /// Aggregate the public nonces `pub_nonces` of the signers
+    ///
+    /// # Errors
+    ///
+    /// Will give error if an aggregated nonce point is the point at infinity.
+    pub fn aggregate(pub_nonces: &[PubNonce]) -> Result<Self> {
+        let (r1, r2) = pub_nonces.iter().fold(
+            (
+                CurvePoint::zero().into_group(),
+                CurvePoint::zero().into_group(),
+            ),
+            |(r1, r2), nonce| (r1 + nonce.r1, r2 + nonce.r2),
+        );
+        if r1.is_zero() || r2.is_zero() {
+            return Err(MuSigError::AggregateNonce);
+        }
+
+        Ok(AggNonce {
+            r1: r1.into_affine(),
+            r2: r2.into_affine(),
+        })
+    }
+
+    /// Deserialize aggregated nonce from bytes
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` are not the serialization of two curve points.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (r1, r2) = points_from_bytes(bytes)?;
+        Ok(AggNonce { r1, r2 })
+    }
+
+    /// Deserialize aggregated nonce from hex
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `nonce_hex` is not the serialization of two curve points.
+    pub fn from_hex(nonce_hex: &str) -> Result<Self> {
+        let bytes: Vec<u8> = hex::decode(nonce_hex).map_err(|_| MuSigError::Hex)?;
+        AggNonce::from_bytes(&bytes)
+    }
+
+    /// Serialize aggregated nonce into bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        points_to_bytes(&self.r1, &self.r2)
+    }
+
+    /// Serialize aggregated nonce into hex
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+}
+
+/// Partial signature of a signer, sent in the second round
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialSignature(pub ScalarField);
+
+impl PartialSignature {
+    /// Deserialize partial signature from big-endian bytes
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `bytes` are not the serialization of a scalar.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != ScalarField::size_in_bytes() {
+            return Err(MuSigError::PartialSignatureBytes);
+        }
+        let mut bytes = bytes.to_vec();
+        bytes.reverse();
+        let s = ScalarField::from_bytes(&bytes).map_err(|_| MuSigError::PartialSignatureBytes)?;
+
+        Ok(PartialSignature(s))
+    }
+
+    /// Deserialize partial signature from hex
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `signature_hex` is not the serialization of a scalar.
+    pub fn from_hex(signature_hex: &str) -> Result<Self> {
+        let bytes: Vec<u8> = hex::decode(signature_hex).map_err(|_| MuSigError::Hex)?;
+        PartialSignature::from_bytes(&bytes)
+    }
+
+    /// Serialize partial signature into big-endian bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.0.to_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    /// Serialize partial signature into hex
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.to_bytes())
+    }
+}
+
+/// Generate a fresh nonce for the signer with keypair `kp`, to sign with the
+/// signers of `ctx`
+pub fn nonce_gen(
+    rng: &mut (impl RngCore + CryptoRng),
+    kp: &Keypair,
+    ctx: &KeyAggContext,
+) -> (SecNonce, PubNonce) {
+    // The nonces are derived from fresh randomness, and from the secret key so
+    // that they stay secret even if the randomness is weak
+    let mut rand_bytes = [0; 32];
+    rng.fill_bytes(&mut rand_bytes);
+    let derive = |i: u8| {
+        hash_to_scalar(
+            "MinaMuSigNonce",
+            &[
+                &rand_bytes,
+                &kp.secret.to_bytes(),
+                &ctx.aggregate.to_bytes(),
+                &[i],
+            ],
+        )
+    };
+    let (k1, k2) = (derive(1), derive(2));
+
+    let pub_nonce = PubNonce {
+        r1: CurvePoint::generator()
+            .mul_bigint(k1.into_bigint())
+            .into_affine(),
+        r2: CurvePoint::generator()
+            .mul_bigint(k2.into_bigint())
+            .into_affine(),
+    };
+    let sec_nonce = SecNonce {
+        k1,
+        k2,
+        pub_key: kp.public.clone(),
+    };
+
+    (sec_nonce, pub_nonce)
+}
+
+/// Values shared by all the signers of an input
+struct Session {
+    /// Coefficient of the second nonce points
+    b: ScalarField,
+    /// Whether the nonces are negated, so that the nonce point has an even
+    /// y-coordinate
+    negate: bool,
+    /// Nonce point
+    r: CurvePoint,
+    /// Challenge
+    e: ScalarField,
+}
+
+/// Multi-signature context for the Mina signature algorithm
+pub struct MuSig<H: Hashable> {
+    schnorr: Schnorr<H>,
+}
+
+/// Create a legacy multi-signature context with domain parameters initialized
+/// with `domain_param`, whose signatures verify with a legacy signer (see
+/// [`create_legacy`](crate::create_legacy))
+pub fn create_legacy<H: 'static + Hashable>(domain_param: H::D) -> MuSig<H> {
+    MuSig {
+        schnorr: schnorr::create_legacy::<H>(domain_param),
+    }
+}
+
+/// Create an experimental kimchi multi-signature context with domain
+/// parameters initialized with `domain_param`, whose signatures verify with a
+/// kimchi signer (see [`create_kimchi`](crate::create_kimchi))
+pub fn create_kimchi<H: 'static + Hashable>(domain_param: H::D) -> MuSig<H> {
+    MuSig {
+        schnorr: schnorr::create_kimchi::<H>(domain_param),
+    }
+}
+
+impl<H: 'static + Hashable> MuSig<H> {
+    fn session(&mut self, ctx: &KeyAggContext, agg_nonce: &AggNonce, input: &H) -> Result<Session> {
+        let b = hash_to_scalar(
+            "MinaMuSigNonceCoef",
+            &[
+                &ctx.aggregate.to_bytes(),
+                &agg_nonce.to_bytes(),
+                &input.to_roinput().to_bytes(),
+                &self.schnorr.domain_param.clone().into_bytes(),
+            ],
+        );
+
+        let r = agg_nonce.r1.into_group() + agg_nonce.r2.mul_bigint(b.into_bigint());
+        if r.is_zero() {
+            return Err(MuSigError::AggregateNonce);
+        }
+        let r = r.into_affine();
+        let negate = r.y.into_bigint().is_odd();
+        let e = self.schnorr.message_hash(&ctx.aggregate, r.x, input);
+
+        Ok(Session { b, negate, r, e })
+    }
+
+    /// Sign `input` with the keypair `kp` of one of the signers of `ctx`,
+    /// consuming the secret nonce `sec_nonce` of the signer
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `kp` is not a signer of `ctx`, if `sec_nonce` was
+    /// generated for another keypair, or if `agg_nonce` is invalid.
+    pub fn partial_sign(
+        &mut self,
+        ctx: &KeyAggContext,
+        sec_nonce: SecNonce,
+        agg_nonce: &AggNonce,
+        kp: &Keypair,
+        input: &H,
+    ) -> Result<PartialSignature> {
+        if sec_nonce.pub_key != kp.public {
+            return Err(MuSigError::NonceKeypair);
+        }
+        let a = ctx.coefficient(&kp.public)?;
+        let session = self.session(ctx, agg_nonce, input)?;
+
+        let k = sec_nonce.k1 + session.b * sec_nonce.k2;
+        let k = if session.negate { -k } else { k };
+
+        Ok(PartialSignature(k + session.e * a * kp.secret.scalar()))
+    }
+
+    /// Verify that the partial signature `partial` on `input` was produced by
+    /// the signer with public key `pub_key` and public nonce `pub_nonce`.
+    /// Return `true` if the partial signature is valid and `false` otherwise.
+    pub fn partial_verify(
+        &mut self,
+        ctx: &KeyAggContext,
+        pub_nonce: &PubNonce,
+        agg_nonce: &AggNonce,
+        pub_key: &PubKey,
+        partial: &PartialSignature,
+        input: &H,
+    ) -> bool {
+        let (Ok(a), Ok(session)) = (
+            ctx.coefficient(pub_key),
+            self.session(ctx, agg_nonce, input),
+        ) else {
+            return false;
+        };
+
+        // s G = R1 + b R2 + e a P, up to the negation of the nonce points
+        let r = pub_nonce.r1.into_group() + pub_nonce.r2.mul_bigint(session.b.into_bigint());
+        let r = if session.negate { r.neg() } else { r };
+        let lhs = CurvePoint::generator().mul_bigint(partial.0.into_bigint());
+        let rhs = r + pub_key.point().mul_bigint((session.e * a).into_bigint());
+
+        lhs == rhs
+    }
+
+    /// Aggregate the partial signatures `partials` of all the signers of `ctx`
+    /// into a signature on `input`, which verifies against the aggregated
+    /// public key of `ctx`
+    ///
+    /// # Errors
+    ///
+    /// Will give error if `agg_nonce` is invalid.
+    pub fn aggregate(
+        &mut self,
+        ctx: &KeyAggContext,
+        agg_nonce: &AggNonce,
+        partials: &[PartialSignature],
+        input: &H,
+    ) -> Result<Signature> {
+        let session = self.session(ctx, agg_nonce, input)?;
+        let s = partials
+            .iter()
+            .fold(ScalarField::zero(), |acc, partial| acc + partial.0);
+
+        Ok(Signature::new(session.r.x, s))
+    }
+}
@@ -223,7 +223,9 @@ impl fmt::Display for PubKey {
 }
 
 /// Compressed public keys consist of x-coordinate and y-coordinate parity.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// The default compressed public key is the empty key, with x-coordinate zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct CompressedPubKey {
     /// X-coordinate
     pub x: BaseField,
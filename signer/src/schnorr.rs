@@ -7,11 +7,13 @@
 use ark_ec::{
     AffineRepr, // for generator()
     CurveGroup,
+    VariableBaseMSM, // for msm_unchecked()
 };
 use ark_ff::{
     BigInteger, // for is_even()
     Field,      // for from_random_bytes()
     PrimeField, // for from_repr()
+    UniformRand,
     Zero,
 };
 use blake2::{
@@ -28,7 +30,7 @@ use crate::{BaseField, CurvePoint, Hashable, Keypair, PubKey, ScalarField, Signa
 /// For details about the signature algorithm please see the [`schnorr`](crate::schnorr) documentation
 pub struct Schnorr<H: Hashable> {
     hasher: Box<dyn Hasher<Message<H>>>,
-    domain_param: H::D,
+    pub(crate) domain_param: H::D,
 }
 
 #[derive(Clone)]
@@ -86,9 +88,48 @@ impl<H: 'static + Hashable> Signer<H> for Schnorr<H> {
 
         rv.y.into_bigint().is_even() && rv.x == sig.rx
     }
+
+    fn verify_batch(&mut self, batch: &[(&Signature, &PubKey, &H)]) -> bool {
+        // A signature (rx, s) is valid if and only if s*G - e*P = R, where R is the point with
+        // x-coordinate rx and even y-coordinate.  All the equations are checked at once by
+        // checking that their combination with random coefficients holds, using a single
+        // multi-scalar multiplication.
+        let mut rng = rand::thread_rng();
+        let mut points = Vec::with_capacity(2 * batch.len() + 1);
+        let mut scalars = Vec::with_capacity(2 * batch.len() + 1);
+        let mut generator_scalar = ScalarField::zero();
+
+        for (sig, public, input) in batch {
+            let Some(r) = point_from_x_even_y(sig.rx) else {
+                return false;
+            };
+            let e: ScalarField = self.message_hash(public, sig.rx, input);
+            let coefficient = ScalarField::rand(&mut rng);
+
+            generator_scalar += coefficient * sig.s;
+            points.push(*public.point());
+            scalars.push(-(coefficient * e));
+            points.push(r);
+            scalars.push(-coefficient);
+        }
+        points.push(CurvePoint::generator());
+        scalars.push(generator_scalar);
+
+        <CurvePoint as AffineRepr>::Group::msm_unchecked(&points, &scalars).is_zero()
+    }
+}
+
+/// The curve point with x-coordinate `x` and even y-coordinate, if any
+fn point_from_x_even_y(x: BaseField) -> Option<CurvePoint> {
+    let mut point = CurvePoint::get_point_from_x_unchecked(x, false)?;
+    if point.y.into_bigint().is_odd() {
+        point.y = point.y.neg();
+    }
+
+    Some(point)
 }
 
-pub(crate) fn create_legacy<H: 'static + Hashable>(domain_param: H::D) -> impl Signer<H> {
+pub(crate) fn create_legacy<H: 'static + Hashable>(domain_param: H::D) -> Schnorr<H> {
     Schnorr::<H> {
         hasher: Box::new(mina_hasher::create_legacy::<Message<H>>(
             domain_param.clone(),
@@ -97,7 +138,7 @@ pub(crate) fn create_legacy<H: 'static + Hashable>(domain_param: H::D) -> impl S
     }
 }
 
-pub(crate) fn create_kimchi<H: 'static + Hashable>(domain_param: H::D) -> impl Signer<H> {
+pub(crate) fn create_kimchi<H: 'static + Hashable>(domain_param: H::D) -> Schnorr<H> {
     Schnorr::<H> {
         hasher: Box::new(mina_hasher::create_kimchi::<Message<H>>(
             domain_param.clone(),
@@ -140,7 +181,12 @@ impl<H: 'static + Hashable> Schnorr<H> {
     /// randomly distributed scalar field element.  It uses Mina's variant of the Poseidon
     /// SNARK-friendly cryptographic hash function.
     /// Details: <https://github.com/o1-labs/cryptography-rfcs/blob/httpsnapps-notary-signatures/mina/001-poseidon-sponge.md>
-    fn message_hash(&mut self, pub_key: &PubKey, rx: BaseField, input: &H) -> ScalarField {
+    pub(crate) fn message_hash(
+        &mut self,
+        pub_key: &PubKey,
+        rx: BaseField,
+        input: &H,
+    ) -> ScalarField {
         let schnorr_input = Message::<H> {
             input: input.clone(),
             pub_key_x: pub_key.point().x,
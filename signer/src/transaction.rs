@@ -0,0 +1,272 @@
+//! Mina signed commands
+//!
+//! Typed [`Payment`] and [`StakeDelegation`] commands, hashed with the legacy
+//! layout that is signed on mainnet and testnet with a legacy signer (see
+//! [`create_legacy`](crate::create_legacy)).
+
+use mina_hasher::{Hashable, ROInput};
+
+use crate::{CompressedPubKey, NetworkId};
+
+/// Length of a memo in bytes
+pub const MEMO_BYTES: usize = 34;
+
+/// Maximum length of the user-supplied part of a memo in bytes
+pub const MEMO_MAX_LEN: usize = MEMO_BYTES - 2;
+
+const MEMO_TAG_BYTES: u8 = 0x01;
+const TAG_BITS: usize = 3;
+const PAYMENT_TX_TAG: [bool; TAG_BITS] = [false, false, false];
+const DELEGATION_TX_TAG: [bool; TAG_BITS] = [false, false, true];
+
+/// Identifier of the default token, the only one legacy commands can use
+const DEFAULT_TOKEN_ID: u64 = 1;
+
+/// Transaction memo
+///
+/// A memo is made of a tag byte, a length byte and up to [`MEMO_MAX_LEN`] bytes
+/// of user data, padded with zeros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Memo(pub [u8; MEMO_BYTES]);
+
+impl Default for Memo {
+    fn default() -> Self {
+        Memo::from_bytes(&[])
+    }
+}
+
+impl Memo {
+    /// Create a memo from `bytes`, truncated to [`MEMO_MAX_LEN`] bytes
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let len = std::cmp::min(bytes.len(), MEMO_MAX_LEN);
+        let mut memo = [0; MEMO_BYTES];
+        memo[0] = MEMO_TAG_BYTES;
+        memo[1] = len as u8;
+        memo[2..2 + len].copy_from_slice(&bytes[..len]);
+
+        Memo(memo)
+    }
+
+    /// Create a memo from the bytes of `memo`, truncated to [`MEMO_MAX_LEN`] bytes
+    pub fn from_string(memo: &str) -> Self {
+        Memo::from_bytes(memo.as_bytes())
+    }
+
+    /// Borrow the user data of the memo
+    pub fn data(&self) -> &[u8] {
+        let len = std::cmp::min(self.0[1] as usize, MEMO_MAX_LEN);
+        &self.0[2..2 + len]
+    }
+
+    /// Borrow the serialized memo
+    pub fn as_bytes(&self) -> &[u8; MEMO_BYTES] {
+        &self.0
+    }
+}
+
+/// Fields shared by all signed commands
+struct Common<'a> {
+    fee: u64,
+    fee_payer_pk: &'a CompressedPubKey,
+    nonce: u32,
+    valid_until: u32,
+    memo: &'a Memo,
+}
+
+/// Body of a signed command, in its legacy form
+struct Body<'a> {
+    tag: [bool; TAG_BITS],
+    source_pk: &'a CompressedPubKey,
+    receiver_pk: &'a CompressedPubKey,
+    amount: u64,
+}
+
+/// Legacy random oracle input of a signed command
+///
+/// The source of the command is always the fee payer, and the tokens are always
+/// the default token.
+fn legacy_roinput(common: Common, body: Body) -> ROInput {
+    let mut roi = ROInput::new()
+        .append_field(common.fee_payer_pk.x)
+        .append_field(body.source_pk.x)
+        .append_field(body.receiver_pk.x)
+        .append_u64(common.fee)
+        .append_u64(DEFAULT_TOKEN_ID)
+        .append_bool(common.fee_payer_pk.is_odd)
+        .append_u32(common.nonce)
+        .append_u32(common.valid_until)
+        .append_bytes(common.memo.as_bytes());
+
+    for tag_bit in body.tag {
+        roi = roi.append_bool(tag_bit);
+    }
+
+    roi.append_bool(body.source_pk.is_odd)
+        .append_bool(body.receiver_pk.is_odd)
+        .append_u64(DEFAULT_TOKEN_ID)
+        .append_u64(body.amount)
+        .append_bool(false) // token_locked
+}
+
+/// Domain string of signed commands, which must have length <= 20
+fn signature_domain_string(network_id: NetworkId) -> Option<String> {
+    match network_id {
+        NetworkId::MAINNET => "MinaSignatureMainnet",
+        NetworkId::TESTNET => "CodaSignature",
+    }
+    .to_string()
+    .into()
+}
+
+/// Payment of `amount` nanomina from the fee payer to `receiver_pk`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payment {
+    /// Fee in nanomina
+    pub fee: u64,
+    /// Public key of the fee payer, which is also the source of the payment
+    pub fee_payer_pk: CompressedPubKey,
+    /// Nonce of the fee payer account
+    pub nonce: u32,
+    /// Global slot after which the command is invalid
+    pub valid_until: u32,
+    /// Memo
+    pub memo: Memo,
+    /// Public key of the receiver
+    pub receiver_pk: CompressedPubKey,
+    /// Amount in nanomina
+    pub amount: u64,
+}
+
+impl Payment {
+    /// Create a payment of `amount` from `from` to `to`, valid forever and
+    /// with an empty memo
+    pub fn new(
+        from: CompressedPubKey,
+        to: CompressedPubKey,
+        amount: u64,
+        fee: u64,
+        nonce: u32,
+    ) -> Self {
+        Payment {
+            fee,
+            fee_payer_pk: from,
+            nonce,
+            valid_until: u32::MAX,
+            memo: Memo::default(),
+            receiver_pk: to,
+            amount,
+        }
+    }
+
+    /// Set the global slot after which the payment is invalid
+    pub fn set_valid_until(mut self, global_slot: u32) -> Self {
+        self.valid_until = global_slot;
+
+        self
+    }
+
+    /// Set the memo of the payment
+    pub fn set_memo(mut self, memo: Memo) -> Self {
+        self.memo = memo;
+
+        self
+    }
+}
+
+impl Hashable for Payment {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        legacy_roinput(
+            Common {
+                fee: self.fee,
+                fee_payer_pk: &self.fee_payer_pk,
+                nonce: self.nonce,
+                valid_until: self.valid_until,
+                memo: &self.memo,
+            },
+            Body {
+                tag: PAYMENT_TX_TAG,
+                source_pk: &self.fee_payer_pk,
+                receiver_pk: &self.receiver_pk,
+                amount: self.amount,
+            },
+        )
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        signature_domain_string(network_id)
+    }
+}
+
+/// Delegation of the stake of the fee payer to `new_delegate_pk`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakeDelegation {
+    /// Fee in nanomina
+    pub fee: u64,
+    /// Public key of the fee payer, which is also the delegator
+    pub fee_payer_pk: CompressedPubKey,
+    /// Nonce of the fee payer account
+    pub nonce: u32,
+    /// Global slot after which the command is invalid
+    pub valid_until: u32,
+    /// Memo
+    pub memo: Memo,
+    /// Public key of the new delegate
+    pub new_delegate_pk: CompressedPubKey,
+}
+
+impl StakeDelegation {
+    /// Create a delegation of the stake of `from` to `to`, valid forever and
+    /// with an empty memo
+    pub fn new(from: CompressedPubKey, to: CompressedPubKey, fee: u64, nonce: u32) -> Self {
+        StakeDelegation {
+            fee,
+            fee_payer_pk: from,
+            nonce,
+            valid_until: u32::MAX,
+            memo: Memo::default(),
+            new_delegate_pk: to,
+        }
+    }
+
+    /// Set the global slot after which the delegation is invalid
+    pub fn set_valid_until(mut self, global_slot: u32) -> Self {
+        self.valid_until = global_slot;
+
+        self
+    }
+
+    /// Set the memo of the delegation
+    pub fn set_memo(mut self, memo: Memo) -> Self {
+        self.memo = memo;
+
+        self
+    }
+}
+
+impl Hashable for StakeDelegation {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        legacy_roinput(
+            Common {
+                fee: self.fee,
+                fee_payer_pk: &self.fee_payer_pk,
+                nonce: self.nonce,
+                valid_until: self.valid_until,
+                memo: &self.memo,
+            },
+            Body {
+                tag: DELEGATION_TX_TAG,
+                source_pk: &self.fee_payer_pk,
+                receiver_pk: &self.new_delegate_pk,
+                amount: 0,
+            },
+        )
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        signature_domain_string(network_id)
+    }
+}
@@ -0,0 +1,760 @@
+//! Mina zkApp commands
+//!
+//! A zkApp command is authorized by signing one of its two commitments with a
+//! kimchi signer (see [`create_kimchi`](crate::create_kimchi)):
+//! * the [commitment](ZkappCommand::commitment) to its forest of account
+//!   updates, signed by the account updates that do not use the full
+//!   commitment, and
+//! * the [full commitment](ZkappCommand::full_commitment), which also covers
+//!   the memo and the fee payer, signed by the fee payer and by the account
+//!   updates that use the full commitment.
+//!
+//! Account updates are given by the digests of their bodies, which are
+//! computed from an [`AccountUpdateBody`] with [`AccountUpdateBody::hash`].
+//!
+//! The hashes follow the layout and the hash prefixes of the OCaml
+//! implementation, but have not been checked against known-answer vectors
+//! of the OCaml implementation or of o1js yet: the tests only check their
+//! structure, see `tests/zkapp.rs`.
+
+use ark_ff::{Field, PrimeField, Zero};
+use mina_curves::pasta::Fp;
+use mina_hasher::{create_kimchi, Hashable, Hasher, ROInput};
+
+use crate::{transaction::Memo, BaseField, CompressedPubKey, NetworkId};
+
+/// Account update, given by the digest of its body and by the account updates
+/// it calls
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountUpdate {
+    /// Digest of the body of the account update
+    pub body_hash: BaseField,
+    /// Account updates called by this account update
+    pub calls: Vec<AccountUpdate>,
+}
+
+/// zkApp command
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ZkappCommand {
+    /// Digest of the body of the fee payer account update
+    pub fee_payer_hash: BaseField,
+    /// Forest of account updates
+    pub account_updates: Vec<AccountUpdate>,
+    /// Memo
+    pub memo: Memo,
+}
+
+/// Digest of an account update node, made of the digest of the account
+/// update body and of the digest of the forest it calls
+#[derive(Clone)]
+struct Node {
+    body_hash: BaseField,
+    calls_hash: BaseField,
+}
+
+impl Hashable for Node {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new()
+            .append_field(self.body_hash)
+            .append_field(self.calls_hash)
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        "MinaAcctUpdateNode".to_string().into()
+    }
+}
+
+/// Digest of a list of field elements, with the domain string used for
+/// consing account update trees
+#[derive(Clone)]
+struct Cons(Vec<BaseField>);
+
+impl Hashable for Cons {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        self.0
+            .iter()
+            .fold(ROInput::new(), |roi, f| roi.append_field(*f))
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        "MinaAcctUpdateCons".to_string().into()
+    }
+}
+
+impl Hashable for Memo {
+    type D = ();
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new().append_bytes(self.as_bytes())
+    }
+
+    fn domain_string(_: Self::D) -> Option<String> {
+        "MinaZkappMemo".to_string().into()
+    }
+}
+
+impl AccountUpdate {
+    /// Create an account update that calls no other account update
+    pub fn new(body_hash: BaseField) -> Self {
+        AccountUpdate {
+            body_hash,
+            calls: vec![],
+        }
+    }
+
+    /// Set the account updates called by this account update
+    pub fn set_calls(mut self, calls: Vec<AccountUpdate>) -> Self {
+        self.calls = calls;
+
+        self
+    }
+
+    /// Digest of the tree of account updates rooted at this account update
+    pub fn tree_hash(&self) -> BaseField {
+        create_kimchi::<Node>(()).hash(&Node {
+            body_hash: self.body_hash,
+            calls_hash: forest_hash(&self.calls),
+        })
+    }
+}
+
+/// Digest of a forest of account updates
+///
+/// The empty forest has digest zero, and the digest of a non-empty forest
+/// conses the digest of its first tree onto the digest of the others.
+pub fn forest_hash(forest: &[AccountUpdate]) -> BaseField {
+    let mut hasher = create_kimchi::<Cons>(());
+    forest.iter().rev().fold(BaseField::zero(), |tail, tree| {
+        hasher.hash(&Cons(vec![tree.tree_hash(), tail]))
+    })
+}
+
+impl ZkappCommand {
+    /// Create a zkApp command paid by the fee payer with body digest
+    /// `fee_payer_hash`, with an empty memo
+    pub fn new(fee_payer_hash: BaseField, account_updates: Vec<AccountUpdate>) -> Self {
+        ZkappCommand {
+            fee_payer_hash,
+            account_updates,
+            memo: Memo::default(),
+        }
+    }
+
+    /// Set the memo of the command
+    pub fn set_memo(mut self, memo: Memo) -> Self {
+        self.memo = memo;
+
+        self
+    }
+
+    /// Commitment to the forest of account updates of the command
+    pub fn commitment(&self) -> ZkappCommitment {
+        ZkappCommitment(forest_hash(&self.account_updates))
+    }
+
+    /// Commitment to the memo, the fee payer and the forest of account updates
+    /// of the command
+    pub fn full_commitment(&self) -> ZkappCommitment {
+        let memo_hash = create_kimchi::<Memo>(()).hash(&self.memo);
+        let ZkappCommitment(commitment) = self.commitment();
+        ZkappCommitment(create_kimchi::<Cons>(()).hash(&Cons(vec![
+            memo_hash,
+            self.fee_payer_hash,
+            commitment,
+        ])))
+    }
+}
+
+/// Commitment to a zkApp command, which is the message signed to authorize the
+/// command (see [`ZkappCommand::commitment`] and
+/// [`ZkappCommand::full_commitment`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZkappCommitment(pub BaseField);
+
+impl Hashable for ZkappCommitment {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        ROInput::new().append_field(self.0)
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        // Domain strings must have length <= 20
+        match network_id {
+            NetworkId::MAINNET => "MinaSignatureMainnet",
+            NetworkId::TESTNET => "CodaSignature",
+        }
+        .to_string()
+        .into()
+    }
+}
+
+/// Input of the kimchi hash of account update bodies
+///
+/// Field elements are hashed first, followed by the small values, which are
+/// packed greedily into field elements, the first value in the most
+/// significant bits.
+#[derive(Default)]
+struct Packed {
+    fields: Vec<BaseField>,
+    packed: Vec<(u64, u32)>,
+}
+
+impl Packed {
+    fn field(mut self, f: BaseField) -> Self {
+        self.fields.push(f);
+
+        self
+    }
+
+    fn bits(mut self, x: u64, bits: u32) -> Self {
+        self.packed.push((x, bits));
+
+        self
+    }
+
+    fn bool(self, b: bool) -> Self {
+        self.bits(b as u64, 1)
+    }
+
+    fn into_roinput(self) -> ROInput {
+        let roi = self
+            .fields
+            .iter()
+            .fold(ROInput::new(), |roi, f| roi.append_field(*f));
+        let mut chunks = vec![];
+        let (mut acc, mut acc_bits) = (BaseField::zero(), 0);
+        for (x, bits) in self.packed {
+            if acc_bits + bits >= BaseField::MODULUS_BIT_SIZE {
+                chunks.push(acc);
+                (acc, acc_bits) = (BaseField::zero(), 0);
+            }
+            acc = acc * BaseField::from(2u64).pow([bits as u64]) + BaseField::from(x);
+            acc_bits += bits;
+        }
+        if acc_bits > 0 {
+            chunks.push(acc);
+        }
+        chunks.into_iter().fold(roi, |roi, f| roi.append_field(f))
+    }
+}
+
+/// Components of account update bodies
+trait Pack {
+    fn pack(&self, input: Packed) -> Packed;
+}
+
+impl Pack for Fp {
+    fn pack(&self, input: Packed) -> Packed {
+        input.field(*self)
+    }
+}
+
+impl Pack for bool {
+    fn pack(&self, input: Packed) -> Packed {
+        input.bool(*self)
+    }
+}
+
+impl Pack for u32 {
+    fn pack(&self, input: Packed) -> Packed {
+        input.bits(*self as u64, 32)
+    }
+}
+
+impl Pack for u64 {
+    fn pack(&self, input: Packed) -> Packed {
+        input.bits(*self, 64)
+    }
+}
+
+impl Pack for CompressedPubKey {
+    fn pack(&self, input: Packed) -> Packed {
+        input.field(self.x).bool(self.is_odd)
+    }
+}
+
+impl<T: Pack> Pack for [T] {
+    fn pack(&self, input: Packed) -> Packed {
+        self.iter().fold(input, |input, x| x.pack(input))
+    }
+}
+
+/// Optional value of an account update body
+///
+/// The value of a missing entry is still hashed, so it must be set to the
+/// dummy value used by the protocol for this entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Flagged<T> {
+    /// Whether the entry is present
+    pub is_some: bool,
+    /// Value of the entry
+    pub value: T,
+}
+
+/// Field of the account that is either set to a value or kept
+pub type SetOrKeep<T> = Flagged<T>;
+
+/// Precondition that is either checked or ignored
+pub type OrIgnore<T> = Flagged<T>;
+
+impl<T> Flagged<T> {
+    /// Present entry with value `value`
+    pub fn some(value: T) -> Self {
+        Flagged {
+            is_some: true,
+            value,
+        }
+    }
+
+    /// Missing entry, hashed with dummy value `value`
+    pub fn none(value: T) -> Self {
+        Flagged {
+            is_some: false,
+            value,
+        }
+    }
+}
+
+impl<T: Pack> Pack for Flagged<T> {
+    fn pack(&self, input: Packed) -> Packed {
+        self.value.pack(input.bool(self.is_some))
+    }
+}
+
+/// Closed interval of values, by default the interval of all values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClosedInterval<T> {
+    /// Lower bound
+    pub lower: T,
+    /// Upper bound
+    pub upper: T,
+}
+
+/// Precondition on a number, which must lie in an interval unless ignored
+pub type Numeric<T> = OrIgnore<ClosedInterval<T>>;
+
+impl Default for ClosedInterval<u32> {
+    fn default() -> Self {
+        ClosedInterval {
+            lower: 0,
+            upper: u32::MAX,
+        }
+    }
+}
+
+impl Default for ClosedInterval<u64> {
+    fn default() -> Self {
+        ClosedInterval {
+            lower: 0,
+            upper: u64::MAX,
+        }
+    }
+}
+
+impl<T: Pack> Pack for ClosedInterval<T> {
+    fn pack(&self, input: Packed) -> Packed {
+        self.upper.pack(self.lower.pack(input))
+    }
+}
+
+/// Authorization required to change a field of an account, in the encoding
+/// of the protocol
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuthRequired {
+    /// Whether the authorization does not depend on the authorization of the
+    /// account update
+    pub constant: bool,
+    /// Whether a signature is necessary
+    pub signature_necessary: bool,
+    /// Whether a signature is sufficient
+    pub signature_sufficient: bool,
+}
+
+impl Pack for AuthRequired {
+    fn pack(&self, input: Packed) -> Packed {
+        input
+            .bool(self.constant)
+            .bool(self.signature_necessary)
+            .bool(self.signature_sufficient)
+    }
+}
+
+/// Permissions of an account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Permissions {
+    /// Permission to edit the zkApp state
+    pub edit_state: AuthRequired,
+    /// Permission to access the account
+    pub access: AuthRequired,
+    /// Permission to send funds
+    pub send: AuthRequired,
+    /// Permission to receive funds
+    pub receive: AuthRequired,
+    /// Permission to set the delegate
+    pub set_delegate: AuthRequired,
+    /// Permission to set the permissions
+    pub set_permissions: AuthRequired,
+    /// Permission to set the verification key, and the transaction version
+    /// it was set for
+    pub set_verification_key: (AuthRequired, u32),
+    /// Permission to set the zkApp URI
+    pub set_zkapp_uri: AuthRequired,
+    /// Permission to edit the action state
+    pub edit_action_state: AuthRequired,
+    /// Permission to set the token symbol
+    pub set_token_symbol: AuthRequired,
+    /// Permission to increment the nonce
+    pub increment_nonce: AuthRequired,
+    /// Permission to set the voting for field
+    pub set_voting_for: AuthRequired,
+    /// Permission to set the timing
+    pub set_timing: AuthRequired,
+}
+
+impl Pack for Permissions {
+    fn pack(&self, input: Packed) -> Packed {
+        let input = [
+            &self.edit_state,
+            &self.access,
+            &self.send,
+            &self.receive,
+            &self.set_delegate,
+            &self.set_permissions,
+        ]
+        .iter()
+        .fold(input, |input, auth| auth.pack(input));
+        let (auth, txn_version) = &self.set_verification_key;
+        let input = txn_version.pack(auth.pack(input));
+        [
+            &self.set_zkapp_uri,
+            &self.edit_action_state,
+            &self.set_token_symbol,
+            &self.increment_nonce,
+            &self.set_voting_for,
+            &self.set_timing,
+        ]
+        .iter()
+        .fold(input, |input, auth| auth.pack(input))
+    }
+}
+
+/// Token symbol, of at most 6 bytes
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenSymbol(pub String);
+
+impl Pack for TokenSymbol {
+    fn pack(&self, input: Packed) -> Packed {
+        let bytes = self.0.as_bytes();
+        assert!(bytes.len() <= 6, "token symbol longer than 6 bytes");
+        let value = bytes
+            .iter()
+            .rev()
+            .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+        input.bits(value, 48)
+    }
+}
+
+/// Vesting schedule of an account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timing {
+    /// Initial minimum balance
+    pub initial_minimum_balance: u64,
+    /// Global slot of the cliff
+    pub cliff_time: u32,
+    /// Amount released at the cliff
+    pub cliff_amount: u64,
+    /// Number of slots between two releases
+    pub vesting_period: u32,
+    /// Amount released at each vesting period
+    pub vesting_increment: u64,
+}
+
+impl Pack for Timing {
+    fn pack(&self, input: Packed) -> Packed {
+        let input = self
+            .cliff_time
+            .pack(self.initial_minimum_balance.pack(input));
+        let input = self.vesting_period.pack(self.cliff_amount.pack(input));
+        self.vesting_increment.pack(input)
+    }
+}
+
+/// Updates of the fields of an account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Update {
+    /// zkApp state
+    pub app_state: [SetOrKeep<BaseField>; 8],
+    /// Delegate
+    pub delegate: SetOrKeep<CompressedPubKey>,
+    /// Digest of the verification key
+    pub verification_key_hash: SetOrKeep<BaseField>,
+    /// Permissions
+    pub permissions: SetOrKeep<Permissions>,
+    /// Digest of the zkApp URI
+    pub zkapp_uri_hash: SetOrKeep<BaseField>,
+    /// Token symbol
+    pub token_symbol: SetOrKeep<TokenSymbol>,
+    /// Timing
+    pub timing: SetOrKeep<Timing>,
+    /// State hash voted for
+    pub voting_for: SetOrKeep<BaseField>,
+}
+
+impl Pack for Update {
+    fn pack(&self, input: Packed) -> Packed {
+        let input = self.app_state.pack(input);
+        let input = self.delegate.pack(input);
+        let input = self.verification_key_hash.pack(input);
+        let input = self.permissions.pack(input);
+        let input = self.zkapp_uri_hash.pack(input);
+        let input = self.token_symbol.pack(input);
+        let input = self.timing.pack(input);
+        self.voting_for.pack(input)
+    }
+}
+
+/// Change of the balance of an account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BalanceChange {
+    /// Magnitude of the change, in nanomina
+    pub magnitude: u64,
+    /// Whether the balance decreases
+    pub is_negative: bool,
+}
+
+impl Pack for BalanceChange {
+    fn pack(&self, input: Packed) -> Packed {
+        input.bits(self.magnitude, 64).bool(!self.is_negative)
+    }
+}
+
+/// Preconditions on the data of an epoch
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EpochData {
+    /// Digest of the staking ledger
+    pub ledger_hash: OrIgnore<BaseField>,
+    /// Total currency of the staking ledger
+    pub ledger_total_currency: Numeric<u64>,
+    /// Seed of the epoch
+    pub seed: OrIgnore<BaseField>,
+    /// Start checkpoint of the epoch
+    pub start_checkpoint: OrIgnore<BaseField>,
+    /// Lock checkpoint of the epoch
+    pub lock_checkpoint: OrIgnore<BaseField>,
+    /// Length of the epoch
+    pub epoch_length: Numeric<u32>,
+}
+
+impl Pack for EpochData {
+    fn pack(&self, input: Packed) -> Packed {
+        let input = self.ledger_hash.pack(input);
+        let input = self.ledger_total_currency.pack(input);
+        let input = self.seed.pack(input);
+        let input = self.start_checkpoint.pack(input);
+        let input = self.lock_checkpoint.pack(input);
+        self.epoch_length.pack(input)
+    }
+}
+
+/// Preconditions on the state of the network
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NetworkPreconditions {
+    /// Digest of the snarked ledger
+    pub snarked_ledger_hash: OrIgnore<BaseField>,
+    /// Length of the blockchain
+    pub blockchain_length: Numeric<u32>,
+    /// Minimum window density
+    pub min_window_density: Numeric<u32>,
+    /// Total currency
+    pub total_currency: Numeric<u64>,
+    /// Global slot since genesis
+    pub global_slot_since_genesis: Numeric<u32>,
+    /// Data of the staking epoch
+    pub staking_epoch_data: EpochData,
+    /// Data of the next epoch
+    pub next_epoch_data: EpochData,
+}
+
+impl Pack for NetworkPreconditions {
+    fn pack(&self, input: Packed) -> Packed {
+        let input = self.snarked_ledger_hash.pack(input);
+        let input = self.blockchain_length.pack(input);
+        let input = self.min_window_density.pack(input);
+        let input = self.total_currency.pack(input);
+        let input = self.global_slot_since_genesis.pack(input);
+        let input = self.staking_epoch_data.pack(input);
+        self.next_epoch_data.pack(input)
+    }
+}
+
+/// Preconditions on the account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountPreconditions {
+    /// Balance
+    pub balance: Numeric<u64>,
+    /// Nonce
+    pub nonce: Numeric<u32>,
+    /// Receipt chain hash
+    pub receipt_chain_hash: OrIgnore<BaseField>,
+    /// Delegate
+    pub delegate: OrIgnore<CompressedPubKey>,
+    /// zkApp state
+    pub state: [OrIgnore<BaseField>; 8],
+    /// Action state
+    pub action_state: OrIgnore<BaseField>,
+    /// Whether the zkApp state was set by proofs only
+    pub proved_state: OrIgnore<bool>,
+    /// Whether the account is new
+    pub is_new: OrIgnore<bool>,
+}
+
+impl Pack for AccountPreconditions {
+    fn pack(&self, input: Packed) -> Packed {
+        let input = self.balance.pack(input);
+        let input = self.nonce.pack(input);
+        let input = self.receipt_chain_hash.pack(input);
+        let input = self.delegate.pack(input);
+        let input = self.state.pack(input);
+        let input = self.action_state.pack(input);
+        let input = self.proved_state.pack(input);
+        self.is_new.pack(input)
+    }
+}
+
+/// Preconditions of an account update
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Preconditions {
+    /// Preconditions on the state of the network
+    pub network: NetworkPreconditions,
+    /// Preconditions on the account
+    pub account: AccountPreconditions,
+    /// Global slots since genesis during which the account update is valid
+    pub valid_while: Numeric<u32>,
+}
+
+impl Pack for Preconditions {
+    fn pack(&self, input: Packed) -> Packed {
+        let input = self.network.pack(input);
+        let input = self.account.pack(input);
+        self.valid_while.pack(input)
+    }
+}
+
+/// Whether an account update may use the token of its account
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MayUseToken {
+    /// The token is owned by the parent account update
+    pub parents_own_token: bool,
+    /// The permission is inherited from the parent account update
+    pub inherit_from_parent: bool,
+}
+
+impl Pack for MayUseToken {
+    fn pack(&self, input: Packed) -> Packed {
+        input
+            .bool(self.parents_own_token)
+            .bool(self.inherit_from_parent)
+    }
+}
+
+/// Kind of the authorization of an account update
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuthorizationKind {
+    /// The account update is signed
+    pub is_signed: bool,
+    /// The account update is proved
+    pub is_proved: bool,
+    /// Digest of the verification key of the proof, or the dummy digest of
+    /// the protocol if the account update is not proved
+    pub verification_key_hash: BaseField,
+}
+
+impl Pack for AuthorizationKind {
+    fn pack(&self, input: Packed) -> Packed {
+        input
+            .bool(self.is_signed)
+            .bool(self.is_proved)
+            .field(self.verification_key_hash)
+    }
+}
+
+/// Body of an account update
+///
+/// Events and actions are given by their digests. The default body has zero
+/// or empty values everywhere, which are not always the dummy values of the
+/// protocol (e.g. for the token id, the empty events and actions or the
+/// verification key digest of signed account updates).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountUpdateBody {
+    /// Public key of the account
+    pub public_key: CompressedPubKey,
+    /// Token id of the account
+    pub token_id: BaseField,
+    /// Updates of the fields of the account
+    pub update: Update,
+    /// Change of the balance of the account
+    pub balance_change: BalanceChange,
+    /// Whether the nonce of the account is incremented
+    pub increment_nonce: bool,
+    /// Digest of the events
+    pub events_hash: BaseField,
+    /// Digest of the actions
+    pub actions_hash: BaseField,
+    /// Call data
+    pub call_data: BaseField,
+    /// Preconditions
+    pub preconditions: Preconditions,
+    /// Whether the account update is authorized by the full commitment
+    pub use_full_commitment: bool,
+    /// Whether the account creation fee is paid from the balance change
+    pub implicit_account_creation_fee: bool,
+    /// Whether the account update may use the token of its account
+    pub may_use_token: MayUseToken,
+    /// Kind of the authorization
+    pub authorization_kind: AuthorizationKind,
+}
+
+impl Hashable for AccountUpdateBody {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> ROInput {
+        let input = Packed::default().field(self.public_key.x);
+        let input = input.bool(self.public_key.is_odd).field(self.token_id);
+        let input = self.update.pack(input);
+        let input = self.balance_change.pack(input);
+        let input = input
+            .bool(self.increment_nonce)
+            .field(self.events_hash)
+            .field(self.actions_hash)
+            .field(self.call_data);
+        let input = self.preconditions.pack(input);
+        let input = input
+            .bool(self.use_full_commitment)
+            .bool(self.implicit_account_creation_fee);
+        let input = self.may_use_token.pack(input);
+        self.authorization_kind.pack(input).into_roinput()
+    }
+
+    fn domain_string(network_id: NetworkId) -> Option<String> {
+        match network_id {
+            NetworkId::MAINNET => "MainnetZkappBody",
+            NetworkId::TESTNET => "TestnetZkappBody",
+        }
+        .to_string()
+        .into()
+    }
+}
+
+impl AccountUpdateBody {
+    /// Digest of the body on network `network_id`, which is the body digest
+    /// of the [`AccountUpdate`] (or the fee payer digest of the
+    /// [`ZkappCommand`])
+    pub fn hash(&self, network_id: NetworkId) -> BaseField {
+        create_kimchi::<AccountUpdateBody>(network_id).hash(self)
+    }
+}
@@ -0,0 +1,119 @@
+use mina_signer::{
+    self,
+    transaction::{Memo, Payment},
+    zkapp::{AccountUpdate, ZkappCommand, ZkappCommitment},
+    BaseField, Keypair, NetworkId, ScalarField, Signature, Signer,
+};
+
+fn payments(n: usize) -> Vec<(Keypair, Payment)> {
+    let mut rng = rand::thread_rng();
+    (0..n)
+        .map(|i| {
+            let kp = Keypair::rand(&mut rng).expect("failed to generate keypair");
+            let receiver = Keypair::rand(&mut rng).expect("failed to generate keypair");
+            let payment = Payment::new(
+                kp.public.into_compressed(),
+                receiver.public.into_compressed(),
+                1000 * i as u64,
+                10,
+                i as u32,
+            )
+            .set_memo(Memo::from_string(&format!("payment {i}")));
+            (kp, payment)
+        })
+        .collect()
+}
+
+#[test]
+fn verify_batch_payments() {
+    let payments = payments(8);
+    let mut ctx = mina_signer::create_legacy::<Payment>(NetworkId::TESTNET);
+    let sigs: Vec<Signature> = payments
+        .iter()
+        .map(|(kp, payment)| ctx.sign(kp, payment))
+        .collect();
+    let batch: Vec<_> = sigs
+        .iter()
+        .zip(&payments)
+        .map(|(sig, (kp, payment))| (sig, &kp.public, payment))
+        .collect();
+
+    assert!(ctx.verify_batch(&batch));
+    assert!(ctx.verify_batch(&batch[..1]));
+    assert!(ctx.verify_batch(&[]));
+
+    // Signatures from another network
+    let mut mainnet_ctx = mina_signer::create_legacy::<Payment>(NetworkId::MAINNET);
+    assert!(!mainnet_ctx.verify_batch(&batch));
+
+    // Signature for another public key
+    let mut wrong_key = batch.clone();
+    wrong_key[3].1 = &payments[4].0.public;
+    assert!(!ctx.verify_batch(&wrong_key));
+
+    // Signature for another input
+    let mut wrong_input = batch.clone();
+    wrong_input[7].2 = &payments[0].1;
+    assert!(!ctx.verify_batch(&wrong_input));
+
+    // Signatures whose errors would cancel out in a sum
+    let mut sig0 = sigs[0].clone();
+    let mut sig1 = sigs[1].clone();
+    sig0.s += ScalarField::from(1u64);
+    sig1.s -= ScalarField::from(1u64);
+    let mut cancelling = batch.clone();
+    cancelling[0].0 = &sig0;
+    cancelling[1].0 = &sig1;
+    assert!(!ctx.verify_batch(&cancelling));
+
+    // rx with odd y-coordinate, or that is not the x-coordinate of any point
+    for rx in [-sigs[2].rx, BaseField::from(0u64), BaseField::from(5u64)] {
+        let sig = Signature::new(rx, sigs[2].s);
+        let mut wrong_sig = batch.clone();
+        wrong_sig[2].0 = &sig;
+        assert!(!ctx.verify(&sig, &payments[2].0.public, &payments[2].1));
+        assert!(!ctx.verify_batch(&wrong_sig));
+    }
+}
+
+#[test]
+fn verify_batch_matches_verify() {
+    let payments = payments(4);
+    let mut ctx = mina_signer::create_kimchi::<Payment>(NetworkId::MAINNET);
+    for (kp, payment) in &payments {
+        let sig = ctx.sign(kp, payment);
+        assert!(ctx.verify(&sig, &kp.public, payment));
+        assert!(ctx.verify_batch(&[(&sig, &kp.public, payment)]));
+
+        let mut other = payment.clone();
+        other.nonce += 1;
+        assert!(!ctx.verify(&sig, &kp.public, &other));
+        assert!(!ctx.verify_batch(&[(&sig, &kp.public, &other)]));
+    }
+}
+
+#[test]
+fn verify_batch_zkapp_commitments() {
+    let mut rng = rand::thread_rng();
+    let kp = Keypair::rand(&mut rng).expect("failed to generate keypair");
+    let command = ZkappCommand::new(
+        BaseField::from(1u64),
+        vec![AccountUpdate::new(BaseField::from(2u64))
+            .set_calls(vec![AccountUpdate::new(BaseField::from(3u64))])],
+    );
+
+    let mut ctx = mina_signer::create_kimchi::<ZkappCommitment>(NetworkId::TESTNET);
+    let commitment = command.commitment();
+    let full_commitment = command.full_commitment();
+    let sig = ctx.sign(&kp, &commitment);
+    let full_sig = ctx.sign(&kp, &full_commitment);
+
+    assert!(ctx.verify_batch(&[
+        (&sig, &kp.public, &commitment),
+        (&full_sig, &kp.public, &full_commitment)
+    ]));
+    assert!(!ctx.verify_batch(&[
+        (&sig, &kp.public, &full_commitment),
+        (&full_sig, &kp.public, &commitment)
+    ]));
+}
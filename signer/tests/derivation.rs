@@ -0,0 +1,105 @@
+use mina_signer::{
+    derivation::{self, DerivationError},
+    Keypair,
+};
+
+// Test mnemonic of the Mina ledger application
+const MNEMONIC: &str = "course grief vintage slim tell hospital car maze model style elegant kitchen state purpose matrix gas grid enable frown road goddess glove canyon key";
+
+#[test]
+fn account_path() {
+    assert_eq!(derivation::account_path(0), "m/44'/12586'/0'/0/0");
+    assert_eq!(derivation::account_path(49370), "m/44'/12586'/49370'/0/0");
+}
+
+#[test]
+fn keypair_from_mnemonic() {
+    for (account, secret_key, address) in [
+        (
+            0,
+            "164244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718",
+            "B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV",
+        ),
+        (
+            1,
+            "3ca187a58f09da346844964310c7e0dd948a9105702b716f4d732e042e0c172e",
+            "B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt",
+        ),
+        (
+            2,
+            "336eb4a19b3d8905824b0f2254fb495573be302c17582748bf7e101965aa4774",
+            "B62qrKG4Z8hnzZqp1AL8WsQhQYah3quN1qUj3SyfJA8Lw135qWWg1mi",
+        ),
+        (
+            3,
+            "1dee867358d4000f1dafa5978341fb515f89eeddbe450bd57df091f1e63d4444",
+            "B62qoqiAgERjCjXhofXiD7cMLJSKD8hE8ZtMh4jX5MPNgKB4CFxxm1N",
+        ),
+        (
+            49370,
+            "20f84123a26e58dd32b0ea3c80381f35cd01bc22a20346cc65b0a67ae48532ba",
+            "B62qkiT4kgCawkSEF84ga5kP9QnhmTJEYzcfgGuk6okAJtSBfVcjm1M",
+        ),
+        (
+            12586,
+            "3414fc16e86e6ac272fda03cf8dcb4d7d47af91b4b726494dab43bf773ce1779",
+            "B62qoG5Yk4iVxpyczUrBNpwtx2xunhL48dydN53A2VjoRwF8NUTbVr4",
+        ),
+    ] {
+        let kp = derivation::keypair_from_mnemonic(MNEMONIC, "", account)
+            .expect("failed to derive keypair");
+        assert_eq!(kp.to_hex(), secret_key);
+        assert_eq!(kp.public.into_address(), address);
+        assert_eq!(
+            kp,
+            Keypair::from_hex(secret_key).expect("failed to create keypair")
+        );
+    }
+}
+
+#[test]
+fn derive_keypair() {
+    let seed = derivation::seed_from_mnemonic(MNEMONIC, "").expect("invalid mnemonic");
+    let kp =
+        derivation::derive_keypair(&seed, "m/44'/12586'/3'/0/0").expect("failed to derive keypair");
+    assert_eq!(
+        kp.public.into_address(),
+        "B62qoqiAgERjCjXhofXiD7cMLJSKD8hE8ZtMh4jX5MPNgKB4CFxxm1N"
+    );
+
+    // The password is part of the seed
+    let protected = derivation::seed_from_mnemonic(MNEMONIC, "password").expect("invalid mnemonic");
+    assert_ne!(seed, protected);
+    assert_ne!(
+        derivation::derive_keypair(&protected, "m/44'/12586'/3'/0/0")
+            .expect("failed to derive keypair"),
+        kp
+    );
+}
+
+#[test]
+fn derivation_errors() {
+    assert_eq!(
+        derivation::seed_from_mnemonic("course grief vintage", ""),
+        Err(DerivationError::Mnemonic)
+    );
+    // Wrong checksum
+    assert_eq!(
+        derivation::seed_from_mnemonic(&MNEMONIC.replace("key", "kitchen"), ""),
+        Err(DerivationError::Mnemonic)
+    );
+
+    let seed = derivation::seed_from_mnemonic(MNEMONIC, "").expect("invalid mnemonic");
+    assert_eq!(
+        derivation::derive_keypair(&seed, "44'/12586'/0'/0/0"),
+        Err(DerivationError::Path)
+    );
+    assert_eq!(
+        derivation::derive_keypair(&seed, "m/44'/x'/0'/0/0"),
+        Err(DerivationError::Path)
+    );
+    assert_eq!(
+        derivation::derive_keypair(&seed[..8], "m/44'/12586'/0'/0/0"),
+        Err(DerivationError::Derivation)
+    );
+}
@@ -0,0 +1,254 @@
+pub mod transaction;
+
+use mina_signer::{
+    self,
+    musig::{self, AggNonce, KeyAggContext, MuSigError, PartialSignature, PubNonce},
+    Keypair, NetworkId, PubKey, Signer,
+};
+use transaction::Transaction;
+
+fn keypairs(n: usize) -> Vec<Keypair> {
+    (0..n)
+        .map(|_| Keypair::rand(&mut rand::rngs::OsRng).expect("failed to generate keypair"))
+        .collect()
+}
+
+fn payment(from: &PubKey) -> Transaction {
+    Transaction::new_payment(
+        from.clone(),
+        PubKey::from_address("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt")
+            .expect("invalid address"),
+        1729000000000,
+        2000000000,
+        16,
+    )
+    .set_memo_str("multisig")
+}
+
+#[test]
+fn musig_sign_verify() {
+    for n in [1, 2, 5] {
+        let kps = keypairs(n);
+        let pub_keys: Vec<PubKey> = kps.iter().map(|kp| kp.public.clone()).collect();
+        let ctx = KeyAggContext::new(&pub_keys).expect("failed to aggregate keys");
+        let tx = payment(ctx.aggregate_pub_key());
+
+        // Round 1
+        let (sec_nonces, pub_nonces): (Vec<_>, Vec<_>) = kps
+            .iter()
+            .map(|kp| musig::nonce_gen(&mut rand::rngs::OsRng, kp, &ctx))
+            .unzip();
+        let agg_nonce = AggNonce::aggregate(&pub_nonces).expect("failed to aggregate nonces");
+
+        // Round 2
+        let mut musig = musig::create_legacy::<Transaction>(NetworkId::TESTNET);
+        let partials: Vec<PartialSignature> = sec_nonces
+            .into_iter()
+            .zip(&kps)
+            .map(|(sec_nonce, kp)| {
+                musig
+                    .partial_sign(&ctx, sec_nonce, &agg_nonce, kp, &tx)
+                    .expect("failed to sign")
+            })
+            .collect();
+        for ((partial, pub_nonce), pub_key) in partials.iter().zip(&pub_nonces).zip(&pub_keys) {
+            assert!(musig.partial_verify(&ctx, pub_nonce, &agg_nonce, pub_key, partial, &tx));
+        }
+        let sig = musig
+            .aggregate(&ctx, &agg_nonce, &partials, &tx)
+            .expect("failed to aggregate signature");
+
+        // The aggregated signature is an ordinary signature
+        let mut testnet_ctx = mina_signer::create_legacy::<Transaction>(NetworkId::TESTNET);
+        let mut mainnet_ctx = mina_signer::create_legacy::<Transaction>(NetworkId::MAINNET);
+        assert!(testnet_ctx.verify(&sig, ctx.aggregate_pub_key(), &tx));
+        assert!(!mainnet_ctx.verify(&sig, ctx.aggregate_pub_key(), &tx));
+        assert!(!testnet_ctx.verify(&sig, &pub_keys[0], &tx));
+        assert!(!testnet_ctx.verify(
+            &sig,
+            ctx.aggregate_pub_key(),
+            &tx.clone().set_valid_until(1)
+        ));
+
+        // A missing partial signature gives an invalid signature
+        if n > 1 {
+            let sig = musig
+                .aggregate(&ctx, &agg_nonce, &partials[1..], &tx)
+                .expect("failed to aggregate signature");
+            assert!(!testnet_ctx.verify(&sig, ctx.aggregate_pub_key(), &tx));
+        }
+    }
+}
+
+#[test]
+fn musig_kimchi() {
+    let kps = keypairs(3);
+    let pub_keys: Vec<PubKey> = kps.iter().map(|kp| kp.public.clone()).collect();
+    let ctx = KeyAggContext::new(&pub_keys).expect("failed to aggregate keys");
+    let tx = payment(&pub_keys[0]);
+
+    let (sec_nonces, pub_nonces): (Vec<_>, Vec<_>) = kps
+        .iter()
+        .map(|kp| musig::nonce_gen(&mut rand::rngs::OsRng, kp, &ctx))
+        .unzip();
+    let agg_nonce = AggNonce::aggregate(&pub_nonces).expect("failed to aggregate nonces");
+
+    let mut musig = musig::create_kimchi::<Transaction>(NetworkId::MAINNET);
+    let partials: Vec<PartialSignature> = sec_nonces
+        .into_iter()
+        .zip(&kps)
+        .map(|(sec_nonce, kp)| {
+            musig
+                .partial_sign(&ctx, sec_nonce, &agg_nonce, kp, &tx)
+                .expect("failed to sign")
+        })
+        .collect();
+    let sig = musig
+        .aggregate(&ctx, &agg_nonce, &partials, &tx)
+        .expect("failed to aggregate signature");
+
+    let mut kimchi_ctx = mina_signer::create_kimchi::<Transaction>(NetworkId::MAINNET);
+    let mut legacy_ctx = mina_signer::create_legacy::<Transaction>(NetworkId::MAINNET);
+    assert!(kimchi_ctx.verify(&sig, ctx.aggregate_pub_key(), &tx));
+    assert!(!legacy_ctx.verify(&sig, ctx.aggregate_pub_key(), &tx));
+}
+
+#[test]
+fn musig_misbehaving_signer() {
+    let kps = keypairs(3);
+    let pub_keys: Vec<PubKey> = kps.iter().map(|kp| kp.public.clone()).collect();
+    let ctx = KeyAggContext::new(&pub_keys).expect("failed to aggregate keys");
+    let tx = payment(ctx.aggregate_pub_key());
+    let mut musig = musig::create_legacy::<Transaction>(NetworkId::TESTNET);
+
+    let (sec_nonces, pub_nonces): (Vec<_>, Vec<_>) = kps
+        .iter()
+        .map(|kp| musig::nonce_gen(&mut rand::rngs::OsRng, kp, &ctx))
+        .unzip();
+    let agg_nonce = AggNonce::aggregate(&pub_nonces).expect("failed to aggregate nonces");
+    let mut sec_nonces = sec_nonces.into_iter();
+
+    // Secret nonce of another signer
+    let sec_nonce = sec_nonces.next().expect("missing nonce");
+    assert_eq!(
+        musig
+            .partial_sign(&ctx, sec_nonce, &agg_nonce, &kps[1], &tx)
+            .err(),
+        Some(MuSigError::NonceKeypair)
+    );
+
+    // Signer outside of the key aggregation context
+    let outsider = &keypairs(1)[0];
+    let (sec_nonce, _) = musig::nonce_gen(&mut rand::rngs::OsRng, outsider, &ctx);
+    assert_eq!(
+        musig
+            .partial_sign(&ctx, sec_nonce, &agg_nonce, outsider, &tx)
+            .err(),
+        Some(MuSigError::UnknownSigner)
+    );
+
+    // Partial signature on another input, or attributed to another signer
+    let sec_nonce = sec_nonces.next().expect("missing nonce");
+    let other_tx = tx.clone().set_valid_until(1);
+    let partial = musig
+        .partial_sign(&ctx, sec_nonce, &agg_nonce, &kps[1], &other_tx)
+        .expect("failed to sign");
+    assert!(musig.partial_verify(
+        &ctx,
+        &pub_nonces[1],
+        &agg_nonce,
+        &pub_keys[1],
+        &partial,
+        &other_tx
+    ));
+    assert!(!musig.partial_verify(
+        &ctx,
+        &pub_nonces[1],
+        &agg_nonce,
+        &pub_keys[1],
+        &partial,
+        &tx
+    ));
+    assert!(!musig.partial_verify(
+        &ctx,
+        &pub_nonces[2],
+        &agg_nonce,
+        &pub_keys[2],
+        &partial,
+        &other_tx
+    ));
+}
+
+#[test]
+fn musig_errors() {
+    assert_eq!(KeyAggContext::new(&[]), Err(MuSigError::NoSigners));
+    assert_eq!(AggNonce::aggregate(&[]), Err(MuSigError::AggregateNonce));
+
+    let kps = keypairs(2);
+    assert_eq!(
+        KeyAggContext::new(&[
+            kps[0].public.clone(),
+            kps[1].public.clone(),
+            kps[0].public.clone()
+        ]),
+        Err(MuSigError::DuplicateSigner)
+    );
+
+    let ctx = KeyAggContext::new(&[kps[0].public.clone()]).expect("failed to aggregate keys");
+    let (_, pub_nonce) = musig::nonce_gen(&mut rand::rngs::OsRng, &kps[0], &ctx);
+    let opposite = PubNonce {
+        r1: -pub_nonce.r1,
+        r2: -pub_nonce.r2,
+    };
+    assert_eq!(
+        AggNonce::aggregate(&[pub_nonce, opposite]),
+        Err(MuSigError::AggregateNonce)
+    );
+}
+
+#[test]
+fn musig_serialization() {
+    let kps = keypairs(2);
+    let pub_keys: Vec<PubKey> = kps.iter().map(|kp| kp.public.clone()).collect();
+    let ctx = KeyAggContext::new(&pub_keys).expect("failed to aggregate keys");
+    let tx = payment(ctx.aggregate_pub_key());
+
+    let (sec_nonce, pub_nonce) = musig::nonce_gen(&mut rand::rngs::OsRng, &kps[0], &ctx);
+    let (_, other_nonce) = musig::nonce_gen(&mut rand::rngs::OsRng, &kps[1], &ctx);
+    assert_eq!(pub_nonce.to_bytes().len(), 128);
+    assert_eq!(
+        PubNonce::from_bytes(&pub_nonce.to_bytes()),
+        Ok(pub_nonce.clone())
+    );
+    assert_eq!(
+        PubNonce::from_hex(&pub_nonce.to_hex()),
+        Ok(pub_nonce.clone())
+    );
+
+    let agg_nonce =
+        AggNonce::aggregate(&[pub_nonce, other_nonce]).expect("failed to aggregate nonces");
+    assert_eq!(
+        AggNonce::from_hex(&agg_nonce.to_hex()),
+        Ok(agg_nonce.clone())
+    );
+
+    let mut musig = musig::create_legacy::<Transaction>(NetworkId::TESTNET);
+    let partial = musig
+        .partial_sign(&ctx, sec_nonce, &agg_nonce, &kps[0], &tx)
+        .expect("failed to sign");
+    assert_eq!(partial.to_bytes().len(), 32);
+    assert_eq!(PartialSignature::from_hex(&partial.to_hex()), Ok(partial));
+
+    assert_eq!(PubNonce::from_bytes(&[0; 127]), Err(MuSigError::NonceBytes));
+    assert_eq!(PubNonce::from_hex("zz"), Err(MuSigError::Hex));
+    // Points not on the curve
+    assert_eq!(PubNonce::from_bytes(&[0; 128]), Err(MuSigError::NonceBytes));
+    assert_eq!(
+        PartialSignature::from_bytes(&[0; 31]),
+        Err(MuSigError::PartialSignatureBytes)
+    );
+    assert_eq!(
+        PartialSignature::from_bytes(&[0xff; 32]),
+        Err(MuSigError::PartialSignatureBytes)
+    );
+}
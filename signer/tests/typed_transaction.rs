@@ -0,0 +1,121 @@
+pub mod transaction;
+
+use mina_hasher::Hashable;
+use mina_signer::{
+    self,
+    transaction::{Memo, Payment, StakeDelegation, MEMO_MAX_LEN},
+    Keypair, NetworkId, PubKey, Signer,
+};
+use transaction::Transaction;
+
+fn address(address: &str) -> PubKey {
+    PubKey::from_address(address).expect("invalid address")
+}
+
+#[test]
+fn memo() {
+    assert_eq!(Memo::default().as_bytes()[..2], [1, 0]);
+    assert!(Memo::default().as_bytes()[2..].iter().all(|b| *b == 0));
+    assert!(Memo::default().data().is_empty());
+
+    let memo = Memo::from_string("Hello Mina!");
+    assert_eq!(memo.data(), b"Hello Mina!");
+    assert_eq!(&memo.as_bytes()[..4], &[1, 11, 72, 101]);
+
+    // Memo > max memo length (truncate)
+    let memo = Memo::from_string("012345678901234567890123456789012345");
+    assert_eq!(memo.data().len(), MEMO_MAX_LEN);
+    assert_eq!(memo.data(), b"01234567890123456789012345678901");
+}
+
+#[test]
+fn payment_layout() {
+    let from = address("B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV");
+    let to = address("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt");
+
+    let payment = Payment::new(
+        from.into_compressed(),
+        to.into_compressed(),
+        1729000000000,
+        2000000000,
+        16,
+    )
+    .set_valid_until(271828)
+    .set_memo(Memo::from_string("Hello Mina!"));
+    let tx = Transaction::new_payment(from, to, 1729000000000, 2000000000, 16)
+        .set_valid_until(271828)
+        .set_memo_str("Hello Mina!");
+
+    assert_eq!(payment.to_roinput(), tx.to_roinput());
+    assert_eq!(
+        Payment::domain_string(NetworkId::MAINNET),
+        Transaction::domain_string(NetworkId::MAINNET)
+    );
+    assert_eq!(
+        Payment::domain_string(NetworkId::TESTNET),
+        Transaction::domain_string(NetworkId::TESTNET)
+    );
+}
+
+#[test]
+fn delegation_layout() {
+    let from = address("B62qnzbXmRNo9q32n4SNu2mpB8e7FYYLH8NmaX6oFCBYjjQ8SbD7uzV");
+    let to = address("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt");
+
+    let delegation =
+        StakeDelegation::new(from.into_compressed(), to.into_compressed(), 2000000000, 16)
+            .set_valid_until(1337)
+            .set_memo(Memo::from_string("Delewho?"));
+    let tx = Transaction::new_delegation(from, to, 2000000000, 16)
+        .set_valid_until(1337)
+        .set_memo_str("Delewho?");
+
+    assert_eq!(delegation.to_roinput(), tx.to_roinput());
+}
+
+#[test]
+fn sign_payment() {
+    let kp = Keypair::from_hex("3414fc16e86e6ac272fda03cf8dcb4d7d47af91b4b726494dab43bf773ce1779")
+        .expect("failed to create keypair");
+    let payment = Payment::new(
+        kp.public.into_compressed(),
+        address("B62qoqiAgERjCjXhofXiD7cMLJSKD8hE8ZtMh4jX5MPNgKB4CFxxm1N").into_compressed(),
+        271828182845904,
+        100000,
+        5687,
+    )
+    .set_memo(Memo::from_string("01234567890123456789012345678901"));
+
+    let mut ctx = mina_signer::create_legacy::<Payment>(NetworkId::TESTNET);
+    let sig = ctx.sign(&kp, &payment);
+    assert_eq!(sig.to_string(), "2b4d0bffcb57981d11a93c05b17672b7be700d42af8496e1ba344394da5d0b0b0432c1e8a77ee1bd4b8ef6449297f7ed4956b81df95bdc6ac95d128984f77205");
+    assert!(ctx.verify(&sig, &kp.public, &payment));
+
+    let mut ctx = mina_signer::create_legacy::<Payment>(NetworkId::MAINNET);
+    let sig = ctx.sign(&kp, &payment);
+    assert_eq!(sig.to_string(), "076d8ebca8ccbfd9c8297a768f756ff9d08c049e585c12c636d57ffcee7f6b3b1bd4b9bd42cc2cbee34b329adbfc5127fe5a2ceea45b7f55a1048b7f1a9f7559");
+    assert!(ctx.verify(&sig, &kp.public, &payment));
+}
+
+#[test]
+fn sign_delegation() {
+    let kp = Keypair::from_hex("336eb4a19b3d8905824b0f2254fb495573be302c17582748bf7e101965aa4774")
+        .expect("failed to create keypair");
+    let delegation = StakeDelegation::new(
+        kp.public.into_compressed(),
+        address("B62qicipYxyEHu7QjUqS7QvBipTs5CzgkYZZZkPoKVYBu6tnDUcE9Zt").into_compressed(),
+        1202056900,
+        0,
+    )
+    .set_valid_until(577216);
+
+    let mut ctx = mina_signer::create_legacy::<StakeDelegation>(NetworkId::TESTNET);
+    let sig = ctx.sign(&kp, &delegation);
+    assert_eq!(sig.to_string(), "26ca6b95dee29d956b813afa642a6a62cd89b1929320ed6b099fd191a217b08d2c9a54ba1c95e5000b44b93cfbd3b625e20e95636f1929311473c10858a27f09");
+    assert!(ctx.verify(&sig, &kp.public, &delegation));
+
+    let mut ctx = mina_signer::create_legacy::<StakeDelegation>(NetworkId::MAINNET);
+    let sig = ctx.sign(&kp, &delegation);
+    assert_eq!(sig.to_string(), "093f9ef0e4e051279da0a3ded85553847590ab739ee1bfd59e5bb30f98ed8a001a7a60d8506e2572164b7a525617a09f17e1756ac37555b72e01b90f37271595");
+    assert!(ctx.verify(&sig, &kp.public, &delegation));
+}
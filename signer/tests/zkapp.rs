@@ -0,0 +1,225 @@
+use ark_ff::Zero;
+use mina_hasher::{create_kimchi, DomainParameter, Hashable, Hasher, ROInput};
+use mina_signer::{
+    transaction::Memo,
+    zkapp::{
+        forest_hash, AccountUpdate, AccountUpdateBody, AuthRequired, ClosedInterval, Flagged,
+        TokenSymbol, ZkappCommand, ZkappCommitment,
+    },
+    BaseField, CompressedPubKey, NetworkId,
+};
+
+/// Field elements hashed with a hash prefix, as computed by the protocol
+#[derive(Clone)]
+struct Prefixed(Vec<BaseField>);
+
+/// Hash prefix, used as domain string
+#[derive(Clone)]
+struct Prefix(&'static str);
+
+impl DomainParameter for Prefix {
+    fn into_bytes(self) -> Vec<u8> {
+        self.0.as_bytes().to_vec()
+    }
+}
+
+impl Hashable for Prefixed {
+    type D = Prefix;
+
+    fn to_roinput(&self) -> ROInput {
+        self.0
+            .iter()
+            .fold(ROInput::new(), |roi, f| roi.append_field(*f))
+    }
+
+    fn domain_string(prefix: Self::D) -> Option<String> {
+        prefix.0.to_string().into()
+    }
+}
+
+fn hash_with_prefix(prefix: &'static str, fields: Vec<BaseField>) -> BaseField {
+    create_kimchi::<Prefixed>(Prefix(prefix)).hash(&Prefixed(fields))
+}
+
+// FIXME: these tests check the structure of the hashes (the hash prefixes,
+// the layout of the bodies, what each commitment covers) against the
+// definitions of the protocol, but there are no known-answer vectors yet.
+// The account update hash, the forest hash and both commitments of a few
+// commands (e.g. exported from o1js with `AccountUpdate.hash`,
+// `CallForest.hashChildren` and `transactionCommitments` on both networks)
+// should be added, as for the signatures of payments and delegations in
+// `tests/signer.rs`.
+
+#[test]
+fn zkapp_forest_hash() {
+    assert_eq!(forest_hash(&[]), BaseField::zero());
+
+    let (a, b, c) = (
+        BaseField::from(1u64),
+        BaseField::from(2u64),
+        BaseField::from(3u64),
+    );
+    let leaf = |x| hash_with_prefix("MinaAcctUpdateNode", vec![x, BaseField::zero()]);
+    let cons = |x, y| hash_with_prefix("MinaAcctUpdateCons", vec![x, y]);
+
+    let forest = vec![
+        AccountUpdate::new(a).set_calls(vec![AccountUpdate::new(b)]),
+        AccountUpdate::new(c),
+    ];
+    let a_calls = cons(leaf(b), BaseField::zero());
+    let a_tree = hash_with_prefix("MinaAcctUpdateNode", vec![a, a_calls]);
+    let expected = cons(a_tree, cons(leaf(c), BaseField::zero()));
+    assert_eq!(forest[0].tree_hash(), a_tree);
+    assert_eq!(forest_hash(&forest), expected);
+
+    // The call structure is committed to
+    let flat = vec![
+        AccountUpdate::new(a),
+        AccountUpdate::new(b),
+        AccountUpdate::new(c),
+    ];
+    assert_ne!(forest_hash(&flat), expected);
+}
+
+#[test]
+fn zkapp_commitments() {
+    let fee_payer_hash = BaseField::from(42u64);
+    let command = ZkappCommand::new(
+        fee_payer_hash,
+        vec![AccountUpdate::new(BaseField::from(7u64))],
+    )
+    .set_memo(Memo::from_string("zkApp"));
+
+    let ZkappCommitment(commitment) = command.commitment();
+    assert_eq!(commitment, forest_hash(&command.account_updates));
+
+    let memo_hash = create_kimchi::<Memo>(()).hash(&command.memo);
+    let ZkappCommitment(full_commitment) = command.full_commitment();
+    assert_eq!(
+        full_commitment,
+        hash_with_prefix(
+            "MinaAcctUpdateCons",
+            vec![memo_hash, fee_payer_hash, commitment]
+        )
+    );
+
+    // The memo and the fee payer are only covered by the full commitment
+    let other = command.clone().set_memo(Memo::from_string("other"));
+    assert_eq!(other.commitment(), command.commitment());
+    assert_ne!(other.full_commitment(), command.full_commitment());
+    let mut other = command.clone();
+    other.fee_payer_hash += BaseField::from(1u64);
+    assert_eq!(other.commitment(), command.commitment());
+    assert_ne!(other.full_commitment(), command.full_commitment());
+
+    assert_eq!(
+        ZkappCommitment::domain_string(NetworkId::MAINNET).expect("missing domain string"),
+        "MinaSignatureMainnet"
+    );
+    assert_eq!(
+        ZkappCommitment::domain_string(NetworkId::TESTNET).expect("missing domain string"),
+        "CodaSignature"
+    );
+}
+
+#[test]
+fn zkapp_body_layout() {
+    let mut body = AccountUpdateBody {
+        public_key: CompressedPubKey {
+            x: BaseField::from(11u64),
+            is_odd: true,
+        },
+        token_id: BaseField::from(1u64),
+        call_data: BaseField::from(5u64),
+        ..Default::default()
+    };
+    body.authorization_kind.verification_key_hash = BaseField::from(3u64);
+
+    // Field elements come first, followed by 1460 bits packed greedily into 7 chunks
+    let fields = body.to_roinput().to_fields();
+    assert_eq!(fields.len(), 38 + 7);
+    assert_eq!(fields[0], BaseField::from(11u64));
+    assert_eq!(fields[1], BaseField::from(1u64));
+    assert_eq!(fields[16], BaseField::from(5u64));
+    assert_eq!(fields[37], BaseField::from(3u64));
+
+    // The last chunk ends with the flags of the body and its authorization
+    body.authorization_kind.is_signed = true;
+    let signed = body.to_roinput().to_fields();
+    assert_eq!(signed[44] - fields[44], BaseField::from(2u64));
+    body.authorization_kind.is_proved = true;
+    let proved = body.to_roinput().to_fields();
+    assert_eq!(proved[44] - signed[44], BaseField::from(1u64));
+}
+
+/// Change of an account update body
+type Change = Box<dyn Fn(&mut AccountUpdateBody)>;
+
+#[test]
+fn zkapp_body_hash() {
+    let body = AccountUpdateBody::default();
+    let hash = body.hash(NetworkId::TESTNET);
+    assert_eq!(
+        hash,
+        create_kimchi::<AccountUpdateBody>(NetworkId::TESTNET).hash(&body)
+    );
+    assert_ne!(hash, body.hash(NetworkId::MAINNET));
+    assert_eq!(
+        AccountUpdateBody::domain_string(NetworkId::MAINNET).expect("missing domain string"),
+        "MainnetZkappBody"
+    );
+    assert_eq!(
+        AccountUpdateBody::domain_string(NetworkId::TESTNET).expect("missing domain string"),
+        "TestnetZkappBody"
+    );
+
+    // Every part of the body is committed to
+    let one = BaseField::from(1u64);
+    let mut changes: Vec<Change> = vec![
+        Box::new(|b| b.public_key.is_odd = true),
+        Box::new(move |b| b.token_id = one),
+        Box::new(move |b| b.update.app_state[7] = Flagged::some(one)),
+        Box::new(|b| b.update.permissions.value.set_verification_key.1 = 3),
+        Box::new(|b| {
+            b.update.permissions.value.set_timing = AuthRequired {
+                constant: true,
+                ..Default::default()
+            }
+        }),
+        Box::new(|b| b.update.token_symbol = Flagged::some(TokenSymbol("MINA".into()))),
+        Box::new(|b| b.update.timing.value.vesting_increment = 1),
+        Box::new(move |b| b.update.voting_for.value = one),
+        Box::new(|b| b.balance_change.is_negative = true),
+        Box::new(|b| b.balance_change.magnitude = 1),
+        Box::new(|b| b.increment_nonce = true),
+        Box::new(move |b| b.events_hash = one),
+        Box::new(move |b| b.actions_hash = one),
+        Box::new(move |b| b.call_data = one),
+        Box::new(|b| {
+            b.preconditions.network.blockchain_length =
+                Flagged::some(ClosedInterval { lower: 1, upper: 2 })
+        }),
+        Box::new(|b| b.preconditions.network.next_epoch_data.epoch_length.is_some = true),
+        Box::new(|b| b.preconditions.account.nonce.value.lower = 1),
+        Box::new(|b| b.preconditions.account.is_new = Flagged::some(true)),
+        Box::new(|b| b.preconditions.valid_while.value.upper = 0),
+        Box::new(|b| b.use_full_commitment = true),
+        Box::new(|b| b.implicit_account_creation_fee = true),
+        Box::new(|b| b.may_use_token.parents_own_token = true),
+        Box::new(|b| b.may_use_token.inherit_from_parent = true),
+        Box::new(move |b| b.authorization_kind.verification_key_hash = one),
+    ];
+    let mut hashes = vec![hash];
+    for change in changes.drain(..) {
+        let mut changed = body.clone();
+        change(&mut changed);
+        hashes.push(changed.hash(NetworkId::TESTNET));
+    }
+    for (i, h) in hashes.iter().enumerate() {
+        assert!(!hashes[i + 1..].contains(h), "change {i} collides");
+    }
+
+    // Bodies give the digests of account updates
+    let update = AccountUpdate::new(hash);
+    assert_eq!(update.body_hash, hash);
+}